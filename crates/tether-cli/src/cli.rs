@@ -0,0 +1,428 @@
+//! Command-line arguments.
+//!
+//! Options may come before or after the command, as `--name value` or
+//! `--name=value`. Connection options fall back to the environment variables
+//! tether-mcp reads, so one shell setup serves both.
+
+use tether_connect::{ConnectionConfig, ConnectionMode, TunnelOptions};
+use url::Url;
+
+/// Environment variable names, the same as tether-mcp's where they overlap.
+pub mod env_vars {
+    pub const CONNECTION: &str = "TETHER_CONNECTION";
+    pub const URL: &str = "TETHER_URL";
+    pub const DUMBPIPE_TICKET: &str = "TETHER_DUMBPIPE_TICKET";
+    pub const TOKEN: &str = "TETHER_TOKEN";
+    pub const LAN_NAME: &str = "TETHER_LAN_NAME";
+}
+
+/// Help text for `--help`.
+pub const USAGE: &str = "\
+Usage: tetherctl [OPTIONS] <COMMAND>
+
+Commands:
+  status                      Server version, uptime and Bluetooth health
+  proximity                   Whether the tracked phone is nearby
+  passes                      Passes left this month
+  passes use --reason <TEXT>  Use a pass for tonight
+  passes history [--month <YYYY-MM>]
+                              Passes used in a month, this month by default
+  config                      Current configuration
+  config set-timezone <TZ>    Set the timezone, e.g. America/Los_Angeles
+  config set-passes <N>       Set the number of passes per month
+  devices scan                Scan for nearby Bluetooth devices
+  ticket                      Print the dumbpipe ticket for remote access
+
+Options:
+  --url <URL>          Server URL [env: TETHER_URL]
+  --ticket <TICKET>    Dumbpipe ticket [env: TETHER_DUMBPIPE_TICKET]
+  --connection <MODE>  auto, direct, lan or ticket [env: TETHER_CONNECTION] [default: auto]
+  --lan-name <NAME>    LAN instance to use; needed to try the LAN when a ticket is set
+                       [env: TETHER_LAN_NAME]
+  --token <TOKEN>      Bearer token for a server behind a proxy [env: TETHER_TOKEN]
+  --json               Print responses as JSON
+  -h, --help           Print help
+  -V, --version        Print version";
+
+/// Errors in the command line.
+#[derive(Debug, PartialEq, Eq, thiserror::Error)]
+pub enum UsageError {
+    #[error("No command given")]
+    MissingCommand,
+
+    #[error("Unknown command '{0}'")]
+    UnknownCommand(String),
+
+    #[error("Unexpected argument '{0}'")]
+    UnexpectedArgument(String),
+
+    #[error("Unknown option '{0}'")]
+    UnknownOption(String),
+
+    #[error("{0} needs a value")]
+    MissingValue(String),
+
+    #[error("{0} does not apply to this command")]
+    InapplicableOption(&'static str),
+
+    #[error("--connection must be auto, direct, lan or ticket, not '{0}'")]
+    InvalidConnectionMode(String),
+
+    #[error("Invalid server URL: {0}")]
+    InvalidUrl(String),
+
+    #[error("Passes per month must be a number from 0 to 31, not '{0}'")]
+    InvalidPassesPerMonth(String),
+
+    #[error("{0}")]
+    Invalid(&'static str),
+}
+
+/// What to ask the server.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    Status,
+    Proximity,
+    Passes,
+    UsePass { reason: String },
+    PassHistory { month: Option<String> },
+    Config,
+    SetTimezone { timezone: String },
+    SetPassesPerMonth { per_month: u8 },
+    ScanDevices,
+    Ticket,
+}
+
+/// A parsed command line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Parsed {
+    /// Run a command.
+    Run(Cli),
+    /// Print help.
+    Help,
+    /// Print the version.
+    Version,
+}
+
+/// Options and command to run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cli {
+    pub mode: ConnectionMode,
+    pub url: Option<Url>,
+    pub ticket: Option<String>,
+    pub lan_name: Option<String>,
+    pub token: Option<String>,
+    pub json: bool,
+    pub command: Command,
+}
+
+impl Cli {
+    /// Returns how to reach the server.
+    pub fn connection_config(&self) -> ConnectionConfig {
+        ConnectionConfig {
+            mode: self.mode,
+            base_url: self.url.clone(),
+            ticket: self.ticket.clone(),
+            lan_name: self.lan_name.clone(),
+            token: self.token.clone(),
+            // Any free port; the tunnel only lives as long as the command
+            local_port: 0,
+            tunnel: TunnelOptions::default(),
+        }
+    }
+}
+
+/// Options that take a value.
+#[derive(Default)]
+struct Values {
+    url: Option<String>,
+    ticket: Option<String>,
+    connection: Option<String>,
+    lan_name: Option<String>,
+    token: Option<String>,
+    reason: Option<String>,
+    month: Option<String>,
+}
+
+/// Parses `args` (without the program name), reading unset connection
+/// options from variables returned by `lookup`.
+pub fn parse(
+    args: impl IntoIterator<Item = String>,
+    lookup: impl Fn(&str) -> Option<String>,
+) -> Result<Parsed, UsageError> {
+    let mut args = args.into_iter();
+    let mut values = Values::default();
+    let mut json = false;
+    let mut positional = Vec::new();
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(Parsed::Help),
+            "-V" | "--version" => return Ok(Parsed::Version),
+            "--json" => json = true,
+            "--" => positional.extend(args.by_ref()),
+            option if option.starts_with("--") => {
+                let (name, inline) = match option.split_once('=') {
+                    Some((name, value)) => (name, Some(value.to_string())),
+                    None => (option, None),
+                };
+                let slot = match name {
+                    "--url" => &mut values.url,
+                    "--ticket" => &mut values.ticket,
+                    "--connection" => &mut values.connection,
+                    "--lan-name" => &mut values.lan_name,
+                    "--token" => &mut values.token,
+                    "--reason" => &mut values.reason,
+                    "--month" => &mut values.month,
+                    _ => return Err(UsageError::UnknownOption(name.to_string())),
+                };
+                let value = inline
+                    .or_else(|| args.next())
+                    .ok_or_else(|| UsageError::MissingValue(name.to_string()))?;
+                *slot = Some(value);
+            }
+            option if option.starts_with('-') && option.len() > 1 => {
+                return Err(UsageError::UnknownOption(option.to_string()));
+            }
+            _ => positional.push(arg),
+        }
+    }
+
+    let command = command(&positional, &mut values)?;
+    if values.reason.is_some() {
+        return Err(UsageError::InapplicableOption("--reason"));
+    }
+    if values.month.is_some() {
+        return Err(UsageError::InapplicableOption("--month"));
+    }
+
+    let mode = match values.connection.or_else(|| lookup(env_vars::CONNECTION)) {
+        Some(mode) => mode.parse().map_err(UsageError::InvalidConnectionMode)?,
+        None => ConnectionMode::Auto,
+    };
+
+    let url = match values
+        .url
+        .or_else(|| lookup(env_vars::URL))
+        .filter(|url| !url.trim().is_empty())
+    {
+        Some(url) => {
+            let url = Url::parse(url.trim()).map_err(|e| UsageError::InvalidUrl(e.to_string()))?;
+            if !matches!(url.scheme(), "http" | "https") {
+                return Err(UsageError::InvalidUrl(format!(
+                    "{url} is not an http(s) URL"
+                )));
+            }
+            Some(url)
+        }
+        None => None,
+    };
+
+    let ticket = values
+        .ticket
+        .or_else(|| lookup(env_vars::DUMBPIPE_TICKET))
+        .filter(|ticket| !ticket.trim().is_empty());
+    let lan_name = values
+        .lan_name
+        .or_else(|| lookup(env_vars::LAN_NAME))
+        .filter(|name| !name.trim().is_empty());
+    let token = values
+        .token
+        .or_else(|| lookup(env_vars::TOKEN))
+        .filter(|token| !token.trim().is_empty());
+
+    match mode {
+        ConnectionMode::Direct if url.is_none() => {
+            return Err(UsageError::Invalid("A direct connection needs --url"));
+        }
+        ConnectionMode::Ticket if ticket.is_none() => {
+            return Err(UsageError::Invalid("A ticket connection needs --ticket"));
+        }
+        _ => {}
+    }
+
+    Ok(Parsed::Run(Cli {
+        mode,
+        url,
+        ticket,
+        lan_name,
+        token,
+        json,
+        command,
+    }))
+}
+
+/// Picks the command from the positional arguments, taking the options it
+/// uses out of `values`.
+fn command(positional: &[String], values: &mut Values) -> Result<Command, UsageError> {
+    let words: Vec<&str> = positional.iter().map(String::as_str).collect();
+    let command = match words.as_slice() {
+        [] => return Err(UsageError::MissingCommand),
+        ["status"] => Command::Status,
+        ["proximity"] => Command::Proximity,
+        ["passes"] => Command::Passes,
+        ["passes", "use"] => {
+            let reason = values
+                .reason
+                .take()
+                .filter(|reason| !reason.trim().is_empty())
+                .ok_or(UsageError::Invalid("Using a pass needs a --reason"))?;
+            Command::UsePass { reason }
+        }
+        ["passes", "history"] => Command::PassHistory {
+            month: values.month.take(),
+        },
+        ["config"] => Command::Config,
+        ["config", "set-timezone", timezone] => Command::SetTimezone {
+            timezone: (*timezone).to_string(),
+        },
+        ["config", "set-passes", per_month] => Command::SetPassesPerMonth {
+            per_month: per_month
+                .parse()
+                .ok()
+                .filter(|n| *n <= 31)
+                .ok_or_else(|| UsageError::InvalidPassesPerMonth((*per_month).to_string()))?,
+        },
+        ["devices", "scan"] => Command::ScanDevices,
+        ["ticket"] => Command::Ticket,
+        ["devices"] => return Err(UsageError::Invalid("devices needs a subcommand: scan")),
+        ["config", sub @ ("set-timezone" | "set-passes")] => {
+            return Err(UsageError::MissingValue(format!("config {sub}")));
+        }
+        [group @ ("passes" | "config" | "devices"), sub, ..]
+            if !matches!(
+                (*group, *sub),
+                ("passes", "use" | "history")
+                    | ("config", "set-timezone" | "set-passes")
+                    | ("devices", "scan")
+            ) =>
+        {
+            return Err(UsageError::UnknownCommand(format!("{group} {sub}")));
+        }
+        [
+            "status" | "proximity" | "passes" | "config" | "devices" | "ticket",
+            ..,
+            extra,
+        ] => {
+            return Err(UsageError::UnexpectedArgument((*extra).to_string()));
+        }
+        [other, ..] => return Err(UsageError::UnknownCommand((*other).to_string())),
+    };
+    Ok(command)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_args(args: &str, env: &[(&str, &str)]) -> Result<Parsed, UsageError> {
+        parse(args.split_whitespace().map(String::from), |name| {
+            env.iter()
+                .find(|(key, _)| *key == name)
+                .map(|(_, value)| (*value).to_string())
+        })
+    }
+
+    fn run(args: &str) -> Cli {
+        match parse_args(args, &[]) {
+            Ok(Parsed::Run(cli)) => cli,
+            other => panic!("expected a command, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_parse_commands() {
+        assert_eq!(run("status").command, Command::Status);
+        assert_eq!(
+            run("passes use --reason late-shift").command,
+            Command::UsePass {
+                reason: "late-shift".to_string()
+            }
+        );
+        assert_eq!(
+            run("--month=2025-01 passes history").command,
+            Command::PassHistory {
+                month: Some("2025-01".to_string())
+            }
+        );
+        assert_eq!(
+            run("config set-timezone Europe/Berlin").command,
+            Command::SetTimezone {
+                timezone: "Europe/Berlin".to_string()
+            }
+        );
+        assert_eq!(
+            run("config set-passes 4").command,
+            Command::SetPassesPerMonth { per_month: 4 }
+        );
+        assert_eq!(run("devices scan --json").command, Command::ScanDevices);
+        assert!(run("ticket --json").json);
+    }
+
+    #[test]
+    fn test_parse_rejects_invalid() {
+        assert_eq!(parse_args("", &[]), Err(UsageError::MissingCommand));
+        assert_eq!(
+            parse_args("passes use", &[]),
+            Err(UsageError::Invalid("Using a pass needs a --reason"))
+        );
+        assert_eq!(
+            parse_args("status --month 2025-01", &[]),
+            Err(UsageError::InapplicableOption("--month"))
+        );
+        assert_eq!(
+            parse_args("devices list", &[]),
+            Err(UsageError::UnknownCommand("devices list".to_string()))
+        );
+        assert_eq!(
+            parse_args("config set-timezone", &[]),
+            Err(UsageError::MissingValue("config set-timezone".to_string()))
+        );
+        assert_eq!(
+            parse_args("config set-passes 40", &[]),
+            Err(UsageError::InvalidPassesPerMonth("40".to_string()))
+        );
+        assert_eq!(
+            parse_args("status --verbose", &[]),
+            Err(UsageError::UnknownOption("--verbose".to_string()))
+        );
+        assert_eq!(
+            parse_args("status --url", &[]),
+            Err(UsageError::MissingValue("--url".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_parse_connection() {
+        let cli = run("status");
+        assert_eq!(cli.mode, ConnectionMode::Auto);
+        assert!(cli.url.is_none());
+
+        let cli = match parse_args(
+            "status --url http://localhost:3000",
+            &[
+                (env_vars::URL, "http://tether.local:8080"),
+                (env_vars::CONNECTION, "direct"),
+            ],
+        ) {
+            Ok(Parsed::Run(cli)) => cli,
+            other => panic!("expected a command, got {other:?}"),
+        };
+        assert_eq!(cli.mode, ConnectionMode::Direct);
+        assert_eq!(cli.url.unwrap().as_str(), "http://localhost:3000/");
+
+        let cli = run("status --lan-name=bedroom");
+        assert_eq!(cli.connection_config().lan_name.as_deref(), Some("bedroom"));
+
+        assert!(parse_args("status --connection direct", &[]).is_err());
+        assert!(parse_args("status --connection ticket", &[]).is_err());
+        assert!(parse_args("status --connection wormhole", &[]).is_err());
+        assert!(parse_args("status --url ftp://pi", &[]).is_err());
+    }
+
+    #[test]
+    fn test_parse_help() {
+        assert_eq!(parse_args("passes --help", &[]), Ok(Parsed::Help));
+        assert_eq!(parse_args("-V", &[]), Ok(Parsed::Version));
+    }
+}
@@ -0,0 +1,160 @@
+//! tetherctl
+//!
+//! Command-line client for a tether server, for scripts and quick checks
+//! from a terminal. Its commands mirror the API:
+//!
+//! ```text
+//! tetherctl status
+//! tetherctl passes use --reason "Late shift"
+//! tetherctl passes history --month 2025-01 --json
+//! tetherctl config set-timezone Europe/Berlin
+//! ```
+//!
+//! It reaches the server the way tether-mcp does: a URL given with `--url`,
+//! an instance found on the LAN, or the dumbpipe tunnel with `--ticket`.
+//! Responses are printed for people, or as the API's JSON with `--json`.
+
+mod cli;
+mod output;
+
+use std::process::ExitCode;
+use std::time::Duration;
+
+use anyhow::{Context, Result, anyhow, bail};
+use serde::Serialize;
+use tether_client::{Guarded, TetherClient};
+use tether_connect::{Connection, ConnectionConfig, Link};
+
+use crate::cli::{Cli, Command, Parsed};
+
+/// Name recorded in the audit log for changes made with tetherctl.
+const ACTOR: &str = "tetherctl";
+
+/// Exit code for a malformed command line.
+const USAGE_EXIT_CODE: u8 = 2;
+
+/// Initialize logging
+///
+/// Only warnings by default, so connection progress doesn't clutter the
+/// output; `RUST_LOG` turns on more.
+fn init_logging() {
+    use tracing_subscriber::{EnvFilter, fmt};
+
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("warn"));
+    fmt()
+        .with_env_filter(filter)
+        .with_target(false)
+        .with_writer(std::io::stderr)
+        .init();
+}
+
+/// Connects to the server and runs the command.
+async fn run(cli: &Cli) -> Result<()> {
+    let config = cli.connection_config();
+    let connection = Connection::establish(&config)
+        .await
+        .context("Failed to connect to the tether server")?;
+
+    let result = match connection.link() {
+        // The connection already waited for the tunnel; fail if it's still down
+        Link::Tunnel(tunnel) => match tunnel.wait_connected(Duration::ZERO).await {
+            Ok(()) => execute(&client(&connection, &config), cli).await,
+            Err(status) => Err(anyhow!(status.unavailable_message())),
+        },
+        Link::Direct { .. } => execute(&client(&connection, &config), cli).await,
+    };
+
+    connection.shutdown().await;
+    result
+}
+
+fn client(connection: &Connection, config: &ConnectionConfig) -> TetherClient {
+    let client = TetherClient::new(connection.base_url()).with_actor(ACTOR);
+    match connection.token(config) {
+        Some(token) => client.with_token(token),
+        None => client,
+    }
+}
+
+/// Sends the request for the command and prints the response.
+async fn execute(client: &TetherClient, cli: &Cli) -> Result<()> {
+    let json = cli.json;
+    let text = match &cli.command {
+        Command::Status => render(json, &client.get_system_status().await?, output::status)?,
+        Command::Proximity => render(json, &client.get_proximity().await?, output::proximity)?,
+        Command::Passes => render(json, &client.get_passes().await?, output::passes)?,
+        Command::UsePass { reason } => {
+            render(json, &client.use_pass(reason).await?, output::used_pass)?
+        }
+        Command::PassHistory { month } => render(
+            json,
+            &client.get_pass_history(month.as_deref()).await?,
+            output::pass_history,
+        )?,
+        Command::Config => render(json, &client.get_config().await?, output::config)?,
+        Command::SetTimezone { timezone } => match client.update_timezone(timezone).await? {
+            Guarded::Applied(update) => render(json, &update, output::timezone)?,
+            Guarded::Pending(change) => render(json, &change, output::pending)?,
+        },
+        Command::SetPassesPerMonth { per_month } => {
+            match client.update_passes_per_month(*per_month).await? {
+                Guarded::Applied(update) => render(json, &update, output::passes_per_month)?,
+                Guarded::Pending(change) => render(json, &change, output::pending)?,
+            }
+        }
+        Command::ScanDevices => render(json, &client.scan_devices().await?, output::scan)?,
+        Command::Ticket => {
+            let ticket = client.get_ticket().await?;
+            if json {
+                serde_json::to_string_pretty(&ticket)?
+            } else {
+                match output::ticket(&ticket) {
+                    Ok(ticket) => ticket,
+                    Err(unavailable) => bail!(unavailable),
+                }
+            }
+        }
+    };
+
+    println!("{text}");
+    Ok(())
+}
+
+/// Formats a response as JSON or for people.
+fn render<T: Serialize>(json: bool, response: &T, human: fn(&T) -> String) -> Result<String> {
+    if json {
+        Ok(serde_json::to_string_pretty(response)?)
+    } else {
+        Ok(human(response))
+    }
+}
+
+/// Main entry point
+#[tokio::main]
+async fn main() -> ExitCode {
+    let cli = match cli::parse(std::env::args().skip(1), |name| std::env::var(name).ok()) {
+        Ok(Parsed::Run(cli)) => cli,
+        Ok(Parsed::Help) => {
+            println!("{}", cli::USAGE);
+            return ExitCode::SUCCESS;
+        }
+        Ok(Parsed::Version) => {
+            println!("tetherctl {}", env!("CARGO_PKG_VERSION"));
+            return ExitCode::SUCCESS;
+        }
+        Err(e) => {
+            eprintln!("tetherctl: {e}\n\n{}", cli::USAGE);
+            return ExitCode::from(USAGE_EXIT_CODE);
+        }
+    };
+
+    init_logging();
+
+    match run(&cli).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("tetherctl: {e:#}");
+            ExitCode::FAILURE
+        }
+    }
+}
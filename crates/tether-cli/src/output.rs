@@ -0,0 +1,236 @@
+//! Human-readable output.
+//!
+//! With `--json`, responses are printed as the API returns them instead.
+
+use std::fmt::Write;
+use std::time::Duration;
+
+use tether_client::types::{
+    ConfigResponse, DumbpipeTicketResponse, PassHistoryResponse, PassesResponse,
+    PendingChangeResponse, ProximityResponse, ScanDevicesResponse, ScannerState,
+    SystemStatusResponse, UpdatePassesPerMonthResponse, UpdateTimezoneResponse, UsePassResponse,
+};
+
+/// Formats a duration as e.g. "2h 5m 3s".
+fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    let (hours, minutes, seconds) = (secs / 3600, secs / 60 % 60, secs % 60);
+    if hours > 0 {
+        format!("{hours}h {minutes}m {seconds}s")
+    } else if minutes > 0 {
+        format!("{minutes}m {seconds}s")
+    } else {
+        format!("{seconds}s")
+    }
+}
+
+pub fn status(status: &SystemStatusResponse) -> String {
+    let health = &status.bluetooth_health;
+    let state = match health.state {
+        ScannerState::Healthy => "healthy",
+        ScannerState::Degraded => "degraded",
+        ScannerState::Recovering => "recovering",
+        ScannerState::Unavailable => "unavailable",
+    };
+
+    let mut text = format!(
+        "tether {}, up {}\nBluetooth:  {state}",
+        status.version,
+        format_duration(Duration::from_secs(status.uptime_secs))
+    );
+    if let Some(at) = &health.last_successful_scan_utc {
+        let _ = write!(text, "\nLast scan:  {at}");
+    }
+    if health.total_failures > 0 {
+        let _ = write!(
+            text,
+            "\nFailures:   {} in a row, {} in total, {} recoveries",
+            health.consecutive_failures, health.total_failures, health.recoveries
+        );
+    }
+    if let Some(error) = &health.last_error {
+        let _ = write!(text, "\nLast error: {error}");
+    }
+    if !status.onboarding_complete {
+        text.push_str("\nOnboarding is not complete");
+    }
+    text
+}
+
+pub fn proximity(proximity: &ProximityResponse) -> String {
+    let state = if proximity.is_nearby {
+        "nearby"
+    } else {
+        "not nearby"
+    };
+    let rssi = proximity
+        .rssi_dbm
+        .map_or_else(|| "no signal".to_string(), |rssi| format!("{rssi} dBm"));
+    format!(
+        "{} ({}) is {state}: {rssi}, threshold {} dBm",
+        proximity.device_name, proximity.device_address, proximity.threshold_dbm
+    )
+}
+
+pub fn passes(passes: &PassesResponse) -> String {
+    format!(
+        "{} of {} passes left for {}\nResets at {} ({})",
+        passes.remaining,
+        passes.total_per_month,
+        passes.month,
+        passes.resets_at_utc,
+        passes.timezone
+    )
+}
+
+pub fn used_pass(pass: &UsePassResponse) -> String {
+    format!(
+        "Used a pass at {}: {}\n{} left this month",
+        pass.used_at_utc, pass.reason, pass.remaining
+    )
+}
+
+pub fn pass_history(history: &PassHistoryResponse) -> String {
+    let mut text = format!(
+        "{} of {} passes used in {}",
+        history.total_used, history.total_per_month, history.month
+    );
+    for entry in &history.entries {
+        let _ = write!(text, "\n  {}  {}", entry.used_at_utc, entry.reason);
+    }
+    text
+}
+
+pub fn config(config: &ConfigResponse) -> String {
+    let bluetooth = &config.bluetooth;
+    let phone = if bluetooth.is_configured {
+        format!(
+            "{} ({}), nearby above {} dBm{}",
+            bluetooth.target_name,
+            bluetooth.target_address,
+            bluetooth.rssi_threshold,
+            if bluetooth.is_paired { ", paired" } else { "" }
+        )
+    } else {
+        "not set".to_string()
+    };
+
+    let mut text = format!(
+        "Phone:    {phone}\nTimezone: {}\nPasses:   {} per month",
+        config.timezone, config.passes_per_month
+    );
+    if !config.wifi_networks.is_empty() {
+        let networks: Vec<_> = config
+            .wifi_networks
+            .iter()
+            .map(|network| network.ssid.as_str())
+            .collect();
+        let _ = write!(text, "\nWi-Fi:    {}", networks.join(", "));
+    }
+    if !config.onboarding_complete {
+        text.push_str("\nOnboarding is not complete");
+    }
+    text
+}
+
+pub fn timezone(update: &UpdateTimezoneResponse) -> String {
+    format!("Timezone set to {}", update.timezone)
+}
+
+pub fn passes_per_month(update: &UpdatePassesPerMonthResponse) -> String {
+    update.message.clone()
+}
+
+pub fn pending(change: &PendingChangeResponse) -> String {
+    let mut text = format!(
+        "Waiting for the accountability partner's approval (change {})",
+        change.id
+    );
+    match &change.effective_at_utc {
+        Some(at) => {
+            let _ = write!(text, "\nTakes effect at {at} unless rejected");
+        }
+        None => text.push_str("\nTakes effect once approved"),
+    }
+    text
+}
+
+pub fn scan(scan: &ScanDevicesResponse) -> String {
+    if scan.devices.is_empty() {
+        return format!("No devices found in {}s", scan.scan_duration_secs);
+    }
+
+    let mut text = format!(
+        "Found {} devices in {}s:",
+        scan.devices.len(),
+        scan.scan_duration_secs
+    );
+    for device in &scan.devices {
+        let rssi = device
+            .rssi_dbm
+            .map_or_else(String::new, |rssi| format!("{rssi} dBm"));
+        let _ = write!(
+            text,
+            "\n  {}  {rssi:>8}  {}{}",
+            device.address,
+            device.name.as_deref().unwrap_or("(unnamed)"),
+            if device.paired { " (paired)" } else { "" }
+        );
+    }
+    text
+}
+
+/// Returns the ticket alone, so it can be piped, or why there is none.
+pub fn ticket(ticket: &DumbpipeTicketResponse) -> Result<String, String> {
+    ticket.ticket.clone().ok_or_else(|| {
+        ticket
+            .message
+            .clone()
+            .unwrap_or_else(|| "Remote access is not available".to_string())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use tether_client::types::PassHistoryEntry;
+
+    use super::*;
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(Duration::from_secs(42)), "42s");
+        assert_eq!(format_duration(Duration::from_secs(7_503)), "2h 5m 3s");
+    }
+
+    #[test]
+    fn test_pass_history() {
+        let history = PassHistoryResponse {
+            month: "2025-01".to_string(),
+            entries: vec![PassHistoryEntry {
+                used_at_utc: "2025-01-15T03:30:00Z".to_string(),
+                reason: "Late shift".to_string(),
+            }],
+            total_used: 1,
+            total_per_month: 3,
+        };
+        assert_eq!(
+            pass_history(&history),
+            "1 of 3 passes used in 2025-01\n  2025-01-15T03:30:00Z  Late shift"
+        );
+    }
+
+    #[test]
+    fn test_ticket() {
+        let unavailable = DumbpipeTicketResponse {
+            ticket: None,
+            expires_at_utc: None,
+            node_id: None,
+            available: false,
+            message: Some("dumbpipe is not running".to_string()),
+        };
+        assert_eq!(
+            ticket(&unavailable),
+            Err("dumbpipe is not running".to_string())
+        );
+    }
+}
@@ -0,0 +1,560 @@
+//! HTTP client for the tether API.
+
+use std::time::Duration;
+
+use reqwest::header::{HeaderValue, AUTHORIZATION};
+use reqwest::{RequestBuilder, Response, StatusCode};
+use serde::de::DeserializeOwned;
+use url::Url;
+
+use crate::error::{ApiError, Error, Result};
+use crate::events::EventStream;
+use crate::types::{
+    AccessResponse, ConfigResponse, DumbpipeTicketResponse, ErrorResponse, HealthResponse,
+    PassHistoryRangeResponse, PassHistoryResponse, PassesResponse, PendingChangeResponse,
+    ProximityResponse, ScanDevicesResponse, SystemStatusResponse, UpdateCurfewRequest,
+    UpdateCurfewResponse, UpdatePassesPerMonthRequest, UpdatePassesPerMonthResponse,
+    UpdateTimezoneRequest, UpdateTimezoneResponse, UsePassRequest, UsePassResponse,
+};
+
+/// Header naming the client making a change, recorded in the audit log.
+pub const ACTOR_HEADER: &str = "x-tether-actor";
+
+/// Default timeout of a request.
+///
+/// Proximity checks scan for several seconds, so this is well above a scan.
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// How long an event stream stays open.
+///
+/// Callers reopen it with the id of the last event they received.
+const EVENT_STREAM_TIMEOUT: Duration = Duration::from_secs(60 * 60);
+
+/// Timeout for a device scan, which takes about ten seconds on the server.
+const SCAN_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Outcome of a change to a guarded setting.
+///
+/// With guarded settings enabled on the server, changes that could loosen
+/// the rules wait for the accountability partner's approval instead of
+/// taking effect.
+#[derive(Debug, Clone)]
+pub enum Guarded<T> {
+    /// The change was applied.
+    Applied(T),
+    /// The change is waiting for approval.
+    Pending(PendingChangeResponse),
+}
+
+/// Client for a tether server.
+///
+/// Cheap to clone; clones share the connection pool.
+///
+/// # Example
+///
+/// ```no_run
+/// # async fn example() -> tether_client::Result<()> {
+/// let url = "http://tether.local:8080".parse().unwrap();
+/// let client = tether_client::TetherClient::new(url).with_actor("my-script");
+///
+/// let passes = client.get_passes().await?;
+/// println!("{} of {} passes left", passes.remaining, passes.total_per_month);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct TetherClient {
+    http: reqwest::Client,
+    base_url: Url,
+    actor: Option<HeaderValue>,
+    authorization: Option<HeaderValue>,
+}
+
+impl TetherClient {
+    /// Creates a client for the server at `base_url`, such as
+    /// `http://tether.local:8080`, or `https://example.com/tether` behind a
+    /// reverse proxy.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the TLS backend cannot be initialized.
+    #[must_use]
+    pub fn new(base_url: Url) -> Self {
+        let http = reqwest::Client::builder()
+            .timeout(DEFAULT_TIMEOUT)
+            .build()
+            .expect("Failed to create HTTP client");
+        Self::with_http_client(base_url, http)
+    }
+
+    /// Creates a client that sends requests with `http`.
+    #[must_use]
+    pub const fn with_http_client(base_url: Url, http: reqwest::Client) -> Self {
+        Self {
+            http,
+            base_url,
+            actor: None,
+            authorization: None,
+        }
+    }
+
+    /// Names the client in the audit log entries of its changes.
+    ///
+    /// Names that aren't valid header values are ignored, and the changes
+    /// are recorded as anonymous.
+    #[must_use]
+    pub fn with_actor(mut self, actor: &str) -> Self {
+        self.actor = HeaderValue::from_str(actor).ok();
+        self
+    }
+
+    /// Sends `token` as a bearer token with every request, for servers
+    /// behind an authenticating reverse proxy.
+    ///
+    /// Tokens that aren't valid header values are ignored.
+    #[must_use]
+    pub fn with_token(mut self, token: &str) -> Self {
+        self.authorization =
+            HeaderValue::from_str(&format!("Bearer {token}"))
+                .ok()
+                .map(|mut value| {
+                    value.set_sensitive(true);
+                    value
+                });
+        self
+    }
+
+    /// Returns the server's base URL.
+    #[must_use]
+    pub const fn base_url(&self) -> &Url {
+        &self.base_url
+    }
+
+    // ------------------------------------------------------------------------
+    // Health
+    // ------------------------------------------------------------------------
+
+    /// Checks that the server is up.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the server can't be reached.
+    pub async fn get_health(&self) -> Result<HealthResponse> {
+        let request = self.http.get(self.url("/health")?);
+        self.send(request).await
+    }
+
+    // ------------------------------------------------------------------------
+    // Proximity
+    // ------------------------------------------------------------------------
+
+    /// Checks whether the tracked phone is near the server.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Api`] with status 424 if no device is configured, or
+    /// 503 if Bluetooth is unavailable.
+    pub async fn get_proximity(&self) -> Result<ProximityResponse> {
+        let request = self.http.get(self.url("/api/proximity")?);
+        self.send(request).await
+    }
+
+    /// Scans for nearby Bluetooth devices for about ten seconds.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Api`] with status 503 if Bluetooth is unavailable.
+    pub async fn scan_devices(&self) -> Result<ScanDevicesResponse> {
+        let request = self
+            .http
+            .get(self.url("/api/devices")?)
+            .timeout(SCAN_TIMEOUT);
+        self.send(request).await
+    }
+
+    // ------------------------------------------------------------------------
+    // Passes
+    // ------------------------------------------------------------------------
+
+    /// Returns the passes remaining this month.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the request fails.
+    pub async fn get_passes(&self) -> Result<PassesResponse> {
+        let request = self.http.get(self.url("/api/passes")?);
+        self.send(request).await
+    }
+
+    /// Returns the passes used in `month` (YYYY-MM), or this month.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Api`] with status 400 if `month` is malformed.
+    pub async fn get_pass_history(&self, month: Option<&str>) -> Result<PassHistoryResponse> {
+        let mut url = self.url("/api/passes/history")?;
+        if let Some(month) = month {
+            url.query_pairs_mut().append_pair("month", month);
+        }
+        self.send(self.http.get(url)).await
+    }
+
+    /// Returns the passes used from `from` (inclusive) to `to` (exclusive),
+    /// both RFC 3339 timestamps.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Api`] with status 400 if a timestamp is malformed or
+    /// the range is empty.
+    pub async fn get_pass_history_range(
+        &self,
+        from: &str,
+        to: &str,
+    ) -> Result<PassHistoryRangeResponse> {
+        let mut url = self.url("/api/passes/history/range")?;
+        url.query_pairs_mut()
+            .append_pair("from", from)
+            .append_pair("to", to);
+        self.send(self.http.get(url)).await
+    }
+
+    /// Uses a pass for tonight.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Api`] with status 400 if the reason is empty or too
+    /// long, or 409 if no passes are left.
+    pub async fn use_pass(&self, reason: &str) -> Result<UsePassResponse> {
+        let body = UsePassRequest {
+            reason: reason.to_string(),
+        };
+        let request = self.http.post(self.url("/api/passes/use")?).json(&body);
+        self.send(request).await
+    }
+
+    // ------------------------------------------------------------------------
+    // Config
+    // ------------------------------------------------------------------------
+
+    /// Returns the current configuration, without network passwords.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the request fails.
+    pub async fn get_config(&self) -> Result<ConfigResponse> {
+        let request = self.http.get(self.url("/api/config")?);
+        self.send(request).await
+    }
+
+    /// Sets the timezone used for pass resets and the curfew, an IANA name
+    /// such as `America/Los_Angeles`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Api`] with status 400 if the timezone is unknown.
+    pub async fn update_timezone(&self, timezone: &str) -> Result<Guarded<UpdateTimezoneResponse>> {
+        let body = UpdateTimezoneRequest {
+            timezone: timezone.to_string(),
+        };
+        let request = self.http.put(self.url("/api/config/timezone")?).json(&body);
+        self.send_guarded(request).await
+    }
+
+    /// Sets the nightly curfew, from `start` to `end` as `HH:MM` in the
+    /// configured timezone.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Api`] with status 400 if a time is malformed.
+    pub async fn update_curfew(
+        &self,
+        enabled: bool,
+        start: &str,
+        end: &str,
+    ) -> Result<Guarded<UpdateCurfewResponse>> {
+        let body = UpdateCurfewRequest {
+            enabled,
+            start: start.to_string(),
+            end: end.to_string(),
+        };
+        let request = self.http.put(self.url("/api/config/curfew")?).json(&body);
+        self.send_guarded(request).await
+    }
+
+    /// Sets the number of passes per month.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Api`] with status 400 if `per_month` is above 31.
+    pub async fn update_passes_per_month(
+        &self,
+        per_month: u8,
+    ) -> Result<Guarded<UpdatePassesPerMonthResponse>> {
+        let body = UpdatePassesPerMonthRequest { per_month };
+        let request = self.http.put(self.url("/api/config/passes")?).json(&body);
+        self.send_guarded(request).await
+    }
+
+    // ------------------------------------------------------------------------
+    // Access
+    // ------------------------------------------------------------------------
+
+    /// Returns what the server lets this client do, which depends on the
+    /// token it sends.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the request fails. Servers from before scopes
+    /// existed answer with [`Error::Api`] status 404.
+    pub async fn get_access(&self) -> Result<AccessResponse> {
+        let request = self.http.get(self.url("/api/access")?);
+        self.send(request).await
+    }
+
+    // ------------------------------------------------------------------------
+    // System
+    // ------------------------------------------------------------------------
+
+    /// Returns the server's version, uptime and Bluetooth health.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the request fails.
+    pub async fn get_system_status(&self) -> Result<SystemStatusResponse> {
+        let request = self.http.get(self.url("/api/system/status")?);
+        self.send(request).await
+    }
+
+    /// Returns the dumbpipe ticket for reaching the server remotely.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the request fails.
+    pub async fn get_ticket(&self) -> Result<DumbpipeTicketResponse> {
+        let request = self.http.get(self.url("/api/system/ticket")?);
+        self.send(request).await
+    }
+
+    // ------------------------------------------------------------------------
+    // Events
+    // ------------------------------------------------------------------------
+
+    /// Opens the server's event stream, resuming after `last_event_id` if
+    /// given.
+    ///
+    /// The stream is closed after an hour; reopen it with the id of the last
+    /// event received to carry on without missing any.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Api`] with status 400 if `last_event_id` is not an
+    /// event id from this server.
+    pub async fn events(&self, last_event_id: Option<u64>) -> Result<EventStream> {
+        let mut request = self
+            .http
+            .get(self.url("/api/events")?)
+            .timeout(EVENT_STREAM_TIMEOUT);
+        if let Some(id) = last_event_id {
+            request = request.header("last-event-id", id.to_string());
+        }
+
+        let response = self.with_headers(request).send().await?;
+        let status = response.status();
+        if !status.is_success() {
+            return Err(error_response(status.as_u16(), &response.bytes().await?));
+        }
+        Ok(EventStream::new(response))
+    }
+
+    // ------------------------------------------------------------------------
+    // Helpers
+    // ------------------------------------------------------------------------
+
+    /// Builds the URL of `path`, below the base URL's own path.
+    fn url(&self, path: &str) -> Result<Url> {
+        // Joined relative to a directory, so a base of `https://host/tether`
+        // keeps its `/tether` prefix
+        let mut base = self.base_url.clone();
+        if !base.path().ends_with('/') {
+            base.set_path(&format!("{}/", base.path()));
+        }
+        Ok(base.join(path.trim_start_matches('/'))?)
+    }
+
+    /// Sends a request and decodes its response.
+    async fn send<T: DeserializeOwned>(&self, request: RequestBuilder) -> Result<T> {
+        decode(self.with_headers(request).send().await?).await
+    }
+
+    /// Sends a change to a guarded setting and decodes its response, or the
+    /// pending change if it awaits approval.
+    async fn send_guarded<T: DeserializeOwned>(
+        &self,
+        request: RequestBuilder,
+    ) -> Result<Guarded<T>> {
+        let response = self.with_headers(request).send().await?;
+        if response.status() == StatusCode::ACCEPTED {
+            return Ok(Guarded::Pending(decode(response).await?));
+        }
+        Ok(Guarded::Applied(decode(response).await?))
+    }
+
+    fn with_headers(&self, mut request: RequestBuilder) -> RequestBuilder {
+        if let Some(actor) = &self.actor {
+            request = request.header(ACTOR_HEADER, actor);
+        }
+        if let Some(authorization) = &self.authorization {
+            request = request.header(AUTHORIZATION, authorization);
+        }
+        request
+    }
+}
+
+/// Decodes a response body, or the error response of a failed request.
+async fn decode<T: DeserializeOwned>(response: Response) -> Result<T> {
+    let status = response.status();
+    let body = response.bytes().await?;
+
+    if status.is_success() {
+        return Ok(serde_json::from_slice(&body)?);
+    }
+
+    Err(error_response(status.as_u16(), &body))
+}
+
+/// Converts the body of a failed request to an error.
+fn error_response(status: u16, body: &[u8]) -> Error {
+    serde_json::from_slice::<ErrorResponse>(body).map_or_else(
+        |_| Error::UnexpectedResponse {
+            status,
+            body: String::from_utf8_lossy(body).into_owned(),
+        },
+        |error| ApiError::new(status, error).into(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::GuardedSetting;
+    use axum::http::{HeaderMap, Uri};
+    use axum::Router;
+    use std::sync::{Arc, Mutex};
+
+    /// Requests received by a stub server, as path and query with headers.
+    type Received = Arc<Mutex<Vec<(String, HeaderMap)>>>;
+
+    /// Serves a stub that answers every request with `status` and `body`.
+    ///
+    /// Returns the stub's base URL and the requests it received.
+    async fn stub(status: StatusCode, body: &'static str) -> (Url, Received) {
+        let received = Received::default();
+        let log = received.clone();
+        let router = Router::new().fallback(move |uri: Uri, headers: HeaderMap| {
+            log.lock().unwrap().push((uri.to_string(), headers));
+            async move { (status, body) }
+        });
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, router).await.unwrap() });
+        (format!("http://{addr}").parse().unwrap(), received)
+    }
+
+    const HISTORY: &str = r#"{"month":"2025-01","entries":[],"total_used":0,"total_per_month":3}"#;
+
+    #[tokio::test]
+    async fn test_builds_urls_below_base_path() {
+        let (url, received) = stub(StatusCode::OK, HISTORY).await;
+        let client = TetherClient::new(url.join("tether").unwrap());
+
+        let history = client.get_pass_history(Some("2025-01")).await.unwrap();
+        assert_eq!(history.total_per_month, 3);
+        let _ = client
+            .get_pass_history_range("2025-01-01T00:00:00+01:00", "2025-02-01T00:00:00+01:00")
+            .await;
+
+        let uris: Vec<String> = received
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|r| r.0.clone())
+            .collect();
+        assert_eq!(
+            uris,
+            [
+                "/tether/api/passes/history?month=2025-01",
+                "/tether/api/passes/history/range?from=2025-01-01T00%3A00%3A00%2B01%3A00\
+                 &to=2025-02-01T00%3A00%3A00%2B01%3A00",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_sends_actor_and_token() {
+        let body = r#"{"status":"ok","version":"0.1.0","onboarding_complete":true}"#;
+        let (url, received) = stub(StatusCode::OK, body).await;
+
+        TetherClient::new(url.clone()).get_health().await.unwrap();
+        let client = TetherClient::new(url)
+            .with_actor("my-script")
+            .with_token("s3cret");
+        assert_eq!(client.get_health().await.unwrap().status, "ok");
+
+        let received = received.lock().unwrap().clone();
+        let (_, anonymous) = &received[0];
+        assert!(anonymous.get(ACTOR_HEADER).is_none());
+        assert!(anonymous.get(AUTHORIZATION).is_none());
+        let (_, named) = &received[1];
+        assert_eq!(named[ACTOR_HEADER], "my-script");
+        assert_eq!(named[AUTHORIZATION], "Bearer s3cret");
+    }
+
+    #[tokio::test]
+    async fn test_maps_error_responses() {
+        let body = r#"{"error":"no_passes_left","message":"No passes left","details":null}"#;
+        let (url, _) = stub(StatusCode::CONFLICT, body).await;
+        let err = TetherClient::new(url)
+            .use_pass("late train")
+            .await
+            .unwrap_err();
+        let Error::Api(err) = err else {
+            panic!("expected an API error, got {err:?}");
+        };
+        assert_eq!((err.status, err.error.as_str()), (409, "no_passes_left"));
+
+        let (url, _) = stub(StatusCode::BAD_GATEWAY, "Bad Gateway").await;
+        let err = TetherClient::new(url).get_passes().await.unwrap_err();
+        assert!(matches!(
+            err,
+            Error::UnexpectedResponse { status: 502, ref body } if body == "Bad Gateway"
+        ));
+
+        let (url, _) = stub(StatusCode::OK, "<html>").await;
+        let err = TetherClient::new(url).get_passes().await.unwrap_err();
+        assert!(matches!(err, Error::Decode(_)));
+    }
+
+    #[tokio::test]
+    async fn test_decodes_pending_changes() {
+        let body = r#"{
+            "id": "5b6f0c7e",
+            "setting": "passes_per_month",
+            "requested_by": "my-script",
+            "requested_at_utc": "2025-01-15T23:05:00Z",
+            "effective_at_utc": null,
+            "before": {"per_month": 3},
+            "after": {"per_month": 10}
+        }"#;
+        let (url, _) = stub(StatusCode::ACCEPTED, body).await;
+        let change = TetherClient::new(url)
+            .update_passes_per_month(10)
+            .await
+            .unwrap();
+        let Guarded::Pending(change) = change else {
+            panic!("expected a pending change, got {change:?}");
+        };
+        assert_eq!(change.setting, GuardedSetting::PassesPerMonth);
+        assert_eq!(change.after["per_month"], 10);
+    }
+}
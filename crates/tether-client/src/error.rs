@@ -0,0 +1,67 @@
+//! Error types for the tether client.
+
+use thiserror::Error;
+
+use crate::types::ErrorResponse;
+
+/// Result type for client requests.
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Errors from calling the tether API.
+#[derive(Debug, Error)]
+pub enum Error {
+    /// The server rejected the request with an error response.
+    #[error(transparent)]
+    Api(#[from] ApiError),
+
+    /// The request could not be sent or its response could not be read.
+    #[error("Request to tether server failed: {0}")]
+    Request(#[from] reqwest::Error),
+
+    /// The response body did not match the expected type.
+    #[error("Failed to decode response from tether server: {0}")]
+    Decode(#[from] serde_json::Error),
+
+    /// The server responded with an error status but no error body.
+    #[error("Unexpected response from tether server ({status}): {body}")]
+    UnexpectedResponse {
+        /// HTTP status code.
+        status: u16,
+        /// Response body, as text.
+        body: String,
+    },
+
+    /// A request URL could not be built from the base URL.
+    #[error("Invalid URL: {0}")]
+    InvalidUrl(#[from] url::ParseError),
+}
+
+/// An error response from the tether server.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("{message} ({status} {error})")]
+pub struct ApiError {
+    /// HTTP status code.
+    pub status: u16,
+
+    /// Machine-readable error code, such as `invalid_bluetooth_address`.
+    pub error: String,
+
+    /// Human-readable error message.
+    pub message: String,
+
+    /// Optional additional details for debugging.
+    pub details: Option<serde_json::Value>,
+}
+
+impl ApiError {
+    /// Creates an error from the status and body of an error response.
+    #[must_use]
+    pub fn new(status: u16, response: ErrorResponse) -> Self {
+        Self {
+            status,
+            error: response.error,
+            message: response.message,
+            details: response.details,
+        }
+    }
+}
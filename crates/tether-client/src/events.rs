@@ -0,0 +1,151 @@
+//! Server-Sent Events from `GET /api/events`.
+//!
+//! The stream is parsed just enough to tell which events arrived: each
+//! [`StreamedEvent`] carries the SSE name (the event's `type`, or `resync`),
+//! the SSE id to resume from, and the raw JSON data.
+
+use reqwest::Response;
+
+use crate::error::Result;
+
+/// SSE name of the event telling clients to refetch their state.
+pub const RESYNC_EVENT: &str = "resync";
+
+/// An event received from the server's event stream.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StreamedEvent {
+    /// SSE id of the event, sent as `Last-Event-ID` to resume after it.
+    pub id: Option<u64>,
+
+    /// SSE name of the event, such as `pass_used` or [`RESYNC_EVENT`].
+    pub event: String,
+
+    /// The event's JSON data.
+    pub data: String,
+}
+
+/// A stream of events from `GET /api/events`.
+///
+/// Created with [`TetherClient::events`](crate::TetherClient::events).
+#[derive(Debug)]
+pub struct EventStream {
+    response: Response,
+    parser: Parser,
+}
+
+impl EventStream {
+    pub(crate) fn new(response: Response) -> Self {
+        Self {
+            response,
+            parser: Parser::default(),
+        }
+    }
+
+    /// Waits for the next event, or returns `None` when the server closes
+    /// the stream.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the connection fails.
+    pub async fn next(&mut self) -> Result<Option<StreamedEvent>> {
+        loop {
+            if let Some(event) = self.parser.next_event() {
+                return Ok(Some(event));
+            }
+            match self.response.chunk().await? {
+                Some(chunk) => self.parser.push(&chunk),
+                None => return Ok(None),
+            }
+        }
+    }
+}
+
+/// Splits received bytes into events.
+#[derive(Debug, Default)]
+struct Parser {
+    buffer: Vec<u8>,
+}
+
+impl Parser {
+    fn push(&mut self, chunk: &[u8]) {
+        self.buffer.extend_from_slice(chunk);
+    }
+
+    /// Returns the next complete event, skipping keep-alive comments.
+    fn next_event(&mut self) -> Option<StreamedEvent> {
+        loop {
+            let end = self
+                .buffer
+                .windows(2)
+                .position(|window| window == b"\n\n")?;
+            let block: Vec<u8> = self.buffer.drain(..end + 2).collect();
+            if let Some(event) = parse_block(&String::from_utf8_lossy(&block)) {
+                return Some(event);
+            }
+        }
+    }
+}
+
+/// Parses the lines of one event, or returns `None` for a comment.
+fn parse_block(block: &str) -> Option<StreamedEvent> {
+    let mut id = None;
+    let mut event = None;
+    let mut data: Option<String> = None;
+
+    for line in block.lines() {
+        let (field, value) = line.split_once(':').unwrap_or((line, ""));
+        let value = value.strip_prefix(' ').unwrap_or(value);
+        match field {
+            "id" => id = value.parse().ok(),
+            "event" => event = Some(value.to_string()),
+            "data" => match &mut data {
+                Some(data) => {
+                    data.push('\n');
+                    data.push_str(value);
+                }
+                None => data = Some(value.to_string()),
+            },
+            _ => {}
+        }
+    }
+
+    if event.is_none() && data.is_none() {
+        return None;
+    }
+    Some(StreamedEvent {
+        id,
+        event: event.unwrap_or_else(|| "message".to_string()),
+        data: data.unwrap_or_default(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parses_events_split_across_chunks() {
+        let mut parser = Parser::default();
+        parser.push(b"id: 7\nevent: pass_used\ndata: {\"remaining\"");
+        assert_eq!(parser.next_event(), None);
+
+        parser.push(b": 2}\n\n:\n\nevent: resync\ndata: {}\n\n");
+        assert_eq!(
+            parser.next_event(),
+            Some(StreamedEvent {
+                id: Some(7),
+                event: "pass_used".to_string(),
+                data: "{\"remaining\": 2}".to_string(),
+            })
+        );
+        assert_eq!(
+            parser.next_event(),
+            Some(StreamedEvent {
+                id: None,
+                event: RESYNC_EVENT.to_string(),
+                data: "{}".to_string(),
+            })
+        );
+        assert_eq!(parser.next_event(), None);
+    }
+}
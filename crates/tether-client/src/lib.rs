@@ -0,0 +1,30 @@
+//! # tether-client
+//!
+//! Typed HTTP client for the tether API.
+//!
+//! The request and response types in [`types`] are the ones tether-server
+//! uses in its handlers and `OpenAPI` schema, so a change to the API that
+//! breaks the client fails to compile instead of failing at runtime.
+//!
+//! ## Modules
+//!
+//! - [`client`] - The [`TetherClient`] itself
+//! - [`types`] - Request and response types shared with tether-server
+//! - [`events`] - The server's event stream
+//! - [`error`] - Client errors, including decoded API error responses
+
+#![forbid(unsafe_code)]
+#![warn(clippy::all, clippy::pedantic, clippy::nursery)]
+#![warn(missing_docs)]
+
+pub mod client;
+pub mod error;
+pub mod events;
+pub mod types;
+
+pub use client::{Guarded, TetherClient, ACTOR_HEADER};
+pub use error::{ApiError, Error, Result};
+pub use events::{EventStream, StreamedEvent};
+
+/// DNS-SD service type tether servers advertise on the LAN.
+pub const MDNS_SERVICE_TYPE: &str = "_tether._tcp.local.";
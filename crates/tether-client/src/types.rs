@@ -0,0 +1,810 @@
+//! Request and response types of the tether API.
+//!
+//! The server uses these same types in its handlers and `OpenAPI` schema, so
+//! the client can't drift from what the server actually sends.
+
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+// ============================================================================
+// Errors
+// ============================================================================
+
+/// Standard JSON error response body.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[schema(example = json!({
+    "error": "invalid_request",
+    "message": "The provided value is not valid",
+    "details": null
+}))]
+pub struct ErrorResponse {
+    /// Machine-readable error code (e.g., `invalid_bluetooth_address`).
+    #[schema(example = "invalid_request")]
+    pub error: String,
+
+    /// Human-readable error message.
+    #[schema(example = "The provided value is not valid")]
+    pub message: String,
+
+    /// Optional additional details for debugging.
+    #[schema(nullable)]
+    pub details: Option<serde_json::Value>,
+}
+
+// ============================================================================
+// Health
+// ============================================================================
+
+/// Health check response.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[schema(example = json!({
+    "status": "ok",
+    "version": "0.1.0",
+    "onboarding_complete": true
+}))]
+pub struct HealthResponse {
+    /// Service status.
+    #[schema(example = "ok")]
+    pub status: String,
+
+    /// Service version from Cargo.toml.
+    #[schema(example = "0.1.0")]
+    pub version: String,
+
+    /// Whether initial onboarding has been completed.
+    #[schema(example = true)]
+    pub onboarding_complete: bool,
+}
+
+/// Nightly curfew configuration in response.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema)]
+#[schema(example = json!({
+    "enabled": true,
+    "start": "22:00",
+    "end": "06:00",
+    "is_active": false
+}))]
+pub struct CurfewResponse {
+    /// Whether the curfew is in effect.
+    pub enabled: bool,
+
+    /// When the curfew starts each night (`HH:MM` in the configured timezone).
+    #[schema(example = "22:00")]
+    pub start: String,
+
+    /// When the curfew ends each morning (`HH:MM` in the configured timezone).
+    #[schema(example = "06:00")]
+    pub end: String,
+
+    /// Whether it is curfew right now.
+    pub is_active: bool,
+}
+
+// ============================================================================
+// Proximity
+// ============================================================================
+
+/// How the tracked device was detected during a proximity check.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum DetectionMethod {
+    /// The device was heard advertising during a scan.
+    Advertisement,
+    /// A connection to the device succeeded.
+    Connection,
+}
+
+/// Proximity check response.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[schema(example = json!({
+    "device_name": "iPhone 15 Pro",
+    "device_address": "AA:BB:CC:DD:EE:FF",
+    "is_nearby": true,
+    "rssi_dbm": -45,
+    "threshold_dbm": -60,
+    "detection_method": "advertisement",
+    "checked_at_utc": "2025-01-15T03:30:00Z"
+}))]
+pub struct ProximityResponse {
+    /// The configured Bluetooth device name.
+    #[schema(example = "iPhone 15 Pro")]
+    pub device_name: String,
+
+    /// The Bluetooth MAC address of the tracked device.
+    #[schema(example = "AA:BB:CC:DD:EE:FF")]
+    pub device_address: String,
+
+    /// Whether the device is considered nearby based on RSSI threshold.
+    #[schema(example = true)]
+    pub is_nearby: bool,
+
+    /// The current RSSI signal strength in dBm.
+    #[schema(example = -45)]
+    pub rssi_dbm: Option<i16>,
+
+    /// The configured RSSI threshold in dBm.
+    #[schema(example = -60)]
+    pub threshold_dbm: i8,
+
+    /// How the device was detected, or `null` if it was not found.
+    ///
+    /// `connection` means the device was not advertising but accepted a
+    /// connection; `rssi_dbm` is then the connection RSSI and may be `null`.
+    pub detection_method: Option<DetectionMethod>,
+
+    /// UTC timestamp of when this check was performed.
+    #[schema(example = "2025-01-15T03:30:00Z")]
+    pub checked_at_utc: String,
+}
+
+// ============================================================================
+// Passes
+// ============================================================================
+
+/// Current pass status for the month.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[schema(example = json!({
+    "remaining": 2,
+    "total_per_month": 3,
+    "used_this_month": 1,
+    "month": "2025-01",
+    "resets_at_utc": "2025-02-01T08:00:00Z",
+    "timezone": "America/Los_Angeles"
+}))]
+pub struct PassesResponse {
+    /// Number of passes remaining this month.
+    #[schema(example = 2, minimum = 0)]
+    pub remaining: u32,
+
+    /// Total passes allocated per month (from config).
+    #[schema(example = 3, minimum = 0)]
+    pub total_per_month: u32,
+
+    /// Number of passes used this month.
+    #[schema(example = 1, minimum = 0)]
+    pub used_this_month: u32,
+
+    /// Current month in YYYY-MM format.
+    #[schema(example = "2025-01")]
+    pub month: String,
+
+    /// UTC timestamp when passes will reset.
+    #[schema(example = "2025-02-01T08:00:00Z")]
+    pub resets_at_utc: String,
+
+    /// Configured timezone for reset calculation.
+    #[schema(example = "America/Los_Angeles")]
+    pub timezone: String,
+}
+
+/// A single pass usage entry in history.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[schema(example = json!({
+    "used_at_utc": "2025-01-15T03:30:00Z",
+    "reason": "On-call for production incident"
+}))]
+pub struct PassHistoryEntry {
+    /// UTC timestamp when the pass was used.
+    #[schema(example = "2025-01-15T03:30:00Z")]
+    pub used_at_utc: String,
+
+    /// Reason provided when using the pass.
+    #[schema(example = "On-call for production incident")]
+    pub reason: String,
+}
+
+/// Pass usage history response.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[schema(example = json!({
+    "month": "2025-01",
+    "entries": [
+        {
+            "used_at_utc": "2025-01-15T03:30:00Z",
+            "reason": "On-call for production incident"
+        }
+    ],
+    "total_used": 1,
+    "total_per_month": 3
+}))]
+pub struct PassHistoryResponse {
+    /// Month in YYYY-MM format.
+    #[schema(example = "2025-01")]
+    pub month: String,
+
+    /// List of pass usage entries for the month.
+    pub entries: Vec<PassHistoryEntry>,
+
+    /// Total passes used this month.
+    #[schema(example = 1)]
+    pub total_used: usize,
+
+    /// Total passes allocated per month.
+    #[schema(example = 3)]
+    pub total_per_month: u32,
+}
+
+/// Pass usage history for a time range.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[schema(example = json!({
+    "from_utc": "2025-01-01T00:00:00+00:00",
+    "to_utc": "2025-04-01T00:00:00+00:00",
+    "entries": [
+        {
+            "used_at_utc": "2025-01-15T03:30:00+00:00",
+            "reason": "On-call for production incident"
+        }
+    ],
+    "total_used": 1
+}))]
+pub struct PassHistoryRangeResponse {
+    /// Start of the range (inclusive).
+    #[schema(example = "2025-01-01T00:00:00+00:00")]
+    pub from_utc: String,
+
+    /// End of the range (exclusive).
+    #[schema(example = "2025-04-01T00:00:00+00:00")]
+    pub to_utc: String,
+
+    /// Pass usage entries in the range, oldest first.
+    pub entries: Vec<PassHistoryEntry>,
+
+    /// Number of passes used in the range.
+    #[schema(example = 1)]
+    pub total_used: usize,
+}
+
+/// Request body for using a pass.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[schema(example = json!({
+    "reason": "On-call for production incident tonight"
+}))]
+pub struct UsePassRequest {
+    /// Reason for using the pass. Required and must be non-empty.
+    /// Maximum 500 characters.
+    #[schema(
+        example = "On-call for production incident tonight",
+        min_length = 1,
+        max_length = 500
+    )]
+    pub reason: String,
+}
+
+/// Response after successfully using a pass.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[schema(example = json!({
+    "success": true,
+    "remaining": 1,
+    "used_at_utc": "2025-01-15T03:30:00Z",
+    "reason": "On-call for production incident tonight"
+}))]
+pub struct UsePassResponse {
+    /// Whether the pass was successfully used.
+    #[schema(example = true)]
+    pub success: bool,
+
+    /// Number of passes remaining after this use.
+    #[schema(example = 1)]
+    pub remaining: u32,
+
+    /// UTC timestamp when the pass was used.
+    #[schema(example = "2025-01-15T03:30:00Z")]
+    pub used_at_utc: String,
+
+    /// The reason that was recorded.
+    #[schema(example = "On-call for production incident tonight")]
+    pub reason: String,
+}
+
+// ============================================================================
+// Config
+// ============================================================================
+
+/// How proximity checks detect the device.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ProbeMode {
+    /// Only listen for advertisements.
+    Passive,
+    /// Only connect to the device. Requires the device to be paired.
+    Active,
+    /// Listen for advertisements, and connect if none were heard.
+    PassiveThenActive,
+}
+
+/// How RSSI readings from several adapters are combined into one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RssiFusion {
+    /// Use the strongest reading, i.e. the adapter closest to the device.
+    Strongest,
+    /// Average the readings in the linear power domain.
+    Average,
+}
+
+/// Current configuration response.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[schema(example = json!({
+    "bluetooth": {
+        "target_address": "AA:BB:CC:DD:EE:FF",
+        "target_name": "iPhone 15 Pro",
+        "rssi_threshold": -60
+    },
+    "wifi_networks": [
+        {"ssid": "HomeNetwork", "is_primary": true, "has_password": true}
+    ],
+    "timezone": "America/Los_Angeles",
+    "passes_per_month": 3,
+    "onboarding_complete": true,
+    "curfew": {"enabled": true, "start": "22:00", "end": "06:00", "is_active": false}
+}))]
+pub struct ConfigResponse {
+    /// Bluetooth target configuration.
+    pub bluetooth: BluetoothConfigResponse,
+
+    /// Configured wireless networks. Passwords are never returned.
+    pub wifi_networks: Vec<WifiNetworkResponse>,
+
+    /// Configured timezone (IANA format).
+    #[schema(example = "America/Los_Angeles")]
+    pub timezone: String,
+
+    /// Number of passes allowed per month.
+    #[schema(example = 3)]
+    pub passes_per_month: u8,
+
+    /// Whether initial onboarding has been completed.
+    #[schema(example = true)]
+    pub onboarding_complete: bool,
+
+    /// Nightly curfew.
+    #[serde(default)]
+    pub curfew: CurfewResponse,
+}
+
+/// Bluetooth configuration in response.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[schema(example = json!({
+    "target_address": "AA:BB:CC:DD:EE:FF",
+    "target_name": "iPhone 15 Pro",
+    "rssi_threshold": -60,
+    "probe_mode": "passive",
+    "adapters": ["hci0", "hci1"],
+    "rssi_fusion": "strongest",
+    "is_configured": true,
+    "is_paired": false
+}))]
+pub struct BluetoothConfigResponse {
+    /// Bluetooth MAC address of target device.
+    #[schema(example = "AA:BB:CC:DD:EE:FF")]
+    pub target_address: String,
+
+    /// User-friendly name of the device.
+    #[schema(example = "iPhone 15 Pro")]
+    pub target_name: String,
+
+    /// RSSI threshold for proximity detection.
+    #[schema(example = -60)]
+    pub rssi_threshold: i8,
+
+    /// How proximity checks detect the device.
+    pub probe_mode: ProbeMode,
+
+    /// Adapters used for scanning. Empty means the default adapter.
+    pub adapters: Vec<String>,
+
+    /// How readings from several adapters are combined.
+    pub rssi_fusion: RssiFusion,
+
+    /// Whether a real device has been configured (not placeholder).
+    #[schema(example = true)]
+    pub is_configured: bool,
+
+    /// Whether the device has been paired and its rotating addresses can be
+    /// resolved.
+    #[schema(example = false)]
+    pub is_paired: bool,
+}
+
+/// A configured wireless network, without its password.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct WifiNetworkResponse {
+    /// Network SSID (name).
+    #[schema(example = "HomeNetwork")]
+    pub ssid: String,
+
+    /// Whether this is the primary network.
+    #[schema(example = true)]
+    pub is_primary: bool,
+
+    /// Whether a password is stored for the network.
+    #[schema(example = true)]
+    pub has_password: bool,
+}
+
+/// Request to update timezone.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[schema(example = json!({
+    "timezone": "America/Los_Angeles"
+}))]
+pub struct UpdateTimezoneRequest {
+    /// IANA timezone name (e.g., `America/Los_Angeles`).
+    #[schema(example = "America/Los_Angeles")]
+    pub timezone: String,
+}
+
+/// Response after updating timezone.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct UpdateTimezoneResponse {
+    /// Whether the update was successful.
+    pub success: bool,
+
+    /// Updated timezone.
+    #[schema(example = "America/Los_Angeles")]
+    pub timezone: String,
+}
+
+/// Request to update the nightly curfew.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[schema(example = json!({
+    "enabled": true,
+    "start": "22:30",
+    "end": "06:30"
+}))]
+pub struct UpdateCurfewRequest {
+    /// Whether the curfew is in effect.
+    pub enabled: bool,
+
+    /// When the curfew starts each night (`HH:MM` in the configured timezone).
+    #[schema(example = "22:30")]
+    pub start: String,
+
+    /// When the curfew ends each morning (`HH:MM` in the configured timezone).
+    /// May be earlier than `start`, for a curfew spanning midnight.
+    #[schema(example = "06:30")]
+    pub end: String,
+}
+
+/// Response after updating the curfew.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct UpdateCurfewResponse {
+    /// Whether the update was successful.
+    pub success: bool,
+
+    /// Updated curfew.
+    pub curfew: CurfewResponse,
+}
+
+/// Request to update passes per month.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[schema(example = json!({
+    "per_month": 3
+}))]
+pub struct UpdatePassesPerMonthRequest {
+    /// Number of passes per month (0-31).
+    #[schema(example = 3, minimum = 0, maximum = 31)]
+    pub per_month: u8,
+}
+
+/// Response after updating passes per month.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct UpdatePassesPerMonthResponse {
+    /// Whether the update was successful.
+    pub success: bool,
+
+    /// Updated passes per month value.
+    #[schema(example = 3)]
+    pub per_month: u8,
+
+    /// Whether the change is pending (will apply next month).
+    #[schema(example = true)]
+    pub pending: bool,
+
+    /// Message explaining when the change takes effect.
+    pub message: String,
+}
+
+// ============================================================================
+// Guarded settings
+// ============================================================================
+
+/// Setting modified by a pending change.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum GuardedSetting {
+    /// The tracked Bluetooth device and its detection settings.
+    Bluetooth,
+    /// Passes per month.
+    PassesPerMonth,
+    /// The nightly curfew.
+    Curfew,
+    /// The timezone passes reset and the curfew runs in.
+    Timezone,
+    /// Webhooks, which may be how the partner hears about passes.
+    Webhooks,
+}
+
+/// A change to a guarded setting waiting for approval.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[schema(example = json!({
+    "id": "5b6f0c7e-2a1d-4c3b-9e8f-7a6b5c4d3e2f",
+    "setting": "bluetooth",
+    "requested_by": "web-ui",
+    "requested_at_utc": "2025-01-15T23:05:00Z",
+    "effective_at_utc": "2025-01-16T23:05:00Z",
+    "before": {
+        "target_address": "AA:BB:CC:DD:EE:FF",
+        "target_name": "iPhone 15 Pro",
+        "rssi_threshold": -60
+    },
+    "after": {
+        "target_address": "AA:BB:CC:DD:EE:FF",
+        "target_name": "iPhone 15 Pro",
+        "rssi_threshold": -90
+    }
+}))]
+pub struct PendingChangeResponse {
+    /// Unique id of the change.
+    pub id: String,
+
+    /// The setting the change modifies.
+    pub setting: GuardedSetting,
+
+    /// Who requested the change.
+    #[schema(example = "web-ui")]
+    pub requested_by: String,
+
+    /// When the change was requested.
+    #[schema(example = "2025-01-15T23:05:00Z")]
+    pub requested_at_utc: String,
+
+    /// When the change takes effect without approval, if ever.
+    #[schema(example = "2025-01-16T23:05:00Z")]
+    pub effective_at_utc: Option<String>,
+
+    /// The affected settings when the change was requested.
+    pub before: serde_json::Value,
+
+    /// The affected settings as they will be after the change.
+    pub after: serde_json::Value,
+}
+
+// ============================================================================
+// Access
+// ============================================================================
+
+/// What a request to the API may do, from least to most.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, ToSchema,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum ApiScope {
+    /// Only read state.
+    Read,
+    /// Read state and use passes.
+    Passes,
+    /// Read state, use passes and change settings.
+    Settings,
+}
+
+/// What the caller may do.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[schema(example = json!({
+    "scope": "passes",
+    "authenticated_as": "token:assistant"
+}))]
+pub struct AccessResponse {
+    /// The caller's scope.
+    pub scope: ApiScope,
+
+    /// Who the caller's token authenticated them as, if anyone.
+    #[schema(example = "token:assistant")]
+    pub authenticated_as: Option<String>,
+}
+
+// ============================================================================
+// Devices
+// ============================================================================
+
+/// Type of address a Bluetooth device advertises with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum BluetoothAddressType {
+    /// A public (IEEE-assigned) address, including all BR/EDR addresses.
+    Public,
+    /// A random address that does not rotate (static) or cannot be resolved.
+    Random,
+    /// A resolvable private address that rotates periodically.
+    Resolvable,
+}
+
+/// A discovered Bluetooth device.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[schema(example = json!({
+    "address": "AA:BB:CC:DD:EE:FF",
+    "name": "iPhone 15 Pro",
+    "rssi_dbm": -45,
+    "address_type": "public",
+    "paired": false
+}))]
+pub struct DiscoveredDevice {
+    /// Bluetooth MAC address.
+    #[schema(example = "AA:BB:CC:DD:EE:FF")]
+    pub address: String,
+
+    /// Device name (if broadcast).
+    #[schema(example = "iPhone 15 Pro")]
+    pub name: Option<String>,
+
+    /// Signal strength in dBm.
+    #[schema(example = -45)]
+    pub rssi_dbm: Option<i16>,
+
+    /// Kind of address the device is advertising with.
+    ///
+    /// Devices with a `resolvable` address rotate it periodically and should
+    /// be paired so they can still be recognised afterwards.
+    pub address_type: BluetoothAddressType,
+
+    /// Whether the device is already paired with this Tether.
+    #[schema(example = false)]
+    pub paired: bool,
+}
+
+/// Device scan response.
+///
+/// Also sent as the final `complete` event of `GET /api/devices/stream`.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[schema(example = json!({
+    "devices": [
+        {
+            "address": "AA:BB:CC:DD:EE:FF",
+            "name": "iPhone 15 Pro",
+            "rssi_dbm": -45,
+            "address_type": "public",
+            "paired": false
+        }
+    ],
+    "scan_duration_secs": 5,
+    "scanned_at_utc": "2025-01-15T03:30:00Z"
+}))]
+pub struct ScanDevicesResponse {
+    /// List of discovered devices.
+    pub devices: Vec<DiscoveredDevice>,
+
+    /// How long the scan took.
+    #[schema(example = 5)]
+    pub scan_duration_secs: u64,
+
+    /// When the scan completed.
+    #[schema(example = "2025-01-15T03:30:00Z")]
+    pub scanned_at_utc: String,
+}
+
+// ============================================================================
+// System
+// ============================================================================
+
+/// Overall state of the Bluetooth scanner.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ScannerState {
+    /// The scanner is available and the last scan succeeded.
+    Healthy,
+    /// The scanner is available but recent scans failed.
+    Degraded,
+    /// The scanner is being re-created.
+    Recovering,
+    /// No scanner is available.
+    Unavailable,
+}
+
+/// Snapshot of the Bluetooth scanner's health.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[schema(example = json!({
+    "state": "healthy",
+    "last_successful_scan_utc": "2025-01-15T03:30:00Z",
+    "consecutive_failures": 0,
+    "total_failures": 2,
+    "recoveries": 1,
+    "last_error": "Bluetooth adapter is powered off. Run 'bluetoothctl power on' to enable.",
+    "last_error_at_utc": "2025-01-14T22:10:00Z"
+}))]
+pub struct BluetoothHealth {
+    /// Overall scanner state.
+    pub state: ScannerState,
+
+    /// When a scan last completed successfully.
+    #[schema(example = "2025-01-15T03:30:00Z")]
+    pub last_successful_scan_utc: Option<String>,
+
+    /// Scan failures since the last successful scan or recovery.
+    #[schema(example = 0)]
+    pub consecutive_failures: u32,
+
+    /// Scan failures since the server started.
+    #[schema(example = 2)]
+    pub total_failures: u64,
+
+    /// How many times the scanner was re-created after a failure.
+    #[schema(example = 1)]
+    pub recoveries: u32,
+
+    /// The most recent error, if any.
+    pub last_error: Option<String>,
+
+    /// When the most recent error occurred.
+    #[schema(example = "2025-01-14T22:10:00Z")]
+    pub last_error_at_utc: Option<String>,
+}
+
+/// System status response.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[schema(example = json!({
+    "version": "0.1.0",
+    "uptime_secs": 3600,
+    "bluetooth_available": true,
+    "bluetooth_health": {
+        "state": "healthy",
+        "last_successful_scan_utc": "2025-01-15T03:30:00Z",
+        "consecutive_failures": 0,
+        "total_failures": 0,
+        "recoveries": 0,
+        "last_error": null,
+        "last_error_at_utc": null
+    },
+    "config_loaded": true,
+    "onboarding_complete": true
+}))]
+pub struct SystemStatusResponse {
+    /// Server version.
+    #[schema(example = "0.1.0")]
+    pub version: String,
+
+    /// Server uptime in seconds.
+    #[schema(example = 3600)]
+    pub uptime_secs: u64,
+
+    /// Whether Bluetooth is available.
+    #[schema(example = true)]
+    pub bluetooth_available: bool,
+
+    /// Health of the Bluetooth scanner, including failures and recoveries.
+    pub bluetooth_health: BluetoothHealth,
+
+    /// Whether configuration is loaded.
+    #[schema(example = true)]
+    pub config_loaded: bool,
+
+    /// Whether onboarding is complete.
+    #[schema(example = true)]
+    pub onboarding_complete: bool,
+}
+
+/// Dumbpipe ticket response.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[schema(example = json!({
+    "ticket": "blobfd23abc...",
+    "expires_at_utc": "2025-01-15T04:30:00Z",
+    "node_id": "n0abc123..."
+}))]
+pub struct DumbpipeTicketResponse {
+    /// The dumbpipe ticket for remote access.
+    /// This is a base32-encoded iroh ticket.
+    #[schema(example = "blobfd23abc...")]
+    pub ticket: Option<String>,
+
+    /// When the ticket expires (if applicable).
+    #[schema(example = "2025-01-15T04:30:00Z")]
+    pub expires_at_utc: Option<String>,
+
+    /// The node ID of this tether instance.
+    #[schema(example = "n0abc123...")]
+    pub node_id: Option<String>,
+
+    /// Whether dumbpipe is available.
+    #[schema(example = true)]
+    pub available: bool,
+
+    /// Message if not available.
+    pub message: Option<String>,
+}
@@ -0,0 +1,494 @@
+//! Choosing how to reach the tether server.
+//!
+//! The server can be reached three ways:
+//!
+//! - **Direct**: a base URL from the config, e.g. a server running locally
+//!   for development.
+//! - **LAN**: an instance found on the local network over mDNS, when the
+//!   laptop is on the same Wi-Fi as the Pi.
+//! - **Ticket**: the iroh tunnel, which works from anywhere.
+//!
+//! In [`ConnectionMode::Auto`], a configured URL wins; otherwise the LAN is
+//! searched, falling back to the tunnel if no instance answers. When several
+//! servers are configured, each names the LAN instance it may use, so one
+//! doesn't connect to another's Pi.
+//!
+//! Anything on the LAN can advertise a tether service, so with a ticket to
+//! fall back to, the LAN is only searched for a named instance, and the
+//! bearer token is never sent to a discovered one. A direct route that stops
+//! answering can be swapped for the tunnel with [`Connection::fall_back`].
+
+use std::net::{IpAddr, SocketAddr};
+use std::time::Duration;
+
+use mdns_sd::{ServiceDaemon, ServiceEvent, ServiceInfo};
+use tether_client::{MDNS_SERVICE_TYPE, TetherClient};
+use tokio::time::timeout;
+use tracing::{debug, info, warn};
+use url::Url;
+
+use crate::tunnel::{Tunnel, TunnelError, TunnelMonitor, TunnelOptions};
+
+/// How long to browse the LAN for an instance.
+const LAN_DISCOVERY_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// How long a health check of a direct URL may take.
+const HEALTH_CHECK_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// How a client reaches the tether server.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConnectionMode {
+    /// Use the configured URL, else the LAN, else the ticket.
+    Auto,
+    /// Only the configured URL.
+    Direct,
+    /// Only an instance discovered on the LAN.
+    Lan,
+    /// Only the iroh tunnel.
+    Ticket,
+}
+
+impl std::str::FromStr for ConnectionMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "auto" => Ok(Self::Auto),
+            "direct" | "url" => Ok(Self::Direct),
+            "lan" | "mdns" => Ok(Self::Lan),
+            "ticket" | "tunnel" | "dumbpipe" => Ok(Self::Ticket),
+            other => Err(other.to_string()),
+        }
+    }
+}
+
+/// Settings for reaching the server.
+#[derive(Debug, Clone)]
+pub struct ConnectionConfig {
+    /// Which routes to try.
+    pub mode: ConnectionMode,
+    /// Base URL for direct connections.
+    pub base_url: Option<Url>,
+    /// Ticket for the iroh tunnel.
+    pub ticket: Option<String>,
+    /// Name of the LAN instance to use, or any if unset.
+    ///
+    /// In auto mode with a ticket, the LAN is skipped unless this is set.
+    pub lan_name: Option<String>,
+    /// Bearer token to send with requests, if any, except to instances
+    /// discovered on the LAN.
+    pub token: Option<String>,
+    /// Local port the tunnel forwards.
+    pub local_port: u16,
+    /// Timing of tunnel connection attempts.
+    pub tunnel: TunnelOptions,
+}
+
+/// Errors choosing a route to the server.
+#[derive(Debug, thiserror::Error)]
+pub enum ConnectError {
+    #[error("Server at {url} is not reachable: {reason}")]
+    Unreachable { url: Url, reason: String },
+
+    #[error("No tether server found on the LAN: {0}")]
+    NotOnLan(String),
+
+    #[error(
+        "No tether server found on the LAN ({lan_error}), and no dumbpipe ticket to fall back to"
+    )]
+    NoRoute { lan_error: String },
+
+    #[error(transparent)]
+    Tunnel(#[from] TunnelError),
+}
+
+/// How the server is reached, for reporting the connection's health.
+#[derive(Debug, Clone)]
+pub enum Link {
+    /// Plain HTTP to a configured or discovered URL.
+    Direct {
+        base_url: Url,
+        /// Name of the mDNS instance, if discovered on the LAN.
+        instance: Option<String>,
+    },
+    /// Through the iroh tunnel.
+    Tunnel(TunnelMonitor),
+}
+
+/// An established route to the server.
+pub enum Connection {
+    /// Plain HTTP.
+    Direct {
+        base_url: Url,
+        instance: Option<String>,
+    },
+    /// Through the iroh tunnel.
+    Tunnel(Tunnel),
+}
+
+impl Connection {
+    /// Picks a route to the server according to `config.mode`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no route in the mode reaches the server.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `config` is in direct mode without a URL, or ticket mode
+    /// without a ticket.
+    pub async fn establish(config: &ConnectionConfig) -> Result<Self, ConnectError> {
+        match config.mode {
+            ConnectionMode::Direct => {
+                // Config validation guarantees the URL in direct mode
+                let base_url = config.base_url.clone().expect("Direct mode has a base URL");
+                check_health(&base_url, config.token.as_deref()).await?;
+                Ok(Self::direct(base_url, None))
+            }
+            ConnectionMode::Lan => {
+                let (instance, base_url) =
+                    discover(config).await.map_err(ConnectError::NotOnLan)?;
+                Ok(Self::direct(base_url, Some(instance)))
+            }
+            ConnectionMode::Ticket => {
+                // Config validation guarantees the ticket in ticket mode
+                let ticket = config.ticket.as_deref().expect("Ticket mode has a ticket");
+                Self::tunnel(ticket, config).await
+            }
+            ConnectionMode::Auto => {
+                if let Some(base_url) = &config.base_url {
+                    return match (
+                        check_health(base_url, config.token.as_deref()).await,
+                        &config.ticket,
+                    ) {
+                        (Ok(()), _) => Ok(Self::direct(base_url.clone(), None)),
+                        (Err(e), Some(ticket)) => {
+                            info!("{}; falling back to the tunnel", e);
+                            Self::tunnel(ticket, config).await
+                        }
+                        (Err(e), None) => Err(e),
+                    };
+                }
+
+                // Without a name, any host advertising the service would do;
+                // the tunnel authenticates the Pi
+                if let (Some(ticket), None) = (&config.ticket, &config.lan_name) {
+                    info!("No LAN instance named; using the tunnel");
+                    return Self::tunnel(ticket, config).await;
+                }
+
+                match discover(config).await {
+                    Ok((instance, base_url)) => Ok(Self::direct(base_url, Some(instance))),
+                    Err(lan_error) => match &config.ticket {
+                        Some(ticket) => {
+                            info!("{}; falling back to the tunnel", lan_error);
+                            Self::tunnel(ticket, config).await
+                        }
+                        None => Err(ConnectError::NoRoute { lan_error }),
+                    },
+                }
+            }
+        }
+    }
+
+    fn direct(base_url: Url, instance: Option<String>) -> Self {
+        if let Some(instance) = &instance {
+            info!("Connecting directly to '{}' at {}", instance, base_url);
+        } else {
+            info!("Connecting directly to {}", base_url);
+        }
+        Self::Direct { base_url, instance }
+    }
+
+    async fn tunnel(ticket: &str, config: &ConnectionConfig) -> Result<Self, ConnectError> {
+        let tunnel = Tunnel::connect(ticket, config.local_port, config.tunnel).await?;
+
+        // Serve even if the Pi is unreachable for now; tools report the
+        // tunnel's status until it comes up
+        match tunnel
+            .monitor()
+            .wait_connected(config.tunnel.connect_timeout)
+            .await
+        {
+            Ok(()) => info!("Tunnel established at {}", tunnel.base_url()),
+            Err(status) => warn!("{}", status.unavailable_message()),
+        }
+
+        Ok(Self::Tunnel(tunnel))
+    }
+
+    /// Replaces a direct route that no longer answers with the tunnel.
+    ///
+    /// Returns `None` if the route is a healthy direct one, is already the
+    /// tunnel, or has nothing to fall back to: only auto mode with a ticket
+    /// falls back.
+    pub async fn fall_back(&self, config: &ConnectionConfig) -> Option<Result<Self, ConnectError>> {
+        let Self::Direct { base_url, .. } = self else {
+            return None;
+        };
+        let ticket = config
+            .ticket
+            .as_deref()
+            .filter(|_| config.mode == ConnectionMode::Auto)?;
+
+        let e = check_health(base_url, self.token(config)).await.err()?;
+        info!("{}; falling back to the tunnel", e);
+        Some(Self::tunnel(ticket, config).await)
+    }
+
+    /// Returns the bearer token to send over this route.
+    ///
+    /// Instances discovered on the LAN get none, as nothing proves they're
+    /// the server the token is for.
+    #[must_use]
+    pub fn token<'a>(&self, config: &'a ConnectionConfig) -> Option<&'a str> {
+        match self {
+            Self::Direct {
+                instance: Some(_), ..
+            } => None,
+            _ => config.token.as_deref(),
+        }
+    }
+
+    /// Returns the base URL to send API requests to.
+    #[must_use]
+    pub fn base_url(&self) -> Url {
+        match self {
+            Self::Direct { base_url, .. } => base_url.clone(),
+            Self::Tunnel(tunnel) => tunnel.base_url(),
+        }
+    }
+
+    /// Returns a handle describing the route, for health reporting.
+    #[must_use]
+    pub fn link(&self) -> Link {
+        match self {
+            Self::Direct { base_url, instance } => Link::Direct {
+                base_url: base_url.clone(),
+                instance: instance.clone(),
+            },
+            Self::Tunnel(tunnel) => Link::Tunnel(tunnel.monitor()),
+        }
+    }
+
+    /// Closes the tunnel, if any.
+    pub async fn shutdown(self) {
+        if let Self::Tunnel(tunnel) = self {
+            tunnel.shutdown().await;
+        }
+    }
+}
+
+/// Checks that a tether server answers at `base_url`.
+async fn check_health(base_url: &Url, token: Option<&str>) -> Result<(), ConnectError> {
+    let mut client = TetherClient::new(base_url.clone());
+    if let Some(token) = token {
+        client = client.with_token(token);
+    }
+    let unreachable = |reason: String| ConnectError::Unreachable {
+        url: base_url.clone(),
+        reason,
+    };
+
+    match timeout(HEALTH_CHECK_TIMEOUT, client.get_health()).await {
+        Ok(Ok(health)) => {
+            debug!("{} is tether {}", base_url, health.version);
+            Ok(())
+        }
+        Ok(Err(e)) => Err(unreachable(e.to_string())),
+        Err(_) => Err(unreachable(format!(
+            "no answer within {} seconds",
+            HEALTH_CHECK_TIMEOUT.as_secs()
+        ))),
+    }
+}
+
+/// Browses the LAN for a tether server that answers its health check.
+///
+/// Returns the instance name and base URL of the first one found, skipping
+/// instances other than `config.lan_name` if set.
+async fn discover(config: &ConnectionConfig) -> Result<(String, Url), String> {
+    let daemon = ServiceDaemon::new().map_err(|e| format!("mDNS unavailable: {e}"))?;
+    let receiver = daemon
+        .browse(MDNS_SERVICE_TYPE)
+        .map_err(|e| format!("mDNS browse failed: {e}"))?;
+
+    let search = async {
+        while let Ok(event) = receiver.recv_async().await {
+            let ServiceEvent::ServiceResolved(info) = event else {
+                continue;
+            };
+            let Some(base_url) = base_url_of(&info) else {
+                continue;
+            };
+            let instance = instance_name(&info);
+            if config
+                .lan_name
+                .as_ref()
+                .is_some_and(|name| !name.eq_ignore_ascii_case(&instance))
+            {
+                debug!("Skipping '{}': not the configured instance", instance);
+                continue;
+            }
+            // No token: the instance is only as trustworthy as its name
+            match check_health(&base_url, None).await {
+                Ok(()) => return Some((instance, base_url)),
+                Err(e) => debug!("Skipping '{}': {}", instance, e),
+            }
+        }
+        None
+    };
+    let found = timeout(LAN_DISCOVERY_TIMEOUT, search).await.ok().flatten();
+
+    let _ = daemon.shutdown();
+
+    found.ok_or_else(|| {
+        let instance = config.lan_name.as_ref().map_or_else(
+            || "no instance".to_string(),
+            |name| format!("no instance named '{name}'"),
+        );
+        format!(
+            "{instance} answered within {} seconds",
+            LAN_DISCOVERY_TIMEOUT.as_secs()
+        )
+    })
+}
+
+/// Returns the instance name without the service type.
+fn instance_name(info: &ServiceInfo) -> String {
+    let fullname = info.get_fullname();
+    fullname
+        .strip_suffix(MDNS_SERVICE_TYPE)
+        .map_or(fullname, |name| name.trim_end_matches('.'))
+        .to_string()
+}
+
+/// Builds the base URL of a resolved instance, preferring IPv4.
+///
+/// IPv6 link-local addresses are skipped, as they'd need a scope ID.
+fn base_url_of(info: &ServiceInfo) -> Option<Url> {
+    let addresses = info.get_addresses();
+    let ip = addresses.iter().find(|ip| ip.is_ipv4()).or_else(|| {
+        addresses
+            .iter()
+            .find(|ip| matches!(ip, IpAddr::V6(v6) if !v6.is_unicast_link_local()))
+    })?;
+
+    Url::parse(&format!("http://{}", SocketAddr::new(*ip, info.get_port()))).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn service(ips: &str) -> ServiceInfo {
+        ServiceInfo::new(
+            MDNS_SERVICE_TYPE,
+            "bedroom",
+            "tether-bedroom.local.",
+            ips,
+            8080,
+            None::<std::collections::HashMap<String, String>>,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_parse_connection_mode() {
+        assert_eq!("auto".parse(), Ok(ConnectionMode::Auto));
+        assert_eq!("Direct".parse(), Ok(ConnectionMode::Direct));
+        assert_eq!("mdns".parse(), Ok(ConnectionMode::Lan));
+        assert_eq!("tunnel".parse(), Ok(ConnectionMode::Ticket));
+        assert!("carrier-pigeon".parse::<ConnectionMode>().is_err());
+    }
+
+    #[test]
+    fn test_base_url_prefers_ipv4() {
+        let info = service("fe80::1,fd00::20,192.168.1.20");
+        assert_eq!(
+            base_url_of(&info).unwrap().as_str(),
+            "http://192.168.1.20:8080/"
+        );
+
+        let info = service("fe80::1,fd00::20");
+        assert_eq!(
+            base_url_of(&info).unwrap().as_str(),
+            "http://[fd00::20]:8080/"
+        );
+
+        let info = service("fe80::1");
+        assert!(base_url_of(&info).is_none());
+    }
+
+    #[test]
+    fn test_instance_name() {
+        assert_eq!(instance_name(&service("192.168.1.20")), "bedroom");
+    }
+
+    #[tokio::test]
+    async fn test_direct_needs_a_server() {
+        // Nothing listens on the discard port
+        let config = ConnectionConfig {
+            mode: ConnectionMode::Direct,
+            base_url: Some("http://127.0.0.1:9".parse().unwrap()),
+            ticket: None,
+            lan_name: None,
+            token: None,
+            local_port: 0,
+            tunnel: TunnelOptions::default(),
+        };
+
+        let result = Connection::establish(&config).await;
+
+        assert!(matches!(result, Err(ConnectError::Unreachable { .. })));
+    }
+
+    #[test]
+    fn test_token_withheld_from_lan_instances() {
+        let config = ConnectionConfig {
+            mode: ConnectionMode::Auto,
+            base_url: None,
+            ticket: None,
+            lan_name: None,
+            token: Some("secret".to_string()),
+            local_port: 0,
+            tunnel: TunnelOptions::default(),
+        };
+        let base_url: Url = "http://192.168.1.20:8080".parse().unwrap();
+
+        let configured = Connection::Direct {
+            base_url: base_url.clone(),
+            instance: None,
+        };
+        let discovered = Connection::Direct {
+            base_url,
+            instance: Some("bedroom".to_string()),
+        };
+
+        assert_eq!(configured.token(&config), Some("secret"));
+        assert_eq!(discovered.token(&config), None);
+    }
+
+    #[tokio::test]
+    async fn test_fall_back_needs_auto_mode_and_a_ticket() {
+        let mut config = ConnectionConfig {
+            mode: ConnectionMode::Direct,
+            base_url: Some("http://127.0.0.1:9".parse().unwrap()),
+            ticket: Some("ticket".to_string()),
+            lan_name: None,
+            token: None,
+            local_port: 0,
+            tunnel: TunnelOptions::default(),
+        };
+        let connection = Connection::Direct {
+            base_url: config.base_url.clone().unwrap(),
+            instance: None,
+        };
+
+        assert!(connection.fall_back(&config).await.is_none());
+
+        config.mode = ConnectionMode::Auto;
+        config.ticket = None;
+        assert!(connection.fall_back(&config).await.is_none());
+    }
+}
@@ -0,0 +1,15 @@
+//! # tether-connect
+//!
+//! Reaching a tether server from another machine, shared by tether-mcp and
+//! tetherctl.
+//!
+//! ## Modules
+//!
+//! - [`connection`] - Choosing between a direct URL, the LAN and the tunnel
+//! - [`tunnel`] - The in-process iroh tunnel to a Pi running dumbpipe
+
+pub mod connection;
+pub mod tunnel;
+
+pub use connection::{ConnectError, Connection, ConnectionConfig, ConnectionMode, Link};
+pub use tunnel::{Tunnel, TunnelError, TunnelMonitor, TunnelOptions, TunnelState, TunnelStatus};
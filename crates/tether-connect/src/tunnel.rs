@@ -0,0 +1,736 @@
+//! In-process iroh tunnel to the Raspberry Pi.
+//!
+//! The Pi runs `dumbpipe listen-tcp`, which accepts QUIC connections on the
+//! `DUMBPIPEV0` ALPN and forwards each bidirectional stream to the tether
+//! HTTP server. This module is the other end of that pipe: it dials the
+//! endpoint in the ticket, listens on a local TCP port, and forwards every
+//! accepted TCP connection over its own stream, so the HTTP client can talk
+//! to `http://127.0.0.1:<port>` as if the server were local.
+
+use std::collections::BTreeSet;
+use std::fmt::Write;
+use std::net::{Ipv4Addr, SocketAddr};
+use std::time::{Duration, Instant};
+
+use iroh::endpoint::{Connection, presets};
+use iroh::{Endpoint, EndpointAddr, EndpointId, RelayUrl, TransportAddr};
+use serde::Deserialize;
+use tokio::io::AsyncWriteExt;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+use tokio::time::timeout;
+use tracing::{debug, info, warn};
+use url::Url;
+
+/// ALPN spoken by `dumbpipe listen` and `dumbpipe listen-tcp`.
+pub const ALPN: &[u8] = b"DUMBPIPEV0";
+
+/// Bytes the connecting side sends first on every stream.
+///
+/// QUIC streams only reach the peer once data is written on them, so
+/// dumbpipe has the dialer send this before any payload.
+pub const HANDSHAKE: [u8; 5] = *b"hello";
+
+/// Prefix of dumbpipe endpoint tickets.
+const TICKET_KIND: &str = "endpoint";
+
+/// Errors establishing the tunnel.
+#[derive(Debug, thiserror::Error)]
+pub enum TunnelError {
+    #[error("Invalid dumbpipe ticket: {0}")]
+    InvalidTicket(String),
+
+    #[error("Failed to start the iroh endpoint: {0}")]
+    Bind(String),
+
+    #[error("Failed to connect to {endpoint_id}: {reason}")]
+    Connect {
+        endpoint_id: EndpointId,
+        reason: String,
+    },
+
+    #[error("Timed out connecting to {endpoint_id} after {secs} seconds")]
+    ConnectTimeout { endpoint_id: EndpointId, secs: u64 },
+
+    #[error("Failed to listen on {addr}: {source}")]
+    Listen {
+        addr: SocketAddr,
+        #[source]
+        source: std::io::Error,
+    },
+}
+
+/// Wire format of an endpoint ticket, as written by dumbpipe.
+#[derive(Deserialize)]
+#[cfg_attr(test, derive(serde::Serialize))]
+enum TicketWireFormat {
+    Variant0(Variant0Ticket),
+}
+
+#[derive(Deserialize)]
+#[cfg_attr(test, derive(serde::Serialize))]
+struct Variant0Ticket {
+    addr: Variant0Addr,
+}
+
+#[derive(Deserialize)]
+#[cfg_attr(test, derive(serde::Serialize))]
+struct Variant0Addr {
+    endpoint_id: EndpointId,
+    info: Variant0AddrInfo,
+}
+
+#[derive(Deserialize)]
+#[cfg_attr(test, derive(serde::Serialize))]
+struct Variant0AddrInfo {
+    relay_url: Option<RelayUrl>,
+    direct_addresses: BTreeSet<SocketAddr>,
+}
+
+/// Parses a dumbpipe ticket into the address of the endpoint to dial.
+///
+/// Tickets are `endpoint` followed by the base32 encoding of the
+/// postcard-serialized endpoint address.
+///
+/// # Errors
+///
+/// Returns [`TunnelError::InvalidTicket`] if the ticket is malformed.
+pub fn parse_ticket(ticket: &str) -> Result<EndpointAddr, TunnelError> {
+    let encoded = ticket.trim().strip_prefix(TICKET_KIND).ok_or_else(|| {
+        TunnelError::InvalidTicket(format!("expected a ticket starting with '{TICKET_KIND}'"))
+    })?;
+
+    let bytes = data_encoding::BASE32_NOPAD
+        .decode(encoded.to_ascii_uppercase().as_bytes())
+        .map_err(|e| TunnelError::InvalidTicket(format!("not base32: {e}")))?;
+
+    let TicketWireFormat::Variant0(ticket) = postcard::from_bytes(&bytes)
+        .map_err(|e| TunnelError::InvalidTicket(format!("malformed ticket: {e}")))?;
+
+    let Variant0Addr { endpoint_id, info } = ticket.addr;
+    let addrs = info
+        .relay_url
+        .map(TransportAddr::Relay)
+        .into_iter()
+        .chain(info.direct_addresses.into_iter().map(TransportAddr::Ip));
+
+    Ok(EndpointAddr::from_parts(endpoint_id, addrs))
+}
+
+/// Timing of connection attempts.
+#[derive(Debug, Clone, Copy)]
+pub struct TunnelOptions {
+    /// How long one connection attempt may take.
+    pub connect_timeout: Duration,
+    /// Delay before retrying after the first failed attempt.
+    pub initial_backoff: Duration,
+    /// Longest delay between attempts.
+    pub max_backoff: Duration,
+}
+
+impl Default for TunnelOptions {
+    fn default() -> Self {
+        Self {
+            connect_timeout: Duration::from_secs(30),
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(60),
+        }
+    }
+}
+
+/// State of the connection to the Pi.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TunnelState {
+    /// Dialing for the first time.
+    Connecting,
+    /// Connected; requests are forwarded.
+    Connected,
+    /// The connection was lost or never came up; retrying with backoff.
+    Reconnecting,
+}
+
+impl std::fmt::Display for TunnelState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Connecting => write!(f, "connecting"),
+            Self::Connected => write!(f, "connected"),
+            Self::Reconnecting => write!(f, "reconnecting"),
+        }
+    }
+}
+
+/// Health of the tunnel, as tracked by its supervisor.
+#[derive(Debug, Clone)]
+pub struct TunnelStatus {
+    /// Current state.
+    pub state: TunnelState,
+    /// Endpoint of the Pi.
+    pub endpoint_id: EndpointId,
+    /// When the current connection came up.
+    pub connected_at: Option<Instant>,
+    /// Failed attempts since the last successful connection.
+    pub failed_attempts: u32,
+    /// Times the connection was re-established after being lost.
+    pub reconnects: u32,
+    /// Why the last attempt failed or the last connection was lost.
+    pub last_error: Option<String>,
+    /// When the next attempt is due, while backing off.
+    pub next_attempt_at: Option<Instant>,
+}
+
+impl TunnelStatus {
+    const fn new(endpoint_id: EndpointId) -> Self {
+        Self {
+            state: TunnelState::Connecting,
+            endpoint_id,
+            connected_at: None,
+            failed_attempts: 0,
+            reconnects: 0,
+            last_error: None,
+            next_attempt_at: None,
+        }
+    }
+
+    /// Describes why requests can't be forwarded right now.
+    #[must_use]
+    pub fn unavailable_message(&self) -> String {
+        let mut message = format!("The tunnel to the Pi is {}", self.state);
+        if self.failed_attempts > 0 {
+            let _ = write!(message, " (attempt {} failed", self.failed_attempts);
+            if let Some(at) = self.next_attempt_at {
+                let wait = at.saturating_duration_since(Instant::now());
+                let _ = write!(message, ", retrying in {}s", wait.as_secs());
+            }
+            message.push(')');
+        }
+        if let Some(error) = &self.last_error {
+            let _ = write!(message, ". Last error: {error}");
+        }
+        message.push_str(". Try again shortly.");
+        message
+    }
+}
+
+/// Read-only view of a tunnel's health, cheap to clone.
+#[derive(Debug, Clone)]
+pub struct TunnelMonitor {
+    status: watch::Receiver<TunnelStatus>,
+}
+
+impl TunnelMonitor {
+    /// Returns the current status.
+    #[must_use]
+    pub fn status(&self) -> TunnelStatus {
+        self.status.borrow().clone()
+    }
+
+    /// Waits up to `max_wait` for the tunnel to be connected.
+    ///
+    /// Returns the status at the deadline if it still isn't, so callers can
+    /// fail fast with a useful message instead of a bare connection error.
+    ///
+    /// # Errors
+    ///
+    /// Returns the tunnel's status if it isn't connected by the deadline.
+    pub async fn wait_connected(&self, max_wait: Duration) -> Result<(), TunnelStatus> {
+        let mut status = self.status.clone();
+        let connected = status.wait_for(|s| s.state == TunnelState::Connected);
+        match timeout(max_wait, connected).await {
+            Ok(Ok(_)) => Ok(()),
+            _ => Err(self.status()),
+        }
+    }
+}
+
+/// A supervised tunnel forwarding a local TCP port to the tether server.
+///
+/// The local port stays open for the tunnel's lifetime. A supervisor task
+/// dials the Pi, watches the connection, and redials with exponential
+/// backoff when it is lost; local connections accepted while disconnected
+/// are closed immediately.
+pub struct Tunnel {
+    endpoint: Endpoint,
+    local_addr: SocketAddr,
+    status: watch::Receiver<TunnelStatus>,
+    connection: watch::Receiver<Option<Connection>>,
+    supervisor: JoinHandle<()>,
+    forwarder: JoinHandle<()>,
+}
+
+impl Tunnel {
+    /// Starts a tunnel to the endpoint in `ticket`, forwarding `local_port`.
+    ///
+    /// Uses the n0 relays and address lookup, so the Pi is reachable from
+    /// behind NAT. A `local_port` of 0 picks a free port. Returns once the
+    /// port is open; the connection comes up in the background.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the ticket is malformed, or the endpoint or the
+    /// local port can't be bound.
+    pub async fn connect(
+        ticket: &str,
+        local_port: u16,
+        options: TunnelOptions,
+    ) -> Result<Self, TunnelError> {
+        let addr = parse_ticket(ticket)?;
+
+        let endpoint = Endpoint::builder(presets::N0)
+            .bind()
+            .await
+            .map_err(|e| TunnelError::Bind(e.to_string()))?;
+
+        Self::start(endpoint, addr, local_port, options).await
+    }
+
+    /// Starts a tunnel from `endpoint` to `addr`, forwarding `local_port`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TunnelError::Listen`] if the local port can't be bound.
+    pub async fn start(
+        endpoint: Endpoint,
+        addr: EndpointAddr,
+        local_port: u16,
+        options: TunnelOptions,
+    ) -> Result<Self, TunnelError> {
+        let listen_addr = SocketAddr::from((Ipv4Addr::LOCALHOST, local_port));
+        let listener = match TcpListener::bind(listen_addr).await {
+            Ok(listener) => listener,
+            Err(source) => {
+                endpoint.close().await;
+                return Err(TunnelError::Listen {
+                    addr: listen_addr,
+                    source,
+                });
+            }
+        };
+        let local_addr = listener.local_addr().unwrap_or(listen_addr);
+
+        let (status_tx, status) = watch::channel(TunnelStatus::new(addr.id));
+        let (connection_tx, connection) = watch::channel(None);
+
+        let supervisor = tokio::spawn(supervise(
+            endpoint.clone(),
+            addr,
+            options,
+            status_tx,
+            connection_tx,
+        ));
+        let forwarder = tokio::spawn(forward(listener, connection.clone()));
+
+        info!("Forwarding {} through the tunnel", local_addr);
+
+        Ok(Self {
+            endpoint,
+            local_addr,
+            status,
+            connection,
+            supervisor,
+            forwarder,
+        })
+    }
+
+    /// Returns the base URL of the server through the tunnel.
+    ///
+    /// # Panics
+    ///
+    /// Never panics in practice: a socket address always makes a valid URL.
+    #[must_use]
+    pub fn base_url(&self) -> Url {
+        Url::parse(&format!("http://{}", self.local_addr)).expect("Valid URL")
+    }
+
+    /// Returns a handle for watching the tunnel's health.
+    #[must_use]
+    pub fn monitor(&self) -> TunnelMonitor {
+        TunnelMonitor {
+            status: self.status.clone(),
+        }
+    }
+
+    /// Stops forwarding and closes the connection.
+    pub async fn shutdown(self) {
+        info!("Closing tunnel...");
+        self.supervisor.abort();
+        self.forwarder.abort();
+        let connection = self.connection.borrow().clone();
+        if let Some(connection) = connection {
+            connection.close(0u32.into(), b"shutdown");
+        }
+        self.endpoint.close().await;
+        info!("Tunnel closed");
+    }
+}
+
+/// Dials `addr` once.
+async fn dial(
+    endpoint: &Endpoint,
+    addr: EndpointAddr,
+    connect_timeout: Duration,
+) -> Result<Connection, TunnelError> {
+    let endpoint_id = addr.id;
+
+    match timeout(connect_timeout, endpoint.connect(addr, ALPN)).await {
+        Ok(Ok(connection)) => Ok(connection),
+        Ok(Err(e)) => Err(TunnelError::Connect {
+            endpoint_id,
+            reason: e.to_string(),
+        }),
+        Err(_) => Err(TunnelError::ConnectTimeout {
+            endpoint_id,
+            secs: connect_timeout.as_secs(),
+        }),
+    }
+}
+
+/// Keeps a connection to `addr` up, publishing it and its health.
+async fn supervise(
+    endpoint: Endpoint,
+    addr: EndpointAddr,
+    options: TunnelOptions,
+    status: watch::Sender<TunnelStatus>,
+    connection_tx: watch::Sender<Option<Connection>>,
+) {
+    let endpoint_id = addr.id;
+    let mut backoff = options.initial_backoff;
+    let mut was_connected = false;
+
+    loop {
+        info!("Connecting to {}", endpoint_id);
+        status.send_modify(|s| s.next_attempt_at = None);
+
+        match dial(&endpoint, addr.clone(), options.connect_timeout).await {
+            Ok(connection) => {
+                info!("Connected to {}", endpoint_id);
+                backoff = options.initial_backoff;
+                connection_tx.send_replace(Some(connection.clone()));
+                status.send_modify(|s| {
+                    s.state = TunnelState::Connected;
+                    s.connected_at = Some(Instant::now());
+                    s.failed_attempts = 0;
+                    if was_connected {
+                        s.reconnects += 1;
+                    }
+                });
+                was_connected = true;
+
+                let reason = connection.closed().await;
+                warn!("Connection to {} lost: {}", endpoint_id, reason);
+                connection_tx.send_replace(None);
+                status.send_modify(|s| {
+                    s.state = TunnelState::Reconnecting;
+                    s.connected_at = None;
+                    s.last_error = Some(format!("Connection lost: {reason}"));
+                });
+                // Redial right away; a dropped connection is usually a
+                // network change, not the Pi going down
+            }
+            Err(e) => {
+                warn!("{}; retrying in {:?}", e, backoff);
+                status.send_modify(|s| {
+                    if was_connected {
+                        s.state = TunnelState::Reconnecting;
+                    }
+                    s.failed_attempts += 1;
+                    s.last_error = Some(e.to_string());
+                    s.next_attempt_at = Some(Instant::now() + backoff);
+                });
+                tokio::time::sleep(backoff).await;
+                backoff = (backoff * 2).min(options.max_backoff);
+            }
+        }
+    }
+}
+
+/// Forwards each connection accepted on `listener` over its own stream.
+async fn forward(listener: TcpListener, connection: watch::Receiver<Option<Connection>>) {
+    loop {
+        let (tcp, peer) = match listener.accept().await {
+            Ok(accepted) => accepted,
+            Err(e) => {
+                warn!("Failed to accept local connection: {}", e);
+                continue;
+            }
+        };
+
+        // Dropping the socket fails the request now rather than leaving it
+        // hanging until the client times out
+        let Some(connection) = connection.borrow().clone() else {
+            debug!("Refusing {} while disconnected", peer);
+            continue;
+        };
+
+        tokio::spawn(async move {
+            if let Err(e) = forward_stream(tcp, &connection).await {
+                debug!("Forwarding for {} ended: {}", peer, e);
+            }
+        });
+    }
+}
+
+/// Copies bytes between a local TCP connection and a new QUIC stream.
+async fn forward_stream(tcp: TcpStream, connection: &Connection) -> anyhow::Result<()> {
+    let (mut send, mut recv) = connection.open_bi().await?;
+    send.write_all(&HANDSHAKE).await?;
+
+    let (mut tcp_read, mut tcp_write) = tcp.into_split();
+
+    let upload = async {
+        tokio::io::copy(&mut tcp_read, &mut send).await?;
+        send.finish()?;
+        anyhow::Ok(())
+    };
+    let download = async {
+        tokio::io::copy(&mut recv, &mut tcp_write).await?;
+        tcp_write.shutdown().await?;
+        anyhow::Ok(())
+    };
+
+    tokio::try_join!(upload, download)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use iroh::RelayMode;
+    use tokio::io::{AsyncBufReadExt, AsyncReadExt, BufReader};
+
+    const TIMEOUT: Duration = Duration::from_secs(10);
+
+    const FAST: TunnelOptions = TunnelOptions {
+        connect_timeout: Duration::from_secs(1),
+        initial_backoff: Duration::from_millis(50),
+        max_backoff: Duration::from_millis(200),
+    };
+
+    /// Binds an endpoint that only talks to other endpoints on this host.
+    async fn local_endpoint(alpns: Vec<Vec<u8>>) -> Endpoint {
+        Endpoint::builder(presets::Minimal)
+            .relay_mode(RelayMode::Disabled)
+            .alpns(alpns)
+            .bind()
+            .await
+            .unwrap()
+    }
+
+    /// Returns the address of `endpoint` on localhost.
+    fn local_addr_of(endpoint: &Endpoint) -> EndpointAddr {
+        let addrs = endpoint
+            .bound_sockets()
+            .into_iter()
+            .filter(SocketAddr::is_ipv4)
+            .map(|addr| TransportAddr::Ip(SocketAddr::from((Ipv4Addr::LOCALHOST, addr.port()))));
+        EndpointAddr::from_parts(endpoint.id(), addrs)
+    }
+
+    /// Plays the Pi: answers every stream like dumbpipe in front of an
+    /// HTTP server would, after dropping the first `drops` connections.
+    async fn serve_http(endpoint: Endpoint, body: &'static str, mut drops: usize) {
+        while let Some(incoming) = endpoint.accept().await {
+            let Ok(connection) = incoming.await else {
+                continue;
+            };
+            if drops > 0 {
+                drops -= 1;
+                connection.close(1u32.into(), b"restarting");
+                continue;
+            }
+            tokio::spawn(async move {
+                while let Ok((mut send, recv)) = connection.accept_bi().await {
+                    tokio::spawn(async move {
+                        let mut recv = BufReader::new(recv);
+
+                        let mut handshake = [0; HANDSHAKE.len()];
+                        recv.read_exact(&mut handshake).await.unwrap();
+                        assert_eq!(handshake, HANDSHAKE);
+
+                        let mut line = String::new();
+                        loop {
+                            line.clear();
+                            recv.read_line(&mut line).await.unwrap();
+                            if line == "\r\n" || line.is_empty() {
+                                break;
+                            }
+                        }
+
+                        let response = format!(
+                            "HTTP/1.1 200 OK\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{body}",
+                            body.len()
+                        );
+                        send.write_all(response.as_bytes()).await.unwrap();
+                        send.finish().unwrap();
+                        send.stopped().await.ok();
+                    });
+                }
+            });
+        }
+    }
+
+    /// Waits until the tunnel's status satisfies `f`.
+    async fn wait_for_status(tunnel: &Tunnel, f: impl Fn(&TunnelStatus) -> bool) -> TunnelStatus {
+        let mut status = tunnel.status.clone();
+        let status = timeout(TIMEOUT, status.wait_for(|s| f(s)))
+            .await
+            .unwrap()
+            .unwrap();
+        status.clone()
+    }
+
+    async fn get(addr: SocketAddr) -> String {
+        let mut tcp = TcpStream::connect(addr).await.unwrap();
+        tcp.write_all(b"GET /api/passes HTTP/1.1\r\nhost: tether\r\nconnection: close\r\n\r\n")
+            .await
+            .unwrap();
+        let mut response = String::new();
+        tcp.read_to_string(&mut response).await.unwrap();
+        response
+    }
+
+    #[tokio::test]
+    async fn test_parse_ticket() {
+        let endpoint_id = local_endpoint(vec![]).await.id();
+        let relay_url: RelayUrl = "https://relay.example.com".parse().unwrap();
+        let direct: SocketAddr = "192.168.1.20:41641".parse().unwrap();
+
+        let wire = TicketWireFormat::Variant0(Variant0Ticket {
+            addr: Variant0Addr {
+                endpoint_id,
+                info: Variant0AddrInfo {
+                    relay_url: Some(relay_url.clone()),
+                    direct_addresses: BTreeSet::from([direct]),
+                },
+            },
+        });
+        let encoded = data_encoding::BASE32_NOPAD.encode(&postcard::to_stdvec(&wire).unwrap());
+        let ticket = format!("{TICKET_KIND}{}\n", encoded.to_ascii_lowercase());
+
+        let addr = parse_ticket(&ticket).unwrap();
+        assert_eq!(addr.id, endpoint_id);
+        assert_eq!(addr.relay_urls().collect::<Vec<_>>(), vec![&relay_url]);
+        assert_eq!(addr.ip_addrs().collect::<Vec<_>>(), vec![&direct]);
+    }
+
+    #[test]
+    fn test_parse_ticket_rejects_other_kinds() {
+        let result = parse_ticket("blobdownloadaaaa");
+        assert!(matches!(result, Err(TunnelError::InvalidTicket(_))));
+    }
+
+    #[test]
+    fn test_parse_ticket_rejects_garbage() {
+        let result = parse_ticket("endpoint12345");
+        assert!(matches!(result, Err(TunnelError::InvalidTicket(_))));
+    }
+
+    #[tokio::test]
+    async fn test_forwards_http_over_iroh() {
+        let server = local_endpoint(vec![ALPN.to_vec()]).await;
+        let server_addr = local_addr_of(&server);
+        tokio::spawn(serve_http(server, "{\"remaining\":3}", 0));
+
+        let client = local_endpoint(vec![]).await;
+        let tunnel = Tunnel::start(client, server_addr, 0, FAST).await.unwrap();
+        tunnel.monitor().wait_connected(TIMEOUT).await.unwrap();
+
+        // Each request gets its own stream over the one connection
+        for _ in 0..2 {
+            let response = timeout(TIMEOUT, get(tunnel.local_addr)).await.unwrap();
+            assert!(response.starts_with("HTTP/1.1 200 OK"));
+            assert!(response.ends_with("{\"remaining\":3}"));
+        }
+
+        tunnel.shutdown().await;
+    }
+
+    #[tokio::test]
+    async fn test_reconnects_after_connection_lost() {
+        let server = local_endpoint(vec![ALPN.to_vec()]).await;
+        let server_addr = local_addr_of(&server);
+        tokio::spawn(serve_http(server, "ok", 1));
+
+        let client = local_endpoint(vec![]).await;
+        let tunnel = Tunnel::start(client, server_addr, 0, FAST).await.unwrap();
+
+        let status = wait_for_status(&tunnel, |s| s.reconnects == 1).await;
+        assert_eq!(status.state, TunnelState::Connected);
+        assert!(status.last_error.unwrap().contains("Connection lost"));
+
+        let response = timeout(TIMEOUT, get(tunnel.local_addr)).await.unwrap();
+        assert!(response.ends_with("ok"));
+
+        tunnel.shutdown().await;
+    }
+
+    #[tokio::test]
+    async fn test_fails_fast_while_disconnected() {
+        let unreachable = local_endpoint(vec![ALPN.to_vec()]).await;
+        let addr = local_addr_of(&unreachable);
+        unreachable.close().await;
+
+        let client = local_endpoint(vec![]).await;
+        let tunnel = Tunnel::start(client, addr, 0, FAST).await.unwrap();
+
+        wait_for_status(&tunnel, |s| s.failed_attempts >= 1).await;
+        let status = tunnel
+            .monitor()
+            .wait_connected(Duration::from_millis(100))
+            .await
+            .unwrap_err();
+        assert_eq!(status.state, TunnelState::Connecting);
+        assert!(status.last_error.is_some());
+        assert!(
+            status
+                .unavailable_message()
+                .starts_with("The tunnel to the Pi is connecting")
+        );
+
+        // The local port stays open, but requests are refused right away
+        let response = timeout(TIMEOUT, async {
+            let mut tcp = TcpStream::connect(tunnel.local_addr).await.unwrap();
+            let mut response = Vec::new();
+            let _ = tcp.read_to_end(&mut response).await;
+            response
+        })
+        .await
+        .unwrap();
+        assert!(response.is_empty());
+
+        tunnel.shutdown().await;
+    }
+
+    #[tokio::test]
+    async fn test_reports_wrong_alpn() {
+        let server = local_endpoint(vec![b"not-dumbpipe".to_vec()]).await;
+        let server_addr = local_addr_of(&server);
+        let server_id = server.id();
+        tokio::spawn(async move { while server.accept().await.is_some() {} });
+
+        let client = local_endpoint(vec![]).await;
+        let result = dial(&client, server_addr, TIMEOUT).await;
+
+        match result {
+            Err(TunnelError::Connect { endpoint_id, .. }) => assert_eq!(endpoint_id, server_id),
+            other => panic!("expected a connect error, got {:?}", other.err()),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_reports_connect_timeout() {
+        // An endpoint that has shut down, at an address nobody listens on
+        let unreachable = local_endpoint(vec![ALPN.to_vec()]).await;
+        let addr = local_addr_of(&unreachable);
+        unreachable.close().await;
+
+        let client = local_endpoint(vec![]).await;
+        let result = dial(&client, addr, Duration::from_secs(1)).await;
+
+        assert!(matches!(
+            result,
+            Err(TunnelError::ConnectTimeout { secs: 1, .. } | TunnelError::Connect { .. })
+        ));
+    }
+}
@@ -5,6 +5,9 @@
 //! - Checking if a configured device is nearby based on RSSI threshold
 //! - Device discovery for onboarding (listing visible devices)
 //! - Getting raw RSSI values for calibration
+//! - Pairing with phones that rotate their address, so that resolvable
+//!   private addresses can be matched back to the tracked identity
+//! - Actively probing bonded devices that stop advertising when idle
 //!
 //! # Feature Flags
 //!
@@ -23,6 +26,10 @@
 //!     let config = BluetoothConfig {
 //!         device_address: "AA:BB:CC:DD:EE:FF".to_string(),
 //!         rssi_threshold: -70,
+//!         identity_resolving_key: None,
+//!         probe_mode: ProbeMode::Passive,
+//!         trust_connection_without_rssi: false,
+//!         rssi_fusion: RssiFusion::Strongest,
 //!     };
 //!
 //!     let result = scanner.check_proximity(&config).await?;
@@ -38,6 +45,8 @@ use thiserror::Error;
 use tracing::{debug, error, info, instrument, warn};
 use utoipa::ToSchema;
 
+use crate::metrics::{self, ScanOperation};
+
 // ============================================================================
 // ERROR TYPES
 // ============================================================================
@@ -94,6 +103,19 @@ pub enum BluetoothError {
         message: String,
     },
 
+    /// Invalid Identity Resolving Key format.
+    /// Expected 32 hex digits, most-significant byte first.
+    #[error("Invalid Identity Resolving Key: expected 32 hex digits")]
+    InvalidIrk,
+
+    /// Pairing (bonding) with a device failed.
+    /// The phone usually has to confirm the pairing request on screen.
+    #[error("Failed to pair with device: {message}")]
+    PairingFailed {
+        /// Detailed error message.
+        message: String,
+    },
+
     /// A generic internal error occurred.
     #[error("Bluetooth internal error: {message}")]
     Internal {
@@ -127,6 +149,30 @@ pub struct BluetoothConfig {
     /// A device is considered "nearby" if its RSSI >= this threshold.
     #[schema(example = -60)]
     pub rssi_threshold: i16,
+
+    /// Identity Resolving Key of the tracked device, if it has been paired.
+    ///
+    /// When set, devices advertising a resolvable private address that
+    /// resolves with this key are treated as the tracked device.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<String>, example = "ec0234a357c8ad05341010a60a397d9b")]
+    pub identity_resolving_key: Option<IdentityResolvingKey>,
+
+    /// How presence of the device is detected.
+    #[serde(default)]
+    pub probe_mode: ProbeMode,
+
+    /// Whether a connection counts as nearby when the controller reports
+    /// no RSSI for it.
+    ///
+    /// Connections reach into other rooms, so by default a connection only
+    /// counts when its RSSI meets the threshold.
+    #[serde(default)]
+    pub trust_connection_without_rssi: bool,
+
+    /// How readings from several adapters are combined.
+    #[serde(default)]
+    pub rssi_fusion: RssiFusion,
 }
 
 impl BluetoothConfig {
@@ -160,6 +206,322 @@ impl BluetoothConfig {
 
         Ok(())
     }
+
+    /// Returns `true` if `address` belongs to the tracked device.
+    ///
+    /// Matches either the configured address directly (case-insensitive) or,
+    /// when an IRK is configured, a resolvable private address generated
+    /// from it.
+    #[must_use]
+    pub fn matches_address(&self, address: &str) -> bool {
+        if address.eq_ignore_ascii_case(&self.device_address) {
+            return true;
+        }
+        self.identity_resolving_key
+            .as_ref()
+            .is_some_and(|irk| irk.resolves(address))
+    }
+}
+
+/// How a proximity check detects the tracked device.
+///
+/// Many phones stop advertising while the screen is off, so a passive scan
+/// can miss a phone that is right next to the Pi. Bonded devices can still
+/// be reached by opening a short connection to them.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ProbeMode {
+    /// Only listen for advertisements.
+    #[default]
+    Passive,
+    /// Only connect to the device. Requires the device to be paired.
+    Active,
+    /// Listen for advertisements, and connect if none were heard.
+    PassiveThenActive,
+}
+
+impl ProbeMode {
+    /// Returns `true` if this mode listens for advertisements.
+    #[must_use]
+    pub const fn scans(self) -> bool {
+        matches!(self, Self::Passive | Self::PassiveThenActive)
+    }
+
+    /// Returns `true` if this mode may connect to the device.
+    #[must_use]
+    pub const fn connects(self) -> bool {
+        matches!(self, Self::Active | Self::PassiveThenActive)
+    }
+}
+
+/// How RSSI readings from several adapters are combined into one.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RssiFusion {
+    /// Use the strongest reading, i.e. the adapter closest to the device.
+    #[default]
+    Strongest,
+    /// Average the readings in the linear power domain.
+    ///
+    /// Smooths out fading on a single antenna, at the cost of being pulled
+    /// down by adapters placed further away.
+    Average,
+}
+
+impl RssiFusion {
+    /// Combines readings in dBm. Returns `None` if there are no readings.
+    #[must_use]
+    pub fn combine(self, readings: &[i16]) -> Option<i16> {
+        match self {
+            Self::Strongest => readings.iter().copied().max(),
+            Self::Average => {
+                if readings.is_empty() {
+                    return None;
+                }
+                let (sum, count) = readings.iter().fold((0.0, 0.0), |(sum, count), &dbm| {
+                    (sum + 10f64.powf(f64::from(dbm) / 10.0), count + 1.0)
+                });
+                let milliwatts: f64 = sum / count;
+                #[allow(clippy::cast_possible_truncation)]
+                Some((10.0 * milliwatts.log10()).round() as i16)
+            }
+        }
+    }
+}
+
+/// A Bluetooth adapter (controller) known to `bluetoothd`.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct AdapterInfo {
+    /// The adapter's interface name.
+    #[schema(example = "hci0")]
+    pub name: String,
+
+    /// The adapter's Bluetooth address.
+    #[schema(example = "B8:27:EB:12:34:56")]
+    pub address: String,
+
+    /// Whether the adapter is powered on.
+    pub powered: bool,
+
+    /// Whether the scanner is currently using this adapter.
+    pub in_use: bool,
+}
+
+/// How the tracked device was detected during a proximity check.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum DetectionMethod {
+    /// The device was heard advertising during a scan.
+    Advertisement,
+    /// A connection to the device succeeded.
+    Connection,
+}
+
+/// The kind of address a Bluetooth device is advertising with.
+///
+/// Phones with LE privacy enabled advertise a [`Resolvable`](Self::Resolvable)
+/// address that rotates roughly every 15 minutes. Such devices can only be
+/// tracked reliably after pairing, which yields their Identity Resolving Key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum BluetoothAddressType {
+    /// A public (IEEE-assigned) address, including all BR/EDR addresses.
+    Public,
+    /// A random address that does not rotate (static) or cannot be resolved.
+    Random,
+    /// A resolvable private address that rotates periodically.
+    Resolvable,
+}
+
+impl BluetoothAddressType {
+    /// Classifies an address given whether the device reported it as random.
+    ///
+    /// For random LE addresses the two most significant bits of the address
+    /// select the sub-type: `0b01` marks a resolvable private address.
+    #[must_use]
+    pub fn classify(address: &str, is_random: bool) -> Self {
+        if !is_random {
+            return Self::Public;
+        }
+        match parse_address(address) {
+            Some(bytes) if bytes[0] >> 6 == 0b01 => Self::Resolvable,
+            _ => Self::Random,
+        }
+    }
+}
+
+/// A 128-bit Identity Resolving Key (IRK) exchanged during LE pairing.
+///
+/// The key is held most-significant byte first, matching the notation used
+/// by the Bluetooth Core Specification. It is written as 32 hex digits.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct IdentityResolvingKey([u8; 16]);
+
+impl IdentityResolvingKey {
+    /// Creates a key from its bytes, most-significant byte first.
+    #[must_use]
+    pub const fn from_bytes(bytes: [u8; 16]) -> Self {
+        Self(bytes)
+    }
+
+    /// Returns the key bytes, most-significant byte first.
+    #[must_use]
+    pub const fn as_bytes(&self) -> &[u8; 16] {
+        &self.0
+    }
+
+    /// Returns `true` if `address` is a resolvable private address generated
+    /// from this key.
+    ///
+    /// Implements the random address hash function `ah` from the Bluetooth
+    /// Core Specification (Vol 3, Part H, 2.2.2): the upper 24 bits of the
+    /// address (`prand`) are encrypted with the IRK and the low 24 bits of the
+    /// result must equal the lower half of the address.
+    #[must_use]
+    pub fn resolves(&self, address: &str) -> bool {
+        let Some(bytes) = parse_address(address) else {
+            return false;
+        };
+        if bytes[0] >> 6 != 0b01 {
+            return false;
+        }
+
+        self.hash([bytes[0], bytes[1], bytes[2]]) == bytes[3..]
+    }
+
+    /// Generates the resolvable private address for `prand`.
+    ///
+    /// The two most significant bits of `prand` are replaced with `0b01`,
+    /// which marks the address as resolvable.
+    #[must_use]
+    pub fn resolvable_address(&self, mut prand: [u8; 3]) -> String {
+        prand[0] = (prand[0] & 0x3f) | 0x40;
+        let hash = self.hash(prand);
+        prand
+            .iter()
+            .chain(&hash)
+            .map(|byte| format!("{byte:02X}"))
+            .collect::<Vec<_>>()
+            .join(":")
+    }
+
+    /// The random address hash function `ah`, returning the 24-bit hash of
+    /// `prand`.
+    fn hash(&self, prand: [u8; 3]) -> [u8; 3] {
+        use aes::cipher::{BlockEncrypt, KeyInit};
+
+        let cipher = aes::Aes128::new(&self.0.into());
+        let mut block = aes::Block::default();
+        block[13..].copy_from_slice(&prand);
+        cipher.encrypt_block(&mut block);
+
+        [block[13], block[14], block[15]]
+    }
+
+    /// Parses the key `bluetoothd` stores for a bonded device.
+    ///
+    /// `bluetoothd` keeps per-device keys in `/var/lib/bluetooth/<adapter>/<device>/info`
+    /// under the `[IdentityResolvingKey]` group, least-significant byte first.
+    #[must_use]
+    pub fn from_bluez_info(info: &str) -> Option<Self> {
+        let mut in_group = false;
+        for line in info.lines().map(str::trim) {
+            if line.starts_with('[') {
+                in_group = line == "[IdentityResolvingKey]";
+            } else if in_group {
+                if let Some(value) = line.strip_prefix("Key=") {
+                    let mut key: Self = value.trim().parse().ok()?;
+                    key.0.reverse();
+                    return Some(key);
+                }
+            }
+        }
+        None
+    }
+}
+
+impl std::fmt::Debug for IdentityResolvingKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("IdentityResolvingKey(..)")
+    }
+}
+
+impl std::fmt::Display for IdentityResolvingKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for byte in self.0 {
+            write!(f, "{byte:02x}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::str::FromStr for IdentityResolvingKey {
+    type Err = BluetoothError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.len() != 32 || !s.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return Err(BluetoothError::InvalidIrk);
+        }
+        let mut bytes = [0u8; 16];
+        for (i, byte) in bytes.iter_mut().enumerate() {
+            *byte = u8::from_str_radix(&s[i * 2..i * 2 + 2], 16)
+                .map_err(|_| BluetoothError::InvalidIrk)?;
+        }
+        Ok(Self(bytes))
+    }
+}
+
+impl Serialize for IdentityResolvingKey {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for IdentityResolvingKey {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// Decides whether a detected device counts as nearby.
+///
+/// The RSSI must meet the threshold, however the device was detected. A
+/// connection without an RSSI, which many controllers never report, only
+/// counts when `trust_connection` is set.
+const fn is_nearby(
+    detection: Option<DetectionMethod>,
+    rssi: Option<i16>,
+    threshold: i16,
+    trust_connection: bool,
+) -> bool {
+    match (detection, rssi) {
+        (Some(_), Some(rssi)) => rssi >= threshold,
+        (Some(DetectionMethod::Connection), None) => trust_connection,
+        (None, _) | (Some(DetectionMethod::Advertisement), None) => false,
+    }
+}
+
+/// Records the duration and outcome of a proximity check.
+fn record_proximity_check(started: std::time::Instant, result: &BluetoothResult<ProximityResult>) {
+    metrics::record_scan(ScanOperation::Proximity, started, result.is_ok());
+    if let Ok(result) = result {
+        metrics::record_rssi(&result.device_address, result.rssi);
+    }
+}
+
+/// Parses a colon-separated MAC address into bytes, most-significant first.
+fn parse_address(address: &str) -> Option<[u8; 6]> {
+    let mut bytes = [0u8; 6];
+    let mut parts = address.split(':');
+    for byte in &mut bytes {
+        let part = parts.next()?;
+        if part.len() != 2 || !part.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return None;
+        }
+        *byte = u8::from_str_radix(part, 16).ok()?;
+    }
+    parts.next().is_none().then_some(bytes)
 }
 
 /// A discovered Bluetooth device.
@@ -179,6 +541,105 @@ pub struct BluetoothDevice {
     /// This may be `None` if RSSI wasn't available during discovery.
     #[schema(example = -55)]
     pub rssi: Option<i16>,
+
+    /// The kind of address the device is advertising with.
+    pub address_type: BluetoothAddressType,
+
+    /// Whether the device is paired (bonded) with this adapter.
+    pub paired: bool,
+}
+
+/// The outcome of pairing with a device.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct PairedDevice {
+    /// The device's identity address.
+    ///
+    /// For phones using LE privacy this differs from the rotating address
+    /// that was used to initiate pairing.
+    #[schema(example = "AA:BB:CC:DD:EE:FF")]
+    pub identity_address: String,
+
+    /// The human-readable name of the device, if available.
+    #[schema(example = "iPhone")]
+    pub name: Option<String>,
+
+    /// The kind of address the device was advertising with.
+    pub address_type: BluetoothAddressType,
+
+    /// The Identity Resolving Key distributed during pairing, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<String>)]
+    pub identity_resolving_key: Option<IdentityResolvingKey>,
+}
+
+/// A change in the state of the Bluetooth adapter reported by `bluetoothd`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AdapterChange {
+    /// The adapter (re)appeared, e.g. after `bluetoothd` restarted.
+    Added,
+    /// The adapter disappeared.
+    Removed,
+    /// The adapter's power state changed.
+    Powered(bool),
+}
+
+/// Receives changes to the state of the scanner's adapter.
+///
+/// Obtained from `BluetoothScanner::monitor_adapter`. The monitor does not
+/// borrow the scanner, so it can be awaited without holding any lock on it.
+#[derive(Debug)]
+pub struct AdapterMonitor {
+    changes: tokio::sync::mpsc::Receiver<AdapterChange>,
+}
+
+impl AdapterMonitor {
+    /// Channel capacity between the event source and the monitor.
+    const CAPACITY: usize = 16;
+
+    /// Creates a monitor and the sender that feeds it.
+    fn channel() -> (tokio::sync::mpsc::Sender<AdapterChange>, Self) {
+        let (tx, changes) = tokio::sync::mpsc::channel(Self::CAPACITY);
+        (tx, Self { changes })
+    }
+
+    /// Waits for the next adapter change.
+    ///
+    /// Returns `None` once `bluetoothd` stops reporting changes, which
+    /// usually means the connection to it was lost.
+    pub async fn next(&mut self) -> Option<AdapterChange> {
+        self.changes.recv().await
+    }
+}
+
+/// Receives devices from an ongoing discovery scan.
+///
+/// Obtained from `BluetoothScanner::watch_devices`. A device is delivered
+/// when it is first seen and again whenever its RSSI changes. Dropping the
+/// watch stops the scan early.
+#[derive(Debug)]
+pub struct DeviceWatch {
+    updates: tokio::sync::mpsc::Receiver<BluetoothDevice>,
+}
+
+impl DeviceWatch {
+    /// Channel capacity between the scan and the watch.
+    const CAPACITY: usize = 64;
+
+    /// How often the RSSI of devices already seen is re-read.
+    const REFRESH_INTERVAL: Duration = Duration::from_secs(1);
+
+    /// Creates a watch and the sender that feeds it.
+    fn channel() -> (tokio::sync::mpsc::Sender<BluetoothDevice>, Self) {
+        let (tx, updates) = tokio::sync::mpsc::channel(Self::CAPACITY);
+        (tx, Self { updates })
+    }
+
+    /// Waits for the next new or updated device.
+    ///
+    /// Returns `None` once the scan duration has elapsed.
+    pub async fn next(&mut self) -> Option<BluetoothDevice> {
+        self.updates.recv().await
+    }
 }
 
 /// Result of a proximity check.
@@ -189,8 +650,13 @@ pub struct ProximityResult {
 
     /// The actual RSSI value if the device was found.
     /// `None` if the device was not detected during the scan.
+    /// For devices detected over a connection this is the connection RSSI,
+    /// which the controller may not report.
     pub rssi: Option<i16>,
 
+    /// How the device was detected. `None` if it was not detected.
+    pub detection_method: Option<DetectionMethod>,
+
     /// The device name if available.
     pub device_name: Option<String>,
 
@@ -208,10 +674,16 @@ pub struct ProximityResult {
 #[cfg(all(feature = "bluetooth", not(feature = "mock-bluetooth")))]
 mod real_impl {
     use super::*;
-    use bluer::{Adapter, AdapterEvent, Address, DiscoveryFilter, DiscoveryTransport, Session};
+    use bluer::agent::Agent;
+    use bluer::{
+        Adapter, AdapterEvent, AdapterProperty, Address, AddressType, Device, DiscoveryFilter,
+        DiscoveryTransport, Session, SessionEvent,
+    };
     use futures::StreamExt;
+    use once_cell::sync::Lazy;
     use std::collections::HashMap;
     use std::str::FromStr;
+    use std::sync::Arc;
     use tokio::sync::Mutex;
     use tokio::time::{timeout, Instant};
 
@@ -226,13 +698,24 @@ mod real_impl {
     /// using `Arc<BluetoothScanner>`.
     pub struct BluetoothScanner {
         /// The BlueZ session handle.
-        _session: Session,
-        /// The default Bluetooth adapter.
-        adapter: Adapter,
+        session: Session,
+        /// The adapters used for scanning, never empty.
+        ///
+        /// The first adapter is the primary one, used for pairing and active
+        /// probes.
+        adapters: Vec<Adapter>,
         /// Mutex to prevent concurrent scans (BlueZ doesn't support this well).
-        scan_lock: Mutex<()>,
+        ///
+        /// Always [`SCAN_LOCK`], so a scan still running on a scanner that
+        /// has been replaced keeps the new one waiting.
+        scan_lock: Arc<Mutex<()>>,
     }
 
+    /// The scan lock shared by every scanner in the process.
+    ///
+    /// An `Arc` so that background scans can hold it after returning.
+    static SCAN_LOCK: Lazy<Arc<Mutex<()>>> = Lazy::new(|| Arc::new(Mutex::new(())));
+
     impl BluetoothScanner {
         /// Default scan duration in seconds for proximity checks.
         const DEFAULT_SCAN_DURATION_SECS: u64 = 3;
@@ -240,6 +723,24 @@ mod real_impl {
         /// Maximum scan duration to prevent indefinite hangs.
         const MAX_SCAN_DURATION_SECS: u64 = 30;
 
+        /// Maximum duration of a streaming scan, which the user watches.
+        const MAX_WATCH_DURATION_SECS: u64 = 120;
+
+        /// How long a streaming scan holds the scan lock at a time.
+        const WATCH_WINDOW_SECS: u64 = 10;
+
+        /// How long to wait for the phone to accept a pairing request.
+        const PAIRING_TIMEOUT_SECS: u64 = 30;
+
+        /// Directory where BlueZ persists bonding keys.
+        const BLUEZ_STORAGE_DIR: &'static str = "/var/lib/bluetooth";
+
+        /// How long an active probe waits for a connection to a bonded device.
+        const ACTIVE_PROBE_TIMEOUT_SECS: u64 = 5;
+
+        /// How long the adapter stays off while being power-cycled.
+        const POWER_CYCLE_DELAY_SECS: u64 = 2;
+
         /// Creates a new Bluetooth scanner.
         ///
         /// This initializes a connection to the BlueZ daemon and obtains
@@ -250,8 +751,24 @@ mod real_impl {
         /// - `BluetoothError::SessionInitFailed`: BlueZ daemon not running
         /// - `BluetoothError::AdapterNotFound`: No Bluetooth adapter available
         /// - `BluetoothError::AdapterPoweredOff`: Adapter exists but is off
-        #[instrument(name = "bluetooth_scanner_new")]
         pub async fn new() -> BluetoothResult<Self> {
+            Self::with_adapters(&[]).await
+        }
+
+        /// Creates a Bluetooth scanner using the named adapters (e.g. `hci1`).
+        ///
+        /// Scans run on all adapters at once. The first adapter is used for
+        /// pairing and active probes. An empty list selects the default
+        /// adapter.
+        ///
+        /// # Errors
+        ///
+        /// - `BluetoothError::SessionInitFailed`: BlueZ daemon not running
+        /// - `BluetoothError::AdapterNotFound`: An adapter does not exist
+        /// - `BluetoothError::AdapterPoweredOff`: An adapter is off and could
+        ///   not be powered on
+        #[instrument(name = "bluetooth_scanner_new")]
+        pub async fn with_adapters(names: &[String]) -> BluetoothResult<Self> {
             info!("Initializing Bluetooth scanner");
 
             // Create session to BlueZ daemon
@@ -262,22 +779,53 @@ mod real_impl {
                 }
             })?;
 
-            // Get default adapter
-            let adapter = session.default_adapter().await.map_err(|e| {
-                error!("Failed to get default adapter: {}", e);
-                BluetoothError::AdapterNotFound
-            })?;
+            let adapters = if names.is_empty() {
+                // Get default adapter
+                let adapter = session.default_adapter().await.map_err(|e| {
+                    error!("Failed to get default adapter: {}", e);
+                    BluetoothError::AdapterNotFound
+                })?;
+                vec![adapter]
+            } else {
+                names
+                    .iter()
+                    .map(|name| {
+                        session.adapter(name).map_err(|e| {
+                            error!("Failed to get adapter {}: {}", name, e);
+                            BluetoothError::AdapterNotFound
+                        })
+                    })
+                    .collect::<BluetoothResult<Vec<_>>>()?
+            };
+
+            for adapter in &adapters {
+                Self::prepare_adapter(adapter).await?;
+            }
+
+            Ok(Self {
+                session,
+                adapters,
+                scan_lock: Arc::clone(&SCAN_LOCK),
+            })
+        }
 
-            // Check if adapter is powered
+        /// Ensures an adapter exists and is powered on.
+        async fn prepare_adapter(adapter: &Adapter) -> BluetoothResult<()> {
+            // Check if adapter is powered; this also fails if it does not exist
             let is_powered = adapter.is_powered().await.map_err(|e| {
-                error!("Failed to check adapter power state: {}", e);
-                BluetoothError::Internal {
-                    message: format!("Failed to check adapter power: {}", e),
-                }
+                error!(
+                    "Failed to check power state of adapter {}: {}",
+                    adapter.name(),
+                    e
+                );
+                BluetoothError::AdapterNotFound
             })?;
 
             if !is_powered {
-                warn!("Bluetooth adapter is powered off, attempting to power on");
+                warn!(
+                    "Bluetooth adapter {} is powered off, attempting to power on",
+                    adapter.name()
+                );
                 adapter.set_powered(true).await.map_err(|e| {
                     error!("Failed to power on adapter: {}", e);
                     BluetoothError::AdapterPoweredOff
@@ -293,15 +841,50 @@ mod real_impl {
                 })?;
 
             info!(
+                adapter = %adapter.name(),
                 adapter_address = %adapter_addr,
-                "Bluetooth scanner initialized successfully"
+                "Bluetooth adapter initialized successfully"
             );
 
-            Ok(Self {
-                _session: session,
-                adapter,
-                scan_lock: Mutex::new(()),
-            })
+            Ok(())
+        }
+
+        /// Returns the primary adapter.
+        fn primary(&self) -> &Adapter {
+            &self.adapters[0]
+        }
+
+        /// Lists all adapters known to BlueZ.
+        ///
+        /// # Errors
+        ///
+        /// - `BluetoothError::Internal`: Failed to query BlueZ
+        pub async fn list_adapters(&self) -> BluetoothResult<Vec<AdapterInfo>> {
+            let names =
+                self.session
+                    .adapter_names()
+                    .await
+                    .map_err(|e| BluetoothError::Internal {
+                        message: format!("Failed to list adapters: {e}"),
+                    })?;
+
+            let mut adapters = Vec::with_capacity(names.len());
+            for name in names {
+                let Ok(adapter) = self.session.adapter(&name) else {
+                    continue;
+                };
+                let Ok(address) = adapter.address().await else {
+                    continue;
+                };
+                adapters.push(AdapterInfo {
+                    in_use: self.adapters.iter().any(|a| a.name() == name),
+                    address: address.to_string(),
+                    powered: adapter.is_powered().await.unwrap_or(false),
+                    name,
+                });
+            }
+
+            Ok(adapters)
         }
 
         /// Checks if a configured device is nearby based on RSSI threshold.
@@ -309,16 +892,21 @@ mod real_impl {
         pub async fn check_proximity(
             &self,
             config: &BluetoothConfig,
+        ) -> BluetoothResult<ProximityResult> {
+            let started = std::time::Instant::now();
+            let result = self.probe_proximity(config).await;
+            record_proximity_check(started, &result);
+            result
+        }
+
+        /// Scans for the configured device, then tries connecting to it.
+        async fn probe_proximity(
+            &self,
+            config: &BluetoothConfig,
         ) -> BluetoothResult<ProximityResult> {
             // Validate configuration
             config.validate()?;
 
-            let target_address = Address::from_str(&config.device_address).map_err(|_| {
-                BluetoothError::InvalidAddress {
-                    address: config.device_address.clone(),
-                }
-            })?;
-
             info!("Checking proximity for device {}", config.device_address);
 
             // Acquire scan lock to prevent concurrent scans
@@ -329,29 +917,49 @@ mod real_impl {
                 .unwrap_or_default()
                 .as_secs();
 
-            // Try to get device info from a quick scan
-            let scan_result = self
-                .scan_for_device(
-                    &target_address,
-                    Duration::from_secs(Self::DEFAULT_SCAN_DURATION_SECS),
-                )
-                .await?;
+            let mut detection_method = None;
+            let mut rssi = None;
+            let mut device_name = None;
 
-            let (rssi, device_name) = match scan_result {
-                Some((rssi, name)) => (Some(rssi), name),
-                None => {
+            // Try to get device info from a quick scan
+            if config.probe_mode.scans() {
+                let scan_result = self
+                    .scan_all_for_device(
+                        config,
+                        Duration::from_secs(Self::DEFAULT_SCAN_DURATION_SECS),
+                    )
+                    .await?;
+
+                if let Some((scan_rssi, name)) = scan_result {
+                    detection_method = Some(DetectionMethod::Advertisement);
+                    rssi = Some(scan_rssi);
+                    device_name = name;
+                } else {
                     // Device not found during scan
                     debug!("Device {} not found during scan", config.device_address);
-                    (None, None)
                 }
-            };
+            }
+
+            // Fall back to connecting if the device is not advertising
+            if detection_method.is_none() && config.probe_mode.connects() {
+                if let Some((connection_rssi, name)) = self.probe_connection(config).await? {
+                    detection_method = Some(DetectionMethod::Connection);
+                    rssi = connection_rssi;
+                    device_name = name;
+                }
+            }
 
-            // Determine if nearby based on RSSI threshold
-            let nearby = rssi.map(|r| r >= config.rssi_threshold).unwrap_or(false);
+            let nearby = is_nearby(
+                detection_method,
+                rssi,
+                config.rssi_threshold,
+                config.trust_connection_without_rssi,
+            );
 
             let result = ProximityResult {
                 nearby,
                 rssi,
+                detection_method,
                 device_name,
                 device_address: config.device_address.clone(),
                 timestamp,
@@ -360,6 +968,7 @@ mod real_impl {
             info!(
                 nearby = result.nearby,
                 rssi = ?result.rssi,
+                detection_method = ?result.detection_method,
                 device_name = ?result.device_name,
                 "Proximity check complete"
             );
@@ -373,6 +982,14 @@ mod real_impl {
             &self,
             duration_secs: u64,
         ) -> BluetoothResult<Vec<BluetoothDevice>> {
+            let started = std::time::Instant::now();
+            let result = self.discover_all(duration_secs).await;
+            metrics::record_scan(ScanOperation::Discovery, started, result.is_ok());
+            result
+        }
+
+        /// Runs discovery on every adapter and merges the results.
+        async fn discover_all(&self, duration_secs: u64) -> BluetoothResult<Vec<BluetoothDevice>> {
             let duration_secs = duration_secs.min(Self::MAX_SCAN_DURATION_SECS);
             let duration = Duration::from_secs(duration_secs);
 
@@ -381,6 +998,217 @@ mod real_impl {
             // Acquire scan lock
             let _lock = self.scan_lock.lock().await;
 
+            let results = futures::future::join_all(
+                self.adapters
+                    .iter()
+                    .map(|adapter| Self::discover_on(adapter, duration)),
+            )
+            .await;
+
+            // Merge per-adapter results, keeping the strongest reading
+            let mut devices: HashMap<Address, BluetoothDevice> = HashMap::new();
+            let mut last_error = None;
+            let mut any_succeeded = false;
+            for result in results {
+                match result {
+                    Ok(found) => {
+                        any_succeeded = true;
+                        for (addr, device) in found {
+                            match devices.get(&addr) {
+                                Some(existing) if existing.rssi >= device.rssi => {}
+                                _ => {
+                                    devices.insert(addr, device);
+                                }
+                            }
+                        }
+                    }
+                    Err(e) => {
+                        warn!(error = %e, "Discovery failed on one adapter");
+                        last_error = Some(e);
+                    }
+                }
+            }
+            if let (false, Some(e)) = (any_succeeded, last_error) {
+                return Err(e);
+            }
+
+            let result: Vec<BluetoothDevice> = devices.into_values().collect();
+
+            info!(device_count = result.len(), "Device discovery complete");
+
+            Ok(result)
+        }
+
+        /// Starts a discovery scan that reports devices as they are found.
+        ///
+        /// The scan runs in the background for `duration_secs` (capped at
+        /// two minutes) or until the returned watch is dropped. It holds the
+        /// scan lock for one window of discovery at a time, so proximity
+        /// checks wait for at most one window instead of the whole scan.
+        ///
+        /// # Errors
+        ///
+        /// - `BluetoothError::DiscoveryFailed`: No adapter could start discovery
+        #[instrument(skip(self), fields(duration_secs))]
+        pub async fn watch_devices(&self, duration_secs: u64) -> BluetoothResult<DeviceWatch> {
+            let duration_secs = duration_secs.min(Self::MAX_WATCH_DURATION_SECS);
+
+            info!(
+                "Starting streaming device discovery for {} seconds",
+                duration_secs
+            );
+
+            let scan_lock = Arc::clone(&self.scan_lock);
+            let mut lock = Arc::clone(&scan_lock).lock_owned().await;
+            let mut streams = Self::start_watch_discovery(&self.adapters)
+                .await
+                .map_err(|e| BluetoothError::DiscoveryFailed {
+                    message: e.to_string(),
+                })?;
+            let adapters = self.adapters.clone();
+
+            let (tx, watch) = DeviceWatch::channel();
+            tokio::spawn(async move {
+                // Each device with the handles of the adapters that saw it
+                let mut seen: HashMap<Address, (Vec<Device>, BluetoothDevice)> = HashMap::new();
+                let deadline = Instant::now() + Duration::from_secs(duration_secs);
+                let mut refresh = tokio::time::interval(DeviceWatch::REFRESH_INTERVAL);
+
+                'watch: loop {
+                    let window = tokio::time::sleep_until(
+                        deadline.min(Instant::now() + Duration::from_secs(Self::WATCH_WINDOW_SECS)),
+                    );
+                    tokio::pin!(window);
+                    let mut events = futures::stream::select_all(streams);
+
+                    loop {
+                        let updates = tokio::select! {
+                            () = tx.closed() => break 'watch,
+                            () = &mut window => break,
+                            event = events.next() => match event {
+                                Some((adapter, AdapterEvent::DeviceAdded(addr))) => {
+                                    let Ok(device) = adapter.device(addr) else {
+                                        continue;
+                                    };
+                                    let described = Self::describe_device(&device).await;
+                                    // Discovery reports known devices again when it restarts
+                                    if let Some((handles, current)) = seen.get_mut(&addr) {
+                                        if !handles
+                                            .iter()
+                                            .any(|d| d.adapter_name() == device.adapter_name())
+                                        {
+                                            handles.push(device);
+                                        }
+                                        if current.rssi >= described.rssi {
+                                            continue;
+                                        }
+                                        current.rssi = described.rssi;
+                                        vec![current.clone()]
+                                    } else {
+                                        seen.insert(addr, (vec![device], described.clone()));
+                                        vec![described]
+                                    }
+                                }
+                                Some(_) => continue,
+                                None => break 'watch,
+                            },
+                            _ = refresh.tick() => {
+                                let mut updates = Vec::new();
+                                for (handles, current) in seen.values_mut() {
+                                    let mut rssi = None;
+                                    for device in handles.iter() {
+                                        rssi = rssi.max(device.rssi().await.ok().flatten());
+                                    }
+                                    if rssi.is_some() && rssi != current.rssi {
+                                        current.rssi = rssi;
+                                        updates.push(current.clone());
+                                    }
+                                }
+                                updates
+                            }
+                        };
+
+                        for device in updates {
+                            if tx.send(device).await.is_err() {
+                                break 'watch;
+                            }
+                        }
+                    }
+
+                    // Stop discovery so proximity checks waiting for the lock can run
+                    drop(events);
+                    drop(lock);
+                    if Instant::now() >= deadline {
+                        break;
+                    }
+
+                    lock = tokio::select! {
+                        () = tx.closed() => break,
+                        lock = Arc::clone(&scan_lock).lock_owned() => lock,
+                    };
+                    streams = match Self::start_watch_discovery(&adapters).await {
+                        Ok(streams) => streams,
+                        Err(e) => {
+                            warn!(error = %e, "Failed to restart streaming device discovery");
+                            break;
+                        }
+                    };
+                }
+
+                info!(
+                    device_count = seen.len(),
+                    "Streaming device discovery complete"
+                );
+            });
+
+            Ok(watch)
+        }
+
+        /// Starts discovery on every adapter for a streaming scan.
+        ///
+        /// Fails only if no adapter could start discovery.
+        async fn start_watch_discovery(
+            adapters: &[Adapter],
+        ) -> bluer::Result<Vec<futures::stream::BoxStream<'static, (Adapter, AdapterEvent)>>>
+        {
+            // Duplicate data keeps BlueZ updating RSSI while the scan runs
+            let filter = DiscoveryFilter {
+                transport: DiscoveryTransport::Auto,
+                duplicate_data: true,
+                ..Default::default()
+            };
+
+            let mut streams: Vec<futures::stream::BoxStream<'static, (Adapter, AdapterEvent)>> =
+                Vec::new();
+            let mut last_error = None;
+            for adapter in adapters {
+                let started = match adapter.set_discovery_filter(filter.clone()).await {
+                    Ok(()) => adapter.discover_devices().await,
+                    Err(e) => Err(e),
+                };
+                match started {
+                    Ok(events) => {
+                        let adapter = adapter.clone();
+                        streams.push(Box::pin(events.map(move |event| (adapter.clone(), event))));
+                    }
+                    Err(e) => {
+                        warn!(adapter = %adapter.name(), error = %e, "Failed to start discovery");
+                        last_error = Some(e);
+                    }
+                }
+            }
+
+            match last_error {
+                Some(e) if streams.is_empty() => Err(e),
+                _ => Ok(streams),
+            }
+        }
+
+        /// Discovers devices on a single adapter.
+        async fn discover_on(
+            adapter: &Adapter,
+            duration: Duration,
+        ) -> BluetoothResult<HashMap<Address, BluetoothDevice>> {
             // Set up discovery filter for all devices (BR/EDR and LE)
             let filter = DiscoveryFilter {
                 transport: DiscoveryTransport::Auto,
@@ -388,18 +1216,15 @@ mod real_impl {
                 ..Default::default()
             };
 
-            self.adapter
-                .set_discovery_filter(filter)
-                .await
-                .map_err(|e| {
-                    error!("Failed to set discovery filter: {}", e);
-                    BluetoothError::DiscoveryFailed {
-                        message: e.to_string(),
-                    }
-                })?;
+            adapter.set_discovery_filter(filter).await.map_err(|e| {
+                error!("Failed to set discovery filter: {}", e);
+                BluetoothError::DiscoveryFailed {
+                    message: e.to_string(),
+                }
+            })?;
 
             // Start discovery
-            let events = self.adapter.discover_devices().await.map_err(|e| {
+            let events = adapter.discover_devices().await.map_err(|e| {
                 error!("Failed to start device discovery: {}", e);
                 BluetoothError::DiscoveryFailed {
                     message: e.to_string(),
@@ -421,16 +1246,8 @@ mod real_impl {
                 match timeout(remaining, events.next()).await {
                     Ok(Some(event)) => {
                         if let AdapterEvent::DeviceAdded(addr) = event {
-                            if let Ok(device) = self.adapter.device(addr) {
-                                // Query device properties
-                                let name = device.name().await.ok().flatten();
-                                let rssi = device.rssi().await.ok().flatten();
-
-                                let bt_device = BluetoothDevice {
-                                    address: addr.to_string(),
-                                    name,
-                                    rssi,
-                                };
+                            if let Ok(device) = adapter.device(addr) {
+                                let bt_device = Self::describe_device(&device).await;
 
                                 debug!(
                                     address = %addr,
@@ -454,21 +1271,22 @@ mod real_impl {
                 }
             }
 
-            let result: Vec<BluetoothDevice> = devices.into_values().collect();
-
-            info!(device_count = result.len(), "Device discovery complete");
-
-            Ok(result)
+            Ok(devices)
         }
 
         /// Gets the current RSSI value for a specific device.
         #[instrument(skip(self), fields(address = %address))]
         pub async fn get_device_rssi(&self, address: &str) -> BluetoothResult<Option<i16>> {
             // Validate address format
-            let target_address =
-                Address::from_str(address).map_err(|_| BluetoothError::InvalidAddress {
-                    address: address.to_string(),
-                })?;
+            let target = BluetoothConfig {
+                device_address: address.to_string(),
+                rssi_threshold: -100,
+                identity_resolving_key: None,
+                probe_mode: ProbeMode::Passive,
+                trust_connection_without_rssi: false,
+                rssi_fusion: RssiFusion::Strongest,
+            };
+            target.validate()?;
 
             debug!("Getting RSSI for device {}", address);
 
@@ -477,8 +1295,8 @@ mod real_impl {
 
             // Quick scan to find the device
             let result = self
-                .scan_for_device(
-                    &target_address,
+                .scan_all_for_device(
+                    &target,
                     Duration::from_secs(Self::DEFAULT_SCAN_DURATION_SECS),
                 )
                 .await?;
@@ -490,10 +1308,258 @@ mod real_impl {
             Ok(rssi)
         }
 
-        /// Internal helper to scan for a specific device.
-        async fn scan_for_device(
+        /// Pairs with a device so that its rotating addresses can be resolved.
+        ///
+        /// Registers a no-input/no-output agent for the duration of the
+        /// request, so the phone only has to confirm the pairing prompt. Once
+        /// bonded, the device is marked as trusted and its Identity Resolving
+        /// Key is read from BlueZ's key store.
+        ///
+        /// # Errors
+        ///
+        /// - `BluetoothError::InvalidAddress`: The address is malformed
+        /// - `BluetoothError::DeviceNotFound`: BlueZ has not seen the device
+        /// - `BluetoothError::PairingFailed`: Pairing was rejected or timed out
+        #[instrument(skip(self), fields(address = %address))]
+        pub async fn pair_device(&self, address: &str) -> BluetoothResult<PairedDevice> {
+            let target =
+                Address::from_str(address).map_err(|_| BluetoothError::InvalidAddress {
+                    address: address.to_string(),
+                })?;
+
+            let _lock = self.scan_lock.lock().await;
+
+            let device = self
+                .primary()
+                .device(target)
+                .map_err(|e| BluetoothError::Internal {
+                    message: e.to_string(),
+                })?;
+            let described = Self::describe_device(&device).await;
+
+            let paired = device
+                .is_paired()
+                .await
+                .map_err(|_| BluetoothError::DeviceNotFound {
+                    address: address.to_string(),
+                })?;
+
+            if !paired {
+                info!("Pairing with device {}", address);
+
+                let _agent = self
+                    .session
+                    .register_agent(Agent::default())
+                    .await
+                    .map_err(|e| BluetoothError::PairingFailed {
+                        message: format!("Failed to register pairing agent: {e}"),
+                    })?;
+
+                timeout(
+                    Duration::from_secs(Self::PAIRING_TIMEOUT_SECS),
+                    device.pair(),
+                )
+                .await
+                .map_err(|_| BluetoothError::PairingFailed {
+                    message: format!(
+                        "Timed out after {} seconds waiting for the device to accept",
+                        Self::PAIRING_TIMEOUT_SECS
+                    ),
+                })?
+                .map_err(|e| BluetoothError::PairingFailed {
+                    message: e.to_string(),
+                })?;
+            }
+
+            device
+                .set_trusted(true)
+                .await
+                .map_err(|e| BluetoothError::PairingFailed {
+                    message: format!("Failed to trust device: {e}"),
+                })?;
+
+            // After bonding BlueZ reports the identity address instead of the
+            // private address that was used to connect.
+            let identity = device.remote_address().await.unwrap_or(target);
+            let identity_resolving_key = self.read_identity_resolving_key(identity).await;
+
+            if identity_resolving_key.is_none() {
+                warn!(
+                    identity = %identity,
+                    "No Identity Resolving Key stored for device; rotating addresses cannot be resolved"
+                );
+            }
+
+            info!(identity = %identity, "Device paired");
+
+            Ok(PairedDevice {
+                identity_address: identity.to_string(),
+                name: described.name,
+                address_type: described.address_type,
+                identity_resolving_key,
+            })
+        }
+
+        /// Probes a bonded device by briefly connecting to it.
+        ///
+        /// Returns the connection RSSI (if the controller reports one) and
+        /// name when the device is reachable, or `None` if it is not paired or
+        /// the connection fails. An existing connection is reused and left
+        /// open; a connection opened by the probe is closed again.
+        async fn probe_connection(
             &self,
-            target_address: &Address,
+            config: &BluetoothConfig,
+        ) -> BluetoothResult<Option<(Option<i16>, Option<String>)>> {
+            let address = Address::from_str(&config.device_address).map_err(|_| {
+                BluetoothError::InvalidAddress {
+                    address: config.device_address.clone(),
+                }
+            })?;
+
+            let device = self
+                .primary()
+                .device(address)
+                .map_err(|e| BluetoothError::Internal {
+                    message: e.to_string(),
+                })?;
+
+            // Only bonded devices accept connections without user interaction
+            if !device.is_paired().await.unwrap_or(false) {
+                warn!(
+                    "Skipping active probe: device {} is not paired",
+                    config.device_address
+                );
+                return Ok(None);
+            }
+
+            let was_connected = device.is_connected().await.unwrap_or(false);
+            if !was_connected {
+                debug!("Connecting to {} for active probe", config.device_address);
+                match timeout(
+                    Duration::from_secs(Self::ACTIVE_PROBE_TIMEOUT_SECS),
+                    device.connect(),
+                )
+                .await
+                {
+                    Ok(Ok(())) => {}
+                    Ok(Err(e)) => {
+                        debug!(error = %e, "Active probe connection failed");
+                        return Ok(None);
+                    }
+                    Err(_) => {
+                        debug!(
+                            "Active probe timed out after {} seconds",
+                            Self::ACTIVE_PROBE_TIMEOUT_SECS
+                        );
+                        return Ok(None);
+                    }
+                }
+            }
+
+            let rssi = device.rssi().await.ok().flatten();
+            let name = device.name().await.ok().flatten();
+
+            if !was_connected {
+                if let Err(e) = device.disconnect().await {
+                    debug!(error = %e, "Failed to disconnect after active probe");
+                }
+            }
+
+            Ok(Some((rssi, name)))
+        }
+
+        /// Reads the IRK BlueZ stored for a bonded device, if any.
+        async fn read_identity_resolving_key(
+            &self,
+            identity: Address,
+        ) -> Option<IdentityResolvingKey> {
+            let adapter_address = self.primary().address().await.ok()?;
+            let path = std::path::Path::new(Self::BLUEZ_STORAGE_DIR)
+                .join(adapter_address.to_string())
+                .join(identity.to_string())
+                .join("info");
+
+            match tokio::fs::read_to_string(&path).await {
+                Ok(info) => IdentityResolvingKey::from_bluez_info(&info),
+                Err(e) => {
+                    warn!(path = %path.display(), error = %e, "Failed to read BlueZ device info");
+                    None
+                }
+            }
+        }
+
+        /// Queries the properties of a device known to BlueZ.
+        async fn describe_device(device: &Device) -> BluetoothDevice {
+            let address = device.address().to_string();
+            let name = device.name().await.ok().flatten();
+            let rssi = device.rssi().await.ok().flatten();
+            let is_random = matches!(device.address_type().await, Ok(AddressType::LeRandom));
+            let paired = device.is_paired().await.unwrap_or(false);
+
+            BluetoothDevice {
+                address_type: BluetoothAddressType::classify(&address, is_random),
+                address,
+                name,
+                rssi,
+                paired,
+            }
+        }
+
+        /// Scans for a specific device on all adapters at once.
+        ///
+        /// Readings from the adapters that saw the device are combined
+        /// according to the target's `rssi_fusion`. Fails only if every
+        /// adapter failed.
+        async fn scan_all_for_device(
+            &self,
+            target: &BluetoothConfig,
+            duration: Duration,
+        ) -> BluetoothResult<Option<(i16, Option<String>)>> {
+            let results = futures::future::join_all(
+                self.adapters
+                    .iter()
+                    .map(|adapter| Self::scan_for_device(adapter, target, duration)),
+            )
+            .await;
+
+            let mut readings = Vec::new();
+            let mut device_name = None;
+            let mut last_error = None;
+            let mut any_succeeded = false;
+            for result in results {
+                match result {
+                    Ok(found) => {
+                        any_succeeded = true;
+                        if let Some((rssi, name)) = found {
+                            readings.push(rssi);
+                            device_name = device_name.or(name);
+                        }
+                    }
+                    Err(e) => {
+                        warn!(error = %e, "Scan failed on one adapter");
+                        last_error = Some(e);
+                    }
+                }
+            }
+            if let (false, Some(e)) = (any_succeeded, last_error) {
+                return Err(e);
+            }
+
+            debug!(readings = ?readings, fusion = ?target.rssi_fusion, "Combining RSSI readings");
+
+            Ok(target
+                .rssi_fusion
+                .combine(&readings)
+                .map(|rssi| (rssi, device_name)))
+        }
+
+        /// Internal helper to scan for a specific device on one adapter.
+        ///
+        /// A device matches if its address equals the target address or
+        /// resolves with the target's Identity Resolving Key.
+        async fn scan_for_device(
+            adapter: &Adapter,
+            target: &BluetoothConfig,
             duration: Duration,
         ) -> BluetoothResult<Option<(i16, Option<String>)>> {
             // Set up discovery filter
@@ -503,20 +1569,21 @@ mod real_impl {
                 ..Default::default()
             };
 
-            self.adapter
-                .set_discovery_filter(filter)
-                .await
-                .map_err(|e| BluetoothError::DiscoveryFailed {
-                    message: e.to_string(),
-                })?;
-
-            // Start discovery
-            let events = self.adapter.discover_devices().await.map_err(|e| {
+            adapter.set_discovery_filter(filter).await.map_err(|e| {
                 BluetoothError::DiscoveryFailed {
                     message: e.to_string(),
                 }
             })?;
 
+            // Start discovery
+            let events =
+                adapter
+                    .discover_devices()
+                    .await
+                    .map_err(|e| BluetoothError::DiscoveryFailed {
+                        message: e.to_string(),
+                    })?;
+
             let start = Instant::now();
             let mut found_device: Option<(i16, Option<String>)> = None;
 
@@ -531,8 +1598,8 @@ mod real_impl {
                 match timeout(remaining, events.next()).await {
                     Ok(Some(event)) => {
                         if let AdapterEvent::DeviceAdded(addr) = event {
-                            if addr == *target_address {
-                                if let Ok(device) = self.adapter.device(addr) {
+                            if target.matches_address(&addr.to_string()) {
+                                if let Ok(device) = adapter.device(addr) {
                                     if let Ok(Some(rssi)) = device.rssi().await {
                                         let name = device.name().await.ok().flatten();
                                         debug!(rssi = rssi, name = ?name, "Found target device");
@@ -549,10 +1616,17 @@ mod real_impl {
 
             // Also check if we already know about this device
             if found_device.is_none() {
-                if let Ok(device) = self.adapter.device(*target_address) {
-                    if let Ok(Some(rssi)) = device.rssi().await {
-                        let name = device.name().await.ok().flatten();
-                        found_device = Some((rssi, name));
+                let known = adapter.device_addresses().await.unwrap_or_default();
+                for addr in known {
+                    if !target.matches_address(&addr.to_string()) {
+                        continue;
+                    }
+                    if let Ok(device) = adapter.device(addr) {
+                        if let Ok(Some(rssi)) = device.rssi().await {
+                            let name = device.name().await.ok().flatten();
+                            found_device = Some((rssi, name));
+                            break;
+                        }
                     }
                 }
             }
@@ -560,20 +1634,26 @@ mod real_impl {
             Ok(found_device)
         }
 
-        /// Checks if the Bluetooth adapter is powered on.
+        /// Checks if the Bluetooth adapters are all powered on.
         pub async fn is_adapter_powered(&self) -> BluetoothResult<bool> {
-            self.adapter
-                .is_powered()
-                .await
-                .map_err(|e| BluetoothError::Internal {
-                    message: format!("Failed to check adapter power: {}", e),
-                })
+            for adapter in &self.adapters {
+                let powered = adapter
+                    .is_powered()
+                    .await
+                    .map_err(|e| BluetoothError::Internal {
+                        message: format!("Failed to check adapter power: {}", e),
+                    })?;
+                if !powered {
+                    return Ok(false);
+                }
+            }
+            Ok(true)
         }
 
-        /// Gets the adapter's Bluetooth address.
+        /// Gets the primary adapter's Bluetooth address.
         pub async fn adapter_address(&self) -> BluetoothResult<String> {
             let addr = self
-                .adapter
+                .primary()
                 .address()
                 .await
                 .map_err(|e| BluetoothError::Internal {
@@ -581,6 +1661,103 @@ mod real_impl {
                 })?;
             Ok(addr.to_string())
         }
+
+        /// Starts monitoring the adapters for removal and power changes.
+        ///
+        /// # Errors
+        ///
+        /// - `BluetoothError::Internal`: Failed to subscribe to BlueZ events
+        pub async fn monitor_adapter(&self) -> BluetoothResult<AdapterMonitor> {
+            let subscribe_failed = |e: bluer::Error| BluetoothError::Internal {
+                message: format!("Failed to subscribe to adapter events: {e}"),
+            };
+            let session_events = self.session.events().await.map_err(subscribe_failed)?;
+
+            let names: Vec<String> = self.adapters.iter().map(|a| a.name().to_string()).collect();
+            let session_changes = session_events.filter_map(move |event| {
+                let change = match event {
+                    SessionEvent::AdapterAdded(added) if names.contains(&added) => {
+                        Some(AdapterChange::Added)
+                    }
+                    SessionEvent::AdapterRemoved(removed) if names.contains(&removed) => {
+                        Some(AdapterChange::Removed)
+                    }
+                    _ => None,
+                };
+                std::future::ready(change)
+            });
+
+            let mut streams: Vec<futures::stream::BoxStream<'static, AdapterChange>> =
+                vec![Box::pin(session_changes)];
+            for adapter in &self.adapters {
+                let adapter_events = adapter.events().await.map_err(subscribe_failed)?;
+                streams.push(Box::pin(adapter_events.filter_map(|event| {
+                    let change = match event {
+                        AdapterEvent::PropertyChanged(AdapterProperty::Powered(powered)) => {
+                            Some(AdapterChange::Powered(powered))
+                        }
+                        _ => None,
+                    };
+                    std::future::ready(change)
+                })));
+            }
+
+            let (tx, monitor) = AdapterMonitor::channel();
+            tokio::spawn(async move {
+                let mut changes = futures::stream::select_all(streams);
+                loop {
+                    tokio::select! {
+                        () = tx.closed() => break,
+                        change = changes.next() => match change {
+                            Some(change) => {
+                                if tx.send(change).await.is_err() {
+                                    break;
+                                }
+                            }
+                            None => break,
+                        },
+                    }
+                }
+            });
+
+            Ok(monitor)
+        }
+
+        /// Turns the adapters off and on again.
+        ///
+        /// This recovers adapters whose controller has wedged while BlueZ
+        /// still reports them as present.
+        ///
+        /// # Errors
+        ///
+        /// - `BluetoothError::Internal`: An adapter could not be powered off
+        /// - `BluetoothError::AdapterPoweredOff`: An adapter did not power on again
+        #[instrument(skip(self))]
+        pub async fn power_cycle_adapter(&self) -> BluetoothResult<()> {
+            let _lock = self.scan_lock.lock().await;
+
+            for adapter in &self.adapters {
+                warn!(adapter = %adapter.name(), "Power-cycling Bluetooth adapter");
+
+                adapter
+                    .set_powered(false)
+                    .await
+                    .map_err(|e| BluetoothError::Internal {
+                        message: format!("Failed to power off adapter: {e}"),
+                    })?;
+            }
+
+            tokio::time::sleep(Duration::from_secs(Self::POWER_CYCLE_DELAY_SECS)).await;
+
+            for adapter in &self.adapters {
+                adapter.set_powered(true).await.map_err(|e| {
+                    error!("Failed to power on adapter: {}", e);
+                    BluetoothError::AdapterPoweredOff
+                })?;
+            }
+
+            Ok(())
+        }
     }
 }
 
@@ -590,24 +1767,80 @@ mod real_impl {
 
 #[cfg(any(feature = "mock-bluetooth", not(feature = "bluetooth")))]
 mod mock_impl {
-    use super::*;
+    use super::{
+        is_nearby, metrics, parse_address, record_proximity_check, AdapterChange, AdapterInfo,
+        AdapterMonitor, BluetoothAddressType, BluetoothConfig, BluetoothDevice, BluetoothError,
+        BluetoothResult, DetectionMethod, DeviceWatch, IdentityResolvingKey, PairedDevice,
+        ProbeMode, ProximityResult, RssiFusion, ScanOperation,
+    };
     use std::collections::HashMap;
+    use std::sync::atomic::{AtomicU32, Ordering};
     use std::sync::Arc;
+    use std::time::Duration;
     use tokio::sync::RwLock;
+    use tracing::{debug, info, instrument};
 
     /// Mock device configuration for testing.
     #[derive(Debug, Clone)]
     pub struct MockDevice {
-        /// The MAC address.
+        /// The identity address. Devices with an IRK advertise a resolvable
+        /// private address generated from it instead.
         pub address: String,
         /// The device name.
         pub name: Option<String>,
         /// The RSSI value.
         pub rssi: Option<i16>,
-        /// Whether the device is visible.
+        /// Whether the device is visible (in range).
         pub is_visible: bool,
+        /// Whether the device is advertising, and so found by passive scans.
+        pub is_advertising: bool,
+        /// Whether the device has been paired.
+        pub paired: bool,
+        /// The IRK handed over when pairing, for devices with rotating addresses.
+        pub identity_resolving_key: Option<IdentityResolvingKey>,
     }
 
+    impl MockDevice {
+        /// Returns the address the device advertises with.
+        ///
+        /// Devices with an IRK advertise a resolvable private address that
+        /// changes with each `rotation`; others their identity address.
+        fn advertised_address(&self, rotation: u32) -> String {
+            let Some(irk) = self.identity_resolving_key else {
+                return self.address.clone();
+            };
+            let identity = parse_address(&self.address).unwrap_or_default();
+            let [_, r0, r1, r2] = rotation.to_be_bytes();
+            irk.resolvable_address([identity[3] ^ r0, identity[4] ^ r1, identity[5] ^ r2])
+        }
+
+        /// Returns `true` if `address` is the device's identity address or
+        /// the address it currently advertises with.
+        fn is_known_as(&self, address: &str, rotation: u32) -> bool {
+            self.address.eq_ignore_ascii_case(address)
+                || self
+                    .advertised_address(rotation)
+                    .eq_ignore_ascii_case(address)
+        }
+
+        /// Describes the device as discovery reports it.
+        fn discovered(&self, rotation: u32) -> BluetoothDevice {
+            let address = self.advertised_address(rotation);
+            let is_random = self.identity_resolving_key.is_some();
+            BluetoothDevice {
+                address_type: BluetoothAddressType::classify(&address, is_random),
+                address,
+                name: self.name.clone(),
+                rssi: self.rssi,
+                paired: self.paired,
+            }
+        }
+    }
+
+    /// Mock adapters as (name, address) pairs.
+    const MOCK_ADAPTERS: [(&str, &str); 2] =
+        [("hci0", "00:00:00:00:00:00"), ("hci1", "00:00:00:00:00:01")];
+
     /// Mock Bluetooth scanner for local development and testing.
     pub struct BluetoothScanner {
         /// Mock devices keyed by address.
@@ -616,6 +1849,14 @@ mod mock_impl {
         scan_delay_ms: u64,
         /// Whether the adapter is "powered on".
         is_powered: Arc<RwLock<bool>>,
+        /// Whether the adapter is "present".
+        is_present: Arc<RwLock<bool>>,
+        /// Broadcasts adapter changes to monitors.
+        changes: tokio::sync::broadcast::Sender<AdapterChange>,
+        /// Names of the mock adapters in use.
+        adapter_names: Vec<String>,
+        /// Bumped to rotate the addresses of devices with an IRK.
+        address_rotation: Arc<AtomicU32>,
     }
 
     impl BluetoothScanner {
@@ -634,6 +1875,9 @@ mod mock_impl {
                     name: Some("Test iPhone".to_string()),
                     rssi: Some(-55),
                     is_visible: true,
+                    is_advertising: true,
+                    paired: false,
+                    identity_resolving_key: None,
                 },
             );
 
@@ -644,6 +1888,9 @@ mod mock_impl {
                     name: Some("Test Android".to_string()),
                     rssi: Some(-72),
                     is_visible: true,
+                    is_advertising: true,
+                    paired: false,
+                    identity_resolving_key: None,
                 },
             );
 
@@ -654,6 +1901,9 @@ mod mock_impl {
                     name: None,
                     rssi: Some(-85),
                     is_visible: true,
+                    is_advertising: true,
+                    paired: false,
+                    identity_resolving_key: None,
                 },
             );
 
@@ -661,9 +1911,60 @@ mod mock_impl {
                 mock_devices: Arc::new(RwLock::new(mock_devices)),
                 scan_delay_ms: 100,
                 is_powered: Arc::new(RwLock::new(true)),
+                is_present: Arc::new(RwLock::new(true)),
+                changes: tokio::sync::broadcast::channel(16).0,
+                adapter_names: vec![MOCK_ADAPTERS[0].0.to_string()],
+                address_rotation: Arc::new(AtomicU32::new(0)),
             })
         }
 
+        /// Sets how long each simulated scan takes.
+        #[must_use]
+        pub fn with_scan_delay(mut self, delay: Duration) -> Self {
+            self.scan_delay_ms = u64::try_from(delay.as_millis()).unwrap_or(u64::MAX);
+            self
+        }
+
+        /// Creates a mock scanner using the named mock adapters.
+        ///
+        /// The mock knows `hci0` and `hci1`.
+        ///
+        /// # Errors
+        ///
+        /// - `BluetoothError::AdapterNotFound`: A name is not a mock adapter
+        pub async fn with_adapters(names: &[String]) -> BluetoothResult<Self> {
+            if names
+                .iter()
+                .any(|name| !MOCK_ADAPTERS.iter().any(|(known, _)| known == name))
+            {
+                return Err(BluetoothError::AdapterNotFound);
+            }
+
+            let mut scanner = Self::new().await?;
+            if !names.is_empty() {
+                scanner.adapter_names = names.to_vec();
+            }
+            Ok(scanner)
+        }
+
+        /// Lists the mock adapters.
+        ///
+        /// # Errors
+        ///
+        /// Never fails; returns a `Result` to match the real scanner.
+        pub async fn list_adapters(&self) -> BluetoothResult<Vec<AdapterInfo>> {
+            let powered = *self.is_powered.read().await;
+            Ok(MOCK_ADAPTERS
+                .iter()
+                .map(|(name, address)| AdapterInfo {
+                    name: (*name).to_string(),
+                    address: (*address).to_string(),
+                    powered,
+                    in_use: self.adapter_names.iter().any(|n| n == name),
+                })
+                .collect())
+        }
+
         /// Adds a mock device for testing.
         pub async fn add_mock_device(&self, device: MockDevice) {
             let mut devices = self.mock_devices.write().await;
@@ -692,9 +1993,51 @@ mod mock_impl {
             }
         }
 
+        /// Sets whether a mock device is advertising.
+        pub async fn set_mock_device_advertising(&self, address: &str, is_advertising: bool) {
+            let mut devices = self.mock_devices.write().await;
+            if let Some(device) = devices.get_mut(address) {
+                device.is_advertising = is_advertising;
+            }
+        }
+
+        /// Rotates the resolvable private addresses of devices with an IRK,
+        /// as phones do about every 15 minutes.
+        pub fn rotate_private_addresses(&self) {
+            self.address_rotation.fetch_add(1, Ordering::Relaxed);
+        }
+
+        /// Returns the current address rotation.
+        fn rotation(&self) -> u32 {
+            self.address_rotation.load(Ordering::Relaxed)
+        }
+
         /// Sets the mock adapter power state.
         pub async fn set_adapter_powered(&self, powered: bool) {
             *self.is_powered.write().await = powered;
+            let _ = self.changes.send(AdapterChange::Powered(powered));
+        }
+
+        /// Simulates the adapter disappearing or reappearing.
+        pub async fn set_adapter_present(&self, present: bool) {
+            *self.is_present.write().await = present;
+            let change = if present {
+                AdapterChange::Added
+            } else {
+                AdapterChange::Removed
+            };
+            let _ = self.changes.send(change);
+        }
+
+        /// Fails unless the mock adapter is present and powered on.
+        async fn ensure_adapter_ready(&self) -> BluetoothResult<()> {
+            if !*self.is_present.read().await {
+                return Err(BluetoothError::AdapterNotFound);
+            }
+            if !*self.is_powered.read().await {
+                return Err(BluetoothError::AdapterPoweredOff);
+            }
+            Ok(())
         }
 
         /// Checks proximity for a configured device (mock implementation).
@@ -702,13 +2045,22 @@ mod mock_impl {
         pub async fn check_proximity(
             &self,
             config: &BluetoothConfig,
+        ) -> BluetoothResult<ProximityResult> {
+            let started = std::time::Instant::now();
+            let result = self.probe_proximity(config).await;
+            record_proximity_check(started, &result);
+            result
+        }
+
+        /// Looks the configured device up among the mock devices.
+        async fn probe_proximity(
+            &self,
+            config: &BluetoothConfig,
         ) -> BluetoothResult<ProximityResult> {
             config.validate()?;
 
             // Check if adapter is "powered"
-            if !*self.is_powered.read().await {
-                return Err(BluetoothError::AdapterPoweredOff);
-            }
+            self.ensure_adapter_ready().await?;
 
             info!(
                 "[MOCK] Checking proximity for device {}",
@@ -724,25 +2076,38 @@ mod mock_impl {
                 .as_secs();
 
             let devices = self.mock_devices.read().await;
+            let rotation = self.rotation();
 
-            // Normalize address to uppercase for comparison
-            let normalized_address = config.device_address.to_uppercase();
+            // Match on the identity address or on a resolvable private address
+            let device = devices
+                .values()
+                .find(|d| d.is_visible && config.matches_address(&d.advertised_address(rotation)));
 
-            let (rssi, device_name) = if let Some(device) = devices.get(&normalized_address) {
-                if device.is_visible {
-                    (device.rssi, device.name.clone())
+            let detection_method = device.and_then(|d| {
+                if config.probe_mode.scans() && d.is_advertising {
+                    Some(DetectionMethod::Advertisement)
+                } else if config.probe_mode.connects() && d.paired {
+                    Some(DetectionMethod::Connection)
                 } else {
-                    (None, None)
+                    None
                 }
-            } else {
-                (None, None)
-            };
+            });
+
+            let (rssi, device_name) = device
+                .filter(|_| detection_method.is_some())
+                .map_or((None, None), |d| (d.rssi, d.name.clone()));
 
-            let nearby = rssi.map(|r| r >= config.rssi_threshold).unwrap_or(false);
+            let nearby = is_nearby(
+                detection_method,
+                rssi,
+                config.rssi_threshold,
+                config.trust_connection_without_rssi,
+            );
 
             let result = ProximityResult {
                 nearby,
                 rssi,
+                detection_method,
                 device_name,
                 device_address: config.device_address.clone(),
                 timestamp,
@@ -763,10 +2128,16 @@ mod mock_impl {
             &self,
             duration_secs: u64,
         ) -> BluetoothResult<Vec<BluetoothDevice>> {
+            let started = std::time::Instant::now();
+            let result = self.discover_all(duration_secs).await;
+            metrics::record_scan(ScanOperation::Discovery, started, result.is_ok());
+            result
+        }
+
+        /// Lists the visible, advertising mock devices.
+        async fn discover_all(&self, duration_secs: u64) -> BluetoothResult<Vec<BluetoothDevice>> {
             // Check if adapter is "powered"
-            if !*self.is_powered.read().await {
-                return Err(BluetoothError::AdapterPoweredOff);
-            }
+            self.ensure_adapter_ready().await?;
 
             info!("[MOCK] Discovering devices for {} seconds", duration_secs);
 
@@ -775,14 +2146,11 @@ mod mock_impl {
             tokio::time::sleep(Duration::from_millis(delay)).await;
 
             let devices = self.mock_devices.read().await;
+            let rotation = self.rotation();
             let result: Vec<BluetoothDevice> = devices
                 .values()
-                .filter(|d| d.is_visible)
-                .map(|d| BluetoothDevice {
-                    address: d.address.clone(),
-                    name: d.name.clone(),
-                    rssi: d.rssi,
-                })
+                .filter(|d| d.is_visible && d.is_advertising)
+                .map(|d| d.discovered(rotation))
                 .collect();
 
             info!(device_count = result.len(), "[MOCK] Discovery complete");
@@ -790,6 +2158,56 @@ mod mock_impl {
             Ok(result)
         }
 
+        /// Streams visible mock devices, then their RSSI changes.
+        ///
+        /// # Errors
+        ///
+        /// - `BluetoothError::AdapterNotFound`: The mock adapter was removed
+        /// - `BluetoothError::AdapterPoweredOff`: The mock adapter is off
+        #[instrument(skip(self), fields(duration_secs))]
+        pub async fn watch_devices(&self, duration_secs: u64) -> BluetoothResult<DeviceWatch> {
+            self.ensure_adapter_ready().await?;
+
+            info!("[MOCK] Streaming devices for {} seconds", duration_secs);
+
+            let mock_devices = Arc::clone(&self.mock_devices);
+            let address_rotation = Arc::clone(&self.address_rotation);
+            let (tx, watch) = DeviceWatch::channel();
+            tokio::spawn(async move {
+                let mut reported: HashMap<String, Option<i16>> = HashMap::new();
+                let deadline = tokio::time::sleep(Duration::from_secs(duration_secs));
+                tokio::pin!(deadline);
+                let mut refresh = tokio::time::interval(DeviceWatch::REFRESH_INTERVAL);
+
+                loop {
+                    tokio::select! {
+                        () = tx.closed() => break,
+                        () = &mut deadline => break,
+                        _ = refresh.tick() => {}
+                    }
+
+                    let rotation = address_rotation.load(Ordering::Relaxed);
+                    let updates: Vec<BluetoothDevice> = mock_devices
+                        .read()
+                        .await
+                        .values()
+                        .filter(|d| d.is_visible && d.is_advertising)
+                        .map(|d| d.discovered(rotation))
+                        .filter(|d| reported.get(&d.address) != Some(&d.rssi))
+                        .collect();
+
+                    for device in updates {
+                        reported.insert(device.address.clone(), device.rssi);
+                        if tx.send(device).await.is_err() {
+                            return;
+                        }
+                    }
+                }
+            });
+
+            Ok(watch)
+        }
+
         /// Gets the RSSI for a specific mock device.
         #[instrument(skip(self), fields(address = %address))]
         pub async fn get_device_rssi(&self, address: &str) -> BluetoothResult<Option<i16>> {
@@ -797,23 +2215,26 @@ mod mock_impl {
             let config = BluetoothConfig {
                 device_address: address.to_string(),
                 rssi_threshold: -100,
+                identity_resolving_key: None,
+                probe_mode: ProbeMode::Passive,
+                trust_connection_without_rssi: false,
+                rssi_fusion: RssiFusion::Strongest,
             };
             config.validate()?;
 
             // Check if adapter is "powered"
-            if !*self.is_powered.read().await {
-                return Err(BluetoothError::AdapterPoweredOff);
-            }
+            self.ensure_adapter_ready().await?;
 
             // Simulate scan delay
             tokio::time::sleep(Duration::from_millis(self.scan_delay_ms)).await;
 
             let devices = self.mock_devices.read().await;
-            let normalized = address.to_uppercase();
+            let rotation = self.rotation();
 
             let rssi = devices
-                .get(&normalized)
-                .filter(|d| d.is_visible)
+                .values()
+                .find(|d| d.is_known_as(address, rotation))
+                .filter(|d| d.is_visible && d.is_advertising)
                 .and_then(|d| d.rssi);
 
             debug!(rssi = ?rssi, "[MOCK] RSSI query complete");
@@ -821,8 +2242,58 @@ mod mock_impl {
             Ok(rssi)
         }
 
+        /// Pairs with a mock device (mock implementation).
+        ///
+        /// Succeeds for any visible device, found by its identity address or
+        /// the address it advertises with, and returns the IRK configured on
+        /// the mock device, if any.
+        ///
+        /// # Errors
+        ///
+        /// - `BluetoothError::InvalidAddress`: The address is malformed
+        /// - `BluetoothError::DeviceNotFound`: No visible mock device has the address
+        /// - `BluetoothError::AdapterPoweredOff`: The mock adapter is off
+        #[instrument(skip(self), fields(address = %address))]
+        pub async fn pair_device(&self, address: &str) -> BluetoothResult<PairedDevice> {
+            let config = BluetoothConfig {
+                device_address: address.to_string(),
+                rssi_threshold: -100,
+                identity_resolving_key: None,
+                probe_mode: ProbeMode::Passive,
+                trust_connection_without_rssi: false,
+                rssi_fusion: RssiFusion::Strongest,
+            };
+            config.validate()?;
+
+            self.ensure_adapter_ready().await?;
+
+            let rotation = self.rotation();
+            let mut devices = self.mock_devices.write().await;
+            let device = devices
+                .values_mut()
+                .find(|d| d.is_visible && d.is_known_as(address, rotation))
+                .ok_or_else(|| BluetoothError::DeviceNotFound {
+                    address: address.to_string(),
+                })?;
+            device.paired = true;
+            let paired = PairedDevice {
+                identity_address: device.address.clone(),
+                name: device.name.clone(),
+                address_type: device.discovered(rotation).address_type,
+                identity_resolving_key: device.identity_resolving_key,
+            };
+            drop(devices);
+
+            info!("[MOCK] Paired with device {}", paired.identity_address);
+
+            Ok(paired)
+        }
+
         /// Checks if the mock adapter is "powered on".
         pub async fn is_adapter_powered(&self) -> BluetoothResult<bool> {
+            if !*self.is_present.read().await {
+                return Err(BluetoothError::AdapterNotFound);
+            }
             Ok(*self.is_powered.read().await)
         }
 
@@ -830,6 +2301,40 @@ mod mock_impl {
         pub async fn adapter_address(&self) -> BluetoothResult<String> {
             Ok("00:00:00:00:00:00".to_string())
         }
+
+        /// Starts monitoring the mock adapter for changes.
+        ///
+        /// # Errors
+        ///
+        /// Never fails; returns a `Result` to match the real scanner.
+        #[allow(clippy::unused_async, clippy::unused_async_trait_impl)] // Same signature as the real scanner
+        pub async fn monitor_adapter(&self) -> BluetoothResult<AdapterMonitor> {
+            let mut changes = self.changes.subscribe();
+            let (tx, monitor) = AdapterMonitor::channel();
+            tokio::spawn(async move {
+                while let Ok(change) = changes.recv().await {
+                    if tx.send(change).await.is_err() {
+                        break;
+                    }
+                }
+            });
+            Ok(monitor)
+        }
+
+        /// Power-cycles the mock adapter.
+        ///
+        /// # Errors
+        ///
+        /// - `BluetoothError::AdapterNotFound`: The mock adapter was removed
+        pub async fn power_cycle_adapter(&self) -> BluetoothResult<()> {
+            if !*self.is_present.read().await {
+                return Err(BluetoothError::AdapterNotFound);
+            }
+            info!("[MOCK] Power-cycling adapter");
+            self.set_adapter_powered(false).await;
+            self.set_adapter_powered(true).await;
+            Ok(())
+        }
     }
 }
 
@@ -856,6 +2361,10 @@ mod tests {
         let config = BluetoothConfig {
             device_address: "AA:BB:CC:DD:EE:FF".to_string(),
             rssi_threshold: -70,
+            identity_resolving_key: None,
+            probe_mode: ProbeMode::Passive,
+            trust_connection_without_rssi: false,
+            rssi_fusion: RssiFusion::Strongest,
         };
         assert!(config.validate().is_ok());
     }
@@ -865,6 +2374,10 @@ mod tests {
         let config = BluetoothConfig {
             device_address: "aa:bb:cc:dd:ee:ff".to_string(),
             rssi_threshold: -70,
+            identity_resolving_key: None,
+            probe_mode: ProbeMode::Passive,
+            trust_connection_without_rssi: false,
+            rssi_fusion: RssiFusion::Strongest,
         };
         assert!(config.validate().is_ok());
     }
@@ -874,6 +2387,10 @@ mod tests {
         let config = BluetoothConfig {
             device_address: "AA:BB:CC".to_string(),
             rssi_threshold: -70,
+            identity_resolving_key: None,
+            probe_mode: ProbeMode::Passive,
+            trust_connection_without_rssi: false,
+            rssi_fusion: RssiFusion::Strongest,
         };
         assert!(matches!(
             config.validate(),
@@ -886,6 +2403,10 @@ mod tests {
         let config = BluetoothConfig {
             device_address: "AABBCCDDEEFF".to_string(),
             rssi_threshold: -70,
+            identity_resolving_key: None,
+            probe_mode: ProbeMode::Passive,
+            trust_connection_without_rssi: false,
+            rssi_fusion: RssiFusion::Strongest,
         };
         assert!(matches!(
             config.validate(),
@@ -898,6 +2419,10 @@ mod tests {
         let config = BluetoothConfig {
             device_address: "GG:HH:II:JJ:KK:LL".to_string(),
             rssi_threshold: -70,
+            identity_resolving_key: None,
+            probe_mode: ProbeMode::Passive,
+            trust_connection_without_rssi: false,
+            rssi_fusion: RssiFusion::Strongest,
         };
         assert!(matches!(
             config.validate(),
@@ -913,6 +2438,10 @@ mod tests {
         let config = BluetoothConfig {
             device_address: "AA:BB:CC:DD:EE:FF".to_string(),
             rssi_threshold: -60,
+            identity_resolving_key: None,
+            probe_mode: ProbeMode::Passive,
+            trust_connection_without_rssi: false,
+            rssi_fusion: RssiFusion::Strongest,
         };
 
         let result = scanner.check_proximity(&config).await.unwrap();
@@ -929,6 +2458,10 @@ mod tests {
         let config = BluetoothConfig {
             device_address: "AA:BB:CC:DD:EE:FF".to_string(),
             rssi_threshold: -50,
+            identity_resolving_key: None,
+            probe_mode: ProbeMode::Passive,
+            trust_connection_without_rssi: false,
+            rssi_fusion: RssiFusion::Strongest,
         };
 
         let result = scanner.check_proximity(&config).await.unwrap();
@@ -944,6 +2477,10 @@ mod tests {
         let config = BluetoothConfig {
             device_address: "99:99:99:99:99:99".to_string(),
             rssi_threshold: -70,
+            identity_resolving_key: None,
+            probe_mode: ProbeMode::Passive,
+            trust_connection_without_rssi: false,
+            rssi_fusion: RssiFusion::Strongest,
         };
 
         let result = scanner.check_proximity(&config).await.unwrap();
@@ -972,6 +2509,10 @@ mod tests {
         let config = BluetoothConfig {
             device_address: "AA:BB:CC:DD:EE:FF".to_string(),
             rssi_threshold: -70,
+            identity_resolving_key: None,
+            probe_mode: ProbeMode::Passive,
+            trust_connection_without_rssi: false,
+            rssi_fusion: RssiFusion::Strongest,
         };
 
         let result = scanner.check_proximity(&config).await.unwrap();
@@ -991,9 +2532,360 @@ mod tests {
         let config = BluetoothConfig {
             device_address: "AA:BB:CC:DD:EE:FF".to_string(),
             rssi_threshold: -70,
+            identity_resolving_key: None,
+            probe_mode: ProbeMode::Passive,
+            trust_connection_without_rssi: false,
+            rssi_fusion: RssiFusion::Strongest,
         };
 
         let result = scanner.check_proximity(&config).await;
         assert!(matches!(result, Err(BluetoothError::AdapterPoweredOff)));
     }
+
+    // Sample data for `ah` from the Bluetooth Core Specification, Vol 3, Part H, D.7.
+    const SPEC_IRK: &str = "ec0234a357c8ad05341010a60a397d9b";
+    const SPEC_RPA: &str = "70:81:94:0D:FB:AA";
+
+    #[test]
+    fn test_irk_resolves_spec_sample() {
+        let irk: IdentityResolvingKey = SPEC_IRK.parse().unwrap();
+        assert!(irk.resolves(SPEC_RPA));
+        assert!(irk.resolves(&SPEC_RPA.to_lowercase()));
+        assert!(!irk.resolves("70:81:94:0D:FB:AB"));
+        // Top bits are not 0b01, so this is not a resolvable address
+        assert!(!irk.resolves("F0:81:94:0D:FB:AA"));
+        assert!(!irk.resolves("not an address"));
+        assert!(!irk.resolves("70:81:94:+D:FB:AA"));
+    }
+
+    #[test]
+    fn test_irk_resolvable_address() {
+        let irk: IdentityResolvingKey = SPEC_IRK.parse().unwrap();
+        assert_eq!(irk.resolvable_address([0x70, 0x81, 0x94]), SPEC_RPA);
+
+        // The top bits of prand are forced to mark the address resolvable
+        let address = irk.resolvable_address([0xff, 0x00, 0x01]);
+        assert!(address.starts_with("7F:00:01:"));
+        assert!(irk.resolves(&address));
+    }
+
+    #[test]
+    fn test_irk_parse_and_display() {
+        let irk: IdentityResolvingKey = SPEC_IRK.parse().unwrap();
+        assert_eq!(irk.to_string(), SPEC_IRK);
+        assert_eq!(format!("{irk:?}"), "IdentityResolvingKey(..)");
+        assert!(matches!(
+            "ec02".parse::<IdentityResolvingKey>(),
+            Err(BluetoothError::InvalidIrk)
+        ));
+        assert!(matches!(
+            "zz0234a357c8ad05341010a60a397d9b".parse::<IdentityResolvingKey>(),
+            Err(BluetoothError::InvalidIrk)
+        ));
+        // from_str_radix alone would accept a sign
+        assert!(matches!(
+            "+c0234a357c8ad05341010a60a397d9b".parse::<IdentityResolvingKey>(),
+            Err(BluetoothError::InvalidIrk)
+        ));
+    }
+
+    #[test]
+    fn test_irk_serde_round_trip() {
+        let irk: IdentityResolvingKey = SPEC_IRK.parse().unwrap();
+        let json = serde_json::to_string(&irk).unwrap();
+        assert_eq!(json, format!("\"{SPEC_IRK}\""));
+        let back: IdentityResolvingKey = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, irk);
+    }
+
+    #[test]
+    fn test_irk_from_bluez_info() {
+        let info = "[General]\nName=iPhone\n\n[IdentityResolvingKey]\nKey=9B7D390AA610103405ADC857A33402EC\n\n[LongTermKey]\nKey=00112233445566778899AABBCCDDEEFF\n";
+        let irk = IdentityResolvingKey::from_bluez_info(info).unwrap();
+        assert_eq!(irk.to_string(), SPEC_IRK);
+
+        assert!(IdentityResolvingKey::from_bluez_info("[General]\nName=iPhone\n").is_none());
+    }
+
+    #[test]
+    fn test_address_type_classify() {
+        assert_eq!(
+            BluetoothAddressType::classify(SPEC_RPA, false),
+            BluetoothAddressType::Public
+        );
+        assert_eq!(
+            BluetoothAddressType::classify(SPEC_RPA, true),
+            BluetoothAddressType::Resolvable
+        );
+        assert_eq!(
+            BluetoothAddressType::classify("C0:11:22:33:44:55", true),
+            BluetoothAddressType::Random
+        );
+    }
+
+    #[test]
+    fn test_config_matches_resolvable_address() {
+        let mut config = BluetoothConfig {
+            device_address: "AA:BB:CC:DD:EE:FF".to_string(),
+            rssi_threshold: -70,
+            identity_resolving_key: None,
+            probe_mode: ProbeMode::Passive,
+            trust_connection_without_rssi: false,
+            rssi_fusion: RssiFusion::Strongest,
+        };
+        assert!(config.matches_address("aa:bb:cc:dd:ee:ff"));
+        assert!(!config.matches_address(SPEC_RPA));
+
+        config.identity_resolving_key = Some(SPEC_IRK.parse().unwrap());
+        assert!(config.matches_address(SPEC_RPA));
+    }
+
+    #[tokio::test]
+    #[cfg(any(feature = "mock-bluetooth", not(feature = "bluetooth")))]
+    async fn test_mock_scanner_pair_and_track_rotating_address() {
+        const IDENTITY: &str = "AA:BB:CC:DD:EE:01";
+
+        /// Returns the address the private iPhone is advertising with.
+        async fn advertised(scanner: &BluetoothScanner) -> BluetoothDevice {
+            let devices = scanner.discover_devices(1).await.unwrap();
+            devices
+                .into_iter()
+                .find(|d| d.name.as_deref() == Some("Private iPhone"))
+                .unwrap()
+        }
+
+        let scanner = BluetoothScanner::new().await.unwrap();
+        let irk: IdentityResolvingKey = SPEC_IRK.parse().unwrap();
+
+        scanner
+            .add_mock_device(MockDevice {
+                address: IDENTITY.to_string(),
+                name: Some("Private iPhone".to_string()),
+                rssi: Some(-50),
+                is_visible: true,
+                is_advertising: true,
+                paired: false,
+                identity_resolving_key: Some(irk),
+            })
+            .await;
+
+        // Before pairing, only a resolvable private address is seen
+        let device = advertised(&scanner).await;
+        assert_eq!(device.address_type, BluetoothAddressType::Resolvable);
+        assert_ne!(device.address, IDENTITY);
+        assert!(irk.resolves(&device.address));
+
+        let paired = scanner.pair_device(&device.address).await.unwrap();
+        assert_eq!(paired.identity_address, IDENTITY);
+        assert_eq!(paired.address_type, BluetoothAddressType::Resolvable);
+        assert_eq!(paired.identity_resolving_key, Some(irk));
+
+        let mut config = BluetoothConfig {
+            device_address: device.address.clone(),
+            rssi_threshold: -70,
+            identity_resolving_key: None,
+            probe_mode: ProbeMode::Passive,
+            trust_connection_without_rssi: false,
+            rssi_fusion: RssiFusion::Strongest,
+        };
+        assert!(scanner.check_proximity(&config).await.unwrap().nearby);
+
+        // After the address rotates, the old address no longer matches...
+        scanner.rotate_private_addresses();
+        let rotated = advertised(&scanner).await;
+        assert_ne!(rotated.address, device.address);
+        assert!(!scanner.check_proximity(&config).await.unwrap().nearby);
+
+        // ...but the identity address and IRK still find the device
+        config.device_address = paired.identity_address;
+        config.identity_resolving_key = paired.identity_resolving_key;
+        let result = scanner.check_proximity(&config).await.unwrap();
+        assert!(result.nearby);
+        assert_eq!(result.rssi, Some(-50));
+    }
+
+    #[test]
+    fn test_probe_mode_serde() {
+        assert_eq!(ProbeMode::default(), ProbeMode::Passive);
+        let json = serde_json::to_string(&ProbeMode::PassiveThenActive).unwrap();
+        assert_eq!(json, "\"passive_then_active\"");
+        assert!(ProbeMode::PassiveThenActive.scans());
+        assert!(ProbeMode::PassiveThenActive.connects());
+        assert!(!ProbeMode::Active.scans());
+        assert!(!ProbeMode::Passive.connects());
+    }
+
+    #[test]
+    fn test_is_nearby_by_detection_method() {
+        use DetectionMethod::{Advertisement, Connection};
+
+        assert!(is_nearby(Some(Advertisement), Some(-50), -60, false));
+        assert!(!is_nearby(Some(Advertisement), Some(-70), -60, false));
+        assert!(!is_nearby(Some(Advertisement), None, -60, true));
+        assert!(is_nearby(Some(Connection), Some(-50), -60, false));
+        assert!(!is_nearby(Some(Connection), Some(-70), -60, true));
+        assert!(!is_nearby(Some(Connection), None, -60, false));
+        assert!(is_nearby(Some(Connection), None, -60, true));
+        assert!(!is_nearby(None, Some(-50), -60, true));
+    }
+
+    #[tokio::test]
+    #[cfg(any(feature = "mock-bluetooth", not(feature = "bluetooth")))]
+    async fn test_mock_scanner_active_probe_finds_silent_device() {
+        let scanner = BluetoothScanner::new().await.unwrap();
+        scanner
+            .add_mock_device(MockDevice {
+                address: "AA:BB:CC:00:11:22".to_string(),
+                name: Some("Sleeping Phone".to_string()),
+                rssi: Some(-60),
+                is_visible: true,
+                is_advertising: false,
+                paired: true,
+                identity_resolving_key: None,
+            })
+            .await;
+
+        let mut config = BluetoothConfig {
+            device_address: "AA:BB:CC:00:11:22".to_string(),
+            rssi_threshold: -70,
+            identity_resolving_key: None,
+            probe_mode: ProbeMode::Passive,
+            trust_connection_without_rssi: false,
+            rssi_fusion: RssiFusion::Strongest,
+        };
+
+        let result = scanner.check_proximity(&config).await.unwrap();
+        assert!(!result.nearby);
+        assert!(result.detection_method.is_none());
+
+        config.probe_mode = ProbeMode::PassiveThenActive;
+        let result = scanner.check_proximity(&config).await.unwrap();
+        assert!(result.nearby);
+        assert_eq!(result.detection_method, Some(DetectionMethod::Connection));
+        assert_eq!(result.device_name, Some("Sleeping Phone".to_string()));
+
+        // A connection without RSSI could come from another room
+        scanner
+            .set_mock_device_rssi("AA:BB:CC:00:11:22", None)
+            .await;
+        let result = scanner.check_proximity(&config).await.unwrap();
+        assert!(!result.nearby);
+        assert_eq!(result.detection_method, Some(DetectionMethod::Connection));
+
+        config.trust_connection_without_rssi = true;
+        assert!(scanner.check_proximity(&config).await.unwrap().nearby);
+    }
+
+    #[tokio::test]
+    #[cfg(any(feature = "mock-bluetooth", not(feature = "bluetooth")))]
+    async fn test_mock_scanner_active_probe_requires_pairing() {
+        let scanner = BluetoothScanner::new().await.unwrap();
+
+        let config = BluetoothConfig {
+            device_address: "AA:BB:CC:DD:EE:FF".to_string(),
+            rssi_threshold: -70,
+            identity_resolving_key: None,
+            probe_mode: ProbeMode::Active,
+            trust_connection_without_rssi: false,
+            rssi_fusion: RssiFusion::Strongest,
+        };
+
+        let result = scanner.check_proximity(&config).await.unwrap();
+        assert!(!result.nearby);
+
+        scanner.pair_device("AA:BB:CC:DD:EE:FF").await.unwrap();
+        let result = scanner.check_proximity(&config).await.unwrap();
+        assert!(result.nearby);
+        assert_eq!(result.detection_method, Some(DetectionMethod::Connection));
+    }
+
+    #[tokio::test]
+    #[cfg(any(feature = "mock-bluetooth", not(feature = "bluetooth")))]
+    async fn test_mock_scanner_adapter_monitor() {
+        let scanner = BluetoothScanner::new().await.unwrap();
+        let mut monitor = scanner.monitor_adapter().await.unwrap();
+
+        scanner.set_adapter_present(false).await;
+        assert_eq!(monitor.next().await, Some(AdapterChange::Removed));
+        assert!(matches!(
+            scanner.is_adapter_powered().await,
+            Err(BluetoothError::AdapterNotFound)
+        ));
+
+        scanner.set_adapter_present(true).await;
+        assert_eq!(monitor.next().await, Some(AdapterChange::Added));
+
+        scanner.power_cycle_adapter().await.unwrap();
+        assert_eq!(monitor.next().await, Some(AdapterChange::Powered(false)));
+        assert_eq!(monitor.next().await, Some(AdapterChange::Powered(true)));
+        assert!(scanner.is_adapter_powered().await.unwrap());
+    }
+
+    #[test]
+    fn test_rssi_fusion() {
+        assert_eq!(RssiFusion::Strongest.combine(&[-70, -55, -80]), Some(-55));
+        assert_eq!(RssiFusion::Strongest.combine(&[]), None);
+        assert_eq!(RssiFusion::Average.combine(&[]), None);
+        assert_eq!(RssiFusion::Average.combine(&[-60]), Some(-60));
+        // -50 dBm dominates -70 dBm in the power domain: 10*log10((1e-5 + 1e-7)/2)
+        assert_eq!(RssiFusion::Average.combine(&[-50, -70]), Some(-53));
+    }
+
+    #[tokio::test]
+    #[cfg(any(feature = "mock-bluetooth", not(feature = "bluetooth")))]
+    async fn test_mock_scanner_adapter_selection() {
+        let scanner = BluetoothScanner::with_adapters(&["hci1".to_string()])
+            .await
+            .unwrap();
+        let adapters = scanner.list_adapters().await.unwrap();
+        assert_eq!(adapters.len(), 2);
+        assert!(!adapters[0].in_use);
+        assert!(adapters[1].in_use);
+
+        assert!(matches!(
+            BluetoothScanner::with_adapters(&["hci7".to_string()]).await,
+            Err(BluetoothError::AdapterNotFound)
+        ));
+    }
+
+    #[tokio::test]
+    #[cfg(any(feature = "mock-bluetooth", not(feature = "bluetooth")))]
+    async fn test_mock_scanner_watch_devices() {
+        let scanner = BluetoothScanner::new().await.unwrap();
+        scanner
+            .add_mock_device(MockDevice {
+                address: "AA:BB:CC:DD:EE:01".to_string(),
+                name: Some("Phone".to_string()),
+                rssi: Some(-80),
+                is_visible: true,
+                is_advertising: true,
+                paired: false,
+                identity_resolving_key: None,
+            })
+            .await;
+
+        let mut watch = scanner.watch_devices(30).await.unwrap();
+        let first = loop {
+            let device = watch.next().await.unwrap();
+            if device.address == "AA:BB:CC:DD:EE:01" {
+                break device;
+            }
+        };
+        assert_eq!(first.rssi, Some(-80));
+
+        // A device is reported again once its signal changes
+        scanner
+            .set_mock_device_rssi("AA:BB:CC:DD:EE:01", Some(-50))
+            .await;
+        let update = loop {
+            let device = watch.next().await.unwrap();
+            if device.address == "AA:BB:CC:DD:EE:01" {
+                break device;
+            }
+        };
+        assert_eq!(update.rssi, Some(-50));
+
+        // Dropping the watch stops the scan
+        drop(watch);
+    }
 }
@@ -29,13 +29,19 @@
 //! # Ok::<(), tether_core::config::ConfigError>(())
 //! ```
 
+use chrono::{DateTime, NaiveTime, Utc};
+use chrono_tz::Tz;
 use once_cell::sync::Lazy;
 use regex::Regex;
 use serde::{Deserialize, Serialize};
-use std::fs;
+use std::collections::HashSet;
 use std::path::Path;
 use thiserror::Error;
 
+use crate::bluetooth::{IdentityResolvingKey, ProbeMode, RssiFusion};
+use crate::secrets::{SecretResult, SecretStore, SecretString};
+use crate::storage::{DurableFile, LoadError, StorageBackend};
+
 // =============================================================================
 // ERROR TYPES
 // =============================================================================
@@ -185,6 +191,79 @@ pub struct BluetoothConfig {
     /// a device within about 5 meters.
     #[serde(default = "default_rssi_threshold")]
     pub rssi_threshold: i8,
+
+    /// Identity Resolving Key obtained by pairing with the target device.
+    ///
+    /// Phones with LE privacy enabled advertise a random address that
+    /// rotates periodically. When the key is present, any advertised address
+    /// that resolves with it is treated as the target device. It is set by
+    /// the pairing flow and cleared whenever `target_address` changes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target_irk: Option<IdentityResolvingKey>,
+
+    /// How proximity checks detect the target device.
+    ///
+    /// Phones that stop advertising with the screen off need `active` or
+    /// `passive_then_active`, which connect to the device and therefore
+    /// require it to be paired.
+    ///
+    /// # Default
+    ///
+    /// `passive`, which only listens for advertisements.
+    #[serde(default)]
+    pub probe_mode: ProbeMode,
+
+    /// Whether a successful connection counts as nearby when the controller
+    /// reports no RSSI for it.
+    ///
+    /// Many controllers don't report RSSI for connections, and a BLE
+    /// connection reaches into other rooms, so by default such a connection
+    /// does not count. Only enable this if the phone can't connect from
+    /// outside the bedroom.
+    ///
+    /// # Default
+    ///
+    /// `false`
+    #[serde(default)]
+    pub trust_connection_without_rssi: bool,
+
+    /// Whether to power-cycle the adapter when scans keep failing.
+    ///
+    /// The server always re-creates its Bluetooth session when the adapter
+    /// disappears or scans fail repeatedly. Some controllers (notably the
+    /// Pi's onboard chip) only recover after being turned off and on again.
+    ///
+    /// # Default
+    ///
+    /// `false`
+    #[serde(default)]
+    pub power_cycle_on_failure: bool,
+
+    /// Bluetooth adapters to scan with, by interface name.
+    ///
+    /// Placing a USB dongle nearer the front door than the Pi's onboard
+    /// chip improves coverage. The first adapter is used for pairing and
+    /// active probes.
+    ///
+    /// # Default
+    ///
+    /// Empty, which uses the system's default adapter.
+    ///
+    /// # Example
+    ///
+    /// ```text
+    /// ["hci0", "hci1"]
+    /// ```
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub adapters: Vec<String>,
+
+    /// How RSSI readings from several adapters are combined.
+    ///
+    /// # Default
+    ///
+    /// `strongest`, which uses the adapter closest to the device.
+    #[serde(default)]
+    pub rssi_fusion: RssiFusion,
 }
 
 /// Returns the default RSSI threshold (-60 dBm).
@@ -202,6 +281,12 @@ impl Default for BluetoothConfig {
             target_address: String::from("00:00:00:00:00:00"),
             target_name: String::from("Unconfigured Device"),
             rssi_threshold: default_rssi_threshold(),
+            target_irk: None,
+            probe_mode: ProbeMode::default(),
+            trust_connection_without_rssi: false,
+            power_cycle_on_failure: false,
+            adapters: Vec::new(),
+            rssi_fusion: RssiFusion::default(),
         }
     }
 }
@@ -214,6 +299,7 @@ impl BluetoothConfig {
     /// - `target_address` must be a valid MAC address in `XX:XX:XX:XX:XX:XX` format
     /// - `target_name` must not be empty
     /// - `rssi_threshold` must be between -100 and 0 dBm
+    /// - `adapters` must be unique interface names like `hci0`
     ///
     /// # Returns
     ///
@@ -251,6 +337,21 @@ impl BluetoothConfig {
             });
         }
 
+        // Validate adapter names
+        for (i, adapter) in self.adapters.iter().enumerate() {
+            if !ADAPTER_NAME_REGEX.is_match(adapter) {
+                errors.push(ConfigError::ValidationError {
+                    field: format!("bluetooth.adapters[{i}]"),
+                    message: format!("Invalid adapter name '{adapter}'. Expected format: hciN"),
+                });
+            } else if self.adapters[..i].contains(adapter) {
+                errors.push(ConfigError::ValidationError {
+                    field: format!("bluetooth.adapters[{i}]"),
+                    message: format!("Duplicate adapter '{adapter}'"),
+                });
+            }
+        }
+
         errors
     }
 }
@@ -279,17 +380,19 @@ pub struct WifiNetwork {
     /// ```
     pub ssid: String,
 
-    /// The password for the WiFi network.
+    /// A password not yet moved into the secrets store.
     ///
-    /// For open networks (no password), use an empty string.
-    /// WPA2/WPA3 passwords are supported.
-    ///
-    /// # Security Note
+    /// Set by [`WifiNetwork::new`], or read from configuration files written
+    /// before passwords were encrypted. [`Config::store_secrets`] moves it
+    /// into the [`SecretStore`]. It is never written back to disk.
+    #[serde(default, skip_serializing)]
+    pub password: Option<SecretString>,
+
+    /// Id of the network's password in the [`SecretStore`].
     ///
-    /// This value is stored in plaintext in the configuration file.
-    /// Ensure the configuration file has appropriate permissions (0600).
-    #[serde(default)]
-    pub password: String,
+    /// `None` for open networks. WPA2/WPA3 passwords are supported.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub password_id: Option<String>,
 
     /// Whether this is the primary network.
     ///
@@ -313,6 +416,9 @@ impl WifiNetwork {
     /// * `password` - The network password (empty string for open networks)
     /// * `primary` - Whether this is the primary network
     ///
+    /// The password is held in memory until [`Config::store_secrets`] moves
+    /// it into the secrets store.
+    ///
     /// # Example
     ///
     /// ```rust
@@ -322,9 +428,11 @@ impl WifiNetwork {
     /// let backup = WifiNetwork::new("MobileHotspot", "backup456", false);
     /// ```
     pub fn new(ssid: impl Into<String>, password: impl Into<String>, primary: bool) -> Self {
+        let password: String = password.into();
         Self {
             ssid: ssid.into(),
-            password: password.into(),
+            password: Some(SecretString::new(password)).filter(|p| !p.is_empty()),
+            password_id: None,
             primary,
         }
     }
@@ -489,6 +597,14 @@ pub struct PassesConfig {
     /// - On February 1st, `per_month` becomes 5 and `pending_per_month` is cleared
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub pending_per_month: Option<u8>,
+
+    /// Where pass data and history are stored.
+    ///
+    /// # Default
+    ///
+    /// `json`, a single `passes.json` file.
+    #[serde(default)]
+    pub storage: StorageBackend,
 }
 
 /// Returns the default passes per month (3).
@@ -501,6 +617,7 @@ impl Default for PassesConfig {
         Self {
             per_month: default_passes_per_month(),
             pending_per_month: None,
+            storage: StorageBackend::default(),
         }
     }
 }
@@ -563,112 +680,869 @@ impl PassesConfig {
         if let Some(pending) = self.pending_per_month {
             if pending > 31 {
                 errors.push(ConfigError::ValidationError {
-                    field: "passes.pending_per_month".to_string(),
-                    message: format!("Pending passes per month ({}) cannot exceed 31", pending),
+                    field: "passes.pending_per_month".to_string(),
+                    message: format!("Pending passes per month ({}) cannot exceed 31", pending),
+                });
+            }
+        }
+
+        errors
+    }
+}
+
+// =============================================================================
+// SYSTEM CONFIGURATION
+// =============================================================================
+
+/// System-level configuration.
+///
+/// Contains settings that affect the overall system behavior, such as
+/// timezone and onboarding state.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SystemConfig {
+    /// The IANA timezone string for the system.
+    ///
+    /// This is used for determining midnight for pass refresh and for
+    /// displaying times in the UI. The timezone is typically auto-detected
+    /// from the connected WiFi network during onboarding.
+    ///
+    /// # Format
+    ///
+    /// Standard IANA timezone format: `Region/City`
+    ///
+    /// # Examples
+    ///
+    /// ```text
+    /// "America/New_York"
+    /// "Europe/London"
+    /// "Asia/Tokyo"
+    /// "Australia/Sydney"
+    /// ```
+    ///
+    /// # Default
+    ///
+    /// `"UTC"` - Universal Coordinated Time
+    #[serde(default = "default_timezone")]
+    pub timezone: String,
+
+    /// Whether the onboarding process has been completed.
+    ///
+    /// This flag is set to `true` after the user completes the initial
+    /// setup wizard. When `false`, the web UI shows the onboarding flow.
+    ///
+    /// # Default
+    ///
+    /// `false` - Onboarding is required by default.
+    #[serde(default)]
+    pub onboarding_complete: bool,
+}
+
+/// Returns the default timezone ("UTC").
+fn default_timezone() -> String {
+    String::from("UTC")
+}
+
+impl Default for SystemConfig {
+    fn default() -> Self {
+        Self {
+            timezone: default_timezone(),
+            onboarding_complete: false,
+        }
+    }
+}
+
+impl SystemConfig {
+    /// Validates the system configuration.
+    ///
+    /// # Validation Rules
+    ///
+    /// - `timezone` must not be empty
+    /// - `timezone` must be a valid IANA timezone identifier
+    ///
+    /// # Note
+    ///
+    /// Full timezone validation requires the `chrono-tz` crate. This
+    /// implementation performs basic format validation only.
+    ///
+    /// # Returns
+    ///
+    /// A vector of validation errors. Empty if all fields are valid.
+    pub fn validate(&self) -> Vec<ConfigError> {
+        let mut errors = Vec::new();
+
+        if self.timezone.trim().is_empty() {
+            errors.push(ConfigError::ValidationError {
+                field: "system.timezone".to_string(),
+                message: "Timezone cannot be empty".to_string(),
+            });
+        }
+
+        // Basic timezone format validation (Region/City or single word like UTC)
+        if !is_valid_timezone_format(&self.timezone) {
+            errors.push(ConfigError::ValidationError {
+                field: "system.timezone".to_string(),
+                message: format!(
+                    "Invalid timezone format '{}'. Expected IANA format like 'America/New_York'",
+                    self.timezone
+                ),
+            });
+        }
+
+        errors
+    }
+}
+
+// =============================================================================
+// CURFEW CONFIGURATION
+// =============================================================================
+
+/// Nightly curfew configuration.
+///
+/// The curfew is the time of night when the phone should be out of the
+/// bedroom. It starts at `start` and ends at `end`, both in the
+/// [`SystemConfig::timezone`], and may span midnight.
+///
+/// # Example TOML
+///
+/// ```toml
+/// [curfew]
+/// enabled = true
+/// start = "22:30"
+/// end = "06:30"
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct CurfewConfig {
+    /// Whether the curfew is in effect.
+    ///
+    /// # Default
+    ///
+    /// `false`
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// When the curfew starts each night, as `HH:MM`.
+    ///
+    /// # Default
+    ///
+    /// `"22:00"`
+    #[serde(default = "default_curfew_start")]
+    pub start: String,
+
+    /// When the curfew ends each morning, as `HH:MM`.
+    ///
+    /// # Default
+    ///
+    /// `"06:00"`
+    #[serde(default = "default_curfew_end")]
+    pub end: String,
+}
+
+/// Returns the default curfew start ("22:00").
+fn default_curfew_start() -> String {
+    String::from("22:00")
+}
+
+/// Returns the default curfew end ("06:00").
+fn default_curfew_end() -> String {
+    String::from("06:00")
+}
+
+impl Default for CurfewConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            start: default_curfew_start(),
+            end: default_curfew_end(),
+        }
+    }
+}
+
+impl CurfewConfig {
+    /// Returns whether the curfew is in effect at `now`.
+    ///
+    /// `timezone` is the IANA name the start and end times are in; an
+    /// unknown name is treated as UTC. Always `false` when disabled or when
+    /// a time is malformed.
+    #[must_use]
+    pub fn is_active_at(&self, now: DateTime<Utc>, timezone: &str) -> bool {
+        if !self.enabled {
+            return false;
+        }
+        let (Some(start), Some(end)) = (parse_clock_time(&self.start), parse_clock_time(&self.end))
+        else {
+            return false;
+        };
+
+        let timezone: Tz = timezone.parse().unwrap_or(Tz::UTC);
+        let local = now.with_timezone(&timezone).time();
+        if start <= end {
+            start <= local && local < end
+        } else {
+            // Spans midnight
+            local >= start || local < end
+        }
+    }
+
+    /// Validates the curfew configuration.
+    ///
+    /// # Validation Rules
+    ///
+    /// - `start` and `end` must be times of day as `HH:MM`
+    /// - `start` and `end` must differ
+    ///
+    /// # Returns
+    ///
+    /// A vector of validation errors. Empty if all fields are valid.
+    #[must_use]
+    pub fn validate(&self) -> Vec<ConfigError> {
+        let mut errors = Vec::new();
+
+        for (field, value) in [("curfew.start", &self.start), ("curfew.end", &self.end)] {
+            if parse_clock_time(value).is_none() {
+                errors.push(ConfigError::ValidationError {
+                    field: field.to_string(),
+                    message: format!("Invalid time '{value}'. Expected HH:MM, e.g. '22:30'"),
+                });
+            }
+        }
+
+        if errors.is_empty() && parse_clock_time(&self.start) == parse_clock_time(&self.end) {
+            errors.push(ConfigError::ValidationError {
+                field: "curfew.end".to_string(),
+                message: "Curfew must end at a different time than it starts".to_string(),
+            });
+        }
+
+        errors
+    }
+}
+
+// =============================================================================
+// MQTT CONFIGURATION
+// =============================================================================
+
+/// MQTT publishing configuration.
+///
+/// When enabled, the server publishes proximity and pass state to an MQTT
+/// broker, along with Home Assistant discovery payloads so the sensors
+/// appear in Home Assistant without manual setup. Passes can be used by
+/// publishing to the command topic.
+///
+/// # Example TOML
+///
+/// ```toml
+/// [mqtt]
+/// enabled = true
+/// host = "homeassistant.local"
+/// username = "tether"
+/// password_id = "5b9f7c1a-2d3e-4f60-8a71-9c0b1d2e3f40"
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct MqttConfig {
+    /// Whether to connect to the broker.
+    ///
+    /// # Default
+    ///
+    /// `false` - MQTT is disabled by default.
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Hostname or IP address of the broker.
+    ///
+    /// # Default
+    ///
+    /// `"localhost"`
+    #[serde(default = "default_mqtt_host")]
+    pub host: String,
+
+    /// TCP port of the broker.
+    ///
+    /// # Default
+    ///
+    /// `1883`, the standard unencrypted MQTT port.
+    #[serde(default = "default_mqtt_port")]
+    pub port: u16,
+
+    /// Username for brokers that require authentication.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+
+    /// A password not yet moved into the secrets store.
+    ///
+    /// Handled like [`WifiNetwork::password`]: [`Config::store_secrets`]
+    /// moves it into the [`SecretStore`] and it is never written back to disk.
+    #[serde(default, skip_serializing)]
+    pub password: Option<SecretString>,
+
+    /// Id of the broker password in the [`SecretStore`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub password_id: Option<String>,
+
+    /// Identifies this device to the broker and to Home Assistant.
+    ///
+    /// Used as the MQTT client id and as the node id in discovery topics,
+    /// so it must be unique when several devices share a broker.
+    ///
+    /// # Default
+    ///
+    /// `"tether"`
+    #[serde(default = "default_mqtt_node_id")]
+    pub node_id: String,
+
+    /// Prefix of the state and command topics.
+    ///
+    /// # Default
+    ///
+    /// `"tether"` - State is published to `tether/phone_in_bedroom`, etc.
+    #[serde(default = "default_mqtt_base_topic")]
+    pub base_topic: String,
+
+    /// Home Assistant discovery prefix.
+    ///
+    /// # Default
+    ///
+    /// `"homeassistant"`, Home Assistant's default.
+    #[serde(default = "default_mqtt_discovery_prefix")]
+    pub discovery_prefix: String,
+
+    /// Seconds between proximity checks made to keep the sensors current.
+    ///
+    /// Proximity is otherwise only checked when a client asks for it. Set
+    /// to 0 to only publish the results of those checks.
+    ///
+    /// # Default
+    ///
+    /// 60 seconds.
+    #[serde(default = "default_mqtt_scan_interval_secs")]
+    pub scan_interval_secs: u32,
+}
+
+/// Returns the default MQTT broker host ("localhost").
+fn default_mqtt_host() -> String {
+    String::from("localhost")
+}
+
+/// Returns the default MQTT broker port (1883).
+const fn default_mqtt_port() -> u16 {
+    1883
+}
+
+/// Returns the default MQTT node id ("tether").
+fn default_mqtt_node_id() -> String {
+    String::from("tether")
+}
+
+/// Returns the default MQTT base topic ("tether").
+fn default_mqtt_base_topic() -> String {
+    String::from("tether")
+}
+
+/// Returns the default Home Assistant discovery prefix ("homeassistant").
+fn default_mqtt_discovery_prefix() -> String {
+    String::from("homeassistant")
+}
+
+/// Returns the default interval between MQTT proximity checks (60 seconds).
+const fn default_mqtt_scan_interval_secs() -> u32 {
+    60
+}
+
+impl Default for MqttConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            host: default_mqtt_host(),
+            port: default_mqtt_port(),
+            username: None,
+            password: None,
+            password_id: None,
+            node_id: default_mqtt_node_id(),
+            base_topic: default_mqtt_base_topic(),
+            discovery_prefix: default_mqtt_discovery_prefix(),
+            scan_interval_secs: default_mqtt_scan_interval_secs(),
+        }
+    }
+}
+
+impl MqttConfig {
+    /// Validates the MQTT configuration.
+    ///
+    /// # Validation Rules
+    ///
+    /// - `host` must not be empty
+    /// - `port` must not be 0
+    /// - `node_id` may only contain letters, digits, `_` and `-`
+    /// - `base_topic` and `discovery_prefix` must be non-empty topic names
+    ///   without wildcards (`+`, `#`) or leading/trailing `/`
+    ///
+    /// # Returns
+    ///
+    /// A vector of validation errors. Empty if all fields are valid.
+    pub fn validate(&self) -> Vec<ConfigError> {
+        let mut errors = Vec::new();
+
+        if self.host.trim().is_empty() {
+            errors.push(ConfigError::ValidationError {
+                field: "mqtt.host".to_string(),
+                message: "MQTT host cannot be empty".to_string(),
+            });
+        }
+
+        if self.port == 0 {
+            errors.push(ConfigError::ValidationError {
+                field: "mqtt.port".to_string(),
+                message: "MQTT port cannot be 0".to_string(),
+            });
+        }
+
+        if !MQTT_NODE_ID_REGEX.is_match(&self.node_id) {
+            errors.push(ConfigError::ValidationError {
+                field: "mqtt.node_id".to_string(),
+                message: format!(
+                    "Invalid node id '{}'. Use only letters, digits, '_' and '-'",
+                    self.node_id
+                ),
+            });
+        }
+
+        for (field, topic) in [
+            ("mqtt.base_topic", &self.base_topic),
+            ("mqtt.discovery_prefix", &self.discovery_prefix),
+        ] {
+            if !is_valid_mqtt_topic_prefix(topic) {
+                errors.push(ConfigError::ValidationError {
+                    field: field.to_string(),
+                    message: format!(
+                        "Invalid topic '{topic}'. Topics cannot be empty, contain '+' or '#', \
+                         or start or end with '/'"
+                    ),
+                });
+            }
+        }
+
+        errors
+    }
+}
+
+// =============================================================================
+// MDNS CONFIGURATION
+// =============================================================================
+
+/// mDNS advertisement configuration.
+///
+/// When enabled, the server advertises itself on the LAN as a `_tether._tcp`
+/// service for tether clients and as a `_http._tcp` service for browsers,
+/// and answers for `<hostname>.local`. Clients find the Pi this way after
+/// DHCP hands it a new address.
+///
+/// # Example TOML
+///
+/// ```toml
+/// [mdns]
+/// hostname = "tether-bedroom"
+/// instance_name = "bedroom"
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct MdnsConfig {
+    /// Whether to advertise the server.
+    ///
+    /// # Default
+    ///
+    /// `true`
+    #[serde(default = "default_mdns_enabled")]
+    pub enabled: bool,
+
+    /// Host name the server answers for, without `.local`.
+    ///
+    /// Must be unique on the LAN, so give each Pi its own when there are
+    /// several.
+    ///
+    /// # Default
+    ///
+    /// `"tether"` - The server is reachable at `tether.local`.
+    #[serde(default = "default_mdns_hostname")]
+    pub hostname: String,
+
+    /// Name of the advertised service instance, shown by service browsers
+    /// and used by clients to pick a server.
+    ///
+    /// # Default
+    ///
+    /// The host name.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub instance_name: Option<String>,
+
+    /// Port of the web UI, advertised as `_http._tcp`.
+    ///
+    /// Set this when a reverse proxy serves the web UI on another port,
+    /// like nginx on port 80 on the Pi.
+    ///
+    /// # Default
+    ///
+    /// The server's own port.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub web_port: Option<u16>,
+}
+
+/// Longest DNS label, and so the longest host or instance name.
+const MAX_DNS_LABEL_LENGTH: usize = 63;
+
+/// Returns whether mDNS advertisement is enabled by default (true).
+const fn default_mdns_enabled() -> bool {
+    true
+}
+
+/// Returns the default mDNS host name ("tether").
+fn default_mdns_hostname() -> String {
+    String::from("tether")
+}
+
+impl Default for MdnsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_mdns_enabled(),
+            hostname: default_mdns_hostname(),
+            instance_name: None,
+            web_port: None,
+        }
+    }
+}
+
+impl MdnsConfig {
+    /// Returns the name of the advertised service instance.
+    #[must_use]
+    pub fn instance_name(&self) -> &str {
+        self.instance_name.as_deref().unwrap_or(&self.hostname)
+    }
+
+    /// Validates the mDNS configuration.
+    ///
+    /// # Validation Rules
+    ///
+    /// - `hostname` must be a DNS label: letters, digits and `-`, not
+    ///   starting or ending with `-`, at most 63 characters
+    /// - `instance_name` (if set) must not be empty, contain `.`, or be
+    ///   longer than 63 bytes
+    /// - `web_port` (if set) must not be 0
+    ///
+    /// # Returns
+    ///
+    /// A vector of validation errors. Empty if all fields are valid.
+    pub fn validate(&self) -> Vec<ConfigError> {
+        let mut errors = Vec::new();
+
+        if !MDNS_HOSTNAME_REGEX.is_match(&self.hostname) {
+            errors.push(ConfigError::ValidationError {
+                field: "mdns.hostname".to_string(),
+                message: format!(
+                    "Invalid host name '{}'. Use up to {MAX_DNS_LABEL_LENGTH} letters, digits \
+                     and '-', without a leading or trailing '-'",
+                    self.hostname
+                ),
+            });
+        }
+
+        if let Some(name) = &self.instance_name {
+            if name.trim().is_empty() || name.contains('.') || name.len() > MAX_DNS_LABEL_LENGTH {
+                errors.push(ConfigError::ValidationError {
+                    field: "mdns.instance_name".to_string(),
+                    message: format!(
+                        "Invalid instance name '{name}'. Names cannot be empty, contain '.', \
+                         or be longer than {MAX_DNS_LABEL_LENGTH} bytes"
+                    ),
+                });
+            }
+        }
+
+        if self.web_port == Some(0) {
+            errors.push(ConfigError::ValidationError {
+                field: "mdns.web_port".to_string(),
+                message: "Web UI port cannot be 0".to_string(),
+            });
+        }
+
+        errors
+    }
+}
+
+// =============================================================================
+// GUARD CONFIGURATION
+// =============================================================================
+
+/// Guarded settings configuration.
+///
+/// When enabled, changes to the tracked Bluetooth device and to passes per
+/// month don't take effect right away. They wait as pending changes until
+/// an accountability partner approves them with the partner token, or until
+/// the cooling-off period has passed. This keeps the rules from being
+/// loosened on a whim late at night.
+///
+/// The partner sets the token by adding `partner_token` to the TOML file;
+/// on the next start it is moved into the [`SecretStore`] like other
+/// secrets. Guarding only starts once onboarding is complete.
+///
+/// # Example TOML
+///
+/// ```toml
+/// [guard]
+/// enabled = true
+/// partner_token_id = "7d2e9a41-3b5c-4f18-8e60-1a9b2c3d4e5f"
+/// cooling_off_hours = 24
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct GuardConfig {
+    /// Whether guarded settings need approval.
+    ///
+    /// # Default
+    ///
+    /// `false` - Changes take effect immediately.
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// A partner token not yet moved into the secrets store.
+    ///
+    /// Handled like [`WifiNetwork::password`]: [`Config::store_secrets`]
+    /// moves it into the [`SecretStore`] and it is never written back to disk.
+    #[serde(default, skip_serializing)]
+    pub partner_token: Option<SecretString>,
+
+    /// Id of the partner token in the [`SecretStore`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub partner_token_id: Option<String>,
+
+    /// Hours after which a pending change takes effect without approval.
+    ///
+    /// Set to 0 to require the partner's approval for every change.
+    ///
+    /// # Default
+    ///
+    /// 24 hours.
+    #[serde(default = "default_guard_cooling_off_hours")]
+    pub cooling_off_hours: u32,
+}
+
+/// Minimum length of the partner token.
+pub const MIN_PARTNER_TOKEN_LENGTH: usize = 16;
+
+/// Maximum cooling-off period (30 days).
+const MAX_COOLING_OFF_HOURS: u32 = 30 * 24;
+
+/// Returns the default cooling-off period (24 hours).
+const fn default_guard_cooling_off_hours() -> u32 {
+    24
+}
+
+impl Default for GuardConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            partner_token: None,
+            partner_token_id: None,
+            cooling_off_hours: default_guard_cooling_off_hours(),
+        }
+    }
+}
+
+impl GuardConfig {
+    /// Returns whether a partner token is configured.
+    #[must_use]
+    pub const fn has_partner_token(&self) -> bool {
+        self.partner_token.is_some() || self.partner_token_id.is_some()
+    }
+
+    /// Validates the guard configuration.
+    ///
+    /// # Validation Rules
+    ///
+    /// - `partner_token` (if set) must be at least 16 characters
+    /// - `cooling_off_hours` must be at most 720 (30 days)
+    /// - When enabled, a partner token or a cooling-off period is required,
+    ///   or pending changes could never take effect
+    ///
+    /// # Returns
+    ///
+    /// A vector of validation errors. Empty if all fields are valid.
+    #[must_use]
+    pub fn validate(&self) -> Vec<ConfigError> {
+        let mut errors = Vec::new();
+
+        if let Some(token) = &self.partner_token {
+            if token.expose().chars().count() < MIN_PARTNER_TOKEN_LENGTH {
+                errors.push(ConfigError::ValidationError {
+                    field: "guard.partner_token".to_string(),
+                    message: format!(
+                        "Partner token must be at least {MIN_PARTNER_TOKEN_LENGTH} characters"
+                    ),
+                });
+            }
+        }
+
+        if self.cooling_off_hours > MAX_COOLING_OFF_HOURS {
+            errors.push(ConfigError::ValidationError {
+                field: "guard.cooling_off_hours".to_string(),
+                message: format!(
+                    "Cooling-off period ({} hours) cannot exceed {MAX_COOLING_OFF_HOURS} hours",
+                    self.cooling_off_hours
+                ),
+            });
+        }
+
+        if self.enabled && self.cooling_off_hours == 0 && !self.has_partner_token() {
+            errors.push(ConfigError::ValidationError {
+                field: "guard.cooling_off_hours".to_string(),
+                message: "Guarded changes need a partner token or a cooling-off period".to_string(),
+            });
+        }
+
+        errors
+    }
+}
+
+// =============================================================================
+// ACCESS CONFIGURATION
+// =============================================================================
+
+/// What a request to the API may do, from least to most.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum ApiScope {
+    /// Only read state.
+    Read,
+    /// Read state and use passes.
+    Passes,
+    /// Read state, use passes and change settings.
+    #[default]
+    Settings,
+}
+
+/// A bearer token granting a scope of the API.
+///
+/// Like the partner token, `token` is moved into the [`SecretStore`] on the
+/// next start and only `token_id` is written back to disk.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ApiToken {
+    /// Name of the token's holder, recorded in the audit log.
+    pub name: String,
+
+    /// What requests carrying the token may do.
+    pub scope: ApiScope,
+
+    /// A token not yet moved into the secrets store.
+    #[serde(default, skip_serializing)]
+    pub token: Option<SecretString>,
+
+    /// Id of the token in the [`SecretStore`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_id: Option<String>,
+}
+
+/// Minimum length of an API token.
+pub const MIN_API_TOKEN_LENGTH: usize = 16;
+
+impl ApiToken {
+    /// Validates the API token.
+    ///
+    /// # Validation Rules
+    ///
+    /// - `name` must not be empty or contain control characters
+    /// - `token` (if set) must be at least 16 characters
+    /// - `token` or `token_id` must be set
+    ///
+    /// # Returns
+    ///
+    /// A vector of validation errors. Empty if all fields are valid.
+    #[must_use]
+    pub fn validate(&self, index: usize) -> Vec<ConfigError> {
+        let mut errors = Vec::new();
+
+        if self.name.trim().is_empty() || self.name.chars().any(char::is_control) {
+            errors.push(ConfigError::ValidationError {
+                field: format!("access.tokens[{index}].name"),
+                message: "Token names cannot be empty or contain control characters".to_string(),
+            });
+        }
+
+        match &self.token {
+            Some(token) if token.expose().chars().count() < MIN_API_TOKEN_LENGTH => {
+                errors.push(ConfigError::ValidationError {
+                    field: format!("access.tokens[{index}].token"),
+                    message: format!(
+                        "API tokens must be at least {MIN_API_TOKEN_LENGTH} characters"
+                    ),
+                });
+            }
+            None if self.token_id.is_none() => {
+                errors.push(ConfigError::ValidationError {
+                    field: format!("access.tokens[{index}].token"),
+                    message: format!("Token '{}' has no token", self.name),
                 });
             }
+            _ => {}
         }
 
         errors
     }
 }
 
-// =============================================================================
-// SYSTEM CONFIGURATION
-// =============================================================================
-
-/// System-level configuration.
+/// API access configuration.
 ///
-/// Contains settings that affect the overall system behavior, such as
-/// timezone and onboarding state.
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
-pub struct SystemConfig {
-    /// The IANA timezone string for the system.
-    ///
-    /// This is used for determining midnight for pass refresh and for
-    /// displaying times in the UI. The timezone is typically auto-detected
-    /// from the connected WiFi network during onboarding.
-    ///
-    /// # Format
-    ///
-    /// Standard IANA timezone format: `Region/City`
-    ///
-    /// # Examples
-    ///
-    /// ```text
-    /// "America/New_York"
-    /// "Europe/London"
-    /// "Asia/Tokyo"
-    /// "Australia/Sydney"
-    /// ```
-    ///
-    /// # Default
-    ///
-    /// `"UTC"` - Universal Coordinated Time
-    #[serde(default = "default_timezone")]
-    pub timezone: String,
-
-    /// Whether the onboarding process has been completed.
-    ///
-    /// This flag is set to `true` after the user completes the initial
-    /// setup wizard. When `false`, the web UI shows the onboarding flow.
+/// Requests carrying one of `tokens` as a bearer token get that token's
+/// scope, and requests carrying the partner token get every scope. All
+/// other requests, such as those of the web UI, get `default_scope`, so
+/// lower it once every client that changes settings has a token.
+///
+/// # Example TOML
+///
+/// ```toml
+/// [access]
+/// default_scope = "read"
+///
+/// [[access.tokens]]
+/// name = "assistant"
+/// scope = "passes"
+/// token_id = "3c9d1f7a-5e2b-4a86-b0c4-9d8e7f6a5b4c"
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct AccessConfig {
+    /// Scope of requests without a known token.
     ///
     /// # Default
     ///
-    /// `false` - Onboarding is required by default.
+    /// `settings` - Every request may do anything, as before tokens existed.
     #[serde(default)]
-    pub onboarding_complete: bool,
-}
-
-/// Returns the default timezone ("UTC").
-fn default_timezone() -> String {
-    String::from("UTC")
-}
+    pub default_scope: ApiScope,
 
-impl Default for SystemConfig {
-    fn default() -> Self {
-        Self {
-            timezone: default_timezone(),
-            onboarding_complete: false,
-        }
-    }
+    /// Tokens granting their holders a scope.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tokens: Vec<ApiToken>,
 }
 
-impl SystemConfig {
-    /// Validates the system configuration.
+impl AccessConfig {
+    /// Validates the access configuration.
     ///
     /// # Validation Rules
     ///
-    /// - `timezone` must not be empty
-    /// - `timezone` must be a valid IANA timezone identifier
-    ///
-    /// # Note
-    ///
-    /// Full timezone validation requires the `chrono-tz` crate. This
-    /// implementation performs basic format validation only.
+    /// - Every token must be valid (see [`ApiToken::validate`])
+    /// - Token names must be unique
     ///
     /// # Returns
     ///
     /// A vector of validation errors. Empty if all fields are valid.
+    #[must_use]
     pub fn validate(&self) -> Vec<ConfigError> {
         let mut errors = Vec::new();
+        let mut names = HashSet::new();
 
-        if self.timezone.trim().is_empty() {
-            errors.push(ConfigError::ValidationError {
-                field: "system.timezone".to_string(),
-                message: "Timezone cannot be empty".to_string(),
-            });
-        }
-
-        // Basic timezone format validation (Region/City or single word like UTC)
-        if !is_valid_timezone_format(&self.timezone) {
-            errors.push(ConfigError::ValidationError {
-                field: "system.timezone".to_string(),
-                message: format!(
-                    "Invalid timezone format '{}'. Expected IANA format like 'America/New_York'",
-                    self.timezone
-                ),
-            });
+        for (index, token) in self.tokens.iter().enumerate() {
+            errors.extend(token.validate(index));
+            if !names.insert(token.name.trim()) {
+                errors.push(ConfigError::ValidationError {
+                    field: format!("access.tokens[{index}].name"),
+                    message: format!("Token name '{}' is used more than once", token.name),
+                });
+            }
         }
 
         errors
@@ -700,20 +1574,36 @@ impl SystemConfig {
 /// [wifi]
 /// [[wifi.networks]]
 /// ssid = "HomeNetwork"
-/// password = "secret123"
+/// password_id = "0f8c3b1e-6c44-4b7e-9a51-7f3c2f1d9e20"
 /// primary = true
 ///
 /// [[wifi.networks]]
 /// ssid = "MobileHotspot"
-/// password = "backup456"
 /// primary = false
 ///
 /// [passes]
 /// per_month = 3
+/// storage = "sqlite"
 ///
 /// [system]
 /// timezone = "America/New_York"
 /// onboarding_complete = true
+///
+/// [curfew]
+/// enabled = true
+/// start = "22:30"
+/// end = "06:30"
+///
+/// [mqtt]
+/// enabled = true
+/// host = "homeassistant.local"
+///
+/// [guard]
+/// enabled = true
+/// cooling_off_hours = 24
+///
+/// [mdns]
+/// hostname = "tether"
 /// ```
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct Config {
@@ -731,6 +1621,26 @@ pub struct Config {
     /// System configuration.
     #[serde(default)]
     pub system: SystemConfig,
+
+    /// Nightly curfew configuration.
+    #[serde(default)]
+    pub curfew: CurfewConfig,
+
+    /// MQTT publishing configuration.
+    #[serde(default)]
+    pub mqtt: MqttConfig,
+
+    /// Guarded settings configuration.
+    #[serde(default)]
+    pub guard: GuardConfig,
+
+    /// mDNS advertisement configuration.
+    #[serde(default)]
+    pub mdns: MdnsConfig,
+
+    /// API access configuration.
+    #[serde(default)]
+    pub access: AccessConfig,
 }
 
 impl Default for Config {
@@ -742,12 +1652,22 @@ impl Default for Config {
     /// - 3 passes per month
     /// - UTC timezone
     /// - Onboarding not complete
+    /// - No curfew
+    /// - MQTT disabled
+    /// - Guarded settings disabled
+    /// - Advertised over mDNS as `tether.local`
+    /// - Every request may do anything through the API
     fn default() -> Self {
         Self {
             bluetooth: BluetoothConfig::default(),
             wifi: WifiConfig::default(),
             passes: PassesConfig::default(),
             system: SystemConfig::default(),
+            curfew: CurfewConfig::default(),
+            mqtt: MqttConfig::default(),
+            guard: GuardConfig::default(),
+            mdns: MdnsConfig::default(),
+            access: AccessConfig::default(),
         }
     }
 }
@@ -764,6 +1684,10 @@ impl Config {
     /// The parsed configuration, or an error if the file cannot be read
     /// or contains invalid TOML.
     ///
+    /// A file that cannot be read, is empty, or contains invalid TOML is
+    /// replaced by its newest valid backup (see [`DurableFile::load`]).
+    /// The errors below are only returned if no backup is usable.
+    ///
     /// # Errors
     ///
     /// - [`ConfigError::NotFound`] - The file does not exist
@@ -783,21 +1707,16 @@ impl Config {
         let path = path.as_ref();
         let path_str = path.display().to_string();
 
-        // Check if file exists
-        if !path.exists() {
-            return Err(ConfigError::NotFound(path_str));
+        // Read and parse, falling back to a backup if the file is corrupt
+        match DurableFile::new(path).load(toml::from_str::<Self>) {
+            Ok(Some(config)) => Ok(config),
+            Ok(None) => Err(ConfigError::NotFound(path_str)),
+            Err(LoadError::Read(e)) => Err(ConfigError::ReadError {
+                path: path_str,
+                source: e,
+            }),
+            Err(LoadError::Parse(e)) => Err(ConfigError::ParseError(e)),
         }
-
-        // Read file contents
-        let contents = fs::read_to_string(path).map_err(|e| ConfigError::ReadError {
-            path: path_str.clone(),
-            source: e,
-        })?;
-
-        // Parse TOML
-        let config: Config = toml::from_str(&contents)?;
-
-        Ok(config)
     }
 
     /// Loads configuration from a TOML file, with validation.
@@ -872,6 +1791,12 @@ impl Config {
     /// The configuration is serialized to TOML format and written to the
     /// specified path. Parent directories must already exist.
     ///
+    /// The write is atomic and flushed to disk, the previous file is kept as
+    /// a backup, and the file is only readable by its owner.
+    ///
+    /// Passwords are not written; call [`Config::store_secrets`] first so
+    /// that new passwords are kept in the secrets store.
+    ///
     /// # Arguments
     ///
     /// * `path` - Path to write the configuration file
@@ -904,10 +1829,12 @@ impl Config {
         let contents = toml::to_string_pretty(self)?;
 
         // Write to file
-        fs::write(path, contents).map_err(|e| ConfigError::WriteError {
-            path: path_str,
-            source: e,
-        })?;
+        DurableFile::new(path)
+            .write(contents.as_bytes())
+            .map_err(|e| ConfigError::WriteError {
+                path: path_str,
+                source: e,
+            })?;
 
         Ok(())
     }
@@ -934,6 +1861,92 @@ impl Config {
         self.save(path)
     }
 
+    /// Saves configuration and discards every older version of the file.
+    ///
+    /// Used after [`store_secrets`](Self::store_secrets) has moved plaintext
+    /// passwords out, so they don't survive in the backups that
+    /// [`save`](Self::save) keeps. The backups are removed before the write:
+    /// if it fails, the file on disk still holds the passwords and the
+    /// migration runs again on the next start.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`Config::save`].
+    pub fn save_discarding_history<P: AsRef<Path>>(&self, path: P) -> ConfigResult<()> {
+        let path = path.as_ref();
+        let contents = toml::to_string_pretty(self)?;
+
+        let file = DurableFile::new(path);
+        file.discard_history()
+            .and_then(|()| file.with_backups(0).write(contents.as_bytes()))
+            .map_err(|e| ConfigError::WriteError {
+                path: path.display().to_string(),
+                source: e,
+            })
+    }
+
+    /// Moves plaintext passwords and tokens into the secrets store.
+    ///
+    /// Used both to migrate configuration files that still contain
+    /// plaintext passwords and to store passwords of newly added networks.
+    /// Save the store before the configuration, so the configuration never
+    /// refers to a secret that is not on disk.
+    ///
+    /// # Returns
+    ///
+    /// `true` if any password was moved and the configuration needs saving.
+    ///
+    /// # Errors
+    ///
+    /// - `SecretError::EncryptError` - A password could not be encrypted
+    pub fn store_secrets(&mut self, store: &mut SecretStore) -> SecretResult<bool> {
+        let mut moved = false;
+        for network in &mut self.wifi.networks {
+            if let Some(password) = network.password.take() {
+                network.password_id = Some(store.insert(&password)?);
+                moved = true;
+            }
+        }
+        if let Some(password) = self.mqtt.password.take() {
+            self.mqtt.password_id = Some(store.insert(&password)?);
+            moved = true;
+        }
+        if let Some(token) = self.guard.partner_token.take() {
+            self.guard.partner_token_id = Some(store.insert(&token)?);
+            moved = true;
+        }
+        for api_token in &mut self.access.tokens {
+            if let Some(token) = api_token.token.take() {
+                api_token.token_id = Some(store.insert(&token)?);
+                moved = true;
+            }
+        }
+        Ok(moved)
+    }
+
+    /// Removes secrets no longer referenced by a Wi-Fi network, the MQTT
+    /// broker settings, the guard settings or an API token.
+    ///
+    /// Call after the configuration has been saved, so that a failed save
+    /// never leaves the file on disk pointing at a removed secret.
+    pub fn prune_secrets(&self, store: &mut SecretStore) {
+        let referenced: HashSet<&str> = self
+            .wifi
+            .networks
+            .iter()
+            .filter_map(|n| n.password_id.as_deref())
+            .chain(self.mqtt.password_id.as_deref())
+            .chain(self.guard.partner_token_id.as_deref())
+            .chain(
+                self.access
+                    .tokens
+                    .iter()
+                    .filter_map(|t| t.token_id.as_deref()),
+            )
+            .collect();
+        store.retain(&referenced);
+    }
+
     /// Validates all configuration fields.
     ///
     /// Checks all configuration values against their validation rules.
@@ -965,6 +1978,11 @@ impl Config {
         errors.extend(self.wifi.validate());
         errors.extend(self.passes.validate());
         errors.extend(self.system.validate());
+        errors.extend(self.curfew.validate());
+        errors.extend(self.mqtt.validate());
+        errors.extend(self.guard.validate());
+        errors.extend(self.mdns.validate());
+        errors.extend(self.access.validate());
 
         if errors.is_empty() {
             Ok(())
@@ -1000,6 +2018,10 @@ static MAC_ADDRESS_REGEX: Lazy<Regex> = Lazy::new(|| {
     Regex::new(r"^([0-9A-Fa-f]{2}:){5}[0-9A-Fa-f]{2}$").expect("Invalid MAC address regex pattern")
 });
 
+/// Lazy-compiled regex for Bluetooth adapter interface names (`hci0`, `hci1`, ...).
+static ADAPTER_NAME_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^hci[0-9]+$").expect("Invalid adapter name regex pattern"));
+
 /// Lazy-compiled regex for basic timezone format validation.
 ///
 /// Matches IANA timezone formats:
@@ -1010,6 +2032,18 @@ static TIMEZONE_REGEX: Lazy<Regex> = Lazy::new(|| {
     Regex::new(r"^[A-Za-z_]+(/[A-Za-z_]+)*$").expect("Invalid timezone regex pattern")
 });
 
+/// Lazy-compiled regex for MQTT node ids.
+///
+/// Home Assistant only accepts these characters in discovery topics.
+static MQTT_NODE_ID_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^[A-Za-z0-9_-]+$").expect("Invalid MQTT node id regex pattern"));
+
+/// Lazy-compiled regex for mDNS host names: a DNS label (RFC 1123).
+static MDNS_HOSTNAME_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^[A-Za-z0-9]([A-Za-z0-9-]{0,61}[A-Za-z0-9])?$")
+        .expect("Invalid mDNS host name regex pattern")
+});
+
 /// Validates a MAC address string.
 ///
 /// # Arguments
@@ -1034,6 +2068,11 @@ pub fn is_valid_mac_address(address: &str) -> bool {
     MAC_ADDRESS_REGEX.is_match(address)
 }
 
+/// Parses a time of day written as `HH:MM`.
+fn parse_clock_time(value: &str) -> Option<NaiveTime> {
+    NaiveTime::parse_from_str(value, "%H:%M").ok()
+}
+
 /// Validates a timezone string format.
 ///
 /// This performs basic format validation only. It does not verify that
@@ -1065,6 +2104,26 @@ pub fn is_valid_timezone_format(timezone: &str) -> bool {
     TIMEZONE_REGEX.is_match(timezone)
 }
 
+/// Validates an MQTT topic used as a prefix for other topics.
+///
+/// # Example
+///
+/// ```rust
+/// use tether_core::config::is_valid_mqtt_topic_prefix;
+///
+/// assert!(is_valid_mqtt_topic_prefix("tether"));
+/// assert!(is_valid_mqtt_topic_prefix("home/bedroom/tether"));
+/// assert!(!is_valid_mqtt_topic_prefix("tether/#")); // wildcard
+/// assert!(!is_valid_mqtt_topic_prefix("tether/"));  // trailing slash
+/// ```
+#[must_use]
+pub fn is_valid_mqtt_topic_prefix(topic: &str) -> bool {
+    !topic.is_empty()
+        && !topic.starts_with('/')
+        && !topic.ends_with('/')
+        && !topic.contains(['+', '#', '\0'])
+}
+
 // =============================================================================
 // TESTS
 // =============================================================================
@@ -1143,6 +2202,12 @@ mod tests {
             target_address: "A4:C1:38:12:34:56".to_string(),
             target_name: "My iPhone".to_string(),
             rssi_threshold: -60,
+            target_irk: None,
+            probe_mode: ProbeMode::Passive,
+            trust_connection_without_rssi: false,
+            power_cycle_on_failure: false,
+            adapters: Vec::new(),
+            rssi_fusion: RssiFusion::Strongest,
         };
         assert!(config.validate().is_empty());
     }
@@ -1153,6 +2218,12 @@ mod tests {
             target_address: "invalid".to_string(),
             target_name: "My iPhone".to_string(),
             rssi_threshold: -60,
+            target_irk: None,
+            probe_mode: ProbeMode::Passive,
+            trust_connection_without_rssi: false,
+            power_cycle_on_failure: false,
+            adapters: Vec::new(),
+            rssi_fusion: RssiFusion::Strongest,
         };
         let errors = config.validate();
         assert_eq!(errors.len(), 1);
@@ -1168,6 +2239,12 @@ mod tests {
             target_address: "A4:C1:38:12:34:56".to_string(),
             target_name: "   ".to_string(),
             rssi_threshold: -60,
+            target_irk: None,
+            probe_mode: ProbeMode::Passive,
+            trust_connection_without_rssi: false,
+            power_cycle_on_failure: false,
+            adapters: Vec::new(),
+            rssi_fusion: RssiFusion::Strongest,
         };
         let errors = config.validate();
         assert_eq!(errors.len(), 1);
@@ -1183,6 +2260,12 @@ mod tests {
             target_address: "A4:C1:38:12:34:56".to_string(),
             target_name: "My iPhone".to_string(),
             rssi_threshold: 10, // Invalid: positive
+            target_irk: None,
+            probe_mode: ProbeMode::Passive,
+            trust_connection_without_rssi: false,
+            power_cycle_on_failure: false,
+            adapters: Vec::new(),
+            rssi_fusion: RssiFusion::Strongest,
         };
         let errors = config.validate();
         assert_eq!(errors.len(), 1);
@@ -1192,6 +2275,27 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn test_bluetooth_config_validation_adapters() {
+        let mut config = BluetoothConfig {
+            adapters: vec!["hci0".to_string(), "hci1".to_string()],
+            ..BluetoothConfig::default()
+        };
+        assert!(config.validate().is_empty());
+
+        config.adapters = vec!["hci0".to_string(), "wlan0".to_string(), "hci0".to_string()];
+        let errors = config.validate();
+        assert_eq!(errors.len(), 2);
+        assert!(matches!(
+            &errors[0],
+            ConfigError::ValidationError { field, .. } if field == "bluetooth.adapters[1]"
+        ));
+        assert!(matches!(
+            &errors[1],
+            ConfigError::ValidationError { message, .. } if message.contains("Duplicate")
+        ));
+    }
+
     // -------------------------------------------------------------------------
     // WifiConfig Tests
     // -------------------------------------------------------------------------
@@ -1200,7 +2304,11 @@ mod tests {
     fn test_wifi_network_new() {
         let network = WifiNetwork::new("HomeNetwork", "password123", true);
         assert_eq!(network.ssid, "HomeNetwork");
-        assert_eq!(network.password, "password123");
+        assert_eq!(network.password.unwrap().expose(), "password123");
+        assert!(network.password_id.is_none());
+
+        let open = WifiNetwork::new("CoffeeShop", "", false);
+        assert!(open.password.is_none());
         assert!(network.primary);
     }
 
@@ -1292,11 +2400,305 @@ mod tests {
         let config = PassesConfig {
             per_month: 50,                // Invalid: > 31
             pending_per_month: Some(100), // Invalid: > 31
+            storage: StorageBackend::Json,
         };
         let errors = config.validate();
         assert_eq!(errors.len(), 2);
     }
 
+    // -------------------------------------------------------------------------
+    // MqttConfig Tests
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_mqtt_config_default() {
+        let config = MqttConfig::default();
+        assert!(!config.enabled);
+        assert_eq!(config.port, 1883);
+        assert_eq!(config.base_topic, "tether");
+        assert_eq!(config.discovery_prefix, "homeassistant");
+        assert!(config.validate().is_empty());
+    }
+
+    #[test]
+    fn test_mqtt_config_validation() {
+        let config = MqttConfig {
+            host: " ".to_string(),              // Invalid: empty
+            port: 0,                            // Invalid: 0
+            node_id: "bed room".to_string(),    // Invalid: space
+            base_topic: "tether/#".to_string(), // Invalid: wildcard
+            ..MqttConfig::default()
+        };
+        let errors = config.validate();
+        assert_eq!(errors.len(), 4);
+    }
+
+    #[test]
+    fn test_store_secrets_moves_mqtt_password() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut store = SecretStore::open(
+            dir.path().join("secrets.json"),
+            dir.path().join("secrets.key"),
+        )
+        .unwrap();
+
+        let mut config: Config = toml::from_str(
+            r#"
+            [bluetooth]
+            target_address = "A4:C1:38:12:34:56"
+            target_name = "iPhone"
+
+            [mqtt]
+            enabled = true
+            username = "tether"
+            password = "brokerpass"
+            "#,
+        )
+        .unwrap();
+        assert!(config.store_secrets(&mut store).unwrap());
+        let id = config.mqtt.password_id.clone().unwrap();
+        assert_eq!(store.get(&id).unwrap().unwrap().expose(), "brokerpass");
+        assert!(!toml::to_string(&config).unwrap().contains("brokerpass"));
+
+        // Still referenced, so pruning keeps it
+        config.prune_secrets(&mut store);
+        assert!(store.contains(&id));
+
+        config.mqtt.password_id = None;
+        config.prune_secrets(&mut store);
+        assert!(!store.contains(&id));
+    }
+
+    // -------------------------------------------------------------------------
+    // MdnsConfig Tests
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_mdns_config_default() {
+        let config = MdnsConfig::default();
+        assert!(config.enabled);
+        assert_eq!(config.hostname, "tether");
+        assert_eq!(config.instance_name(), "tether");
+        assert!(config.validate().is_empty());
+    }
+
+    #[test]
+    fn test_mdns_config_validation() {
+        let config = MdnsConfig {
+            enabled: true,
+            hostname: "tether.local".to_string(), // Invalid: dot
+            instance_name: Some("bed.room".to_string()), // Invalid: dot
+            web_port: Some(0),                    // Invalid: 0
+        };
+        assert_eq!(config.validate().len(), 3);
+
+        for hostname in ["-tether", "tether-", "", &"a".repeat(64)] {
+            let config = MdnsConfig {
+                hostname: hostname.to_string(),
+                ..MdnsConfig::default()
+            };
+            assert_eq!(config.validate().len(), 1, "{hostname}");
+        }
+
+        let config = MdnsConfig {
+            hostname: "tether-bedroom2".to_string(),
+            instance_name: Some("Bedroom Pi".to_string()),
+            ..MdnsConfig::default()
+        };
+        assert!(config.validate().is_empty());
+        assert_eq!(config.instance_name(), "Bedroom Pi");
+    }
+
+    // -------------------------------------------------------------------------
+    // GuardConfig Tests
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_guard_config_validation() {
+        assert!(GuardConfig::default().validate().is_empty());
+
+        let config = GuardConfig {
+            enabled: true,
+            partner_token: Some(SecretString::new("short")), // Invalid: too short
+            cooling_off_hours: 1000,                         // Invalid: over 30 days
+            ..GuardConfig::default()
+        };
+        assert_eq!(config.validate().len(), 2);
+
+        // Nothing could ever approve a change
+        let config = GuardConfig {
+            enabled: true,
+            cooling_off_hours: 0,
+            ..GuardConfig::default()
+        };
+        assert_eq!(config.validate().len(), 1);
+    }
+
+    #[test]
+    fn test_store_secrets_moves_partner_token() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut store = SecretStore::open(
+            dir.path().join("secrets.json"),
+            dir.path().join("secrets.key"),
+        )
+        .unwrap();
+
+        let mut config: Config = toml::from_str(
+            r#"
+            [bluetooth]
+            target_address = "A4:C1:38:12:34:56"
+            target_name = "iPhone"
+
+            [guard]
+            enabled = true
+            partner_token = "ask-before-you-loosen"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.guard.cooling_off_hours, 24);
+        assert!(config.store_secrets(&mut store).unwrap());
+        let id = config.guard.partner_token_id.clone().unwrap();
+        assert_eq!(
+            store.get(&id).unwrap().unwrap().expose(),
+            "ask-before-you-loosen"
+        );
+        assert!(!toml::to_string(&config).unwrap().contains("loosen"));
+
+        config.prune_secrets(&mut store);
+        assert!(store.contains(&id));
+    }
+
+    #[test]
+    fn test_store_secrets_moves_api_tokens() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut store = SecretStore::open(
+            dir.path().join("secrets.json"),
+            dir.path().join("secrets.key"),
+        )
+        .unwrap();
+
+        let mut config: Config = toml::from_str(
+            r#"
+            [bluetooth]
+            target_address = "A4:C1:38:12:34:56"
+            target_name = "iPhone"
+
+            [access]
+            default_scope = "read"
+
+            [[access.tokens]]
+            name = "assistant"
+            scope = "passes"
+            token = "only-passes-please"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.access.default_scope, ApiScope::Read);
+        assert!(config.validate().is_ok());
+        assert!(config.store_secrets(&mut store).unwrap());
+        let id = config.access.tokens[0].token_id.clone().unwrap();
+        assert_eq!(
+            store.get(&id).unwrap().unwrap().expose(),
+            "only-passes-please"
+        );
+        assert!(!toml::to_string(&config).unwrap().contains("please"));
+
+        config.prune_secrets(&mut store);
+        assert!(store.contains(&id));
+    }
+
+    #[test]
+    fn test_access_validation() {
+        let token = |name: &str, token: Option<&str>| ApiToken {
+            name: name.to_string(),
+            scope: ApiScope::Read,
+            token: token.map(SecretString::new),
+            token_id: None,
+        };
+        assert_eq!(AccessConfig::default().default_scope, ApiScope::Settings);
+
+        let access = AccessConfig {
+            default_scope: ApiScope::Read,
+            tokens: vec![
+                token("dashboard", Some("read-only-dashboard")),
+                token("dashboard", Some("another-dashboard")),
+                token("short", Some("short")),
+                token(" ", None),
+            ],
+        };
+        let fields: Vec<_> = access
+            .validate()
+            .into_iter()
+            .map(|error| match error {
+                ConfigError::ValidationError { field, .. } => field,
+                other => panic!("unexpected error {other:?}"),
+            })
+            .collect();
+        assert_eq!(
+            fields,
+            [
+                "access.tokens[1].name",
+                "access.tokens[2].token",
+                "access.tokens[3].name",
+                "access.tokens[3].token",
+            ]
+        );
+    }
+
+    // -------------------------------------------------------------------------
+    // CurfewConfig Tests
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_curfew_is_active_across_midnight() {
+        use chrono::TimeZone;
+
+        let curfew = CurfewConfig {
+            enabled: true,
+            ..CurfewConfig::default()
+        };
+        let at = |hour, minute| Utc.with_ymd_and_hms(2025, 1, 15, hour, minute, 0).unwrap();
+
+        assert!(curfew.is_active_at(at(23, 0), "UTC"));
+        assert!(curfew.is_active_at(at(5, 59), "UTC"));
+        assert!(!curfew.is_active_at(at(6, 0), "UTC"));
+        assert!(!curfew.is_active_at(at(21, 59), "UTC"));
+
+        // 03:00 UTC is 22:00 the evening before in New York
+        assert!(curfew.is_active_at(at(3, 0), "America/New_York"));
+        assert!(!curfew.is_active_at(at(2, 59), "America/New_York"));
+
+        let disabled = CurfewConfig::default();
+        assert!(!disabled.is_active_at(at(23, 0), "UTC"));
+    }
+
+    #[test]
+    fn test_curfew_config_validation() {
+        assert!(CurfewConfig::default().validate().is_empty());
+
+        let daytime = CurfewConfig {
+            enabled: true,
+            start: "09:00".to_string(),
+            end: "17:00".to_string(),
+        };
+        assert!(daytime.validate().is_empty());
+
+        let malformed = CurfewConfig {
+            start: "10pm".to_string(),
+            ..CurfewConfig::default()
+        };
+        let errors = malformed.validate();
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].to_string().contains("curfew.start"));
+
+        let empty = CurfewConfig {
+            start: "22:00".to_string(),
+            end: "22:00".to_string(),
+            ..CurfewConfig::default()
+        };
+        assert_eq!(empty.validate().len(), 1);
+    }
+
     // -------------------------------------------------------------------------
     // Config Load/Save Tests
     // -------------------------------------------------------------------------
@@ -1317,29 +2719,48 @@ mod tests {
 
     #[test]
     fn test_config_roundtrip() {
-        let temp_file = NamedTempFile::new().unwrap();
-        let path = temp_file.path().to_path_buf();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
 
         let original = Config {
             bluetooth: BluetoothConfig {
                 target_address: "A4:C1:38:12:34:56".to_string(),
                 target_name: "Test Phone".to_string(),
                 rssi_threshold: -70,
+                target_irk: None,
+                probe_mode: ProbeMode::Passive,
+                trust_connection_without_rssi: false,
+                power_cycle_on_failure: false,
+                adapters: Vec::new(),
+                rssi_fusion: RssiFusion::Strongest,
             },
             wifi: WifiConfig {
                 networks: vec![
-                    WifiNetwork::new("HomeNetwork", "password123", true),
-                    WifiNetwork::new("BackupNetwork", "backup456", false),
+                    WifiNetwork {
+                        password_id: Some("0f8c3b1e-6c44-4b7e-9a51-7f3c2f1d9e20".to_string()),
+                        ..WifiNetwork::new("HomeNetwork", "", true)
+                    },
+                    WifiNetwork::new("BackupNetwork", "", false),
                 ],
             },
             passes: PassesConfig {
                 per_month: 5,
                 pending_per_month: Some(10),
+                storage: StorageBackend::Sqlite,
             },
             system: SystemConfig {
                 timezone: "America/New_York".to_string(),
                 onboarding_complete: true,
             },
+            curfew: CurfewConfig {
+                enabled: true,
+                start: "22:30".to_string(),
+                end: "06:30".to_string(),
+            },
+            mqtt: MqttConfig::default(),
+            guard: GuardConfig::default(),
+            mdns: MdnsConfig::default(),
+            access: AccessConfig::default(),
         };
 
         // Save
@@ -1351,6 +2772,24 @@ mod tests {
         assert_eq!(original, loaded);
     }
 
+    #[test]
+    fn test_config_truncated_file_recovers_from_backup() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+
+        let mut config = Config::default();
+        config.system.timezone = "Europe/London".to_string();
+        config.save(&path).unwrap();
+        config.system.timezone = "Asia/Tokyo".to_string();
+        config.save(&path).unwrap();
+
+        // Simulate a power cut leaving an empty file
+        std::fs::write(&path, "").unwrap();
+
+        let loaded = Config::load(&path).unwrap();
+        assert_eq!(loaded.system.timezone, "Europe/London");
+    }
+
     #[test]
     fn test_config_parse_error() {
         let mut temp_file = NamedTempFile::new().unwrap();
@@ -1367,16 +2806,28 @@ mod tests {
                 target_address: "invalid".to_string(),
                 target_name: "".to_string(),
                 rssi_threshold: 10,
+                target_irk: None,
+                probe_mode: ProbeMode::Passive,
+                trust_connection_without_rssi: false,
+                power_cycle_on_failure: false,
+                adapters: Vec::new(),
+                rssi_fusion: RssiFusion::Strongest,
             },
             wifi: WifiConfig::default(),
             passes: PassesConfig {
                 per_month: 100,
                 pending_per_month: None,
+                storage: StorageBackend::Json,
             },
             system: SystemConfig {
                 timezone: "".to_string(),
                 onboarding_complete: false,
             },
+            curfew: CurfewConfig::default(),
+            mqtt: MqttConfig::default(),
+            guard: GuardConfig::default(),
+            mdns: MdnsConfig::default(),
+            access: AccessConfig::default(),
         };
 
         let result = config.validate();
@@ -1397,6 +2848,12 @@ mod tests {
                 target_address: "A4:C1:38:12:34:56".to_string(),
                 target_name: "Jeffrey's iPhone".to_string(),
                 rssi_threshold: -60,
+                target_irk: None,
+                probe_mode: ProbeMode::Passive,
+                trust_connection_without_rssi: false,
+                power_cycle_on_failure: false,
+                adapters: Vec::new(),
+                rssi_fusion: RssiFusion::Strongest,
             },
             wifi: WifiConfig {
                 networks: vec![WifiNetwork::new("HomeNetwork", "secret123", true)],
@@ -1404,11 +2861,17 @@ mod tests {
             passes: PassesConfig {
                 per_month: 3,
                 pending_per_month: None,
+                storage: StorageBackend::Json,
             },
             system: SystemConfig {
                 timezone: "America/New_York".to_string(),
                 onboarding_complete: true,
             },
+            curfew: CurfewConfig::default(),
+            mqtt: MqttConfig::default(),
+            guard: GuardConfig::default(),
+            mdns: MdnsConfig::default(),
+            access: AccessConfig::default(),
         };
 
         let toml_str = toml::to_string_pretty(&config).unwrap();
@@ -1421,6 +2884,63 @@ mod tests {
         assert!(toml_str.contains("target_address = \"A4:C1:38:12:34:56\""));
     }
 
+    #[test]
+    fn test_store_secrets_migrates_plaintext_passwords() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        std::fs::write(
+            &path,
+            r#"
+            [bluetooth]
+            target_address = "A4:C1:38:12:34:56"
+            target_name = "iPhone"
+
+            [[wifi.networks]]
+            ssid = "HomeNetwork"
+            password = "secret123"
+            primary = true
+
+            [[wifi.networks]]
+            ssid = "CoffeeShop"
+            primary = false
+            "#,
+        )
+        .unwrap();
+
+        let mut store = SecretStore::open(
+            dir.path().join("secrets.json"),
+            dir.path().join("secrets.key"),
+        )
+        .unwrap();
+        let mut config = Config::load(&path).unwrap();
+        assert!(config.store_secrets(&mut store).unwrap());
+        store.save().unwrap();
+        config.save(&path).unwrap();
+
+        // The password now lives only in the store
+        assert!(!std::fs::read_to_string(&path)
+            .unwrap()
+            .contains("secret123"));
+        let config = Config::load(&path).unwrap();
+        let id = config.wifi.networks[0].password_id.clone().unwrap();
+        assert_eq!(store.get(&id).unwrap().unwrap().expose(), "secret123");
+        assert!(config.wifi.networks[1].password_id.is_none());
+        assert!(!format!("{config:?}").contains("secret123"));
+
+        // Nothing left to migrate
+        let mut config = config;
+        assert!(!config.store_secrets(&mut store).unwrap());
+        assert_eq!(store.len(), 1);
+
+        // Replacing the networks drops the old secret
+        config.wifi.networks = vec![WifiNetwork::new("NewNetwork", "newpass", true)];
+        assert!(config.store_secrets(&mut store).unwrap());
+        assert_eq!(store.len(), 2);
+        config.prune_secrets(&mut store);
+        assert_eq!(store.len(), 1);
+        assert!(!store.contains(&id));
+    }
+
     #[test]
     fn test_toml_deserialization_with_defaults() {
         let toml_str = r#"
@@ -0,0 +1,583 @@
+//! SQLite storage for pass data.
+//!
+//! [`SqlitePassStore`] keeps the pass allowance and history in an embedded
+//! SQLite database. Using a pass inserts a single row instead of rewriting
+//! the whole history, and history is indexed by month and by timestamp.
+//!
+//! # Migrations
+//!
+//! The schema version is kept in SQLite's `user_version` pragma, and
+//! [`MIGRATIONS`] lists every schema change in order. Opening a database
+//! applies the migrations it has not seen yet, each in its own transaction.
+//! New migrations go at the end of the list; a migration that has shipped
+//! is never edited.
+//!
+//! # Importing
+//!
+//! [`SqlitePassStore::import_json`] copies an existing `passes.json` into an
+//! empty database, so switching from [`JsonPassStore`](crate::passes::JsonPassStore)
+//! keeps the history.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use tether_core::database::SqlitePassStore;
+//! use tether_core::passes::PassManager;
+//! use std::path::Path;
+//!
+//! let mut store = SqlitePassStore::open(Path::new("/var/lib/tether/tether.db"))?;
+//! store.import_json(Path::new("/var/lib/tether/passes.json"))?;
+//!
+//! let manager = PassManager::open(Box::new(store), 3)?;
+//! println!("Remaining: {}", manager.remaining());
+//! # Ok::<(), tether_core::passes::PassError>(())
+//! ```
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use rusqlite::types::Type;
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use std::time::Instant;
+
+use crate::metrics;
+use crate::passes::{JsonPassStore, PassEntry, PassError, PassResult, PassState, PassStore};
+
+/// Schema migrations, applied in order.
+///
+/// Migration `n` (1-based) brings the database to `user_version = n`.
+pub const MIGRATIONS: &[&str] = &[
+    // 1: Pass allowance and history
+    "CREATE TABLE pass_state (
+        id INTEGER PRIMARY KEY CHECK (id = 1),
+        current_month TEXT NOT NULL,
+        remaining INTEGER NOT NULL,
+        per_month INTEGER NOT NULL,
+        pending_per_month INTEGER
+    );
+    CREATE TABLE pass_history (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        month TEXT NOT NULL,
+        used_at_utc TEXT NOT NULL,
+        reason TEXT NOT NULL
+    );
+    CREATE INDEX pass_history_month ON pass_history (month, id);
+    CREATE INDEX pass_history_used_at ON pass_history (used_at_utc);",
+];
+
+/// Stores pass data in an embedded SQLite database.
+///
+/// Timestamps are stored as RFC 3339 text with nanosecond precision in UTC,
+/// so they sort chronologically as strings.
+#[derive(Debug)]
+pub struct SqlitePassStore {
+    /// The database path.
+    path: PathBuf,
+
+    /// The database connection.
+    ///
+    /// Behind a mutex so the store can be shared between threads.
+    conn: Mutex<Connection>,
+}
+
+impl SqlitePassStore {
+    /// Opens the database, creating it and applying migrations as needed.
+    ///
+    /// The database and its `-wal` and `-shm` files are only readable by
+    /// their owner (mode `0600`).
+    ///
+    /// # Errors
+    ///
+    /// - `PassError::CreateDirError` - Failed to create parent directories
+    /// - `PassError::DatabaseError` - The database could not be opened or migrated
+    /// - `PassError::UnsupportedSchema` - The database is from a newer version
+    pub fn open(path: &Path) -> PassResult<Self> {
+        if let Some(parent) = path.parent() {
+            if !parent.exists() {
+                fs::create_dir_all(parent).map_err(|source| PassError::CreateDirError {
+                    path: path.to_path_buf(),
+                    source,
+                })?;
+            }
+        }
+
+        // Create the file before SQLite does, so it is never readable by others
+        #[cfg(unix)]
+        if !path.exists() {
+            use std::os::unix::fs::OpenOptionsExt;
+            fs::OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .mode(0o600)
+                .open(path)
+                .map_err(|source| PassError::WriteError {
+                    path: path.to_path_buf(),
+                    source,
+                })?;
+        }
+        let mut conn = Connection::open(path).map_err(|e| db_error(path, e))?;
+
+        // Survive power loss: every commit is flushed before it returns
+        conn.execute_batch("PRAGMA journal_mode = WAL; PRAGMA synchronous = FULL;")
+            .map_err(|e| db_error(path, e))?;
+
+        migrate(&mut conn, path)?;
+        restrict_permissions(path)?;
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            conn: Mutex::new(conn),
+        })
+    }
+
+    /// Returns the database path.
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the schema version of the database.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the version cannot be read.
+    pub fn schema_version(&self) -> PassResult<u32> {
+        schema_version(&self.conn(), &self.path)
+    }
+
+    /// Copies the contents of a `passes.json` file into an empty database.
+    ///
+    /// Nothing is imported if the database already holds pass data or the
+    /// file does not exist, so this is safe to call on every start. The
+    /// JSON file is left in place.
+    ///
+    /// # Returns
+    ///
+    /// The number of history entries imported, or `None` if nothing was
+    /// imported.
+    ///
+    /// # Errors
+    ///
+    /// - `PassError::ReadError` / `ParseError` - The JSON file is unusable
+    /// - `PassError::DatabaseError` - The data could not be written
+    pub fn import_json(&mut self, json_path: &Path) -> PassResult<Option<usize>> {
+        if self.load_state()?.is_some() || !json_path.exists() {
+            return Ok(None);
+        }
+
+        let json = JsonPassStore::open(json_path)?;
+        let Some(data) = json.data() else {
+            return Ok(None);
+        };
+
+        let path = self.path.clone();
+        let mut conn = self.conn();
+        let tx = conn.transaction().map_err(|e| db_error(&path, e))?;
+        write_state(&tx, &data.state()).map_err(|e| db_error(&path, e))?;
+
+        // Months in order, entries within a month as recorded
+        let mut months: Vec<&String> = data.history.keys().collect();
+        months.sort();
+        let mut imported = 0;
+        for month in months {
+            for entry in &data.history[month] {
+                insert_entry(&tx, month, entry).map_err(|e| db_error(&path, e))?;
+                imported += 1;
+            }
+        }
+
+        tx.commit().map_err(|e| db_error(&path, e))?;
+        drop(conn);
+        Ok(Some(imported))
+    }
+
+    /// Locks the connection.
+    fn conn(&self) -> MutexGuard<'_, Connection> {
+        self.conn.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Runs a query returning pass entries.
+    fn query_entries(
+        &self,
+        sql: &str,
+        params: impl rusqlite::Params,
+    ) -> PassResult<Vec<PassEntry>> {
+        let conn = self.conn();
+        let mut stmt = conn
+            .prepare_cached(sql)
+            .map_err(|e| db_error(&self.path, e))?;
+        let entries = stmt
+            .query_map(params, |row| read_entry(row, 0))
+            .and_then(Iterator::collect)
+            .map_err(|e| db_error(&self.path, e));
+        drop(stmt);
+        drop(conn);
+        entries
+    }
+}
+
+impl PassStore for SqlitePassStore {
+    fn load_state(&self) -> PassResult<Option<PassState>> {
+        self.conn()
+            .query_row(
+                "SELECT current_month, remaining, per_month, pending_per_month
+                 FROM pass_state WHERE id = 1",
+                [],
+                |row| {
+                    Ok(PassState {
+                        current_month: row.get(0)?,
+                        remaining: row.get(1)?,
+                        per_month: row.get(2)?,
+                        pending_per_month: row.get(3)?,
+                    })
+                },
+            )
+            .optional()
+            .map_err(|e| db_error(&self.path, e))
+    }
+
+    fn save_state(&mut self, state: &PassState) -> PassResult<()> {
+        let started = Instant::now();
+        let result = write_state(&self.conn(), state).map_err(|e| db_error(&self.path, e));
+        metrics::record_storage_write(&self.path, started);
+        result
+    }
+
+    fn record_pass(&mut self, state: &PassState, entry: &PassEntry) -> PassResult<()> {
+        let started = Instant::now();
+        let mut conn = self.conn();
+        let result = conn.transaction().and_then(|tx| {
+            insert_entry(&tx, &state.current_month, entry)?;
+            write_state(&tx, state)?;
+            tx.commit()
+        });
+        drop(conn);
+        metrics::record_storage_write(&self.path, started);
+        result.map_err(|e| db_error(&self.path, e))
+    }
+
+    fn history(&self, month: &str) -> PassResult<Vec<PassEntry>> {
+        self.query_entries(
+            "SELECT used_at_utc, reason FROM pass_history WHERE month = ?1 ORDER BY id",
+            params![month],
+        )
+    }
+
+    fn history_range(&self, from: DateTime<Utc>, to: DateTime<Utc>) -> PassResult<Vec<PassEntry>> {
+        self.query_entries(
+            "SELECT used_at_utc, reason FROM pass_history
+             WHERE used_at_utc >= ?1 AND used_at_utc < ?2
+             ORDER BY used_at_utc, id",
+            params![format_timestamp(from), format_timestamp(to)],
+        )
+    }
+
+    fn all_history(&self) -> PassResult<HashMap<String, Vec<PassEntry>>> {
+        let conn = self.conn();
+        let mut stmt = conn
+            .prepare_cached("SELECT month, used_at_utc, reason FROM pass_history ORDER BY id")
+            .map_err(|e| db_error(&self.path, e))?;
+        let rows: Vec<(String, PassEntry)> = stmt
+            .query_map([], |row| {
+                Ok((row.get::<_, String>(0)?, read_entry(row, 1)?))
+            })
+            .and_then(Iterator::collect)
+            .map_err(|e| db_error(&self.path, e))?;
+        drop(stmt);
+        drop(conn);
+
+        let mut history: HashMap<String, Vec<PassEntry>> = HashMap::new();
+        for (month, entry) in rows {
+            history.entry(month).or_default().push(entry);
+        }
+        Ok(history)
+    }
+}
+
+// ============================================================================
+// HELPER FUNCTIONS
+// ============================================================================
+
+/// Makes the database and the WAL files next to it readable by the owner only.
+///
+/// SQLite creates the `-wal` and `-shm` files with the database's mode, but
+/// files left by an older version may have been created with the umask.
+#[cfg(unix)]
+fn restrict_permissions(path: &Path) -> PassResult<()> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let mut files = vec![path.to_path_buf()];
+    for suffix in ["-wal", "-shm"] {
+        let mut name = path.as_os_str().to_os_string();
+        name.push(suffix);
+        files.push(PathBuf::from(name));
+    }
+    for file in files.into_iter().filter(|file| file.exists()) {
+        fs::set_permissions(&file, fs::Permissions::from_mode(0o600))
+            .map_err(|source| PassError::WriteError { path: file, source })?;
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+#[allow(clippy::unnecessary_wraps)]
+const fn restrict_permissions(_path: &Path) -> PassResult<()> {
+    Ok(())
+}
+
+/// Applies the migrations the database has not seen yet.
+fn migrate(conn: &mut Connection, path: &Path) -> PassResult<()> {
+    let version = schema_version(conn, path)?;
+    let supported = u32::try_from(MIGRATIONS.len()).unwrap_or(u32::MAX);
+    if version > supported {
+        return Err(PassError::UnsupportedSchema {
+            path: path.to_path_buf(),
+            found: version,
+            supported,
+        });
+    }
+
+    for (migration, target) in MIGRATIONS.iter().zip(1..).skip(version as usize) {
+        let tx = conn.transaction().map_err(|e| db_error(path, e))?;
+        tx.execute_batch(migration).map_err(|e| db_error(path, e))?;
+        tx.pragma_update(None, "user_version", target)
+            .map_err(|e| db_error(path, e))?;
+        tx.commit().map_err(|e| db_error(path, e))?;
+        tracing::info!(path = %path.display(), version = target, "Migrated database schema");
+    }
+    Ok(())
+}
+
+/// Reads the schema version from the `user_version` pragma.
+fn schema_version(conn: &Connection, path: &Path) -> PassResult<u32> {
+    conn.pragma_query_value(None, "user_version", |row| row.get(0))
+        .map_err(|e| db_error(path, e))
+}
+
+/// Inserts or replaces the single pass allowance row.
+fn write_state(conn: &Connection, state: &PassState) -> rusqlite::Result<()> {
+    conn.execute(
+        "INSERT INTO pass_state (id, current_month, remaining, per_month, pending_per_month)
+         VALUES (1, ?1, ?2, ?3, ?4)
+         ON CONFLICT (id) DO UPDATE SET
+             current_month = excluded.current_month,
+             remaining = excluded.remaining,
+             per_month = excluded.per_month,
+             pending_per_month = excluded.pending_per_month",
+        params![
+            state.current_month,
+            state.remaining,
+            state.per_month,
+            state.pending_per_month
+        ],
+    )?;
+    Ok(())
+}
+
+/// Appends a history entry for a month.
+fn insert_entry(conn: &Connection, month: &str, entry: &PassEntry) -> rusqlite::Result<()> {
+    conn.execute(
+        "INSERT INTO pass_history (month, used_at_utc, reason) VALUES (?1, ?2, ?3)",
+        params![month, format_timestamp(entry.used_at_utc), entry.reason],
+    )?;
+    Ok(())
+}
+
+/// Reads a pass entry from the timestamp and reason columns at `index`.
+fn read_entry(row: &Row<'_>, index: usize) -> rusqlite::Result<PassEntry> {
+    let used_at: String = row.get(index)?;
+    let used_at_utc = DateTime::parse_from_rfc3339(&used_at)
+        .map_err(|e| rusqlite::Error::FromSqlConversionFailure(index, Type::Text, Box::new(e)))?
+        .with_timezone(&Utc);
+    Ok(PassEntry {
+        used_at_utc,
+        reason: row.get(index + 1)?,
+    })
+}
+
+/// Formats a timestamp so that string order matches chronological order.
+fn format_timestamp(timestamp: DateTime<Utc>) -> String {
+    timestamp.to_rfc3339_opts(SecondsFormat::Nanos, true)
+}
+
+/// Wraps a SQLite error with the database path.
+fn db_error(path: &Path, source: rusqlite::Error) -> PassError {
+    PassError::DatabaseError {
+        path: path.to_path_buf(),
+        source,
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::passes::{current_month_string, PassManager};
+    use chrono::TimeZone;
+    use tempfile::tempdir;
+
+    fn at(month: u32, day: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2025, month, day, 3, 30, 0).unwrap()
+    }
+
+    #[test]
+    fn test_open_applies_migrations() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("nested").join("tether.db");
+
+        let store = SqlitePassStore::open(&path).unwrap();
+        assert_eq!(store.schema_version().unwrap() as usize, MIGRATIONS.len());
+        assert!(store.load_state().unwrap().is_none());
+        drop(store);
+
+        // Reopening is a no-op
+        let store = SqlitePassStore::open(&path).unwrap();
+        assert_eq!(store.schema_version().unwrap() as usize, MIGRATIONS.len());
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_open_restricts_wal_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("tether.db");
+        drop(SqlitePassStore::open(&path).unwrap());
+
+        // WAL files written with a permissive umask by an older version
+        let wal_files = [
+            dir.path().join("tether.db-wal"),
+            dir.path().join("tether.db-shm"),
+        ];
+        for file in &wal_files {
+            if !file.exists() {
+                fs::write(file, "").unwrap();
+            }
+            fs::set_permissions(file, fs::Permissions::from_mode(0o644)).unwrap();
+        }
+
+        let mut store = SqlitePassStore::open(&path).unwrap();
+        let mut state = PassState::new(3);
+        state.current_month = "2025-01".to_string();
+        store
+            .record_pass(
+                &state,
+                &PassEntry::with_timestamp(at(1, 10), "Late shift".to_string()),
+            )
+            .unwrap();
+
+        for file in &wal_files {
+            let mode = fs::metadata(file).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600, "{}", file.display());
+        }
+    }
+
+    #[test]
+    fn test_rejects_newer_schema() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("tether.db");
+        SqlitePassStore::open(&path).unwrap();
+
+        let conn = Connection::open(&path).unwrap();
+        conn.pragma_update(None, "user_version", 99).unwrap();
+        drop(conn);
+
+        let result = SqlitePassStore::open(&path);
+        assert!(matches!(
+            result,
+            Err(PassError::UnsupportedSchema { found: 99, .. })
+        ));
+    }
+
+    #[test]
+    fn test_history_by_month_and_range() {
+        let dir = tempdir().unwrap();
+        let mut store = SqlitePassStore::open(&dir.path().join("tether.db")).unwrap();
+
+        let mut state = PassState::new(3);
+        for (month, day) in [(1, 10), (1, 25), (2, 3)] {
+            state.current_month = format!("2025-{month:02}");
+            let entry = PassEntry::with_timestamp(at(month, day), format!("{month}/{day}"));
+            store.record_pass(&state, &entry).unwrap();
+        }
+
+        let january = store.history("2025-01").unwrap();
+        assert_eq!(january.len(), 2);
+        assert_eq!(january[0].used_at_utc, at(1, 10));
+        assert_eq!(january[1].reason, "1/25");
+        assert_eq!(store.history("2025-03").unwrap(), Vec::new());
+
+        let range = store.history_range(at(1, 25), at(2, 3)).unwrap();
+        assert_eq!(range.len(), 1);
+        assert_eq!(range[0].reason, "1/25");
+
+        let all = store.all_history().unwrap();
+        assert_eq!(all["2025-01"].len(), 2);
+        assert_eq!(all["2025-02"].len(), 1);
+        assert_eq!(store.load_state().unwrap(), Some(state));
+    }
+
+    #[test]
+    fn test_import_json() {
+        let dir = tempdir().unwrap();
+        let json_path = dir.path().join("passes.json");
+        {
+            let mut manager = PassManager::load_or_create(&json_path, 3).unwrap();
+            manager.use_pass("First".to_string()).unwrap();
+            manager.use_pass("Second".to_string()).unwrap();
+        }
+
+        let mut store = SqlitePassStore::open(&dir.path().join("tether.db")).unwrap();
+        assert_eq!(store.import_json(&json_path).unwrap(), Some(2));
+        // Only an empty database is imported into
+        assert_eq!(store.import_json(&json_path).unwrap(), None);
+
+        let manager = PassManager::open(Box::new(store), 3).unwrap();
+        assert_eq!(manager.remaining(), 1);
+        let history = manager.history(&current_month_string()).unwrap();
+        let reasons: Vec<&str> = history.iter().map(|e| e.reason.as_str()).collect();
+        assert_eq!(reasons, ["First", "Second"]);
+    }
+
+    #[test]
+    fn test_import_missing_json() {
+        let dir = tempdir().unwrap();
+        let mut store = SqlitePassStore::open(&dir.path().join("tether.db")).unwrap();
+        assert_eq!(
+            store.import_json(&dir.path().join("passes.json")).unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_manager_persists_across_opens() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("tether.db");
+
+        {
+            let store = SqlitePassStore::open(&path).unwrap();
+            let mut manager = PassManager::open(Box::new(store), 5).unwrap();
+            manager.use_pass("Late shift".to_string()).unwrap();
+            manager.set_per_month(2).unwrap();
+        }
+
+        let store = SqlitePassStore::open(&path).unwrap();
+        let manager = PassManager::open(Box::new(store), 3).unwrap();
+        assert_eq!(manager.remaining(), 4);
+        assert_eq!(manager.per_month(), 5);
+        assert_eq!(manager.pending_per_month(), Some(2));
+        assert_eq!(manager.history(&current_month_string()).unwrap().len(), 1);
+    }
+}
@@ -55,6 +55,14 @@ pub enum TetherError {
     #[error("Bluetooth scan failed: {0}")]
     BluetoothScanFailed(String),
 
+    /// Pairing with a Bluetooth device failed or was rejected.
+    #[error("Bluetooth pairing failed: {0}")]
+    BluetoothPairingFailed(String),
+
+    /// An Identity Resolving Key was not 32 hex digits.
+    #[error("Invalid Identity Resolving Key. Expected 32 hex digits.")]
+    InvalidIrk,
+
     /// The configured Bluetooth device was not found during scanning.
     #[error("Device not found: '{0}'. Ensure the device is powered on and within range.")]
     DeviceNotFound(String),
@@ -131,6 +139,8 @@ impl TetherError {
             Self::BluetoothAdapterNotFound
                 | Self::BluetoothAdapterPoweredOff
                 | Self::BluetoothScanFailed(_)
+                | Self::BluetoothPairingFailed(_)
+                | Self::InvalidIrk
                 | Self::DeviceNotFound(_)
         )
     }
@@ -190,7 +200,8 @@ impl TetherError {
             // 400 Bad Request - malformed input
             Self::InvalidMonthFormat(_)
             | Self::EmptyPassReason
-            | Self::PassReasonTooLong { .. } => 400,
+            | Self::PassReasonTooLong { .. }
+            | Self::InvalidIrk => 400,
 
             // 403 Forbidden - understood but refused
             Self::NoPassesRemaining => 403,
@@ -201,6 +212,9 @@ impl TetherError {
             // 422 Unprocessable Entity - semantic errors
             Self::ConfigParseError(_) | Self::ConfigValidationError(_) => 422,
 
+            // 424 Failed Dependency - the remote device did not cooperate
+            Self::BluetoothPairingFailed(_) => 424,
+
             // 500 Internal Server Error - server-side issues
             Self::PersistenceError(_) | Self::IoError(_) => 500,
 
@@ -219,6 +233,8 @@ impl TetherError {
             Self::BluetoothAdapterNotFound => "BLUETOOTH_ADAPTER_NOT_FOUND",
             Self::BluetoothAdapterPoweredOff => "BLUETOOTH_ADAPTER_POWERED_OFF",
             Self::BluetoothScanFailed(_) => "BLUETOOTH_SCAN_FAILED",
+            Self::BluetoothPairingFailed(_) => "BLUETOOTH_PAIRING_FAILED",
+            Self::InvalidIrk => "INVALID_IRK",
             Self::DeviceNotFound(_) => "DEVICE_NOT_FOUND",
             Self::NoPassesRemaining => "NO_PASSES_REMAINING",
             Self::InvalidMonthFormat(_) => "INVALID_MONTH_FORMAT",
@@ -283,10 +299,19 @@ impl From<crate::passes::PassError> for TetherError {
                 path.display(),
                 source
             )),
+            err @ (PassError::DatabaseError { .. } | PassError::UnsupportedSchema { .. }) => {
+                Self::PersistenceError(err.to_string())
+            }
         }
     }
 }
 
+impl From<crate::secrets::SecretError> for TetherError {
+    fn from(err: crate::secrets::SecretError) -> Self {
+        Self::PersistenceError(err.to_string())
+    }
+}
+
 impl From<crate::bluetooth::BluetoothError> for TetherError {
     fn from(err: crate::bluetooth::BluetoothError) -> Self {
         use crate::bluetooth::BluetoothError;
@@ -302,6 +327,8 @@ impl From<crate::bluetooth::BluetoothError> for TetherError {
             }
             BluetoothError::SessionInitFailed { message } => Self::BluetoothScanFailed(message),
             BluetoothError::DiscoveryFailed { message } => Self::BluetoothScanFailed(message),
+            BluetoothError::InvalidIrk => Self::InvalidIrk,
+            BluetoothError::PairingFailed { message } => Self::BluetoothPairingFailed(message),
             BluetoothError::Internal { message } => Self::BluetoothScanFailed(message),
         }
     }
@@ -321,6 +348,8 @@ mod tests {
         assert!(TetherError::BluetoothAdapterNotFound.is_bluetooth_error());
         assert!(TetherError::BluetoothAdapterPoweredOff.is_bluetooth_error());
         assert!(TetherError::BluetoothScanFailed("test".into()).is_bluetooth_error());
+        assert!(TetherError::BluetoothPairingFailed("test".into()).is_bluetooth_error());
+        assert!(TetherError::InvalidIrk.is_bluetooth_error());
         assert!(TetherError::DeviceNotFound("iPhone".into()).is_bluetooth_error());
 
         assert!(!TetherError::NoPassesRemaining.is_bluetooth_error());
@@ -376,6 +405,7 @@ mod tests {
             TetherError::InvalidMonthFormat("bad".into()).http_status_code(),
             400
         );
+        assert_eq!(TetherError::InvalidIrk.http_status_code(), 400);
         assert_eq!(TetherError::NoPassesRemaining.http_status_code(), 403);
         assert_eq!(
             TetherError::ConfigNotFound(PathBuf::new()).http_status_code(),
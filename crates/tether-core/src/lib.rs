@@ -14,8 +14,11 @@
 //!
 //! - [`bluetooth`] - Bluetooth Low Energy scanning and RSSI-based proximity detection
 //! - [`config`] - Application configuration loading, saving, and validation
+//! - [`database`] - SQLite storage for pass history, with schema migrations
 //! - [`passes`] - Monthly pass allocation, usage tracking, and history
-//! - [`storage`] - Persistent storage for pass data using JSON files
+//! - [`secrets`] - Encrypted storage for credentials such as Wi-Fi passwords
+//! - [`storage`] - Crash-safe file persistence with backups, and default storage paths
+//! - [`metrics`] - Instrumentation of Bluetooth scans and storage writes
 //! - [`error`] - Unified error types for the crate
 //! - [`types`] - Shared types and OpenAPI schemas
 
@@ -25,8 +28,11 @@
 
 pub mod bluetooth;
 pub mod config;
+pub mod database;
 pub mod error;
+pub mod metrics;
 pub mod passes;
+pub mod secrets;
 pub mod storage;
 pub mod types;
 
@@ -34,17 +40,23 @@ pub mod types;
 #[cfg(any(feature = "mock-bluetooth", not(feature = "bluetooth")))]
 pub use bluetooth::MockDevice;
 pub use bluetooth::{
-    BluetoothConfig as BtConfig, BluetoothDevice, BluetoothError, BluetoothResult,
-    BluetoothScanner, ProximityResult,
+    AdapterChange, AdapterInfo, AdapterMonitor, BluetoothAddressType, BluetoothConfig as BtConfig,
+    BluetoothDevice, BluetoothError, BluetoothResult, BluetoothScanner, DetectionMethod,
+    DeviceWatch, IdentityResolvingKey, PairedDevice, ProbeMode, ProximityResult, RssiFusion,
 };
 pub use config::{
-    is_valid_mac_address, is_valid_timezone_format, BluetoothConfig, Config, ConfigError,
-    ConfigResult, PassesConfig, SystemConfig, WifiConfig, WifiNetwork,
+    is_valid_mac_address, is_valid_timezone_format, AccessConfig, ApiScope, ApiToken,
+    BluetoothConfig, Config, ConfigError, ConfigResult, CurfewConfig, GuardConfig, MdnsConfig,
+    MqttConfig, PassesConfig, SystemConfig, WifiConfig, WifiNetwork,
 };
+pub use database::SqlitePassStore;
 pub use error::{Error, Result, TetherError};
 pub use passes::{
-    current_month_string, is_valid_month_string, PassData, PassEntry, PassError, PassManager,
-    PassResult, MAX_REASON_LENGTH,
+    current_month_string, is_valid_month_string, JsonPassStore, PassData, PassEntry, PassError,
+    PassManager, PassResult, PassState, PassStore, MAX_REASON_LENGTH,
+};
+pub use secrets::{SecretError, SecretResult, SecretStore, SecretString};
+pub use storage::{
+    default_data_dir, default_database_path, default_passes_path, DurableFile, StorageBackend,
 };
-pub use storage::{default_data_dir, default_passes_path};
 pub use types::HealthResponse;
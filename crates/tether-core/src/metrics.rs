@@ -0,0 +1,120 @@
+//! Instrumentation of Bluetooth scans and storage writes.
+//!
+//! Measurements are recorded through the [`metrics`](https://docs.rs/metrics)
+//! facade and are only kept once the application installs a recorder;
+//! `tether-server` exports them in Prometheus format at `/metrics`. Without
+//! the `metrics` feature, the recording functions compile to nothing.
+//!
+//! # Metrics
+//!
+//! | Name | Type | Labels |
+//! |------|------|--------|
+//! | [`SCAN_DURATION`] | histogram | `operation` |
+//! | [`SCAN_FAILURES`] | counter | `operation` |
+//! | [`DEVICE_RSSI`] | gauge | `device` |
+//! | [`STORAGE_WRITE_DURATION`] | histogram | `file` |
+//!
+//! `operation` is `proximity` or `discovery`; `file` is the name of the
+//! file or database written.
+
+use std::path::Path;
+use std::time::Instant;
+
+/// Duration of Bluetooth scans in seconds, successful or not.
+pub const SCAN_DURATION: &str = "tether_bluetooth_scan_duration_seconds";
+
+/// Number of Bluetooth scans that failed.
+pub const SCAN_FAILURES: &str = "tether_bluetooth_scan_failures_total";
+
+/// Last signal strength seen for a tracked device, in dBm.
+pub const DEVICE_RSSI: &str = "tether_bluetooth_device_rssi_dbm";
+
+/// Duration of durable writes to files and the pass database in seconds.
+pub const STORAGE_WRITE_DURATION: &str = "tether_storage_write_duration_seconds";
+
+/// Kind of Bluetooth scan being measured.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ScanOperation {
+    /// A proximity check of the configured device.
+    Proximity,
+    /// Discovery of all nearby devices.
+    Discovery,
+}
+
+impl ScanOperation {
+    #[cfg_attr(not(feature = "metrics"), allow(dead_code))]
+    const fn as_str(self) -> &'static str {
+        match self {
+            Self::Proximity => "proximity",
+            Self::Discovery => "discovery",
+        }
+    }
+}
+
+/// Registers descriptions of the metrics above with the installed recorder.
+#[cfg_attr(not(feature = "metrics"), allow(clippy::missing_const_for_fn))]
+pub fn describe() {
+    #[cfg(feature = "metrics")]
+    {
+        use metrics::{describe_counter, describe_gauge, describe_histogram, Unit};
+
+        describe_histogram!(SCAN_DURATION, Unit::Seconds, "Duration of Bluetooth scans");
+        describe_counter!(SCAN_FAILURES, "Bluetooth scans that failed");
+        describe_gauge!(
+            DEVICE_RSSI,
+            "Last signal strength seen for a tracked device in dBm"
+        );
+        describe_histogram!(
+            STORAGE_WRITE_DURATION,
+            Unit::Seconds,
+            "Duration of durable writes to files and the pass database"
+        );
+    }
+}
+
+/// Records a finished scan that started at `started`.
+#[cfg_attr(
+    not(feature = "metrics"),
+    allow(unused_variables, clippy::missing_const_for_fn)
+)]
+pub(crate) fn record_scan(operation: ScanOperation, started: Instant, succeeded: bool) {
+    #[cfg(feature = "metrics")]
+    {
+        let operation = operation.as_str();
+        metrics::histogram!(SCAN_DURATION, "operation" => operation)
+            .record(started.elapsed().as_secs_f64());
+        if !succeeded {
+            metrics::counter!(SCAN_FAILURES, "operation" => operation).increment(1);
+        }
+    }
+}
+
+/// Records the signal strength of a tracked device, if it was seen.
+#[cfg_attr(
+    not(feature = "metrics"),
+    allow(unused_variables, clippy::missing_const_for_fn)
+)]
+pub(crate) fn record_rssi(device_address: &str, rssi: Option<i16>) {
+    #[cfg(feature = "metrics")]
+    if let Some(rssi) = rssi {
+        metrics::gauge!(DEVICE_RSSI, "device" => device_address.to_uppercase())
+            .set(f64::from(rssi));
+    }
+}
+
+/// Records a durable write to `path` that started at `started`.
+#[cfg_attr(
+    not(feature = "metrics"),
+    allow(unused_variables, clippy::missing_const_for_fn)
+)]
+pub(crate) fn record_storage_write(path: &Path, started: Instant) {
+    #[cfg(feature = "metrics")]
+    {
+        let file = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        metrics::histogram!(STORAGE_WRITE_DURATION, "file" => file)
+            .record(started.elapsed().as_secs_f64());
+    }
+}
@@ -13,8 +13,11 @@
 //!
 //! # Persistence
 //!
-//! Data is stored in JSON format at the configured path. The file is
-//! atomically updated on each mutation to prevent corruption.
+//! `PassManager` persists through a [`PassStore`]. [`JsonPassStore`] keeps
+//! everything in one JSON file that is atomically rewritten on each
+//! mutation; [`SqlitePassStore`](crate::database::SqlitePassStore) keeps an
+//! indexed history in an embedded database and only appends a row when a
+//! pass is used.
 //!
 //! # Example
 //!
@@ -32,7 +35,7 @@
 //! manager.use_pass("Medical appointment tonight".to_string())?;
 //!
 //! // View history
-//! let history = manager.history(&tether_core::passes::current_month_string());
+//! let history = manager.history(&tether_core::passes::current_month_string())?;
 //! for entry in history {
 //!     println!("{}: {}", entry.used_at_utc, entry.reason);
 //! }
@@ -42,12 +45,15 @@
 use chrono::{DateTime, Datelike, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::fmt;
 use std::fs;
 use std::io;
 use std::path::{Path, PathBuf};
 use thiserror::Error;
 use utoipa::ToSchema;
 
+use crate::storage::{DurableFile, LoadError};
+
 // ============================================================================
 // ERROR TYPES
 // ============================================================================
@@ -108,6 +114,30 @@ pub enum PassError {
         source: io::Error,
     },
 
+    /// A database operation failed.
+    #[error("database error at {}: {source}", path.display())]
+    DatabaseError {
+        /// The database path.
+        path: PathBuf,
+        /// The underlying SQLite error.
+        #[source]
+        source: rusqlite::Error,
+    },
+
+    /// The database was written by a newer version of tether.
+    #[error(
+        "database at {} has schema version {found}, but only versions up to {supported} are supported",
+        path.display()
+    )]
+    UnsupportedSchema {
+        /// The database path.
+        path: PathBuf,
+        /// The schema version found in the database.
+        found: u32,
+        /// The newest schema version this build understands.
+        supported: u32,
+    },
+
     /// The reason provided for using a pass was empty.
     #[error("reason cannot be empty when using a pass")]
     EmptyReason,
@@ -161,7 +191,7 @@ impl PassEntry {
 
     /// Creates a pass entry with a specific timestamp (for testing).
     #[cfg(test)]
-    fn with_timestamp(used_at_utc: DateTime<Utc>, reason: String) -> Self {
+    pub(crate) fn with_timestamp(used_at_utc: DateTime<Utc>, reason: String) -> Self {
         Self {
             used_at_utc,
             reason,
@@ -169,9 +199,38 @@ impl PassEntry {
     }
 }
 
-/// The persisted data structure for pass management.
+/// The pass allowance for the current month, without history.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PassState {
+    /// The current month in "YYYY-MM" format (e.g., "2025-01").
+    pub current_month: String,
+
+    /// The number of passes remaining for the current month.
+    pub remaining: u32,
+
+    /// The number of passes granted per month.
+    pub per_month: u32,
+
+    /// Pending per-month value to apply at the next month reset.
+    pub pending_per_month: Option<u32>,
+}
+
+impl PassState {
+    /// Creates the state for a fresh start in the current month.
+    #[must_use]
+    pub fn new(per_month: u32) -> Self {
+        Self {
+            current_month: current_month_string(),
+            remaining: per_month,
+            per_month,
+            pending_per_month: None,
+        }
+    }
+}
+
+/// The contents of a `passes.json` file.
 ///
-/// This struct is serialized to and from JSON for persistence.
+/// This struct is serialized to and from JSON by [`JsonPassStore`].
 /// It contains all information needed to track passes across months.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct PassData {
@@ -212,14 +271,224 @@ impl Default for PassData {
     }
 }
 
+impl PassData {
+    /// Returns the pass allowance stored in this data.
+    #[must_use]
+    pub fn state(&self) -> PassState {
+        PassState {
+            current_month: self.current_month.clone(),
+            remaining: self.remaining,
+            per_month: self.per_month,
+            pending_per_month: self.pending_per_month,
+        }
+    }
+
+    /// Replaces the pass allowance, keeping the history.
+    fn set_state(&mut self, state: &PassState) {
+        self.current_month.clone_from(&state.current_month);
+        self.remaining = state.remaining;
+        self.per_month = state.per_month;
+        self.pending_per_month = state.pending_per_month;
+    }
+}
+
+// ============================================================================
+// STORAGE BACKENDS
+// ============================================================================
+
+/// Persistence backend for [`PassManager`].
+///
+/// Every mutation is written through before the call returns. History is
+/// filed under the month that was current when the pass was used.
+pub trait PassStore: fmt::Debug + Send + Sync {
+    /// Loads the stored pass allowance, or `None` if nothing is stored yet.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the store cannot be read.
+    fn load_state(&self) -> PassResult<Option<PassState>>;
+
+    /// Stores the pass allowance.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the store cannot be written.
+    fn save_state(&mut self, state: &PassState) -> PassResult<()>;
+
+    /// Records a used pass together with the allowance after using it.
+    ///
+    /// Both are stored atomically.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the store cannot be written.
+    fn record_pass(&mut self, state: &PassState, entry: &PassEntry) -> PassResult<()>;
+
+    /// Returns the passes used in a month, in chronological order.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the store cannot be read.
+    fn history(&self, month: &str) -> PassResult<Vec<PassEntry>>;
+
+    /// Returns the passes used at or after `from` and before `to`, in
+    /// chronological order.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the store cannot be read.
+    fn history_range(&self, from: DateTime<Utc>, to: DateTime<Utc>) -> PassResult<Vec<PassEntry>>;
+
+    /// Returns all pass usage history, keyed by month.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the store cannot be read.
+    fn all_history(&self) -> PassResult<HashMap<String, Vec<PassEntry>>>;
+}
+
+/// Stores pass data in a single JSON file.
+///
+/// The whole file is rewritten through [`DurableFile`] on every change, so
+/// this suits small histories. Use
+/// [`SqlitePassStore`](crate::database::SqlitePassStore) for long ones.
+#[derive(Debug)]
+pub struct JsonPassStore {
+    /// The JSON file used for persistence.
+    file: DurableFile,
+
+    /// The file contents, `None` until something has been stored.
+    data: Option<PassData>,
+}
+
+impl JsonPassStore {
+    /// Opens the store, reading the file if it exists.
+    ///
+    /// A corrupt file is replaced by its newest valid backup (see
+    /// [`DurableFile::load`]); read and parse errors are only returned if no
+    /// backup is usable.
+    ///
+    /// # Errors
+    ///
+    /// - `PassError::ReadError` - Failed to read the file (other than not found)
+    /// - `PassError::ParseError` - File exists but contains invalid JSON
+    /// - `PassError::CreateDirError` - Failed to create parent directories
+    pub fn open(path: &Path) -> PassResult<Self> {
+        let path = path.to_path_buf();
+        let file = DurableFile::new(&path);
+
+        let data = file
+            .load(|contents| serde_json::from_str::<PassData>(contents))
+            .map_err(|e| match e {
+                LoadError::Read(source) => PassError::ReadError {
+                    path: path.clone(),
+                    source,
+                },
+                LoadError::Parse(source) => PassError::ParseError {
+                    path: path.clone(),
+                    source,
+                },
+            })?;
+
+        // Ensure parent directory exists
+        if data.is_none() {
+            if let Some(parent) = path.parent() {
+                if !parent.exists() {
+                    fs::create_dir_all(parent).map_err(|source| PassError::CreateDirError {
+                        path: path.clone(),
+                        source,
+                    })?;
+                }
+            }
+        }
+
+        Ok(Self { file, data })
+    }
+
+    /// Returns the file contents, if anything has been stored.
+    #[must_use]
+    pub const fn data(&self) -> Option<&PassData> {
+        self.data.as_ref()
+    }
+
+    /// Writes the data to disk.
+    fn write(&self, data: &PassData) -> PassResult<()> {
+        let json = serde_json::to_string_pretty(data)?;
+
+        self.file
+            .write(json.as_bytes())
+            .map_err(|source| PassError::WriteError {
+                path: self.file.path().to_path_buf(),
+                source,
+            })
+    }
+
+    /// Applies a change to a copy of the data, writes it, and keeps it.
+    fn update(&mut self, change: impl FnOnce(&mut PassData)) -> PassResult<()> {
+        let mut data = self.data.clone().unwrap_or_default();
+        change(&mut data);
+        self.write(&data)?;
+        self.data = Some(data);
+        Ok(())
+    }
+}
+
+impl PassStore for JsonPassStore {
+    fn load_state(&self) -> PassResult<Option<PassState>> {
+        Ok(self.data.as_ref().map(PassData::state))
+    }
+
+    fn save_state(&mut self, state: &PassState) -> PassResult<()> {
+        self.update(|data| data.set_state(state))
+    }
+
+    fn record_pass(&mut self, state: &PassState, entry: &PassEntry) -> PassResult<()> {
+        self.update(|data| {
+            data.set_state(state);
+            data.history
+                .entry(state.current_month.clone())
+                .or_default()
+                .push(entry.clone());
+        })
+    }
+
+    fn history(&self, month: &str) -> PassResult<Vec<PassEntry>> {
+        Ok(self
+            .data
+            .as_ref()
+            .and_then(|data| data.history.get(month).cloned())
+            .unwrap_or_default())
+    }
+
+    fn history_range(&self, from: DateTime<Utc>, to: DateTime<Utc>) -> PassResult<Vec<PassEntry>> {
+        let mut entries: Vec<PassEntry> = self
+            .data
+            .iter()
+            .flat_map(|data| data.history.values().flatten())
+            .filter(|entry| entry.used_at_utc >= from && entry.used_at_utc < to)
+            .cloned()
+            .collect();
+        entries.sort_by_key(|entry| entry.used_at_utc);
+        Ok(entries)
+    }
+
+    fn all_history(&self) -> PassResult<HashMap<String, Vec<PassEntry>>> {
+        Ok(self
+            .data
+            .as_ref()
+            .map(|data| data.history.clone())
+            .unwrap_or_default())
+    }
+}
+
 // ============================================================================
 // PASS MANAGER
 // ============================================================================
 
-/// Manages monthly accountability passes with JSON persistence.
+/// Manages monthly accountability passes.
 ///
 /// `PassManager` provides the core business logic for:
-/// - Loading and saving pass data to a JSON file
+/// - Loading and saving pass data through a [`PassStore`]
 /// - Automatically resetting passes at the start of each month
 /// - Tracking pass usage with reasons
 /// - Querying pass history by month
@@ -230,15 +499,15 @@ impl Default for PassData {
 /// wrapped in an `RwLock` for concurrent access.
 #[derive(Debug)]
 pub struct PassManager {
-    /// The path to the JSON file for persistence.
-    path: PathBuf,
+    /// The storage backend.
+    store: Box<dyn PassStore>,
 
-    /// The current pass data.
-    data: PassData,
+    /// The current pass allowance.
+    state: PassState,
 }
 
 impl PassManager {
-    /// Loads pass data from disk or creates a new file if none exists.
+    /// Loads pass data from a JSON file or creates a new file if none exists.
     ///
     /// # Arguments
     ///
@@ -247,6 +516,10 @@ impl PassManager {
     ///   when creating a new file. For existing files, this parameter is
     ///   ignored (the stored `per_month` value takes precedence).
     ///
+    /// A corrupt file is replaced by its newest valid backup (see
+    /// [`DurableFile::load`]); read and parse errors are only returned if no
+    /// backup is usable.
+    ///
     /// # Errors
     ///
     /// - `PassError::ReadError` - Failed to read the file (other than not found)
@@ -254,42 +527,31 @@ impl PassManager {
     /// - `PassError::CreateDirError` - Failed to create parent directories
     /// - `PassError::WriteError` - Failed to write initial data
     pub fn load_or_create(path: &Path, per_month: u32) -> PassResult<Self> {
-        let path = path.to_path_buf();
-
-        let data = if path.exists() {
-            // Load existing data
-            let contents = fs::read_to_string(&path).map_err(|source| PassError::ReadError {
-                path: path.clone(),
-                source,
-            })?;
-
-            serde_json::from_str::<PassData>(&contents).map_err(|source| PassError::ParseError {
-                path: path.clone(),
-                source,
-            })?
-        } else {
-            // Create new data
-            let data = PassData::new(per_month);
-
-            // Ensure parent directory exists
-            if let Some(parent) = path.parent() {
-                if !parent.exists() {
-                    fs::create_dir_all(parent).map_err(|source| PassError::CreateDirError {
-                        path: path.clone(),
-                        source,
-                    })?;
-                }
-            }
+        Self::open(Box::new(JsonPassStore::open(path)?), per_month)
+    }
 
-            data
-        };
+    /// Creates a manager on top of a storage backend.
+    ///
+    /// # Arguments
+    ///
+    /// * `store` - The backend holding the pass data.
+    /// * `per_month` - The number of passes to grant per month if the store
+    ///   is empty. Otherwise the stored value takes precedence.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the store cannot be read or written.
+    pub fn open(store: Box<dyn PassStore>, per_month: u32) -> PassResult<Self> {
+        let state = store
+            .load_state()?
+            .unwrap_or_else(|| PassState::new(per_month));
 
-        let mut manager = Self { path, data };
+        let mut manager = Self { store, state };
 
         // Check for month change and reset if needed
         manager.maybe_reset_month(None)?;
 
-        // Ensure data is persisted (especially for new files)
+        // Ensure data is persisted (especially for new stores)
         manager.save()?;
 
         Ok(manager)
@@ -311,30 +573,30 @@ impl PassManager {
         // Set new pending value if provided
         if let Some(pending) = new_pending_per_month {
             // Only set pending if it differs from current per_month
-            if pending != self.data.per_month {
-                self.data.pending_per_month = Some(pending);
+            if pending != self.state.per_month {
+                self.state.pending_per_month = Some(pending);
                 changed = true;
             } else {
                 // If setting to current value, clear any pending
-                if self.data.pending_per_month.is_some() {
-                    self.data.pending_per_month = None;
+                if self.state.pending_per_month.is_some() {
+                    self.state.pending_per_month = None;
                     changed = true;
                 }
             }
         }
 
         // Check if month has changed
-        if current != self.data.current_month {
+        if current != self.state.current_month {
             // Apply pending per_month if set
-            if let Some(pending) = self.data.pending_per_month.take() {
-                self.data.per_month = pending;
+            if let Some(pending) = self.state.pending_per_month.take() {
+                self.state.per_month = pending;
             }
 
             // Reset remaining passes
-            self.data.remaining = self.data.per_month;
+            self.state.remaining = self.state.per_month;
 
             // Update current month
-            self.data.current_month = current;
+            self.state.current_month = current;
 
             changed = true;
         }
@@ -350,28 +612,28 @@ impl PassManager {
     #[inline]
     #[must_use]
     pub fn remaining(&self) -> u32 {
-        self.data.remaining
+        self.state.remaining
     }
 
     /// Returns the number of passes granted per month.
     #[inline]
     #[must_use]
     pub fn per_month(&self) -> u32 {
-        self.data.per_month
+        self.state.per_month
     }
 
     /// Returns the pending per-month value, if any.
     #[inline]
     #[must_use]
     pub fn pending_per_month(&self) -> Option<u32> {
-        self.data.pending_per_month
+        self.state.pending_per_month
     }
 
     /// Returns the current month string in "YYYY-MM" format.
     #[inline]
     #[must_use]
     pub fn current_month(&self) -> &str {
-        &self.data.current_month
+        &self.state.current_month
     }
 
     /// Uses one pass and records the reason.
@@ -408,26 +670,21 @@ impl PassManager {
         }
 
         // Check if passes are available
-        if self.data.remaining == 0 {
+        if self.state.remaining == 0 {
             return Err(PassError::NoPassesRemaining {
-                month: self.data.current_month.clone(),
-                max: self.data.per_month,
+                month: self.state.current_month.clone(),
+                max: self.state.per_month,
             });
         }
 
-        // Use a pass
-        self.data.remaining -= 1;
-
-        // Record in history
+        // Use a pass and record it in history
+        let mut state = self.state.clone();
+        state.remaining -= 1;
         let entry = PassEntry::new(reason);
-        self.data
-            .history
-            .entry(self.data.current_month.clone())
-            .or_default()
-            .push(entry.clone());
 
         // Persist
-        self.save()?;
+        self.store.record_pass(&state, &entry)?;
+        self.state = state;
 
         Ok(entry)
     }
@@ -441,15 +698,38 @@ impl PassManager {
     /// # Returns
     ///
     /// A vector of `PassEntry` for the specified month, in chronological order.
-    #[must_use]
-    pub fn history(&self, month: &str) -> Vec<PassEntry> {
-        self.data.history.get(month).cloned().unwrap_or_default()
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the store cannot be read.
+    pub fn history(&self, month: &str) -> PassResult<Vec<PassEntry>> {
+        self.store.history(month)
+    }
+
+    /// Returns the passes used at or after `from` and before `to`.
+    ///
+    /// # Returns
+    ///
+    /// A vector of `PassEntry` in chronological order.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the store cannot be read.
+    pub fn history_range(
+        &self,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> PassResult<Vec<PassEntry>> {
+        self.store.history_range(from, to)
     }
 
     /// Returns all pass usage history across all months.
-    #[must_use]
-    pub fn all_history(&self) -> HashMap<String, Vec<PassEntry>> {
-        self.data.history.clone()
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the store cannot be read.
+    pub fn all_history(&self) -> PassResult<HashMap<String, Vec<PassEntry>>> {
+        self.store.all_history()
     }
 
     /// Updates the per-month pass configuration.
@@ -470,10 +750,10 @@ impl PassManager {
         self.maybe_reset_month(None)?;
 
         // If same as current, no change needed
-        if new_per_month == self.data.per_month {
+        if new_per_month == self.state.per_month {
             // Clear any pending if exists
-            if self.data.pending_per_month.is_some() {
-                self.data.pending_per_month = None;
+            if self.state.pending_per_month.is_some() {
+                self.state.pending_per_month = None;
                 self.save()?;
             }
             return Ok(true);
@@ -481,53 +761,29 @@ impl PassManager {
 
         // Check if we can apply immediately:
         // - No passes have been used yet this month
-        let can_apply_immediately = self.data.remaining == self.data.per_month;
+        let can_apply_immediately = self.state.remaining == self.state.per_month;
 
         if can_apply_immediately {
-            self.data.per_month = new_per_month;
-            self.data.remaining = new_per_month;
-            self.data.pending_per_month = None;
+            self.state.per_month = new_per_month;
+            self.state.remaining = new_per_month;
+            self.state.pending_per_month = None;
             self.save()?;
             Ok(true)
         } else {
             // Defer to next month
-            self.data.pending_per_month = Some(new_per_month);
+            self.state.pending_per_month = Some(new_per_month);
             self.save()?;
             Ok(false)
         }
     }
 
-    /// Persists the current data to disk.
-    ///
-    /// Uses atomic write (write to temp file, then rename) to prevent
-    /// data corruption if the process crashes mid-write.
+    /// Persists the current pass allowance.
     ///
     /// Note: This is called automatically by `use_pass`, `set_per_month`,
     /// and `maybe_reset_month`. You only need to call this directly if
     /// you're making custom modifications to the data.
-    pub fn save(&self) -> PassResult<()> {
-        let json = serde_json::to_string_pretty(&self.data)?;
-
-        // Atomic write: write to temp file, then rename
-        let temp_path = self.path.with_extension("json.tmp");
-
-        fs::write(&temp_path, &json).map_err(|source| PassError::WriteError {
-            path: temp_path.clone(),
-            source,
-        })?;
-
-        fs::rename(&temp_path, &self.path).map_err(|source| PassError::WriteError {
-            path: self.path.clone(),
-            source,
-        })?;
-
-        Ok(())
-    }
-
-    /// Returns a reference to the underlying PassData (for testing/debugging).
-    #[cfg(test)]
-    pub fn data(&self) -> &PassData {
-        &self.data
+    pub fn save(&mut self) -> PassResult<()> {
+        self.store.save_state(&self.state)
     }
 }
 
@@ -591,7 +847,7 @@ pub fn is_valid_month_string(month: &str) -> bool {
     let month_num: Result<u32, _> = parts[1].parse();
 
     match (year, month_num) {
-        (Ok(y), Ok(m)) => y >= 1970 && y <= 9999 && (1..=12).contains(&m),
+        (Ok(y), Ok(m)) => (1970..=9999).contains(&y) && (1..=12).contains(&m),
         _ => false,
     }
 }
@@ -610,10 +866,9 @@ mod tests {
         let dir = tempdir().unwrap();
         let path = dir.path().join("passes.json");
         // Keep dir alive by leaking it (for testing only)
-        let path_clone = path.clone();
         std::mem::forget(dir);
-        let manager = PassManager::load_or_create(&path_clone, per_month).unwrap();
-        (manager, path_clone)
+        let manager = PassManager::load_or_create(&path, per_month).unwrap();
+        (manager, path)
     }
 
     #[test]
@@ -689,7 +944,7 @@ mod tests {
         let entry = manager.use_pass("Test reason".to_string()).unwrap();
         assert_eq!(entry.reason, "Test reason");
 
-        let history = manager.history(&current_month_string());
+        let history = manager.history(&current_month_string()).unwrap();
         assert_eq!(history.len(), 1);
         assert_eq!(history[0].reason, "Test reason");
     }
@@ -741,7 +996,7 @@ mod tests {
     fn test_history_empty_month() {
         let (manager, _path) = create_temp_manager(3);
 
-        let history = manager.history("2020-01");
+        let history = manager.history("2020-01").unwrap();
         assert!(history.is_empty());
     }
 
@@ -751,10 +1006,36 @@ mod tests {
 
         manager.use_pass("Test".to_string()).unwrap();
 
-        let all = manager.all_history();
+        let all = manager.all_history().unwrap();
         assert!(all.contains_key(&current_month_string()));
     }
 
+    #[test]
+    fn test_json_store_history_range() {
+        let dir = tempdir().unwrap();
+        let mut store = JsonPassStore::open(&dir.path().join("passes.json")).unwrap();
+        assert!(store.load_state().unwrap().is_none());
+
+        let at =
+            |day: u32| chrono::TimeZone::with_ymd_and_hms(&Utc, 2025, 1, day, 3, 0, 0).unwrap();
+        let mut state = PassState::new(3);
+        state.current_month = "2025-01".to_string();
+        for day in [20, 5, 12] {
+            state.remaining -= 1;
+            store
+                .record_pass(
+                    &state,
+                    &PassEntry::with_timestamp(at(day), format!("Day {day}")),
+                )
+                .unwrap();
+        }
+
+        assert_eq!(store.load_state().unwrap(), Some(state));
+        let range = store.history_range(at(5), at(20)).unwrap();
+        let reasons: Vec<&str> = range.iter().map(|e| e.reason.as_str()).collect();
+        assert_eq!(reasons, ["Day 5", "Day 12"]);
+    }
+
     #[test]
     fn test_set_per_month_immediate_when_no_passes_used() {
         let (mut manager, _path) = create_temp_manager(3);
@@ -833,13 +1114,33 @@ mod tests {
         {
             let manager = PassManager::load_or_create(&path, 3).unwrap();
             assert_eq!(manager.remaining(), 1);
-            let history = manager.history(&current_month_string());
+            let history = manager.history(&current_month_string()).unwrap();
             assert_eq!(history.len(), 2);
             assert_eq!(history[0].reason, "First");
             assert_eq!(history[1].reason, "Second");
         }
     }
 
+    #[test]
+    fn test_corrupt_file_recovers_from_backup() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("passes.json");
+
+        {
+            let mut manager = PassManager::load_or_create(&path, 3).unwrap();
+            manager.use_pass("First".to_string()).unwrap();
+            manager.use_pass("Second".to_string()).unwrap();
+        }
+
+        // Simulate a torn write
+        fs::write(&path, "{\"per_month\": 3, \"hist").unwrap();
+
+        // The newest backup predates the second pass
+        let manager = PassManager::load_or_create(&path, 3).unwrap();
+        assert_eq!(manager.remaining(), 2);
+        assert!(path.with_file_name("passes.json.corrupt").exists());
+    }
+
     #[test]
     fn test_creates_parent_directories() {
         let dir = tempdir().unwrap();
@@ -0,0 +1,468 @@
+//! Encrypted storage for credentials.
+//!
+//! Secrets such as Wi-Fi passwords are kept out of `config.toml`. The
+//! configuration refers to them by id, and their values live in a separate
+//! secrets file, encrypted with a key that never leaves the device.
+//!
+//! # Files
+//!
+//! - **Key file** (`secrets.key`): 32 random bytes, hex-encoded, created on
+//!   first use. Losing it makes the stored secrets unreadable.
+//! - **Secrets file** (`secrets.json`): each secret encrypted with
+//!   ChaCha20-Poly1305 under a random nonce, with its id as associated data
+//!   so ciphertexts cannot be swapped between ids.
+//!
+//! Both files are written through [`DurableFile`] and are only readable by
+//! their owner (mode `0600`).
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use tether_core::secrets::{SecretStore, SecretString};
+//!
+//! let mut store = SecretStore::open("/var/lib/tether/secrets.json", "/var/lib/tether/secrets.key")?;
+//! let id = store.insert(&SecretString::new("hunter22"))?;
+//! store.save()?;
+//!
+//! let password = store.get(&id)?.expect("just stored");
+//! assert_eq!(password.expose(), "hunter22");
+//! # Ok::<(), tether_core::secrets::SecretError>(())
+//! ```
+
+use chacha20poly1305::aead::{Aead, KeyInit, OsRng, Payload};
+use chacha20poly1305::{AeadCore, ChaCha20Poly1305, Key, Nonce};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::io;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+use crate::storage::{DurableFile, LoadError};
+
+// =============================================================================
+// ERROR TYPES
+// =============================================================================
+
+/// Errors that can occur when using the secrets store.
+#[derive(Debug, Error)]
+pub enum SecretError {
+    /// Failed to read or create the key file.
+    #[error("failed to access secrets key at {}: {source}", path.display())]
+    KeyError {
+        /// The key file path.
+        path: PathBuf,
+        /// The underlying IO error.
+        #[source]
+        source: io::Error,
+    },
+
+    /// The key file does not contain a valid key.
+    #[error("secrets key at {} is invalid: expected 64 hex characters", path.display())]
+    InvalidKey {
+        /// The key file path.
+        path: PathBuf,
+    },
+
+    /// Failed to read the secrets file.
+    #[error("failed to read secrets file at {}: {source}", path.display())]
+    ReadError {
+        /// The secrets file path.
+        path: PathBuf,
+        /// The underlying IO error.
+        #[source]
+        source: io::Error,
+    },
+
+    /// Failed to write the secrets file.
+    #[error("failed to write secrets file at {}: {source}", path.display())]
+    WriteError {
+        /// The secrets file path.
+        path: PathBuf,
+        /// The underlying IO error.
+        #[source]
+        source: io::Error,
+    },
+
+    /// The secrets file is not valid JSON.
+    #[error("failed to parse secrets file at {}: {source}", path.display())]
+    ParseError {
+        /// The secrets file path.
+        path: PathBuf,
+        /// The underlying JSON error.
+        #[source]
+        source: serde_json::Error,
+    },
+
+    /// A secret could not be decrypted, usually because the key changed.
+    #[error("secret '{id}' could not be decrypted with this device's key")]
+    DecryptError {
+        /// The id of the secret.
+        id: String,
+    },
+
+    /// A secret could not be encrypted.
+    #[error("failed to encrypt secret")]
+    EncryptError,
+}
+
+/// A specialized Result type for secrets operations.
+pub type SecretResult<T> = std::result::Result<T, SecretError>;
+
+// =============================================================================
+// SECRET STRING
+// =============================================================================
+
+/// A string that is never printed.
+///
+/// `Debug` shows a placeholder, and there is deliberately no `Display` or
+/// `Serialize` implementation. Use [`expose`](Self::expose) at the point
+/// where the value is actually needed.
+#[derive(Clone, PartialEq, Eq, Default)]
+pub struct SecretString(String);
+
+impl SecretString {
+    /// Wraps a secret value.
+    pub fn new(value: impl Into<String>) -> Self {
+        Self(value.into())
+    }
+
+    /// Returns the secret value.
+    #[must_use]
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+
+    /// Returns `true` if the secret is empty.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl std::fmt::Debug for SecretString {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("SecretString([REDACTED])")
+    }
+}
+
+impl<'de> Deserialize<'de> for SecretString {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer).map(Self)
+    }
+}
+
+// =============================================================================
+// SECRET STORE
+// =============================================================================
+
+/// An encrypted secret as stored on disk.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct EncryptedSecret {
+    /// Hex-encoded 96-bit nonce.
+    nonce: String,
+    /// Hex-encoded ciphertext including the authentication tag.
+    ciphertext: String,
+}
+
+/// Contents of the secrets file.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct SecretsFile {
+    /// Encrypted secrets keyed by id.
+    #[serde(default)]
+    secrets: BTreeMap<String, EncryptedSecret>,
+}
+
+/// Encrypted store of credentials, keyed by id.
+///
+/// Changes are kept in memory until [`save`](Self::save) is called.
+pub struct SecretStore {
+    /// The secrets file.
+    file: DurableFile,
+    /// The device-local encryption key.
+    cipher: ChaCha20Poly1305,
+    /// Encrypted secrets keyed by id.
+    secrets: BTreeMap<String, EncryptedSecret>,
+}
+
+impl SecretStore {
+    /// Opens the store, creating the key on first use.
+    ///
+    /// # Errors
+    ///
+    /// - `SecretError::KeyError` - The key file could not be read or created
+    /// - `SecretError::InvalidKey` - The key file is malformed
+    /// - `SecretError::ReadError` / `ParseError` - The secrets file is
+    ///   unreadable and no backup is usable
+    pub fn open(path: impl Into<PathBuf>, key_path: impl AsRef<Path>) -> SecretResult<Self> {
+        let key = Self::load_or_create_key(key_path.as_ref())?;
+        let file = DurableFile::new(path);
+
+        let contents = file
+            .load(|contents| serde_json::from_str::<SecretsFile>(contents))
+            .map_err(|e| match e {
+                LoadError::Read(source) => SecretError::ReadError {
+                    path: file.path().to_path_buf(),
+                    source,
+                },
+                LoadError::Parse(source) => SecretError::ParseError {
+                    path: file.path().to_path_buf(),
+                    source,
+                },
+            })?
+            .unwrap_or_default();
+
+        Ok(Self {
+            file,
+            cipher: ChaCha20Poly1305::new(&key),
+            secrets: contents.secrets,
+        })
+    }
+
+    /// Reads the key file, generating a new key if it does not exist.
+    fn load_or_create_key(path: &Path) -> SecretResult<Key> {
+        let key_error = |source| SecretError::KeyError {
+            path: path.to_path_buf(),
+            source,
+        };
+
+        if !path.exists() {
+            let key = ChaCha20Poly1305::generate_key(&mut OsRng);
+            // No backups: an old key is useless without the matching secrets
+            DurableFile::new(path)
+                .with_backups(0)
+                .write(hex::encode(key).as_bytes())
+                .map_err(key_error)?;
+            return Ok(key);
+        }
+
+        let contents = std::fs::read_to_string(path).map_err(key_error)?;
+        let bytes = hex::decode(contents.trim()).map_err(|_| SecretError::InvalidKey {
+            path: path.to_path_buf(),
+        })?;
+        if bytes.len() != 32 {
+            return Err(SecretError::InvalidKey {
+                path: path.to_path_buf(),
+            });
+        }
+        Ok(*Key::from_slice(&bytes))
+    }
+
+    /// Encrypts and stores a secret under a new id, which is returned.
+    ///
+    /// # Errors
+    ///
+    /// - `SecretError::EncryptError` - Encryption failed
+    pub fn insert(&mut self, value: &SecretString) -> SecretResult<String> {
+        let id = uuid::Uuid::new_v4().to_string();
+        let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let ciphertext = self
+            .cipher
+            .encrypt(
+                &nonce,
+                Payload {
+                    msg: value.expose().as_bytes(),
+                    aad: id.as_bytes(),
+                },
+            )
+            .map_err(|_| SecretError::EncryptError)?;
+
+        self.secrets.insert(
+            id.clone(),
+            EncryptedSecret {
+                nonce: hex::encode(nonce),
+                ciphertext: hex::encode(ciphertext),
+            },
+        );
+        Ok(id)
+    }
+
+    /// Decrypts the secret with the given id.
+    ///
+    /// Returns `Ok(None)` if no secret has that id.
+    ///
+    /// # Errors
+    ///
+    /// - `SecretError::DecryptError` - The secret was tampered with or was
+    ///   encrypted with a different key
+    pub fn get(&self, id: &str) -> SecretResult<Option<SecretString>> {
+        let Some(secret) = self.secrets.get(id) else {
+            return Ok(None);
+        };
+        let decrypt_error = || SecretError::DecryptError { id: id.to_string() };
+
+        let nonce = hex::decode(&secret.nonce).map_err(|_| decrypt_error())?;
+        let ciphertext = hex::decode(&secret.ciphertext).map_err(|_| decrypt_error())?;
+        if nonce.len() != 12 {
+            return Err(decrypt_error());
+        }
+
+        let plaintext = self
+            .cipher
+            .decrypt(
+                Nonce::from_slice(&nonce),
+                Payload {
+                    msg: &ciphertext,
+                    aad: id.as_bytes(),
+                },
+            )
+            .map_err(|_| decrypt_error())?;
+        let value = String::from_utf8(plaintext).map_err(|_| decrypt_error())?;
+
+        Ok(Some(SecretString(value)))
+    }
+
+    /// Removes the secret with the given id. Returns `true` if it existed.
+    pub fn remove(&mut self, id: &str) -> bool {
+        self.secrets.remove(id).is_some()
+    }
+
+    /// Removes every secret whose id is not in `ids`.
+    pub fn retain(&mut self, ids: &HashSet<&str>) {
+        self.secrets.retain(|id, _| ids.contains(id.as_str()));
+    }
+
+    /// Returns `true` if a secret with the given id exists.
+    #[must_use]
+    pub fn contains(&self, id: &str) -> bool {
+        self.secrets.contains_key(id)
+    }
+
+    /// Returns the number of stored secrets.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.secrets.len()
+    }
+
+    /// Returns `true` if no secrets are stored.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.secrets.is_empty()
+    }
+
+    /// Persists the secrets file.
+    ///
+    /// # Errors
+    ///
+    /// - `SecretError::WriteError` - The file could not be written
+    pub fn save(&self) -> SecretResult<()> {
+        let contents = SecretsFile {
+            secrets: self.secrets.clone(),
+        };
+        let json =
+            serde_json::to_string_pretty(&contents).map_err(|source| SecretError::ParseError {
+                path: self.file.path().to_path_buf(),
+                source,
+            })?;
+
+        self.file
+            .write(json.as_bytes())
+            .map_err(|source| SecretError::WriteError {
+                path: self.file.path().to_path_buf(),
+                source,
+            })
+    }
+}
+
+impl std::fmt::Debug for SecretStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SecretStore")
+            .field("path", &self.file.path())
+            .field("secrets", &self.secrets.len())
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn open_store(dir: &Path) -> SecretStore {
+        SecretStore::open(dir.join("secrets.json"), dir.join("secrets.key")).unwrap()
+    }
+
+    #[test]
+    fn test_secret_roundtrip_across_opens() {
+        let dir = tempdir().unwrap();
+
+        let id = {
+            let mut store = open_store(dir.path());
+            let id = store.insert(&SecretString::new("hunter22")).unwrap();
+            store.save().unwrap();
+            id
+        };
+
+        let store = open_store(dir.path());
+        assert_eq!(store.get(&id).unwrap().unwrap().expose(), "hunter22");
+        assert!(store.get("missing").unwrap().is_none());
+
+        // The value is not stored in the clear
+        let on_disk = std::fs::read_to_string(dir.path().join("secrets.json")).unwrap();
+        assert!(!on_disk.contains("hunter22"));
+    }
+
+    #[test]
+    fn test_secret_with_other_key_fails_to_decrypt() {
+        let dir = tempdir().unwrap();
+        let mut store = open_store(dir.path());
+        let id = store.insert(&SecretString::new("hunter22")).unwrap();
+        store.save().unwrap();
+
+        std::fs::remove_file(dir.path().join("secrets.key")).unwrap();
+        let store = open_store(dir.path());
+        assert!(matches!(
+            store.get(&id),
+            Err(SecretError::DecryptError { .. })
+        ));
+    }
+
+    #[test]
+    fn test_secret_bound_to_id() {
+        let dir = tempdir().unwrap();
+        let mut store = open_store(dir.path());
+        let first = store.insert(&SecretString::new("one")).unwrap();
+        let second = store.insert(&SecretString::new("two")).unwrap();
+
+        // Moving a ciphertext to another id is detected
+        let moved = store.secrets[&first].clone();
+        store.secrets.insert(second.clone(), moved);
+        assert!(store.get(&second).is_err());
+    }
+
+    #[test]
+    fn test_retain() {
+        let dir = tempdir().unwrap();
+        let mut store = open_store(dir.path());
+        let keep = store.insert(&SecretString::new("keep")).unwrap();
+        store.insert(&SecretString::new("drop")).unwrap();
+
+        store.retain(&HashSet::from([keep.as_str()]));
+        assert_eq!(store.len(), 1);
+        assert!(store.contains(&keep));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_files_are_private() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempdir().unwrap();
+        let mut store = open_store(dir.path());
+        store.insert(&SecretString::new("hunter22")).unwrap();
+        store.save().unwrap();
+
+        for name in ["secrets.json", "secrets.key"] {
+            let mode = std::fs::metadata(dir.path().join(name))
+                .unwrap()
+                .permissions()
+                .mode();
+            assert_eq!(mode & 0o777, 0o600, "{name}");
+        }
+    }
+
+    #[test]
+    fn test_secret_string_debug_is_redacted() {
+        let secret = SecretString::new("hunter22");
+        assert!(!format!("{secret:?}").contains("hunter22"));
+    }
+}
@@ -1,9 +1,41 @@
 //! Storage utilities.
 //!
-//! This module provides helper functions for determining storage paths.
-//! The actual persistence is handled by [`PassManager`](crate::passes::PassManager).
+//! This module provides helper functions for determining storage paths,
+//! the [`StorageBackend`] selection for pass data, and [`DurableFile`], the
+//! crash-safe persistence used by [`Config`](crate::config::Config) and
+//! [`JsonPassStore`](crate::passes::JsonPassStore).
+//!
+//! # Durability
+//!
+//! Tether runs on a Raspberry Pi that may lose power at any moment. A plain
+//! `fs::write` can leave a truncated file behind, and even write-then-rename
+//! is not durable until both the file and its directory are flushed. Every
+//! write through [`DurableFile::write`]:
+//!
+//! 1. Keeps the current file as the newest of the rolling backups
+//!    (`<name>.bak.1` is the newest, `<name>.bak.N` the oldest)
+//! 2. Writes the new contents to `<name>.tmp` with mode `0600`
+//! 3. Flushes the temporary file to disk
+//! 4. Renames it over the original
+//! 5. Flushes the parent directory so the rename itself survives a power cut
+//!
+//! # Recovery
+//!
+//! [`DurableFile::load`] treats a file that cannot be read, is empty, or
+//! fails to parse as corrupt. The corrupt file is moved aside to
+//! `<name>.corrupt` and the newest backup that parses is restored in its
+//! place.
+
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+use tracing::{error, warn};
+use utoipa::ToSchema;
 
-use std::path::PathBuf;
+use crate::metrics;
 
 /// Returns the default data directory for tether.
 ///
@@ -29,9 +61,299 @@ pub fn default_passes_path() -> PathBuf {
     default_data_dir().join("passes.json")
 }
 
+/// Returns the default path for the SQLite database.
+#[must_use]
+pub fn default_database_path() -> PathBuf {
+    default_data_dir().join("tether.db")
+}
+
+/// Where pass data is stored.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum StorageBackend {
+    /// A single JSON file (`passes.json`), rewritten on every change.
+    #[default]
+    Json,
+    /// An embedded SQLite database (`tether.db`) with indexed history.
+    ///
+    /// An existing `passes.json` is imported the first time it is used.
+    Sqlite,
+}
+
+/// Error returned by [`DurableFile::load`] when no usable copy exists.
+///
+/// Carries the failure of the primary file, since that is what the user
+/// needs to look at.
+#[derive(Debug)]
+pub enum LoadError<E> {
+    /// The file could not be read and no backup was usable.
+    Read(io::Error),
+    /// The file could not be parsed and no backup was usable.
+    Parse(E),
+}
+
+/// A file written atomically and durably, with rolling backups.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use tether_core::storage::DurableFile;
+///
+/// let file = DurableFile::new("/var/lib/tether/passes.json");
+/// file.write(b"{}")?;
+/// let contents = file.load(|s| Ok::<_, std::convert::Infallible>(s.to_string()));
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DurableFile {
+    /// Path of the primary file.
+    path: PathBuf,
+    /// Number of previous versions to keep.
+    backups: usize,
+}
+
+impl DurableFile {
+    /// Number of previous versions kept by default.
+    pub const DEFAULT_BACKUPS: usize = 3;
+
+    /// Permissions of written files: readable and writable by the owner only.
+    #[cfg(unix)]
+    const MODE: u32 = 0o600;
+
+    /// Creates a handle for the file at `path` keeping the default number of
+    /// backups.
+    #[must_use]
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            backups: Self::DEFAULT_BACKUPS,
+        }
+    }
+
+    /// Sets how many previous versions to keep. Zero disables backups.
+    #[must_use]
+    pub const fn with_backups(mut self, backups: usize) -> Self {
+        self.backups = backups;
+        self
+    }
+
+    /// Returns the path of the primary file.
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the path of the `n`th backup, where 1 is the newest.
+    #[must_use]
+    pub fn backup_path(&self, n: usize) -> PathBuf {
+        self.sibling(&format!("bak.{n}"))
+    }
+
+    /// Returns the path a corrupt primary file is moved to.
+    #[must_use]
+    pub fn corrupt_path(&self) -> PathBuf {
+        self.sibling("corrupt")
+    }
+
+    /// Returns `<path>.<suffix>`.
+    fn sibling(&self, suffix: &str) -> PathBuf {
+        let mut name = self.path.file_name().unwrap_or_default().to_os_string();
+        name.push(".");
+        name.push(suffix);
+        self.path.with_file_name(name)
+    }
+
+    /// Replaces the file's contents atomically and durably.
+    ///
+    /// The previous contents become the newest backup. The parent directory
+    /// must already exist.
+    ///
+    /// # Errors
+    ///
+    /// Returns the underlying I/O error. The original file is left intact
+    /// if any step before the final rename fails.
+    pub fn write(&self, contents: &[u8]) -> io::Result<()> {
+        let started = Instant::now();
+        let result = self.replace(contents);
+        metrics::record_storage_write(&self.path, started);
+        result
+    }
+
+    /// Backs up the current contents, then swaps in the new ones.
+    fn replace(&self, contents: &[u8]) -> io::Result<()> {
+        if self.backups > 0 && self.path.exists() {
+            self.rotate_backups()?;
+        }
+
+        let temp_path = self.sibling("tmp");
+        let mut options = OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(Self::MODE);
+        }
+
+        let mut file = options.open(&temp_path)?;
+        file.write_all(contents)?;
+        file.sync_all()?;
+        drop(file);
+
+        // A leftover temp file from an older version may have wider permissions
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&temp_path, fs::Permissions::from_mode(Self::MODE))?;
+        }
+
+        fs::rename(&temp_path, &self.path)?;
+        self.sync_parent()
+    }
+
+    /// Shifts backups up by one and links the current file in as the newest.
+    ///
+    /// Hard-linking keeps the current file in place, so a crash mid-write
+    /// never leaves the primary file missing.
+    fn rotate_backups(&self) -> io::Result<()> {
+        for n in (1..self.backups).rev() {
+            let from = self.backup_path(n);
+            if from.exists() {
+                fs::rename(&from, self.backup_path(n + 1))?;
+            }
+        }
+
+        let newest = self.backup_path(1);
+        if newest.exists() {
+            fs::remove_file(&newest)?;
+        }
+        if fs::hard_link(&self.path, &newest).is_err() {
+            fs::copy(&self.path, &newest)?;
+        }
+        Ok(())
+    }
+
+    /// Deletes the backups and any corrupt file moved aside by
+    /// [`load`](Self::load), keeping only the primary file.
+    ///
+    /// Used when older versions must not survive, e.g. once secrets they
+    /// held in plaintext have moved elsewhere.
+    ///
+    /// # Errors
+    ///
+    /// Returns the underlying I/O error if a file could not be removed.
+    pub fn discard_history(&self) -> io::Result<()> {
+        let stale = (1..=self.backups)
+            .map(|n| self.backup_path(n))
+            .chain([self.corrupt_path()]);
+        for path in stale {
+            match fs::remove_file(&path) {
+                Ok(()) => {}
+                Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+                Err(e) => return Err(e),
+            }
+        }
+        self.sync_parent()
+    }
+
+    /// Flushes the parent directory so renames within it are durable.
+    fn sync_parent(&self) -> io::Result<()> {
+        #[cfg(unix)]
+        {
+            let parent = match self.path.parent() {
+                Some(parent) if !parent.as_os_str().is_empty() => parent,
+                _ => Path::new("."),
+            };
+            File::open(parent)?.sync_all()?;
+        }
+        Ok(())
+    }
+
+    /// Loads and parses the file, recovering from backups if it is corrupt.
+    ///
+    /// Returns `Ok(None)` if the file does not exist. If the file cannot be
+    /// read, is empty, or `parse` rejects it, backups are tried newest first.
+    /// The first one that parses is restored as the primary file and the
+    /// corrupt file is kept at [`corrupt_path`](Self::corrupt_path).
+    ///
+    /// # Errors
+    ///
+    /// Returns the primary file's read or parse error if no backup could be
+    /// parsed either.
+    pub fn load<T, E, F>(&self, parse: F) -> Result<Option<T>, LoadError<E>>
+    where
+        E: Display,
+        F: Fn(&str) -> Result<T, E>,
+    {
+        if !self.path.exists() {
+            return Ok(None);
+        }
+
+        let primary_error = match fs::read_to_string(&self.path) {
+            // A power cut during a non-durable write typically leaves an empty file
+            Ok(contents) if contents.trim().is_empty() => LoadError::Read(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "file is empty",
+            )),
+            Ok(contents) => match parse(&contents) {
+                Ok(value) => return Ok(Some(value)),
+                Err(e) => LoadError::Parse(e),
+            },
+            Err(e) => LoadError::Read(e),
+        };
+
+        for n in 1..=self.backups {
+            let backup = self.backup_path(n);
+            let Ok(contents) = fs::read_to_string(&backup) else {
+                continue;
+            };
+            if contents.trim().is_empty() {
+                continue;
+            }
+            let Ok(value) = parse(&contents) else {
+                continue;
+            };
+
+            warn!(
+                path = %self.path.display(),
+                backup = %backup.display(),
+                error = %primary_error,
+                "File is corrupt, restoring newest valid backup"
+            );
+            if let Err(e) = self.restore(contents.as_bytes()) {
+                error!(path = %self.path.display(), error = %e, "Failed to restore backup");
+            }
+            return Ok(Some(value));
+        }
+
+        Err(primary_error)
+    }
+
+    /// Moves the corrupt primary aside and writes `contents` in its place.
+    fn restore(&self, contents: &[u8]) -> io::Result<()> {
+        fs::rename(&self.path, self.corrupt_path())?;
+        // The corrupt file is gone, so this must not rotate it into the backups
+        self.clone().with_backups(0).write(contents)
+    }
+}
+
+impl<E: Display> Display for LoadError<E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Read(e) => write!(f, "{e}"),
+            Self::Parse(e) => write!(f, "{e}"),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use tempfile::tempdir;
+
+    /// Parses a file holding a single integer.
+    fn parse_number(s: &str) -> Result<u32, std::num::ParseIntError> {
+        s.trim().parse()
+    }
 
     #[test]
     fn test_default_data_dir_exists() {
@@ -44,4 +366,104 @@ mod tests {
         let path = default_passes_path();
         assert!(path.ends_with("passes.json"));
     }
+
+    #[test]
+    fn test_durable_file_paths() {
+        let file = DurableFile::new("/var/lib/tether/passes.json");
+        assert_eq!(
+            file.backup_path(2),
+            PathBuf::from("/var/lib/tether/passes.json.bak.2")
+        );
+        assert_eq!(
+            file.corrupt_path(),
+            PathBuf::from("/var/lib/tether/passes.json.corrupt")
+        );
+    }
+
+    #[test]
+    fn test_write_rotates_backups() {
+        let dir = tempdir().unwrap();
+        let file = DurableFile::new(dir.path().join("data")).with_backups(2);
+
+        for n in 1..=4 {
+            file.write(n.to_string().as_bytes()).unwrap();
+        }
+
+        assert_eq!(fs::read_to_string(file.path()).unwrap(), "4");
+        assert_eq!(fs::read_to_string(file.backup_path(1)).unwrap(), "3");
+        assert_eq!(fs::read_to_string(file.backup_path(2)).unwrap(), "2");
+        assert!(!file.backup_path(3).exists());
+        assert!(!dir.path().join("data.tmp").exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_write_restricts_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempdir().unwrap();
+        let file = DurableFile::new(dir.path().join("data"));
+        file.write(b"1").unwrap();
+
+        let mode = fs::metadata(file.path()).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+
+    #[test]
+    fn test_discard_history() {
+        let dir = tempdir().unwrap();
+        let file = DurableFile::new(dir.path().join("data"));
+        for n in 1..=4 {
+            file.write(n.to_string().as_bytes()).unwrap();
+        }
+        fs::write(file.corrupt_path(), "garbage").unwrap();
+
+        file.discard_history().unwrap();
+
+        assert_eq!(fs::read_to_string(file.path()).unwrap(), "4");
+        for n in 1..=DurableFile::DEFAULT_BACKUPS {
+            assert!(!file.backup_path(n).exists());
+        }
+        assert!(!file.corrupt_path().exists());
+        // Nothing left to discard is not an error
+        file.discard_history().unwrap();
+    }
+
+    #[test]
+    fn test_load_missing_file() {
+        let dir = tempdir().unwrap();
+        let file = DurableFile::new(dir.path().join("data"));
+        assert!(matches!(file.load(parse_number), Ok(None)));
+    }
+
+    #[test]
+    fn test_load_recovers_newest_valid_backup() {
+        let dir = tempdir().unwrap();
+        let file = DurableFile::new(dir.path().join("data"));
+        file.write(b"1").unwrap();
+        file.write(b"2").unwrap();
+        file.write(b"3").unwrap();
+
+        // Corrupt the primary and the newest backup
+        fs::write(file.path(), "").unwrap();
+        fs::write(file.backup_path(1), "garbage").unwrap();
+
+        assert_eq!(file.load(parse_number).unwrap(), Some(1));
+        assert_eq!(fs::read_to_string(file.path()).unwrap(), "1");
+        assert_eq!(fs::read_to_string(file.corrupt_path()).unwrap(), "");
+
+        // The restored file loads normally afterwards
+        assert_eq!(file.load(parse_number).unwrap(), Some(1));
+    }
+
+    #[test]
+    fn test_load_without_valid_backup_fails() {
+        let dir = tempdir().unwrap();
+        let file = DurableFile::new(dir.path().join("data"));
+        fs::write(file.path(), "garbage").unwrap();
+
+        assert!(matches!(file.load(parse_number), Err(LoadError::Parse(_))));
+        // The corrupt file is left in place for inspection
+        assert_eq!(fs::read_to_string(file.path()).unwrap(), "garbage");
+    }
 }
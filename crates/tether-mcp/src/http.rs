@@ -0,0 +1,173 @@
+//! Streamable HTTP transport.
+//!
+//! Serves MCP at `/mcp` using rmcp's streamable HTTP service, so one
+//! tether-mcp on a home server can be shared by several AI clients. Each
+//! client gets its own session (the `Mcp-Session-Id` header); sessions that
+//! stay idle are closed. When a token is configured, every request must
+//! carry it as `Authorization: Bearer <token>`.
+
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::Router;
+use axum::extract::{Request, State};
+use axum::http::{StatusCode, header};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use rmcp::RoleServer;
+use rmcp::transport::streamable_http_server::session::local::{LocalSessionManager, SessionConfig};
+use rmcp::transport::{StreamableHttpServerConfig, StreamableHttpService};
+use sha2::{Digest, Sha256};
+use tokio_util::sync::CancellationToken;
+use tracing::info;
+
+/// Path the MCP endpoint is served at.
+pub const MCP_PATH: &str = "/mcp";
+
+/// How long a session may stay idle before it is closed.
+const SESSION_IDLE_TIMEOUT: Duration = Duration::from_mins(30);
+
+/// Builds the router serving `factory`'s MCP service at [`MCP_PATH`].
+///
+/// `factory` is called once per session. Cancelling `shutdown` ends all
+/// sessions.
+pub fn router<S>(
+    factory: impl Fn() -> S + Send + Sync + 'static,
+    auth_token: Option<&str>,
+    shutdown: CancellationToken,
+) -> Router
+where
+    S: rmcp::Service<RoleServer> + Send + 'static,
+{
+    let session_manager = LocalSessionManager {
+        session_config: SessionConfig {
+            keep_alive: Some(SESSION_IDLE_TIMEOUT),
+            ..SessionConfig::default()
+        },
+        ..LocalSessionManager::default()
+    };
+    let config = StreamableHttpServerConfig {
+        cancellation_token: shutdown,
+        ..StreamableHttpServerConfig::default()
+    };
+    let service =
+        StreamableHttpService::new(move || Ok(factory()), Arc::new(session_manager), config);
+
+    let router = Router::new().nest_service(MCP_PATH, service);
+
+    match auth_token {
+        Some(token) => {
+            let expected = Arc::new(Sha256::digest(token.as_bytes()));
+            router.layer(middleware::from_fn_with_state(expected, require_token))
+        }
+        None => router,
+    }
+}
+
+/// Serves `router` on `addr` until `shutdown` is cancelled.
+pub async fn serve(
+    addr: SocketAddr,
+    router: Router,
+    shutdown: CancellationToken,
+) -> std::io::Result<()> {
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    info!(
+        "Serving MCP at http://{}{}",
+        listener.local_addr()?,
+        MCP_PATH
+    );
+
+    axum::serve(listener, router)
+        .with_graceful_shutdown(async move { shutdown.cancelled().await })
+        .await
+}
+
+/// Rejects requests without the configured bearer token.
+async fn require_token(
+    State(expected): State<Arc<sha2::digest::Output<Sha256>>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let given = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .unwrap_or_default();
+
+    // Compare digests so the time taken doesn't reveal how much of the token matched
+    if Sha256::digest(given.as_bytes()) == *expected {
+        next.run(request).await
+    } else {
+        (
+            StatusCode::UNAUTHORIZED,
+            [(header::WWW_AUTHENTICATE, "Bearer")],
+            "Missing or invalid bearer token",
+        )
+            .into_response()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use axum::body::Body;
+    use rmcp::ServerHandler;
+    use tower::ServiceExt;
+
+    #[derive(Clone)]
+    struct EmptyServer;
+
+    impl ServerHandler for EmptyServer {}
+
+    fn initialize(token: Option<&str>) -> Request {
+        let body = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "initialize",
+            "params": {
+                "protocolVersion": "2025-03-26",
+                "capabilities": {},
+                "clientInfo": { "name": "test", "version": "0.0.0" }
+            }
+        });
+
+        let mut request = axum::http::Request::post(MCP_PATH)
+            .header(header::CONTENT_TYPE, "application/json")
+            .header(header::ACCEPT, "application/json, text/event-stream");
+        if let Some(token) = token {
+            request = request.header(header::AUTHORIZATION, format!("Bearer {token}"));
+        }
+        request.body(Body::from(body.to_string())).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_initialize_opens_session() {
+        let router = router(|| EmptyServer, None, CancellationToken::new());
+
+        let response = router.oneshot(initialize(None)).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(response.headers().contains_key("mcp-session-id"));
+    }
+
+    #[tokio::test]
+    async fn test_token_required() {
+        let router = router(|| EmptyServer, Some("s3cret"), CancellationToken::new());
+
+        let response = router.clone().oneshot(initialize(None)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        let response = router
+            .clone()
+            .oneshot(initialize(Some("wrong")))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        let response = router.oneshot(initialize(Some("s3cret"))).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+}
@@ -0,0 +1,408 @@
+//! Named tether instances.
+//!
+//! One MCP server can talk to several tether servers, such as a Pi at home
+//! and one at a partner's place. They are listed in a TOML file named by
+//! `TETHER_MCP_INSTANCES`:
+//!
+//! ```toml
+//! default = "home"
+//!
+//! [[instance]]
+//! name = "home"
+//! url = "http://192.168.1.20:8080"
+//!
+//! [[instance]]
+//! name = "partner"
+//! ticket = "endpoint..."
+//! token = "s3cret"
+//! ```
+//!
+//! Each instance is reached like a single server configured from the
+//! environment: `connection` picks the route ("auto" by default), and in
+//! auto or LAN mode only the LAN instance named `lan_name` (by default the
+//! instance's own name) is used. `token` is sent as a bearer token, for
+//! servers behind an authenticating proxy, but never to an instance found on
+//! the LAN. An instance reached directly in auto mode falls back to its
+//! ticket if it stops answering.
+//!
+//! Tools take an `instance` parameter; without it they use the default
+//! instance, the one named by `default` or else the first listed.
+
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde::Deserialize;
+use tether_client::TetherClient;
+use tether_connect::{Connection, ConnectionConfig, ConnectionMode, Link, TunnelOptions};
+use tokio::sync::Mutex;
+use tracing::warn;
+use url::Url;
+
+use crate::MCP_ACTOR;
+
+/// Name of the instance configured from the environment.
+pub const DEFAULT_INSTANCE: &str = "default";
+
+/// How to reach one named instance.
+#[derive(Debug, Clone)]
+pub struct InstanceConfig {
+    /// Name agents use to pick the instance.
+    pub name: String,
+    /// How to reach it.
+    pub connection: ConnectionConfig,
+}
+
+/// The instances file.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct InstancesFile {
+    default: Option<String>,
+    #[serde(default, rename = "instance")]
+    instances: Vec<InstanceEntry>,
+}
+
+/// One `[[instance]]` table.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct InstanceEntry {
+    name: String,
+    connection: Option<String>,
+    url: Option<String>,
+    ticket: Option<String>,
+    lan_name: Option<String>,
+    token: Option<String>,
+    #[serde(default)]
+    local_port: u16,
+}
+
+/// Parses an instances file, returning the default instance first.
+pub fn parse(contents: &str, tunnel: TunnelOptions) -> Result<Vec<InstanceConfig>, String> {
+    let file: InstancesFile = toml::from_str(contents).map_err(|e| e.to_string())?;
+    if file.instances.is_empty() {
+        return Err("no [[instance]] tables".to_string());
+    }
+
+    let mut instances = Vec::with_capacity(file.instances.len());
+    for entry in file.instances {
+        let name = entry.name.trim().to_string();
+        if name.is_empty() {
+            return Err("an instance has an empty name".to_string());
+        }
+        // Names appear as a segment of resource URIs
+        if name.contains('/') {
+            return Err(format!("instance '{name}' has a '/' in its name"));
+        }
+        if instances
+            .iter()
+            .any(|other: &InstanceConfig| other.name.eq_ignore_ascii_case(&name))
+        {
+            return Err(format!("instance '{name}' is listed twice"));
+        }
+        let connection = connection_config(&name, entry, tunnel)
+            .map_err(|e| format!("instance '{name}': {e}"))?;
+        instances.push(InstanceConfig { name, connection });
+    }
+
+    if let Some(default) = file.default {
+        let index = instances
+            .iter()
+            .position(|instance| instance.name.eq_ignore_ascii_case(default.trim()))
+            .ok_or_else(|| format!("default instance '{default}' is not listed"))?;
+        let default = instances.remove(index);
+        instances.insert(0, default);
+    }
+
+    Ok(instances)
+}
+
+/// Validates an entry the way the environment is validated for one server.
+fn connection_config(
+    name: &str,
+    entry: InstanceEntry,
+    tunnel: TunnelOptions,
+) -> Result<ConnectionConfig, String> {
+    let mode = match &entry.connection {
+        Some(mode) => mode.parse().map_err(|mode| {
+            format!("connection must be auto, direct, lan or ticket, not '{mode}'")
+        })?,
+        None => ConnectionMode::Auto,
+    };
+
+    let base_url = match entry
+        .url
+        .as_deref()
+        .map(str::trim)
+        .filter(|url| !url.is_empty())
+    {
+        Some(url) => {
+            let url = Url::parse(url).map_err(|e| format!("url is not a valid URL: {e}"))?;
+            if !matches!(url.scheme(), "http" | "https") {
+                return Err(format!("url {url} is not an http(s) URL"));
+            }
+            Some(url)
+        }
+        None => None,
+    };
+
+    if entry
+        .ticket
+        .as_ref()
+        .is_some_and(|ticket| ticket.trim().is_empty())
+    {
+        return Err("ticket is empty".to_string());
+    }
+
+    match mode {
+        ConnectionMode::Direct if base_url.is_none() => {
+            return Err("direct connection needs a url".to_string());
+        }
+        ConnectionMode::Ticket if entry.ticket.is_none() => {
+            return Err("ticket connection needs a ticket".to_string());
+        }
+        _ => {}
+    }
+
+    Ok(ConnectionConfig {
+        mode,
+        base_url,
+        ticket: entry.ticket,
+        lan_name: Some(entry.lan_name.unwrap_or_else(|| name.to_string())),
+        token: entry.token.filter(|token| !token.trim().is_empty()),
+        local_port: entry.local_port,
+        tunnel,
+    })
+}
+
+/// A connected instance.
+#[derive(Clone)]
+pub struct Instance {
+    /// Name agents use to pick the instance.
+    pub name: String,
+    config: ConnectionConfig,
+    route: Arc<Mutex<Route>>,
+}
+
+/// The current route to an instance, replaced when it falls back to the
+/// tunnel.
+struct Route {
+    client: TetherClient,
+    link: Link,
+    /// `None` once shut down.
+    connection: Option<Connection>,
+}
+
+impl Instance {
+    /// Wraps an established connection to the instance.
+    pub fn new(name: String, config: ConnectionConfig, connection: Connection) -> Self {
+        let route = Route {
+            client: client(&connection, &config),
+            link: connection.link(),
+            connection: Some(connection),
+        };
+        Self {
+            name,
+            config,
+            route: Arc::new(Mutex::new(route)),
+        }
+    }
+
+    /// Returns a client for its API over the current route.
+    pub async fn client(&self) -> TetherClient {
+        self.route.lock().await.client.clone()
+    }
+
+    /// Returns how it is currently reached.
+    pub async fn link(&self) -> Link {
+        self.route.lock().await.link.clone()
+    }
+
+    /// Falls back to the tunnel if a direct route stopped answering, then
+    /// waits up to `wait` for the tunnel if it is down, failing with a
+    /// description of the tunnel's status if it stays down.
+    pub async fn ensure_connected(&self, wait: Duration) -> Result<(), String> {
+        let link = {
+            // Held while falling back, so concurrent calls don't both do it
+            let mut route = self.route.lock().await;
+            self.fall_back(&mut route).await;
+            route.link.clone()
+        };
+        let Link::Tunnel(tunnel) = link else {
+            return Ok(());
+        };
+        tunnel
+            .wait_connected(wait)
+            .await
+            .map_err(|status| status.unavailable_message())
+    }
+
+    async fn fall_back(&self, route: &mut Route) {
+        let Some(connection) = &route.connection else {
+            return;
+        };
+        match connection.fall_back(&self.config).await {
+            Some(Ok(tunnel)) => {
+                route.client = client(&tunnel, &self.config);
+                route.link = tunnel.link();
+                if let Some(direct) = route.connection.replace(tunnel) {
+                    direct.shutdown().await;
+                }
+            }
+            Some(Err(e)) => warn!(
+                "Instance '{}' can't fall back to the tunnel: {}",
+                self.name, e
+            ),
+            None => {}
+        }
+    }
+
+    /// Closes the connection, if not already closed.
+    pub async fn shutdown(&self) {
+        let connection = self.route.lock().await.connection.take();
+        if let Some(connection) = connection {
+            connection.shutdown().await;
+        }
+    }
+}
+
+impl fmt::Debug for Instance {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Instance")
+            .field("name", &self.name)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Builds a client for the API over `connection`.
+fn client(connection: &Connection, config: &ConnectionConfig) -> TetherClient {
+    let client = TetherClient::new(connection.base_url()).with_actor(MCP_ACTOR);
+    match connection.token(config) {
+        Some(token) => client.with_token(token),
+        None => client,
+    }
+}
+
+/// Finds an instance by name, or the default one (the first) if `name` is
+/// `None`.
+pub fn find<'a>(instances: &'a [Instance], name: Option<&str>) -> Result<&'a Instance, String> {
+    let Some(name) = name.map(str::trim).filter(|name| !name.is_empty()) else {
+        return instances
+            .first()
+            .ok_or_else(|| "No instances are configured".to_string());
+    };
+    instances
+        .iter()
+        .find(|instance| instance.name.eq_ignore_ascii_case(name))
+        .ok_or_else(|| {
+            let names: Vec<_> = instances
+                .iter()
+                .map(|instance| instance.name.as_str())
+                .collect();
+            format!(
+                "Unknown instance '{name}'. Known instances: {}",
+                names.join(", ")
+            )
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn instance(name: &str) -> Instance {
+        let base_url: Url = "http://localhost:3000".parse().unwrap();
+        let config = ConnectionConfig {
+            mode: ConnectionMode::Direct,
+            base_url: Some(base_url.clone()),
+            ticket: None,
+            lan_name: None,
+            token: None,
+            local_port: 0,
+            tunnel: TunnelOptions::default(),
+        };
+        let connection = Connection::Direct {
+            base_url,
+            instance: None,
+        };
+        Instance::new(name.to_string(), config, connection)
+    }
+
+    #[test]
+    fn test_parse() {
+        let instances = parse(
+            r#"
+            default = "partner"
+
+            [[instance]]
+            name = "home"
+            url = "http://192.168.1.20:8080"
+
+            [[instance]]
+            name = "partner"
+            ticket = "endpoint12345"
+            token = "s3cret"
+            "#,
+            TunnelOptions::default(),
+        )
+        .unwrap();
+
+        let names: Vec<_> = instances
+            .iter()
+            .map(|instance| instance.name.as_str())
+            .collect();
+        assert_eq!(names, ["partner", "home"]);
+
+        let partner = &instances[0].connection;
+        assert_eq!(partner.mode, ConnectionMode::Auto);
+        assert_eq!(partner.ticket.as_deref(), Some("endpoint12345"));
+        assert_eq!(partner.token.as_deref(), Some("s3cret"));
+        assert_eq!(partner.lan_name.as_deref(), Some("partner"));
+
+        let home = &instances[1].connection;
+        assert_eq!(
+            home.base_url.as_ref().unwrap().as_str(),
+            "http://192.168.1.20:8080/"
+        );
+    }
+
+    #[test]
+    fn test_parse_rejects_invalid() {
+        let options = TunnelOptions::default();
+        assert!(parse("", options).is_err());
+        assert!(
+            parse(
+                "[[instance]]\nname = \"home\"\nconnection = \"direct\"",
+                options
+            )
+            .is_err()
+        );
+        assert!(parse("[[instance]]\nname = \"home\"\nurl = \"ftp://pi\"", options).is_err());
+        assert!(
+            parse(
+                "[[instance]]\nname = \"home\"\n[[instance]]\nname = \"Home\"",
+                options
+            )
+            .is_err()
+        );
+        assert!(parse("default = \"away\"\n[[instance]]\nname = \"home\"", options).is_err());
+        assert!(parse("[[instance]]\nname = \"home/bed\"", options).is_err());
+        assert!(
+            parse(
+                "[[instance]]\nname = \"home\"\npassword = \"hunter2\"",
+                options
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn test_find() {
+        let instances = [instance("home"), instance("partner")];
+
+        assert_eq!(find(&instances, None).unwrap().name, "home");
+        assert_eq!(find(&instances, Some("Partner")).unwrap().name, "partner");
+
+        let error = find(&instances, Some("office")).unwrap_err();
+        assert!(error.contains("Known instances: home, partner"));
+    }
+}
@@ -1,57 +1,109 @@
 //! Tether MCP Server
 //!
-//! This MCP server connects to a Raspberry Pi running the Tether HTTP server
-//! via dumbpipe (iroh-based secure tunnel) and exposes filtered API endpoints
-//! as MCP tools for AI agents.
+//! This MCP server connects to one or more Raspberry Pis running the Tether
+//! HTTP server directly, on the LAN, or via dumbpipe (iroh-based secure
+//! tunnel) and exposes filtered API endpoints as MCP tools for AI agents.
 //!
 //! # Architecture
 //!
-//! 1. Read TETHER_DUMBPIPE_TICKET from environment
-//! 2. Spawn dumbpipe as a subprocess in connect-tcp mode
-//! 3. Wait for dumbpipe to establish connection
-//! 4. Proxy API requests through the tunnel
-//! 5. Expose MCP tools for proximity and pass management
+//! 1. Read the connection settings from environment, or the named instances
+//!    from the file in `TETHER_MCP_INSTANCES`
+//! 2. Reach the server at `TETHER_URL`, on the LAN over mDNS, or over iroh
+//!    with `TETHER_DUMBPIPE_TICKET`, forwarding a local port to it
+//! 3. Proxy API requests to the server
+//! 4. Expose MCP tools for proximity, passes, settings and system status,
+//!    limited to the configured scope and the one the server grants,
+//!    resources for the passes, proximity and config, and prompts for
+//!    reviewing them
+//! 5. Follow the server's event stream to notify subscribers of resources
+//!    when they change
 //!
 //! # Environment Variables
 //!
-//! - `TETHER_DUMBPIPE_TICKET`: Required. The dumbpipe ticket for connecting to the Pi
-//! - `TETHER_LOCAL_PORT`: Optional. Local port for dumbpipe tunnel (default: 38080)
+//! - `TETHER_CONNECTION`: Optional. "auto", "direct", "lan" or "ticket" (default: auto).
+//!   Auto uses `TETHER_URL` if set, else a server found on the LAN, else the ticket,
+//!   and falls back to the ticket if the server stops answering directly
+//! - `TETHER_URL`: Optional. Base URL of the server, e.g. `http://localhost:3000`
+//! - `TETHER_DUMBPIPE_TICKET`: Optional. The dumbpipe ticket for connecting to the Pi
+//! - `TETHER_LOCAL_PORT`: Optional. Local port for the tunnel (default: 38080)
+//! - `TETHER_LAN_NAME`: Optional. The LAN instance to use. With a ticket set, auto
+//!   only searches the LAN for a named instance, and otherwise uses the tunnel
+//! - `TETHER_MCP_INSTANCES`: Optional. Path of a TOML file listing several named
+//!   instances, used instead of the variables above (see [`instances`])
 //! - `RUST_LOG`: Optional. Logging level (default: info)
 //! - `MCP_TRANSPORT`: Optional. "stdio" or "streamable-http" (default: stdio)
+//! - `MCP_HTTP_BIND`: Optional. Address for HTTP transport to bind (default: 127.0.0.1)
 //! - `MCP_HTTP_PORT`: Optional. Port for HTTP transport (default: 8080)
-
-use std::net::SocketAddr;
-use std::process::Stdio;
+//! - `MCP_AUTH_TOKEN`: Optional. Bearer token HTTP clients must send
+//! - `TETHER_MCP_SCOPE`: Optional. "read", "passes" or "settings" (default: passes).
+//!   What agents may do: only read, also use passes, or also change settings. Narrowed
+//!   to the widest scope the tether servers grant the tokens sent to them
+
+mod http;
+mod instances;
+mod prompts;
+mod resources;
+mod scope;
+
+use std::fmt::Write;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
 use std::time::Duration;
 
 use anyhow::{Context, Result};
 use rmcp::{
-    ErrorData as McpError, ServerHandler, ServiceExt,
-    handler::server::{router::tool::ToolRouter, wrapper::Parameters},
+    ErrorData as McpError, RoleServer, ServerHandler, ServiceExt,
+    handler::server::{
+        router::{prompt::PromptRouter, tool::ToolRouter},
+        wrapper::Parameters,
+    },
     model::*,
-    schemars,
+    prompt_handler, schemars,
+    service::RequestContext,
     tool, tool_handler, tool_router,
 };
 use serde::{Deserialize, Serialize};
-use tokio::io::{AsyncBufReadExt, BufReader};
-use tokio::process::{Child, Command};
-use tokio::sync::oneshot;
-use tokio::time::timeout;
-use tracing::{debug, error, info, warn};
+use tether_client::Guarded;
+use tether_client::types::{
+    ConfigResponse, PassesResponse, PendingChangeResponse, ProximityResponse, ScanDevicesResponse,
+    ScannerState, SystemStatusResponse,
+};
+use tether_connect::{
+    Connection, ConnectionConfig, ConnectionMode, Link, TunnelOptions, TunnelState, TunnelStatus,
+};
+use tokio::sync::{broadcast, oneshot};
+use tokio_util::sync::CancellationToken;
+use tracing::{error, info, warn};
 use url::Url;
 
+use crate::instances::{Instance, InstanceConfig};
+use crate::resources::{InstanceChange, Subscriptions, TetherResource};
+use crate::scope::Scope;
+
 /// Environment variable names
 mod env_vars {
+    pub const CONNECTION: &str = "TETHER_CONNECTION";
+    pub const URL: &str = "TETHER_URL";
     pub const DUMBPIPE_TICKET: &str = "TETHER_DUMBPIPE_TICKET";
     pub const LOCAL_PORT: &str = "TETHER_LOCAL_PORT";
+    pub const LAN_NAME: &str = "TETHER_LAN_NAME";
+    pub const INSTANCES: &str = "TETHER_MCP_INSTANCES";
     pub const MCP_TRANSPORT: &str = "MCP_TRANSPORT";
+    pub const MCP_HTTP_BIND: &str = "MCP_HTTP_BIND";
     pub const MCP_HTTP_PORT: &str = "MCP_HTTP_PORT";
+    pub const MCP_AUTH_TOKEN: &str = "MCP_AUTH_TOKEN";
+    pub const SCOPE: &str = "TETHER_MCP_SCOPE";
 }
 
+/// Name recorded in the server's audit log for changes made by agents
+const MCP_ACTOR: &str = "mcp";
+
 /// Default configuration values
 mod defaults {
     pub const LOCAL_PORT: u16 = 38080;
-    pub const DUMBPIPE_CONNECT_TIMEOUT_SECS: u64 = 30;
+    pub const CONNECT_TIMEOUT_SECS: u64 = 30;
+    pub const RECONNECT_WAIT_SECS: u64 = 5;
+    pub const HTTP_BIND: std::net::IpAddr = std::net::IpAddr::V4(std::net::Ipv4Addr::LOCALHOST);
     pub const HTTP_PORT: u16 = 8080;
 }
 
@@ -64,30 +116,45 @@ pub enum TetherMcpError {
     #[error("TETHER_DUMBPIPE_TICKET is empty or invalid: {0}")]
     InvalidTicket(String),
 
-    #[error("Failed to spawn dumbpipe subprocess: {0}")]
-    DumbpipeSpawnFailed(#[source] std::io::Error),
+    #[error("TETHER_URL environment variable is not set")]
+    UrlNotSet,
+
+    #[error("TETHER_URL is not a valid URL: {0}")]
+    InvalidUrl(String),
 
-    #[error("Dumbpipe failed to connect within {0} seconds")]
-    DumbpipeConnectionTimeout(u64),
+    #[error("TETHER_CONNECTION must be auto, direct, lan or ticket, not '{0}'")]
+    InvalidConnectionMode(String),
 
-    #[error("Dumbpipe process exited unexpectedly: {0}")]
-    DumbpipeExitedUnexpectedly(String),
+    #[error("MCP_HTTP_BIND is not an IP address: {0}")]
+    InvalidBindAddress(String),
+
+    #[error("TETHER_MCP_SCOPE must be read, passes or settings, not '{0}'")]
+    InvalidScope(String),
+
+    #[error("TETHER_MCP_INSTANCES file {path} is invalid: {reason}")]
+    InvalidInstances { path: String, reason: String },
 }
 
 /// Configuration for the MCP server
 #[derive(Debug, Clone)]
 pub struct Config {
-    /// The dumbpipe ticket for connecting to the Raspberry Pi
-    pub dumbpipe_ticket: String,
-
-    /// Local port for the dumbpipe tunnel
-    pub local_port: u16,
+    /// The tether servers to reach, the default one first
+    pub instances: Vec<InstanceConfig>,
 
     /// Transport mode: "stdio" or "streamable-http"
     pub transport_mode: TransportMode,
 
+    /// Address for HTTP transport to bind
+    pub http_bind: IpAddr,
+
     /// Port for HTTP transport
     pub http_port: u16,
+
+    /// Bearer token HTTP clients must send, if any
+    pub auth_token: Option<String>,
+
+    /// What agents may do
+    pub scope: Scope,
 }
 
 /// Transport mode for the MCP server
@@ -95,27 +162,41 @@ pub struct Config {
 pub enum TransportMode {
     /// Standard input/output - for local use with Claude Desktop
     Stdio,
-    /// HTTP/SSE - for Cloud Run deployment
+    /// Streamable HTTP - for sharing one server between several clients
     StreamableHttp,
 }
 
 impl Config {
     /// Load configuration from environment variables
     pub fn from_env() -> Result<Self, TetherMcpError> {
-        let dumbpipe_ticket = std::env::var(env_vars::DUMBPIPE_TICKET)
-            .map_err(|_| TetherMcpError::TicketNotSet)?;
+        Self::from_lookup(|name| std::env::var(name).ok())
+    }
 
-        if dumbpipe_ticket.trim().is_empty() {
-            return Err(TetherMcpError::InvalidTicket("Ticket is empty".to_string()));
-        }
+    /// Load configuration from variables returned by `lookup`
+    fn from_lookup(lookup: impl Fn(&str) -> Option<String>) -> Result<Self, TetherMcpError> {
+        let tunnel = TunnelOptions {
+            connect_timeout: Duration::from_secs(defaults::CONNECT_TIMEOUT_SECS),
+            ..TunnelOptions::default()
+        };
 
-        let local_port = std::env::var(env_vars::LOCAL_PORT)
-            .ok()
-            .and_then(|s| s.parse().ok())
-            .unwrap_or(defaults::LOCAL_PORT);
+        let instances = match lookup(env_vars::INSTANCES).filter(|path| !path.trim().is_empty()) {
+            Some(path) => {
+                let invalid = |reason: String| TetherMcpError::InvalidInstances {
+                    path: path.clone(),
+                    reason,
+                };
+                let contents =
+                    std::fs::read_to_string(&path).map_err(|e| invalid(e.to_string()))?;
+                instances::parse(&contents, tunnel).map_err(invalid)?
+            }
+            None => vec![InstanceConfig {
+                name: instances::DEFAULT_INSTANCE.to_string(),
+                connection: Self::connection_from_lookup(&lookup, tunnel)?,
+            }],
+        };
 
-        let transport_mode = match std::env::var(env_vars::MCP_TRANSPORT)
-            .unwrap_or_else(|_| "stdio".to_string())
+        let transport_mode = match lookup(env_vars::MCP_TRANSPORT)
+            .unwrap_or_else(|| "stdio".to_string())
             .to_lowercase()
             .as_str()
         {
@@ -123,281 +204,510 @@ impl Config {
             _ => TransportMode::Stdio,
         };
 
-        let http_port = std::env::var(env_vars::MCP_HTTP_PORT)
-            .ok()
+        let http_bind = match lookup(env_vars::MCP_HTTP_BIND) {
+            Some(bind) => bind
+                .trim()
+                .parse()
+                .map_err(|_| TetherMcpError::InvalidBindAddress(bind))?,
+            None => defaults::HTTP_BIND,
+        };
+
+        let http_port = lookup(env_vars::MCP_HTTP_PORT)
             .and_then(|s| s.parse().ok())
             .unwrap_or(defaults::HTTP_PORT);
 
+        let auth_token = lookup(env_vars::MCP_AUTH_TOKEN).filter(|token| !token.trim().is_empty());
+
+        let scope = match lookup(env_vars::SCOPE) {
+            Some(scope) => scope.parse().map_err(TetherMcpError::InvalidScope)?,
+            None => Scope::default(),
+        };
+
         Ok(Self {
-            dumbpipe_ticket,
-            local_port,
+            instances,
             transport_mode,
+            http_bind,
             http_port,
+            auth_token,
+            scope,
         })
     }
-}
-
-/// Manages the dumbpipe subprocess lifecycle
-pub struct DumbpipeManager {
-    child: Child,
-    local_addr: SocketAddr,
-}
-
-impl DumbpipeManager {
-    /// Spawn dumbpipe in connect-tcp mode and wait for it to be ready
-    pub async fn spawn_and_wait(ticket: &str, local_port: u16) -> Result<Self, TetherMcpError> {
-        info!("Spawning dumbpipe connect-tcp on port {}", local_port);
-
-        let local_addr: SocketAddr = format!("127.0.0.1:{local_port}")
-            .parse()
-            .expect("Valid socket address");
-
-        let mut child = Command::new("dumbpipe")
-            .arg("connect-tcp")
-            .arg("--addr")
-            .arg(local_addr.to_string())
-            .arg(ticket)
-            .stdin(Stdio::null())
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .kill_on_drop(true)
-            .spawn()
-            .map_err(TetherMcpError::DumbpipeSpawnFailed)?;
-
-        let stderr = child
-            .stderr
-            .take()
-            .expect("stderr was configured as piped");
-
-        let ready = Self::wait_for_ready(stderr, defaults::DUMBPIPE_CONNECT_TIMEOUT_SECS).await?;
-
-        if !ready {
-            if let Ok(Some(status)) = child.try_wait() {
-                return Err(TetherMcpError::DumbpipeExitedUnexpectedly(format!(
-                    "Exit status: {status}"
-                )));
-            }
-            return Err(TetherMcpError::DumbpipeConnectionTimeout(
-                defaults::DUMBPIPE_CONNECT_TIMEOUT_SECS,
-            ));
-        }
-
-        info!(
-            "Dumbpipe connected successfully, tunnel available at {}",
-            local_addr
-        );
-
-        Ok(Self { child, local_addr })
-    }
-
-    async fn wait_for_ready(
-        stderr: tokio::process::ChildStderr,
-        timeout_secs: u64,
-    ) -> Result<bool, TetherMcpError> {
-        let mut reader = BufReader::new(stderr).lines();
-
-        let wait_future = async {
-            while let Ok(Some(line)) = reader.next_line().await {
-                debug!("dumbpipe: {}", line);
 
-                if line.contains("listening")
-                    || line.contains("connected")
-                    || line.contains("forwarding")
-                    || line.contains("Listening")
-                    || line.contains("Connected")
-                {
-                    return true;
-                }
+    /// Load the connection to a single server from variables returned by `lookup`
+    fn connection_from_lookup(
+        lookup: &impl Fn(&str) -> Option<String>,
+        tunnel: TunnelOptions,
+    ) -> Result<ConnectionConfig, TetherMcpError> {
+        let mode = match lookup(env_vars::CONNECTION) {
+            Some(mode) => mode
+                .parse()
+                .map_err(TetherMcpError::InvalidConnectionMode)?,
+            None => ConnectionMode::Auto,
+        };
 
-                if line.contains("error") || line.contains("Error") || line.contains("failed") {
-                    error!("Dumbpipe error: {}", line);
-                    return false;
+        let base_url = match lookup(env_vars::URL).filter(|url| !url.trim().is_empty()) {
+            Some(url) => {
+                let url = Url::parse(url.trim())
+                    .map_err(|e| TetherMcpError::InvalidUrl(e.to_string()))?;
+                if !matches!(url.scheme(), "http" | "https") {
+                    return Err(TetherMcpError::InvalidUrl(format!(
+                        "{url} is not an http(s) URL"
+                    )));
                 }
+                Some(url)
             }
-            false
+            None => None,
         };
 
-        match timeout(Duration::from_secs(timeout_secs), wait_future).await {
-            Ok(result) => Ok(result),
-            Err(_) => {
-                warn!("Timeout waiting for dumbpipe ready signal, proceeding anyway...");
-                Ok(true)
-            }
+        let ticket = lookup(env_vars::DUMBPIPE_TICKET);
+        if ticket
+            .as_ref()
+            .is_some_and(|ticket| ticket.trim().is_empty())
+        {
+            return Err(TetherMcpError::InvalidTicket("Ticket is empty".to_string()));
         }
-    }
 
-    pub fn base_url(&self) -> Url {
-        Url::parse(&format!("http://{}", self.local_addr)).expect("Valid URL")
-    }
+        match mode {
+            ConnectionMode::Direct if base_url.is_none() => return Err(TetherMcpError::UrlNotSet),
+            ConnectionMode::Ticket if ticket.is_none() => return Err(TetherMcpError::TicketNotSet),
+            _ => {}
+        }
+
+        let local_port = lookup(env_vars::LOCAL_PORT)
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(defaults::LOCAL_PORT);
 
-    pub async fn shutdown(mut self) -> Result<()> {
-        info!("Shutting down dumbpipe...");
-        let _ = self.child.kill().await;
-        let _ = self.child.wait().await;
-        info!("Dumbpipe shutdown complete");
-        Ok(())
+        Ok(ConnectionConfig {
+            mode,
+            base_url,
+            ticket,
+            lan_name: lookup(env_vars::LAN_NAME).filter(|name| !name.trim().is_empty()),
+            token: None,
+            local_port,
+            tunnel,
+        })
     }
 }
 
-// API response types
-#[derive(Debug, Serialize, Deserialize)]
-pub struct ProximityResponse {
-    pub device_name: Option<String>,
-    pub is_nearby: bool,
-    pub rssi: Option<i16>,
-    pub threshold: i16,
-    pub last_seen: Option<String>,
+// Tool parameter types
+#[derive(Debug, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct InstanceArgs {
+    /// Name of the tether instance to use (see the instances in the server instructions). Defaults to the default instance.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub instance: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct PassesResponse {
-    pub remaining: u32,
-    pub total: u32,
-    pub month: String,
+#[derive(Debug, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct GetPassHistoryArgs {
+    /// Month to query in YYYY-MM format (e.g., '2025-01'). Defaults to current month if not provided.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub month: Option<String>,
+
+    /// Name of the tether instance to use (see the instances in the server instructions). Defaults to the default instance.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub instance: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct PassHistoryEntry {
-    pub used_at: String,
+#[derive(Debug, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct UsePassArgs {
+    /// The reason for using the pass (e.g., 'Early flight tomorrow', 'On-call for work')
     pub reason: String,
-}
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct PassHistoryResponse {
-    pub month: String,
-    pub entries: Vec<PassHistoryEntry>,
+    /// Name of the tether instance to use (see the instances in the server instructions). Defaults to the default instance.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub instance: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct UsePassRequest {
-    pub reason: String,
+#[derive(Debug, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct SetTimezoneArgs {
+    /// IANA timezone name (e.g., `America/Los_Angeles`). Passes reset at midnight in this timezone.
+    pub timezone: String,
+
+    /// Name of the tether instance to use (see the instances in the server instructions). Defaults to the default instance.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub instance: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct UsePassResponse {
-    pub success: bool,
-    pub remaining: u32,
-    pub message: String,
+#[derive(Debug, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct SetPassesPerMonthArgs {
+    /// Number of emergency passes per month (0-31)
+    pub per_month: u8,
+
+    /// Name of the tether instance to use (see the instances in the server instructions). Defaults to the default instance.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub instance: Option<String>,
 }
 
-/// HTTP client for talking to the Tether server through the dumbpipe tunnel
+/// The MCP server handler for Tether
 #[derive(Clone)]
-pub struct TetherClient {
-    client: reqwest::Client,
-    base_url: Url,
+pub struct TetherMcpServer {
+    instances: Arc<[Instance]>,
+    changes: broadcast::Sender<InstanceChange>,
+    subscriptions: Arc<Subscriptions>,
+    tool_router: ToolRouter<Self>,
+    prompt_router: PromptRouter<Self>,
 }
 
-impl TetherClient {
-    pub fn new(base_url: Url) -> Self {
-        let client = reqwest::Client::builder()
-            .timeout(Duration::from_secs(15))
-            .build()
-            .expect("Failed to create HTTP client");
+impl TetherMcpServer {
+    /// Returns a handler for a new session, sharing the connection but not
+    /// the resource subscriptions.
+    #[must_use]
+    pub fn session(&self) -> Self {
+        Self {
+            subscriptions: Arc::default(),
+            ..self.clone()
+        }
+    }
 
-        Self { client, base_url }
+    /// Returns the name of the default instance.
+    fn default_name(&self) -> &str {
+        &self.instances[0].name
     }
 
-    pub async fn get_proximity(&self) -> Result<ProximityResponse> {
-        let url = self.base_url.join("/api/proximity")?;
-        let resp = self
-            .client
-            .get(url)
-            .send()
-            .await
-            .context("Failed to fetch proximity")?;
-        resp.json().await.context("Failed to parse proximity response")
+    /// Finds the instance named `name`, or the default one, and waits
+    /// briefly for its tunnel if it is down, so a call made during a
+    /// reconnect succeeds once the Pi is back. Fails with a description of
+    /// the tunnel's status otherwise.
+    async fn ensure_connected(&self, name: Option<&str>) -> Result<&Instance, String> {
+        let instance = instances::find(&self.instances, name)?;
+        instance
+            .ensure_connected(Duration::from_secs(defaults::RECONNECT_WAIT_SECS))
+            .await?;
+        Ok(instance)
     }
 
-    pub async fn get_passes(&self) -> Result<PassesResponse> {
-        let url = self.base_url.join("/api/passes")?;
-        let resp = self
-            .client
-            .get(url)
-            .send()
+    /// Finds the instance a tool was asked to use like
+    /// [`Self::ensure_connected`], failing with a tool error result.
+    async fn connected_instance(&self, name: Option<&str>) -> Result<&Instance, CallToolResult> {
+        self.ensure_connected(name)
             .await
-            .context("Failed to fetch passes")?;
-        resp.json().await.context("Failed to parse passes response")
+            .map_err(|message| CallToolResult::error(vec![Content::text(message)]))
+    }
+
+    /// Returns the names of the instances other than the default one.
+    fn other_names(&self) -> Vec<&str> {
+        self.instances[1..]
+            .iter()
+            .map(|instance| instance.name.as_str())
+            .collect()
     }
 
-    pub async fn get_pass_history(&self, month: Option<&str>) -> Result<PassHistoryResponse> {
-        let mut url = self.base_url.join("/api/passes/history")?;
-        if let Some(m) = month {
-            url.set_query(Some(&format!("month={m}")));
+    /// Parses a resource URI, failing if it isn't one of ours or names an
+    /// instance that isn't configured.
+    fn parse_resource<'a>(
+        &self,
+        uri: &'a str,
+    ) -> Result<(Option<&'a str>, TetherResource), McpError> {
+        let not_found = |message: String| {
+            McpError::resource_not_found(message, Some(serde_json::json!({ "uri": uri })))
+        };
+        let (instance, resource) =
+            resources::parse(uri).ok_or_else(|| not_found(format!("Unknown resource: {uri}")))?;
+        instances::find(&self.instances, instance).map_err(not_found)?;
+        Ok((instance, resource))
+    }
+}
+
+/// Formats a duration as e.g. "2h 5m 3s".
+fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    let (hours, minutes, seconds) = (secs / 3600, secs / 60 % 60, secs % 60);
+    if hours > 0 {
+        format!("{hours}h {minutes}m {seconds}s")
+    } else if minutes > 0 {
+        format!("{minutes}m {seconds}s")
+    } else {
+        format!("{seconds}s")
+    }
+}
+
+/// Describes a direct connection for the `get_connection_status` tool.
+fn describe_direct(
+    base_url: &Url,
+    instance: Option<&str>,
+    health: &tether_client::Result<tether_client::types::HealthResponse>,
+) -> String {
+    let mut text = instance.map_or_else(
+        || format!("Connected directly to {base_url}."),
+        |instance| format!("Connected directly to '{instance}' at {base_url}, found on the LAN."),
+    );
+    let _ = match health {
+        Ok(health) => write!(text, "\nServer is up, running tether {}.", health.version),
+        Err(e) => write!(text, "\nServer is not answering: {e}"),
+    };
+    text
+}
+
+/// Describes the tunnel's health for the `get_connection_status` tool.
+fn describe_status(status: &TunnelStatus) -> String {
+    let mut text = format!(
+        "Tunnel to the Pi ({}): {}.",
+        status.endpoint_id.fmt_short(),
+        status.state
+    );
+
+    if let Some(connected_at) = status.connected_at {
+        let _ = write!(
+            text,
+            "\nConnected for {}.",
+            format_duration(connected_at.elapsed())
+        );
+    }
+    if status.reconnects > 0 {
+        let _ = write!(
+            text,
+            "\nReconnected {} times since startup.",
+            status.reconnects
+        );
+    }
+    if status.state != TunnelState::Connected {
+        if status.failed_attempts > 0 {
+            let _ = write!(text, "\nFailed attempts: {}.", status.failed_attempts);
         }
-        let resp = self
-            .client
-            .get(url)
-            .send()
-            .await
-            .context("Failed to fetch pass history")?;
-        resp.json()
-            .await
-            .context("Failed to parse pass history response")
-    }
-
-    pub async fn use_pass(&self, reason: &str) -> Result<UsePassResponse> {
-        let url = self.base_url.join("/api/passes/use")?;
-        let resp = self
-            .client
-            .post(url)
-            .json(&UsePassRequest {
-                reason: reason.to_string(),
-            })
-            .send()
-            .await
-            .context("Failed to use pass")?;
-        resp.json().await.context("Failed to parse use pass response")
+        if let Some(at) = status.next_attempt_at {
+            let wait = at.saturating_duration_since(std::time::Instant::now());
+            let _ = write!(text, "\nNext attempt in {}.", format_duration(wait));
+        }
+    }
+    if let Some(error) = &status.last_error {
+        let _ = write!(text, "\nLast error: {error}");
     }
+
+    text
 }
 
-// Tool parameter types
-#[derive(Debug, Serialize, Deserialize, schemars::JsonSchema)]
-pub struct GetPassHistoryArgs {
-    /// Month to query in YYYY-MM format (e.g., '2025-01'). Defaults to current month if not provided.
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub month: Option<String>,
+/// Checks one instance for the `summarize_instances` tool.
+async fn summarize_instance(instance: &Instance) -> String {
+    let wait = Duration::from_secs(defaults::RECONNECT_WAIT_SECS);
+    if let Err(unavailable) = instance.ensure_connected(wait).await {
+        return format!("- {}: unavailable. {unavailable}", instance.name);
+    }
+
+    let client = instance.client().await;
+    let (proximity, passes) = tokio::join!(client.get_proximity(), client.get_passes());
+    describe_summary(&instance.name, &proximity, &passes)
 }
 
-#[derive(Debug, Serialize, Deserialize, schemars::JsonSchema)]
-pub struct UsePassArgs {
-    /// The reason for using the pass (e.g., 'Early flight tomorrow', 'On-call for work')
-    pub reason: String,
+/// Describes one instance's proximity and passes in a line.
+fn describe_summary(
+    name: &str,
+    proximity: &tether_client::Result<ProximityResponse>,
+    passes: &tether_client::Result<PassesResponse>,
+) -> String {
+    let proximity = match proximity {
+        Ok(proximity) if proximity.is_nearby => "phone is nearby".to_string(),
+        Ok(_) => "phone is not nearby".to_string(),
+        Err(e) => format!("proximity unknown ({e})"),
+    };
+    let passes = match passes {
+        Ok(passes) => format!(
+            "{}/{} passes left for {}",
+            passes.remaining, passes.total_per_month, passes.month
+        ),
+        Err(e) => format!("passes unknown ({e})"),
+    };
+    format!("- {name}: {proximity}, {passes}.")
 }
 
-/// The MCP server handler for Tether
-#[derive(Clone)]
-pub struct TetherMcpServer {
-    client: TetherClient,
-    tool_router: ToolRouter<TetherMcpServer>,
+/// Describes the configuration for the `get_config` tool.
+fn describe_config(config: &ConfigResponse) -> String {
+    let bluetooth = &config.bluetooth;
+    let mut text = if bluetooth.is_configured {
+        format!(
+            "Tracked phone: {} ({}), nearby above {} dBm{}.",
+            bluetooth.target_name,
+            bluetooth.target_address,
+            bluetooth.rssi_threshold,
+            if bluetooth.is_paired { ", paired" } else { "" }
+        )
+    } else {
+        "No phone is tracked yet.".to_string()
+    };
+    let _ = write!(text, "\nTimezone: {}.", config.timezone);
+    let _ = write!(text, "\nPasses per month: {}.", config.passes_per_month);
+    if !config.onboarding_complete {
+        text.push_str("\nOnboarding is not complete.");
+    }
+    text
+}
+
+/// Describes a device scan for the `scan_devices` tool.
+fn describe_scan(scan: &ScanDevicesResponse) -> String {
+    if scan.devices.is_empty() {
+        return format!("No devices found in {}s.", scan.scan_duration_secs);
+    }
+
+    let mut text = format!(
+        "Found {} devices in {}s:\n",
+        scan.devices.len(),
+        scan.scan_duration_secs
+    );
+    for device in &scan.devices {
+        let name = device.name.as_deref().unwrap_or("(unnamed)");
+        let rssi = device
+            .rssi_dbm
+            .map_or_else(String::new, |r| format!(", {r} dBm"));
+        let paired = if device.paired { ", paired" } else { "" };
+        let _ = writeln!(text, "- {name} ({}{rssi}{paired})", device.address);
+    }
+    text
+}
+
+/// Describes the server's status for the `get_system_status` tool.
+fn describe_system_status(status: &SystemStatusResponse) -> String {
+    let health = &status.bluetooth_health;
+    let state = match health.state {
+        ScannerState::Healthy => "healthy",
+        ScannerState::Degraded => "degraded",
+        ScannerState::Recovering => "recovering",
+        ScannerState::Unavailable => "unavailable",
+    };
+
+    let mut text = format!(
+        "tether {} up for {}.\nBluetooth scanner: {state}.",
+        status.version,
+        format_duration(Duration::from_secs(status.uptime_secs))
+    );
+    if let Some(at) = &health.last_successful_scan_utc {
+        let _ = write!(text, "\nLast successful scan: {at}.");
+    }
+    if health.total_failures > 0 {
+        let _ = write!(
+            text,
+            "\nScan failures: {} in a row, {} in total, {} recoveries.",
+            health.consecutive_failures, health.total_failures, health.recoveries
+        );
+    }
+    if let Some(error) = &health.last_error {
+        let _ = write!(text, "\nLast error: {error}");
+    }
+    text
+}
+
+/// Describes a change waiting for the accountability partner's approval.
+fn describe_pending(change: &PendingChangeResponse) -> String {
+    let mut text = format!(
+        "The change needs the accountability partner's approval and has not been applied yet (change {}).",
+        change.id
+    );
+    match &change.effective_at_utc {
+        Some(at) => {
+            let _ = write!(text, "\nWithout approval it takes effect at {at}.");
+        }
+        None => text.push_str("\nIt only takes effect once approved."),
+    }
+    text
 }
 
 #[tool_router]
 impl TetherMcpServer {
-    pub fn new(client: TetherClient) -> Self {
+    /// Creates a handler for `instances`, the default one first.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `instances` is empty.
+    #[must_use]
+    pub fn new(
+        instances: Vec<Instance>,
+        changes: broadcast::Sender<InstanceChange>,
+        scope: Scope,
+    ) -> Self {
+        assert!(!instances.is_empty(), "At least one instance is needed");
+        let mut tool_router = Self::tool_router();
+        scope.restrict(&mut tool_router);
+
         Self {
-            client,
-            tool_router: Self::tool_router(),
+            instances: instances.into(),
+            changes,
+            subscriptions: Arc::default(),
+            tool_router,
+            prompt_router: Self::prompt_router(),
+        }
+    }
+
+    /// Report the health of the tunnel to the Pi
+    #[tool(
+        annotations(read_only_hint = true, open_world_hint = false),
+        description = "Check the health of the connection to the Raspberry Pi: whether it is reached directly, on the LAN, or through the tunnel, whether it is connected, how often it has reconnected, and the last error if it is down. Use this when other tools report connection problems."
+    )]
+    async fn get_connection_status(
+        &self,
+        Parameters(args): Parameters<InstanceArgs>,
+    ) -> Result<CallToolResult, McpError> {
+        let instance = match instances::find(&self.instances, args.instance.as_deref()) {
+            Ok(instance) => instance,
+            Err(unknown) => return Ok(CallToolResult::error(vec![Content::text(unknown)])),
+        };
+
+        let text = match &instance.link().await {
+            Link::Direct {
+                base_url,
+                instance: lan_name,
+            } => describe_direct(
+                base_url,
+                lan_name.as_deref(),
+                &instance.client().await.get_health().await,
+            ),
+            Link::Tunnel(tunnel) => describe_status(&tunnel.status()),
+        };
+        Ok(CallToolResult::success(vec![Content::text(text)]))
+    }
+
+    /// Summarise proximity and passes across all instances
+    #[tool(
+        annotations(read_only_hint = true, open_world_hint = false),
+        description = "Summarise every configured tether instance at once: whether each phone is near its Pi and how many emergency passes are left this month. Use this for an overview when there is more than one Pi, e.g. one at home and one at a partner's place."
+    )]
+    async fn summarize_instances(&self) -> Result<CallToolResult, McpError> {
+        let mut checks = tokio::task::JoinSet::new();
+        for (index, instance) in self.instances.iter().cloned().enumerate() {
+            checks.spawn(async move { (index, summarize_instance(&instance).await) });
+        }
+
+        let mut lines: Vec<_> = self
+            .instances
+            .iter()
+            .map(|instance| format!("- {}: check failed.", instance.name))
+            .collect();
+        while let Some(checked) = checks.join_next().await {
+            if let Ok((index, line)) = checked {
+                lines[index] = line;
+            }
         }
+
+        Ok(CallToolResult::success(vec![Content::text(
+            lines.join("\n"),
+        )]))
     }
 
     /// Check if the tracked phone is near the Raspberry Pi
-    #[tool(description = "Check if the tracked phone is currently near the Raspberry Pi based on Bluetooth signal strength. Returns whether the phone is nearby along with signal strength information.")]
-    async fn get_proximity(&self) -> Result<CallToolResult, McpError> {
-        match self.client.get_proximity().await {
+    #[tool(
+        annotations(read_only_hint = true, open_world_hint = false),
+        description = "Check if the tracked phone is currently near the Raspberry Pi based on Bluetooth signal strength. Returns whether the phone is nearby along with signal strength information."
+    )]
+    async fn get_proximity(
+        &self,
+        Parameters(args): Parameters<InstanceArgs>,
+    ) -> Result<CallToolResult, McpError> {
+        let instance = match self.connected_instance(args.instance.as_deref()).await {
+            Ok(instance) => instance,
+            Err(unavailable) => return Ok(unavailable),
+        };
+
+        match instance.client().await.get_proximity().await {
             Ok(resp) => {
-                let status = if resp.is_nearby { "nearby" } else { "not nearby" };
+                let status = if resp.is_nearby {
+                    "nearby"
+                } else {
+                    "not nearby"
+                };
                 let rssi_info = resp
-                    .rssi
-                    .map(|r| format!(" (signal: {r} dBm)"))
-                    .unwrap_or_default();
-                let device = resp
-                    .device_name
-                    .as_deref()
-                    .unwrap_or("configured device");
+                    .rssi_dbm
+                    .map_or_else(String::new, |r| format!(" (signal: {r} dBm)"));
 
                 let text = format!(
-                    "Phone ({device}) is {status}{rssi_info}. Threshold: {} dBm.",
-                    resp.threshold
+                    "Phone ({}) is {status}{rssi_info}. Threshold: {} dBm.",
+                    resp.device_name, resp.threshold_dbm
                 );
 
                 Ok(CallToolResult::success(vec![Content::text(text)]))
@@ -409,13 +719,24 @@ impl TetherMcpServer {
     }
 
     /// Get the number of remaining passes for the current month
-    #[tool(description = "Get the number of remaining emergency passes for the current month. These passes allow keeping the phone nearby on exceptional nights.")]
-    async fn get_passes_remaining(&self) -> Result<CallToolResult, McpError> {
-        match self.client.get_passes().await {
+    #[tool(
+        annotations(read_only_hint = true, open_world_hint = false),
+        description = "Get the number of remaining emergency passes for the current month. These passes allow keeping the phone nearby on exceptional nights."
+    )]
+    async fn get_passes_remaining(
+        &self,
+        Parameters(args): Parameters<InstanceArgs>,
+    ) -> Result<CallToolResult, McpError> {
+        let instance = match self.connected_instance(args.instance.as_deref()).await {
+            Ok(instance) => instance,
+            Err(unavailable) => return Ok(unavailable),
+        };
+
+        match instance.client().await.get_passes().await {
             Ok(resp) => {
                 let text = format!(
                     "Passes remaining for {}: {}/{} passes available.",
-                    resp.month, resp.remaining, resp.total
+                    resp.month, resp.remaining, resp.total_per_month
                 );
 
                 Ok(CallToolResult::success(vec![Content::text(text)]))
@@ -427,12 +748,25 @@ impl TetherMcpServer {
     }
 
     /// Get the history of pass usage
-    #[tool(description = "Get the history of emergency pass usage for a specific month or the current month. Shows when passes were used and the reasons provided.")]
+    #[tool(
+        annotations(read_only_hint = true, open_world_hint = false),
+        description = "Get the history of emergency pass usage for a specific month or the current month. Shows when passes were used and the reasons provided."
+    )]
     async fn get_pass_history(
         &self,
         Parameters(args): Parameters<GetPassHistoryArgs>,
     ) -> Result<CallToolResult, McpError> {
-        match self.client.get_pass_history(args.month.as_deref()).await {
+        let instance = match self.connected_instance(args.instance.as_deref()).await {
+            Ok(instance) => instance,
+            Err(unavailable) => return Ok(unavailable),
+        };
+
+        match instance
+            .client()
+            .await
+            .get_pass_history(args.month.as_deref())
+            .await
+        {
             Ok(resp) => {
                 if resp.entries.is_empty() {
                     let text = format!("No passes used in {}.", resp.month);
@@ -441,7 +775,7 @@ impl TetherMcpServer {
 
                 let mut text = format!("Pass history for {}:\n", resp.month);
                 for entry in &resp.entries {
-                    text.push_str(&format!("- {}: {}\n", entry.used_at, entry.reason));
+                    let _ = writeln!(text, "- {}: {}", entry.used_at_utc, entry.reason);
                 }
 
                 Ok(CallToolResult::success(vec![Content::text(text)]))
@@ -453,24 +787,36 @@ impl TetherMcpServer {
     }
 
     /// Use an emergency pass
-    #[tool(description = "Use an emergency pass for tonight. This allows keeping the phone nearby for one night. Requires a reason explaining why the pass is needed. Use sparingly as passes are limited each month.")]
-    async fn use_pass(&self, Parameters(args): Parameters<UsePassArgs>) -> Result<CallToolResult, McpError> {
+    #[tool(
+        annotations(
+            read_only_hint = false,
+            destructive_hint = true,
+            idempotent_hint = false,
+            open_world_hint = false
+        ),
+        description = "Use an emergency pass for tonight. This allows keeping the phone nearby for one night. Requires a reason explaining why the pass is needed. Use sparingly as passes are limited each month. A used pass cannot be given back, so confirm with the user before calling this."
+    )]
+    async fn use_pass(
+        &self,
+        Parameters(args): Parameters<UsePassArgs>,
+    ) -> Result<CallToolResult, McpError> {
         if args.reason.trim().is_empty() {
             return Ok(CallToolResult::error(vec![Content::text(
                 "A reason is required to use a pass.",
             )]));
         }
 
-        match self.client.use_pass(&args.reason).await {
+        let instance = match self.connected_instance(args.instance.as_deref()).await {
+            Ok(instance) => instance,
+            Err(unavailable) => return Ok(unavailable),
+        };
+
+        match instance.client().await.use_pass(&args.reason).await {
             Ok(resp) => {
-                let text = if resp.success {
-                    format!(
-                        "Pass used successfully. {}. You have {} passes remaining.",
-                        resp.message, resp.remaining
-                    )
-                } else {
-                    format!("Could not use pass: {}", resp.message)
-                };
+                let text = format!(
+                    "Pass used successfully at {}. You have {} passes remaining.",
+                    resp.used_at_utc, resp.remaining
+                );
 
                 Ok(CallToolResult::success(vec![Content::text(text)]))
             }
@@ -479,14 +825,169 @@ impl TetherMcpServer {
             ))])),
         }
     }
+
+    /// Read the server's configuration
+    #[tool(
+        annotations(read_only_hint = true, open_world_hint = false),
+        description = "Get the current configuration: the tracked phone and its signal threshold, the timezone passes reset in, and the number of passes per month."
+    )]
+    async fn get_config(
+        &self,
+        Parameters(args): Parameters<InstanceArgs>,
+    ) -> Result<CallToolResult, McpError> {
+        let instance = match self.connected_instance(args.instance.as_deref()).await {
+            Ok(instance) => instance,
+            Err(unavailable) => return Ok(unavailable),
+        };
+
+        match instance.client().await.get_config().await {
+            Ok(resp) => Ok(CallToolResult::success(vec![Content::text(
+                describe_config(&resp),
+            )])),
+            Err(e) => Ok(CallToolResult::error(vec![Content::text(format!(
+                "Failed to get config: {e}"
+            ))])),
+        }
+    }
+
+    /// Scan for nearby Bluetooth devices
+    #[tool(
+        annotations(read_only_hint = true, open_world_hint = true),
+        description = "Scan for Bluetooth devices near the Raspberry Pi for about ten seconds. Lists each device's name, address and signal strength, strongest first. Useful for checking which phone is being tracked or whether it can be seen at all."
+    )]
+    async fn scan_devices(
+        &self,
+        Parameters(args): Parameters<InstanceArgs>,
+    ) -> Result<CallToolResult, McpError> {
+        let instance = match self.connected_instance(args.instance.as_deref()).await {
+            Ok(instance) => instance,
+            Err(unavailable) => return Ok(unavailable),
+        };
+
+        match instance.client().await.scan_devices().await {
+            Ok(resp) => Ok(CallToolResult::success(vec![Content::text(describe_scan(
+                &resp,
+            ))])),
+            Err(e) => Ok(CallToolResult::error(vec![Content::text(format!(
+                "Failed to scan for devices: {e}"
+            ))])),
+        }
+    }
+
+    /// Read the server's status
+    #[tool(
+        annotations(read_only_hint = true, open_world_hint = false),
+        description = "Get the status of the tether server: its version, uptime, and the health of the Bluetooth scanner including recent failures. Use this when proximity checks fail or look wrong."
+    )]
+    async fn get_system_status(
+        &self,
+        Parameters(args): Parameters<InstanceArgs>,
+    ) -> Result<CallToolResult, McpError> {
+        let instance = match self.connected_instance(args.instance.as_deref()).await {
+            Ok(instance) => instance,
+            Err(unavailable) => return Ok(unavailable),
+        };
+
+        match instance.client().await.get_system_status().await {
+            Ok(resp) => Ok(CallToolResult::success(vec![Content::text(
+                describe_system_status(&resp),
+            )])),
+            Err(e) => Ok(CallToolResult::error(vec![Content::text(format!(
+                "Failed to get system status: {e}"
+            ))])),
+        }
+    }
+
+    /// Change the timezone passes reset in
+    #[tool(
+        annotations(
+            read_only_hint = false,
+            destructive_hint = true,
+            idempotent_hint = true,
+            open_world_hint = false
+        ),
+        description = "Change the timezone used to decide when a month starts, when passes reset, and when the curfew runs. Moving the timezone shifts the curfew and when passes reset, so confirm the change with the user before calling this; the accountability partner may also have to approve it."
+    )]
+    async fn set_timezone(
+        &self,
+        Parameters(args): Parameters<SetTimezoneArgs>,
+    ) -> Result<CallToolResult, McpError> {
+        let instance = match self.connected_instance(args.instance.as_deref()).await {
+            Ok(instance) => instance,
+            Err(unavailable) => return Ok(unavailable),
+        };
+
+        match instance
+            .client()
+            .await
+            .update_timezone(&args.timezone)
+            .await
+        {
+            Ok(Guarded::Applied(resp)) => {
+                let text = format!("Timezone set to {}.", resp.timezone);
+                Ok(CallToolResult::success(vec![Content::text(text)]))
+            }
+            Ok(Guarded::Pending(change)) => Ok(CallToolResult::success(vec![Content::text(
+                describe_pending(&change),
+            )])),
+            Err(e) => Ok(CallToolResult::error(vec![Content::text(format!(
+                "Failed to set timezone: {e}"
+            ))])),
+        }
+    }
+
+    /// Change the number of passes per month
+    #[tool(
+        annotations(
+            read_only_hint = false,
+            destructive_hint = true,
+            idempotent_hint = true,
+            open_world_hint = false
+        ),
+        description = "Change the number of emergency passes per month (0-31). The new allowance applies from next month. More passes loosens the rules, so confirm the change with the user before calling this; the accountability partner may also have to approve it."
+    )]
+    async fn set_passes_per_month(
+        &self,
+        Parameters(args): Parameters<SetPassesPerMonthArgs>,
+    ) -> Result<CallToolResult, McpError> {
+        let instance = match self.connected_instance(args.instance.as_deref()).await {
+            Ok(instance) => instance,
+            Err(unavailable) => return Ok(unavailable),
+        };
+
+        match instance
+            .client()
+            .await
+            .update_passes_per_month(args.per_month)
+            .await
+        {
+            Ok(Guarded::Applied(resp)) => {
+                Ok(CallToolResult::success(vec![Content::text(resp.message)]))
+            }
+            Ok(Guarded::Pending(change)) => Ok(CallToolResult::success(vec![Content::text(
+                describe_pending(&change),
+            )])),
+            Err(e) => Ok(CallToolResult::error(vec![Content::text(format!(
+                "Failed to set passes per month: {e}"
+            ))])),
+        }
+    }
 }
 
+// The handler macros generate `async fn`s that don't await
+#[allow(clippy::unused_async_trait_impl)]
 #[tool_handler]
+#[prompt_handler]
 impl ServerHandler for TetherMcpServer {
     fn get_info(&self) -> ServerInfo {
         ServerInfo {
             protocol_version: ProtocolVersion::V_2024_11_05,
-            capabilities: ServerCapabilities::builder().enable_tools().build(),
+            capabilities: ServerCapabilities::builder()
+                .enable_tools()
+                .enable_resources()
+                .enable_resources_subscribe()
+                .enable_prompts()
+                .build(),
             server_info: Implementation {
                 name: "tether-mcp".to_string(),
                 title: Some("Tether MCP Server".to_string()),
@@ -494,17 +995,118 @@ impl ServerHandler for TetherMcpServer {
                 icons: None,
                 website_url: Some("https://github.com/jeffrey/tether".to_string()),
             },
-            instructions: Some(
-                "Tether MCP Server - Monitor phone proximity and manage emergency passes. \
-                \n\nTools available:\
-                \n- get_proximity: Check if the phone is near the Raspberry Pi\
-                \n- get_passes_remaining: See how many emergency passes are left this month\
-                \n- get_pass_history: Review past pass usage\
-                \n- use_pass: Use an emergency pass when needed (requires a reason)"
-                    .to_string(),
-            ),
+            instructions: Some(instructions(&self.instances)),
+        }
+    }
+
+    fn list_resources(
+        &self,
+        _request: Option<PaginatedRequestParam>,
+        _context: RequestContext<RoleServer>,
+    ) -> impl Future<Output = Result<ListResourcesResult, McpError>> + Send + '_ {
+        let resources = resources::list(&self.other_names());
+        std::future::ready(Ok(ListResourcesResult::with_all_items(resources)))
+    }
+
+    fn list_resource_templates(
+        &self,
+        _request: Option<PaginatedRequestParam>,
+        _context: RequestContext<RoleServer>,
+    ) -> impl Future<Output = Result<ListResourceTemplatesResult, McpError>> + Send + '_ {
+        let templates = resources::templates(&self.other_names());
+        std::future::ready(Ok(ListResourceTemplatesResult::with_all_items(templates)))
+    }
+
+    async fn read_resource(
+        &self,
+        request: ReadResourceRequestParam,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ReadResourceResult, McpError> {
+        let (instance, resource) = self.parse_resource(&request.uri)?;
+        let instance = self
+            .ensure_connected(instance)
+            .await
+            .map_err(|unavailable| McpError::internal_error(unavailable, None))?;
+
+        match resource
+            .contents(&request.uri, &instance.client().await)
+            .await
+        {
+            Ok(contents) => Ok(ReadResourceResult {
+                contents: vec![contents],
+            }),
+            Err(e) => Err(McpError::internal_error(
+                format!("Failed to read {}: {e}", request.uri),
+                None,
+            )),
         }
     }
+
+    fn subscribe(
+        &self,
+        request: SubscribeRequestParam,
+        context: RequestContext<RoleServer>,
+    ) -> impl Future<Output = Result<(), McpError>> + Send + '_ {
+        let subscribed = self.parse_resource(&request.uri).map(|_| {
+            let default = self.default_name();
+            self.subscriptions
+                .subscribe(request.uri.clone(), &self.changes, context.peer, default);
+        });
+        std::future::ready(subscribed)
+    }
+
+    fn unsubscribe(
+        &self,
+        request: UnsubscribeRequestParam,
+        _context: RequestContext<RoleServer>,
+    ) -> impl Future<Output = Result<(), McpError>> + Send + '_ {
+        self.subscriptions.unsubscribe(&request.uri);
+        std::future::ready(Ok(()))
+    }
+}
+
+/// Builds the server instructions, listing the instances if there are several.
+fn instructions(instances: &[Instance]) -> String {
+    let mut text = "Tether MCP Server - Monitor phone proximity and manage emergency passes. \
+        \n\nTools available:\
+        \n- get_connection_status: Check the health of the connection to the Pi\
+        \n- summarize_instances: Proximity and passes for every instance at once\
+        \n- get_proximity: Check if the phone is near the Raspberry Pi\
+        \n- get_passes_remaining: See how many emergency passes are left this month\
+        \n- get_pass_history: Review past pass usage\
+        \n- use_pass: Use an emergency pass when needed (requires a reason)\
+        \n- get_config: Read the current configuration\
+        \n- scan_devices: Scan for nearby Bluetooth devices\
+        \n- get_system_status: Check the server's uptime and scanner health\
+        \n- set_timezone: Change the timezone passes reset in\
+        \n- set_passes_per_month: Change the monthly pass allowance\
+        \n\nTools that use passes or change settings are only available if the \
+        server's scope allows them. Confirm with the user before calling them.\
+        \n\nResources (subscribe to be notified of changes):\
+        \n- tether://passes/current: Passes remaining this month\
+        \n- tether://passes/history/{month}: Passes used in a month (YYYY-MM)\
+        \n- tether://proximity: Whether the phone is nearby\
+        \n- tether://config: The current configuration\
+        \n\nPrompts:\
+        \n- weekly_accountability_review: Reflect on the past week\
+        \n- monthly_pass_review: Review the passes used in a month"
+        .to_string();
+
+    if instances.len() > 1 {
+        let names: Vec<_> = instances
+            .iter()
+            .map(|instance| instance.name.as_str())
+            .collect();
+        let _ = write!(
+            text,
+            "\n\nInstances: {} (default: {}). Every tool and prompt takes an optional \
+             instance parameter to choose one. The resources above are the default \
+             instance's; tether://instances/{{instance}}/proximity and so on are another's.",
+            names.join(", "),
+            names[0]
+        );
+    }
+    text
 }
 
 /// Setup signal handlers for graceful shutdown
@@ -514,7 +1116,7 @@ fn setup_signal_handlers() -> oneshot::Receiver<()> {
     tokio::spawn(async move {
         #[cfg(unix)]
         {
-            use tokio::signal::unix::{signal, SignalKind};
+            use tokio::signal::unix::{SignalKind, signal};
             let mut sigint = signal(SignalKind::interrupt()).expect("SIGINT handler");
             let mut sigterm = signal(SignalKind::terminate()).expect("SIGTERM handler");
 
@@ -530,9 +1132,7 @@ fn setup_signal_handlers() -> oneshot::Receiver<()> {
 
         #[cfg(not(unix))]
         {
-            tokio::signal::ctrl_c()
-                .await
-                .expect("Ctrl+C handler");
+            tokio::signal::ctrl_c().await.expect("Ctrl+C handler");
             info!("Received Ctrl+C, initiating shutdown...");
         }
 
@@ -544,7 +1144,7 @@ fn setup_signal_handlers() -> oneshot::Receiver<()> {
 
 /// Initialize logging
 fn init_logging() {
-    use tracing_subscriber::{fmt, prelude::*, EnvFilter};
+    use tracing_subscriber::{EnvFilter, fmt, prelude::*};
 
     let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| {
         EnvFilter::new("info")
@@ -575,22 +1175,45 @@ async fn main() -> Result<()> {
     let config = Config::from_env().context("Failed to load configuration")?;
 
     info!(
-        "Configuration: port={}, transport={:?}",
-        config.local_port, config.transport_mode
+        "Configuration: instances={:?}, transport={:?}, scope={:?}",
+        config
+            .instances
+            .iter()
+            .map(|instance| &instance.name)
+            .collect::<Vec<_>>(),
+        config.transport_mode,
+        config.scope
     );
 
     let shutdown_rx = setup_signal_handlers();
 
-    // Spawn dumbpipe and wait for connection
-    let dumbpipe = DumbpipeManager::spawn_and_wait(&config.dumbpipe_ticket, config.local_port)
-        .await
-        .context("Failed to establish dumbpipe connection")?;
+    let mut instances = Vec::with_capacity(config.instances.len());
+    for instance in &config.instances {
+        let connection = Connection::establish(&instance.connection)
+            .await
+            .with_context(|| {
+                format!("Failed to connect to the tether server '{}'", instance.name)
+            })?;
+
+        instances.push(Instance::new(
+            instance.name.clone(),
+            instance.connection.clone(),
+            connection,
+        ));
+    }
 
-    let base_url = dumbpipe.base_url();
-    info!("Dumbpipe tunnel established at {}", base_url);
+    let server_scope = scope::server_scope(&instances).await;
+    if server_scope < config.scope {
+        info!(
+            "Limiting tools to the {:?} scope the tether servers allow",
+            server_scope
+        );
+    }
 
-    let client = TetherClient::new(base_url);
-    let mcp_server = TetherMcpServer::new(client);
+    let shutdown = CancellationToken::new();
+    let changes = resources::watch(&instances, &shutdown);
+    let mcp_server =
+        TetherMcpServer::new(instances.clone(), changes, config.scope.min(server_scope));
 
     match config.transport_mode {
         TransportMode::Stdio => {
@@ -618,40 +1241,40 @@ async fn main() -> Result<()> {
             }
         }
         TransportMode::StreamableHttp => {
-            // For HTTP transport, we run the same stdio server
-            // The expectation is that HTTP transport will be handled by a reverse proxy
-            // or by running this behind an HTTP-to-stdio adapter like mcp-proxy
-            warn!(
-                "HTTP transport requested on port {} - falling back to stdio. \
-                Use an HTTP-to-stdio proxy for HTTP support.",
-                config.http_port
-            );
-
-            let transport = rmcp::transport::stdio();
+            let addr = SocketAddr::new(config.http_bind, config.http_port);
+            if config.auth_token.is_none() && !addr.ip().is_loopback() {
+                warn!(
+                    "Serving MCP on {} without {}; anyone who can reach it can use passes",
+                    addr,
+                    env_vars::MCP_AUTH_TOKEN
+                );
+            }
 
-            tokio::select! {
-                result = mcp_server.serve(transport) => {
-                    match result {
-                        Ok(ct) => {
-                            info!("MCP server running, waiting for completion...");
-                            if let Err(e) = ct.waiting().await {
-                                error!("MCP server error: {}", e);
-                            }
-                        }
-                        Err(e) => {
-                            error!("Failed to start MCP server: {}", e);
-                        }
-                    }
-                }
-                _ = shutdown_rx => {
+            tokio::spawn({
+                let shutdown = shutdown.clone();
+                async move {
+                    let _ = shutdown_rx.await;
                     info!("Shutdown signal received");
+                    shutdown.cancel();
                 }
+            });
+
+            let router = http::router(
+                move || mcp_server.session(),
+                config.auth_token.as_deref(),
+                shutdown.clone(),
+            );
+            if let Err(e) = http::serve(addr, router, shutdown.clone()).await {
+                error!("MCP HTTP server error on {}: {}", addr, e);
             }
         }
     }
 
     info!("Cleaning up...");
-    dumbpipe.shutdown().await.context("Failed to shutdown dumbpipe")?;
+    shutdown.cancel();
+    for instance in &instances {
+        instance.shutdown().await;
+    }
 
     info!("Tether MCP Server shutdown complete");
     Ok(())
@@ -661,29 +1284,305 @@ async fn main() -> Result<()> {
 mod tests {
     use super::*;
 
+    use std::net::Ipv4Addr;
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(Duration::from_secs(42)), "42s");
+        assert_eq!(format_duration(Duration::from_secs(125)), "2m 5s");
+        assert_eq!(format_duration(Duration::from_secs(7503)), "2h 5m 3s");
+    }
+
+    fn instance(name: &str) -> Instance {
+        let base_url: Url = "http://localhost:3000".parse().unwrap();
+        let config = ConnectionConfig {
+            mode: ConnectionMode::Direct,
+            base_url: Some(base_url.clone()),
+            ticket: None,
+            lan_name: None,
+            token: None,
+            local_port: 0,
+            tunnel: TunnelOptions::default(),
+        };
+        let connection = Connection::Direct {
+            base_url,
+            instance: None,
+        };
+        Instance::new(name.to_string(), config, connection)
+    }
+
+    fn server(scope: Scope) -> TetherMcpServer {
+        TetherMcpServer::new(
+            vec![instance("home"), instance("partner")],
+            broadcast::channel(1).0,
+            scope,
+        )
+    }
+
+    #[tokio::test]
+    async fn test_lists_prompts() {
+        let server = server(Scope::default());
+
+        let mut names: Vec<_> = server
+            .prompt_router
+            .list_all()
+            .into_iter()
+            .map(|p| p.name)
+            .collect();
+        names.sort();
+        assert_eq!(
+            names,
+            ["monthly_pass_review", "weekly_accountability_review"]
+        );
+    }
+
+    fn tool_names(scope: Scope) -> Vec<String> {
+        let mut names: Vec<_> = server(scope)
+            .tool_router
+            .list_all()
+            .into_iter()
+            .map(|t| t.name.to_string())
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn test_scope_limits_tools() {
+        let read = tool_names(Scope::Read);
+        assert!(read.contains(&"get_config".to_string()));
+        assert!(read.contains(&"summarize_instances".to_string()));
+        assert!(!read.contains(&"use_pass".to_string()));
+
+        let passes = tool_names(Scope::Passes);
+        assert!(passes.contains(&"use_pass".to_string()));
+        assert!(!passes.contains(&"set_passes_per_month".to_string()));
+
+        let settings = tool_names(Scope::Settings);
+        assert!(settings.contains(&"set_timezone".to_string()));
+        assert!(settings.contains(&"set_passes_per_month".to_string()));
+    }
+
+    #[test]
+    fn test_changes_are_marked_destructive() {
+        let tools = TetherMcpServer::tool_router().list_all();
+        // Tools that don't say whether they change anything are never offered
+        assert_eq!(
+            server(Scope::Settings).tool_router.list_all().len(),
+            tools.len()
+        );
+
+        for tool in tools {
+            let annotations = tool.annotations.expect("Every tool is annotated");
+            if annotations.read_only_hint == Some(false) {
+                assert_eq!(annotations.destructive_hint, Some(true), "{}", tool.name);
+            }
+        }
+    }
+
+    #[test]
+    fn test_every_tool_takes_an_instance() {
+        for tool in server(Scope::Settings).tool_router.list_all() {
+            if tool.name == "summarize_instances" {
+                continue;
+            }
+            assert!(
+                tool.input_schema["properties"].get("instance").is_some(),
+                "{}",
+                tool.name
+            );
+        }
+    }
+
+    #[test]
+    fn test_instructions_list_instances() {
+        let text = instructions(&[instance("home"), instance("partner")]);
+        assert!(text.contains("Instances: home, partner (default: home)"));
+
+        let text = instructions(&[instance(instances::DEFAULT_INSTANCE)]);
+        assert!(!text.contains("Instances:"));
+    }
+
+    #[test]
+    fn test_describe_summary() {
+        let proximity = Err(tether_client::Error::Api(tether_client::ApiError {
+            status: 503,
+            error: "bluetooth_unavailable".to_string(),
+            message: "Bluetooth is unavailable".to_string(),
+            details: None,
+        }));
+        let passes = Ok(PassesResponse {
+            remaining: 2,
+            total_per_month: 3,
+            used_this_month: 1,
+            month: "2025-01".to_string(),
+            resets_at_utc: "2025-02-01T08:00:00Z".to_string(),
+            timezone: "America/Los_Angeles".to_string(),
+        });
+
+        assert_eq!(
+            describe_summary("partner", &proximity, &passes),
+            "- partner: proximity unknown (Bluetooth is unavailable (503 bluetooth_unavailable)), 2/3 passes left for 2025-01."
+        );
+    }
+
+    #[test]
+    fn test_config_instances_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("instances.toml");
+        std::fs::write(
+            &path,
+            "[[instance]]\nname = \"home\"\nurl = \"http://localhost:3000\"\n\n[[instance]]\nname = \"partner\"\nticket = \"endpoint12345\"\n",
+        )
+        .unwrap();
+        let path = path.to_str().unwrap();
+
+        let loaded = config(&[(env_vars::INSTANCES, path), (env_vars::URL, "ignored")]).unwrap();
+        let names: Vec<_> = loaded
+            .instances
+            .iter()
+            .map(|instance| instance.name.as_str())
+            .collect();
+        assert_eq!(names, ["home", "partner"]);
+
+        let missing = dir.path().join("missing.toml");
+        let result = config(&[(env_vars::INSTANCES, missing.to_str().unwrap())]);
+        assert!(matches!(
+            result,
+            Err(TetherMcpError::InvalidInstances { .. })
+        ));
+    }
+
+    #[test]
+    fn test_describe_pending() {
+        let change = PendingChangeResponse {
+            id: "5b6f".to_string(),
+            setting: tether_client::types::GuardedSetting::PassesPerMonth,
+            requested_by: MCP_ACTOR.to_string(),
+            requested_at_utc: "2025-01-15T23:05:00Z".to_string(),
+            effective_at_utc: None,
+            before: serde_json::json!({ "passes_per_month": 3 }),
+            after: serde_json::json!({ "passes_per_month": 5 }),
+        };
+
+        let text = describe_pending(&change);
+        assert!(text.contains("approval"));
+        assert!(text.contains("only takes effect once approved"));
+    }
+
+    fn config(vars: &[(&str, &str)]) -> Result<Config, TetherMcpError> {
+        let vars: std::collections::HashMap<String, String> = vars
+            .iter()
+            .map(|(name, value)| ((*name).to_string(), (*value).to_string()))
+            .collect();
+        Config::from_lookup(|name| vars.get(name).cloned())
+    }
+
     #[test]
     fn test_config_ticket_not_set() {
-        std::env::remove_var(env_vars::DUMBPIPE_TICKET);
-        let result = Config::from_env();
+        let result = config(&[(env_vars::CONNECTION, "ticket")]);
         assert!(matches!(result, Err(TetherMcpError::TicketNotSet)));
     }
 
+    #[test]
+    fn test_config_defaults_to_auto() {
+        let config = config(&[]).unwrap();
+        assert_eq!(config.instances[0].connection.mode, ConnectionMode::Auto);
+        assert!(config.instances[0].connection.base_url.is_none());
+        assert!(config.instances[0].connection.ticket.is_none());
+    }
+
+    #[test]
+    fn test_config_direct() {
+        let result = config(&[(env_vars::CONNECTION, "direct")]);
+        assert!(matches!(result, Err(TetherMcpError::UrlNotSet)));
+
+        let result = config(&[(env_vars::URL, "localhost:3000")]);
+        assert!(matches!(result, Err(TetherMcpError::InvalidUrl(_))));
+
+        let config = config(&[
+            (env_vars::CONNECTION, "direct"),
+            (env_vars::URL, "http://localhost:3000"),
+        ])
+        .unwrap();
+        assert_eq!(config.instances[0].connection.mode, ConnectionMode::Direct);
+        assert_eq!(
+            config.instances[0]
+                .connection
+                .base_url
+                .as_ref()
+                .unwrap()
+                .as_str(),
+            "http://localhost:3000/"
+        );
+    }
+
+    #[test]
+    fn test_config_invalid_mode() {
+        let result = config(&[(env_vars::CONNECTION, "carrier-pigeon")]);
+        assert!(matches!(
+            result,
+            Err(TetherMcpError::InvalidConnectionMode(_))
+        ));
+    }
+
     #[test]
     fn test_config_empty_ticket() {
-        std::env::set_var(env_vars::DUMBPIPE_TICKET, "");
-        let result = Config::from_env();
+        let result = config(&[(env_vars::DUMBPIPE_TICKET, "")]);
         assert!(matches!(result, Err(TetherMcpError::InvalidTicket(_))));
-        std::env::remove_var(env_vars::DUMBPIPE_TICKET);
     }
 
     #[test]
     fn test_config_valid() {
-        std::env::set_var(env_vars::DUMBPIPE_TICKET, "endpoint12345");
-        std::env::set_var(env_vars::LOCAL_PORT, "9999");
-        let config = Config::from_env().unwrap();
-        assert_eq!(config.local_port, 9999);
-        assert_eq!(config.dumbpipe_ticket, "endpoint12345");
-        std::env::remove_var(env_vars::DUMBPIPE_TICKET);
-        std::env::remove_var(env_vars::LOCAL_PORT);
+        let config = config(&[
+            (env_vars::DUMBPIPE_TICKET, "endpoint12345"),
+            (env_vars::LOCAL_PORT, "9999"),
+        ])
+        .unwrap();
+        assert_eq!(config.instances[0].connection.local_port, 9999);
+        assert_eq!(
+            config.instances[0].connection.ticket.as_deref(),
+            Some("endpoint12345")
+        );
+        assert_eq!(config.transport_mode, TransportMode::Stdio);
+        assert_eq!(config.http_bind, defaults::HTTP_BIND);
+        assert_eq!(config.auth_token, None);
+    }
+
+    #[test]
+    fn test_config_http() {
+        let config = config(&[
+            (env_vars::DUMBPIPE_TICKET, "endpoint12345"),
+            (env_vars::MCP_TRANSPORT, "streamable-http"),
+            (env_vars::MCP_HTTP_BIND, "0.0.0.0"),
+            (env_vars::MCP_HTTP_PORT, "9090"),
+            (env_vars::MCP_AUTH_TOKEN, "s3cret"),
+        ])
+        .unwrap();
+        assert_eq!(config.transport_mode, TransportMode::StreamableHttp);
+        assert_eq!(config.http_bind, IpAddr::V4(Ipv4Addr::UNSPECIFIED));
+        assert_eq!(config.http_port, 9090);
+        assert_eq!(config.auth_token.as_deref(), Some("s3cret"));
+    }
+
+    #[test]
+    fn test_config_scope() {
+        let config_default = config(&[]).unwrap();
+        assert_eq!(config_default.scope, Scope::Passes);
+
+        let config_settings = config(&[(env_vars::SCOPE, "settings")]).unwrap();
+        assert_eq!(config_settings.scope, Scope::Settings);
+
+        let result = config(&[(env_vars::SCOPE, "root")]);
+        assert!(matches!(result, Err(TetherMcpError::InvalidScope(_))));
+    }
+
+    #[test]
+    fn test_config_invalid_bind() {
+        let result = config(&[
+            (env_vars::DUMBPIPE_TICKET, "endpoint12345"),
+            (env_vars::MCP_HTTP_BIND, "home-server"),
+        ]);
+        assert!(matches!(result, Err(TetherMcpError::InvalidBindAddress(_))));
     }
 }
@@ -0,0 +1,258 @@
+//! MCP prompts for reflecting on tether data.
+//!
+//! Each prompt fetches the relevant history and state from the server and
+//! assembles it into a message asking the assistant to help the user reflect
+//! on how their nights went, so the user doesn't have to gather it first.
+//! With several instances configured, each prompt takes the instance to use.
+
+use std::fmt::Write;
+
+use chrono::{Duration as ChronoDuration, SecondsFormat, Utc};
+use rmcp::{
+    ErrorData as McpError,
+    handler::server::wrapper::Parameters,
+    model::{GetPromptResult, PromptMessage, PromptMessageRole},
+    prompt, prompt_router, schemars,
+};
+use serde::{Deserialize, Serialize};
+use tether_client::TetherClient;
+use tether_client::types::{PassHistoryEntry, PassesResponse, ProximityResponse};
+
+use crate::TetherMcpServer;
+use crate::instances;
+
+/// Number of days covered by the weekly review.
+const WEEK_DAYS: i64 = 7;
+
+#[derive(Debug, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct WeeklyReviewArgs {
+    /// Name of the tether instance to review. Defaults to the default instance.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub instance: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct MonthlyReviewArgs {
+    /// Month to review in YYYY-MM format (e.g., '2025-01'). Defaults to the current month.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub month: Option<String>,
+
+    /// Name of the tether instance to review. Defaults to the default instance.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub instance: Option<String>,
+}
+
+#[prompt_router(vis = "pub(crate)")]
+impl TetherMcpServer {
+    /// Reflect on the past week of phone-free nights
+    #[prompt(
+        name = "weekly_accountability_review",
+        description = "Review the past week: the emergency passes used and why, the passes left this month, and whether the phone is near the bed right now. Asks the assistant to help reflect on the week and plan the next one."
+    )]
+    async fn weekly_accountability_review(
+        &self,
+        Parameters(args): Parameters<WeeklyReviewArgs>,
+    ) -> Result<GetPromptResult, McpError> {
+        let client = self.prompt_client(args.instance.as_deref()).await?;
+
+        let to = Utc::now();
+        let from = to - ChronoDuration::days(WEEK_DAYS);
+        let history = client
+            .get_pass_history_range(
+                &from.to_rfc3339_opts(SecondsFormat::Secs, true),
+                &to.to_rfc3339_opts(SecondsFormat::Secs, true),
+            )
+            .await
+            .map_err(|e| request_error("Failed to get pass history", &e))?;
+        let passes = client
+            .get_passes()
+            .await
+            .map_err(|e| request_error("Failed to get passes", &e))?;
+        let proximity = client.get_proximity().await.map_err(|e| e.to_string());
+
+        let text = weekly_review(
+            &from.format("%Y-%m-%d").to_string(),
+            &to.format("%Y-%m-%d").to_string(),
+            &history.entries,
+            &passes,
+            proximity.as_ref(),
+        );
+
+        Ok(GetPromptResult {
+            description: Some("Weekly accountability review".to_string()),
+            messages: vec![PromptMessage::new_text(PromptMessageRole::User, text)],
+        })
+    }
+
+    /// Review the emergency passes used in a month
+    #[prompt(
+        name = "monthly_pass_review",
+        description = "Review the emergency passes used in a month and the reasons given for each. Asks the assistant to look for patterns and whether the passes were really needed."
+    )]
+    async fn monthly_pass_review(
+        &self,
+        Parameters(args): Parameters<MonthlyReviewArgs>,
+    ) -> Result<GetPromptResult, McpError> {
+        let client = self.prompt_client(args.instance.as_deref()).await?;
+
+        let history = client
+            .get_pass_history(args.month.as_deref())
+            .await
+            .map_err(|e| request_error("Failed to get pass history", &e))?;
+
+        let text = monthly_review(&history.month, &history.entries, history.total_per_month);
+
+        Ok(GetPromptResult {
+            description: Some(format!("Pass review for {}", history.month)),
+            messages: vec![PromptMessage::new_text(PromptMessageRole::User, text)],
+        })
+    }
+}
+
+impl TetherMcpServer {
+    /// Returns the client of the instance named `name`, or the default one,
+    /// once it is connected.
+    async fn prompt_client(&self, name: Option<&str>) -> Result<TetherClient, McpError> {
+        instances::find(&self.instances, name).map_err(|e| McpError::invalid_params(e, None))?;
+        let instance = self
+            .ensure_connected(name)
+            .await
+            .map_err(|e| McpError::internal_error(e, None))?;
+        Ok(instance.client().await)
+    }
+}
+
+/// Converts a failed request to an MCP error, blaming the arguments if the
+/// server rejected them.
+fn request_error(context: &str, error: &tether_client::Error) -> McpError {
+    let message = format!("{context}: {error}");
+    match error {
+        tether_client::Error::Api(api) if api.status == 400 => {
+            McpError::invalid_params(message, None)
+        }
+        _ => McpError::internal_error(message, None),
+    }
+}
+
+/// Lists pass usage entries, one per line.
+fn list_passes(entries: &[PassHistoryEntry]) -> String {
+    let mut text = String::new();
+    for entry in entries {
+        let _ = writeln!(text, "- {}: {}", entry.used_at_utc, entry.reason);
+    }
+    text
+}
+
+/// Builds the text of the weekly review prompt.
+fn weekly_review(
+    from: &str,
+    to: &str,
+    entries: &[PassHistoryEntry],
+    passes: &PassesResponse,
+    proximity: Result<&ProximityResponse, &String>,
+) -> String {
+    let mut text = format!(
+        "I use tether to keep my phone away from my bed at night. A Raspberry Pi by the bed \
+         checks over Bluetooth that the phone is out of reach, and I get {} emergency passes \
+         a month for nights when I need it nearby.\n\n\
+         Here is my week from {from} to {to}.\n\n",
+        passes.total_per_month
+    );
+
+    if entries.is_empty() {
+        text.push_str("I used no passes this week.\n");
+    } else {
+        let _ = writeln!(text, "I used {} passes this week:", entries.len());
+        text.push_str(&list_passes(entries));
+    }
+    let _ = writeln!(
+        text,
+        "\nI have {} of {} passes left for {}.",
+        passes.remaining, passes.total_per_month, passes.month
+    );
+
+    match proximity {
+        Ok(proximity) if proximity.is_nearby => {
+            text.push_str("Right now my phone is near the bed.\n");
+        }
+        Ok(_) => text.push_str("Right now my phone is away from the bed.\n"),
+        Err(e) => {
+            let _ = writeln!(text, "I couldn't check where my phone is right now: {e}");
+        }
+    }
+
+    text.push_str(
+        "\nPlease help me reflect on this week. How did it go overall? Were the passes I used \
+         really necessary, and is there a pattern in the reasons? Given the passes I have left, \
+         what is one thing I could do differently next week? Keep it supportive and brief.",
+    );
+    text
+}
+
+/// Builds the text of the monthly pass review prompt.
+fn monthly_review(month: &str, entries: &[PassHistoryEntry], per_month: u32) -> String {
+    let mut text = format!(
+        "I use tether to keep my phone away from my bed at night, with {per_month} emergency \
+         passes a month for nights when I need it nearby.\n\n"
+    );
+
+    if entries.is_empty() {
+        let _ = writeln!(text, "I used no passes in {month}.");
+    } else {
+        let _ = writeln!(text, "In {month} I used {} passes:", entries.len());
+        text.push_str(&list_passes(entries));
+    }
+
+    text.push_str(
+        "\nPlease review these with me. Do the reasons show a pattern, such as a day of the week \
+         or a kind of situation? Which passes could I have avoided, and how? Would a different \
+         number of passes per month suit me better?",
+    );
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn passes() -> PassesResponse {
+        PassesResponse {
+            remaining: 2,
+            total_per_month: 3,
+            used_this_month: 1,
+            month: "2025-01".to_string(),
+            resets_at_utc: "2025-02-01T08:00:00Z".to_string(),
+            timezone: "America/Los_Angeles".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_weekly_review() {
+        let entries = vec![PassHistoryEntry {
+            used_at_utc: "2025-01-15T03:30:00Z".to_string(),
+            reason: "On-call".to_string(),
+        }];
+        let unavailable = "Bluetooth unavailable".to_string();
+
+        let text = weekly_review(
+            "2025-01-10",
+            "2025-01-17",
+            &entries,
+            &passes(),
+            Err(&unavailable),
+        );
+
+        assert!(text.contains("from 2025-01-10 to 2025-01-17"));
+        assert!(text.contains("I used 1 passes this week:\n- 2025-01-15T03:30:00Z: On-call\n"));
+        assert!(text.contains("2 of 3 passes left for 2025-01"));
+        assert!(text.contains("couldn't check where my phone is right now: Bluetooth unavailable"));
+    }
+
+    #[test]
+    fn test_monthly_review_without_passes() {
+        let text = monthly_review("2025-01", &[], 3);
+
+        assert!(text.contains("with 3 emergency passes a month"));
+        assert!(text.contains("I used no passes in 2025-01."));
+    }
+}
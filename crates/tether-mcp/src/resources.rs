@@ -0,0 +1,583 @@
+//! MCP resources for tether state.
+//!
+//! The passes, pass history, proximity and configuration are exposed as
+//! JSON resources that agents can read, and subscribe to so they are told
+//! when the state behind them changes. URIs such as `tether://proximity`
+//! are the default instance's; with several instances configured,
+//! `tether://instances/{instance}/proximity` and so on are another's.
+//!
+//! Changes come from the servers' event streams: [`watch`] follows them and
+//! broadcasts an [`InstanceChange`] for each event, and every session
+//! forwards the changes that affect its subscriptions as
+//! `notifications/resources/updated`.
+
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::Duration;
+
+use rmcp::model::{
+    AnnotateAble, RawResource, RawResourceTemplate, Resource, ResourceContents, ResourceTemplate,
+    ResourceUpdatedNotificationParam,
+};
+use rmcp::{Peer, RoleServer};
+use tether_client::TetherClient;
+use tether_client::events::RESYNC_EVENT;
+use tokio::sync::broadcast;
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, info, warn};
+
+use crate::instances::Instance;
+
+/// URI of this month's pass status.
+pub const CURRENT_PASSES: &str = "tether://passes/current";
+
+/// URI template of the pass history of a month.
+pub const PASS_HISTORY_TEMPLATE: &str = "tether://passes/history/{month}";
+
+/// URI of the phone's proximity.
+pub const PROXIMITY: &str = "tether://proximity";
+
+/// URI of the configuration.
+pub const CONFIG: &str = "tether://config";
+
+/// Prefix of pass history URIs, followed by the month.
+const PASS_HISTORY_PREFIX: &str = "tether://passes/history/";
+
+/// Scheme of resource URIs.
+const SCHEME: &str = "tether://";
+
+/// Prefix of another instance's resource URIs, followed by the instance
+/// name and the default instance's URI without its scheme.
+const INSTANCE_PREFIX: &str = "tether://instances/";
+
+/// MIME type of resource contents.
+const JSON_MIME_TYPE: &str = "application/json";
+
+/// How long to wait before reopening the event stream after it fails.
+const EVENT_STREAM_RETRY: Duration = Duration::from_secs(5);
+
+/// Number of changes buffered for sessions that are slow to forward them.
+const CHANGE_BUFFER: usize = 64;
+
+/// A resource identified by its URI.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TetherResource {
+    /// This month's pass status.
+    CurrentPasses,
+    /// The passes used in a month (YYYY-MM).
+    PassHistory(String),
+    /// Whether the phone is near the Pi.
+    Proximity,
+    /// The configuration, without passwords.
+    Config,
+}
+
+impl TetherResource {
+    /// Parses a default instance resource URI, or returns `None` if it
+    /// isn't one of ours.
+    pub fn parse(uri: &str) -> Option<Self> {
+        match uri {
+            CURRENT_PASSES => Some(Self::CurrentPasses),
+            PROXIMITY => Some(Self::Proximity),
+            CONFIG => Some(Self::Config),
+            _ => {
+                let month = uri.strip_prefix(PASS_HISTORY_PREFIX)?;
+                is_month(month).then(|| Self::PassHistory(month.to_string()))
+            }
+        }
+    }
+
+    /// Fetches the resource from the server as pretty-printed JSON.
+    pub async fn read(&self, client: &TetherClient) -> tether_client::Result<String> {
+        let json = match self {
+            Self::CurrentPasses => serde_json::to_string_pretty(&client.get_passes().await?),
+            Self::PassHistory(month) => {
+                serde_json::to_string_pretty(&client.get_pass_history(Some(month)).await?)
+            }
+            Self::Proximity => serde_json::to_string_pretty(&client.get_proximity().await?),
+            Self::Config => serde_json::to_string_pretty(&client.get_config().await?),
+        };
+        Ok(json?)
+    }
+
+    /// Fetches the resource as the contents of a `resources/read` result.
+    pub async fn contents(
+        &self,
+        uri: &str,
+        client: &TetherClient,
+    ) -> tether_client::Result<ResourceContents> {
+        Ok(ResourceContents::TextResourceContents {
+            uri: uri.to_string(),
+            mime_type: Some(JSON_MIME_TYPE.to_string()),
+            text: self.read(client).await?,
+            meta: None,
+        })
+    }
+}
+
+/// Parses a resource URI into the instance it names, `None` for the default
+/// one, and the resource. Returns `None` if it isn't one of ours.
+pub fn parse(uri: &str) -> Option<(Option<&str>, TetherResource)> {
+    let Some(rest) = uri.strip_prefix(INSTANCE_PREFIX) else {
+        return TetherResource::parse(uri).map(|resource| (None, resource));
+    };
+    let (instance, path) = rest.split_once('/')?;
+    let resource = TetherResource::parse(&format!("{SCHEME}{path}"))?;
+    (!instance.is_empty()).then_some((Some(instance), resource))
+}
+
+/// Returns the URI of `instance`'s copy of the default instance resource `uri`.
+fn instance_uri(instance: &str, uri: &str) -> String {
+    let path = uri.strip_prefix(SCHEME).unwrap_or(uri);
+    format!("{INSTANCE_PREFIX}{instance}/{path}")
+}
+
+/// Whether `month` looks like YYYY-MM. The server does the full validation.
+fn is_month(month: &str) -> bool {
+    let bytes = month.as_bytes();
+    bytes.len() == 7
+        && bytes[4] == b'-'
+        && bytes
+            .iter()
+            .enumerate()
+            .all(|(i, b)| i == 4 || b.is_ascii_digit())
+}
+
+/// Lists the fixed resources of the default instance, then those of the
+/// `others`.
+pub fn list(others: &[&str]) -> Vec<Resource> {
+    let fixed = [
+        (
+            CURRENT_PASSES,
+            "current-passes",
+            "Passes this month",
+            "Emergency passes remaining and used this month, and when they reset.",
+        ),
+        (
+            PROXIMITY,
+            "proximity",
+            "Phone proximity",
+            "Whether the tracked phone is near the Raspberry Pi, with its signal strength. \
+             Reading it runs a Bluetooth scan, which takes a few seconds.",
+        ),
+        (
+            CONFIG,
+            "config",
+            "Configuration",
+            "The tracked device, timezone, passes per month and wireless networks. \
+             Passwords are never included.",
+        ),
+    ];
+    let resource = |uri: String, name: String, title: String, description: &str| {
+        RawResource {
+            title: Some(title),
+            description: Some(description.to_string()),
+            mime_type: Some(JSON_MIME_TYPE.to_string()),
+            ..RawResource::new(uri, name)
+        }
+        .no_annotation()
+    };
+
+    let mut resources: Vec<Resource> = fixed
+        .iter()
+        .map(|&(uri, name, title, description)| {
+            resource(
+                uri.to_string(),
+                name.to_string(),
+                title.to_string(),
+                description,
+            )
+        })
+        .collect();
+    for instance in others {
+        resources.extend(fixed.iter().map(|&(uri, name, title, description)| {
+            resource(
+                instance_uri(instance, uri),
+                format!("{instance}-{name}"),
+                format!("{title} ({instance})"),
+                description,
+            )
+        }));
+    }
+    resources
+}
+
+/// Lists the resource templates, with one for other instances' pass history
+/// if there are `others`.
+pub fn templates(others: &[&str]) -> Vec<ResourceTemplate> {
+    let template = |uri_template: String, name: &str, description: &str| {
+        RawResourceTemplate {
+            uri_template,
+            name: name.to_string(),
+            title: Some("Pass history".to_string()),
+            description: Some(description.to_string()),
+            mime_type: Some(JSON_MIME_TYPE.to_string()),
+        }
+        .no_annotation()
+    };
+
+    let mut templates = vec![template(
+        PASS_HISTORY_TEMPLATE.to_string(),
+        "pass-history",
+        "Emergency passes used in a month, with the reason given for each. \
+         `month` is in YYYY-MM format, e.g. 2025-01.",
+    )];
+    if !others.is_empty() {
+        templates.push(template(
+            instance_uri("{instance}", PASS_HISTORY_TEMPLATE),
+            "instance-pass-history",
+            &format!(
+                "Emergency passes used in a month on another instance ({}). \
+                 `month` is in YYYY-MM format, e.g. 2025-01.",
+                others.join(", ")
+            ),
+        ));
+    }
+    templates
+}
+
+// ============================================================================
+// Change notifications
+// ============================================================================
+
+/// Which resources a server event changed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Change {
+    /// The phone's proximity, or the scanner behind it.
+    Proximity,
+    /// Pass counts or history.
+    Passes,
+    /// The configuration.
+    Config,
+    /// Anything; events were missed and all state should be refetched.
+    All,
+}
+
+impl Change {
+    /// Returns the changes caused by the event with SSE name `event`.
+    fn from_event(event: &str) -> &'static [Self] {
+        match event {
+            "proximity_changed" | "scanner_health_changed" => &[Self::Proximity],
+            "pass_used" | "month_reset" => &[Self::Passes],
+            // The timezone and passes per month also change the pass status
+            "config_changed" => &[Self::Config, Self::Passes],
+            RESYNC_EVENT => &[Self::All],
+            _ => &[],
+        }
+    }
+
+    /// Whether this change may have changed `resource`.
+    const fn affects(self, resource: &TetherResource) -> bool {
+        matches!(
+            (self, resource),
+            (Self::All, _)
+                | (Self::Proximity, TetherResource::Proximity)
+                | (
+                    Self::Passes,
+                    TetherResource::CurrentPasses | TetherResource::PassHistory(_)
+                )
+                | (Self::Config, TetherResource::Config)
+        )
+    }
+}
+
+/// A change to the resources of an instance.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InstanceChange {
+    /// Name of the instance, or `None` for every instance.
+    pub instance: Option<String>,
+    /// What changed.
+    pub change: Change,
+}
+
+/// Follows the event stream of every instance until `shutdown` is
+/// cancelled, broadcasting the changes they report.
+///
+/// Each stream is reopened after failures, resuming after the last event
+/// received so none are missed.
+pub fn watch(
+    instances: &[Instance],
+    shutdown: &CancellationToken,
+) -> broadcast::Sender<InstanceChange> {
+    let (changes, _) = broadcast::channel(CHANGE_BUFFER);
+
+    for instance in instances {
+        let instance = instance.clone();
+        let changes = changes.clone();
+        let shutdown = shutdown.clone();
+        tokio::spawn(async move {
+            tokio::select! {
+                () = follow_events(&instance, &changes) => {}
+                () = shutdown.cancelled() => {}
+            }
+        });
+    }
+
+    changes
+}
+
+async fn follow_events(instance: &Instance, changes: &broadcast::Sender<InstanceChange>) {
+    let mut last_event_id = None;
+
+    loop {
+        // Reopen over the tunnel if the direct route stopped answering
+        let _ = instance.ensure_connected(EVENT_STREAM_RETRY).await;
+        let mut events = match instance.client().await.events(last_event_id).await {
+            Ok(events) => events,
+            Err(e) => {
+                debug!(
+                    "Failed to open event stream, retrying in {:?}: {}",
+                    EVENT_STREAM_RETRY, e
+                );
+                tokio::time::sleep(EVENT_STREAM_RETRY).await;
+                continue;
+            }
+        };
+        info!(instance = %instance.name, "Following server events for resource subscriptions");
+
+        loop {
+            match events.next().await {
+                Ok(Some(event)) => {
+                    last_event_id = event.id.or(last_event_id);
+                    for &change in Change::from_event(&event.event) {
+                        let instance = Some(instance.name.clone());
+                        // No receivers just means nobody is subscribed
+                        let _ = changes.send(InstanceChange { instance, change });
+                    }
+                }
+                Ok(None) => break,
+                Err(e) => {
+                    warn!("Event stream failed, reopening: {}", e);
+                    break;
+                }
+            }
+        }
+
+        tokio::time::sleep(EVENT_STREAM_RETRY).await;
+    }
+}
+
+/// Resources a session has subscribed to.
+#[derive(Debug, Default)]
+pub struct Subscriptions {
+    uris: Mutex<HashSet<String>>,
+    forwarding: AtomicBool,
+}
+
+impl Subscriptions {
+    /// Subscribes to `uri`, and starts forwarding changes to `peer` if this
+    /// is the session's first subscription.
+    ///
+    /// `default` is the name of the default instance, whose URIs don't name it.
+    pub fn subscribe(
+        self: &Arc<Self>,
+        uri: String,
+        changes: &broadcast::Sender<InstanceChange>,
+        peer: Peer<RoleServer>,
+        default: &str,
+    ) {
+        self.uris
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(uri);
+
+        if !self.forwarding.swap(true, Ordering::SeqCst) {
+            let forward =
+                forward_changes(self.clone(), changes.subscribe(), peer, default.to_string());
+            tokio::spawn(forward);
+        }
+    }
+
+    /// Unsubscribes from `uri`.
+    pub fn unsubscribe(&self, uri: &str) {
+        self.uris
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(uri);
+    }
+
+    /// Returns the subscribed URIs that `change` affects.
+    fn affected_by(&self, change: &InstanceChange, default: &str) -> Vec<String> {
+        self.uris
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .iter()
+            .filter(|uri| {
+                parse(uri).is_some_and(|(instance, resource)| {
+                    let instance = instance.unwrap_or(default);
+                    change
+                        .instance
+                        .as_ref()
+                        .is_none_or(|changed| changed.eq_ignore_ascii_case(instance))
+                        && change.change.affects(&resource)
+                })
+            })
+            .cloned()
+            .collect()
+    }
+}
+
+/// Notifies `peer` of changes to its subscribed resources until the session
+/// closes.
+async fn forward_changes(
+    subscriptions: Arc<Subscriptions>,
+    mut changes: broadcast::Receiver<InstanceChange>,
+    peer: Peer<RoleServer>,
+    default: String,
+) {
+    loop {
+        let change = match changes.recv().await {
+            Ok(change) => change,
+            Err(broadcast::error::RecvError::Lagged(_)) => InstanceChange {
+                instance: None,
+                change: Change::All,
+            },
+            Err(broadcast::error::RecvError::Closed) => return,
+        };
+
+        for uri in subscriptions.affected_by(&change, &default) {
+            if peer
+                .notify_resource_updated(ResourceUpdatedNotificationParam { uri })
+                .await
+                .is_err()
+            {
+                debug!("Session closed, no longer forwarding resource changes");
+                return;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            TetherResource::parse(CURRENT_PASSES),
+            Some(TetherResource::CurrentPasses)
+        );
+        assert_eq!(
+            TetherResource::parse(PROXIMITY),
+            Some(TetherResource::Proximity)
+        );
+        assert_eq!(TetherResource::parse(CONFIG), Some(TetherResource::Config));
+        assert_eq!(
+            TetherResource::parse("tether://passes/history/2025-01"),
+            Some(TetherResource::PassHistory("2025-01".to_string()))
+        );
+
+        assert_eq!(TetherResource::parse("tether://passes/history/"), None);
+        assert_eq!(
+            TetherResource::parse("tether://passes/history/2025-1"),
+            None
+        );
+        assert_eq!(
+            TetherResource::parse("tether://passes/history/2025-01/x"),
+            None
+        );
+        assert_eq!(TetherResource::parse("file:///etc/passwd"), None);
+    }
+
+    #[test]
+    fn test_parse_instance_uris() {
+        assert_eq!(
+            parse("tether://instances/home/proximity"),
+            Some((Some("home"), TetherResource::Proximity))
+        );
+        assert_eq!(
+            parse("tether://instances/home/passes/history/2025-01"),
+            Some((
+                Some("home"),
+                TetherResource::PassHistory("2025-01".to_string())
+            ))
+        );
+        assert_eq!(parse(CONFIG), Some((None, TetherResource::Config)));
+
+        assert_eq!(parse("tether://instances//proximity"), None);
+        assert_eq!(parse("tether://instances/home"), None);
+        assert_eq!(
+            parse("tether://instances/home/instances/away/proximity"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_listed_resources_parse() {
+        let resources = list(&["away"]);
+        assert_eq!(resources.len(), 6);
+        for resource in resources {
+            assert!(parse(&resource.uri).is_some(), "{}", resource.uri);
+        }
+        assert_eq!(templates(&[]).len(), 1);
+        assert_eq!(
+            templates(&["away"])[1].uri_template,
+            "tether://instances/{instance}/passes/history/{month}"
+        );
+    }
+
+    #[test]
+    fn test_affected_by() {
+        let subscriptions = Subscriptions::default();
+        for uri in [
+            CURRENT_PASSES,
+            "tether://passes/history/2025-01",
+            PROXIMITY,
+            "tether://instances/away/proximity",
+        ] {
+            subscriptions.uris.lock().unwrap().insert(uri.to_string());
+        }
+        let home = |change| InstanceChange {
+            instance: Some("home".to_string()),
+            change,
+        };
+
+        let mut affected = subscriptions.affected_by(&home(Change::Passes), "home");
+        affected.sort();
+        assert_eq!(
+            affected,
+            [CURRENT_PASSES, "tether://passes/history/2025-01"]
+        );
+
+        assert_eq!(
+            subscriptions.affected_by(&home(Change::Proximity), "home"),
+            [PROXIMITY]
+        );
+        let away = InstanceChange {
+            instance: Some("Away".to_string()),
+            change: Change::Proximity,
+        };
+        assert_eq!(
+            subscriptions.affected_by(&away, "home"),
+            ["tether://instances/away/proximity"]
+        );
+        assert_eq!(
+            subscriptions.affected_by(&home(Change::Config), "home"),
+            Vec::<String>::new()
+        );
+        let everything = InstanceChange {
+            instance: None,
+            change: Change::All,
+        };
+        assert_eq!(subscriptions.affected_by(&everything, "home").len(), 4);
+
+        subscriptions.unsubscribe(PROXIMITY);
+        assert_eq!(
+            subscriptions.affected_by(&home(Change::Proximity), "home"),
+            Vec::<String>::new()
+        );
+    }
+
+    #[test]
+    fn test_change_from_event() {
+        assert_eq!(Change::from_event("pass_used"), [Change::Passes]);
+        assert_eq!(
+            Change::from_event("config_changed"),
+            [Change::Config, Change::Passes]
+        );
+        assert_eq!(Change::from_event(RESYNC_EVENT), [Change::All]);
+        assert_eq!(Change::from_event("change_requested"), []);
+    }
+}
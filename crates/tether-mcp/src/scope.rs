@@ -0,0 +1,175 @@
+//! Permission scope for agents.
+//!
+//! The scope decides which tools the MCP server offers. Tools outside it are
+//! removed from the router, so agents neither see nor can call them. The
+//! default lets agents use passes, as before scopes existed; changing
+//! settings has to be allowed explicitly.
+//!
+//! A tool's scope follows from its annotations: read-only tools need
+//! [`Scope::Read`], and tools that change anything need [`Scope::Settings`],
+//! except the pass tools, which need [`Scope::Passes`]. Tools without a
+//! `read_only_hint`, or read-only ones that claim to be destructive, are
+//! never offered.
+//!
+//! The tether servers enforce a scope of their own for the token sent to
+//! them, so tools are also limited to the widest scope they grant (see
+//! [`server_scope`]).
+
+use rmcp::handler::server::router::tool::ToolRouter;
+use rmcp::model::Tool;
+use tether_client::types::ApiScope;
+use tracing::warn;
+
+use crate::instances::Instance;
+
+/// Tools that use up an emergency pass.
+const PASS_TOOLS: &[&str] = &["use_pass"];
+
+/// What agents connected to this MCP server may do, from least to most.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Scope {
+    /// Only read state.
+    Read,
+    /// Read state and use passes.
+    #[default]
+    Passes,
+    /// Read state, use passes and change settings.
+    Settings,
+}
+
+impl Scope {
+    /// Returns the scope needed to call a tool, or `None` if its annotations
+    /// don't say what it does.
+    fn required_for(tool: &Tool) -> Option<Self> {
+        let annotations = tool.annotations.as_ref()?;
+        match (annotations.read_only_hint?, annotations.destructive_hint) {
+            (true, Some(true)) => None,
+            (true, _) => Some(Self::Read),
+            (false, _) if PASS_TOOLS.contains(&tool.name.as_ref()) => Some(Self::Passes),
+            (false, _) => Some(Self::Settings),
+        }
+    }
+
+    /// Whether agents may call a tool.
+    pub fn allows(self, tool: &Tool) -> bool {
+        Self::required_for(tool).is_some_and(|required| required <= self)
+    }
+
+    /// Removes the tools outside this scope from a router.
+    pub fn restrict<S: Send + Sync + 'static>(self, router: &mut ToolRouter<S>) {
+        for tool in router.list_all() {
+            if !self.allows(&tool) {
+                router.remove_route(&tool.name);
+            }
+        }
+    }
+}
+
+impl From<ApiScope> for Scope {
+    fn from(scope: ApiScope) -> Self {
+        match scope {
+            ApiScope::Read => Self::Read,
+            ApiScope::Passes => Self::Passes,
+            ApiScope::Settings => Self::Settings,
+        }
+    }
+}
+
+/// Returns the widest scope the tether servers grant the token sent to
+/// them.
+///
+/// Servers that can't say, because they are older than scopes or can't be
+/// reached, are assumed to grant every scope, so tools are never hidden
+/// that might work; the server still rejects calls outside its scope.
+pub async fn server_scope(instances: &[Instance]) -> Scope {
+    let mut widest = Scope::Read;
+    for instance in instances {
+        let scope = match instance.client().await.get_access().await {
+            Ok(access) => access.scope.into(),
+            Err(tether_client::Error::Api(error)) if error.status == 404 => Scope::Settings,
+            Err(e) => {
+                warn!(
+                    "Could not get the scope of '{}', assuming it allows everything: {}",
+                    instance.name, e
+                );
+                Scope::Settings
+            }
+        };
+        widest = widest.max(scope);
+    }
+    widest
+}
+
+impl std::str::FromStr for Scope {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "read" | "read-only" | "readonly" => Ok(Self::Read),
+            "passes" => Ok(Self::Passes),
+            "settings" | "all" => Ok(Self::Settings),
+            other => Err(other.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use rmcp::model::{JsonObject, ToolAnnotations};
+
+    #[test]
+    fn test_parse_scope() {
+        assert_eq!("read-only".parse(), Ok(Scope::Read));
+        assert_eq!("Passes".parse(), Ok(Scope::Passes));
+        assert_eq!("settings".parse(), Ok(Scope::Settings));
+        assert!("admin".parse::<Scope>().is_err());
+    }
+
+    #[test]
+    fn test_allows() {
+        let tool = |name: &'static str, annotations: Option<ToolAnnotations>| {
+            let tool = Tool::new(name, "", JsonObject::new());
+            match annotations {
+                Some(annotations) => tool.annotate(annotations),
+                None => tool,
+            }
+        };
+        let read = tool(
+            "get_proximity",
+            Some(ToolAnnotations::new().read_only(true)),
+        );
+        let pass = tool(
+            "use_pass",
+            Some(ToolAnnotations::new().read_only(false).destructive(true)),
+        );
+        let setting = tool(
+            "set_timezone",
+            Some(ToolAnnotations::new().read_only(false).destructive(true)),
+        );
+        assert!(Scope::Read.allows(&read));
+        assert!(!Scope::Read.allows(&pass));
+        assert!(Scope::Passes.allows(&pass));
+        assert!(!Scope::Passes.allows(&setting));
+        assert!(Scope::Settings.allows(&setting));
+
+        // Tools that don't say what they do are never allowed
+        let unannotated = tool("get_proximity", None);
+        let unhinted = tool("get_proximity", Some(ToolAnnotations::new()));
+        let contradictory = tool(
+            "get_proximity",
+            Some(ToolAnnotations::new().read_only(true).destructive(true)),
+        );
+        for tool in [unannotated, unhinted, contradictory] {
+            assert!(!Scope::Settings.allows(&tool), "{tool:?}");
+        }
+    }
+
+    #[test]
+    fn test_from_api_scope() {
+        assert_eq!(Scope::from(ApiScope::Read), Scope::Read);
+        assert_eq!(Scope::from(ApiScope::Passes), Scope::Passes);
+        assert!(Scope::from(ApiScope::Settings) > Scope::Passes);
+    }
+}
@@ -0,0 +1,171 @@
+//! Scopes of API requests.
+//!
+//! Every request below `/api` has a scope. Requests carrying one of the
+//! configured API tokens as a bearer token get that token's scope, requests
+//! carrying the partner token get every scope, and all others get
+//! `access.default_scope`. [`enforce`] rejects requests whose route needs
+//! more with `403 Forbidden`:
+//!
+//! - Reading state needs [`ApiScope::Read`]
+//! - Using a pass needs [`ApiScope::Passes`]
+//! - Any other change needs [`ApiScope::Settings`]
+
+use axum::extract::{MatchedPath, Request, State};
+use axum::http::{HeaderMap, Method};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use sha2::{Digest, Sha256};
+use tether_core::{ApiScope, SecretString};
+
+use crate::api::ApiError;
+use crate::audit::{bearer_token, PARTNER_IDENTITY};
+use crate::guard;
+use crate::state::{AppState, SharedState};
+
+/// Prefix of the identity of requests carrying an API token, followed by
+/// the token's name.
+pub const TOKEN_IDENTITY_PREFIX: &str = "token:";
+
+/// Route of the only change [`ApiScope::Passes`] allows.
+const USE_PASS_ROUTE: &str = "/api/passes/use";
+
+/// What a request may do, and who it authenticated as.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Access {
+    /// What the request may do.
+    pub scope: ApiScope,
+    /// Who the request's token authenticated it as, if anyone.
+    pub identity: Option<String>,
+}
+
+/// Works out the access of a request from its bearer token.
+///
+/// Unknown tokens, and tokens that can't be read from the secrets store,
+/// authenticate no one and get the default scope.
+pub async fn resolve(state: &AppState, headers: &HeaderMap) -> Access {
+    let (default_scope, tokens) = {
+        let config = state.config.read().await;
+        (config.access.default_scope, config.access.tokens.clone())
+    };
+    let Some(given) = bearer_token(headers) else {
+        return Access {
+            scope: default_scope,
+            identity: None,
+        };
+    };
+
+    if let Ok(Some(expected)) = guard::partner_token(state).await {
+        if token_matches(given, &expected) {
+            return Access {
+                scope: ApiScope::Settings,
+                identity: Some(PARTNER_IDENTITY.to_string()),
+            };
+        }
+    }
+
+    let secrets = state.secrets.lock().await;
+    for token in tokens {
+        let Some(id) = &token.token_id else {
+            continue;
+        };
+        if let Ok(Some(expected)) = secrets.get(id) {
+            if token_matches(given, &expected) {
+                return Access {
+                    scope: token.scope,
+                    identity: Some(format!("{TOKEN_IDENTITY_PREFIX}{}", token.name)),
+                };
+            }
+        }
+    }
+    drop(secrets);
+
+    Access {
+        scope: default_scope,
+        identity: None,
+    }
+}
+
+/// Returns whether `given` is the `expected` token.
+#[must_use]
+pub fn token_matches(given: &str, expected: &SecretString) -> bool {
+    // Compare digests so the time taken doesn't reveal how much of the token matched
+    Sha256::digest(given.as_bytes()) == Sha256::digest(expected.expose().as_bytes())
+}
+
+/// Returns the scope a request to `route` needs.
+#[must_use]
+pub fn required(method: &Method, route: &str) -> ApiScope {
+    if matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS) {
+        ApiScope::Read
+    } else if route == USE_PASS_ROUTE {
+        ApiScope::Passes
+    } else {
+        ApiScope::Settings
+    }
+}
+
+/// Returns the name of a scope, as written in the configuration.
+#[must_use]
+pub const fn scope_name(scope: ApiScope) -> &'static str {
+    match scope {
+        ApiScope::Read => "read",
+        ApiScope::Passes => "passes",
+        ApiScope::Settings => "settings",
+    }
+}
+
+/// Middleware rejecting requests outside their scope.
+///
+/// The request's [`Access`] is added to its extensions, so handlers don't
+/// have to work it out again.
+pub async fn enforce(
+    State(state): State<SharedState>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    let access = resolve(&state, request.headers()).await;
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map_or_else(|| request.uri().path(), MatchedPath::as_str);
+    let needed = required(request.method(), route);
+
+    if access.scope < needed {
+        return ApiError::Forbidden {
+            error_code: "insufficient_scope".to_string(),
+            message: format!(
+                "This request needs the '{}' scope, but the {} only has '{}'",
+                scope_name(needed),
+                if access.identity.is_some() {
+                    "token"
+                } else {
+                    "default scope"
+                },
+                scope_name(access.scope)
+            ),
+        }
+        .into_response();
+    }
+
+    request.extensions_mut().insert(access);
+    next.run(request).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_required_scope() {
+        assert_eq!(required(&Method::GET, "/api/config"), ApiScope::Read);
+        assert_eq!(required(&Method::POST, USE_PASS_ROUTE), ApiScope::Passes);
+        assert_eq!(
+            required(&Method::PUT, "/api/config/passes"),
+            ApiScope::Settings
+        );
+        assert_eq!(
+            required(&Method::POST, "/api/guard/changes/{id}/approve"),
+            ApiScope::Settings
+        );
+    }
+}
@@ -1,25 +1,36 @@
 //! HTTP API routes and handlers.
 //!
 //! This module contains all HTTP endpoint implementations organized by domain:
+//! - `access` - The caller's scope
 //! - `bluetooth` - Bluetooth proximity detection and device scanning
 //! - `config` - System configuration management
+//! - `events` - Server-Sent Events stream of server events
 //! - `health` - Service health checks
 //! - `passes` - Monthly pass management
+//! - `webhooks` - Webhook subscriptions and delivery log
+//! - `audit` - Audit log of mutating API calls
+//! - `guard` - Changes to guarded settings waiting for approval
 //! - `error` - API error types
 //! - `openapi` - OpenAPI specification generation
 
-use axum::routing::get;
+use axum::middleware;
+use axum::routing::{get, post};
 use axum::Router;
 
 use crate::state::SharedState;
 
+pub mod access;
+pub mod audit;
 pub mod bluetooth;
 pub mod config;
 pub mod error;
+pub mod events;
+pub mod guard;
 pub mod health;
 pub mod openapi;
 pub mod passes;
 pub mod system;
+pub mod webhooks;
 
 // Re-export commonly used types
 #[allow(unused_imports)]
@@ -35,27 +46,46 @@ pub use openapi::get_openapi_json;
 ///
 /// ```text
 /// /health                - Health check
-/// /api
+/// /metrics               - Prometheus metrics (`metrics` feature)
+/// /api                   - Limited to the request's scope
+/// ├── /access            - The caller's scope
 /// ├── /proximity         - Bluetooth proximity check
 /// ├── /passes            - Pass status, history, and usage
 /// ├── /config            - Configuration management
-/// ├── /devices           - Bluetooth device scanning
+/// ├── /devices           - Bluetooth device scanning and pairing
 /// ├── /system            - System status, ticket, restart
+/// ├── /events            - Server event stream
+/// ├── /webhooks          - Webhook subscriptions and delivery log
+/// ├── /audit             - Audit log of mutating API calls
+/// ├── /guard             - Guarded settings and pending changes
 /// └── /openapi.json      - OpenAPI specification
 /// ```
 pub fn create_router(state: SharedState) -> Router {
     // Initialize server start time for uptime tracking
     system::init_start_time();
 
-    Router::new()
-        .nest("/health", health::router())
+    let router = Router::new().nest("/health", health::router());
+
+    // Prometheus metrics at /metrics
+    #[cfg(feature = "metrics")]
+    let router = router.route("/metrics", get(crate::metrics::render_metrics));
+
+    router
         .nest(
             "/api",
             Router::new()
+                // The caller's scope at /api/access
+                .route("/access", get(access::get_access))
                 // Proximity check at /api/proximity
                 .route("/proximity", get(bluetooth::check_proximity))
                 // Device scanning at /api/devices
                 .route("/devices", get(bluetooth::scan_devices))
+                .route("/devices/pair", post(bluetooth::pair_device))
+                .route("/devices/stream", get(bluetooth::stream_devices))
+                // Server event stream at /api/events
+                .route("/events", get(events::stream_events))
+                // Adapter listing at /api/bluetooth/adapters
+                .route("/bluetooth/adapters", get(bluetooth::list_adapters))
                 // OpenAPI spec at /api/openapi.json
                 .route("/openapi.json", get(openapi::get_openapi_spec))
                 // Pass management
@@ -63,7 +93,169 @@ pub fn create_router(state: SharedState) -> Router {
                 // Configuration management
                 .nest("/config", config::router())
                 // System management
-                .nest("/system", system::router()),
+                .nest("/system", system::router())
+                // Webhook subscriptions
+                .nest("/webhooks", webhooks::router())
+                // Audit log
+                .nest("/audit", audit::router())
+                // Guarded settings
+                .nest("/guard", guard::router())
+                // Reject requests outside their scope
+                .route_layer(middleware::from_fn_with_state(
+                    state.clone(),
+                    crate::access::enforce,
+                )),
         )
         .with_state(state)
 }
+
+#[cfg(test)]
+mod tests {
+    //! Contract tests running [`tether_client::TetherClient`] against the
+    //! real router, so the client and server can't drift apart.
+
+    use super::*;
+    use crate::state::AppState;
+    use tether_client::{Error, TetherClient};
+    use tokio::net::TcpListener;
+
+    /// Serves the router on a local port and returns a client for it.
+    async fn serve(state: SharedState) -> TetherClient {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = axum::serve(listener, create_router(state));
+        tokio::spawn(async move { server.await.unwrap() });
+        TetherClient::new(format!("http://{addr}").parse().unwrap()).with_actor("contract-test")
+    }
+
+    fn api_error(result: Result<impl std::fmt::Debug, Error>) -> tether_client::ApiError {
+        match result {
+            Err(Error::Api(error)) => error,
+            other => panic!("expected an API error, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_client_checks_health() {
+        let dir = tempfile::tempdir().unwrap();
+        let client = serve(AppState::in_dir(dir.path(), None).into_shared()).await;
+
+        let health = client.get_health().await.unwrap();
+        assert_eq!(health.status, "ok");
+        assert_eq!(health.version, env!("CARGO_PKG_VERSION"));
+    }
+
+    #[tokio::test]
+    async fn test_client_uses_and_lists_passes() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = AppState::in_dir(dir.path(), None).into_shared();
+        let client = serve(state.clone()).await;
+
+        let passes = client.get_passes().await.unwrap();
+        assert_eq!(passes.remaining, 3);
+        assert_eq!(passes.total_per_month, 3);
+
+        let used = client.use_pass("On call tonight").await.unwrap();
+        assert!(used.success);
+        assert_eq!(used.remaining, 2);
+        assert_eq!(used.reason, "On call tonight");
+
+        let history = client.get_pass_history(None).await.unwrap();
+        assert_eq!(history.month, passes.month);
+        assert_eq!(history.entries.len(), 1);
+        assert_eq!(history.entries[0].used_at_utc, used.used_at_utc);
+
+        // The actor header reaches the audit log
+        let audit = state.audit.lock().await;
+        assert_eq!(audit.read_all()[0].claimed_actor, "contract-test");
+    }
+
+    #[tokio::test]
+    async fn test_client_decodes_error_responses() {
+        let dir = tempfile::tempdir().unwrap();
+        let client = serve(AppState::in_dir(dir.path(), None).into_shared()).await;
+
+        let error = api_error(client.use_pass("  ").await);
+        assert_eq!(error.status, 400);
+
+        let error = api_error(client.get_pass_history(Some("January")).await);
+        assert_eq!(error.status, 400);
+        assert_eq!(error.error, "invalid_month_format");
+
+        let error = api_error(client.get_proximity().await);
+        assert_eq!(error.status, 424);
+        assert_eq!(error.error, "device_not_configured");
+    }
+
+    #[tokio::test]
+    async fn test_requests_are_limited_to_their_scope() {
+        use tether_client::types::ApiScope;
+        use tether_core::SecretString;
+
+        let dir = tempfile::tempdir().unwrap();
+        let state = AppState::in_dir(dir.path(), None).into_shared();
+        let token_id = state
+            .secrets
+            .lock()
+            .await
+            .insert(&SecretString::new("only-passes-please"))
+            .unwrap();
+        {
+            let mut config = state.config.write().await;
+            config.access.default_scope = tether_core::ApiScope::Read;
+            config.access.tokens.push(tether_core::ApiToken {
+                name: "assistant".to_string(),
+                scope: tether_core::ApiScope::Passes,
+                token: None,
+                token_id: Some(token_id),
+            });
+        }
+        let anonymous = serve(state.clone()).await;
+        let assistant = anonymous.clone().with_token("only-passes-please");
+
+        let access = anonymous.get_access().await.unwrap();
+        assert_eq!(access.scope, ApiScope::Read);
+        assert_eq!(access.authenticated_as, None);
+        anonymous.get_passes().await.unwrap();
+        let error = api_error(anonymous.use_pass("On call tonight").await);
+        assert_eq!(error.status, 403);
+        assert_eq!(error.error, "insufficient_scope");
+
+        let access = assistant.get_access().await.unwrap();
+        assert_eq!(access.scope, ApiScope::Passes);
+        assert_eq!(access.authenticated_as.as_deref(), Some("token:assistant"));
+        assistant.use_pass("On call tonight").await.unwrap();
+        let error = api_error(assistant.update_passes_per_month(10).await);
+        assert_eq!(error.status, 403);
+
+        // Unknown tokens get the default scope
+        let stranger = anonymous.clone().with_token("not-a-known-token");
+        assert_eq!(stranger.get_access().await.unwrap().scope, ApiScope::Read);
+
+        let entries = state.audit.lock().await.read_all();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(
+            entries[0].authenticated_as.as_deref(),
+            Some("token:assistant")
+        );
+    }
+
+    #[cfg(feature = "mock-bluetooth")]
+    #[tokio::test]
+    async fn test_client_checks_proximity() {
+        let dir = tempfile::tempdir().unwrap();
+        let scanner = tether_core::BluetoothScanner::new().await.unwrap();
+        let state = AppState::in_dir(dir.path(), Some(scanner)).into_shared();
+        state.config.write().await.bluetooth.target_address = "AA:BB:CC:DD:EE:FF".to_string();
+        let client = serve(state).await;
+
+        let proximity = client.get_proximity().await.unwrap();
+        assert_eq!(proximity.device_address, "AA:BB:CC:DD:EE:FF");
+        assert_eq!(proximity.rssi_dbm, Some(-55));
+        assert!(proximity.is_nearby);
+        assert_eq!(
+            proximity.detection_method,
+            Some(tether_client::types::DetectionMethod::Advertisement)
+        );
+    }
+}
@@ -0,0 +1,45 @@
+//! Access API endpoint.
+//!
+//! Tells clients what their token lets them do, so they can offer only
+//! what the server will allow.
+
+use axum::extract::State;
+use axum::http::HeaderMap;
+use axum::Json;
+
+use crate::access;
+use crate::state::SharedState;
+
+pub use tether_client::types::{AccessResponse, ApiScope};
+
+/// Get the caller's access.
+///
+/// Returns the scope of the request's bearer token, or the default scope
+/// for requests without a known token.
+#[utoipa::path(
+    get,
+    path = "/api/access",
+    tag = "system",
+    operation_id = "getAccess",
+    summary = "Get the caller's scope",
+    description = "Returns what requests carrying the same bearer token may do: `read` only \
+        reads state, `passes` may also use passes, and `settings` may change anything. \
+        Requests without a known token get the server's default scope.",
+    responses(
+        (status = 200, description = "The caller's scope", body = AccessResponse)
+    )
+)]
+pub async fn get_access(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+) -> Json<AccessResponse> {
+    let access = access::resolve(&state, &headers).await;
+    Json(AccessResponse {
+        scope: match access.scope {
+            tether_core::ApiScope::Read => ApiScope::Read,
+            tether_core::ApiScope::Passes => ApiScope::Passes,
+            tether_core::ApiScope::Settings => ApiScope::Settings,
+        },
+        authenticated_as: access.identity,
+    })
+}
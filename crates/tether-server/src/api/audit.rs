@@ -0,0 +1,158 @@
+//! Audit log API endpoint.
+//!
+//! Exposes the audit log of mutating API calls read-only. See
+//! [`crate::audit`] for what is recorded and how the hash chain works.
+
+use axum::extract::{Query, State};
+use axum::routing::get;
+use axum::{Json, Router};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+use crate::api::error::{ApiError, ApiResult};
+use crate::audit::{AuditChainStatus, AuditEntry};
+use crate::state::SharedState;
+
+/// Maximum number of entries returned per page.
+const MAX_PAGE_SIZE: usize = 200;
+
+/// Creates the audit router with all endpoints.
+pub fn router() -> Router<SharedState> {
+    Router::new().route("/", get(list_audit_entries))
+}
+
+// ============================================================================
+// Request/Response Types
+// ============================================================================
+
+/// Query parameters for the audit log.
+#[derive(Debug, Clone, Deserialize, IntoParams)]
+pub struct AuditLogQuery {
+    /// Only return entries with a `seq` below this, to fetch the next page.
+    #[param(example = 101)]
+    pub before: Option<u64>,
+
+    /// Maximum number of entries to return (at most 200). Defaults to 50.
+    #[param(example = 50)]
+    pub limit: Option<usize>,
+}
+
+/// A page of the audit log.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct AuditLogResponse {
+    /// Entries, newest first.
+    pub entries: Vec<AuditEntry>,
+
+    /// Value of `before` for the next page, if there are older entries.
+    #[schema(example = 51)]
+    pub next_before: Option<u64>,
+
+    /// Whether the hash chain of the whole log is intact.
+    pub chain: AuditChainStatus,
+}
+
+// ============================================================================
+// Handlers
+// ============================================================================
+
+/// List audit log entries.
+#[utoipa::path(
+    get,
+    path = "/audit",
+    tag = "audit",
+    operation_id = "listAuditEntries",
+    summary = "List audit log entries",
+    description = "Returns recorded mutating API calls, newest first, with who made them and \
+        the affected values before and after. Entries are hash-chained: `chain.valid` is false \
+        if an entry was edited or deleted. Record `chain.head_hash` and check that it is still \
+        present later to also detect removal of the newest entries.",
+    params(AuditLogQuery),
+    responses(
+        (status = 200, description = "Audit log retrieved", body = AuditLogResponse)
+    )
+)]
+pub async fn list_audit_entries(
+    State(state): State<SharedState>,
+    Query(query): Query<AuditLogQuery>,
+) -> ApiResult<Json<AuditLogResponse>> {
+    let limit = query.limit.unwrap_or(50).min(MAX_PAGE_SIZE);
+
+    let audit = state.audit.lock().await;
+    let page = audit
+        .page(query.before, limit)
+        .map_err(|e| ApiError::InternalError {
+            error_code: "audit_read_failed".to_string(),
+            message: "Failed to read the audit log".to_string(),
+            details: Some(e.to_string()),
+        })?;
+    let next_before = page
+        .entries
+        .last()
+        .filter(|_| page.has_more)
+        .map(|entry| entry.seq);
+
+    Ok(Json(AuditLogResponse {
+        entries: page.entries,
+        next_before,
+        chain: audit.status(),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::config::{update_timezone, UpdateTimezoneRequest};
+    use crate::api::guard::Guarded;
+    use crate::audit::{Actor, AuditAction};
+    use crate::state::AppState;
+
+    async fn page(state: &SharedState, before: Option<u64>, limit: usize) -> AuditLogResponse {
+        let query = AuditLogQuery {
+            before,
+            limit: Some(limit),
+        };
+        let Json(response) = list_audit_entries(State(state.clone()), Query(query))
+            .await
+            .unwrap();
+        response
+    }
+
+    #[tokio::test]
+    async fn test_records_and_pages_through_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = AppState::in_dir(dir.path(), None).into_shared();
+
+        for timezone in ["Europe/Berlin", "Asia/Tokyo", "America/New_York"] {
+            let request = UpdateTimezoneRequest {
+                timezone: timezone.to_string(),
+            };
+            let response =
+                update_timezone(State(state.clone()), Actor::new("web-ui"), Json(request))
+                    .await
+                    .unwrap();
+            assert!(matches!(response, Guarded::Applied(update) if update.success));
+        }
+
+        let first = page(&state, None, 2).await;
+        assert!(first.chain.valid);
+        assert_eq!(first.chain.entries, 3);
+        assert_eq!(first.chain.head_hash, first.entries[0].hash);
+        let seqs: Vec<u64> = first.entries.iter().map(|entry| entry.seq).collect();
+        assert_eq!(seqs, [3, 2]);
+        assert_eq!(first.next_before, Some(2));
+
+        let newest = &first.entries[0];
+        assert_eq!(newest.claimed_actor, "web-ui");
+        assert_eq!(newest.action, AuditAction::UpdateTimezone);
+        assert_eq!(newest.before.as_ref().unwrap()["timezone"], "Asia/Tokyo");
+        assert_eq!(
+            newest.after.as_ref().unwrap()["timezone"],
+            "America/New_York"
+        );
+
+        let second = page(&state, first.next_before, 2).await;
+        assert_eq!(second.entries.len(), 1);
+        assert_eq!(second.entries[0].seq, 1);
+        assert_eq!(second.next_before, None);
+    }
+}
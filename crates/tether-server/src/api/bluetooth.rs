@@ -2,14 +2,28 @@
 //!
 //! Provides endpoints for proximity detection and device scanning.
 
-use axum::extract::State;
+use std::sync::Arc;
+use std::time::Instant;
+
+use axum::extract::{Query, State};
+use axum::response::sse::{Event, KeepAlive, Sse};
 use axum::Json;
 use chrono::Utc;
+use futures::Stream;
 use serde::{Deserialize, Serialize};
-use utoipa::ToSchema;
+use utoipa::{IntoParams, ToSchema};
 
+use crate::api::config::{bluetooth_config_response, BluetoothConfigResponse};
 use crate::api::error::{ApiError, ApiResult};
-use crate::state::SharedState;
+use crate::api::guard::{Guarded, PendingChangeResponse};
+use crate::audit::{snapshot, Actor, AuditAction};
+use crate::guard::{self, GuardedChange};
+use crate::state::{AppState, SharedState};
+use tether_core::{AdapterInfo, BluetoothDevice, BluetoothScanner, IdentityResolvingKey};
+
+pub use tether_client::types::{
+    BluetoothAddressType, DetectionMethod, DiscoveredDevice, ProximityResponse, ScanDevicesResponse,
+};
 
 // Note: Routes are now exposed directly in api.rs at /api/proximity and /api/devices
 // This module still provides the handlers and types.
@@ -18,87 +32,112 @@ use crate::state::SharedState;
 // Request/Response Types
 // ============================================================================
 
-/// Proximity check response.
+/// Query parameters for the streaming device scan.
+#[derive(Debug, Clone, Deserialize, IntoParams)]
+pub struct StreamDevicesQuery {
+    /// How long to scan for, in seconds (at most 120). Defaults to 60.
+    #[param(example = 60)]
+    pub duration_secs: Option<u64>,
+}
+
+/// Progress of a streaming device scan, sent as a `device` event.
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 #[schema(example = json!({
-    "device_name": "iPhone 15 Pro",
-    "device_address": "AA:BB:CC:DD:EE:FF",
-    "is_nearby": true,
-    "rssi_dbm": -45,
-    "threshold_dbm": -60,
-    "checked_at_utc": "2025-01-15T03:30:00Z"
+    "changed_address": "AA:BB:CC:DD:EE:FF",
+    "devices": [
+        {
+            "address": "AA:BB:CC:DD:EE:FF",
+            "name": "iPhone 15 Pro",
+            "rssi_dbm": -45,
+            "address_type": "public",
+            "paired": false
+        }
+    ]
 }))]
-pub struct ProximityResponse {
-    /// The configured Bluetooth device name.
-    #[schema(example = "iPhone 15 Pro")]
-    pub device_name: String,
-
-    /// The Bluetooth MAC address of the tracked device.
+pub struct DeviceDiscoveryUpdate {
+    /// Address of the device that appeared or whose signal changed.
     #[schema(example = "AA:BB:CC:DD:EE:FF")]
-    pub device_address: String,
-
-    /// Whether the device is considered nearby based on RSSI threshold.
-    #[schema(example = true)]
-    pub is_nearby: bool,
-
-    /// The current RSSI signal strength in dBm.
-    #[schema(example = -45)]
-    pub rssi_dbm: Option<i16>,
-
-    /// The configured RSSI threshold in dBm.
-    #[schema(example = -60)]
-    pub threshold_dbm: i8,
+    pub changed_address: String,
 
-    /// UTC timestamp of when this check was performed.
-    #[schema(example = "2025-01-15T03:30:00Z")]
-    pub checked_at_utc: String,
+    /// All devices seen so far, strongest signal first.
+    pub devices: Vec<DiscoveredDevice>,
 }
 
-/// A discovered Bluetooth device.
+/// Bluetooth adapter listing response.
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 #[schema(example = json!({
-    "address": "AA:BB:CC:DD:EE:FF",
-    "name": "iPhone 15 Pro",
-    "rssi_dbm": -45
+    "adapters": [
+        {
+            "name": "hci0",
+            "address": "B8:27:EB:12:34:56",
+            "powered": true,
+            "in_use": true
+        },
+        {
+            "name": "hci1",
+            "address": "00:1A:7D:DA:71:13",
+            "powered": true,
+            "in_use": false
+        }
+    ]
 }))]
-pub struct DiscoveredDevice {
-    /// Bluetooth MAC address.
-    #[schema(example = "AA:BB:CC:DD:EE:FF")]
+pub struct AdaptersResponse {
+    /// All adapters known to BlueZ.
+    pub adapters: Vec<AdapterInfo>,
+}
+
+/// Request to pair with a device and make it the tracked device.
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+#[schema(example = json!({
+    "address": "5A:1B:2C:3D:4E:5F",
+    "target_name": "iPhone 15 Pro"
+}))]
+pub struct PairDeviceRequest {
+    /// Address the device is currently advertising with, as returned by a scan.
+    #[schema(example = "5A:1B:2C:3D:4E:5F")]
     pub address: String,
 
-    /// Device name (if broadcast).
+    /// User-friendly name for the device. Defaults to the advertised name.
     #[schema(example = "iPhone 15 Pro")]
-    pub name: Option<String>,
-
-    /// Signal strength in dBm.
-    #[schema(example = -45)]
-    pub rssi_dbm: Option<i16>,
+    pub target_name: Option<String>,
 }
 
-/// Device scan response.
+/// Response after pairing with a device.
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 #[schema(example = json!({
-    "devices": [
-        {
-            "address": "AA:BB:CC:DD:EE:FF",
-            "name": "iPhone 15 Pro",
-            "rssi_dbm": -45
-        }
-    ],
-    "scan_duration_secs": 5,
-    "scanned_at_utc": "2025-01-15T03:30:00Z"
+    "success": true,
+    "identity_address": "AA:BB:CC:DD:EE:FF",
+    "address_type": "resolvable",
+    "irk_obtained": true,
+    "bluetooth": {
+        "target_address": "AA:BB:CC:DD:EE:FF",
+        "target_name": "iPhone 15 Pro",
+        "rssi_threshold": -60,
+        "probe_mode": "passive",
+        "is_configured": true,
+        "is_paired": true
+    }
 }))]
-pub struct ScanDevicesResponse {
-    /// List of discovered devices.
-    pub devices: Vec<DiscoveredDevice>,
+pub struct PairDeviceResponse {
+    /// Whether pairing succeeded.
+    pub success: bool,
 
-    /// How long the scan took.
-    #[schema(example = 5)]
-    pub scan_duration_secs: u64,
+    /// The device's stable identity address, now used as the target address.
+    #[schema(example = "AA:BB:CC:DD:EE:FF")]
+    pub identity_address: String,
 
-    /// When the scan completed.
-    #[schema(example = "2025-01-15T03:30:00Z")]
-    pub scanned_at_utc: String,
+    /// Kind of address the device was advertising with when paired.
+    pub address_type: BluetoothAddressType,
+
+    /// Whether an Identity Resolving Key was obtained.
+    ///
+    /// Without it, a device with a rotating address is only recognised while
+    /// it keeps advertising its identity address.
+    #[schema(example = true)]
+    pub irk_obtained: bool,
+
+    /// Updated Bluetooth configuration.
+    pub bluetooth: BluetoothConfigResponse,
 }
 
 // ============================================================================
@@ -108,6 +147,9 @@ pub struct ScanDevicesResponse {
 /// Default scan timeout in seconds.
 const DEFAULT_SCAN_TIMEOUT_SECS: u64 = 10;
 
+/// Default duration of a streaming scan in seconds.
+const DEFAULT_STREAM_DURATION_SECS: u64 = 60;
+
 /// Check if the configured Bluetooth device is nearby.
 ///
 /// Performs a lazy proximity check by scanning for the configured Bluetooth
@@ -131,10 +173,11 @@ const DEFAULT_SCAN_TIMEOUT_SECS: u64 = 10;
 pub async fn check_proximity(
     State(state): State<SharedState>,
 ) -> ApiResult<Json<ProximityResponse>> {
-    let state_guard = state.read().await;
+    // Copy the settings so no lock is held during the scan
+    let bluetooth = state.config.read().await.bluetooth.clone();
 
     // Check if Bluetooth target is configured (not placeholder)
-    let target_address = &state_guard.config.bluetooth.target_address;
+    let target_address = &bluetooth.target_address;
     if target_address == "00:00:00:00:00:00" {
         return Err(ApiError::FailedDependency {
             error_code: "device_not_configured".to_string(),
@@ -144,44 +187,42 @@ pub async fn check_proximity(
         });
     }
 
-    let target_name = state_guard.config.bluetooth.target_name.clone();
-    let threshold_dbm = state_guard.config.bluetooth.rssi_threshold;
+    let target_name = bluetooth.target_name.clone();
+    let threshold_dbm = bluetooth.rssi_threshold;
 
     // Check if Bluetooth scanner is available
-    let scanner = state_guard.bluetooth.as_ref().ok_or_else(|| {
-        ApiError::ServiceUnavailable {
-            error_code: "bluetooth_unavailable".to_string(),
-            message: "Bluetooth adapter is not available".to_string(),
-            details: None,
-        }
-    })?;
+    let scanner = require_scanner(&state).await?;
 
     // Create a config for the scan
     let bt_config = tether_core::BtConfig {
         device_address: target_address.clone(),
         rssi_threshold: i16::from(threshold_dbm),
+        identity_resolving_key: bluetooth.target_irk,
+        probe_mode: bluetooth.probe_mode,
+        trust_connection_without_rssi: bluetooth.trust_connection_without_rssi,
+        rssi_fusion: bluetooth.rssi_fusion,
     };
 
     // Perform proximity check
-    let result = scanner.check_proximity(&bt_config).await.map_err(|e| {
-        ApiError::ServiceUnavailable {
-            error_code: "bluetooth_scan_failed".to_string(),
-            message: "Bluetooth scan failed".to_string(),
-            details: Some(e.to_string()),
-        }
+    let result = scanner.check_proximity(&bt_config).await;
+    match &result {
+        Ok(_) => state.bluetooth_health.record_success(),
+        Err(e) => state.bluetooth_health.record_failure(e),
+    }
+    let result = result.map_err(|e| ApiError::ServiceUnavailable {
+        error_code: "bluetooth_scan_failed".to_string(),
+        message: "Bluetooth scan failed".to_string(),
+        details: Some(e.to_string()),
     })?;
-
-    let is_nearby = result
-        .rssi
-        .map(|rssi| rssi >= i16::from(threshold_dbm))
-        .unwrap_or(false);
+    state.record_proximity(result.nearby, result.rssi, target_address);
 
     Ok(Json(ProximityResponse {
         device_name: target_name,
         device_address: target_address.clone(),
-        is_nearby,
+        is_nearby: result.nearby,
         rssi_dbm: result.rssi,
         threshold_dbm,
+        detection_method: result.detection_method.map(detection_method),
         checked_at_utc: Utc::now().to_rfc3339(),
     }))
 }
@@ -203,46 +244,300 @@ pub async fn check_proximity(
         (status = 503, description = "Bluetooth service unavailable")
     )
 )]
-pub async fn scan_devices(State(state): State<SharedState>) -> ApiResult<Json<ScanDevicesResponse>> {
-    let state_guard = state.read().await;
-
+pub async fn scan_devices(
+    State(state): State<SharedState>,
+) -> ApiResult<Json<ScanDevicesResponse>> {
     // Check if Bluetooth scanner is available
-    let scanner = state_guard.bluetooth.as_ref().ok_or_else(|| {
-        ApiError::ServiceUnavailable {
+    let scanner = require_scanner(&state).await?;
+
+    let timeout_secs = DEFAULT_SCAN_TIMEOUT_SECS;
+
+    // Perform device scan
+    let discovered = scanner.discover_devices(timeout_secs).await;
+    match &discovered {
+        Ok(_) => state.bluetooth_health.record_success(),
+        Err(e) => state.bluetooth_health.record_failure(e),
+    }
+    let discovered = discovered.map_err(|e| ApiError::ServiceUnavailable {
+        error_code: "bluetooth_scan_failed".to_string(),
+        message: "Bluetooth scan failed".to_string(),
+        details: Some(e.to_string()),
+    })?;
+
+    let devices: Vec<DiscoveredDevice> = discovered.into_iter().map(discovered_device).collect();
+
+    Ok(Json(ScanDevicesResponse {
+        devices,
+        scan_duration_secs: timeout_secs,
+        scanned_at_utc: Utc::now().to_rfc3339(),
+    }))
+}
+
+/// Stream nearby Bluetooth devices as they are discovered.
+///
+/// The scan runs in the background on a handle to the scanner, without
+/// holding any state lock, and stops when the client disconnects.
+#[utoipa::path(
+    get,
+    path = "/devices/stream",
+    tag = "devices",
+    operation_id = "streamDevices",
+    summary = "Stream Bluetooth devices as they are discovered",
+    description = "Scans for Bluetooth devices and streams them as Server-Sent Events. \
+        A `device` event is sent whenever a device appears or its signal strength \
+        changes, carrying every device seen so far sorted by signal, strongest first. \
+        A final `complete` event carries the full result once the scan duration has \
+        elapsed. Close the connection to cancel the scan. During onboarding, the \
+        user's phone is usually the device whose signal grows as it is brought closer.",
+    params(StreamDevicesQuery),
+    responses(
+        (status = 200, description = "Event stream of discovered devices", content_type = "text/event-stream", body = DeviceDiscoveryUpdate),
+        (status = 503, description = "Bluetooth service unavailable")
+    )
+)]
+pub async fn stream_devices(
+    State(state): State<SharedState>,
+    Query(query): Query<StreamDevicesQuery>,
+) -> ApiResult<Sse<impl Stream<Item = Result<Event, axum::Error>>>> {
+    let duration_secs = query.duration_secs.unwrap_or(DEFAULT_STREAM_DURATION_SECS);
+
+    let scanner = require_scanner(&state).await?;
+    let watch = scanner.watch_devices(duration_secs).await;
+    match &watch {
+        Ok(_) => state.bluetooth_health.record_success(),
+        Err(e) => state.bluetooth_health.record_failure(e),
+    }
+    let watch = watch.map_err(|e| ApiError::ServiceUnavailable {
+        error_code: "bluetooth_scan_failed".to_string(),
+        message: "Bluetooth scan failed".to_string(),
+        details: Some(e.to_string()),
+    })?;
+
+    let started = Instant::now();
+    let initial = Some((watch, Vec::<DiscoveredDevice>::new()));
+    let stream = futures::stream::unfold(initial, move |scan| async move {
+        let (mut watch, mut devices) = scan?;
+        let Some(device) = watch.next().await else {
+            // Scan finished: send the final result and end the stream
+            let event = Event::default()
+                .event("complete")
+                .json_data(ScanDevicesResponse {
+                    devices,
+                    scan_duration_secs: started.elapsed().as_secs(),
+                    scanned_at_utc: Utc::now().to_rfc3339(),
+                });
+            return Some((event, None));
+        };
+
+        let changed_address = device.address.clone();
+        let device = discovered_device(device);
+        if let Some(existing) = devices.iter_mut().find(|d| d.address == device.address) {
+            *existing = device;
+        } else {
+            devices.push(device);
+        }
+        sort_by_signal(&mut devices);
+
+        let event = Event::default()
+            .event("device")
+            .json_data(DeviceDiscoveryUpdate {
+                changed_address,
+                devices: devices.clone(),
+            });
+        Some((event, Some((watch, devices))))
+    });
+
+    Ok(Sse::new(stream).keep_alive(KeepAlive::default()))
+}
+
+/// Converts how the scanner detected the device to its API representation.
+const fn detection_method(method: tether_core::DetectionMethod) -> DetectionMethod {
+    match method {
+        tether_core::DetectionMethod::Advertisement => DetectionMethod::Advertisement,
+        tether_core::DetectionMethod::Connection => DetectionMethod::Connection,
+    }
+}
+
+/// Returns a handle to the scanner, or an error if Bluetooth is unavailable.
+async fn require_scanner(state: &AppState) -> ApiResult<Arc<BluetoothScanner>> {
+    state
+        .scanner()
+        .await
+        .ok_or_else(|| ApiError::ServiceUnavailable {
             error_code: "bluetooth_unavailable".to_string(),
             message: "Bluetooth adapter is not available".to_string(),
             details: None,
-        }
-    })?;
+        })
+}
 
-    let timeout_secs = DEFAULT_SCAN_TIMEOUT_SECS;
+/// Converts a discovered device to its API representation.
+fn discovered_device(device: BluetoothDevice) -> DiscoveredDevice {
+    DiscoveredDevice {
+        address: device.address,
+        name: device.name,
+        rssi_dbm: device.rssi,
+        address_type: address_type_response(device.address_type),
+        paired: device.paired,
+    }
+}
 
-    // Perform device scan
-    let discovered = scanner
-        .discover_devices(timeout_secs)
+/// Converts an address type to its API representation.
+const fn address_type_response(
+    address_type: tether_core::BluetoothAddressType,
+) -> BluetoothAddressType {
+    match address_type {
+        tether_core::BluetoothAddressType::Public => BluetoothAddressType::Public,
+        tether_core::BluetoothAddressType::Random => BluetoothAddressType::Random,
+        tether_core::BluetoothAddressType::Resolvable => BluetoothAddressType::Resolvable,
+    }
+}
+
+/// Sorts devices by signal strength, strongest first and unknown last.
+fn sort_by_signal(devices: &mut [DiscoveredDevice]) {
+    devices.sort_by_key(|d| std::cmp::Reverse(d.rssi_dbm));
+}
+
+/// List the Bluetooth adapters on this Tether.
+#[utoipa::path(
+    get,
+    path = "/bluetooth/adapters",
+    tag = "devices",
+    operation_id = "listAdapters",
+    summary = "List Bluetooth adapters",
+    description = "Lists the Bluetooth adapters present on this Tether and whether \
+        each is used for scanning. Select adapters with PUT /api/config/bluetooth. \
+        A USB adapter placed closer to the door can extend range.",
+    responses(
+        (status = 200, description = "Adapters listed", body = AdaptersResponse),
+        (status = 503, description = "Bluetooth service unavailable")
+    )
+)]
+pub async fn list_adapters(State(state): State<SharedState>) -> ApiResult<Json<AdaptersResponse>> {
+    let scanner = require_scanner(&state).await?;
+
+    let adapters = scanner
+        .list_adapters()
         .await
         .map_err(|e| ApiError::ServiceUnavailable {
-            error_code: "bluetooth_scan_failed".to_string(),
-            message: "Bluetooth scan failed".to_string(),
+            error_code: "bluetooth_unavailable".to_string(),
+            message: "Failed to list Bluetooth adapters".to_string(),
             details: Some(e.to_string()),
         })?;
 
-    let devices: Vec<DiscoveredDevice> = discovered
-        .into_iter()
-        .map(|d| DiscoveredDevice {
-            address: d.address,
-            name: d.name,
-            rssi_dbm: d.rssi,
-        })
-        .collect();
+    Ok(Json(AdaptersResponse { adapters }))
+}
 
-    Ok(Json(ScanDevicesResponse {
-        devices,
-        scan_duration_secs: timeout_secs,
-        scanned_at_utc: Utc::now().to_rfc3339(),
+/// Pair with a Bluetooth device and track it.
+///
+/// Pairing yields the device's identity address and Identity Resolving Key,
+/// which lets proximity checks recognise phones that rotate their address.
+#[utoipa::path(
+    post,
+    path = "/devices/pair",
+    tag = "devices",
+    operation_id = "pairDevice",
+    summary = "Pair with a Bluetooth device",
+    description = "Pairs with the device at the given address and makes it the \
+        tracked device. The phone will show a pairing prompt that must be \
+        accepted. Phones with address randomisation (all modern iPhones and \
+        Android phones) must be paired to be tracked reliably. With guarded \
+        settings enabled, tracking the paired device waits for approval.",
+    request_body = PairDeviceRequest,
+    responses(
+        (status = 200, description = "Device paired", body = PairDeviceResponse),
+        (status = 202, description = "Device paired; tracking it is waiting for approval", body = PendingChangeResponse),
+        (status = 400, description = "Invalid Bluetooth address format"),
+        (status = 404, description = "Device not found"),
+        (status = 424, description = "Pairing was rejected or timed out"),
+        (status = 503, description = "Bluetooth service unavailable")
+    )
+)]
+pub async fn pair_device(
+    State(state): State<SharedState>,
+    actor: Actor,
+    Json(request): Json<PairDeviceRequest>,
+) -> ApiResult<Guarded<PairDeviceResponse>> {
+    if !tether_core::is_valid_mac_address(&request.address) {
+        return Err(ApiError::BadRequest {
+            error_code: "invalid_bluetooth_address".to_string(),
+            message: "Bluetooth address must be in format XX:XX:XX:XX:XX:XX".to_string(),
+        });
+    }
+
+    let paired = require_scanner(&state)
+        .await?
+        .pair_device(&request.address)
+        .await
+        .map_err(tether_core::TetherError::from)?;
+
+    let identity_address = paired.identity_address.to_uppercase();
+    let target_name = request.target_name.or(paired.name);
+    let target_irk = paired.identity_resolving_key;
+
+    if guard::is_active(&state).await {
+        let change = GuardedChange::PairDevice {
+            identity_address,
+            target_name,
+            target_irk,
+        };
+        let pending = guard::defer(&state, &actor, change).await?;
+        return Ok(Guarded::Pending(pending.into()));
+    }
+
+    let bluetooth = apply_pairing(
+        &state,
+        &actor,
+        &identity_address,
+        target_name.as_deref(),
+        target_irk,
+    )
+    .await?;
+
+    Ok(Guarded::Applied(PairDeviceResponse {
+        success: true,
+        identity_address,
+        address_type: address_type_response(paired.address_type),
+        irk_obtained: target_irk.is_some(),
+        bluetooth,
     }))
 }
 
+/// Makes a paired device the tracked device and returns the new configuration.
+///
+/// Keeps the current name if `target_name` is `None`.
+pub(crate) async fn apply_pairing(
+    state: &AppState,
+    actor: &Actor,
+    identity_address: &str,
+    target_name: Option<&str>,
+    target_irk: Option<IdentityResolvingKey>,
+) -> ApiResult<BluetoothConfigResponse> {
+    let mut config = state.config.write().await;
+    let before = snapshot(&bluetooth_config_response(&config.bluetooth));
+
+    let bluetooth = &mut config.bluetooth;
+    bluetooth.target_address = identity_address.to_string();
+    if let Some(target_name) = target_name {
+        bluetooth.target_name = target_name.to_string();
+    }
+    bluetooth.target_irk = target_irk;
+
+    state
+        .save_config(&config)
+        .map_err(|e| ApiError::InternalError {
+            error_code: "config_save_failed".to_string(),
+            message: "Failed to save configuration".to_string(),
+            details: Some(e.to_string()),
+        })?;
+
+    let bluetooth = bluetooth_config_response(&config.bluetooth);
+    state
+        .record_audit(actor, AuditAction::PairDevice, before, snapshot(&bluetooth))
+        .await;
+
+    Ok(bluetooth)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -255,6 +550,7 @@ mod tests {
             is_nearby: true,
             rssi_dbm: Some(-45),
             threshold_dbm: -60,
+            detection_method: Some(DetectionMethod::Advertisement),
             checked_at_utc: "2025-01-15T03:30:00Z".to_string(),
         };
         let json = serde_json::to_string(&response).unwrap();
@@ -268,11 +564,117 @@ mod tests {
                 address: "AA:BB:CC:DD:EE:FF".to_string(),
                 name: Some("iPhone".to_string()),
                 rssi_dbm: Some(-45),
+                address_type: BluetoothAddressType::Resolvable,
+                paired: false,
             }],
             scan_duration_secs: 5,
             scanned_at_utc: "2025-01-15T03:30:00Z".to_string(),
         };
         let json = serde_json::to_string(&response).unwrap();
         assert!(json.contains("devices"));
+        assert!(json.contains("\"address_type\":\"resolvable\""));
+    }
+
+    #[test]
+    fn test_pair_device_request_deserialization() {
+        let json = r#"{"address": "5A:1B:2C:3D:4E:5F"}"#;
+        let request: PairDeviceRequest = serde_json::from_str(json).unwrap();
+        assert_eq!(request.address, "5A:1B:2C:3D:4E:5F");
+        assert!(request.target_name.is_none());
+    }
+
+    #[test]
+    fn test_sort_by_signal() {
+        let device = |address: &str, rssi_dbm| DiscoveredDevice {
+            address: address.to_string(),
+            name: None,
+            rssi_dbm,
+            address_type: BluetoothAddressType::Public,
+            paired: false,
+        };
+        let mut devices = vec![
+            device("AA:AA:AA:AA:AA:01", None),
+            device("AA:AA:AA:AA:AA:02", Some(-80)),
+            device("AA:AA:AA:AA:AA:03", Some(-40)),
+        ];
+        sort_by_signal(&mut devices);
+        let order: Vec<_> = devices.iter().map(|d| d.address.as_str()).collect();
+        assert_eq!(
+            order,
+            [
+                "AA:AA:AA:AA:AA:03",
+                "AA:AA:AA:AA:AA:02",
+                "AA:AA:AA:AA:AA:01"
+            ]
+        );
+    }
+
+    #[cfg(feature = "mock-bluetooth")]
+    #[tokio::test]
+    async fn test_stream_devices_does_not_hold_state_lock() {
+        use axum::response::IntoResponse;
+        use std::time::Duration;
+
+        let dir = tempfile::tempdir().unwrap();
+        let scanner = BluetoothScanner::new().await.unwrap();
+        let state = AppState::in_dir(dir.path(), Some(scanner)).into_shared();
+
+        let response = stream_devices(
+            State(state.clone()),
+            Query(StreamDevicesQuery {
+                duration_secs: Some(1),
+            }),
+        )
+        .await
+        .unwrap()
+        .into_response();
+
+        // Writers are not blocked while the scan runs
+        let scanner = tokio::time::timeout(Duration::from_millis(100), state.bluetooth.write())
+            .await
+            .expect("scanner lock is free during the scan");
+        drop(scanner);
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body = String::from_utf8(body.to_vec()).unwrap();
+        assert!(body.contains("event: device"));
+        assert!(body.contains("event: complete"));
+    }
+
+    #[cfg(feature = "mock-bluetooth")]
+    #[tokio::test]
+    async fn test_scan_does_not_block_pass_use() {
+        use crate::api::passes::{use_pass, UsePassRequest};
+        use std::time::Duration;
+
+        let dir = tempfile::tempdir().unwrap();
+        let scanner = BluetoothScanner::new()
+            .await
+            .unwrap()
+            .with_scan_delay(Duration::from_secs(2));
+        let state = AppState::in_dir(dir.path(), Some(scanner)).into_shared();
+        state.config.write().await.bluetooth.target_address = "AA:BB:CC:DD:EE:FF".to_string();
+
+        // Start a slow scan, then use a pass while it runs
+        let scan = tokio::spawn(check_proximity(State(state.clone())));
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        let request = UsePassRequest {
+            reason: "On call tonight".to_string(),
+        };
+        let used = tokio::time::timeout(
+            Duration::from_millis(500),
+            use_pass(State(state.clone()), Actor::new("tester"), Json(request)),
+        )
+        .await
+        .expect("using a pass is not blocked by the scan")
+        .unwrap();
+        assert_eq!(used.remaining, 2);
+        assert!(!scan.is_finished());
+
+        let proximity = scan.await.unwrap().unwrap();
+        assert!(proximity.is_nearby);
     }
 }
@@ -1,16 +1,22 @@
 //! Configuration API endpoints.
 //!
 //! Provides endpoints for reading and updating system configuration
-//! including Bluetooth target device, timezone, and passes per month.
+//! including Bluetooth target device, timezone, curfew, and passes per month.
 
 use axum::extract::State;
 use axum::routing::{get, put};
 use axum::{Json, Router};
 use serde::{Deserialize, Serialize};
+use serde_json::json;
 use utoipa::ToSchema;
 
 use crate::api::error::{ApiError, ApiResult};
-use crate::state::SharedState;
+use crate::api::guard::{Guarded, PendingChangeResponse};
+use crate::audit::{snapshot, Actor, AuditAction};
+use crate::events::{reset_month_if_needed, ConfigSection, ServerEvent};
+use crate::guard::{self, GuardedChange};
+use crate::state::{AppState, SharedState};
+use tether_core::{ProbeMode, RssiFusion};
 
 /// Creates the config router with all endpoints.
 pub fn router() -> Router<SharedState> {
@@ -19,6 +25,7 @@ pub fn router() -> Router<SharedState> {
         .route("/bluetooth", put(update_bluetooth))
         .route("/wifi", put(update_wifi))
         .route("/timezone", put(update_timezone))
+        .route("/curfew", put(update_curfew))
         .route("/passes", put(update_passes_per_month))
         .route("/onboarding/complete", put(complete_onboarding))
 }
@@ -27,67 +34,21 @@ pub fn router() -> Router<SharedState> {
 // Request/Response Types
 // ============================================================================
 
-/// Current configuration response.
-#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
-#[schema(example = json!({
-    "bluetooth": {
-        "target_address": "AA:BB:CC:DD:EE:FF",
-        "target_name": "iPhone 15 Pro",
-        "rssi_threshold": -60
-    },
-    "timezone": "America/Los_Angeles",
-    "passes_per_month": 3,
-    "onboarding_complete": true
-}))]
-pub struct ConfigResponse {
-    /// Bluetooth target configuration.
-    pub bluetooth: BluetoothConfigResponse,
-
-    /// Configured timezone (IANA format).
-    #[schema(example = "America/Los_Angeles")]
-    pub timezone: String,
+pub use tether_client::types::{
+    BluetoothConfigResponse, ConfigResponse, CurfewResponse, UpdateCurfewRequest,
+    UpdateCurfewResponse, UpdatePassesPerMonthRequest, UpdatePassesPerMonthResponse,
+    UpdateTimezoneRequest, UpdateTimezoneResponse, WifiNetworkResponse,
+};
 
-    /// Number of passes allowed per month.
-    #[schema(example = 3)]
-    pub passes_per_month: u8,
-
-    /// Whether initial onboarding has been completed.
-    #[schema(example = true)]
-    pub onboarding_complete: bool,
-}
-
-/// Bluetooth configuration in response.
+/// Request to update Bluetooth target device.
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 #[schema(example = json!({
     "target_address": "AA:BB:CC:DD:EE:FF",
     "target_name": "iPhone 15 Pro",
     "rssi_threshold": -60,
-    "is_configured": true
-}))]
-pub struct BluetoothConfigResponse {
-    /// Bluetooth MAC address of target device.
-    #[schema(example = "AA:BB:CC:DD:EE:FF")]
-    pub target_address: String,
-
-    /// User-friendly name of the device.
-    #[schema(example = "iPhone 15 Pro")]
-    pub target_name: String,
-
-    /// RSSI threshold for proximity detection.
-    #[schema(example = -60)]
-    pub rssi_threshold: i8,
-
-    /// Whether a real device has been configured (not placeholder).
-    #[schema(example = true)]
-    pub is_configured: bool,
-}
-
-/// Request to update Bluetooth target device.
-#[derive(Debug, Clone, Deserialize, ToSchema)]
-#[schema(example = json!({
-    "target_address": "AA:BB:CC:DD:EE:FF",
-    "target_name": "iPhone 15 Pro",
-    "rssi_threshold": -60
+    "probe_mode": "passive_then_active",
+    "adapters": ["hci0", "hci1"],
+    "rssi_fusion": "strongest"
 }))]
 pub struct UpdateBluetoothRequest {
     /// Bluetooth MAC address (XX:XX:XX:XX:XX:XX format).
@@ -101,6 +62,18 @@ pub struct UpdateBluetoothRequest {
     /// Optional RSSI threshold (-100 to 0 dBm). Defaults to -60.
     #[schema(example = -60)]
     pub rssi_threshold: Option<i8>,
+
+    /// Optional presence detection mode. `active` and `passive_then_active`
+    /// connect to the device and require it to be paired. Unchanged if omitted.
+    pub probe_mode: Option<ProbeMode>,
+
+    /// Optional adapters to scan with (see `GET /api/bluetooth/adapters`).
+    /// An empty list selects the default adapter. Unchanged if omitted.
+    pub adapters: Option<Vec<String>>,
+
+    /// Optional way of combining readings from several adapters.
+    /// Unchanged if omitted.
+    pub rssi_fusion: Option<RssiFusion>,
 }
 
 /// Response after updating Bluetooth configuration.
@@ -113,59 +86,10 @@ pub struct UpdateBluetoothResponse {
     pub bluetooth: BluetoothConfigResponse,
 }
 
-/// Request to update timezone.
-#[derive(Debug, Clone, Deserialize, ToSchema)]
-#[schema(example = json!({
-    "timezone": "America/Los_Angeles"
-}))]
-pub struct UpdateTimezoneRequest {
-    /// IANA timezone name (e.g., "America/Los_Angeles").
-    #[schema(example = "America/Los_Angeles")]
-    pub timezone: String,
-}
-
-/// Response after updating timezone.
-#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
-pub struct UpdateTimezoneResponse {
-    /// Whether the update was successful.
-    pub success: bool,
-
-    /// Updated timezone.
-    #[schema(example = "America/Los_Angeles")]
-    pub timezone: String,
-}
-
-/// Request to update passes per month.
-#[derive(Debug, Clone, Deserialize, ToSchema)]
-#[schema(example = json!({
-    "per_month": 3
-}))]
-pub struct UpdatePassesPerMonthRequest {
-    /// Number of passes per month (0-31).
-    #[schema(example = 3, minimum = 0, maximum = 31)]
-    pub per_month: u8,
-}
-
-/// Response after updating passes per month.
-#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
-pub struct UpdatePassesPerMonthResponse {
-    /// Whether the update was successful.
-    pub success: bool,
-
-    /// Updated passes per month value.
-    #[schema(example = 3)]
-    pub per_month: u8,
-
-    /// Whether the change is pending (will apply next month).
-    #[schema(example = true)]
-    pub pending: bool,
-
-    /// Message explaining when the change takes effect.
-    pub message: String,
-}
-
 /// A WiFi network configuration.
-#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+///
+/// Only ever received; the password is redacted from `Debug` output.
+#[derive(Clone, Deserialize, ToSchema)]
 #[schema(example = json!({
     "ssid": "HomeNetwork",
     "password": "supersecret123",
@@ -185,6 +109,16 @@ pub struct WifiNetworkConfig {
     pub is_primary: bool,
 }
 
+impl std::fmt::Debug for WifiNetworkConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WifiNetworkConfig")
+            .field("ssid", &self.ssid)
+            .field("password", &"[REDACTED]")
+            .field("is_primary", &self.is_primary)
+            .finish()
+    }
+}
+
 /// Request to update WiFi networks.
 #[derive(Debug, Clone, Deserialize, ToSchema)]
 #[schema(example = json!({
@@ -230,6 +164,68 @@ fn is_bluetooth_configured(address: &str) -> bool {
     address != "00:00:00:00:00:00"
 }
 
+/// Lists the configured WiFi networks without their passwords.
+fn wifi_networks_response(config: &tether_core::Config) -> Vec<WifiNetworkResponse> {
+    config
+        .wifi
+        .networks
+        .iter()
+        .map(wifi_network_response)
+        .collect()
+}
+
+/// Converts the Bluetooth configuration to its API representation.
+pub fn bluetooth_config_response(config: &tether_core::BluetoothConfig) -> BluetoothConfigResponse {
+    BluetoothConfigResponse {
+        target_address: config.target_address.clone(),
+        target_name: config.target_name.clone(),
+        rssi_threshold: config.rssi_threshold,
+        probe_mode: probe_mode_response(config.probe_mode),
+        adapters: config.adapters.clone(),
+        rssi_fusion: rssi_fusion_response(config.rssi_fusion),
+        is_configured: is_bluetooth_configured(&config.target_address),
+        is_paired: config.target_irk.is_some(),
+    }
+}
+
+/// Converts a probe mode to its API representation.
+const fn probe_mode_response(mode: ProbeMode) -> tether_client::types::ProbeMode {
+    match mode {
+        ProbeMode::Passive => tether_client::types::ProbeMode::Passive,
+        ProbeMode::Active => tether_client::types::ProbeMode::Active,
+        ProbeMode::PassiveThenActive => tether_client::types::ProbeMode::PassiveThenActive,
+    }
+}
+
+/// Converts an RSSI fusion mode to its API representation.
+const fn rssi_fusion_response(fusion: RssiFusion) -> tether_client::types::RssiFusion {
+    match fusion {
+        RssiFusion::Strongest => tether_client::types::RssiFusion::Strongest,
+        RssiFusion::Average => tether_client::types::RssiFusion::Average,
+    }
+}
+
+/// Converts a WiFi network to its API representation, without its password.
+fn wifi_network_response(network: &tether_core::WifiNetwork) -> WifiNetworkResponse {
+    WifiNetworkResponse {
+        ssid: network.ssid.clone(),
+        is_primary: network.primary,
+        has_password: network.password_id.is_some() || network.password.is_some(),
+    }
+}
+
+/// Converts the curfew configuration to its API representation.
+///
+/// `timezone` is the configured timezone the curfew times are in.
+pub fn curfew_response(curfew: &tether_core::CurfewConfig, timezone: &str) -> CurfewResponse {
+    CurfewResponse {
+        enabled: curfew.enabled,
+        start: curfew.start.clone(),
+        end: curfew.end.clone(),
+        is_active: curfew.is_active_at(chrono::Utc::now(), timezone),
+    }
+}
+
 /// Get current configuration.
 #[utoipa::path(
     get,
@@ -244,21 +240,15 @@ fn is_bluetooth_configured(address: &str) -> bool {
     )
 )]
 pub async fn get_config(State(state): State<SharedState>) -> ApiResult<Json<ConfigResponse>> {
-    let state_guard = state.read().await;
-    let config = &state_guard.config;
-
-    let bluetooth = BluetoothConfigResponse {
-        target_address: config.bluetooth.target_address.clone(),
-        target_name: config.bluetooth.target_name.clone(),
-        rssi_threshold: config.bluetooth.rssi_threshold,
-        is_configured: is_bluetooth_configured(&config.bluetooth.target_address),
-    };
+    let config = state.config.read().await;
 
     Ok(Json(ConfigResponse {
-        bluetooth,
+        bluetooth: bluetooth_config_response(&config.bluetooth),
+        wifi_networks: wifi_networks_response(&config),
         timezone: config.system.timezone.clone(),
         passes_per_month: config.passes.per_month,
         onboarding_complete: config.system.onboarding_complete,
+        curfew: curfew_response(&config.curfew, &config.system.timezone),
     }))
 }
 
@@ -269,17 +259,20 @@ pub async fn get_config(State(state): State<SharedState>) -> ApiResult<Json<Conf
     tag = "config",
     operation_id = "updateBluetooth",
     summary = "Update Bluetooth target device",
-    description = "Updates the Bluetooth device to track for proximity detection.",
+    description = "Updates the Bluetooth device to track for proximity detection. \
+        With guarded settings enabled, the change waits for approval instead.",
     request_body = UpdateBluetoothRequest,
     responses(
         (status = 200, description = "Bluetooth configuration updated", body = UpdateBluetoothResponse),
-        (status = 400, description = "Invalid Bluetooth address format")
+        (status = 202, description = "Change is waiting for approval", body = PendingChangeResponse),
+        (status = 400, description = "Invalid Bluetooth address format or unknown adapter")
     )
 )]
 pub async fn update_bluetooth(
     State(state): State<SharedState>,
+    actor: Actor,
     Json(request): Json<UpdateBluetoothRequest>,
-) -> ApiResult<Json<UpdateBluetoothResponse>> {
+) -> ApiResult<Guarded<UpdateBluetoothResponse>> {
     // Validate Bluetooth address format
     if !tether_core::is_valid_mac_address(&request.target_address) {
         return Err(ApiError::BadRequest {
@@ -298,35 +291,114 @@ pub async fn update_bluetooth(
         }
     }
 
-    let mut state_guard = state.write().await;
-
-    // Update config
-    state_guard.config.bluetooth.target_address = request.target_address.to_uppercase();
-    state_guard.config.bluetooth.target_name = request.target_name.clone();
-    if let Some(threshold) = request.rssi_threshold {
-        state_guard.config.bluetooth.rssi_threshold = threshold;
+    if let Some(adapters) = &request.adapters {
+        let mut candidate = state.config.read().await.bluetooth.clone();
+        candidate.adapters.clone_from(adapters);
+        if let Some(error) = candidate.validate().into_iter().next() {
+            return Err(ApiError::BadRequest {
+                error_code: "invalid_adapter".to_string(),
+                message: error.to_string(),
+            });
+        }
     }
 
-    // Save config
-    state_guard.save_config().map_err(|e| ApiError::InternalError {
-        error_code: "config_save_failed".to_string(),
-        message: "Failed to save configuration".to_string(),
-        details: Some(e.to_string()),
-    })?;
-
-    let bluetooth = BluetoothConfigResponse {
-        target_address: state_guard.config.bluetooth.target_address.clone(),
-        target_name: state_guard.config.bluetooth.target_name.clone(),
-        rssi_threshold: state_guard.config.bluetooth.rssi_threshold,
-        is_configured: is_bluetooth_configured(&state_guard.config.bluetooth.target_address),
-    };
+    if guard::is_active(&state).await {
+        let pending = guard::defer(&state, &actor, GuardedChange::UpdateBluetooth(request)).await?;
+        return Ok(Guarded::Pending(pending.into()));
+    }
 
-    Ok(Json(UpdateBluetoothResponse {
+    let bluetooth = apply_bluetooth(&state, &actor, &request).await?;
+    Ok(Guarded::Applied(UpdateBluetoothResponse {
         success: true,
         bluetooth,
     }))
 }
 
+/// Applies a validated Bluetooth update and returns the new configuration.
+pub(crate) async fn apply_bluetooth(
+    state: &AppState,
+    actor: &Actor,
+    request: &UpdateBluetoothRequest,
+) -> ApiResult<BluetoothConfigResponse> {
+    // Set up the new scanner first so an unknown adapter leaves config
+    // untouched, and without the config lock so requests aren't held up
+    let current_adapters = state.config.read().await.bluetooth.adapters.clone();
+    let mut scanner = None;
+    if let Some(adapters) = &request.adapters {
+        if *adapters != current_adapters && state.scanner().await.is_some() {
+            scanner = Some(
+                tether_core::BluetoothScanner::with_adapters(adapters)
+                    .await
+                    .map_err(|e| ApiError::BadRequest {
+                        error_code: "invalid_adapter".to_string(),
+                        message: format!("Cannot use adapters {adapters:?}: {e}"),
+                    })?,
+            );
+        }
+    }
+
+    let mut config = state.config.write().await;
+    let before = snapshot(&bluetooth_config_response(&config.bluetooth));
+
+    // Switched under the lock, so the scanner matches the saved adapters
+    if let Some(scanner) = scanner {
+        state.set_scanner(Some(scanner)).await;
+    }
+
+    merge_bluetooth(&mut config.bluetooth, request);
+
+    // Save config
+    state
+        .save_config(&config)
+        .map_err(|e| ApiError::InternalError {
+            error_code: "config_save_failed".to_string(),
+            message: "Failed to save configuration".to_string(),
+            details: Some(e.to_string()),
+        })?;
+
+    state.events.publish(ServerEvent::ConfigChanged {
+        section: ConfigSection::Bluetooth,
+    });
+
+    let bluetooth = bluetooth_config_response(&config.bluetooth);
+    state
+        .record_audit(
+            actor,
+            AuditAction::UpdateBluetooth,
+            before,
+            snapshot(&bluetooth),
+        )
+        .await;
+
+    Ok(bluetooth)
+}
+
+/// Applies a Bluetooth update to `bluetooth`.
+pub(crate) fn merge_bluetooth(
+    bluetooth: &mut tether_core::BluetoothConfig,
+    request: &UpdateBluetoothRequest,
+) {
+    // A key obtained by pairing belongs to the previous device
+    let target_address = request.target_address.to_uppercase();
+    if bluetooth.target_address != target_address {
+        bluetooth.target_irk = None;
+    }
+    bluetooth.target_address = target_address;
+    bluetooth.target_name.clone_from(&request.target_name);
+    if let Some(threshold) = request.rssi_threshold {
+        bluetooth.rssi_threshold = threshold;
+    }
+    if let Some(probe_mode) = request.probe_mode {
+        bluetooth.probe_mode = probe_mode;
+    }
+    if let Some(adapters) = &request.adapters {
+        bluetooth.adapters.clone_from(adapters);
+    }
+    if let Some(rssi_fusion) = request.rssi_fusion {
+        bluetooth.rssi_fusion = rssi_fusion;
+    }
+}
+
 /// Update WiFi networks.
 #[utoipa::path(
     put,
@@ -336,7 +408,7 @@ pub async fn update_bluetooth(
     summary = "Update WiFi networks",
     description = "Updates the list of WiFi networks. Each network needs an SSID, \
         password, and whether it's the primary network. Exactly one network should \
-        be marked as primary.",
+        be marked as primary. Passwords are encrypted at rest and never returned.",
     request_body = UpdateWifiRequest,
     responses(
         (status = 200, description = "WiFi configuration updated", body = UpdateWifiResponse),
@@ -345,6 +417,7 @@ pub async fn update_bluetooth(
 )]
 pub async fn update_wifi(
     State(state): State<SharedState>,
+    actor: Actor,
     Json(request): Json<UpdateWifiRequest>,
 ) -> ApiResult<Json<UpdateWifiResponse>> {
     // Validate: at least one network required
@@ -381,10 +454,7 @@ pub async fn update_wifi(
         if network.ssid.len() > 32 {
             return Err(ApiError::BadRequest {
                 error_code: "ssid_too_long".to_string(),
-                message: format!(
-                    "Network '{}' SSID exceeds 32 character limit",
-                    network.ssid
-                ),
+                message: format!("Network '{}' SSID exceeds 32 character limit", network.ssid),
             });
         }
     }
@@ -400,7 +470,9 @@ pub async fn update_wifi(
         }
     }
 
-    let mut state_guard = state.write().await;
+    let mut current = state.config.write().await;
+    let mut secrets = state.secrets.lock().await;
+    let before = snapshot(&wifi_networks_response(&current));
 
     // Convert to tether-core WifiNetwork type
     let wifi_networks: Vec<tether_core::WifiNetwork> = request
@@ -416,15 +488,45 @@ pub async fn update_wifi(
         .find(|n| n.is_primary)
         .map(|n| n.ssid.clone());
 
-    // Update config
-    state_guard.config.wifi.networks = wifi_networks;
+    // Move the passwords into the secrets store, and save it before the
+    // config so the config never refers to a secret that is not on disk
+    let mut config = current.clone();
+    config.wifi.networks = wifi_networks;
+    let secrets_error = |e: tether_core::SecretError| ApiError::InternalError {
+        error_code: "secrets_save_failed".to_string(),
+        message: "Failed to store WiFi passwords".to_string(),
+        details: Some(e.to_string()),
+    };
+    config.store_secrets(&mut secrets).map_err(secrets_error)?;
+    secrets.save().map_err(secrets_error)?;
 
     // Save config
-    state_guard.save_config().map_err(|e| ApiError::InternalError {
-        error_code: "config_save_failed".to_string(),
-        message: "Failed to save configuration".to_string(),
-        details: Some(e.to_string()),
-    })?;
+    state
+        .save_config(&config)
+        .map_err(|e| ApiError::InternalError {
+            error_code: "config_save_failed".to_string(),
+            message: "Failed to save configuration".to_string(),
+            details: Some(e.to_string()),
+        })?;
+    *current = config;
+
+    // Let the network watchdog connect without reading the secrets store
+    tokio::spawn(crate::wifi::sync(crate::wifi::profiles(&current, &secrets)));
+
+    // Drop the passwords of networks that were removed or replaced
+    current.prune_secrets(&mut secrets);
+    if let Err(e) = secrets.save() {
+        tracing::warn!(error = %e, "Failed to prune unused secrets");
+    }
+
+    state.events.publish(ServerEvent::ConfigChanged {
+        section: ConfigSection::Wifi,
+    });
+
+    let after = snapshot(&wifi_networks_response(&current));
+    state
+        .record_audit(&actor, AuditAction::UpdateWifi, before, after)
+        .await;
 
     Ok(Json(UpdateWifiResponse {
         success: true,
@@ -440,17 +542,21 @@ pub async fn update_wifi(
     tag = "config",
     operation_id = "updateTimezone",
     summary = "Update timezone",
-    description = "Updates the timezone used for pass reset calculations.",
+    description = "Updates the timezone used for pass reset calculations and the \
+        curfew. With guarded settings enabled, the change waits for approval \
+        first.",
     request_body = UpdateTimezoneRequest,
     responses(
         (status = 200, description = "Timezone updated", body = UpdateTimezoneResponse),
+        (status = 202, description = "Change is waiting for approval", body = PendingChangeResponse),
         (status = 400, description = "Invalid timezone")
     )
 )]
 pub async fn update_timezone(
     State(state): State<SharedState>,
+    actor: Actor,
     Json(request): Json<UpdateTimezoneRequest>,
-) -> ApiResult<Json<UpdateTimezoneResponse>> {
+) -> ApiResult<Guarded<UpdateTimezoneResponse>> {
     // Validate timezone
     if !tether_core::is_valid_timezone_format(&request.timezone) {
         return Err(ApiError::BadRequest {
@@ -462,20 +568,135 @@ pub async fn update_timezone(
         });
     }
 
-    let mut state_guard = state.write().await;
+    let timezone = request.timezone;
+    if guard::is_active(&state).await {
+        let change = GuardedChange::UpdateTimezone { timezone };
+        let pending = guard::defer(&state, &actor, change).await?;
+        return Ok(Guarded::Pending(pending.into()));
+    }
 
-    state_guard.config.system.timezone = request.timezone.clone();
+    apply_timezone(&state, &actor, timezone)
+        .await
+        .map(Guarded::Applied)
+}
 
-    state_guard.save_config().map_err(|e| ApiError::InternalError {
-        error_code: "config_save_failed".to_string(),
-        message: "Failed to save configuration".to_string(),
-        details: Some(e.to_string()),
-    })?;
+/// Applies a validated timezone update.
+pub(crate) async fn apply_timezone(
+    state: &AppState,
+    actor: &Actor,
+    timezone: String,
+) -> ApiResult<UpdateTimezoneResponse> {
+    let mut config = state.config.write().await;
+    let before = json!({ "timezone": config.system.timezone });
+
+    config.system.timezone.clone_from(&timezone);
+
+    state
+        .save_config(&config)
+        .map_err(|e| ApiError::InternalError {
+            error_code: "config_save_failed".to_string(),
+            message: "Failed to save configuration".to_string(),
+            details: Some(e.to_string()),
+        })?;
+
+    state.events.publish(ServerEvent::ConfigChanged {
+        section: ConfigSection::Timezone,
+    });
+
+    let after = json!({ "timezone": timezone });
+    state
+        .record_audit(
+            actor,
+            AuditAction::UpdateTimezone,
+            Some(before),
+            Some(after),
+        )
+        .await;
+
+    Ok(UpdateTimezoneResponse {
+        success: true,
+        timezone,
+    })
+}
+
+/// Update the nightly curfew.
+#[utoipa::path(
+    put,
+    path = "/config/curfew",
+    tag = "config",
+    operation_id = "updateCurfew",
+    summary = "Update curfew",
+    description = "Sets the nightly curfew, when the phone should be out of the \
+        bedroom. Times are `HH:MM` in the configured timezone, and the curfew \
+        may span midnight. With guarded settings enabled, the change waits \
+        for approval first.",
+    request_body = UpdateCurfewRequest,
+    responses(
+        (status = 200, description = "Curfew updated", body = UpdateCurfewResponse),
+        (status = 202, description = "Change is waiting for approval", body = PendingChangeResponse),
+        (status = 400, description = "Invalid time")
+    )
+)]
+pub async fn update_curfew(
+    State(state): State<SharedState>,
+    actor: Actor,
+    Json(request): Json<UpdateCurfewRequest>,
+) -> ApiResult<Guarded<UpdateCurfewResponse>> {
+    let curfew = tether_core::CurfewConfig {
+        enabled: request.enabled,
+        start: request.start,
+        end: request.end,
+    };
+    if let Some(error) = curfew.validate().into_iter().next() {
+        return Err(ApiError::BadRequest {
+            error_code: "invalid_curfew".to_string(),
+            message: error.to_string(),
+        });
+    }
+
+    if guard::is_active(&state).await {
+        let change = GuardedChange::UpdateCurfew(curfew);
+        let pending = guard::defer(&state, &actor, change).await?;
+        return Ok(Guarded::Pending(pending.into()));
+    }
 
-    Ok(Json(UpdateTimezoneResponse {
+    apply_curfew(&state, &actor, curfew)
+        .await
+        .map(Guarded::Applied)
+}
+
+/// Applies a validated curfew update.
+pub(crate) async fn apply_curfew(
+    state: &AppState,
+    actor: &Actor,
+    curfew: tether_core::CurfewConfig,
+) -> ApiResult<UpdateCurfewResponse> {
+    let mut config = state.config.write().await;
+    let before = snapshot(&curfew_response(&config.curfew, &config.system.timezone));
+
+    config.curfew = curfew;
+
+    state
+        .save_config(&config)
+        .map_err(|e| ApiError::InternalError {
+            error_code: "config_save_failed".to_string(),
+            message: "Failed to save configuration".to_string(),
+            details: Some(e.to_string()),
+        })?;
+
+    state.events.publish(ServerEvent::ConfigChanged {
+        section: ConfigSection::Curfew,
+    });
+
+    let curfew = curfew_response(&config.curfew, &config.system.timezone);
+    state
+        .record_audit(actor, AuditAction::UpdateCurfew, before, snapshot(&curfew))
+        .await;
+
+    Ok(UpdateCurfewResponse {
         success: true,
-        timezone: request.timezone,
-    }))
+        curfew,
+    })
 }
 
 /// Update passes per month.
@@ -487,17 +708,20 @@ pub async fn update_timezone(
     summary = "Update passes per month",
     description = "Updates the number of emergency passes allowed per month. \
         If passes have already been used this month, the change will take \
-        effect next month.",
+        effect next month. With guarded settings enabled, the change waits \
+        for approval first.",
     request_body = UpdatePassesPerMonthRequest,
     responses(
         (status = 200, description = "Passes per month updated", body = UpdatePassesPerMonthResponse),
+        (status = 202, description = "Change is waiting for approval", body = PendingChangeResponse),
         (status = 400, description = "Invalid value")
     )
 )]
 pub async fn update_passes_per_month(
     State(state): State<SharedState>,
+    actor: Actor,
     Json(request): Json<UpdatePassesPerMonthRequest>,
-) -> ApiResult<Json<UpdatePassesPerMonthResponse>> {
+) -> ApiResult<Guarded<UpdatePassesPerMonthResponse>> {
     // Validate range
     if request.per_month > 31 {
         return Err(ApiError::BadRequest {
@@ -506,27 +730,49 @@ pub async fn update_passes_per_month(
         });
     }
 
-    let mut state_guard = state.write().await;
+    let per_month = request.per_month;
+    if guard::is_active(&state).await {
+        let change = GuardedChange::UpdatePassesPerMonth { per_month };
+        let pending = guard::defer(&state, &actor, change).await?;
+        return Ok(Guarded::Pending(pending.into()));
+    }
+
+    apply_passes_per_month(&state, &actor, per_month)
+        .await
+        .map(Guarded::Applied)
+}
+
+/// Applies a validated passes per month update.
+pub(crate) async fn apply_passes_per_month(
+    state: &AppState,
+    actor: &Actor,
+    new_per_month: u8,
+) -> ApiResult<UpdatePassesPerMonthResponse> {
+    let mut config = state.config.write().await;
+    let mut pass_manager = state.pass_manager.write().await;
+    reset_month_if_needed(&state.events, &mut pass_manager)?;
 
     // Check if passes have been used this month
-    let remaining = state_guard.pass_manager.remaining();
-    let per_month = state_guard.pass_manager.per_month();
+    let remaining = pass_manager.remaining();
+    let per_month = pass_manager.per_month();
     let passes_used = per_month > remaining;
 
+    let before = json!({ "per_month": config.passes.per_month });
+
     // Update config
-    state_guard.config.passes.per_month = request.per_month;
+    config.passes.per_month = new_per_month;
 
     // Update pass manager - will be deferred if passes used (saved internally)
-    let pending = state_guard
-        .pass_manager
-        .set_per_month(request.per_month.into())?;
+    let pending = pass_manager.set_per_month(new_per_month.into())?;
 
     // Save config
-    state_guard.save_config().map_err(|e| ApiError::InternalError {
-        error_code: "config_save_failed".to_string(),
-        message: "Failed to save configuration".to_string(),
-        details: Some(e.to_string()),
-    })?;
+    state
+        .save_config(&config)
+        .map_err(|e| ApiError::InternalError {
+            error_code: "config_save_failed".to_string(),
+            message: "Failed to save configuration".to_string(),
+            details: Some(e.to_string()),
+        })?;
 
     let message = if pending {
         "Change will take effect on the first of next month".to_string()
@@ -534,12 +780,27 @@ pub async fn update_passes_per_month(
         "Passes per month updated immediately".to_string()
     };
 
-    Ok(Json(UpdatePassesPerMonthResponse {
+    state.events.publish(ServerEvent::ConfigChanged {
+        section: ConfigSection::Passes,
+    });
+
+    let pending = pending && passes_used;
+    let after = json!({ "per_month": new_per_month, "pending": pending });
+    state
+        .record_audit(
+            actor,
+            AuditAction::UpdatePassesPerMonth,
+            Some(before),
+            Some(after),
+        )
+        .await;
+
+    Ok(UpdatePassesPerMonthResponse {
         success: true,
-        per_month: request.per_month,
-        pending: pending && passes_used,
+        per_month: new_per_month,
+        pending,
         message,
-    }))
+    })
 }
 
 /// Complete onboarding.
@@ -559,11 +820,12 @@ pub async fn update_passes_per_month(
 )]
 pub async fn complete_onboarding(
     State(state): State<SharedState>,
+    actor: Actor,
 ) -> ApiResult<Json<CompleteOnboardingResponse>> {
-    let mut state_guard = state.write().await;
+    let mut config = state.config.write().await;
 
     // Check if already complete
-    if state_guard.config.system.onboarding_complete {
+    if config.system.onboarding_complete {
         return Err(ApiError::BadRequest {
             error_code: "already_complete".to_string(),
             message: "Onboarding has already been completed".to_string(),
@@ -571,7 +833,7 @@ pub async fn complete_onboarding(
     }
 
     // Check prerequisites - Bluetooth must be configured (not placeholder)
-    if !is_bluetooth_configured(&state_guard.config.bluetooth.target_address) {
+    if !is_bluetooth_configured(&config.bluetooth.target_address) {
         return Err(ApiError::FailedDependency {
             error_code: "prerequisites_not_met".to_string(),
             message: "Cannot complete onboarding: Bluetooth device not configured".to_string(),
@@ -580,13 +842,28 @@ pub async fn complete_onboarding(
     }
 
     // Mark as complete
-    state_guard.config.system.onboarding_complete = true;
-
-    state_guard.save_config().map_err(|e| ApiError::InternalError {
-        error_code: "config_save_failed".to_string(),
-        message: "Failed to save configuration".to_string(),
-        details: Some(e.to_string()),
-    })?;
+    config.system.onboarding_complete = true;
+
+    state
+        .save_config(&config)
+        .map_err(|e| ApiError::InternalError {
+            error_code: "config_save_failed".to_string(),
+            message: "Failed to save configuration".to_string(),
+            details: Some(e.to_string()),
+        })?;
+
+    state.events.publish(ServerEvent::ConfigChanged {
+        section: ConfigSection::Onboarding,
+    });
+
+    state
+        .record_audit(
+            &actor,
+            AuditAction::CompleteOnboarding,
+            Some(json!({ "onboarding_complete": false })),
+            Some(json!({ "onboarding_complete": true })),
+        )
+        .await;
 
     Ok(Json(CompleteOnboardingResponse {
         success: true,
@@ -605,11 +882,21 @@ mod tests {
                 target_address: "AA:BB:CC:DD:EE:FF".to_string(),
                 target_name: "iPhone".to_string(),
                 rssi_threshold: -60,
+                probe_mode: tether_client::types::ProbeMode::Passive,
+                adapters: Vec::new(),
+                rssi_fusion: tether_client::types::RssiFusion::Strongest,
                 is_configured: true,
+                is_paired: false,
             },
+            wifi_networks: vec![WifiNetworkResponse {
+                ssid: "HomeNetwork".to_string(),
+                is_primary: true,
+                has_password: true,
+            }],
             timezone: "UTC".to_string(),
             passes_per_month: 3,
             onboarding_complete: false,
+            curfew: CurfewResponse::default(),
         };
         let json = serde_json::to_string(&response).unwrap();
         assert!(json.contains("AA:BB:CC:DD:EE:FF"));
@@ -620,6 +907,29 @@ mod tests {
         let json = r#"{"target_address": "AA:BB:CC:DD:EE:FF", "target_name": "iPhone"}"#;
         let request: UpdateBluetoothRequest = serde_json::from_str(json).unwrap();
         assert_eq!(request.target_address, "AA:BB:CC:DD:EE:FF");
+        assert!(request.probe_mode.is_none());
+
+        let json = r#"{"target_address": "AA:BB:CC:DD:EE:FF", "target_name": "iPhone", "probe_mode": "active"}"#;
+        let request: UpdateBluetoothRequest = serde_json::from_str(json).unwrap();
+        assert_eq!(request.probe_mode, Some(ProbeMode::Active));
+        assert!(request.adapters.is_none());
+
+        let json = r#"{"target_address": "AA:BB:CC:DD:EE:FF", "target_name": "iPhone", "adapters": ["hci0", "hci1"], "rssi_fusion": "average"}"#;
+        let request: UpdateBluetoothRequest = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            request.adapters,
+            Some(vec!["hci0".to_string(), "hci1".to_string()])
+        );
+        assert_eq!(request.rssi_fusion, Some(RssiFusion::Average));
+    }
+
+    #[test]
+    fn test_wifi_network_config_debug_redacts_password() {
+        let json = r#"{"ssid": "HomeNetwork", "password": "secret123", "is_primary": true}"#;
+        let network: WifiNetworkConfig = serde_json::from_str(json).unwrap();
+        let debug = format!("{network:?}");
+        assert!(debug.contains("HomeNetwork"));
+        assert!(!debug.contains("secret123"));
     }
 
     #[test]
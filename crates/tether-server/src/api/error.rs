@@ -6,8 +6,8 @@
 use axum::http::StatusCode;
 use axum::response::{IntoResponse, Response};
 use axum::Json;
-use serde::{Deserialize, Serialize};
-use utoipa::ToSchema;
+
+pub use tether_client::types::ErrorResponse;
 
 /// Result type alias for API handlers.
 pub type ApiResult<T> = Result<T, ApiError>;
@@ -26,6 +26,22 @@ pub enum ApiError {
         message: String,
     },
 
+    /// 401 Unauthorized - Missing or wrong credentials.
+    Unauthorized {
+        /// Machine-readable error code.
+        error_code: String,
+        /// Human-readable error message.
+        message: String,
+    },
+
+    /// 403 Forbidden - The request's scope doesn't allow it.
+    Forbidden {
+        /// Machine-readable error code.
+        error_code: String,
+        /// Human-readable error message.
+        message: String,
+    },
+
     /// 404 Not Found - Resource does not exist.
     NotFound {
         /// Machine-readable error code.
@@ -77,47 +93,60 @@ pub enum ApiError {
     },
 }
 
-/// Standard JSON error response body.
-#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
-#[schema(example = json!({
-    "error": "invalid_request",
-    "message": "The provided value is not valid",
-    "details": null
-}))]
-pub struct ErrorResponse {
-    /// Machine-readable error code (e.g., "invalid_bluetooth_address").
-    #[schema(example = "invalid_request")]
-    pub error: String,
-
-    /// Human-readable error message.
-    #[schema(example = "The provided value is not valid")]
-    pub message: String,
-
-    /// Optional additional details for debugging.
-    #[schema(nullable)]
-    pub details: Option<serde_json::Value>,
+impl ApiError {
+    /// Returns the machine-readable error code.
+    #[must_use]
+    pub fn error_code(&self) -> &str {
+        match self {
+            Self::BadRequest { error_code, .. }
+            | Self::Unauthorized { error_code, .. }
+            | Self::Forbidden { error_code, .. }
+            | Self::NotFound { error_code, .. }
+            | Self::Conflict { error_code, .. }
+            | Self::FailedDependency { error_code, .. }
+            | Self::InternalError { error_code, .. }
+            | Self::ServiceUnavailable { error_code, .. } => error_code,
+        }
+    }
+
+    /// Returns the human-readable error message.
+    #[must_use]
+    pub fn message(&self) -> &str {
+        match self {
+            Self::BadRequest { message, .. }
+            | Self::Unauthorized { message, .. }
+            | Self::Forbidden { message, .. }
+            | Self::NotFound { message, .. }
+            | Self::Conflict { message, .. }
+            | Self::FailedDependency { message, .. }
+            | Self::InternalError { message, .. }
+            | Self::ServiceUnavailable { message, .. } => message,
+        }
+    }
 }
 
 impl IntoResponse for ApiError {
     fn into_response(self) -> Response {
         let (status, error_response) = match self {
-            Self::BadRequest { error_code, message } => (
-                StatusCode::BAD_REQUEST,
-                ErrorResponse {
-                    error: error_code,
-                    message,
-                    details: None,
-                },
-            ),
+            Self::BadRequest {
+                error_code,
+                message,
+            } => plain(StatusCode::BAD_REQUEST, error_code, message),
 
-            Self::NotFound { error_code, message } => (
-                StatusCode::NOT_FOUND,
-                ErrorResponse {
-                    error: error_code,
-                    message,
-                    details: None,
-                },
-            ),
+            Self::Unauthorized {
+                error_code,
+                message,
+            } => plain(StatusCode::UNAUTHORIZED, error_code, message),
+
+            Self::Forbidden {
+                error_code,
+                message,
+            } => plain(StatusCode::FORBIDDEN, error_code, message),
+
+            Self::NotFound {
+                error_code,
+                message,
+            } => plain(StatusCode::NOT_FOUND, error_code, message),
 
             Self::Conflict {
                 error_code,
@@ -190,10 +219,28 @@ impl IntoResponse for ApiError {
     }
 }
 
+/// Returns the status and body of an error without details.
+const fn plain(
+    status: StatusCode,
+    error_code: String,
+    message: String,
+) -> (StatusCode, ErrorResponse) {
+    (
+        status,
+        ErrorResponse {
+            error: error_code,
+            message,
+            details: None,
+        },
+    )
+}
+
 impl std::fmt::Display for ApiError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Self::BadRequest { message, .. } => write!(f, "Bad Request: {message}"),
+            Self::Unauthorized { message, .. } => write!(f, "Unauthorized: {message}"),
+            Self::Forbidden { message, .. } => write!(f, "Forbidden: {message}"),
             Self::NotFound { message, .. } => write!(f, "Not Found: {message}"),
             Self::Conflict { message, .. } => write!(f, "Conflict: {message}"),
             Self::FailedDependency { message, .. } => {
@@ -223,12 +270,12 @@ impl From<tether_core::TetherError> for ApiError {
                 remaining: Some(0),
                 resets_at_utc: None,
             },
-            TetherError::EmptyPassReason | TetherError::PassReasonTooLong { .. } => {
-                Self::BadRequest {
-                    error_code: err.error_code().to_string(),
-                    message: err.to_string(),
-                }
-            }
+            TetherError::EmptyPassReason
+            | TetherError::PassReasonTooLong { .. }
+            | TetherError::InvalidIrk => Self::BadRequest {
+                error_code: err.error_code().to_string(),
+                message: err.to_string(),
+            },
             TetherError::InvalidMonthFormat(_) => Self::BadRequest {
                 error_code: "invalid_month_format".to_string(),
                 message: err.to_string(),
@@ -247,6 +294,11 @@ impl From<tether_core::TetherError> for ApiError {
                 message: err.to_string(),
                 details: None,
             },
+            TetherError::BluetoothPairingFailed(_) => Self::FailedDependency {
+                error_code: err.error_code().to_string(),
+                message: err.to_string(),
+                details: None,
+            },
             TetherError::DeviceNotFound(addr) => Self::NotFound {
                 error_code: "device_not_found".to_string(),
                 message: format!("Bluetooth device not found: {addr}"),
@@ -272,6 +324,46 @@ impl From<tether_core::ConfigError> for ApiError {
     }
 }
 
+impl From<crate::webhooks::WebhookError> for ApiError {
+    fn from(err: crate::webhooks::WebhookError) -> Self {
+        use crate::webhooks::WebhookError;
+
+        match err {
+            WebhookError::NotFound { id } => Self::NotFound {
+                error_code: "webhook_not_found".to_string(),
+                message: format!("No webhook with id '{id}'"),
+            },
+            err @ WebhookError::InvalidUrl { .. } => Self::BadRequest {
+                error_code: "invalid_webhook_url".to_string(),
+                message: err.to_string(),
+            },
+            err => Self::InternalError {
+                error_code: "webhook_save_failed".to_string(),
+                message: "Failed to save webhooks".to_string(),
+                details: Some(err.to_string()),
+            },
+        }
+    }
+}
+
+impl From<crate::guard::ChangeError> for ApiError {
+    fn from(err: crate::guard::ChangeError) -> Self {
+        use crate::guard::ChangeError;
+
+        match err {
+            ChangeError::NotFound { id } => Self::NotFound {
+                error_code: "change_not_found".to_string(),
+                message: format!("No pending change with id '{id}'"),
+            },
+            err => Self::InternalError {
+                error_code: "change_save_failed".to_string(),
+                message: "Failed to save pending changes".to_string(),
+                details: Some(err.to_string()),
+            },
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,194 @@
+//! Server event stream endpoint.
+//!
+//! Streams events from the [`EventBus`](crate::events::EventBus) as
+//! Server-Sent Events, so clients can react to changes instead of polling.
+
+use std::sync::Arc;
+
+use axum::extract::State;
+use axum::http::HeaderMap;
+use axum::response::sse::{Event, KeepAlive, Sse};
+use futures::{Stream, StreamExt};
+use tokio::sync::broadcast::error::RecvError;
+use tracing::debug;
+
+use crate::api::error::{ApiError, ApiResult};
+use crate::events::{EventMessage, Missed, Subscription};
+use crate::state::SharedState;
+
+/// Header sent by `EventSource` clients when they reconnect.
+const LAST_EVENT_ID: &str = "last-event-id";
+
+/// Stream server events.
+///
+/// Replays missed events when resuming with `Last-Event-ID`, then streams
+/// new events until the client disconnects.
+#[utoipa::path(
+    get,
+    path = "/events",
+    tag = "events",
+    operation_id = "streamEvents",
+    summary = "Stream server events",
+    description = "Streams server events as Server-Sent Events. Each event's SSE name is its \
+        `type` (`proximity_changed`, `pass_used`, `month_reset`, `config_changed` or \
+        `scanner_health_changed`) and its SSE id is the event id. When reconnecting with \
+        `Last-Event-ID`, the events published since that id are sent first. If some of them \
+        are no longer buffered, or the client fell behind, a `resync` event is sent instead: \
+        refetch any state derived from events, then keep listening.",
+    params(
+        ("Last-Event-ID" = Option<u64>, Header, description = "Id of the last event received")
+    ),
+    responses(
+        (status = 200, description = "Event stream", content_type = "text/event-stream", body = EventMessage),
+        (status = 400, description = "Invalid Last-Event-ID")
+    )
+)]
+pub async fn stream_events(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+) -> ApiResult<Sse<impl Stream<Item = Result<Event, axum::Error>>>> {
+    let last_event_id = parse_last_event_id(&headers)?;
+
+    let Subscription { missed, receiver } = state.events.subscribe(last_event_id);
+    let missed: Vec<_> = match missed {
+        Missed::Events(events) => events.iter().map(to_event).collect(),
+        Missed::Gap(latest) => vec![Ok(resync_event(latest))],
+    };
+
+    let live = futures::stream::unfold(receiver, |mut receiver| async move {
+        let event = match receiver.recv().await {
+            Ok(message) => to_event(&message),
+            Err(RecvError::Lagged(skipped)) => {
+                debug!(skipped, "Event stream client fell behind");
+                Ok(resync_event(None))
+            }
+            Err(RecvError::Closed) => return None,
+        };
+        Some((event, receiver))
+    });
+
+    Ok(Sse::new(futures::stream::iter(missed).chain(live)).keep_alive(KeepAlive::default()))
+}
+
+/// Parses the `Last-Event-ID` header, if present.
+fn parse_last_event_id(headers: &HeaderMap) -> ApiResult<Option<u64>> {
+    let Some(value) = headers.get(LAST_EVENT_ID) else {
+        return Ok(None);
+    };
+
+    value
+        .to_str()
+        .ok()
+        .and_then(|value| value.trim().parse().ok())
+        .map(Some)
+        .ok_or_else(|| ApiError::BadRequest {
+            error_code: "invalid_last_event_id".to_string(),
+            message: "Last-Event-ID must be an event id received from this endpoint".to_string(),
+        })
+}
+
+/// Converts a published event to an SSE event.
+fn to_event(message: &Arc<EventMessage>) -> Result<Event, axum::Error> {
+    Event::default()
+        .id(message.id.to_string())
+        .event(message.event.name())
+        .json_data(message.as_ref())
+}
+
+/// Creates a `resync` event, resuming from `latest` if known.
+fn resync_event(latest: Option<u64>) -> Event {
+    let event = Event::default().event("resync").data("{}");
+    match latest {
+        Some(id) => event.id(id.to_string()),
+        None => event,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::{ConfigSection, ServerEvent};
+    use crate::state::AppState;
+    use axum::http::HeaderValue;
+    use axum::response::IntoResponse;
+    use std::time::Duration;
+
+    fn config_changed(section: ConfigSection) -> ServerEvent {
+        ServerEvent::ConfigChanged { section }
+    }
+
+    /// Reads from an SSE body until `expected` appears.
+    async fn read_until(
+        body: &mut (impl Stream<Item = Result<axum::body::Bytes, axum::Error>> + Unpin),
+        expected: &str,
+    ) -> String {
+        let mut received = String::new();
+        while !received.contains(expected) {
+            let chunk = tokio::time::timeout(Duration::from_secs(1), body.next())
+                .await
+                .unwrap_or_else(|_| panic!("no {expected:?} in {received:?}"))
+                .unwrap()
+                .unwrap();
+            received.push_str(std::str::from_utf8(&chunk).unwrap());
+        }
+        received
+    }
+
+    async fn open_stream(
+        state: &SharedState,
+        last_event_id: Option<&str>,
+    ) -> impl Stream<Item = Result<axum::body::Bytes, axum::Error>> + Unpin {
+        let mut headers = HeaderMap::new();
+        if let Some(id) = last_event_id {
+            headers.insert(LAST_EVENT_ID, HeaderValue::from_str(id).unwrap());
+        }
+        stream_events(State(state.clone()), headers)
+            .await
+            .unwrap()
+            .into_response()
+            .into_body()
+            .into_data_stream()
+    }
+
+    #[tokio::test]
+    async fn test_stream_resumes_after_last_event_id() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = AppState::in_dir(dir.path(), None).into_shared();
+        let first = state.events.publish(config_changed(ConfigSection::Wifi));
+        let second = state
+            .events
+            .publish(config_changed(ConfigSection::Timezone));
+
+        let mut body = open_stream(&state, Some(&first.to_string())).await;
+        let received = read_until(&mut body, &format!("id: {second}")).await;
+        assert!(received.contains("event: config_changed"));
+        assert!(received.contains("\"section\":\"timezone\""));
+        assert!(!received.contains(&format!("id: {first}\n")));
+
+        // Events published after subscribing follow
+        let third = state.events.publish(config_changed(ConfigSection::Passes));
+        read_until(&mut body, &format!("id: {third}")).await;
+    }
+
+    #[tokio::test]
+    async fn test_stream_sends_resync_for_unknown_last_event_id() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = AppState::in_dir(dir.path(), None).into_shared();
+        let latest = state.events.publish(config_changed(ConfigSection::Wifi));
+
+        let mut body = open_stream(&state, Some("1")).await;
+        let received = read_until(&mut body, "event: resync").await;
+        assert!(received.contains(&format!("id: {latest}")));
+    }
+
+    #[tokio::test]
+    async fn test_stream_rejects_invalid_last_event_id() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = AppState::in_dir(dir.path(), None).into_shared();
+
+        let mut headers = HeaderMap::new();
+        headers.insert(LAST_EVENT_ID, HeaderValue::from_static("yesterday"));
+        let result = stream_events(State(state), headers).await;
+        assert!(matches!(result, Err(ApiError::BadRequest { .. })));
+    }
+}
@@ -0,0 +1,626 @@
+//! Guarded settings API endpoints.
+//!
+//! Lists changes to guarded settings waiting for approval, and lets the
+//! accountability partner approve or reject them. See [`crate::guard`] for
+//! which settings are guarded and when pending changes take effect.
+
+use axum::extract::{Path, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::api::error::{ApiError, ApiResult};
+use crate::audit::{bearer_token, Actor};
+use crate::guard::{self, ChangeResolution, PendingChange};
+use crate::state::{AppState, SharedState};
+
+/// Creates the guard router with all endpoints.
+pub fn router() -> Router<SharedState> {
+    Router::new()
+        .route("/", get(get_guard_status))
+        .route("/changes", get(list_pending_changes))
+        .route("/changes/{id}/approve", post(approve_change))
+        .route("/changes/{id}/reject", post(reject_change))
+        .route("/changes/{id}/cancel", post(cancel_change))
+}
+
+// ============================================================================
+// Request/Response Types
+// ============================================================================
+
+/// Response of an update to a guarded setting.
+///
+/// Sent as `200 OK` with the update's usual response when it was applied,
+/// or as `202 Accepted` with the pending change when it needs approval.
+#[derive(Debug)]
+pub enum Guarded<T> {
+    /// The update took effect.
+    Applied(T),
+    /// The update is waiting for approval.
+    Pending(PendingChangeResponse),
+}
+
+impl<T: Serialize> IntoResponse for Guarded<T> {
+    fn into_response(self) -> Response {
+        match self {
+            Self::Applied(response) => Json(response).into_response(),
+            Self::Pending(change) => (StatusCode::ACCEPTED, Json(change)).into_response(),
+        }
+    }
+}
+
+/// Whether guarded settings are enabled.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct GuardStatusResponse {
+    /// Whether guarded settings are enabled in the configuration.
+    #[schema(example = true)]
+    pub enabled: bool,
+
+    /// Whether changes currently need approval. Guarding only starts once
+    /// onboarding is complete.
+    #[schema(example = true)]
+    pub active: bool,
+
+    /// Hours after which a pending change takes effect without approval.
+    /// 0 if changes only take effect when approved.
+    #[schema(example = 24)]
+    pub cooling_off_hours: u32,
+
+    /// Whether a partner token is configured to approve changes.
+    #[schema(example = true)]
+    pub partner_token_configured: bool,
+
+    /// Number of changes waiting for approval.
+    #[schema(example = 1)]
+    pub pending_changes: usize,
+}
+
+pub use tether_client::types::PendingChangeResponse;
+
+impl From<PendingChange> for PendingChangeResponse {
+    fn from(change: PendingChange) -> Self {
+        Self {
+            id: change.id,
+            setting: change.change.setting(),
+            requested_by: change.requested_by,
+            requested_at_utc: change.requested_at.to_rfc3339(),
+            effective_at_utc: change.effective_at.map(|at| at.to_rfc3339()),
+            before: change.before,
+            after: change.after,
+        }
+    }
+}
+
+/// List of changes waiting for approval.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct PendingChangesResponse {
+    /// Pending changes, oldest first.
+    pub changes: Vec<PendingChangeResponse>,
+}
+
+/// Response after resolving a pending change.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ResolveChangeResponse {
+    /// Whether the change was resolved.
+    pub success: bool,
+
+    /// How the change was resolved.
+    pub resolution: ChangeResolution,
+
+    /// The resolved change.
+    pub change: PendingChangeResponse,
+}
+
+// ============================================================================
+// Handlers
+// ============================================================================
+
+/// Get guarded settings status.
+#[utoipa::path(
+    get,
+    path = "/guard",
+    tag = "guard",
+    operation_id = "getGuardStatus",
+    summary = "Get guarded settings status",
+    description = "Returns whether changes to the tracked Bluetooth device, to passes \
+        per month, to the curfew, to the timezone and to existing webhooks need an \
+        accountability partner's approval. Guarded settings are configured in the `[guard]` \
+        section of the config file.",
+    responses(
+        (status = 200, description = "Status retrieved", body = GuardStatusResponse)
+    )
+)]
+pub async fn get_guard_status(
+    State(state): State<SharedState>,
+) -> ApiResult<Json<GuardStatusResponse>> {
+    let active = guard::is_active(&state).await;
+    let config = state.config.read().await;
+    let pending_changes = state.changes.lock().await.changes().len();
+
+    Ok(Json(GuardStatusResponse {
+        enabled: config.guard.enabled,
+        active,
+        cooling_off_hours: config.guard.cooling_off_hours,
+        partner_token_configured: config.guard.has_partner_token(),
+        pending_changes,
+    }))
+}
+
+/// List pending changes.
+#[utoipa::path(
+    get,
+    path = "/guard/changes",
+    tag = "guard",
+    operation_id = "listPendingChanges",
+    summary = "List pending changes",
+    description = "Returns changes to guarded settings that are waiting for approval or for \
+        their cooling-off period to pass.",
+    responses(
+        (status = 200, description = "Pending changes retrieved", body = PendingChangesResponse)
+    )
+)]
+pub async fn list_pending_changes(
+    State(state): State<SharedState>,
+) -> ApiResult<Json<PendingChangesResponse>> {
+    let changes = state
+        .changes
+        .lock()
+        .await
+        .changes()
+        .iter()
+        .cloned()
+        .map(PendingChangeResponse::from)
+        .collect();
+    Ok(Json(PendingChangesResponse { changes }))
+}
+
+/// Approve a pending change.
+#[utoipa::path(
+    post,
+    path = "/guard/changes/{id}/approve",
+    tag = "guard",
+    operation_id = "approveChange",
+    summary = "Approve a pending change",
+    description = "Applies a pending change right away. Requires the partner token as \
+        `Authorization: Bearer <token>`.",
+    params(("id" = String, Path, description = "Pending change id")),
+    responses(
+        (status = 200, description = "Change approved and applied", body = ResolveChangeResponse),
+        (status = 401, description = "Missing or wrong partner token"),
+        (status = 404, description = "Pending change not found"),
+        (status = 424, description = "No partner token is configured")
+    )
+)]
+pub async fn approve_change(
+    State(state): State<SharedState>,
+    actor: Actor,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+) -> ApiResult<Json<ResolveChangeResponse>> {
+    require_partner(&state, &headers).await?;
+    resolve(&state, &actor, &id, ChangeResolution::Approved).await
+}
+
+/// Reject a pending change.
+#[utoipa::path(
+    post,
+    path = "/guard/changes/{id}/reject",
+    tag = "guard",
+    operation_id = "rejectChange",
+    summary = "Reject a pending change",
+    description = "Discards a pending change. Requires the partner token as \
+        `Authorization: Bearer <token>`.",
+    params(("id" = String, Path, description = "Pending change id")),
+    responses(
+        (status = 200, description = "Change rejected", body = ResolveChangeResponse),
+        (status = 401, description = "Missing or wrong partner token"),
+        (status = 404, description = "Pending change not found"),
+        (status = 424, description = "No partner token is configured")
+    )
+)]
+pub async fn reject_change(
+    State(state): State<SharedState>,
+    actor: Actor,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+) -> ApiResult<Json<ResolveChangeResponse>> {
+    require_partner(&state, &headers).await?;
+    resolve(&state, &actor, &id, ChangeResolution::Rejected).await
+}
+
+/// Cancel a pending change.
+#[utoipa::path(
+    post,
+    path = "/guard/changes/{id}/cancel",
+    tag = "guard",
+    operation_id = "cancelChange",
+    summary = "Cancel a pending change",
+    description = "Withdraws a pending change. Requires the partner token as \
+        `Authorization: Bearer <token>`, so that a change the partner asked for can't be \
+        withdrawn behind their back.",
+    params(("id" = String, Path, description = "Pending change id")),
+    responses(
+        (status = 200, description = "Change cancelled", body = ResolveChangeResponse),
+        (status = 401, description = "Missing or wrong partner token"),
+        (status = 404, description = "Pending change not found"),
+        (status = 424, description = "No partner token is configured")
+    )
+)]
+pub async fn cancel_change(
+    State(state): State<SharedState>,
+    actor: Actor,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+) -> ApiResult<Json<ResolveChangeResponse>> {
+    require_partner(&state, &headers).await?;
+    resolve(&state, &actor, &id, ChangeResolution::Cancelled).await
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+async fn resolve(
+    state: &AppState,
+    actor: &Actor,
+    id: &str,
+    resolution: ChangeResolution,
+) -> ApiResult<Json<ResolveChangeResponse>> {
+    let change = guard::resolve(state, actor, id, resolution).await?;
+    Ok(Json(ResolveChangeResponse {
+        success: true,
+        resolution,
+        change: change.into(),
+    }))
+}
+
+/// Checks that the request carries the partner token.
+async fn require_partner(state: &AppState, headers: &HeaderMap) -> ApiResult<()> {
+    let expected = guard::partner_token(state)
+        .await
+        .map_err(|e| ApiError::InternalError {
+            error_code: "secret_read_failed".to_string(),
+            message: "Failed to read the partner token".to_string(),
+            details: Some(e.to_string()),
+        })?;
+    let Some(expected) = expected else {
+        return Err(ApiError::FailedDependency {
+            error_code: "partner_token_not_configured".to_string(),
+            message: "Set guard.partner_token in the config file to approve changes".to_string(),
+            details: None,
+        });
+    };
+
+    let given = bearer_token(headers).unwrap_or_default();
+    if guard::is_partner_token(given, &expected) {
+        Ok(())
+    } else {
+        Err(ApiError::Unauthorized {
+            error_code: "invalid_partner_token".to_string(),
+            message: "Resolving a change needs the partner token".to_string(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::config::{
+        update_bluetooth, update_curfew, update_passes_per_month, update_timezone,
+        UpdateBluetoothRequest, UpdateCurfewRequest, UpdatePassesPerMonthRequest,
+        UpdateTimezoneRequest,
+    };
+    use crate::api::webhooks::{
+        create_webhook, delete_webhook, update_webhook, CreateWebhookRequest, UpdateWebhookRequest,
+    };
+    use crate::audit::AuditAction;
+    use axum::http::{header, HeaderValue};
+    use chrono::Utc;
+    use tether_core::SecretString;
+
+    const PARTNER_TOKEN: &str = "ask-before-you-loosen";
+
+    /// Creates state with guarded settings enabled.
+    async fn guarded_state(dir: &std::path::Path) -> SharedState {
+        let state = AppState::in_dir(dir, None).into_shared();
+        let token_id = state
+            .secrets
+            .lock()
+            .await
+            .insert(&SecretString::new(PARTNER_TOKEN))
+            .unwrap();
+        let mut config = state.config.write().await;
+        config.guard.enabled = true;
+        config.guard.partner_token_id = Some(token_id);
+        config.system.onboarding_complete = true;
+        drop(config);
+        state
+    }
+
+    fn partner(token: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        let value = HeaderValue::from_str(&format!("Bearer {token}")).unwrap();
+        headers.insert(header::AUTHORIZATION, value);
+        headers
+    }
+
+    async fn request_passes(state: &SharedState, per_month: u8) -> PendingChangeResponse {
+        let request = Json(UpdatePassesPerMonthRequest { per_month });
+        match update_passes_per_month(State(state.clone()), Actor::new("tester"), request).await {
+            Ok(Guarded::Pending(change)) => change,
+            other => panic!("expected a pending change, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_guarded_change_waits_for_partner_approval() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = guarded_state(dir.path()).await;
+
+        let change = request_passes(&state, 10).await;
+        assert_eq!(change.setting, guard::GuardedSetting::PassesPerMonth);
+        assert_eq!(change.after["per_month"], 10);
+        assert!(change.effective_at_utc.is_some());
+        assert_eq!(state.config.read().await.passes.per_month, 3);
+
+        let err = approve_change(
+            State(state.clone()),
+            Actor::new("partner"),
+            partner("not-the-partner-token"),
+            Path(change.id.clone()),
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(err, ApiError::Unauthorized { .. }));
+        assert_eq!(state.config.read().await.passes.per_month, 3);
+
+        let Json(response) = approve_change(
+            State(state.clone()),
+            Actor::new("partner"),
+            partner(PARTNER_TOKEN),
+            Path(change.id.clone()),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.resolution, ChangeResolution::Approved);
+        assert_eq!(state.config.read().await.passes.per_month, 10);
+        assert!(state.changes.lock().await.changes().is_empty());
+
+        let audit = state.audit.lock().await;
+        let actions: Vec<_> = audit.read_all().iter().map(|e| e.action).collect();
+        assert_eq!(
+            actions,
+            [
+                AuditAction::RequestChange,
+                AuditAction::UpdatePassesPerMonth,
+                AuditAction::ApproveChange
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_rejected_and_cancelled_changes_are_discarded() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = guarded_state(dir.path()).await;
+
+        let rejected = request_passes(&state, 10).await;
+        let cancelled = request_passes(&state, 20).await;
+        assert_eq!(state.changes.lock().await.changes().len(), 2);
+
+        let Json(response) = reject_change(
+            State(state.clone()),
+            Actor::new("partner"),
+            partner(PARTNER_TOKEN),
+            Path(rejected.id),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.resolution, ChangeResolution::Rejected);
+
+        let err = cancel_change(
+            State(state.clone()),
+            Actor::new("tester"),
+            HeaderMap::new(),
+            Path(cancelled.id.clone()),
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(err, ApiError::Unauthorized { .. }));
+
+        let Json(response) = cancel_change(
+            State(state.clone()),
+            Actor::new("partner"),
+            partner(PARTNER_TOKEN),
+            Path(cancelled.id.clone()),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.resolution, ChangeResolution::Cancelled);
+
+        let err = cancel_change(
+            State(state.clone()),
+            Actor::new("partner"),
+            partner(PARTNER_TOKEN),
+            Path(cancelled.id),
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(err, ApiError::NotFound { .. }));
+        assert!(state.changes.lock().await.changes().is_empty());
+        assert_eq!(state.config.read().await.passes.per_month, 3);
+    }
+
+    #[tokio::test]
+    async fn test_change_applies_after_cooling_off_period() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = guarded_state(dir.path()).await;
+
+        let request = Json(UpdateBluetoothRequest {
+            target_address: "aa:bb:cc:dd:ee:ff".to_string(),
+            target_name: "Spare phone".to_string(),
+            rssi_threshold: Some(-95),
+            probe_mode: None,
+            adapters: None,
+            rssi_fusion: None,
+        });
+        let change =
+            match update_bluetooth(State(state.clone()), Actor::new("tester"), request).await {
+                Ok(Guarded::Pending(change)) => change,
+                other => panic!("expected a pending change, got {other:?}"),
+            };
+        assert_eq!(change.after["rssi_threshold"], -95);
+
+        // Not yet due
+        guard::apply_due(&state, Utc::now()).await;
+        assert_eq!(state.config.read().await.bluetooth.rssi_threshold, -60);
+
+        guard::apply_due(&state, Utc::now() + chrono::Duration::hours(25)).await;
+        let config = state.config.read().await;
+        assert_eq!(config.bluetooth.rssi_threshold, -95);
+        assert_eq!(config.bluetooth.target_address, "AA:BB:CC:DD:EE:FF");
+        drop(config);
+        assert!(state.changes.lock().await.changes().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_curfew_change_is_guarded() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = guarded_state(dir.path()).await;
+        state.config.write().await.curfew.enabled = true;
+
+        let request = Json(UpdateCurfewRequest {
+            enabled: false,
+            start: "22:00".to_string(),
+            end: "06:00".to_string(),
+        });
+        let change = match update_curfew(State(state.clone()), Actor::new("tester"), request).await
+        {
+            Ok(Guarded::Pending(change)) => change,
+            other => panic!("expected a pending change, got {other:?}"),
+        };
+        assert_eq!(change.setting, guard::GuardedSetting::Curfew);
+        assert_eq!(change.after["enabled"], false);
+        assert!(state.config.read().await.curfew.enabled);
+
+        let Json(response) = approve_change(
+            State(state.clone()),
+            Actor::new("partner"),
+            partner(PARTNER_TOKEN),
+            Path(change.id),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.resolution, ChangeResolution::Approved);
+        assert!(!state.config.read().await.curfew.enabled);
+    }
+
+    #[tokio::test]
+    async fn test_timezone_change_is_guarded() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = guarded_state(dir.path()).await;
+
+        let request = Json(UpdateTimezoneRequest {
+            timezone: "Pacific/Kiritimati".to_string(),
+        });
+        let change =
+            match update_timezone(State(state.clone()), Actor::new("tester"), request).await {
+                Ok(Guarded::Pending(change)) => change,
+                other => panic!("expected a pending change, got {other:?}"),
+            };
+        assert_eq!(change.setting, guard::GuardedSetting::Timezone);
+        assert_eq!(change.before["timezone"], "UTC");
+        assert_eq!(change.after["timezone"], "Pacific/Kiritimati");
+        assert_eq!(state.config.read().await.system.timezone, "UTC");
+
+        guard::apply_due(&state, Utc::now() + chrono::Duration::hours(25)).await;
+        assert_eq!(
+            state.config.read().await.system.timezone,
+            "Pacific/Kiritimati"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_webhook_changes_are_guarded() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = guarded_state(dir.path()).await;
+        let request = CreateWebhookRequest {
+            url: "https://chat.example.com/hooks/tether".to_string(),
+            events: Vec::new(),
+            secret: "correct-horse-battery-staple".to_string(),
+        };
+        let (_, Json(webhook)) =
+            create_webhook(State(state.clone()), Actor::new("tester"), Json(request))
+                .await
+                .unwrap();
+        let secrets = state.secrets.lock().await.len();
+
+        let request = Json(UpdateWebhookRequest {
+            url: Some("https://example.com/elsewhere".to_string()),
+            events: None,
+            secret: Some("another-long-enough-secret".to_string()),
+        });
+        let path = Path(webhook.id.clone());
+        let change =
+            match update_webhook(State(state.clone()), Actor::new("tester"), path, request).await {
+                Ok(Guarded::Pending(change)) => change,
+                other => panic!("expected a pending change, got {other:?}"),
+            };
+        assert_eq!(change.setting, guard::GuardedSetting::Webhooks);
+        assert_eq!(change.after["url"], "https://example.com/elsewhere");
+        assert_eq!(change.after["secret_changed"], true);
+        assert_eq!(state.secrets.lock().await.len(), secrets + 1);
+
+        // Rejecting discards the staged secret
+        let Json(response) = reject_change(
+            State(state.clone()),
+            Actor::new("partner"),
+            partner(PARTNER_TOKEN),
+            Path(change.id),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.resolution, ChangeResolution::Rejected);
+        assert_eq!(state.secrets.lock().await.len(), secrets);
+        let (url, secret) = state.webhooks.lock().await.target(&webhook.id).unwrap();
+        assert_eq!(url, "https://chat.example.com/hooks/tether");
+        assert_eq!(secret.expose(), "correct-horse-battery-staple");
+
+        let response = delete_webhook(
+            State(state.clone()),
+            Actor::new("tester"),
+            Path(webhook.id.clone()),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.status(), StatusCode::ACCEPTED);
+        assert!(state.webhooks.lock().await.get(&webhook.id).is_some());
+
+        let missing = delete_webhook(
+            State(state.clone()),
+            Actor::new("tester"),
+            Path("nope".to_string()),
+        )
+        .await;
+        assert!(matches!(missing, Err(ApiError::NotFound { .. })));
+
+        guard::apply_due(&state, Utc::now() + chrono::Duration::hours(25)).await;
+        assert!(state.webhooks.lock().await.webhooks().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_changes_apply_immediately_before_onboarding() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = guarded_state(dir.path()).await;
+        state.config.write().await.system.onboarding_complete = false;
+
+        let request = Json(UpdatePassesPerMonthRequest { per_month: 5 });
+        let response = update_passes_per_month(State(state.clone()), Actor::new("tester"), request)
+            .await
+            .unwrap();
+        assert!(matches!(response, Guarded::Applied(_)));
+        assert_eq!(state.config.read().await.passes.per_month, 5);
+    }
+}
@@ -5,31 +5,10 @@
 use axum::extract::State;
 use axum::routing::get;
 use axum::{Json, Router};
-use serde::{Deserialize, Serialize};
-use utoipa::ToSchema;
 
 use crate::state::SharedState;
 
-/// Health check response.
-#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
-#[schema(example = json!({
-    "status": "ok",
-    "version": "0.1.0",
-    "onboarding_complete": true
-}))]
-pub struct HealthResponse {
-    /// Service status.
-    #[schema(example = "ok")]
-    pub status: String,
-
-    /// Service version from Cargo.toml.
-    #[schema(example = "0.1.0")]
-    pub version: String,
-
-    /// Whether initial onboarding has been completed.
-    #[schema(example = true)]
-    pub onboarding_complete: bool,
-}
+pub use tether_client::types::HealthResponse;
 
 /// Creates the health router.
 pub fn router() -> Router<SharedState> {
@@ -53,8 +32,7 @@ pub fn router() -> Router<SharedState> {
     )
 )]
 pub async fn health_check(State(state): State<SharedState>) -> Json<HealthResponse> {
-    let state_guard = state.read().await;
-    let onboarding_complete = state_guard.config.system.onboarding_complete;
+    let onboarding_complete = state.config.read().await.system.onboarding_complete;
 
     Json(HealthResponse {
         status: "ok".to_string(),
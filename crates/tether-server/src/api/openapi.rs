@@ -10,22 +10,35 @@ use axum::Json;
 use utoipa::OpenApi;
 
 // Import all the handler modules to reference their types
-use super::bluetooth::{DiscoveredDevice, ProximityResponse, ScanDevicesResponse};
+use super::access::{AccessResponse, ApiScope};
+use super::audit::AuditLogResponse;
+use super::bluetooth::{
+    AdaptersResponse, DeviceDiscoveryUpdate, DiscoveredDevice, PairDeviceRequest,
+    PairDeviceResponse, ProximityResponse, ScanDevicesResponse,
+};
 use super::config::{
-    BluetoothConfigResponse, CompleteOnboardingResponse, ConfigResponse, UpdateBluetoothRequest,
-    UpdateBluetoothResponse, UpdatePassesPerMonthRequest, UpdatePassesPerMonthResponse,
-    UpdateTimezoneRequest, UpdateTimezoneResponse, UpdateWifiRequest, UpdateWifiResponse,
-    WifiNetworkConfig,
+    BluetoothConfigResponse, CompleteOnboardingResponse, ConfigResponse, CurfewResponse,
+    UpdateBluetoothRequest, UpdateBluetoothResponse, UpdateCurfewRequest, UpdateCurfewResponse,
+    UpdatePassesPerMonthRequest, UpdatePassesPerMonthResponse, UpdateTimezoneRequest,
+    UpdateTimezoneResponse, UpdateWifiRequest, UpdateWifiResponse, WifiNetworkConfig,
+    WifiNetworkResponse,
 };
 use super::error::ErrorResponse;
+use super::guard::{
+    GuardStatusResponse, PendingChangeResponse, PendingChangesResponse, ResolveChangeResponse,
+};
 use super::health::HealthResponse;
 use super::passes::{
-    PassHistoryEntry, PassHistoryResponse, PassesResponse, UsePassRequest,
-    UsePassResponse,
+    PassHistoryEntry, PassHistoryRangeResponse, PassHistoryResponse, PassesResponse,
+    UsePassRequest, UsePassResponse,
 };
 use super::system::{
     DumbpipeTicketResponse, RestartRequest, RestartResponse, SystemStatusResponse,
 };
+use super::webhooks::{
+    CreateWebhookRequest, DeliveryAttemptResponse, DeliveryLogResponse, UpdateWebhookRequest,
+    WebhookResponse, WebhooksResponse,
+};
 
 /// Serve the OpenAPI specification as JSON.
 ///
@@ -72,6 +85,30 @@ If you're accessing this API via MCP tools:
 - **usePass**: Use when user has legitimate reason (on-call, emergency). Requires a reason.
 - **getPassHistory**: Review past pass usage to identify patterns.
 
+## Access
+
+Requests carrying an API token from the `[access]` configuration as `Authorization: Bearer <token>`
+get that token's scope: `read` only reads state, `passes` may also use passes, and `settings`
+may change anything. Requests carrying the partner token may do anything, and all other requests
+get the configured default scope, `settings` unless lowered. Requests outside their scope fail with
+`403 Forbidden`. See `/api/access` for the caller's scope.
+
+## Audit Log
+
+Every change made through this API is recorded in a hash-chained audit log, readable at
+`/api/audit`. Name your client in the `X-Tether-Actor` header (such as `web-ui`) to be
+recorded as the `claimed_actor`; requests without it are recorded as `anonymous`. The header
+is not authenticated. Requests carrying the partner token are also recorded with
+`authenticated_as: "partner"`, and those carrying an API token with `authenticated_as: "token:<name>"`.
+
+## Guarded Settings
+
+When guarded settings are enabled, changes to the tracked Bluetooth device, passes per month,
+the curfew, the timezone and existing webhooks return `202 Accepted` with a pending change
+instead of taking effect. An accountability partner approves, rejects or cancels it with their
+token, or it takes effect after a cooling-off period.
+See `/api/guard/changes`.
+
 ## Design Philosophy
 
 - **Lazy evaluation**: Bluetooth checks only happen when requested
@@ -102,7 +139,23 @@ If you're accessing this API via MCP tools:
         ),
         (
             name = "devices",
-            description = "Bluetooth device scanning for onboarding"
+            description = "Bluetooth device scanning and pairing for onboarding"
+        ),
+        (
+            name = "events",
+            description = "Server-Sent Events stream of changes, for clients that would otherwise poll"
+        ),
+        (
+            name = "webhooks",
+            description = "Signed HTTP callbacks delivering server events to other services"
+        ),
+        (
+            name = "audit",
+            description = "Tamper-evident log of changes made through the API"
+        ),
+        (
+            name = "guard",
+            description = "Changes to guarded settings waiting for an accountability partner's approval"
         )
     ),
     paths(
@@ -113,20 +166,43 @@ If you're accessing this API via MCP tools:
         // Pass endpoints
         super::passes::get_passes,
         super::passes::get_pass_history,
+        super::passes::get_pass_history_range,
         super::passes::use_pass,
         // Config endpoints
         super::config::get_config,
         super::config::update_bluetooth,
         super::config::update_wifi,
         super::config::update_timezone,
+        super::config::update_curfew,
         super::config::update_passes_per_month,
         super::config::complete_onboarding,
         // System endpoints
+        super::access::get_access,
         super::system::get_status,
         super::system::get_ticket,
         super::system::restart,
         // Device endpoints
         super::bluetooth::scan_devices,
+        super::bluetooth::stream_devices,
+        super::bluetooth::pair_device,
+        super::bluetooth::list_adapters,
+        // Event endpoints
+        super::events::stream_events,
+        // Webhook endpoints
+        super::webhooks::list_webhooks,
+        super::webhooks::create_webhook,
+        super::webhooks::get_webhook,
+        super::webhooks::update_webhook,
+        super::webhooks::delete_webhook,
+        super::webhooks::list_deliveries,
+        // Audit endpoints
+        super::audit::list_audit_entries,
+        // Guard endpoints
+        super::guard::get_guard_status,
+        super::guard::list_pending_changes,
+        super::guard::approve_change,
+        super::guard::reject_change,
+        super::guard::cancel_change,
     ),
     components(
         schemas(
@@ -138,6 +214,7 @@ If you're accessing this API via MCP tools:
             PassesResponse,
             PassHistoryEntry,
             PassHistoryResponse,
+            PassHistoryRangeResponse,
             UsePassRequest,
             UsePassResponse,
             // Config types
@@ -146,22 +223,64 @@ If you're accessing this API via MCP tools:
             UpdateBluetoothRequest,
             UpdateBluetoothResponse,
             WifiNetworkConfig,
+            WifiNetworkResponse,
             UpdateWifiRequest,
             UpdateWifiResponse,
             UpdateTimezoneRequest,
             UpdateTimezoneResponse,
+            CurfewResponse,
+            UpdateCurfewRequest,
+            UpdateCurfewResponse,
             UpdatePassesPerMonthRequest,
             UpdatePassesPerMonthResponse,
             CompleteOnboardingResponse,
             // System types
+            AccessResponse,
+            ApiScope,
             SystemStatusResponse,
             DumbpipeTicketResponse,
             RestartRequest,
             RestartResponse,
+            crate::supervisor::BluetoothHealth,
+            crate::supervisor::ScannerState,
             // Bluetooth types
             ProximityResponse,
             DiscoveredDevice,
             ScanDevicesResponse,
+            DeviceDiscoveryUpdate,
+            PairDeviceRequest,
+            PairDeviceResponse,
+            AdaptersResponse,
+            tether_core::AdapterInfo,
+            super::bluetooth::BluetoothAddressType,
+            super::bluetooth::DetectionMethod,
+            tether_core::ProbeMode,
+            tether_core::RssiFusion,
+            // Event types
+            crate::events::EventMessage,
+            crate::events::ServerEvent,
+            crate::events::ConfigSection,
+            crate::events::EventKind,
+            // Webhook types
+            WebhookResponse,
+            WebhooksResponse,
+            CreateWebhookRequest,
+            UpdateWebhookRequest,
+            DeliveryAttemptResponse,
+            DeliveryLogResponse,
+            crate::webhooks::DeliveryStatus,
+            // Audit types
+            AuditLogResponse,
+            crate::audit::AuditEntry,
+            crate::audit::AuditAction,
+            crate::audit::AuditChainStatus,
+            // Guard types
+            GuardStatusResponse,
+            PendingChangeResponse,
+            PendingChangesResponse,
+            ResolveChangeResponse,
+            crate::guard::GuardedSetting,
+            crate::guard::ChangeResolution,
         )
     )
 )]
@@ -8,18 +8,27 @@
 use axum::extract::{Query, State};
 use axum::routing::{get, post};
 use axum::{Json, Router};
-use chrono::{Datelike, TimeZone, Utc};
-use serde::{Deserialize, Serialize};
-use utoipa::{IntoParams, ToSchema};
+use chrono::{DateTime, Datelike, TimeZone, Utc};
+use serde::Deserialize;
+use serde_json::json;
+use utoipa::IntoParams;
 
 use crate::api::error::{ApiError, ApiResult};
+use crate::audit::{Actor, AuditAction};
+use crate::events::{reset_month_if_needed, ServerEvent};
 use crate::state::SharedState;
 
+pub use tether_client::types::{
+    PassHistoryEntry, PassHistoryRangeResponse, PassHistoryResponse, PassesResponse,
+    UsePassRequest, UsePassResponse,
+};
+
 /// Creates the passes router with all endpoints.
 pub fn router() -> Router<SharedState> {
     Router::new()
         .route("/", get(get_passes))
         .route("/history", get(get_pass_history))
+        .route("/history/range", get(get_pass_history_range))
         .route("/use", post(use_pass))
 }
 
@@ -27,42 +36,6 @@ pub fn router() -> Router<SharedState> {
 // Request/Response Types
 // ============================================================================
 
-/// Current pass status for the month.
-#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
-#[schema(example = json!({
-    "remaining": 2,
-    "total_per_month": 3,
-    "used_this_month": 1,
-    "month": "2025-01",
-    "resets_at_utc": "2025-02-01T08:00:00Z",
-    "timezone": "America/Los_Angeles"
-}))]
-pub struct PassesResponse {
-    /// Number of passes remaining this month.
-    #[schema(example = 2, minimum = 0)]
-    pub remaining: u32,
-
-    /// Total passes allocated per month (from config).
-    #[schema(example = 3, minimum = 0)]
-    pub total_per_month: u32,
-
-    /// Number of passes used this month.
-    #[schema(example = 1, minimum = 0)]
-    pub used_this_month: u32,
-
-    /// Current month in YYYY-MM format.
-    #[schema(example = "2025-01")]
-    pub month: String,
-
-    /// UTC timestamp when passes will reset.
-    #[schema(example = "2025-02-01T08:00:00Z")]
-    pub resets_at_utc: String,
-
-    /// Configured timezone for reset calculation.
-    #[schema(example = "America/Los_Angeles")]
-    pub timezone: String,
-}
-
 /// Query parameters for pass history endpoint.
 #[derive(Debug, Clone, Deserialize, IntoParams)]
 pub struct PassHistoryQuery {
@@ -72,88 +45,16 @@ pub struct PassHistoryQuery {
     pub month: Option<String>,
 }
 
-/// A single pass usage entry in history.
-#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
-#[schema(example = json!({
-    "used_at_utc": "2025-01-15T03:30:00Z",
-    "reason": "On-call for production incident"
-}))]
-pub struct PassHistoryEntry {
-    /// UTC timestamp when the pass was used.
-    #[schema(example = "2025-01-15T03:30:00Z")]
-    pub used_at_utc: String,
-
-    /// Reason provided when using the pass.
-    #[schema(example = "On-call for production incident")]
-    pub reason: String,
-}
-
-/// Pass usage history response.
-#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
-#[schema(example = json!({
-    "month": "2025-01",
-    "entries": [
-        {
-            "used_at_utc": "2025-01-15T03:30:00Z",
-            "reason": "On-call for production incident"
-        }
-    ],
-    "total_used": 1,
-    "total_per_month": 3
-}))]
-pub struct PassHistoryResponse {
-    /// Month in YYYY-MM format.
-    #[schema(example = "2025-01")]
-    pub month: String,
-
-    /// List of pass usage entries for the month.
-    pub entries: Vec<PassHistoryEntry>,
-
-    /// Total passes used this month.
-    #[schema(example = 1)]
-    pub total_used: usize,
-
-    /// Total passes allocated per month.
-    #[schema(example = 3)]
-    pub total_per_month: u32,
-}
-
-/// Request body for using a pass.
-#[derive(Debug, Clone, Deserialize, ToSchema)]
-#[schema(example = json!({
-    "reason": "On-call for production incident tonight"
-}))]
-pub struct UsePassRequest {
-    /// Reason for using the pass. Required and must be non-empty.
-    /// Maximum 500 characters.
-    #[schema(example = "On-call for production incident tonight", min_length = 1, max_length = 500)]
-    pub reason: String,
-}
-
-/// Response after successfully using a pass.
-#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
-#[schema(example = json!({
-    "success": true,
-    "remaining": 1,
-    "used_at_utc": "2025-01-15T03:30:00Z",
-    "reason": "On-call for production incident tonight"
-}))]
-pub struct UsePassResponse {
-    /// Whether the pass was successfully used.
-    #[schema(example = true)]
-    pub success: bool,
-
-    /// Number of passes remaining after this use.
-    #[schema(example = 1)]
-    pub remaining: u32,
-
-    /// UTC timestamp when the pass was used.
-    #[schema(example = "2025-01-15T03:30:00Z")]
-    pub used_at_utc: String,
-
-    /// The reason that was recorded.
-    #[schema(example = "On-call for production incident tonight")]
-    pub reason: String,
+/// Query parameters for the pass history range endpoint.
+#[derive(Debug, Clone, Deserialize, IntoParams)]
+pub struct PassHistoryRangeQuery {
+    /// Start of the range (inclusive), as an RFC 3339 timestamp.
+    #[param(example = "2025-01-01T00:00:00Z")]
+    pub from: String,
+
+    /// End of the range (exclusive), as an RFC 3339 timestamp.
+    #[param(example = "2025-04-01T00:00:00Z")]
+    pub to: String,
 }
 
 // ============================================================================
@@ -176,15 +77,18 @@ pub struct UsePassResponse {
     )
 )]
 pub async fn get_passes(State(state): State<SharedState>) -> ApiResult<Json<PassesResponse>> {
-    let state_guard = state.read().await;
-
-    let remaining = state_guard.pass_manager.remaining();
-    let per_month = state_guard.pass_manager.per_month();
-    let month = state_guard.pass_manager.current_month().to_string();
+    let (remaining, per_month, month) = {
+        let pass_manager = state.pass_manager.read().await;
+        (
+            pass_manager.remaining(),
+            pass_manager.per_month(),
+            pass_manager.current_month().to_string(),
+        )
+    };
     let used_this_month = per_month.saturating_sub(remaining);
 
-    let timezone = &state_guard.config.system.timezone;
-    let resets_at_utc = calculate_next_reset_utc(timezone);
+    let timezone = state.config.read().await.system.timezone.clone();
+    let resets_at_utc = calculate_next_reset_utc(&timezone);
 
     Ok(Json(PassesResponse {
         remaining,
@@ -192,7 +96,7 @@ pub async fn get_passes(State(state): State<SharedState>) -> ApiResult<Json<Pass
         used_this_month,
         month,
         resets_at_utc,
-        timezone: timezone.clone(),
+        timezone,
     }))
 }
 
@@ -217,7 +121,7 @@ pub async fn get_pass_history(
     State(state): State<SharedState>,
     Query(query): Query<PassHistoryQuery>,
 ) -> ApiResult<Json<PassHistoryResponse>> {
-    let state_guard = state.read().await;
+    let pass_manager = state.pass_manager.read().await;
 
     // Determine which month to query
     let month = match query.month {
@@ -231,20 +135,14 @@ pub async fn get_pass_history(
             }
             m
         }
-        None => state_guard.pass_manager.current_month().to_string(),
+        None => pass_manager.current_month().to_string(),
     };
 
-    let history = state_guard.pass_manager.history(&month);
-    let entries: Vec<PassHistoryEntry> = history
-        .iter()
-        .map(|entry| PassHistoryEntry {
-            used_at_utc: entry.used_at_utc.to_rfc3339(),
-            reason: entry.reason.clone(),
-        })
-        .collect();
+    let history = pass_manager.history(&month)?;
+    let entries: Vec<PassHistoryEntry> = history.iter().map(history_entry).collect();
 
     let total_used = entries.len();
-    let per_month = state_guard.pass_manager.per_month();
+    let per_month = pass_manager.per_month();
 
     Ok(Json(PassHistoryResponse {
         month,
@@ -254,6 +152,47 @@ pub async fn get_pass_history(
     }))
 }
 
+/// Get pass usage history for a time range.
+///
+/// Returns all pass usage entries between two timestamps, across months.
+#[utoipa::path(
+    get,
+    path = "/passes/history/range",
+    tag = "passes",
+    operation_id = "getPassHistoryRange",
+    summary = "Get pass usage history for a time range",
+    description = "Returns all pass usage entries used at or after `from` and before \
+        `to`, oldest first. Both are RFC 3339 timestamps.",
+    params(PassHistoryRangeQuery),
+    responses(
+        (status = 200, description = "History retrieved", body = PassHistoryRangeResponse),
+        (status = 400, description = "Invalid timestamp or empty range")
+    )
+)]
+pub async fn get_pass_history_range(
+    State(state): State<SharedState>,
+    Query(query): Query<PassHistoryRangeQuery>,
+) -> ApiResult<Json<PassHistoryRangeResponse>> {
+    let from = parse_timestamp("from", &query.from)?;
+    let to = parse_timestamp("to", &query.to)?;
+    if from >= to {
+        return Err(ApiError::BadRequest {
+            error_code: "invalid_range".to_string(),
+            message: "'from' must be before 'to'".to_string(),
+        });
+    }
+
+    let history = state.pass_manager.read().await.history_range(from, to)?;
+    let entries: Vec<PassHistoryEntry> = history.iter().map(history_entry).collect();
+
+    Ok(Json(PassHistoryRangeResponse {
+        from_utc: from.to_rfc3339(),
+        to_utc: to.to_rfc3339(),
+        total_used: entries.len(),
+        entries,
+    }))
+}
+
 /// Use a pass for today.
 ///
 /// Uses one of the remaining passes with a required reason.
@@ -274,13 +213,32 @@ pub async fn get_pass_history(
 )]
 pub async fn use_pass(
     State(state): State<SharedState>,
+    actor: Actor,
     Json(request): Json<UsePassRequest>,
 ) -> ApiResult<Json<UsePassResponse>> {
-    let mut state_guard = state.write().await;
+    let mut pass_manager = state.pass_manager.write().await;
+    reset_month_if_needed(&state.events, &mut pass_manager)?;
+    let before = json!({ "remaining": pass_manager.remaining() });
 
     // Use the pass (validation and persistence happen in PassManager)
-    let entry = state_guard.pass_manager.use_pass(request.reason)?;
-    let remaining = state_guard.pass_manager.remaining();
+    let entry = pass_manager.use_pass(request.reason)?;
+    let remaining = pass_manager.remaining();
+
+    state.events.publish(ServerEvent::PassUsed {
+        remaining,
+        used_at_utc: entry.used_at_utc.to_rfc3339(),
+        reason: entry.reason.clone(),
+    });
+
+    let after = json!({
+        "remaining": remaining,
+        "used_at_utc": entry.used_at_utc.to_rfc3339(),
+        "reason": entry.reason,
+    });
+    state
+        .record_audit(&actor, AuditAction::UsePass, Some(before), Some(after))
+        .await;
+    drop(pass_manager);
 
     Ok(Json(UsePassResponse {
         success: true,
@@ -294,6 +252,24 @@ pub async fn use_pass(
 // Helpers
 // ============================================================================
 
+/// Converts a recorded pass use to a history entry.
+fn history_entry(entry: &tether_core::PassEntry) -> PassHistoryEntry {
+    PassHistoryEntry {
+        used_at_utc: entry.used_at_utc.to_rfc3339(),
+        reason: entry.reason.clone(),
+    }
+}
+
+/// Parses an RFC 3339 query parameter.
+fn parse_timestamp(name: &str, value: &str) -> ApiResult<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|t| t.with_timezone(&Utc))
+        .map_err(|_| ApiError::BadRequest {
+            error_code: "invalid_timestamp".to_string(),
+            message: format!("'{name}' must be an RFC 3339 timestamp (e.g., 2025-01-01T00:00:00Z)"),
+        })
+}
+
 /// Calculate when passes will reset (first of next month at midnight local time).
 fn calculate_next_reset_utc(timezone: &str) -> String {
     let tz: chrono_tz::Tz = timezone.parse().unwrap_or(chrono_tz::UTC);
@@ -337,6 +313,37 @@ mod tests {
         assert_eq!(request.reason, "Test reason");
     }
 
+    #[test]
+    fn test_parse_timestamp() {
+        let parsed = parse_timestamp("from", "2025-01-01T01:00:00+01:00").unwrap();
+        assert_eq!(parsed, Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap());
+        assert!(parse_timestamp("from", "2025-01-01").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_use_pass_publishes_event() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = crate::state::AppState::in_dir(dir.path(), None).into_shared();
+        let mut subscription = state.events.subscribe(None);
+
+        let request = UsePassRequest {
+            reason: "On call tonight".to_string(),
+        };
+        let used = use_pass(State(state.clone()), Actor::new("tester"), Json(request))
+            .await
+            .unwrap();
+
+        let message = subscription.receiver.try_recv().unwrap();
+        assert_eq!(
+            message.event,
+            ServerEvent::PassUsed {
+                remaining: 2,
+                used_at_utc: used.used_at_utc.clone(),
+                reason: "On call tonight".to_string(),
+            }
+        );
+    }
+
     #[test]
     fn test_calculate_next_reset_utc() {
         let reset = calculate_next_reset_utc("UTC");
@@ -6,9 +6,11 @@ use axum::extract::State;
 use axum::routing::{get, post};
 use axum::{Json, Router};
 use serde::{Deserialize, Serialize};
+use serde_json::json;
 use utoipa::ToSchema;
 
 use crate::api::error::{ApiError, ApiResult};
+use crate::audit::{Actor, AuditAction};
 use crate::state::SharedState;
 
 /// Creates the system router with all endpoints.
@@ -23,65 +25,7 @@ pub fn router() -> Router<SharedState> {
 // Request/Response Types
 // ============================================================================
 
-/// System status response.
-#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
-#[schema(example = json!({
-    "version": "0.1.0",
-    "uptime_secs": 3600,
-    "bluetooth_available": true,
-    "config_loaded": true,
-    "onboarding_complete": true
-}))]
-pub struct SystemStatusResponse {
-    /// Server version.
-    #[schema(example = "0.1.0")]
-    pub version: String,
-
-    /// Server uptime in seconds.
-    #[schema(example = 3600)]
-    pub uptime_secs: u64,
-
-    /// Whether Bluetooth is available.
-    #[schema(example = true)]
-    pub bluetooth_available: bool,
-
-    /// Whether configuration is loaded.
-    #[schema(example = true)]
-    pub config_loaded: bool,
-
-    /// Whether onboarding is complete.
-    #[schema(example = true)]
-    pub onboarding_complete: bool,
-}
-
-/// Dumbpipe ticket response.
-#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
-#[schema(example = json!({
-    "ticket": "blobfd23abc...",
-    "expires_at_utc": "2025-01-15T04:30:00Z",
-    "node_id": "n0abc123..."
-}))]
-pub struct DumbpipeTicketResponse {
-    /// The dumbpipe ticket for remote access.
-    /// This is a base32-encoded iroh ticket.
-    #[schema(example = "blobfd23abc...")]
-    pub ticket: Option<String>,
-
-    /// When the ticket expires (if applicable).
-    #[schema(example = "2025-01-15T04:30:00Z")]
-    pub expires_at_utc: Option<String>,
-
-    /// The node ID of this tether instance.
-    #[schema(example = "n0abc123...")]
-    pub node_id: Option<String>,
-
-    /// Whether dumbpipe is available.
-    #[schema(example = true)]
-    pub available: bool,
-
-    /// Message if not available.
-    pub message: Option<String>,
-}
+pub use tether_client::types::{DumbpipeTicketResponse, SystemStatusResponse};
 
 /// System restart request.
 #[derive(Debug, Clone, Deserialize, ToSchema)]
@@ -149,20 +93,19 @@ fn get_uptime_secs() -> u64 {
     operation_id = "getSystemStatus",
     summary = "Get system status",
     description = "Returns the current system status including version, uptime, \
-        and component availability.",
+        component availability, and Bluetooth scanner health.",
     responses(
         (status = 200, description = "System status retrieved", body = SystemStatusResponse)
     )
 )]
 pub async fn get_status(State(state): State<SharedState>) -> ApiResult<Json<SystemStatusResponse>> {
-    let state_guard = state.read().await;
-
     Ok(Json(SystemStatusResponse {
         version: env!("CARGO_PKG_VERSION").to_string(),
         uptime_secs: get_uptime_secs(),
-        bluetooth_available: state_guard.bluetooth.is_some(),
+        bluetooth_available: state.scanner().await.is_some(),
+        bluetooth_health: state.bluetooth_health.snapshot(),
         config_loaded: true,
-        onboarding_complete: state_guard.config.system.onboarding_complete,
+        onboarding_complete: state.config.read().await.system.onboarding_complete,
     }))
 }
 
@@ -211,7 +154,8 @@ pub async fn get_ticket(
     )
 )]
 pub async fn restart(
-    State(_state): State<SharedState>,
+    State(state): State<SharedState>,
+    actor: Actor,
     Json(request): Json<RestartRequest>,
 ) -> ApiResult<Json<RestartResponse>> {
     let delay_secs = request.delay_secs.unwrap_or(5);
@@ -227,9 +171,15 @@ pub async fn restart(
     // TODO: Actually implement system restart
     // For now, return a response indicating it's not implemented
     // In production, this would spawn a task to restart the system after the delay
+    let accepted = false;
+
+    let after = json!({ "delay_secs": delay_secs, "accepted": accepted });
+    state
+        .record_audit(&actor, AuditAction::Restart, None, Some(after))
+        .await;
 
     Ok(Json(RestartResponse {
-        accepted: false,
+        accepted,
         message: format!(
             "Restart not implemented. Would restart in {} seconds.",
             delay_secs
@@ -248,11 +198,13 @@ mod tests {
             version: "0.1.0".to_string(),
             uptime_secs: 3600,
             bluetooth_available: true,
+            bluetooth_health: crate::supervisor::HealthTracker::new(true).snapshot(),
             config_loaded: true,
             onboarding_complete: false,
         };
         let json = serde_json::to_string(&response).unwrap();
         assert!(json.contains("\"version\":\"0.1.0\""));
+        assert!(json.contains("\"state\":\"healthy\""));
     }
 
     #[test]
@@ -0,0 +1,689 @@
+//! Webhook API endpoints.
+//!
+//! Manages webhook subscriptions that forward server events to other
+//! services, and exposes the log of delivery attempts. See
+//! [`crate::webhooks`] for how events are signed and retried.
+
+use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::{Json, Router};
+use serde::{Deserialize, Serialize};
+use tether_core::SecretString;
+use utoipa::{IntoParams, ToSchema};
+
+use crate::api::error::{ApiError, ApiResult};
+use crate::api::guard::{Guarded, PendingChangeResponse};
+use crate::audit::{snapshot, Actor, AuditAction};
+use crate::events::EventKind;
+use crate::guard::{self, GuardedChange};
+use crate::state::{AppState, SharedState};
+use crate::webhooks::{
+    validate_url, DeliveryAttempt, DeliveryStatus, Webhook, WebhookStore, DELIVERY_LOG_CAPACITY,
+};
+
+/// Minimum length of a webhook secret.
+const MIN_SECRET_LENGTH: usize = 16;
+
+/// Maximum length of a webhook secret.
+const MAX_SECRET_LENGTH: usize = 256;
+
+/// Creates the webhooks router with all endpoints.
+pub fn router() -> Router<SharedState> {
+    Router::new()
+        .route("/", get(list_webhooks).post(create_webhook))
+        .route("/deliveries", get(list_deliveries))
+        .route(
+            "/{id}",
+            get(get_webhook).put(update_webhook).delete(delete_webhook),
+        )
+}
+
+// ============================================================================
+// Request/Response Types
+// ============================================================================
+
+/// A webhook subscription. The secret is never returned.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[schema(example = json!({
+    "id": "4f6c1a52-8f1e-4d3b-9a0e-2b7c5d9e1f30",
+    "url": "https://chat.example.com/hooks/tether",
+    "events": ["pass_used", "proximity_changed"],
+    "created_at_utc": "2025-01-15T03:30:00+00:00",
+    "pending_deliveries": 0
+}))]
+pub struct WebhookResponse {
+    /// Unique id of the webhook.
+    #[schema(example = "4f6c1a52-8f1e-4d3b-9a0e-2b7c5d9e1f30")]
+    pub id: String,
+
+    /// URL events are posted to.
+    #[schema(example = "https://chat.example.com/hooks/tether")]
+    pub url: String,
+
+    /// Event types delivered to the webhook; empty for all events.
+    pub events: Vec<EventKind>,
+
+    /// When the webhook was created.
+    #[schema(example = "2025-01-15T03:30:00+00:00")]
+    pub created_at_utc: String,
+
+    /// Deliveries waiting to be sent or retried.
+    #[schema(example = 0)]
+    pub pending_deliveries: usize,
+}
+
+/// All webhook subscriptions.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct WebhooksResponse {
+    /// Webhooks, oldest first.
+    pub webhooks: Vec<WebhookResponse>,
+}
+
+/// Request to create a webhook.
+///
+/// Only ever received; the secret is redacted from `Debug` output.
+#[derive(Clone, Deserialize, ToSchema)]
+#[schema(example = json!({
+    "url": "https://chat.example.com/hooks/tether",
+    "events": ["pass_used", "proximity_changed"],
+    "secret": "correct-horse-battery-staple"
+}))]
+pub struct CreateWebhookRequest {
+    /// HTTP or HTTPS URL to post events to.
+    #[schema(example = "https://chat.example.com/hooks/tether")]
+    pub url: String,
+
+    /// Event types to deliver. Omit or leave empty for all events.
+    #[serde(default)]
+    pub events: Vec<EventKind>,
+
+    /// Secret used to sign requests (16 to 256 characters).
+    #[schema(
+        example = "correct-horse-battery-staple",
+        min_length = 16,
+        max_length = 256
+    )]
+    pub secret: String,
+}
+
+impl std::fmt::Debug for CreateWebhookRequest {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CreateWebhookRequest")
+            .field("url", &self.url)
+            .field("events", &self.events)
+            .field("secret", &"[REDACTED]")
+            .finish()
+    }
+}
+
+/// Request to update a webhook. Omitted fields are left unchanged.
+///
+/// Only ever received; the secret is redacted from `Debug` output.
+#[derive(Clone, Deserialize, ToSchema)]
+#[schema(example = json!({
+    "events": ["pass_used"]
+}))]
+pub struct UpdateWebhookRequest {
+    /// New HTTP or HTTPS URL to post events to.
+    #[schema(example = "https://chat.example.com/hooks/tether")]
+    pub url: Option<String>,
+
+    /// New event types to deliver; empty for all events.
+    pub events: Option<Vec<EventKind>>,
+
+    /// New secret used to sign requests (16 to 256 characters).
+    #[schema(min_length = 16, max_length = 256)]
+    pub secret: Option<String>,
+}
+
+impl std::fmt::Debug for UpdateWebhookRequest {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("UpdateWebhookRequest")
+            .field("url", &self.url)
+            .field("events", &self.events)
+            .field("secret", &self.secret.as_ref().map(|_| "[REDACTED]"))
+            .finish()
+    }
+}
+
+/// Query parameters for the delivery log.
+#[derive(Debug, Clone, Deserialize, IntoParams)]
+pub struct DeliveryLogQuery {
+    /// Only return attempts for this webhook.
+    #[param(example = "4f6c1a52-8f1e-4d3b-9a0e-2b7c5d9e1f30")]
+    pub webhook_id: Option<String>,
+
+    /// Maximum number of attempts to return (at most 200). Defaults to 50.
+    #[param(example = 50)]
+    pub limit: Option<usize>,
+}
+
+/// A single delivery attempt.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[schema(example = json!({
+    "delivery_id": "0d9c8b7a-6f5e-4d3c-2b1a-0f9e8d7c6b5a",
+    "webhook_id": "4f6c1a52-8f1e-4d3b-9a0e-2b7c5d9e1f30",
+    "event_id": 1_736_911_800_000_001_u64,
+    "event_type": "pass_used",
+    "attempt": 2,
+    "status": "retrying",
+    "status_code": 502,
+    "error": "Webhook responded with 502 Bad Gateway",
+    "attempted_at_utc": "2025-01-15T03:30:10+00:00",
+    "next_attempt_at_utc": "2025-01-15T03:30:30+00:00"
+}))]
+pub struct DeliveryAttemptResponse {
+    /// Id of the delivery, sent in the `X-Tether-Delivery` header.
+    pub delivery_id: String,
+
+    /// The webhook delivered to.
+    pub webhook_id: String,
+
+    /// Id of the delivered event.
+    pub event_id: u64,
+
+    /// Type of the delivered event.
+    pub event_type: EventKind,
+
+    /// Attempt number, starting at 1.
+    pub attempt: u32,
+
+    /// Result of the attempt.
+    pub status: DeliveryStatus,
+
+    /// HTTP status of the response, if there was one.
+    pub status_code: Option<u16>,
+
+    /// What went wrong, for failed attempts.
+    pub error: Option<String>,
+
+    /// When the attempt was made.
+    pub attempted_at_utc: String,
+
+    /// When the next attempt is due, for retried deliveries.
+    pub next_attempt_at_utc: Option<String>,
+}
+
+/// Recent delivery attempts.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct DeliveryLogResponse {
+    /// Delivery attempts, newest first.
+    pub deliveries: Vec<DeliveryAttemptResponse>,
+
+    /// Deliveries waiting to be sent or retried.
+    #[schema(example = 1)]
+    pub pending: usize,
+}
+
+// ============================================================================
+// Handlers
+// ============================================================================
+
+/// List webhooks.
+#[utoipa::path(
+    get,
+    path = "/webhooks",
+    tag = "webhooks",
+    operation_id = "listWebhooks",
+    summary = "List webhooks",
+    description = "Returns all webhook subscriptions. Secrets are never returned.",
+    responses(
+        (status = 200, description = "Webhooks retrieved", body = WebhooksResponse)
+    )
+)]
+pub async fn list_webhooks(State(state): State<SharedState>) -> ApiResult<Json<WebhooksResponse>> {
+    let store = state.webhooks.lock().await;
+    let webhooks = store
+        .webhooks()
+        .iter()
+        .map(|webhook| to_response(webhook, pending_for(&store, &webhook.id)))
+        .collect();
+    Ok(Json(WebhooksResponse { webhooks }))
+}
+
+/// Create a webhook.
+#[utoipa::path(
+    post,
+    path = "/webhooks",
+    tag = "webhooks",
+    operation_id = "createWebhook",
+    summary = "Create a webhook",
+    description = "Subscribes a URL to server events. Each event is posted as JSON, signed \
+        with an `X-Tether-Signature-256: sha256=<hex>` header holding the HMAC-SHA256 of \
+        the raw body keyed with the secret. Failed deliveries are retried with \
+        exponential backoff.",
+    request_body = CreateWebhookRequest,
+    responses(
+        (status = 201, description = "Webhook created", body = WebhookResponse),
+        (status = 400, description = "Invalid URL or secret")
+    )
+)]
+pub async fn create_webhook(
+    State(state): State<SharedState>,
+    actor: Actor,
+    Json(request): Json<CreateWebhookRequest>,
+) -> ApiResult<(StatusCode, Json<WebhookResponse>)> {
+    let secret = validate_secret(request.secret)?;
+
+    let mut store = state.webhooks.lock().await;
+    let webhook = to_response(&store.create(request.url, request.events, &secret)?, 0);
+    state
+        .record_audit(&actor, AuditAction::CreateWebhook, None, snapshot(&webhook))
+        .await;
+    drop(store);
+    Ok((StatusCode::CREATED, Json(webhook)))
+}
+
+/// Get a webhook.
+#[utoipa::path(
+    get,
+    path = "/webhooks/{id}",
+    tag = "webhooks",
+    operation_id = "getWebhook",
+    summary = "Get a webhook",
+    params(("id" = String, Path, description = "Webhook id")),
+    responses(
+        (status = 200, description = "Webhook retrieved", body = WebhookResponse),
+        (status = 404, description = "Webhook not found")
+    )
+)]
+pub async fn get_webhook(
+    State(state): State<SharedState>,
+    Path(id): Path<String>,
+) -> ApiResult<Json<WebhookResponse>> {
+    let store = state.webhooks.lock().await;
+    let webhook = store.get(&id).ok_or_else(|| not_found(&id))?;
+    Ok(Json(to_response(webhook, pending_for(&store, &id))))
+}
+
+/// Update a webhook.
+#[utoipa::path(
+    put,
+    path = "/webhooks/{id}",
+    tag = "webhooks",
+    operation_id = "updateWebhook",
+    summary = "Update a webhook",
+    description = "Changes a webhook's URL, event filter or secret. Omitted fields are left \
+        unchanged. Queued retries are sent to the new URL with the new secret. With guarded \
+        settings enabled, the change waits for approval first.",
+    params(("id" = String, Path, description = "Webhook id")),
+    request_body = UpdateWebhookRequest,
+    responses(
+        (status = 200, description = "Webhook updated", body = WebhookResponse),
+        (status = 202, description = "Change is waiting for approval", body = PendingChangeResponse),
+        (status = 400, description = "Invalid URL or secret"),
+        (status = 404, description = "Webhook not found")
+    )
+)]
+pub async fn update_webhook(
+    State(state): State<SharedState>,
+    actor: Actor,
+    Path(id): Path<String>,
+    Json(request): Json<UpdateWebhookRequest>,
+) -> ApiResult<Guarded<WebhookResponse>> {
+    let secret = request.secret.map(validate_secret).transpose()?;
+    if let Some(url) = &request.url {
+        validate_url(url)?;
+    }
+
+    if guard::is_active(&state).await {
+        require_webhook(&state, &id).await?;
+        let secret_id = match &secret {
+            Some(secret) => Some(guard::stage_secret(&state, secret).await?),
+            None => None,
+        };
+        let change = GuardedChange::UpdateWebhook {
+            id,
+            url: request.url,
+            events: request.events,
+            secret_id,
+        };
+        let pending = guard::defer(&state, &actor, change).await?;
+        return Ok(Guarded::Pending(pending.into()));
+    }
+
+    apply_webhook_update(
+        &state,
+        &actor,
+        &id,
+        request.url,
+        request.events,
+        secret.as_ref(),
+    )
+    .await
+    .map(Guarded::Applied)
+}
+
+/// Applies a validated webhook update.
+///
+/// # Errors
+///
+/// Returns an error if there is no such webhook or it cannot be saved.
+pub async fn apply_webhook_update(
+    state: &AppState,
+    actor: &Actor,
+    id: &str,
+    url: Option<String>,
+    events: Option<Vec<EventKind>>,
+    secret: Option<&SecretString>,
+) -> ApiResult<WebhookResponse> {
+    let mut store = state.webhooks.lock().await;
+    let before = store
+        .get(id)
+        .map(|webhook| webhook_response(&store, webhook));
+    let webhook = store.update(id, url, events, secret)?;
+    let webhook = webhook_response(&store, &webhook);
+    state
+        .record_audit(
+            actor,
+            AuditAction::UpdateWebhook,
+            before.as_ref().and_then(snapshot),
+            snapshot(&webhook),
+        )
+        .await;
+    drop(store);
+    Ok(webhook)
+}
+
+/// Delete a webhook.
+#[utoipa::path(
+    delete,
+    path = "/webhooks/{id}",
+    tag = "webhooks",
+    operation_id = "deleteWebhook",
+    summary = "Delete a webhook",
+    description = "Deletes a webhook, its secret and any deliveries still queued for it. \
+        With guarded settings enabled, the deletion waits for approval first.",
+    params(("id" = String, Path, description = "Webhook id")),
+    responses(
+        (status = 204, description = "Webhook deleted"),
+        (status = 202, description = "Deletion is waiting for approval", body = PendingChangeResponse),
+        (status = 404, description = "Webhook not found")
+    )
+)]
+pub async fn delete_webhook(
+    State(state): State<SharedState>,
+    actor: Actor,
+    Path(id): Path<String>,
+) -> ApiResult<Response> {
+    if guard::is_active(&state).await {
+        require_webhook(&state, &id).await?;
+        let change = GuardedChange::DeleteWebhook { id };
+        let pending = guard::defer(&state, &actor, change).await?;
+        return Ok(Guarded::<()>::Pending(pending.into()).into_response());
+    }
+
+    apply_webhook_delete(&state, &actor, &id).await?;
+    Ok(StatusCode::NO_CONTENT.into_response())
+}
+
+/// Deletes a webhook.
+///
+/// # Errors
+///
+/// Returns an error if there is no such webhook or it cannot be saved.
+pub async fn apply_webhook_delete(state: &AppState, actor: &Actor, id: &str) -> ApiResult<()> {
+    let mut store = state.webhooks.lock().await;
+    let pending_deliveries = pending_for(&store, id);
+    let webhook = to_response(&store.delete(id)?, pending_deliveries);
+    state
+        .record_audit(actor, AuditAction::DeleteWebhook, snapshot(&webhook), None)
+        .await;
+    drop(store);
+    Ok(())
+}
+
+/// List recent delivery attempts.
+#[utoipa::path(
+    get,
+    path = "/webhooks/deliveries",
+    tag = "webhooks",
+    operation_id = "listWebhookDeliveries",
+    summary = "List recent webhook deliveries",
+    description = "Returns the most recent delivery attempts, newest first, and the number \
+        of deliveries still queued. Use it to check that a webhook is reachable.",
+    params(DeliveryLogQuery),
+    responses(
+        (status = 200, description = "Delivery log retrieved", body = DeliveryLogResponse)
+    )
+)]
+pub async fn list_deliveries(
+    State(state): State<SharedState>,
+    Query(query): Query<DeliveryLogQuery>,
+) -> ApiResult<Json<DeliveryLogResponse>> {
+    let limit = query.limit.unwrap_or(50).min(DELIVERY_LOG_CAPACITY);
+    let webhook_id = query.webhook_id.as_deref();
+
+    let store = state.webhooks.lock().await;
+    let deliveries = store
+        .log()
+        .filter(|attempt| webhook_id.map_or(true, |id| attempt.webhook_id == id))
+        .take(limit)
+        .map(DeliveryAttemptResponse::from)
+        .collect();
+    let pending = store
+        .pending()
+        .iter()
+        .filter(|delivery| webhook_id.map_or(true, |id| delivery.webhook_id == id))
+        .count();
+
+    Ok(Json(DeliveryLogResponse {
+        deliveries,
+        pending,
+    }))
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+impl From<&DeliveryAttempt> for DeliveryAttemptResponse {
+    fn from(attempt: &DeliveryAttempt) -> Self {
+        Self {
+            delivery_id: attempt.delivery_id.clone(),
+            webhook_id: attempt.webhook_id.clone(),
+            event_id: attempt.event_id,
+            event_type: attempt.event_type,
+            attempt: attempt.attempt,
+            status: attempt.status,
+            status_code: attempt.status_code,
+            error: attempt.error.clone(),
+            attempted_at_utc: attempt.attempted_at.to_rfc3339(),
+            next_attempt_at_utc: attempt.next_attempt_at.map(|at| at.to_rfc3339()),
+        }
+    }
+}
+
+fn to_response(webhook: &Webhook, pending_deliveries: usize) -> WebhookResponse {
+    WebhookResponse {
+        id: webhook.id.clone(),
+        url: webhook.url.clone(),
+        events: webhook.events.clone(),
+        created_at_utc: webhook.created_at.to_rfc3339(),
+        pending_deliveries,
+    }
+}
+
+/// Returns the response for a webhook in `store`.
+#[must_use]
+pub fn webhook_response(store: &WebhookStore, webhook: &Webhook) -> WebhookResponse {
+    to_response(webhook, pending_for(store, &webhook.id))
+}
+
+fn pending_for(store: &WebhookStore, id: &str) -> usize {
+    store
+        .pending()
+        .iter()
+        .filter(|d| d.webhook_id == id)
+        .count()
+}
+
+/// Checks that a webhook exists before queueing a change to it.
+async fn require_webhook(state: &AppState, id: &str) -> ApiResult<()> {
+    if state.webhooks.lock().await.get(id).is_some() {
+        Ok(())
+    } else {
+        Err(not_found(id))
+    }
+}
+
+fn not_found(id: &str) -> ApiError {
+    ApiError::NotFound {
+        error_code: "webhook_not_found".to_string(),
+        message: format!("No webhook with id '{id}'"),
+    }
+}
+
+/// Checks the secret's length.
+fn validate_secret(secret: String) -> ApiResult<SecretString> {
+    let length = secret.chars().count();
+    if !(MIN_SECRET_LENGTH..=MAX_SECRET_LENGTH).contains(&length) {
+        return Err(ApiError::BadRequest {
+            error_code: "invalid_webhook_secret".to_string(),
+            message: format!(
+                "Webhook secret must be {MIN_SECRET_LENGTH} to {MAX_SECRET_LENGTH} characters"
+            ),
+        });
+    }
+    Ok(SecretString::new(secret))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::AppState;
+
+    fn create_request(url: &str) -> CreateWebhookRequest {
+        CreateWebhookRequest {
+            url: url.to_string(),
+            events: vec![EventKind::PassUsed],
+            secret: "correct-horse-battery-staple".to_string(),
+        }
+    }
+
+    fn actor() -> Actor {
+        Actor::new("tester")
+    }
+
+    #[test]
+    fn test_create_request_debug_redacts_secret() {
+        let debug = format!("{:?}", create_request("https://example.com/"));
+        assert!(!debug.contains("battery"));
+        assert!(debug.contains("[REDACTED]"));
+    }
+
+    #[tokio::test]
+    async fn test_webhook_crud() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = AppState::in_dir(dir.path(), None).into_shared();
+
+        let (status, Json(created)) = create_webhook(
+            State(state.clone()),
+            actor(),
+            Json(create_request("https://example.com/hook")),
+        )
+        .await
+        .unwrap();
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!(created.events, [EventKind::PassUsed]);
+
+        let update = UpdateWebhookRequest {
+            url: None,
+            events: Some(Vec::new()),
+            secret: Some("another-long-enough-secret".to_string()),
+        };
+        let Guarded::Applied(updated) = update_webhook(
+            State(state.clone()),
+            actor(),
+            Path(created.id.clone()),
+            Json(update),
+        )
+        .await
+        .unwrap() else {
+            panic!("expected the update to apply");
+        };
+        assert_eq!(updated.url, "https://example.com/hook");
+        assert!(updated.events.is_empty());
+
+        let Json(list) = list_webhooks(State(state.clone())).await.unwrap();
+        assert_eq!(list.webhooks.len(), 1);
+
+        let response = delete_webhook(State(state.clone()), actor(), Path(created.id.clone()))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        let result = get_webhook(State(state.clone()), Path(created.id)).await;
+        assert!(matches!(result, Err(ApiError::NotFound { .. })));
+
+        let audit = state.audit.lock().await;
+        let actions: Vec<_> = audit.read_all().iter().map(|entry| entry.action).collect();
+        assert_eq!(
+            actions,
+            [
+                AuditAction::CreateWebhook,
+                AuditAction::UpdateWebhook,
+                AuditAction::DeleteWebhook
+            ]
+        );
+        assert!(!serde_json::to_string(&audit.read_all())
+            .unwrap()
+            .contains("battery"));
+    }
+
+    #[tokio::test]
+    async fn test_webhook_rejects_non_http_url() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = AppState::in_dir(dir.path(), None).into_shared();
+
+        let result = create_webhook(
+            State(state.clone()),
+            actor(),
+            Json(create_request("ftp://example.com/hook")),
+        )
+        .await;
+        assert!(matches!(
+            result,
+            Err(ApiError::BadRequest { ref error_code, .. }) if error_code == "invalid_webhook_url"
+        ));
+        assert!(state.webhooks.lock().await.webhooks().is_empty());
+
+        let (_, Json(created)) = create_webhook(
+            State(state.clone()),
+            actor(),
+            Json(create_request("https://example.com/hook")),
+        )
+        .await
+        .unwrap();
+        let request = UpdateWebhookRequest {
+            url: Some("javascript:alert(1)".to_string()),
+            events: None,
+            secret: None,
+        };
+        let result = update_webhook(
+            State(state.clone()),
+            actor(),
+            Path(created.id.clone()),
+            Json(request),
+        )
+        .await;
+        assert!(matches!(result, Err(ApiError::BadRequest { .. })));
+        let store = state.webhooks.lock().await;
+        assert_eq!(
+            store.get(&created.id).unwrap().url,
+            "https://example.com/hook"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_create_webhook_rejects_short_secret() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = AppState::in_dir(dir.path(), None).into_shared();
+
+        let mut request = create_request("https://example.com/hook");
+        request.secret = "hunter2".to_string();
+        let result = create_webhook(State(state), actor(), Json(request)).await;
+        assert!(matches!(result, Err(ApiError::BadRequest { .. })));
+    }
+}
@@ -0,0 +1,820 @@
+//! Tamper-evident audit log.
+//!
+//! Every successful mutating API call is appended to `audit.jsonl`, one JSON
+//! [`AuditEntry`] per line, with the actor, the action, and the affected
+//! values before and after the change. The file is only ever appended to.
+//!
+//! The actor name is whatever the client claims and is not authenticated,
+//! so each entry records it as `claimed_actor`, separate from who the
+//! request authenticated as (the partner, by their token) and the address
+//! it came from.
+//!
+//! Only the newest entries are kept in memory; older pages are read from
+//! the file on request, so the log can grow for years on a Pi.
+//!
+//! # Hash Chain
+//!
+//! Each entry stores the hash of the previous entry and its own hash, the
+//! hex SHA-256 of `prev_hash` followed by the entry's other fields as JSON.
+//! Editing, reordering or deleting an entry breaks the chain from that point
+//! on, which [`AuditLog::status`] reports. Truncating the newest entries
+//! leaves a valid chain, so clients that need to detect it should remember
+//! the last `head_hash` they saw and check that it is still in the log.
+
+use std::collections::VecDeque;
+use std::convert::Infallible;
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{IpAddr, SocketAddr};
+use std::path::{Path, PathBuf};
+
+use axum::extract::{ConnectInfo, FromRequestParts};
+use axum::http::request::Parts;
+use axum::http::{header, HeaderMap};
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+use tracing::warn;
+use utoipa::ToSchema;
+
+use crate::access::{self, Access};
+use crate::state::SharedState;
+
+/// Header naming who performs a request.
+pub const ACTOR_HEADER: &str = "x-tether-actor";
+
+/// Actor recorded when a request does not name one.
+pub const ANONYMOUS_ACTOR: &str = "anonymous";
+
+/// Identity of requests that carry the partner token.
+pub const PARTNER_IDENTITY: &str = "partner";
+
+/// Number of newest entries kept in memory.
+const RECENT_ENTRIES: usize = 256;
+
+/// Maximum length of an actor name, in characters.
+const MAX_ACTOR_LENGTH: usize = 64;
+
+/// `prev_hash` of the first entry.
+pub const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+// ============================================================================
+// Errors
+// ============================================================================
+
+/// Errors from reading or appending to the audit log.
+#[derive(Debug, Error)]
+pub enum AuditError {
+    /// Failed to read the audit log.
+    #[error("Failed to read audit log {path}: {source}")]
+    ReadError {
+        /// Path of the audit log.
+        path: PathBuf,
+        /// The underlying I/O error.
+        #[source]
+        source: io::Error,
+    },
+
+    /// Failed to append to the audit log.
+    #[error("Failed to write audit log {path}: {source}")]
+    WriteError {
+        /// Path of the audit log.
+        path: PathBuf,
+        /// The underlying I/O error.
+        #[source]
+        source: io::Error,
+    },
+
+    /// Failed to serialize an entry.
+    #[error("Failed to serialize audit entry: {0}")]
+    SerializeError(#[from] serde_json::Error),
+}
+
+/// Result type for audit log operations.
+pub type AuditResult<T> = Result<T, AuditError>;
+
+// ============================================================================
+// Actor
+// ============================================================================
+
+/// Who performed an action.
+///
+/// tether has no user accounts, so clients name themselves, such as
+/// `web-ui` or `home-assistant`, in the `X-Tether-Actor` header. Requests
+/// without one are recorded as `anonymous`. Any client can claim any name,
+/// so the claimed name is recorded together with the identity the request
+/// authenticated as, if any, such as `partner` or `token:<name>` for an API
+/// token (see [`crate::access`]), and the peer address of the request, when it
+/// came over the network.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Actor {
+    name: String,
+    identity: Option<String>,
+    peer: Option<IpAddr>,
+}
+
+impl Actor {
+    /// Creates an actor, dropping control characters and truncating long names.
+    #[must_use]
+    pub fn new(name: &str) -> Self {
+        let name: String = name
+            .trim()
+            .chars()
+            .filter(|c| !c.is_control())
+            .take(MAX_ACTOR_LENGTH)
+            .collect();
+        let name = if name.is_empty() {
+            ANONYMOUS_ACTOR.to_string()
+        } else {
+            name
+        };
+        Self {
+            name,
+            identity: None,
+            peer: None,
+        }
+    }
+
+    /// Sets who the actor's request authenticated as.
+    #[must_use]
+    pub fn with_identity(mut self, identity: &str) -> Self {
+        self.identity = Some(identity.to_string());
+        self
+    }
+
+    /// Sets the address the actor's request came from.
+    #[must_use]
+    pub const fn with_peer(mut self, peer: IpAddr) -> Self {
+        self.peer = Some(peer);
+        self
+    }
+
+    /// Returns the actor's name, as claimed by the client.
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns who the actor's request authenticated as, if anyone.
+    #[must_use]
+    pub fn identity(&self) -> Option<&str> {
+        self.identity.as_deref()
+    }
+
+    /// Returns the address the actor's request came from, if known.
+    ///
+    /// Requests relayed by the tunnel come from a loopback address.
+    #[must_use]
+    pub const fn peer(&self) -> Option<IpAddr> {
+        self.peer
+    }
+}
+
+impl FromRequestParts<SharedState> for Actor {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &SharedState,
+    ) -> Result<Self, Self::Rejection> {
+        let name = parts
+            .headers
+            .get(ACTOR_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or_default();
+        let mut actor = Self::new(name);
+        if let Some(ConnectInfo(addr)) = parts.extensions.get::<ConnectInfo<SocketAddr>>() {
+            actor = actor.with_peer(addr.ip());
+        }
+        let access = match parts.extensions.get::<Access>() {
+            Some(access) => access.clone(),
+            None => access::resolve(state, &parts.headers).await,
+        };
+        if let Some(identity) = &access.identity {
+            actor = actor.with_identity(identity);
+        }
+        Ok(actor)
+    }
+}
+
+/// Returns the token of an `Authorization: Bearer <token>` header.
+pub fn bearer_token(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+}
+
+// ============================================================================
+// Entries
+// ============================================================================
+
+/// A mutating action recorded in the audit log.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditAction {
+    /// `PUT /api/config/bluetooth`
+    UpdateBluetooth,
+    /// `POST /api/devices/pair`
+    PairDevice,
+    /// `PUT /api/config/wifi`
+    UpdateWifi,
+    /// `PUT /api/config/timezone`
+    UpdateTimezone,
+    /// `PUT /api/config/curfew`
+    UpdateCurfew,
+    /// `PUT /api/config/passes`
+    UpdatePassesPerMonth,
+    /// `PUT /api/config/onboarding/complete`
+    CompleteOnboarding,
+    /// `POST /api/passes/use`, or the MQTT pass command
+    UsePass,
+    /// `POST /api/system/restart`
+    Restart,
+    /// `POST /api/webhooks`
+    CreateWebhook,
+    /// `PUT /api/webhooks/{id}`
+    UpdateWebhook,
+    /// `DELETE /api/webhooks/{id}`
+    DeleteWebhook,
+    /// A change to a guarded setting that now waits for approval
+    RequestChange,
+    /// `POST /api/guard/changes/{id}/approve`
+    ApproveChange,
+    /// `POST /api/guard/changes/{id}/reject`
+    RejectChange,
+    /// `POST /api/guard/changes/{id}/cancel`
+    CancelChange,
+}
+
+/// One recorded action.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[schema(example = json!({
+    "seq": 12,
+    "recorded_at_utc": "2025-01-15T03:30:00Z",
+    "claimed_actor": "web-ui",
+    "peer_addr": "192.168.1.23",
+    "action": "update_passes_per_month",
+    "before": {"per_month": 3},
+    "after": {"per_month": 5, "pending": true},
+    "prev_hash": "9f2c4b0e5d1a7c3e8b6f4a2d0c9e7b5a3f1d8c6e4b2a0f9e7d5c3b1a9f8e7d6c",
+    "hash": "4e1d9c7b5a3f2e0d8c6b4a2f1e9d7c5b3a1f0e8d6c4b2a9f7e5d3c1b0a8f6e4d"
+}))]
+pub struct AuditEntry {
+    /// Position in the log, starting at 1.
+    pub seq: u64,
+
+    /// When the action was recorded.
+    pub recorded_at_utc: String,
+
+    /// Who performed the action, from the `X-Tether-Actor` request header.
+    /// Claimed by the client and not authenticated: any client can send any
+    /// name, including `partner`.
+    #[serde(alias = "actor")]
+    pub claimed_actor: String,
+
+    /// Who the request authenticated as, such as `partner` for a request
+    /// carrying the partner token. Absent for unauthenticated requests.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub authenticated_as: Option<String>,
+
+    /// IP address the request came from, if it came over the network.
+    /// Requests relayed by the tunnel come from a loopback address.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub peer_addr: Option<String>,
+
+    /// What was done.
+    pub action: AuditAction,
+
+    /// Affected values before the action, if any.
+    #[schema(value_type = Option<Object>)]
+    pub before: Option<Value>,
+
+    /// Affected values after the action, if any.
+    #[schema(value_type = Option<Object>)]
+    pub after: Option<Value>,
+
+    /// Hash of the previous entry, or [`GENESIS_HASH`] for the first.
+    pub prev_hash: String,
+
+    /// Hash of this entry.
+    pub hash: String,
+}
+
+/// The hashed fields of an entry, in a fixed order.
+#[derive(Serialize)]
+struct HashedFields<'a> {
+    seq: u64,
+    recorded_at_utc: &'a str,
+    // Hashed under its original name, so older entries still verify
+    actor: &'a str,
+    // Omitted when absent, so entries written before it was recorded still
+    // verify
+    #[serde(skip_serializing_if = "Option::is_none")]
+    peer_addr: &'a Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    authenticated_as: &'a Option<String>,
+    action: AuditAction,
+    before: &'a Option<Value>,
+    after: &'a Option<Value>,
+}
+
+impl AuditEntry {
+    /// Computes the hash this entry should have.
+    fn compute_hash(&self) -> AuditResult<String> {
+        let fields = serde_json::to_vec(&HashedFields {
+            seq: self.seq,
+            recorded_at_utc: &self.recorded_at_utc,
+            actor: &self.claimed_actor,
+            peer_addr: &self.peer_addr,
+            authenticated_as: &self.authenticated_as,
+            action: self.action,
+            before: &self.before,
+            after: &self.after,
+        })?;
+        let mut hasher = Sha256::new();
+        hasher.update(self.prev_hash.as_bytes());
+        hasher.update(&fields);
+        Ok(hex::encode(hasher.finalize()))
+    }
+}
+
+/// Converts a response DTO into an entry's `before` or `after` value.
+///
+/// Record DTOs rather than configuration, which holds secrets.
+pub fn snapshot<T: Serialize>(value: &T) -> Option<Value> {
+    serde_json::to_value(value).ok()
+}
+
+/// Result of verifying the hash chain.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct AuditChainStatus {
+    /// Whether every entry links to the one before it and matches its hash.
+    pub valid: bool,
+
+    /// Line of the audit log, starting at 1, where the chain first breaks.
+    pub first_invalid_line: Option<usize>,
+
+    /// Number of entries in the log.
+    pub entries: usize,
+
+    /// Hash of the newest entry, or [`GENESIS_HASH`] if the log is empty.
+    pub head_hash: String,
+}
+
+// ============================================================================
+// Log
+// ============================================================================
+
+/// A page of entries read from the log.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuditPage {
+    /// Entries, newest first.
+    pub entries: Vec<AuditEntry>,
+
+    /// Whether there are older entries than the last one returned.
+    pub has_more: bool,
+}
+
+/// The audit log file, its chain head and its newest entries.
+///
+/// Appends only need the head of the chain, so older entries stay on disk
+/// and are read back by [`AuditLog::page`] when asked for.
+pub struct AuditLog {
+    path: PathBuf,
+    /// The newest entries, at most [`RECENT_ENTRIES`], oldest first.
+    recent: VecDeque<AuditEntry>,
+    /// `seq` and hash of the newest entry.
+    head: Option<(u64, String)>,
+    /// Number of entries in the file.
+    len: usize,
+    first_invalid_line: Option<usize>,
+    /// The file ends in a partial line, left by a crash mid-append.
+    torn: bool,
+}
+
+impl AuditLog {
+    /// Opens the audit log at `path` and verifies its chain.
+    ///
+    /// A missing file is an empty log. A broken chain is logged and
+    /// reported by [`AuditLog::status`]; it does not prevent appending.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file exists but cannot be read.
+    pub fn open(path: impl Into<PathBuf>) -> AuditResult<Self> {
+        let path = path.into();
+        let mut log = Self {
+            path: path.clone(),
+            recent: VecDeque::new(),
+            head: None,
+            len: 0,
+            first_invalid_line: None,
+            torn: false,
+        };
+
+        let torn = Self::read_lines(&path, |index, entry| {
+            let valid = entry.as_ref().is_some_and(|entry| log.links(entry));
+            if !valid && log.first_invalid_line.is_none() {
+                log.first_invalid_line = Some(index + 1);
+            }
+            if let Some(entry) = entry {
+                log.push_recent(entry);
+            }
+        })
+        .map_err(|source| AuditError::ReadError {
+            path: log.path.clone(),
+            source,
+        })?;
+        log.torn = torn;
+
+        if let Some(line) = log.first_invalid_line {
+            warn!(path = %log.path.display(), line, "Audit log hash chain is broken");
+        }
+        Ok(log)
+    }
+
+    /// Calls `f` with the index and parsed entry of every line of the file
+    /// at `path`, and returns whether the file ends in a partial line.
+    ///
+    /// Lines that are not valid entries are passed as `None`.
+    fn read_lines(path: &Path, mut f: impl FnMut(usize, Option<AuditEntry>)) -> io::Result<bool> {
+        let file = match File::open(path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(false),
+            Err(e) => return Err(e),
+        };
+        let mut reader = BufReader::new(file);
+        let mut line = Vec::new();
+        let mut index = 0;
+        loop {
+            line.clear();
+            if reader.read_until(b'\n', &mut line)? == 0 {
+                return Ok(false);
+            }
+            let complete = line.last() == Some(&b'\n');
+            f(index, serde_json::from_slice(&line).ok());
+            if !complete {
+                return Ok(true);
+            }
+            index += 1;
+        }
+    }
+
+    /// Adds an entry to the in-memory tail, dropping the oldest if it is full.
+    fn push_recent(&mut self, entry: AuditEntry) {
+        self.head = Some((entry.seq, entry.hash.clone()));
+        self.len += 1;
+        if self.recent.len() == RECENT_ENTRIES {
+            self.recent.pop_front();
+        }
+        self.recent.push_back(entry);
+    }
+
+    /// Returns the `seq` the next entry gets.
+    fn next_seq(&self) -> u64 {
+        self.head.as_ref().map_or(1, |(seq, _)| seq + 1)
+    }
+
+    /// Returns whether `entry` correctly follows the newest entry.
+    fn links(&self, entry: &AuditEntry) -> bool {
+        entry.seq == self.next_seq()
+            && entry.prev_hash == self.head_hash()
+            && entry.compute_hash().is_ok_and(|hash| hash == entry.hash)
+    }
+
+    /// Returns the newest entries kept in memory, oldest first.
+    #[cfg(test)]
+    pub(crate) const fn recent(&self) -> &VecDeque<AuditEntry> {
+        &self.recent
+    }
+
+    /// Returns up to `limit` entries with a `seq` below `before`, newest first.
+    ///
+    /// Served from memory when possible, otherwise read from the file.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file has to be read and cannot be.
+    pub fn page(&self, before: Option<u64>, limit: usize) -> AuditResult<AuditPage> {
+        let matches = |entry: &AuditEntry| before.map_or(true, |before| entry.seq < before);
+
+        // One more than asked for tells whether there are older entries
+        let mut found: Vec<AuditEntry> = self
+            .recent
+            .iter()
+            .rev()
+            .filter(|entry| matches(entry))
+            .take(limit + 1)
+            .cloned()
+            .collect();
+
+        if found.len() <= limit && self.recent.len() < self.len {
+            let mut older = VecDeque::with_capacity(limit + 1);
+            Self::read_lines(&self.path, |_, entry| {
+                if let Some(entry) = entry.filter(|entry| matches(entry)) {
+                    if older.len() == limit + 1 {
+                        older.pop_front();
+                    }
+                    older.push_back(entry);
+                }
+            })
+            .map_err(|source| AuditError::ReadError {
+                path: self.path.clone(),
+                source,
+            })?;
+            found = older.into_iter().rev().collect();
+        }
+
+        let has_more = found.len() > limit;
+        found.truncate(limit);
+        Ok(AuditPage {
+            entries: found,
+            has_more,
+        })
+    }
+
+    /// Returns the hash of the newest entry.
+    #[must_use]
+    pub fn head_hash(&self) -> &str {
+        self.head.as_ref().map_or(GENESIS_HASH, |(_, hash)| hash)
+    }
+
+    /// Returns the result of verifying the chain.
+    #[must_use]
+    pub fn status(&self) -> AuditChainStatus {
+        AuditChainStatus {
+            valid: self.first_invalid_line.is_none(),
+            first_invalid_line: self.first_invalid_line,
+            entries: self.len,
+            head_hash: self.head_hash().to_string(),
+        }
+    }
+
+    /// Appends an entry and flushes it to disk.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the entry cannot be written; it is then not added.
+    pub fn append(
+        &mut self,
+        actor: &Actor,
+        action: AuditAction,
+        before: Option<Value>,
+        after: Option<Value>,
+        now: DateTime<Utc>,
+    ) -> AuditResult<()> {
+        let mut entry = AuditEntry {
+            seq: self.next_seq(),
+            recorded_at_utc: now.to_rfc3339_opts(SecondsFormat::Millis, true),
+            claimed_actor: actor.as_str().to_string(),
+            authenticated_as: actor.identity().map(str::to_string),
+            peer_addr: actor.peer().map(|peer| peer.to_string()),
+            action,
+            before,
+            after,
+            prev_hash: self.head_hash().to_string(),
+            hash: String::new(),
+        };
+        entry.hash = entry.compute_hash()?;
+
+        let mut line = serde_json::to_string(&entry)?;
+        line.push('\n');
+        if self.torn {
+            // Keep the partial line separate from the new entry
+            line.insert(0, '\n');
+        }
+        self.write_line(&line)
+            .map_err(|source| AuditError::WriteError {
+                path: self.path.clone(),
+                source,
+            })?;
+
+        self.torn = false;
+        self.push_recent(entry);
+        Ok(())
+    }
+
+    /// Reads every entry from the file, oldest first.
+    #[cfg(test)]
+    pub(crate) fn read_all(&self) -> Vec<AuditEntry> {
+        let mut entries = Vec::new();
+        Self::read_lines(&self.path, |_, entry| entries.extend(entry)).unwrap();
+        entries
+    }
+
+    fn write_line(&self, line: &str) -> io::Result<()> {
+        let mut options = OpenOptions::new();
+        options.append(true).create(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        let mut file: File = options.open(&self.path)?;
+        file.write_all(line.as_bytes())?;
+        file.sync_data()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::AppState;
+    use serde_json::json;
+    use tether_core::SecretString;
+
+    fn append(log: &mut AuditLog, action: AuditAction, after: Value) {
+        log.append(&Actor::new("tester"), action, None, Some(after), Utc::now())
+            .unwrap();
+    }
+
+    #[test]
+    fn test_actor_is_sanitized() {
+        assert_eq!(Actor::new("  web-ui\n").as_str(), "web-ui");
+        assert_eq!(Actor::new("").as_str(), ANONYMOUS_ACTOR);
+        assert_eq!(
+            Actor::new(&"x".repeat(100)).as_str().len(),
+            MAX_ACTOR_LENGTH
+        );
+    }
+
+    #[tokio::test]
+    async fn test_actor_records_peer_address() {
+        let mut parts = axum::http::Request::builder()
+            .header(ACTOR_HEADER, "web-ui")
+            .body(())
+            .unwrap()
+            .into_parts()
+            .0;
+        let addr: SocketAddr = "192.168.1.23:51234".parse().unwrap();
+        parts.extensions.insert(ConnectInfo(addr));
+        let dir = tempfile::tempdir().unwrap();
+        let state = AppState::in_dir(dir.path(), None).into_shared();
+        let actor = Actor::from_request_parts(&mut parts, &state).await.unwrap();
+        assert_eq!(actor.as_str(), "web-ui");
+        assert_eq!(actor.peer(), Some(addr.ip()));
+        assert_eq!(actor.identity(), None);
+
+        let path = dir.path().join("audit.jsonl");
+        let mut log = AuditLog::open(&path).unwrap();
+        append(&mut log, AuditAction::UsePass, json!({"remaining": 2}));
+        log.append(&actor, AuditAction::UsePass, None, None, Utc::now())
+            .unwrap();
+        let entries = AuditLog::open(&path).unwrap().read_all();
+        assert_eq!(entries[0].peer_addr, None);
+        assert_eq!(entries[1].peer_addr.as_deref(), Some("192.168.1.23"));
+        assert!(log.status().valid);
+    }
+
+    #[test]
+    fn test_append_chains_entries_and_survives_reopen() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("audit.jsonl");
+
+        let mut log = AuditLog::open(&path).unwrap();
+        assert_eq!(log.head_hash(), GENESIS_HASH);
+        append(
+            &mut log,
+            AuditAction::UpdateTimezone,
+            json!({"timezone": "UTC"}),
+        );
+        append(&mut log, AuditAction::UsePass, json!({"remaining": 2}));
+        let entries = log.read_all();
+        let (first, second) = (&entries[0], &entries[1]);
+        assert_eq!((first.seq, second.seq), (1, 2));
+        assert_eq!(first.prev_hash, GENESIS_HASH);
+        assert_eq!(second.prev_hash, first.hash);
+
+        let reopened = AuditLog::open(&path).unwrap();
+        assert_eq!(reopened.recent(), log.recent());
+        assert!(reopened.status().valid);
+        assert_eq!(reopened.status().head_hash, second.hash);
+    }
+
+    #[test]
+    fn test_detects_edits_and_deletions() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("audit.jsonl");
+        let mut log = AuditLog::open(&path).unwrap();
+        for remaining in [2, 1, 0] {
+            append(
+                &mut log,
+                AuditAction::UsePass,
+                json!({"remaining": remaining}),
+            );
+        }
+        let original = std::fs::read_to_string(&path).unwrap();
+        let lines: Vec<&str> = original.lines().collect();
+
+        // Deleting the middle entry
+        std::fs::write(&path, format!("{}\n{}\n", lines[0], lines[2])).unwrap();
+        let status = AuditLog::open(&path).unwrap().status();
+        assert!(!status.valid);
+        assert_eq!(status.first_invalid_line, Some(2));
+
+        // Rewriting a value
+        std::fs::write(
+            &path,
+            original.replace("\"remaining\":1", "\"remaining\":3"),
+        )
+        .unwrap();
+        let status = AuditLog::open(&path).unwrap().status();
+        assert_eq!(status.first_invalid_line, Some(2));
+    }
+
+    #[test]
+    fn test_append_after_torn_write() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("audit.jsonl");
+        let mut log = AuditLog::open(&path).unwrap();
+        append(&mut log, AuditAction::UsePass, json!({"remaining": 2}));
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(b"{\"seq\":2,\"reco").unwrap();
+
+        let mut log = AuditLog::open(&path).unwrap();
+        assert_eq!(log.status().first_invalid_line, Some(2));
+        append(&mut log, AuditAction::UsePass, json!({"remaining": 1}));
+
+        // The new entry is intact and still chains to the last good entry
+        let log = AuditLog::open(&path).unwrap();
+        let entries = log.read_all();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[1].prev_hash, entries[0].hash);
+        assert_eq!(log.status().first_invalid_line, Some(2));
+    }
+
+    #[tokio::test]
+    async fn test_actor_authenticates_partner_token() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = AppState::in_dir(dir.path(), None).into_shared();
+        let token_id = state
+            .secrets
+            .lock()
+            .await
+            .insert(&SecretString::new("ask-before-you-loosen"))
+            .unwrap();
+        state.config.write().await.guard.partner_token_id = Some(token_id);
+
+        let parts = |token: &str| {
+            axum::http::Request::builder()
+                .header(ACTOR_HEADER, "partner")
+                .header(header::AUTHORIZATION, format!("Bearer {token}"))
+                .body(())
+                .unwrap()
+                .into_parts()
+                .0
+        };
+
+        // Claiming to be the partner isn't enough
+        let actor = Actor::from_request_parts(&mut parts("guess"), &state)
+            .await
+            .unwrap();
+        assert_eq!(actor.as_str(), "partner");
+        assert_eq!(actor.identity(), None);
+
+        let actor = Actor::from_request_parts(&mut parts("ask-before-you-loosen"), &state)
+            .await
+            .unwrap();
+        assert_eq!(actor.identity(), Some(PARTNER_IDENTITY));
+
+        let mut log = AuditLog::open(dir.path().join("audit.jsonl")).unwrap();
+        log.append(&actor, AuditAction::ApproveChange, None, None, Utc::now())
+            .unwrap();
+        let entry = &log.read_all()[0];
+        assert_eq!(entry.claimed_actor, "partner");
+        assert_eq!(entry.authenticated_as.as_deref(), Some(PARTNER_IDENTITY));
+        assert_eq!(entry.compute_hash().unwrap(), entry.hash);
+    }
+
+    #[test]
+    fn test_pages_beyond_recent_entries_from_disk() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("audit.jsonl");
+        let mut log = AuditLog::open(&path).unwrap();
+        let total = RECENT_ENTRIES as u64 + 10;
+        for seq in 1..=total {
+            append(&mut log, AuditAction::UsePass, json!({ "seq": seq }));
+        }
+
+        let log = AuditLog::open(&path).unwrap();
+        assert_eq!(log.recent().len(), RECENT_ENTRIES);
+        assert_eq!(log.status().entries, RECENT_ENTRIES + 10);
+        assert!(log.status().valid);
+
+        let seqs = |page: &AuditPage| page.entries.iter().map(|e| e.seq).collect::<Vec<_>>();
+        let newest = log.page(None, 3).unwrap();
+        assert_eq!(seqs(&newest), [total, total - 1, total - 2]);
+        assert!(newest.has_more);
+
+        let oldest = log.page(Some(13), 20).unwrap();
+        assert_eq!(seqs(&oldest), (1..=12).rev().collect::<Vec<_>>());
+        assert!(!oldest.has_more);
+
+        let straddling = log.page(Some(15), 3).unwrap();
+        assert_eq!(seqs(&straddling), [14, 13, 12]);
+        assert!(straddling.has_more);
+    }
+}
@@ -0,0 +1,568 @@
+//! Server-wide event bus.
+//!
+//! Components publish typed [`ServerEvent`]s to the [`EventBus`] whenever
+//! something the web UI displays changes, and `GET /api/events` streams
+//! them to clients as Server-Sent Events so they don't have to poll.
+//!
+//! Every event gets an increasing id. Ids start from the server's start
+//! time, so ids from before a restart are always lower than new ones, and
+//! a stale `Last-Event-ID` is never mistaken for a recent one. The most
+//! recent events are kept
+//! in a bounded ring buffer, and a client that reconnects with
+//! `Last-Event-ID` receives the events it missed. If those events have
+//! already been dropped from the buffer, the client is told to resync
+//! instead and should refetch its state.
+//!
+//! Background tasks publish the events nothing else would: `month_reset` at
+//! the start of a month, and `curfew_started` and `curfew_ended` as the
+//! configured curfew (see [`tether_core::CurfewConfig`]) begins and ends.
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::time::Duration;
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use tether_core::{CurfewConfig, PassManager, PassResult};
+use tokio::sync::broadcast;
+use tracing::{info, warn};
+use utoipa::ToSchema;
+
+use crate::guard::{ChangeResolution, GuardedSetting};
+use crate::state::SharedState;
+use crate::supervisor::ScannerState;
+
+/// Number of recent events kept for clients resuming with `Last-Event-ID`.
+pub const EVENT_BUFFER_CAPACITY: usize = 256;
+
+/// How often the month is checked so passes reset without a request.
+const MONTH_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// How often the curfew is checked for starting or ending.
+const CURFEW_CHECK_INTERVAL: Duration = Duration::from_secs(15);
+
+// ============================================================================
+// Events
+// ============================================================================
+
+/// Configuration section that was changed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ConfigSection {
+    /// Bluetooth target device and scan settings.
+    Bluetooth,
+    /// WiFi networks.
+    Wifi,
+    /// Timezone used for pass resets and the curfew.
+    Timezone,
+    /// Passes per month.
+    Passes,
+    /// Nightly curfew.
+    Curfew,
+    /// Onboarding was completed.
+    Onboarding,
+}
+
+/// Type of a [`ServerEvent`], used to filter events.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum EventKind {
+    /// See [`ServerEvent::ProximityChanged`].
+    ProximityChanged,
+    /// See [`ServerEvent::PassUsed`].
+    PassUsed,
+    /// See [`ServerEvent::MonthReset`].
+    MonthReset,
+    /// See [`ServerEvent::ConfigChanged`].
+    ConfigChanged,
+    /// See [`ServerEvent::ScannerHealthChanged`].
+    ScannerHealthChanged,
+    /// See [`ServerEvent::ChangeRequested`].
+    ChangeRequested,
+    /// See [`ServerEvent::ChangeResolved`].
+    ChangeResolved,
+    /// See [`ServerEvent::CurfewStarted`].
+    CurfewStarted,
+    /// See [`ServerEvent::CurfewEnded`].
+    CurfewEnded,
+}
+
+impl EventKind {
+    /// Returns the name of the event type, as used in the `type` field.
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::ProximityChanged => "proximity_changed",
+            Self::PassUsed => "pass_used",
+            Self::MonthReset => "month_reset",
+            Self::ConfigChanged => "config_changed",
+            Self::ScannerHealthChanged => "scanner_health_changed",
+            Self::ChangeRequested => "change_requested",
+            Self::ChangeResolved => "change_resolved",
+            Self::CurfewStarted => "curfew_started",
+            Self::CurfewEnded => "curfew_ended",
+        }
+    }
+}
+
+/// Something that changed on the server.
+///
+/// Serialized with a `type` field naming the event, which is also used as
+/// the SSE event name.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ServerEvent {
+    /// The phone moved in or out of range of the configured threshold.
+    ProximityChanged {
+        /// Whether the phone is now nearby.
+        is_nearby: bool,
+        /// Signal strength of the phone, if it was seen.
+        rssi_dbm: Option<i16>,
+        /// Address of the target device.
+        device_address: String,
+    },
+    /// A pass was used.
+    PassUsed {
+        /// Passes remaining this month.
+        remaining: u32,
+        /// When the pass was used.
+        used_at_utc: String,
+        /// Reason recorded for the pass.
+        reason: String,
+    },
+    /// A new month started and passes were replenished.
+    MonthReset {
+        /// The new month in YYYY-MM format.
+        month: String,
+        /// Passes remaining after the reset.
+        remaining: u32,
+        /// Passes granted per month.
+        per_month: u32,
+    },
+    /// A configuration section was updated.
+    ConfigChanged {
+        /// The section that changed.
+        section: ConfigSection,
+    },
+    /// The Bluetooth scanner's state changed.
+    ScannerHealthChanged {
+        /// The new state.
+        state: ScannerState,
+        /// The state before the change.
+        previous: ScannerState,
+        /// The most recent scanner error, if any.
+        last_error: Option<String>,
+    },
+    /// A change to a guarded setting is waiting for approval.
+    ChangeRequested {
+        /// Id of the pending change.
+        change_id: String,
+        /// The setting the change modifies.
+        setting: GuardedSetting,
+        /// Who requested the change.
+        requested_by: String,
+        /// When the change takes effect without approval, if ever.
+        effective_at_utc: Option<String>,
+    },
+    /// A pending change was applied, rejected or cancelled.
+    ChangeResolved {
+        /// Id of the pending change.
+        change_id: String,
+        /// The setting the change modifies.
+        setting: GuardedSetting,
+        /// How the change was resolved.
+        resolution: ChangeResolution,
+    },
+    /// The nightly curfew began.
+    CurfewStarted {
+        /// When the curfew started (`HH:MM` in the configured timezone).
+        start: String,
+        /// When it ends (`HH:MM` in the configured timezone).
+        end: String,
+    },
+    /// The curfew ended, or was disabled or moved while in effect.
+    CurfewEnded {
+        /// When the curfew starts (`HH:MM` in the configured timezone).
+        start: String,
+        /// When it ended (`HH:MM` in the configured timezone).
+        end: String,
+    },
+}
+
+impl ServerEvent {
+    /// Returns the event's type.
+    #[must_use]
+    pub const fn kind(&self) -> EventKind {
+        match self {
+            Self::ProximityChanged { .. } => EventKind::ProximityChanged,
+            Self::PassUsed { .. } => EventKind::PassUsed,
+            Self::MonthReset { .. } => EventKind::MonthReset,
+            Self::ConfigChanged { .. } => EventKind::ConfigChanged,
+            Self::ScannerHealthChanged { .. } => EventKind::ScannerHealthChanged,
+            Self::ChangeRequested { .. } => EventKind::ChangeRequested,
+            Self::ChangeResolved { .. } => EventKind::ChangeResolved,
+            Self::CurfewStarted { .. } => EventKind::CurfewStarted,
+            Self::CurfewEnded { .. } => EventKind::CurfewEnded,
+        }
+    }
+
+    /// Returns the event's type name, used as the SSE event name.
+    #[must_use]
+    pub const fn name(&self) -> &'static str {
+        self.kind().as_str()
+    }
+}
+
+/// A published event, as sent in the `data` field of `GET /api/events`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[schema(example = json!({
+    "id": 1_736_911_800_000_001_u64,
+    "emitted_at_utc": "2025-01-15T03:30:00Z",
+    "type": "pass_used",
+    "remaining": 2,
+    "used_at_utc": "2025-01-15T03:30:00Z",
+    "reason": "On-call for production incident tonight"
+}))]
+pub struct EventMessage {
+    /// Sequential event id, also sent as the SSE `id`.
+    pub id: u64,
+
+    /// When the event was published.
+    #[schema(example = "2025-01-15T03:30:00Z")]
+    pub emitted_at_utc: String,
+
+    /// The event itself.
+    #[serde(flatten)]
+    pub event: ServerEvent,
+}
+
+// ============================================================================
+// Event Bus
+// ============================================================================
+
+/// Events a subscriber missed since the id it last saw.
+#[derive(Debug)]
+pub enum Missed {
+    /// The missed events, oldest first (empty when none were missed).
+    Events(Vec<Arc<EventMessage>>),
+    /// Some missed events are no longer buffered.
+    ///
+    /// Carries the id of the latest event, if any, so the client can resume
+    /// from there after refetching its state.
+    Gap(Option<u64>),
+}
+
+/// A new subscription to the event bus.
+#[derive(Debug)]
+pub struct Subscription {
+    /// Events published before subscribing that the client hasn't seen.
+    pub missed: Missed,
+    /// Receives events published after subscribing.
+    pub receiver: broadcast::Receiver<Arc<EventMessage>>,
+}
+
+/// Ring buffer of recent events.
+#[derive(Debug)]
+struct History {
+    next_id: u64,
+    events: VecDeque<Arc<EventMessage>>,
+    capacity: usize,
+}
+
+#[derive(Debug)]
+struct Inner {
+    sender: broadcast::Sender<Arc<EventMessage>>,
+    history: Mutex<History>,
+}
+
+/// Publishes server events to any number of subscribers.
+///
+/// Cheap to clone; clones share the same subscribers and buffer.
+#[derive(Debug, Clone)]
+pub struct EventBus {
+    inner: Arc<Inner>,
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EventBus {
+    /// Creates an event bus buffering [`EVENT_BUFFER_CAPACITY`] events.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::with_capacity(EVENT_BUFFER_CAPACITY)
+    }
+
+    /// Creates an event bus buffering up to `capacity` events.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is zero.
+    #[must_use]
+    pub fn with_capacity(capacity: usize) -> Self {
+        let (sender, _) = broadcast::channel(capacity);
+        // Start from the current time so ids keep increasing across restarts
+        let first_id = u64::try_from(Utc::now().timestamp_micros()).unwrap_or(0) + 1;
+        Self {
+            inner: Arc::new(Inner {
+                sender,
+                history: Mutex::new(History {
+                    next_id: first_id,
+                    events: VecDeque::with_capacity(capacity),
+                    capacity,
+                }),
+            }),
+        }
+    }
+
+    fn history(&self) -> MutexGuard<'_, History> {
+        self.inner
+            .history
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Publishes an event to all subscribers and returns its id.
+    pub fn publish(&self, event: ServerEvent) -> u64 {
+        // Holding the buffer lock while sending keeps subscribe() from
+        // seeing an event both in the buffer and on its receiver
+        let mut history = self.history();
+        let id = history.next_id;
+        history.next_id += 1;
+
+        let message = Arc::new(EventMessage {
+            id,
+            emitted_at_utc: Utc::now().to_rfc3339(),
+            event,
+        });
+        if history.events.len() == history.capacity {
+            history.events.pop_front();
+        }
+        history.events.push_back(message.clone());
+
+        // Sending only fails when nobody is subscribed
+        let _ = self.inner.sender.send(message);
+        id
+    }
+
+    /// Subscribes to events published from now on.
+    ///
+    /// With `last_event_id`, also returns the buffered events after that id.
+    #[must_use]
+    pub fn subscribe(&self, last_event_id: Option<u64>) -> Subscription {
+        let history = self.history();
+        let receiver = self.inner.sender.subscribe();
+
+        let missed = match last_event_id {
+            None => Missed::Events(Vec::new()),
+            Some(last) => {
+                let latest = history.next_id - 1;
+                let oldest = history.events.front().map_or(history.next_id, |e| e.id);
+                if last > latest || last + 1 < oldest {
+                    let latest = history.events.back().map(|e| e.id);
+                    Missed::Gap(latest)
+                } else {
+                    let events = history.events.iter().filter(|e| e.id > last).cloned();
+                    Missed::Events(events.collect())
+                }
+            }
+        };
+
+        Subscription { missed, receiver }
+    }
+}
+
+// ============================================================================
+// Month Reset
+// ============================================================================
+
+/// Spawns a background task that resets passes when a new month starts.
+pub fn spawn_month_watcher(state: SharedState) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(MONTH_CHECK_INTERVAL);
+        loop {
+            interval.tick().await;
+            check_month_reset(&state).await;
+        }
+    })
+}
+
+/// Resets passes if the month changed, logging any failure.
+async fn check_month_reset(state: &SharedState) {
+    let mut pass_manager = state.pass_manager.write().await;
+    if let Err(e) = reset_month_if_needed(&state.events, &mut pass_manager) {
+        warn!(error = %e, "Failed to reset passes for the new month");
+    }
+}
+
+/// Resets passes if a new month started and publishes a `month_reset` event.
+///
+/// Handlers call this before pass operations, which would otherwise reset
+/// the month without anyone noticing.
+///
+/// # Errors
+///
+/// Returns an error if the reset passes cannot be saved.
+pub fn reset_month_if_needed(events: &EventBus, pass_manager: &mut PassManager) -> PassResult<()> {
+    let month = pass_manager.current_month().to_string();
+    pass_manager.maybe_reset_month(None)?;
+
+    if pass_manager.current_month() != month {
+        info!(
+            month = pass_manager.current_month(),
+            "Passes reset for the new month"
+        );
+        events.publish(ServerEvent::MonthReset {
+            month: pass_manager.current_month().to_string(),
+            remaining: pass_manager.remaining(),
+            per_month: pass_manager.per_month(),
+        });
+    }
+    Ok(())
+}
+
+// ============================================================================
+// Curfew
+// ============================================================================
+
+/// Spawns a background task that publishes when the curfew starts and ends.
+///
+/// The state at startup is taken as given, so restarting during the night
+/// doesn't announce a curfew that already started.
+pub fn spawn_curfew_watcher(state: SharedState) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(CURFEW_CHECK_INTERVAL);
+        let mut active = None;
+        loop {
+            interval.tick().await;
+            let (curfew, timezone) = {
+                let config = state.config.read().await;
+                (config.curfew.clone(), config.system.timezone.clone())
+            };
+            let now_active = curfew.is_active_at(Utc::now(), &timezone);
+            if let Some(event) = curfew_transition(active, now_active, &curfew) {
+                info!(event = event.name(), "Curfew changed");
+                state.events.publish(event);
+            }
+            active = Some(now_active);
+        }
+    })
+}
+
+/// Returns the event to publish when the curfew went from `was_active` to
+/// `is_active`, if any.
+fn curfew_transition(
+    was_active: Option<bool>,
+    is_active: bool,
+    curfew: &CurfewConfig,
+) -> Option<ServerEvent> {
+    let start = curfew.start.clone();
+    let end = curfew.end.clone();
+    match (was_active?, is_active) {
+        (false, true) => Some(ServerEvent::CurfewStarted { start, end }),
+        (true, false) => Some(ServerEvent::CurfewEnded { start, end }),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config_changed(section: ConfigSection) -> ServerEvent {
+        ServerEvent::ConfigChanged { section }
+    }
+
+    #[test]
+    fn test_event_serialization() {
+        let message = EventMessage {
+            id: 7,
+            emitted_at_utc: "2025-01-15T03:30:00Z".to_string(),
+            event: config_changed(ConfigSection::Timezone),
+        };
+        let json = serde_json::to_value(&message).unwrap();
+        assert_eq!(json["id"], 7);
+        assert_eq!(json["type"], "config_changed");
+        assert_eq!(json["section"], "timezone");
+        assert_eq!(message.event.name(), "config_changed");
+    }
+
+    #[tokio::test]
+    async fn test_subscriber_receives_published_events() {
+        let bus = EventBus::new();
+        let mut subscription = bus.subscribe(None);
+        assert!(matches!(subscription.missed, Missed::Events(ref e) if e.is_empty()));
+
+        let id = bus.publish(config_changed(ConfigSection::Wifi));
+        let message = subscription.receiver.recv().await.unwrap();
+        assert_eq!(message.id, id);
+        assert_eq!(message.event, config_changed(ConfigSection::Wifi));
+    }
+
+    #[test]
+    fn test_resume_replays_missed_events() {
+        let bus = EventBus::new();
+        let first = bus.publish(config_changed(ConfigSection::Wifi));
+        let second = bus.publish(config_changed(ConfigSection::Timezone));
+        let third = bus.publish(config_changed(ConfigSection::Passes));
+        assert!(first < second && second < third);
+
+        let Missed::Events(missed) = bus.subscribe(Some(first)).missed else {
+            panic!("expected missed events");
+        };
+        let ids: Vec<_> = missed.iter().map(|e| e.id).collect();
+        assert_eq!(ids, [second, third]);
+
+        let Missed::Events(missed) = bus.subscribe(Some(third)).missed else {
+            panic!("expected missed events");
+        };
+        assert!(missed.is_empty());
+    }
+
+    #[test]
+    fn test_resume_after_buffer_overflow_is_a_gap() {
+        let bus = EventBus::with_capacity(2);
+        let first = bus.publish(config_changed(ConfigSection::Wifi));
+        bus.publish(config_changed(ConfigSection::Timezone));
+        bus.publish(config_changed(ConfigSection::Passes));
+        let latest = bus.publish(config_changed(ConfigSection::Bluetooth));
+
+        assert!(matches!(bus.subscribe(Some(first)).missed, Missed::Gap(Some(id)) if id == latest));
+        // The oldest buffered event follows the one the client saw
+        assert!(
+            matches!(bus.subscribe(Some(latest - 2)).missed, Missed::Events(ref e) if e.len() == 2)
+        );
+    }
+
+    #[test]
+    fn test_resume_with_id_from_previous_run_is_a_gap() {
+        let bus = EventBus::new();
+        assert!(matches!(bus.subscribe(Some(3)).missed, Missed::Gap(None)));
+        assert!(matches!(
+            bus.subscribe(Some(u64::MAX)).missed,
+            Missed::Gap(None)
+        ));
+    }
+
+    #[test]
+    fn test_curfew_transition() {
+        let curfew = CurfewConfig::default();
+
+        // Nothing is announced for the state found at startup
+        assert_eq!(curfew_transition(None, true, &curfew), None);
+        assert_eq!(curfew_transition(Some(true), true, &curfew), None);
+
+        let started = curfew_transition(Some(false), true, &curfew).unwrap();
+        assert_eq!(started.kind(), EventKind::CurfewStarted);
+        let json = serde_json::to_value(&started).unwrap();
+        assert_eq!(json["type"], "curfew_started");
+        assert_eq!(json["start"], "22:00");
+        assert_eq!(json["end"], "06:00");
+
+        let ended = curfew_transition(Some(true), false, &curfew).unwrap();
+        assert_eq!(ended.name(), "curfew_ended");
+    }
+}
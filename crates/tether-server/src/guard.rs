@@ -0,0 +1,729 @@
+//! Guarded settings.
+//!
+//! With `[guard]` enabled (see [`tether_core::GuardConfig`]) and onboarding
+//! complete, changes that would loosen the rules don't take effect right
+//! away:
+//!
+//! - `PUT /api/config/bluetooth`, which could lower `rssi_threshold` or
+//!   swap the tracked device
+//! - `POST /api/devices/pair`, which makes the paired device the tracked one
+//! - `PUT /api/config/passes`
+//! - `PUT /api/config/curfew`, which could shorten or disable the curfew
+//! - `PUT /api/config/timezone`, which shifts the curfew and when passes
+//!   reset
+//! - `PUT` and `DELETE /api/webhooks/{id}`, which could stop the partner
+//!   hearing about passes
+//!
+//! Each becomes a [`PendingChange`] that is applied once the accountability
+//! partner approves it with the partner token, or automatically once the
+//! cooling-off period has passed. The partner can also reject or cancel it,
+//! again with the partner token. Pending changes are saved to
+//! `pending-changes.json` and survive restarts.
+
+use std::path::PathBuf;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tether_core::storage::{DurableFile, LoadError};
+use tether_core::{Config, IdentityResolvingKey, SecretError, SecretString};
+use thiserror::Error;
+use tracing::{info, warn};
+use utoipa::ToSchema;
+
+use crate::api::bluetooth::apply_pairing;
+use crate::api::config::{
+    apply_bluetooth, apply_curfew, apply_passes_per_month, apply_timezone,
+    bluetooth_config_response, curfew_response, merge_bluetooth, UpdateBluetoothRequest,
+};
+use crate::api::error::{ApiError, ApiResult};
+use crate::api::webhooks::{apply_webhook_delete, apply_webhook_update, webhook_response};
+use crate::audit::{snapshot, Actor, AuditAction};
+use crate::events::{EventKind, ServerEvent};
+use crate::state::{AppState, SharedState};
+use crate::webhooks::WebhookStore;
+
+/// How often pending changes are checked for an elapsed cooling-off period.
+const CHANGE_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+// ============================================================================
+// Errors
+// ============================================================================
+
+/// Errors from managing pending changes.
+#[derive(Debug, Error)]
+pub enum ChangeError {
+    /// Failed to read the pending changes file.
+    #[error("Failed to read pending changes from {path}: {source}")]
+    ReadError {
+        /// Path of the pending changes file.
+        path: PathBuf,
+        /// Underlying I/O error.
+        source: std::io::Error,
+    },
+
+    /// The pending changes file is not valid JSON.
+    #[error("Failed to parse pending changes in {path}: {source}")]
+    ParseError {
+        /// Path of the pending changes file.
+        path: PathBuf,
+        /// Underlying JSON error.
+        source: serde_json::Error,
+    },
+
+    /// Failed to write the pending changes file.
+    #[error("Failed to write pending changes to {path}: {source}")]
+    WriteError {
+        /// Path of the pending changes file.
+        path: PathBuf,
+        /// Underlying I/O error.
+        source: std::io::Error,
+    },
+
+    /// Failed to serialize pending changes.
+    #[error("Failed to serialize pending changes: {0}")]
+    SerializeError(#[from] serde_json::Error),
+
+    /// No pending change has the given id.
+    #[error("Pending change not found: {id}")]
+    NotFound {
+        /// The requested change id.
+        id: String,
+    },
+}
+
+/// Result type for pending change operations.
+pub type ChangeResult<T> = Result<T, ChangeError>;
+
+// ============================================================================
+// Changes
+// ============================================================================
+
+pub use tether_client::types::GuardedSetting;
+
+/// How a pending change was resolved.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeResolution {
+    /// The partner approved the change and it was applied.
+    Approved,
+    /// The cooling-off period passed and the change was applied.
+    Elapsed,
+    /// The partner rejected the change.
+    Rejected,
+    /// The change was withdrawn.
+    Cancelled,
+    /// The cooling-off period passed, but the change could not be applied.
+    Failed,
+}
+
+/// A change to a guarded setting.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum GuardedChange {
+    /// `PUT /api/config/bluetooth`
+    UpdateBluetooth(UpdateBluetoothRequest),
+    /// `POST /api/devices/pair`, after pairing succeeded
+    PairDevice {
+        /// Identity address of the paired device.
+        identity_address: String,
+        /// Name to track the device as; unchanged if `None`.
+        target_name: Option<String>,
+        /// Key obtained by pairing, if any.
+        target_irk: Option<IdentityResolvingKey>,
+    },
+    /// `PUT /api/config/passes`
+    UpdatePassesPerMonth {
+        /// New number of passes per month.
+        per_month: u8,
+    },
+    /// `PUT /api/config/curfew`
+    UpdateCurfew(tether_core::CurfewConfig),
+    /// `PUT /api/config/timezone`
+    UpdateTimezone {
+        /// New IANA timezone name.
+        timezone: String,
+    },
+    /// `PUT /api/webhooks/{id}`
+    UpdateWebhook {
+        /// Id of the webhook.
+        id: String,
+        /// New URL; unchanged if `None`.
+        url: Option<String>,
+        /// New event types; unchanged if `None`.
+        events: Option<Vec<EventKind>>,
+        /// Id of the new secret, staged in the secrets store until the
+        /// change is resolved; unchanged if `None`.
+        secret_id: Option<String>,
+    },
+    /// `DELETE /api/webhooks/{id}`
+    DeleteWebhook {
+        /// Id of the webhook.
+        id: String,
+    },
+}
+
+impl GuardedChange {
+    /// Returns the setting the change modifies.
+    #[must_use]
+    pub const fn setting(&self) -> GuardedSetting {
+        match self {
+            Self::UpdateBluetooth(_) | Self::PairDevice { .. } => GuardedSetting::Bluetooth,
+            Self::UpdatePassesPerMonth { .. } => GuardedSetting::PassesPerMonth,
+            Self::UpdateCurfew(_) => GuardedSetting::Curfew,
+            Self::UpdateTimezone { .. } => GuardedSetting::Timezone,
+            Self::UpdateWebhook { .. } | Self::DeleteWebhook { .. } => GuardedSetting::Webhooks,
+        }
+    }
+
+    /// Returns the affected settings before and after applying the change.
+    fn preview(&self, config: &Config, webhooks: &WebhookStore) -> (Value, Value) {
+        match self {
+            Self::UpdateBluetooth(request) => {
+                let mut bluetooth = config.bluetooth.clone();
+                merge_bluetooth(&mut bluetooth, request);
+                bluetooth_preview(config, &bluetooth)
+            }
+            Self::PairDevice {
+                identity_address,
+                target_name,
+                target_irk,
+            } => {
+                let mut bluetooth = config.bluetooth.clone();
+                bluetooth.target_address.clone_from(identity_address);
+                if let Some(name) = target_name {
+                    bluetooth.target_name.clone_from(name);
+                }
+                bluetooth.target_irk = *target_irk;
+                bluetooth_preview(config, &bluetooth)
+            }
+            Self::UpdatePassesPerMonth { per_month } => (
+                json!({ "per_month": config.passes.per_month }),
+                json!({ "per_month": per_month }),
+            ),
+            Self::UpdateCurfew(curfew) => {
+                let timezone = &config.system.timezone;
+                let before = curfew_response(&config.curfew, timezone);
+                let after = curfew_response(curfew, timezone);
+                (
+                    snapshot(&before).unwrap_or_default(),
+                    snapshot(&after).unwrap_or_default(),
+                )
+            }
+            Self::UpdateTimezone { timezone } => (
+                json!({ "timezone": config.system.timezone }),
+                json!({ "timezone": timezone }),
+            ),
+            Self::UpdateWebhook {
+                id,
+                url,
+                events,
+                secret_id,
+            } => {
+                let Some(webhook) = webhooks.get(id) else {
+                    return (Value::Null, Value::Null);
+                };
+                let before = webhook_response(webhooks, webhook);
+                let mut webhook = webhook.clone();
+                if let Some(url) = url {
+                    webhook.url.clone_from(url);
+                }
+                if let Some(events) = events {
+                    webhook.events.clone_from(events);
+                }
+                let mut after = snapshot(&webhook_response(webhooks, &webhook)).unwrap_or_default();
+                if secret_id.is_some() {
+                    after["secret_changed"] = json!(true);
+                }
+                (snapshot(&before).unwrap_or_default(), after)
+            }
+            Self::DeleteWebhook { id } => {
+                let before = webhooks
+                    .get(id)
+                    .and_then(|webhook| snapshot(&webhook_response(webhooks, webhook)));
+                (before.unwrap_or_default(), Value::Null)
+            }
+        }
+    }
+
+    /// Applies the change on behalf of `actor`.
+    async fn apply(&self, state: &AppState, actor: &Actor) -> ApiResult<()> {
+        match self {
+            Self::UpdateBluetooth(request) => {
+                apply_bluetooth(state, actor, request).await?;
+            }
+            Self::PairDevice {
+                identity_address,
+                target_name,
+                target_irk,
+            } => {
+                apply_pairing(
+                    state,
+                    actor,
+                    identity_address,
+                    target_name.as_deref(),
+                    *target_irk,
+                )
+                .await?;
+            }
+            Self::UpdatePassesPerMonth { per_month } => {
+                apply_passes_per_month(state, actor, *per_month).await?;
+            }
+            Self::UpdateCurfew(curfew) => {
+                apply_curfew(state, actor, curfew.clone()).await?;
+            }
+            Self::UpdateTimezone { timezone } => {
+                apply_timezone(state, actor, timezone.clone()).await?;
+            }
+            Self::UpdateWebhook {
+                id,
+                url,
+                events,
+                secret_id,
+            } => {
+                let secret = match secret_id {
+                    Some(secret_id) => Some(staged_secret(state, secret_id).await?),
+                    None => None,
+                };
+                apply_webhook_update(
+                    state,
+                    actor,
+                    id,
+                    url.clone(),
+                    events.clone(),
+                    secret.as_ref(),
+                )
+                .await?;
+            }
+            Self::DeleteWebhook { id } => {
+                apply_webhook_delete(state, actor, id).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Removes anything staged for the change once it is resolved.
+    async fn release(&self, state: &AppState) {
+        if let Self::UpdateWebhook {
+            secret_id: Some(secret_id),
+            ..
+        } = self
+        {
+            let mut secrets = state.secrets.lock().await;
+            if secrets.remove(secret_id) {
+                if let Err(e) = secrets.save() {
+                    warn!(error = %e, "Failed to remove the staged webhook secret");
+                }
+            }
+        }
+    }
+}
+
+/// Reads a secret staged for a pending change.
+async fn staged_secret(state: &AppState, secret_id: &str) -> ApiResult<SecretString> {
+    let secret =
+        state
+            .secrets
+            .lock()
+            .await
+            .get(secret_id)
+            .map_err(|e| ApiError::InternalError {
+                error_code: "secret_read_failed".to_string(),
+                message: "Failed to read the new webhook secret".to_string(),
+                details: Some(e.to_string()),
+            })?;
+    secret.ok_or_else(|| ApiError::InternalError {
+        error_code: "secret_missing".to_string(),
+        message: "The new webhook secret is missing from the secrets store".to_string(),
+        details: None,
+    })
+}
+
+/// Stages a secret for a pending change until it is resolved.
+///
+/// # Errors
+///
+/// Returns an error if the secret cannot be saved.
+pub async fn stage_secret(state: &AppState, secret: &SecretString) -> ApiResult<String> {
+    let mut secrets = state.secrets.lock().await;
+    let secret_id = secrets.insert(secret).and_then(|id| {
+        secrets.save()?;
+        Ok(id)
+    });
+    drop(secrets);
+    secret_id.map_err(|e| ApiError::InternalError {
+        error_code: "secret_write_failed".to_string(),
+        message: "Failed to save the new webhook secret".to_string(),
+        details: Some(e.to_string()),
+    })
+}
+
+fn bluetooth_preview(config: &Config, bluetooth: &tether_core::BluetoothConfig) -> (Value, Value) {
+    let before = bluetooth_config_response(&config.bluetooth);
+    let after = bluetooth_config_response(bluetooth);
+    (
+        snapshot(&before).unwrap_or_default(),
+        snapshot(&after).unwrap_or_default(),
+    )
+}
+
+/// A change waiting for approval or for its cooling-off period to pass.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingChange {
+    /// Unique id of the change.
+    pub id: String,
+
+    /// The change itself.
+    pub change: GuardedChange,
+
+    /// Who requested the change.
+    pub requested_by: String,
+
+    /// When the change was requested.
+    pub requested_at: DateTime<Utc>,
+
+    /// When the change takes effect without approval, if ever.
+    pub effective_at: Option<DateTime<Utc>>,
+
+    /// The affected settings when the change was requested.
+    pub before: Value,
+
+    /// The affected settings as they would be after the change.
+    pub after: Value,
+}
+
+/// Contents of the pending changes file.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct ChangeData {
+    #[serde(default)]
+    changes: Vec<PendingChange>,
+}
+
+// ============================================================================
+// Store
+// ============================================================================
+
+/// Pending changes, saved on every change.
+#[derive(Debug)]
+pub struct ChangeStore {
+    file: DurableFile,
+    data: ChangeData,
+}
+
+impl ChangeStore {
+    /// Opens the store at `path`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file exists but neither it nor a backup can
+    /// be read.
+    pub fn open(path: impl Into<PathBuf>) -> ChangeResult<Self> {
+        let path = path.into();
+        let file = DurableFile::new(&path);
+
+        let data = file
+            .load(|contents| serde_json::from_str::<ChangeData>(contents))
+            .map_err(|e| match e {
+                LoadError::Read(source) => ChangeError::ReadError {
+                    path: path.clone(),
+                    source,
+                },
+                LoadError::Parse(source) => ChangeError::ParseError {
+                    path: path.clone(),
+                    source,
+                },
+            })?
+            .unwrap_or_default();
+
+        Ok(Self { file, data })
+    }
+
+    /// Returns all pending changes, oldest first.
+    #[must_use]
+    pub fn changes(&self) -> &[PendingChange] {
+        &self.data.changes
+    }
+
+    /// Adds a pending change.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the change cannot be saved; it is then not added.
+    pub fn insert(&mut self, change: PendingChange) -> ChangeResult<()> {
+        self.data.changes.push(change);
+        let saved = self.save();
+        if saved.is_err() {
+            self.data.changes.pop();
+        }
+        saved
+    }
+
+    /// Removes a pending change and returns it.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ChangeError::NotFound`] if there is no such change, or an
+    /// error if the removal cannot be saved; the change is then kept.
+    pub fn remove(&mut self, id: &str) -> ChangeResult<PendingChange> {
+        let index = self
+            .data
+            .changes
+            .iter()
+            .position(|c| c.id == id)
+            .ok_or_else(|| ChangeError::NotFound { id: id.to_string() })?;
+        let change = self.data.changes.remove(index);
+        if let Err(e) = self.save() {
+            self.data.changes.insert(index, change);
+            return Err(e);
+        }
+        Ok(change)
+    }
+
+    /// Returns the ids of changes whose cooling-off period passed by `now`.
+    #[must_use]
+    pub fn due(&self, now: DateTime<Utc>) -> Vec<String> {
+        self.data
+            .changes
+            .iter()
+            .filter(|c| c.effective_at.is_some_and(|at| at <= now))
+            .map(|c| c.id.clone())
+            .collect()
+    }
+
+    fn save(&self) -> ChangeResult<()> {
+        let json = serde_json::to_string_pretty(&self.data)?;
+        self.file
+            .write(json.as_bytes())
+            .map_err(|source| ChangeError::WriteError {
+                path: self.file.path().to_path_buf(),
+                source,
+            })
+    }
+}
+
+// ============================================================================
+// Requesting and Resolving
+// ============================================================================
+
+/// Returns whether changes to guarded settings currently need approval.
+pub async fn is_active(state: &AppState) -> bool {
+    let config = state.config.read().await;
+    config.guard.enabled && config.system.onboarding_complete
+}
+
+/// Queues `change` for approval instead of applying it.
+///
+/// # Errors
+///
+/// Returns an error if the pending change cannot be saved.
+pub async fn defer(
+    state: &AppState,
+    actor: &Actor,
+    change: GuardedChange,
+) -> ApiResult<PendingChange> {
+    let config = state.config.read().await;
+    let now = Utc::now();
+    let (before, after) = change.preview(&config, &*state.webhooks.lock().await);
+    let cooling_off_hours = config.guard.cooling_off_hours;
+    let pending = PendingChange {
+        id: uuid::Uuid::new_v4().to_string(),
+        change,
+        requested_by: actor.as_str().to_string(),
+        requested_at: now,
+        effective_at: (cooling_off_hours > 0)
+            .then(|| now + chrono::Duration::hours(cooling_off_hours.into())),
+        before,
+        after,
+    };
+
+    state.changes.lock().await.insert(pending.clone())?;
+    drop(config);
+
+    info!(
+        id = %pending.id,
+        setting = ?pending.change.setting(),
+        "Change to a guarded setting is waiting for approval"
+    );
+    state.events.publish(ServerEvent::ChangeRequested {
+        change_id: pending.id.clone(),
+        setting: pending.change.setting(),
+        requested_by: pending.requested_by.clone(),
+        effective_at_utc: pending.effective_at.map(|at| at.to_rfc3339()),
+    });
+    state
+        .record_audit(
+            actor,
+            AuditAction::RequestChange,
+            Some(pending.before.clone()),
+            Some(json!({ "change_id": pending.id, "settings": pending.after })),
+        )
+        .await;
+    Ok(pending)
+}
+
+/// Resolves a pending change, applying it unless it was rejected or cancelled.
+///
+/// An approved change that fails to apply stays pending.
+///
+/// # Errors
+///
+/// Returns an error if there is no such change, or it cannot be applied or
+/// removed.
+pub async fn resolve(
+    state: &AppState,
+    actor: &Actor,
+    id: &str,
+    resolution: ChangeResolution,
+) -> ApiResult<PendingChange> {
+    // Taken out of the store so the change is applied at most once
+    let pending = state.changes.lock().await.remove(id)?;
+
+    if matches!(
+        resolution,
+        ChangeResolution::Approved | ChangeResolution::Elapsed
+    ) {
+        let requested_by = Actor::new(&pending.requested_by);
+        if let Err(e) = pending.change.apply(state, &requested_by).await {
+            if resolution == ChangeResolution::Elapsed {
+                pending.change.release(state).await;
+                finish(state, actor, &pending, ChangeResolution::Failed).await;
+                return Err(e);
+            }
+            let restored = state.changes.lock().await.insert(pending.clone());
+            if let Err(restore) = restored {
+                warn!(id, error = %restore, "Failed to restore a change that could not be applied");
+            }
+            return Err(e);
+        }
+    }
+
+    pending.change.release(state).await;
+    finish(state, actor, &pending, resolution).await;
+    Ok(pending)
+}
+
+/// Publishes and records the resolution of a change.
+async fn finish(
+    state: &AppState,
+    actor: &Actor,
+    pending: &PendingChange,
+    resolution: ChangeResolution,
+) {
+    info!(id = %pending.id, ?resolution, "Resolved change to a guarded setting");
+    state.events.publish(ServerEvent::ChangeResolved {
+        change_id: pending.id.clone(),
+        setting: pending.change.setting(),
+        resolution,
+    });
+
+    let action = match resolution {
+        ChangeResolution::Approved => AuditAction::ApproveChange,
+        ChangeResolution::Rejected => AuditAction::RejectChange,
+        ChangeResolution::Cancelled => AuditAction::CancelChange,
+        // The change itself is recorded when applied
+        ChangeResolution::Elapsed | ChangeResolution::Failed => return,
+    };
+    state
+        .record_audit(
+            actor,
+            action,
+            None,
+            Some(json!({ "change_id": pending.id })),
+        )
+        .await;
+}
+
+/// Applies the changes whose cooling-off period passed by `now`.
+pub async fn apply_due(state: &AppState, now: DateTime<Utc>) {
+    let due = state.changes.lock().await.due(now);
+    let actor = Actor::new(GUARD_ACTOR);
+    for id in due {
+        if let Err(e) = resolve(state, &actor, &id, ChangeResolution::Elapsed).await {
+            warn!(id, error = %e, "Failed to apply a change after its cooling-off period");
+        }
+    }
+}
+
+/// Actor recorded for changes applied after their cooling-off period.
+const GUARD_ACTOR: &str = "cooling-off";
+
+/// Spawns a background task that applies changes once their cooling-off
+/// period has passed.
+pub fn spawn(state: SharedState) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(CHANGE_CHECK_INTERVAL);
+        loop {
+            interval.tick().await;
+            apply_due(&state, Utc::now()).await;
+        }
+    })
+}
+
+// ============================================================================
+// Partner Token
+// ============================================================================
+
+/// Returns the accountability partner's token, if one is configured.
+///
+/// # Errors
+///
+/// Returns an error if the token is configured but cannot be read.
+pub async fn partner_token(state: &AppState) -> Result<Option<SecretString>, SecretError> {
+    let token_id = state.config.read().await.guard.partner_token_id.clone();
+    match token_id {
+        Some(id) => state.secrets.lock().await.get(&id),
+        None => Ok(None),
+    }
+}
+
+/// Returns whether `given` is the partner token.
+#[must_use]
+pub fn is_partner_token(given: &str, expected: &SecretString) -> bool {
+    crate::access::token_matches(given, expected)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pending(id: &str, effective_at: Option<DateTime<Utc>>) -> PendingChange {
+        PendingChange {
+            id: id.to_string(),
+            change: GuardedChange::UpdatePassesPerMonth { per_month: 10 },
+            requested_by: "tester".to_string(),
+            requested_at: Utc::now(),
+            effective_at,
+            before: json!({ "per_month": 3 }),
+            after: json!({ "per_month": 10 }),
+        }
+    }
+
+    #[test]
+    fn test_store_persists_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("pending-changes.json");
+        let now = Utc::now();
+
+        let mut store = ChangeStore::open(&path).unwrap();
+        store.insert(pending("due", Some(now))).unwrap();
+        store
+            .insert(pending("later", Some(now + chrono::Duration::hours(1))))
+            .unwrap();
+        store.insert(pending("approval-only", None)).unwrap();
+        assert_eq!(store.due(now), ["due"]);
+
+        let mut store = ChangeStore::open(&path).unwrap();
+        assert_eq!(store.changes().len(), 3);
+        assert_eq!(store.remove("due").unwrap().id, "due");
+        assert!(matches!(
+            store.remove("due"),
+            Err(ChangeError::NotFound { .. })
+        ));
+
+        let store = ChangeStore::open(&path).unwrap();
+        let ids: Vec<_> = store.changes().iter().map(|c| c.id.as_str()).collect();
+        assert_eq!(ids, ["later", "approval-only"]);
+        assert_eq!(store.due(now + chrono::Duration::days(365)), ["later"]);
+    }
+}
@@ -7,6 +7,17 @@
 #![forbid(unsafe_code)]
 #![warn(clippy::all, clippy::pedantic, clippy::nursery)]
 
+pub mod access;
 pub mod api;
+pub mod audit;
+pub mod events;
+pub mod guard;
 pub mod logging;
+pub mod mdns;
+#[cfg(feature = "metrics")]
+pub mod metrics;
+pub mod mqtt;
 pub mod state;
+pub mod supervisor;
+pub mod webhooks;
+pub mod wifi;
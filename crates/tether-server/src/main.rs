@@ -15,6 +15,9 @@
 //! - `TETHER_HOST`: Bind address (default: `0.0.0.0`)
 //! - `TETHER_PORT`: Bind port (default: `8080`)
 //!
+//! The server advertises itself on the LAN over mDNS as `tether.local`;
+//! see the `[mdns]` section of the configuration.
+//!
 //! ## Running
 //!
 //! ```bash
@@ -30,7 +33,7 @@
 
 use std::env;
 use std::net::SocketAddr;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::time::Duration;
 
 use axum::http::{header, Method};
@@ -42,13 +45,29 @@ use tower_http::cors::{Any, CorsLayer};
 use tower_http::trace::{DefaultMakeSpan, DefaultOnRequest, DefaultOnResponse, TraceLayer};
 use tracing::{info, warn, Level};
 
-use tether_core::{default_data_dir, Config, PassManager};
+use tether_core::{
+    default_data_dir, Config, PassManager, SecretStore, SqlitePassStore, StorageBackend,
+};
 
+mod access;
 mod api;
+mod audit;
+mod events;
+mod guard;
 mod logging;
+mod mdns;
+#[cfg(feature = "metrics")]
+mod metrics;
+mod mqtt;
 mod state;
+mod supervisor;
+mod webhooks;
+mod wifi;
 
+use audit::AuditLog;
+use guard::ChangeStore;
 use state::{AppState, SharedState};
+use webhooks::WebhookStore;
 
 // ============================================================================
 // Main Entry Point
@@ -64,8 +83,16 @@ async fn main() -> anyhow::Result<()> {
     // Step 2: Initialize logging/tracing
     logging::init(is_production)?;
 
+    // Step 2b: Record metrics from the start, so early storage writes count
+    #[cfg(feature = "metrics")]
+    metrics::install();
+
     info!(
-        env = if is_production { "production" } else { "development" },
+        env = if is_production {
+            "production"
+        } else {
+            "development"
+        },
         "Starting tether server"
     );
 
@@ -74,7 +101,7 @@ async fn main() -> anyhow::Result<()> {
 
     info!(config_path = %config_path.display(), "Loading configuration");
 
-    let config = match Config::load(&config_path) {
+    let mut config = match Config::load(&config_path) {
         Ok(cfg) => cfg,
         Err(e) => {
             if config_path.exists() {
@@ -90,19 +117,74 @@ async fn main() -> anyhow::Result<()> {
         }
     };
 
+    // Step 3b: Open the secrets store and move any plaintext passwords into it
+    let mut secrets = SecretStore::open(
+        passes_path.with_file_name("secrets.json"),
+        passes_path.with_file_name("secrets.key"),
+    )?;
+    migrate_secrets(&mut config, &mut secrets, &config_path)?;
+    if !config.wifi.networks.is_empty() {
+        tokio::spawn(wifi::sync(wifi::profiles(&config, &secrets)));
+    }
+
+    // Step 3c: Open webhooks, whose secrets are kept in a store of their own
+    let webhook_secrets = SecretStore::open(
+        passes_path.with_file_name("webhook-secrets.json"),
+        passes_path.with_file_name("secrets.key"),
+    )?;
+    let webhooks =
+        WebhookStore::open(passes_path.with_file_name("webhooks.json"), webhook_secrets)?;
+
+    // Step 3d: Open changes to guarded settings waiting for approval
+    let changes = ChangeStore::open(passes_path.with_file_name("pending-changes.json"))?;
+
+    // Step 3e: Open the audit log of mutating API calls
+    let audit = AuditLog::open(passes_path.with_file_name("audit.jsonl"))?;
+
     // Step 4: Initialize pass manager
-    info!(passes_path = %passes_path.display(), "Loading pass data");
-    let passes_per_month = config.passes.per_month;
-    let pass_manager = PassManager::load_or_create(&passes_path, passes_per_month.into())?;
+    let pass_manager = open_pass_manager(&config, &passes_path)?;
 
     // Step 5: Initialize Bluetooth scanner (optional)
     let bluetooth = init_bluetooth(&config).await;
 
     // Step 6: Create shared state
-    let state = AppState::new(config, pass_manager, bluetooth, config_path, passes_path).into_shared();
+    let state = AppState::new(
+        config,
+        pass_manager,
+        secrets,
+        webhooks,
+        changes,
+        audit,
+        bluetooth,
+        config_path,
+        passes_path,
+    )
+    .into_shared();
+
+    // Step 6b: Keep the Bluetooth scanner working across adapter failures
+    #[cfg(feature = "bluetooth")]
+    supervisor::spawn(state.clone());
+
+    // Step 6c: Reset passes when a new month starts and announce the curfew,
+    // notifying event clients
+    events::spawn_month_watcher(state.clone());
+    events::spawn_curfew_watcher(state.clone());
+
+    // Step 6d: Deliver events to webhooks, resuming queued retries
+    webhooks::spawn(state.clone());
+
+    // Step 6e: Publish state to Home Assistant over MQTT, if enabled
+    mqtt::spawn(state.clone());
+
+    // Step 6f: Keep buffered metric samples bounded between scrapes
+    #[cfg(feature = "metrics")]
+    metrics::spawn_upkeep();
+
+    // Step 6g: Apply guarded changes once their cooling-off period has passed
+    guard::spawn(state.clone());
 
     // Step 7: Build the router
-    let app = build_router(state, is_production);
+    let app = build_router(state.clone(), is_production);
 
     // Step 8: Determine bind address
     let host = env::var("TETHER_HOST").unwrap_or_else(|_| "0.0.0.0".to_string());
@@ -117,9 +199,24 @@ async fn main() -> anyhow::Result<()> {
     // Step 9: Start server with graceful shutdown
     let listener = TcpListener::bind(addr).await?;
 
-    axum::serve(listener, app)
-        .with_graceful_shutdown(shutdown_signal())
-        .await?;
+    // Step 9b: Advertise the server on the LAN, unless it only listens locally
+    let advertiser = if addr.ip().is_loopback() {
+        info!("Listening on loopback only, not advertising over mDNS");
+        None
+    } else {
+        mdns::start(state, port).await
+    };
+
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .with_graceful_shutdown(shutdown_signal())
+    .await?;
+
+    if let Some(advertiser) = advertiser {
+        advertiser.shutdown().await;
+    }
 
     info!("Server shutdown complete");
     Ok(())
@@ -161,6 +258,59 @@ fn resolve_data_paths(is_production: bool) -> (PathBuf, PathBuf) {
     (config_path, passes_path)
 }
 
+/// Moves plaintext WiFi passwords from the config file into the secrets store.
+///
+/// The store is saved before the config so that the config never refers to a
+/// secret that is not on disk. The config's backups are discarded, since they
+/// still hold the plaintext passwords. Secrets left behind by an interrupted
+/// update are pruned once the config is saved.
+fn migrate_secrets(
+    config: &mut Config,
+    secrets: &mut SecretStore,
+    config_path: &Path,
+) -> anyhow::Result<()> {
+    if config.store_secrets(secrets)? {
+        info!("Moving plaintext WiFi passwords into the secrets store");
+        secrets.save()?;
+        config.save_discarding_history(config_path)?;
+    }
+
+    let stored = secrets.len();
+    config.prune_secrets(secrets);
+    if secrets.len() != stored {
+        secrets.save()?;
+    }
+    Ok(())
+}
+
+/// Opens the pass manager on the configured storage backend.
+///
+/// The SQLite database lives next to `passes.json`. The first time it is
+/// used, the existing JSON history is imported; the JSON file is kept.
+fn open_pass_manager(config: &Config, passes_path: &Path) -> anyhow::Result<PassManager> {
+    let per_month = config.passes.per_month.into();
+
+    match config.passes.storage {
+        StorageBackend::Json => {
+            info!(passes_path = %passes_path.display(), "Loading pass data");
+            Ok(PassManager::load_or_create(passes_path, per_month)?)
+        }
+        StorageBackend::Sqlite => {
+            let db_path = passes_path.with_file_name("tether.db");
+            info!(db_path = %db_path.display(), "Loading pass data");
+            let mut store = SqlitePassStore::open(&db_path)?;
+            if let Some(entries) = store.import_json(passes_path)? {
+                info!(
+                    passes_path = %passes_path.display(),
+                    entries,
+                    "Imported pass history into the database"
+                );
+            }
+            Ok(PassManager::open(Box::new(store), per_month)?)
+        }
+    }
+}
+
 // ============================================================================
 // Bluetooth Initialization
 // ============================================================================
@@ -168,13 +318,13 @@ fn resolve_data_paths(is_production: bool) -> (PathBuf, PathBuf) {
 /// Initialize the Bluetooth scanner if available.
 ///
 /// Returns `None` if Bluetooth is not available.
-/// The scanner is initialized without configuration - the BtConfig is
+/// The scanner only takes the configured adapters - the BtConfig is
 /// passed to check_proximity() and discover_devices() calls instead.
 #[cfg(feature = "bluetooth")]
-async fn init_bluetooth(_config: &Config) -> Option<tether_core::BluetoothScanner> {
+async fn init_bluetooth(config: &Config) -> Option<tether_core::BluetoothScanner> {
     use tether_core::BluetoothScanner;
 
-    match BluetoothScanner::new().await {
+    match BluetoothScanner::with_adapters(&config.bluetooth.adapters).await {
         Ok(scanner) => {
             info!("Bluetooth scanner initialized");
             Some(scanner)
@@ -201,8 +351,9 @@ async fn init_bluetooth(_config: &Config) -> Option<tether_core::BluetoothScanne
 /// # Middleware Order (bottom to top execution)
 ///
 /// 1. **TraceLayer** (outermost): Logs all requests/responses
-/// 2. **CorsLayer** (dev only): Handles CORS preflight and headers
-/// 3. Route-specific handlers
+/// 2. **Metrics** (`metrics` feature): Counts and times requests per route
+/// 3. **CorsLayer** (dev only): Handles CORS preflight and headers
+/// 4. Route-specific handlers
 fn build_router(state: SharedState, is_production: bool) -> Router {
     // Build the main router with all API routes
     let mut app = api::create_router(state);
@@ -229,14 +380,25 @@ fn build_router(state: SharedState, is_production: bool) -> Router {
                 Method::PATCH,
                 Method::OPTIONS,
             ])
-            // Allow common headers
-            .allow_headers([header::CONTENT_TYPE, header::AUTHORIZATION, header::ACCEPT])
+            // Allow common headers, and the actor recorded in the audit log
+            .allow_headers([
+                header::CONTENT_TYPE,
+                header::AUTHORIZATION,
+                header::ACCEPT,
+                header::HeaderName::from_static(audit::ACTOR_HEADER),
+            ])
             // Cache preflight requests for 1 hour
             .max_age(Duration::from_secs(3600));
 
         app = app.layer(cors);
     }
 
+    // Count requests per route inside the trace layer, where routes are matched
+    #[cfg(feature = "metrics")]
+    {
+        app = app.layer(axum::middleware::from_fn(metrics::track_http));
+    }
+
     app.layer(ServiceBuilder::new().layer(trace_layer))
 }
 
@@ -283,6 +445,7 @@ async fn shutdown_signal() {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use tether_core::storage::DurableFile;
 
     #[test]
     fn test_resolve_data_paths_development() {
@@ -299,4 +462,47 @@ mod tests {
         assert_eq!(config_path, PathBuf::from("/etc/tether/config.toml"));
         assert_eq!(passes_path, PathBuf::from("/var/lib/tether/passes.json"));
     }
+
+    #[test]
+    fn test_migrate_secrets_leaves_no_plaintext_backups() {
+        let dir = tempfile::tempdir().unwrap();
+        let config_path = dir.path().join("config.toml");
+        let plaintext = r#"
+            [bluetooth]
+            target_address = "A4:C1:38:12:34:56"
+            target_name = "iPhone"
+
+            [[wifi.networks]]
+            ssid = "HomeNetwork"
+            password = "hunter22"
+        "#;
+        // Earlier saves left plaintext backups behind
+        let file = DurableFile::new(&config_path);
+        for _ in 0..=DurableFile::DEFAULT_BACKUPS {
+            file.write(plaintext.as_bytes()).unwrap();
+        }
+
+        let mut config = Config::load(&config_path).unwrap();
+        let mut secrets = SecretStore::open(
+            dir.path().join("secrets.json"),
+            dir.path().join("secrets.key"),
+        )
+        .unwrap();
+        migrate_secrets(&mut config, &mut secrets, &config_path).unwrap();
+
+        for entry in std::fs::read_dir(dir.path()).unwrap() {
+            let path = entry.unwrap().path();
+            let contents = std::fs::read(&path).unwrap();
+            assert!(
+                !String::from_utf8_lossy(&contents).contains("hunter22"),
+                "{} holds the plaintext password",
+                path.display()
+            );
+        }
+        assert!(!file.backup_path(1).exists());
+
+        let config = Config::load(&config_path).unwrap();
+        let id = config.wifi.networks[0].password_id.clone().unwrap();
+        assert_eq!(secrets.get(&id).unwrap().unwrap().expose(), "hunter22");
+    }
 }
@@ -0,0 +1,256 @@
+//! mDNS advertisement on the LAN.
+//!
+//! When `[mdns]` is enabled, which it is by default, the server advertises
+//! itself so clients find the Pi after DHCP hands it a new address:
+//!
+//! - `_tether._tcp`, for tether clients such as tether-mcp and tetherctl,
+//!   with the TXT records `version` (the server's version), `onboarding`
+//!   (`complete` or `pending`) and `api` (`/api`, the path of the REST API)
+//! - `_http._tcp`, for browsers, on the web UI's port with the standard
+//!   `path` record
+//!
+//! The server also answers for `<hostname>.local`, `tether.local` by
+//! default. Advertised addresses follow the host's interfaces, and the
+//! `onboarding` record is updated when onboarding completes. Other changes
+//! to `[mdns]` take effect after a restart.
+
+use std::time::Duration;
+
+use mdns_sd::{ServiceDaemon, ServiceInfo};
+use tether_client::MDNS_SERVICE_TYPE;
+use tether_core::MdnsConfig;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::task::JoinHandle;
+use tracing::{debug, info, warn};
+
+use crate::events::{ConfigSection, ServerEvent, Subscription};
+use crate::state::SharedState;
+
+/// DNS-SD service type of web servers.
+pub const HTTP_SERVICE_TYPE: &str = "_http._tcp.local.";
+
+/// Path of the REST API, advertised in the `api` record.
+const API_PATH: &str = "/api";
+
+/// How long to wait for each goodbye announcement when stopping.
+const UNREGISTER_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Returns the TXT records of the `_tether._tcp` service.
+#[must_use]
+pub fn tether_properties(onboarding_complete: bool) -> Vec<(&'static str, String)> {
+    let onboarding = if onboarding_complete {
+        "complete"
+    } else {
+        "pending"
+    };
+    vec![
+        ("version", env!("CARGO_PKG_VERSION").to_string()),
+        ("onboarding", onboarding.to_string()),
+        ("api", API_PATH.to_string()),
+    ]
+}
+
+/// Describes a service of type `service_type` on `port`.
+///
+/// The service has no addresses of its own; they are filled in from the
+/// host's interfaces and kept current as they change.
+///
+/// # Errors
+///
+/// Returns an error if a TXT record key is invalid.
+pub fn service_info(
+    service_type: &str,
+    config: &MdnsConfig,
+    port: u16,
+    properties: &[(&str, String)],
+) -> mdns_sd::Result<ServiceInfo> {
+    let hostname = format!("{}.local.", config.hostname);
+    ServiceInfo::new(
+        service_type,
+        config.instance_name(),
+        &hostname,
+        (),
+        port,
+        properties,
+    )
+    .map(ServiceInfo::enable_addr_auto)
+}
+
+/// Advertises the server until shut down.
+pub struct Advertiser {
+    daemon: ServiceDaemon,
+    fullnames: Vec<String>,
+    updater: JoinHandle<()>,
+}
+
+/// Starts advertising the server listening on `port`.
+///
+/// Returns `None` if advertisement is disabled or mDNS is unavailable; the
+/// server works without it, clients just have to be given its address.
+pub async fn start(state: SharedState, port: u16) -> Option<Advertiser> {
+    let (config, onboarding_complete) = {
+        let config = state.config.read().await;
+        (config.mdns.clone(), config.is_onboarding_complete())
+    };
+    if !config.enabled {
+        debug!("mDNS advertisement disabled");
+        return None;
+    }
+
+    let daemon = match ServiceDaemon::new() {
+        Ok(daemon) => daemon,
+        Err(e) => {
+            warn!(error = %e, "mDNS advertisement unavailable");
+            return None;
+        }
+    };
+
+    let web_port = config.web_port.unwrap_or(port);
+    let services = [
+        service_info(
+            MDNS_SERVICE_TYPE,
+            &config,
+            port,
+            &tether_properties(onboarding_complete),
+        ),
+        service_info(
+            HTTP_SERVICE_TYPE,
+            &config,
+            web_port,
+            &[("path", "/".to_string())],
+        ),
+    ];
+
+    let mut fullnames = Vec::with_capacity(services.len());
+    for service in services {
+        let registered = service.and_then(|service| {
+            let fullname = service.get_fullname().to_string();
+            daemon.register(service).map(|()| fullname)
+        });
+        match registered {
+            Ok(fullname) => fullnames.push(fullname),
+            Err(e) => warn!(error = %e, "Failed to advertise over mDNS"),
+        }
+    }
+    if fullnames.is_empty() {
+        let _ = daemon.shutdown();
+        return None;
+    }
+
+    info!(
+        hostname = %format!("{}.local", config.hostname),
+        instance = config.instance_name(),
+        port,
+        web_port,
+        "Advertising over mDNS"
+    );
+
+    let updater = tokio::spawn(update_onboarding(state, daemon.clone(), config, port));
+    Some(Advertiser {
+        daemon,
+        fullnames,
+        updater,
+    })
+}
+
+/// Re-announces the `_tether._tcp` service when onboarding completes.
+async fn update_onboarding(
+    state: SharedState,
+    daemon: ServiceDaemon,
+    config: MdnsConfig,
+    port: u16,
+) {
+    let Subscription { mut receiver, .. } = state.events.subscribe(None);
+    loop {
+        match receiver.recv().await {
+            Ok(message) => {
+                let ServerEvent::ConfigChanged {
+                    section: ConfigSection::Onboarding,
+                } = message.event
+                else {
+                    continue;
+                };
+            }
+            // The change may have been missed; announcing again is harmless
+            Err(RecvError::Lagged(_)) => {}
+            Err(RecvError::Closed) => break,
+        }
+
+        let onboarding_complete = state.config.read().await.is_onboarding_complete();
+        let service = service_info(
+            MDNS_SERVICE_TYPE,
+            &config,
+            port,
+            &tether_properties(onboarding_complete),
+        );
+        // Registering again under the same name updates the records
+        match service.and_then(|service| daemon.register(service)) {
+            Ok(()) => debug!(onboarding_complete, "Updated mDNS advertisement"),
+            Err(e) => warn!(error = %e, "Failed to update mDNS advertisement"),
+        }
+    }
+}
+
+impl Advertiser {
+    /// Withdraws the advertisements, so clients stop finding the server
+    /// right away instead of when the records expire.
+    pub async fn shutdown(self) {
+        self.updater.abort();
+        for fullname in &self.fullnames {
+            match self.daemon.unregister(fullname) {
+                Ok(status) => {
+                    let _ = tokio::time::timeout(UNREGISTER_TIMEOUT, status.recv_async()).await;
+                }
+                Err(e) => debug!(error = %e, "Failed to withdraw mDNS advertisement"),
+            }
+        }
+        let _ = self.daemon.shutdown();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tether_properties() {
+        let properties = tether_properties(false);
+        assert!(properties.contains(&("onboarding", "pending".to_string())));
+        assert!(properties.contains(&("api", "/api".to_string())));
+        assert!(properties.contains(&("version", env!("CARGO_PKG_VERSION").to_string())));
+
+        assert!(tether_properties(true).contains(&("onboarding", "complete".to_string())));
+    }
+
+    #[test]
+    fn test_service_info() {
+        let config = MdnsConfig {
+            hostname: "tether-bedroom".to_string(),
+            instance_name: Some("bedroom".to_string()),
+            ..MdnsConfig::default()
+        };
+        let info =
+            service_info(MDNS_SERVICE_TYPE, &config, 8080, &tether_properties(true)).unwrap();
+
+        assert_eq!(info.get_fullname(), "bedroom._tether._tcp.local.");
+        assert_eq!(info.get_hostname(), "tether-bedroom.local.");
+        assert_eq!(info.get_port(), 8080);
+        assert_eq!(info.get_property_val_str("onboarding"), Some("complete"));
+        assert!(info.is_addr_auto());
+    }
+
+    #[test]
+    fn test_service_info_defaults_to_hostname() {
+        let info = service_info(
+            HTTP_SERVICE_TYPE,
+            &MdnsConfig::default(),
+            80,
+            &[("path", "/".to_string())],
+        )
+        .unwrap();
+
+        assert_eq!(info.get_fullname(), "tether._http._tcp.local.");
+        assert_eq!(info.get_hostname(), "tether.local.");
+        assert_eq!(info.get_property_val_str("path"), Some("/"));
+    }
+}
@@ -0,0 +1,335 @@
+//! Prometheus metrics.
+//!
+//! `GET /metrics` returns metrics in the Prometheus text format:
+//!
+//! - `tether_http_requests_total` and `tether_http_request_duration_seconds`
+//!   per method and route, recorded by [`track_http`] next to the request
+//!   tracing layer
+//! - Bluetooth scan durations and failures, the last RSSI of the tracked
+//!   device and storage write durations, recorded by `tether-core` (see
+//!   [`tether_core::metrics`])
+//! - `tether_passes_remaining` and `tether_passes_per_month`
+//! - Standard `process_*` metrics for CPU time, memory, threads and file
+//!   descriptors, read from `/proc` on Linux
+//!
+//! Metrics are compiled in with the `metrics` cargo feature, which is on by
+//! default. Build with `--no-default-features --features bluetooth` to
+//! leave them out.
+
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+use axum::extract::{MatchedPath, Request, State};
+use axum::http::header;
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use metrics::{describe_counter, describe_gauge, describe_histogram, Unit};
+use metrics_exporter_prometheus::{Matcher, PrometheusBuilder, PrometheusHandle};
+
+use crate::state::SharedState;
+
+/// Number of HTTP requests handled.
+pub const HTTP_REQUESTS: &str = "tether_http_requests_total";
+
+/// Duration of HTTP requests in seconds.
+pub const HTTP_REQUEST_DURATION: &str = "tether_http_request_duration_seconds";
+
+/// Passes remaining this month.
+pub const PASSES_REMAINING: &str = "tether_passes_remaining";
+
+/// Passes granted per month.
+pub const PASSES_PER_MONTH: &str = "tether_passes_per_month";
+
+/// Route label for requests that matched no route.
+const UNMATCHED_ROUTE: &str = "unmatched";
+
+/// Histogram buckets in seconds, from fast API calls to long Bluetooth scans.
+const DURATION_BUCKETS: &[f64] = &[
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0,
+];
+
+/// How often recorded durations are folded into the histograms.
+///
+/// Until then they are buffered, which would grow without bound if nothing
+/// scraped `/metrics`.
+const UPKEEP_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Content type of the Prometheus text format.
+const CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+static HANDLE: OnceLock<PrometheusHandle> = OnceLock::new();
+
+/// Installs the Prometheus recorder, if not yet installed.
+///
+/// Call before anything is recorded; measurements made earlier are lost.
+///
+/// # Panics
+///
+/// Panics if the histogram buckets are invalid, which they are not.
+pub fn install() -> &'static PrometheusHandle {
+    HANDLE.get_or_init(|| {
+        let recorder = PrometheusBuilder::new()
+            .set_buckets_for_metric(
+                Matcher::Suffix("_duration_seconds".into()),
+                DURATION_BUCKETS,
+            )
+            .expect("duration buckets are not empty")
+            .build_recorder();
+        let handle = recorder.handle();
+        if metrics::set_global_recorder(recorder).is_err() {
+            tracing::warn!("A metrics recorder was already installed; /metrics will be empty");
+        }
+        describe();
+        handle
+    })
+}
+
+/// Registers descriptions of all metrics.
+fn describe() {
+    describe_counter!(HTTP_REQUESTS, "HTTP requests handled");
+    describe_histogram!(
+        HTTP_REQUEST_DURATION,
+        Unit::Seconds,
+        "Duration of HTTP requests"
+    );
+    describe_gauge!(PASSES_REMAINING, "Passes remaining this month");
+    describe_gauge!(PASSES_PER_MONTH, "Passes granted per month");
+    process::describe();
+    tether_core::metrics::describe();
+}
+
+/// Spawns a background task that keeps the histograms' memory bounded.
+pub fn spawn_upkeep() -> tokio::task::JoinHandle<()> {
+    let handle = install();
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(UPKEEP_INTERVAL);
+        loop {
+            interval.tick().await;
+            handle.run_upkeep();
+        }
+    })
+}
+
+/// Middleware recording the count and duration of requests per route.
+///
+/// Routes are labelled with their path template, such as
+/// `/api/webhooks/{id}`, so ids in paths do not create new series.
+pub async fn track_http(
+    matched_path: Option<MatchedPath>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let started = Instant::now();
+    let method = request.method().to_string();
+    let route = matched_path.map_or_else(
+        || UNMATCHED_ROUTE.to_string(),
+        |path| path.as_str().to_string(),
+    );
+
+    let response = next.run(request).await;
+
+    let status = response.status().as_u16().to_string();
+    metrics::counter!(
+        HTTP_REQUESTS,
+        "method" => method.clone(),
+        "route" => route.clone(),
+        "status" => status
+    )
+    .increment(1);
+    metrics::histogram!(HTTP_REQUEST_DURATION, "method" => method, "route" => route)
+        .record(started.elapsed().as_secs_f64());
+
+    response
+}
+
+/// Export metrics.
+///
+/// Returns all metrics in the Prometheus text format. Pass and process
+/// metrics are sampled when scraped.
+pub async fn render_metrics(State(state): State<SharedState>) -> impl IntoResponse {
+    {
+        let pass_manager = state.pass_manager.read().await;
+        metrics::gauge!(PASSES_REMAINING).set(f64::from(pass_manager.remaining()));
+        metrics::gauge!(PASSES_PER_MONTH).set(f64::from(pass_manager.per_month()));
+    }
+    process::record();
+
+    ([(header::CONTENT_TYPE, CONTENT_TYPE)], install().render())
+}
+
+/// Standard Prometheus process metrics.
+mod process {
+    use metrics::{describe_gauge, Unit};
+
+    const CPU_SECONDS: &str = "process_cpu_seconds_total";
+    const RESIDENT_MEMORY: &str = "process_resident_memory_bytes";
+    const VIRTUAL_MEMORY: &str = "process_virtual_memory_bytes";
+    const OPEN_FDS: &str = "process_open_fds";
+    const THREADS: &str = "process_threads";
+    const START_TIME: &str = "process_start_time_seconds";
+
+    pub(super) fn describe() {
+        describe_gauge!(CPU_SECONDS, Unit::Seconds, "Total user and system CPU time");
+        describe_gauge!(RESIDENT_MEMORY, Unit::Bytes, "Resident memory size");
+        describe_gauge!(VIRTUAL_MEMORY, Unit::Bytes, "Virtual memory size");
+        describe_gauge!(OPEN_FDS, "Number of open file descriptors");
+        describe_gauge!(THREADS, "Number of OS threads");
+        describe_gauge!(START_TIME, Unit::Seconds, "Start time since the Unix epoch");
+    }
+
+    /// Samples the process metrics, skipping any that cannot be read.
+    #[cfg(target_os = "linux")]
+    #[allow(clippy::cast_precision_loss)] // Far below 2^52 in practice
+    pub(super) fn record() {
+        use std::fs;
+
+        /// `USER_HZ`, the unit of CPU times in `/proc`; 100 on all
+        /// architectures tether runs on.
+        const TICKS_PER_SECOND: f64 = 100.0;
+
+        if let Some(stat) = fs::read_to_string("/proc/self/stat")
+            .ok()
+            .as_deref()
+            .and_then(parse_stat)
+        {
+            metrics::gauge!(CPU_SECONDS).set((stat.utime + stat.stime) as f64 / TICKS_PER_SECOND);
+            if let Some(boot_time) = fs::read_to_string("/proc/stat")
+                .ok()
+                .as_deref()
+                .and_then(parse_boot_time)
+            {
+                metrics::gauge!(START_TIME)
+                    .set(boot_time as f64 + stat.start_ticks as f64 / TICKS_PER_SECOND);
+            }
+        }
+
+        if let Ok(status) = fs::read_to_string("/proc/self/status") {
+            for (field, name, scale) in [
+                ("VmRSS", RESIDENT_MEMORY, 1024.0),
+                ("VmSize", VIRTUAL_MEMORY, 1024.0),
+                ("Threads", THREADS, 1.0),
+            ] {
+                if let Some(value) = status_field(&status, field) {
+                    metrics::gauge!(name).set(value as f64 * scale);
+                }
+            }
+        }
+
+        if let Ok(fds) = fs::read_dir("/proc/self/fd") {
+            metrics::gauge!(OPEN_FDS).set(fds.count() as f64);
+        }
+    }
+
+    /// Process metrics are only available on Linux.
+    #[cfg(not(target_os = "linux"))]
+    pub(super) const fn record() {}
+
+    /// CPU and start times from `/proc/self/stat`, in clock ticks.
+    #[derive(Debug, PartialEq, Eq)]
+    pub(super) struct Stat {
+        pub utime: u64,
+        pub stime: u64,
+        pub start_ticks: u64,
+    }
+
+    /// Parses `/proc/self/stat`.
+    ///
+    /// Fields are counted after the command name, which is in parentheses
+    /// and may itself contain spaces.
+    pub(super) fn parse_stat(stat: &str) -> Option<Stat> {
+        let (_, fields) = stat.rsplit_once(')')?;
+        let fields: Vec<&str> = fields.split_whitespace().collect();
+        // Field 3 (state) is the first after the command name
+        let field = |number: usize| fields.get(number - 3)?.parse().ok();
+        Some(Stat {
+            utime: field(14)?,
+            stime: field(15)?,
+            start_ticks: field(22)?,
+        })
+    }
+
+    /// Parses the boot time, in seconds since the Unix epoch, from `/proc/stat`.
+    pub(super) fn parse_boot_time(stat: &str) -> Option<u64> {
+        stat.lines()
+            .find_map(|line| line.strip_prefix("btime "))
+            .and_then(|value| value.trim().parse().ok())
+    }
+
+    /// Reads a numeric field, such as `VmRSS:   1234 kB`, from `/proc/self/status`.
+    pub(super) fn status_field(status: &str, field: &str) -> Option<u64> {
+        status.lines().find_map(|line| {
+            let value = line.strip_prefix(field)?.strip_prefix(':')?;
+            value.split_whitespace().next()?.parse().ok()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::AppState;
+    use axum::body::Body;
+    use axum::routing::get;
+    use axum::Router;
+    use tower::ServiceExt;
+
+    async fn scrape(state: SharedState) -> String {
+        let response = render_metrics(State(state)).await.into_response();
+        assert_eq!(response.headers()[header::CONTENT_TYPE], CONTENT_TYPE);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        String::from_utf8(body.to_vec()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_records_requests_per_route_template() {
+        install();
+        let dir = tempfile::tempdir().unwrap();
+        let state = AppState::in_dir(dir.path(), None).into_shared();
+        let app = Router::new()
+            .route("/api/metrics-test/{id}", get(|| async { "ok" }))
+            .layer(axum::middleware::from_fn(track_http));
+
+        for id in ["a", "b"] {
+            let request = Request::get(format!("/api/metrics-test/{id}"))
+                .body(Body::empty())
+                .unwrap();
+            app.clone().oneshot(request).await.unwrap();
+        }
+        let request = Request::get("/nowhere").body(Body::empty()).unwrap();
+        app.oneshot(request).await.unwrap();
+
+        let body = scrape(state).await;
+        assert!(body.contains(
+            r#"tether_http_requests_total{method="GET",route="/api/metrics-test/{id}",status="200"} 2"#
+        ));
+        assert!(body.contains(r#"route="unmatched",status="404""#));
+        assert!(body.contains("tether_http_request_duration_seconds_bucket"));
+        assert!(body.contains("tether_passes_remaining 3"));
+    }
+
+    #[test]
+    fn test_parse_process_stat() {
+        let stat = "1234 (tether server) S 1 1234 1234 0 -1 4194560 2000 0 0 0 \
+            250 75 0 0 20 0 9 0 5000 123456789 3000 18446744073709551615";
+        assert_eq!(
+            process::parse_stat(stat),
+            Some(process::Stat {
+                utime: 250,
+                stime: 75,
+                start_ticks: 5000,
+            })
+        );
+        assert_eq!(process::parse_stat("garbage"), None);
+
+        assert_eq!(
+            process::parse_boot_time("cpu  1 2 3\nbtime 1700000000\nprocesses 42\n"),
+            Some(1_700_000_000)
+        );
+        assert_eq!(
+            process::status_field("Name:\ttether\nVmRSS:\t   5120 kB\nThreads:\t9\n", "VmRSS"),
+            Some(5120)
+        );
+    }
+}
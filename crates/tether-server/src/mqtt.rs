@@ -0,0 +1,711 @@
+//! MQTT publishing with Home Assistant discovery.
+//!
+//! When `[mqtt]` is enabled in the configuration, the server connects to an
+//! MQTT broker and publishes its state as retained messages under the base
+//! topic (`tether` by default):
+//!
+//! - `tether/status`: `online`, or `offline` once the server disconnects
+//! - `tether/phone_in_bedroom`: `ON` when the phone is out of range of the
+//!   Pi, i.e. not put away; `OFF` otherwise
+//! - `tether/rssi`: signal strength of the phone in dBm
+//! - `tether/passes_remaining` and `tether/passes_per_month`
+//! - `tether/curfew`: `ON` while the nightly curfew is in effect; `OFF`
+//!   otherwise, including when it is disabled
+//!
+//! Home Assistant discovery payloads are published under the discovery
+//! prefix, so these show up as binary sensors, sensors and a "Use pass"
+//! button on a single Tether device.
+//!
+//! Publishing a reason to `tether/pass/use` uses a pass, exactly like
+//! `POST /api/passes/use`, and is recorded in the audit log with the actor
+//! `mqtt`. The outcome is published to `tether/pass/use/result` as JSON.
+//!
+//! Configuration changes to `[mqtt]` take effect after a restart.
+
+use std::time::Duration;
+
+use axum::extract::State;
+use axum::Json;
+use chrono::Utc;
+use rumqttc::{AsyncClient, Event, LastWill, MqttOptions, Packet, QoS};
+use serde_json::{json, Value};
+use tether_core::{MqttConfig, SecretString};
+use tokio::sync::broadcast::error::RecvError;
+use tracing::{debug, info, warn};
+
+use crate::api::bluetooth::check_proximity;
+use crate::api::passes::{use_pass, UsePassRequest};
+use crate::audit::Actor;
+use crate::events::{ConfigSection, ServerEvent, Subscription};
+use crate::state::SharedState;
+
+/// Delay before reconnecting after the connection to the broker failed.
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+/// Keep-alive interval negotiated with the broker.
+const KEEP_ALIVE: Duration = Duration::from_secs(30);
+
+/// Messages that can be queued while the client is busy or disconnected.
+///
+/// The full state is published again after every reconnect, so messages
+/// dropped when the queue is full are not lost for good.
+const REQUEST_CAPACITY: usize = 64;
+
+/// Payload of the availability topic while connected.
+const ONLINE: &str = "online";
+
+/// Payload of the availability topic after disconnecting.
+const OFFLINE: &str = "offline";
+
+/// Actor recorded in the audit log for MQTT commands.
+const MQTT_ACTOR: &str = "mqtt";
+
+// ============================================================================
+// Topics
+// ============================================================================
+
+/// Topics derived from the MQTT configuration.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Topics {
+    base: String,
+    discovery_prefix: String,
+    node_id: String,
+}
+
+impl Topics {
+    /// Creates the topics for a configuration.
+    #[must_use]
+    pub fn new(config: &MqttConfig) -> Self {
+        Self {
+            base: config.base_topic.clone(),
+            discovery_prefix: config.discovery_prefix.clone(),
+            node_id: config.node_id.clone(),
+        }
+    }
+
+    /// Availability topic, `online` or `offline`.
+    #[must_use]
+    pub fn availability(&self) -> String {
+        format!("{}/status", self.base)
+    }
+
+    /// Whether the phone is away from the Pi, `ON` or `OFF`.
+    #[must_use]
+    pub fn phone_in_bedroom(&self) -> String {
+        format!("{}/phone_in_bedroom", self.base)
+    }
+
+    /// Signal strength of the phone in dBm.
+    #[must_use]
+    pub fn rssi(&self) -> String {
+        format!("{}/rssi", self.base)
+    }
+
+    /// Passes remaining this month.
+    #[must_use]
+    pub fn passes_remaining(&self) -> String {
+        format!("{}/passes_remaining", self.base)
+    }
+
+    /// Passes granted per month.
+    #[must_use]
+    pub fn passes_per_month(&self) -> String {
+        format!("{}/passes_per_month", self.base)
+    }
+
+    /// Whether the curfew is in effect, `ON` or `OFF`.
+    #[must_use]
+    pub fn curfew(&self) -> String {
+        format!("{}/curfew", self.base)
+    }
+
+    /// Command topic to use a pass; the payload is the reason.
+    #[must_use]
+    pub fn use_pass(&self) -> String {
+        format!("{}/pass/use", self.base)
+    }
+
+    /// Outcome of the last command to use a pass.
+    #[must_use]
+    pub fn use_pass_result(&self) -> String {
+        format!("{}/pass/use/result", self.base)
+    }
+
+    /// Home Assistant discovery topic for an entity.
+    fn discovery(&self, component: &str, object_id: &str) -> String {
+        format!(
+            "{}/{component}/{}/{object_id}/config",
+            self.discovery_prefix, self.node_id
+        )
+    }
+}
+
+// ============================================================================
+// Home Assistant Discovery
+// ============================================================================
+
+/// Returns the Home Assistant discovery topics and payloads.
+///
+/// All entities belong to one device identified by the node id, and are
+/// unavailable while the server is offline.
+#[must_use]
+pub fn discovery_messages(topics: &Topics) -> Vec<(String, Value)> {
+    let node_id = &topics.node_id;
+    let device = json!({
+        "identifiers": [node_id],
+        "name": "Tether",
+        "manufacturer": "Tether",
+        "model": "Phone proximity tracker",
+        "sw_version": env!("CARGO_PKG_VERSION"),
+    });
+    let entity = |object_id: &str, fields: Value| {
+        let mut payload = json!({
+            "unique_id": format!("{node_id}_{object_id}"),
+            "object_id": format!("{node_id}_{object_id}"),
+            "availability_topic": topics.availability(),
+            "device": device,
+        });
+        if let (Some(payload), Value::Object(fields)) = (payload.as_object_mut(), fields) {
+            payload.extend(fields);
+        }
+        payload
+    };
+
+    vec![
+        (
+            topics.discovery("binary_sensor", "phone_in_bedroom"),
+            entity(
+                "phone_in_bedroom",
+                json!({
+                    "name": "Phone in bedroom",
+                    "device_class": "presence",
+                    "state_topic": topics.phone_in_bedroom(),
+                    "payload_on": "ON",
+                    "payload_off": "OFF",
+                }),
+            ),
+        ),
+        (
+            topics.discovery("sensor", "rssi"),
+            entity(
+                "rssi",
+                json!({
+                    "name": "Phone signal strength",
+                    "device_class": "signal_strength",
+                    "unit_of_measurement": "dBm",
+                    "state_class": "measurement",
+                    "entity_category": "diagnostic",
+                    "state_topic": topics.rssi(),
+                }),
+            ),
+        ),
+        (
+            topics.discovery("sensor", "passes_remaining"),
+            entity(
+                "passes_remaining",
+                json!({
+                    "name": "Passes remaining",
+                    "icon": "mdi:ticket",
+                    "state_class": "measurement",
+                    "state_topic": topics.passes_remaining(),
+                }),
+            ),
+        ),
+        (
+            topics.discovery("sensor", "passes_per_month"),
+            entity(
+                "passes_per_month",
+                json!({
+                    "name": "Passes per month",
+                    "icon": "mdi:ticket-confirmation",
+                    "entity_category": "diagnostic",
+                    "state_topic": topics.passes_per_month(),
+                }),
+            ),
+        ),
+        (
+            topics.discovery("binary_sensor", "curfew"),
+            entity(
+                "curfew",
+                json!({
+                    "name": "Curfew",
+                    "icon": "mdi:weather-night",
+                    "state_topic": topics.curfew(),
+                    "payload_on": "ON",
+                    "payload_off": "OFF",
+                }),
+            ),
+        ),
+        (
+            topics.discovery("button", "use_pass"),
+            entity(
+                "use_pass",
+                json!({
+                    "name": "Use pass",
+                    "icon": "mdi:ticket-outline",
+                    "command_topic": topics.use_pass(),
+                    "payload_press": "Used from Home Assistant",
+                }),
+            ),
+        ),
+    ]
+}
+
+// ============================================================================
+// Publisher
+// ============================================================================
+
+/// Publishes retained state through an MQTT client.
+#[derive(Clone)]
+struct Publisher {
+    client: AsyncClient,
+    topics: Topics,
+}
+
+impl Publisher {
+    /// Queues a retained message, dropping it if the queue is full.
+    fn retain(&self, topic: &str, payload: impl Into<Vec<u8>>) {
+        if let Err(e) = self
+            .client
+            .try_publish(topic, QoS::AtLeastOnce, true, payload)
+        {
+            debug!(topic, error = %e, "Dropped MQTT message");
+        }
+    }
+
+    fn proximity(&self, is_nearby: bool, rssi_dbm: Option<i16>) {
+        let in_bedroom = if is_nearby { "OFF" } else { "ON" };
+        self.retain(&self.topics.phone_in_bedroom(), in_bedroom);
+        if let Some(rssi) = rssi_dbm {
+            self.retain(&self.topics.rssi(), rssi.to_string());
+        }
+    }
+
+    fn passes(&self, remaining: u32, per_month: u32) {
+        self.retain(&self.topics.passes_remaining(), remaining.to_string());
+        self.retain(&self.topics.passes_per_month(), per_month.to_string());
+    }
+
+    fn curfew(&self, is_active: bool) {
+        self.retain(&self.topics.curfew(), if is_active { "ON" } else { "OFF" });
+    }
+
+    /// Announces the entities and publishes the current state.
+    async fn connected(&self, state: &SharedState) {
+        self.retain(&self.topics.availability(), ONLINE);
+        for (topic, payload) in discovery_messages(&self.topics) {
+            self.retain(&topic, payload.to_string());
+        }
+        if let Err(e) = self
+            .client
+            .try_subscribe(self.topics.use_pass(), QoS::AtLeastOnce)
+        {
+            warn!(error = %e, "Failed to subscribe to the MQTT pass command topic");
+        }
+
+        self.publish_passes(state).await;
+        self.publish_curfew(state).await;
+        let last_nearby = *state
+            .last_nearby
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        if let Some(is_nearby) = last_nearby {
+            self.proximity(is_nearby, None);
+        }
+    }
+
+    async fn publish_passes(&self, state: &SharedState) {
+        let pass_manager = state.pass_manager.read().await;
+        self.passes(pass_manager.remaining(), pass_manager.per_month());
+    }
+
+    async fn publish_curfew(&self, state: &SharedState) {
+        let config = state.config.read().await;
+        let is_active = config
+            .curfew
+            .is_active_at(Utc::now(), &config.system.timezone);
+        drop(config);
+        self.curfew(is_active);
+    }
+
+    /// Publishes the state changed by a server event.
+    async fn event(&self, state: &SharedState, event: &ServerEvent) {
+        match event {
+            ServerEvent::ProximityChanged {
+                is_nearby,
+                rssi_dbm,
+                ..
+            } => self.proximity(*is_nearby, *rssi_dbm),
+            ServerEvent::PassUsed { remaining, .. } => {
+                self.retain(&self.topics.passes_remaining(), remaining.to_string());
+            }
+            ServerEvent::MonthReset {
+                remaining,
+                per_month,
+                ..
+            } => self.passes(*remaining, *per_month),
+            ServerEvent::ConfigChanged {
+                section: ConfigSection::Passes,
+            } => self.publish_passes(state).await,
+            ServerEvent::ConfigChanged {
+                section: ConfigSection::Curfew | ConfigSection::Timezone,
+            } => self.publish_curfew(state).await,
+            ServerEvent::CurfewStarted { .. } => self.curfew(true),
+            ServerEvent::CurfewEnded { .. } => self.curfew(false),
+            ServerEvent::ConfigChanged { .. }
+            | ServerEvent::ScannerHealthChanged { .. }
+            | ServerEvent::ChangeRequested { .. }
+            | ServerEvent::ChangeResolved { .. } => {}
+        }
+    }
+
+    /// Uses a pass on behalf of a command message and publishes the outcome.
+    async fn use_pass(&self, state: &SharedState, payload: &[u8]) {
+        let reason = String::from_utf8_lossy(payload).trim().to_string();
+        let request = Json(UsePassRequest { reason });
+        let result = match use_pass(State(state.clone()), Actor::new(MQTT_ACTOR), request).await {
+            Ok(Json(response)) => {
+                info!(remaining = response.remaining, "Pass used over MQTT");
+                json!(response)
+            }
+            Err(e) => {
+                info!(error = %e, "Rejected MQTT command to use a pass");
+                json!({
+                    "success": false,
+                    "error": e.error_code(),
+                    "message": e.message(),
+                })
+            }
+        };
+        if let Err(e) = self.client.try_publish(
+            self.topics.use_pass_result(),
+            QoS::AtLeastOnce,
+            false,
+            result.to_string(),
+        ) {
+            debug!(error = %e, "Dropped MQTT pass command result");
+        }
+    }
+}
+
+/// Builds the client options, including credentials and the last will.
+fn options(config: &MqttConfig, password: Option<&str>, topics: &Topics) -> MqttOptions {
+    let mut options = MqttOptions::new(&config.node_id, &config.host, config.port);
+    options.set_keep_alive(KEEP_ALIVE);
+    options.set_last_will(LastWill::new(
+        topics.availability(),
+        OFFLINE,
+        QoS::AtLeastOnce,
+        true,
+    ));
+    if let Some(username) = &config.username {
+        options.set_credentials(username, password.unwrap_or_default());
+    }
+    options
+}
+
+// ============================================================================
+// Background Task
+// ============================================================================
+
+/// Spawns the MQTT publisher if it is enabled in the configuration.
+pub fn spawn(state: SharedState) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(run(state))
+}
+
+/// Connects to the broker and publishes state until the server stops.
+///
+/// Returns immediately if MQTT is disabled. Connection failures are retried
+/// every few seconds; after each reconnect the full state is published again.
+pub async fn run(state: SharedState) {
+    let config = state.config.read().await.mqtt.clone();
+    if !config.enabled {
+        debug!("MQTT disabled");
+        return;
+    }
+
+    let password = match &config.password_id {
+        Some(id) => {
+            let password = state.secrets.lock().await.get(id);
+            match password {
+                Ok(password) => password,
+                Err(e) => {
+                    warn!(error = %e, "MQTT disabled: failed to read the broker password");
+                    return;
+                }
+            }
+        }
+        None => None,
+    };
+
+    let topics = Topics::new(&config);
+    let options = options(
+        &config,
+        password.as_ref().map(SecretString::expose),
+        &topics,
+    );
+    let (client, mut eventloop) = AsyncClient::new(options, REQUEST_CAPACITY);
+    let publisher = Publisher { client, topics };
+    info!(host = %config.host, port = config.port, "MQTT publisher started");
+
+    let poller = (config.scan_interval_secs > 0).then(|| {
+        let interval = Duration::from_secs(u64::from(config.scan_interval_secs));
+        tokio::spawn(poll_proximity(state.clone(), publisher.clone(), interval))
+    });
+
+    let Subscription { mut receiver, .. } = state.events.subscribe(None);
+    loop {
+        tokio::select! {
+            notification = eventloop.poll() => match notification {
+                Ok(Event::Incoming(Packet::ConnAck(_))) => {
+                    info!("Connected to MQTT broker");
+                    publisher.connected(&state).await;
+                }
+                Ok(Event::Incoming(Packet::Publish(message))) => {
+                    if message.topic == publisher.topics.use_pass() {
+                        publisher.use_pass(&state, &message.payload).await;
+                    }
+                }
+                Ok(_) => {}
+                Err(e) => {
+                    warn!(error = %e, "MQTT connection failed, reconnecting");
+                    tokio::time::sleep(RECONNECT_DELAY).await;
+                }
+            },
+            received = receiver.recv() => match received {
+                Ok(message) => publisher.event(&state, &message.event).await,
+                Err(RecvError::Lagged(_)) => {
+                    publisher.publish_passes(&state).await;
+                    publisher.publish_curfew(&state).await;
+                }
+                Err(RecvError::Closed) => break,
+            },
+        }
+    }
+
+    if let Some(poller) = poller {
+        poller.abort();
+    }
+}
+
+/// Checks proximity periodically so the sensors stay current even when no
+/// client is polling.
+async fn poll_proximity(state: SharedState, publisher: Publisher, interval: Duration) {
+    let mut interval = tokio::time::interval(interval);
+    loop {
+        interval.tick().await;
+        match check_proximity(State(state.clone())).await {
+            Ok(Json(proximity)) => publisher.proximity(proximity.is_nearby, proximity.rssi_dbm),
+            Err(e) => debug!(error = %e, "Proximity check for MQTT failed"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::AppState;
+    use bytes::BytesMut;
+    use rumqttc::mqttbytes::v4::{self, ConnAck, ConnectReturnCode, PubAck, SubAck};
+    use rumqttc::mqttbytes::Error as PacketError;
+    use rumqttc::{Publish, SubscribeReasonCode};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::{TcpListener, TcpStream};
+
+    /// A minimal MQTT broker for a single client.
+    struct StubBroker {
+        stream: TcpStream,
+        buffer: BytesMut,
+    }
+
+    impl StubBroker {
+        async fn accept(listener: &TcpListener) -> Self {
+            let (stream, _) = tokio::time::timeout(Duration::from_secs(5), listener.accept())
+                .await
+                .expect("client did not connect")
+                .unwrap();
+            Self {
+                stream,
+                buffer: BytesMut::new(),
+            }
+        }
+
+        async fn read(&mut self) -> Packet {
+            loop {
+                match v4::read(&mut self.buffer, 1 << 20) {
+                    Ok(packet) => return packet,
+                    Err(PacketError::InsufficientBytes(_)) => {
+                        let read = tokio::time::timeout(
+                            Duration::from_secs(5),
+                            self.stream.read_buf(&mut self.buffer),
+                        )
+                        .await
+                        .expect("no packet from client")
+                        .unwrap();
+                        assert!(read > 0, "client disconnected");
+                    }
+                    Err(e) => panic!("invalid packet: {e:?}"),
+                }
+            }
+        }
+
+        async fn send(&mut self, packet: Packet) {
+            let mut buffer = BytesMut::new();
+            match packet {
+                Packet::ConnAck(ack) => ack.write(&mut buffer),
+                Packet::SubAck(ack) => ack.write(&mut buffer),
+                Packet::PubAck(ack) => ack.write(&mut buffer),
+                Packet::Publish(publish) => publish.write(&mut buffer),
+                other => panic!("unsupported packet {other:?}"),
+            }
+            .unwrap();
+            self.stream.write_all(&buffer).await.unwrap();
+        }
+
+        /// Acknowledges packets until a publish to `topic` arrives.
+        async fn expect_publish(&mut self, topic: &str) -> Publish {
+            loop {
+                match self.read().await {
+                    Packet::Connect(_) => {
+                        self.send(Packet::ConnAck(ConnAck::new(
+                            ConnectReturnCode::Success,
+                            false,
+                        )))
+                        .await;
+                    }
+                    Packet::Subscribe(subscribe) => {
+                        let codes = vec![SubscribeReasonCode::Success(QoS::AtLeastOnce)];
+                        self.send(Packet::SubAck(SubAck::new(subscribe.pkid, codes)))
+                            .await;
+                    }
+                    Packet::Publish(publish) => {
+                        if publish.qos != QoS::AtMostOnce {
+                            self.send(Packet::PubAck(PubAck::new(publish.pkid))).await;
+                        }
+                        if publish.topic == topic {
+                            return publish;
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    fn payload(publish: &Publish) -> &str {
+        std::str::from_utf8(&publish.payload).unwrap()
+    }
+
+    #[test]
+    fn test_discovery_messages() {
+        let config = MqttConfig {
+            node_id: "bedroom".to_string(),
+            ..MqttConfig::default()
+        };
+        let topics = Topics::new(&config);
+        let messages = discovery_messages(&topics);
+
+        let (topic, sensor) = &messages[0];
+        assert_eq!(
+            topic,
+            "homeassistant/binary_sensor/bedroom/phone_in_bedroom/config"
+        );
+        assert_eq!(sensor["state_topic"], "tether/phone_in_bedroom");
+        assert_eq!(sensor["unique_id"], "bedroom_phone_in_bedroom");
+        assert_eq!(sensor["availability_topic"], "tether/status");
+        assert_eq!(sensor["device"]["identifiers"][0], "bedroom");
+
+        let button = messages
+            .iter()
+            .find(|(topic, _)| topic.contains("/button/"))
+            .map(|(_, payload)| payload)
+            .unwrap();
+        assert_eq!(button["command_topic"], "tether/pass/use");
+
+        let (_, curfew) = messages
+            .iter()
+            .find(|(topic, _)| topic.ends_with("/curfew/config"))
+            .unwrap();
+        assert_eq!(curfew["state_topic"], "tether/curfew");
+    }
+
+    #[tokio::test]
+    async fn test_publishes_state_and_uses_pass_on_command() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = AppState::in_dir(dir.path(), None).into_shared();
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        {
+            let mut config = state.config.write().await;
+            config.mqtt.enabled = true;
+            config.mqtt.host = "127.0.0.1".to_string();
+            config.mqtt.port = listener.local_addr().unwrap().port();
+            config.mqtt.scan_interval_secs = 0;
+        }
+        let task = spawn(state.clone());
+        let mut broker = StubBroker::accept(&listener).await;
+
+        let online = broker.expect_publish("tether/status").await;
+        assert_eq!(payload(&online), "online");
+        assert!(online.retain);
+        broker
+            .expect_publish("homeassistant/sensor/tether/passes_remaining/config")
+            .await;
+        let remaining = broker.expect_publish("tether/passes_remaining").await;
+        assert_eq!(payload(&remaining), "3");
+        let curfew = broker.expect_publish("tether/curfew").await;
+        assert_eq!(payload(&curfew), "OFF");
+
+        // Server events are forwarded
+        state.record_proximity(false, Some(-80), "A4:C1:38:12:34:56");
+        let in_bedroom = broker.expect_publish("tether/phone_in_bedroom").await;
+        assert_eq!(payload(&in_bedroom), "ON");
+        let rssi = broker.expect_publish("tether/rssi").await;
+        assert_eq!(payload(&rssi), "-80");
+        state.events.publish(ServerEvent::CurfewStarted {
+            start: "22:00".to_string(),
+            end: "06:00".to_string(),
+        });
+        let curfew = broker.expect_publish("tether/curfew").await;
+        assert_eq!(payload(&curfew), "ON");
+
+        // A command uses a pass and reports the outcome
+        broker
+            .send(Packet::Publish(Publish::new(
+                "tether/pass/use",
+                QoS::AtMostOnce,
+                "Late shift",
+            )))
+            .await;
+        let result = broker.expect_publish("tether/pass/use/result").await;
+        let result: Value = serde_json::from_slice(&result.payload).unwrap();
+        assert_eq!(result["success"], true);
+        assert_eq!(result["reason"], "Late shift");
+        let remaining = broker.expect_publish("tether/passes_remaining").await;
+        assert_eq!(payload(&remaining), "2");
+        assert_eq!(state.pass_manager.read().await.remaining(), 2);
+
+        // An empty reason is rejected like over HTTP
+        broker
+            .send(Packet::Publish(Publish::new(
+                "tether/pass/use",
+                QoS::AtMostOnce,
+                "",
+            )))
+            .await;
+        let result = broker.expect_publish("tether/pass/use/result").await;
+        let result: Value = serde_json::from_slice(&result.payload).unwrap();
+        assert_eq!(result["success"], false);
+        assert_eq!(state.pass_manager.read().await.remaining(), 2);
+
+        task.abort();
+    }
+
+    #[tokio::test]
+    async fn test_disabled_returns_immediately() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = AppState::in_dir(dir.path(), None).into_shared();
+        tokio::time::timeout(Duration::from_secs(1), run(state))
+            .await
+            .unwrap();
+    }
+}
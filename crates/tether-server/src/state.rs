@@ -2,21 +2,36 @@
 //!
 //! This module provides the [`AppState`] struct which holds all shared state
 //! including configuration, pass management, and Bluetooth connectivity.
-//! State is wrapped in [`SharedState`] (Arc<RwLock<AppState>>) for safe
-//! concurrent access across async request handlers.
+//! Each component sits behind its own lock, so a multi-second Bluetooth scan
+//! never blocks unrelated requests such as using a pass. State is shared
+//! across handlers as [`SharedState`] (`Arc<AppState>`).
+//!
+//! # Locking
+//!
+//! - Never hold a lock across a Bluetooth scan. Take the scanner handle with
+//!   [`AppState::scanner`] and copy the settings the scan needs instead.
+//! - When several locks are needed at once, take them in field order
+//!   (`config`, `pass_manager`, `secrets`, `webhooks`, `changes`, `audit`,
+//!   `bluetooth`) to avoid deadlocks.
 
 use std::path::PathBuf;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex as SyncMutex, PoisonError};
 
-use tether_core::{BluetoothScanner, Config, PassManager};
-use tokio::sync::RwLock;
+use serde_json::Value;
+use tether_core::{BluetoothScanner, Config, PassManager, SecretStore};
+use tokio::sync::{Mutex, RwLock};
+
+use crate::audit::{Actor, AuditAction, AuditLog};
+use crate::events::{EventBus, ServerEvent};
+use crate::guard::ChangeStore;
+use crate::supervisor::HealthTracker;
+use crate::webhooks::WebhookStore;
 
 /// Type alias for thread-safe shared application state.
 ///
-/// Uses `Arc` for reference counting across async tasks and `RwLock` for
-/// interior mutability with read-write semantics. Prefer `read()` when
-/// only reading state to allow concurrent readers.
-pub type SharedState = Arc<RwLock<AppState>>;
+/// The components of [`AppState`] are locked individually, so the state
+/// itself only needs reference counting.
+pub type SharedState = Arc<AppState>;
 
 /// Core application state shared across all HTTP handlers.
 ///
@@ -24,24 +39,57 @@ pub type SharedState = Arc<RwLock<AppState>>;
 ///
 /// - `config`: Server and application configuration loaded from disk
 /// - `pass_manager`: Manages monthly passes, history, and persistence
-/// - `bluetooth`: Handles Bluetooth device proximity detection
+/// - `secrets`: Encrypted credentials referenced from the configuration
+/// - `webhooks`: Webhook subscriptions and their queued deliveries
+/// - `changes`: Changes to guarded settings waiting for approval
+/// - `audit`: Hash-chained log of mutating API calls
+/// - `bluetooth`: Handle to the scanner used for proximity detection
+/// - `bluetooth_health`: Scan outcomes and recoveries of the scanner
+/// - `events`: Bus for events streamed to clients by `/api/events`
+/// - `last_nearby`: Result of the last proximity check, to detect changes
 /// - `config_path`: Path to the config file for saving changes
 /// - `passes_path`: Path to the passes JSON file
 ///
 /// # Thread Safety
 ///
-/// All fields are designed to be accessed through the `SharedState` wrapper.
-/// The `PassManager` handles its own persistence, so writes to state should
-/// be followed by explicit persistence calls where needed.
+/// Each mutable component has its own lock; see the module documentation
+/// for the locking rules. The `PassManager` handles its own persistence,
+/// while configuration changes are saved explicitly with
+/// [`AppState::save_config`].
 pub struct AppState {
     /// Application configuration loaded from TOML file.
-    pub config: Config,
+    pub config: RwLock<Config>,
 
     /// Manages pass allocation, usage, and history.
-    pub pass_manager: PassManager,
+    pub pass_manager: RwLock<PassManager>,
+
+    /// Encrypted store for WiFi passwords and other credentials.
+    pub secrets: Mutex<SecretStore>,
+
+    /// Webhook subscriptions, queued deliveries and the delivery log.
+    pub webhooks: Mutex<WebhookStore>,
+
+    /// Changes to guarded settings waiting for approval.
+    pub changes: Mutex<ChangeStore>,
+
+    /// Append-only audit log of mutating API calls.
+    pub audit: Mutex<AuditLog>,
 
     /// Bluetooth scanner for proximity detection.
-    pub bluetooth: Option<BluetoothScanner>,
+    ///
+    /// Replaced by the supervisor when the adapter disappears or wedges.
+    /// Scans run on a clone of the handle, so replacing it never waits for
+    /// a scan in progress.
+    pub bluetooth: RwLock<Option<Arc<BluetoothScanner>>>,
+
+    /// Health of the Bluetooth scanner.
+    pub bluetooth_health: HealthTracker,
+
+    /// Publishes server events to `/api/events` subscribers.
+    pub events: EventBus,
+
+    /// Whether the phone was nearby at the last proximity check.
+    pub last_nearby: SyncMutex<Option<bool>>,
 
     /// Path to the configuration file.
     pub config_path: PathBuf,
@@ -60,40 +108,138 @@ impl AppState {
     ///
     /// * `config` - Loaded configuration from disk
     /// * `pass_manager` - Initialized pass manager with loaded history
+    /// * `secrets` - Opened secrets store
+    /// * `webhooks` - Opened webhook store
+    /// * `changes` - Opened pending change store
+    /// * `audit` - Opened audit log
     /// * `bluetooth` - Optional Bluetooth scanner (None if not available)
     /// * `config_path` - Path to the config file
     /// * `passes_path` - Path to the passes JSON file
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         config: Config,
         pass_manager: PassManager,
+        secrets: SecretStore,
+        webhooks: WebhookStore,
+        changes: ChangeStore,
+        audit: AuditLog,
         bluetooth: Option<BluetoothScanner>,
         config_path: PathBuf,
         passes_path: PathBuf,
     ) -> Self {
+        let events = EventBus::new();
         Self {
-            config,
-            pass_manager,
-            bluetooth,
+            config: RwLock::new(config),
+            pass_manager: RwLock::new(pass_manager),
+            secrets: Mutex::new(secrets),
+            webhooks: Mutex::new(webhooks),
+            changes: Mutex::new(changes),
+            audit: Mutex::new(audit),
+            bluetooth_health: HealthTracker::new(bluetooth.is_some()).with_events(events.clone()),
+            bluetooth: RwLock::new(bluetooth.map(Arc::new)),
+            events,
+            last_nearby: SyncMutex::new(None),
             config_path,
             passes_path,
         }
     }
 
-    /// Wraps the `AppState` in an `Arc<RwLock<_>>` for shared access.
+    /// Wraps the `AppState` in an `Arc` for shared access.
     ///
     /// This is the preferred way to create state for use with Axum handlers.
     #[must_use]
     pub fn into_shared(self) -> SharedState {
-        Arc::new(RwLock::new(self))
+        Arc::new(self)
+    }
+
+    /// Returns a handle to the current Bluetooth scanner, if any.
+    ///
+    /// The handle stays usable after the supervisor replaces the scanner,
+    /// and holding it does not block other requests.
+    pub async fn scanner(&self) -> Option<Arc<BluetoothScanner>> {
+        self.bluetooth.read().await.clone()
+    }
+
+    /// Replaces the Bluetooth scanner.
+    ///
+    /// Scanners share one scan lock, so a scan still running on the old
+    /// scanner finishes before the new one starts scanning.
+    pub async fn set_scanner(&self, scanner: Option<BluetoothScanner>) {
+        *self.bluetooth.write().await = scanner.map(Arc::new);
+    }
+
+    /// Records the result of a proximity check.
+    ///
+    /// Publishes a `proximity_changed` event when the phone moved in or out
+    /// of range since the last check, or on the first check.
+    pub fn record_proximity(&self, is_nearby: bool, rssi_dbm: Option<i16>, device_address: &str) {
+        let mut last_nearby = self
+            .last_nearby
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        if last_nearby.replace(is_nearby) != Some(is_nearby) {
+            self.events.publish(ServerEvent::ProximityChanged {
+                is_nearby,
+                rssi_dbm,
+                device_address: device_address.to_string(),
+            });
+        }
+    }
+
+    /// Records a successful mutating action in the audit log.
+    ///
+    /// The action has already taken effect, so a failure to record it is
+    /// logged rather than returned. Call it while still holding the locks
+    /// the action took, so entries are in the order actions were made.
+    pub async fn record_audit(
+        &self,
+        actor: &Actor,
+        action: AuditAction,
+        before: Option<Value>,
+        after: Option<Value>,
+    ) {
+        let mut audit = self.audit.lock().await;
+        if let Err(e) = audit.append(actor, action, before, after, chrono::Utc::now()) {
+            tracing::error!(error = %e, ?action, "Failed to record audit entry");
+        }
     }
 
-    /// Saves the current configuration to disk.
+    /// Saves a configuration to the config file.
+    ///
+    /// Pass the configuration while still holding its write lock, so that
+    /// concurrent updates are saved in the order they were made.
     ///
     /// # Errors
     ///
     /// Returns an error if the configuration cannot be written.
-    pub fn save_config(&self) -> tether_core::ConfigResult<()> {
-        self.config.save(&self.config_path)
+    pub fn save_config(&self, config: &Config) -> tether_core::ConfigResult<()> {
+        config.save(&self.config_path)
+    }
+}
+
+#[cfg(test)]
+impl AppState {
+    /// Creates state with default configuration, storing its files in `dir`.
+    pub(crate) fn in_dir(dir: &std::path::Path, bluetooth: Option<BluetoothScanner>) -> Self {
+        let passes_path = dir.join("passes.json");
+        let pass_manager = PassManager::load_or_create(&passes_path, 3).unwrap();
+        let secrets = SecretStore::open(dir.join("secrets.json"), dir.join("secrets.key")).unwrap();
+        let webhook_secrets =
+            SecretStore::open(dir.join("webhook-secrets.json"), dir.join("secrets.key")).unwrap();
+        let webhooks = WebhookStore::open(dir.join("webhooks.json"), webhook_secrets).unwrap();
+        let changes = ChangeStore::open(dir.join("pending-changes.json")).unwrap();
+        let audit = AuditLog::open(dir.join("audit.jsonl")).unwrap();
+        Self::new(
+            Config::default(),
+            pass_manager,
+            secrets,
+            webhooks,
+            changes,
+            audit,
+            bluetooth,
+            dir.join("config.toml"),
+            passes_path,
+        )
     }
 }
 
@@ -110,20 +256,37 @@ pub trait SharedStateExt {
 
 impl SharedStateExt for SharedState {
     async fn get_config(&self) -> Config {
-        self.read().await.config.clone()
+        self.config.read().await.clone()
     }
 
     async fn is_configured(&self) -> bool {
-        let state = self.read().await;
-        state.config.system.onboarding_complete
+        self.config.read().await.system.onboarding_complete
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use tether_core::{Config, PassManager};
     use tempfile::tempdir;
+    use tether_core::{Config, PassManager};
+
+    fn open_secrets(dir: &std::path::Path) -> SecretStore {
+        SecretStore::open(dir.join("secrets.json"), dir.join("secrets.key")).unwrap()
+    }
+
+    fn open_webhooks(dir: &std::path::Path) -> WebhookStore {
+        let secrets =
+            SecretStore::open(dir.join("webhook-secrets.json"), dir.join("secrets.key")).unwrap();
+        WebhookStore::open(dir.join("webhooks.json"), secrets).unwrap()
+    }
+
+    fn open_changes(dir: &std::path::Path) -> ChangeStore {
+        ChangeStore::open(dir.join("pending-changes.json")).unwrap()
+    }
+
+    fn open_audit(dir: &std::path::Path) -> AuditLog {
+        AuditLog::open(dir.join("audit.jsonl")).unwrap()
+    }
 
     #[tokio::test]
     async fn test_shared_state_creation() {
@@ -137,6 +300,10 @@ mod tests {
         let state = AppState::new(
             config.clone(),
             pass_manager,
+            open_secrets(dir.path()),
+            open_webhooks(dir.path()),
+            open_changes(dir.path()),
+            open_audit(dir.path()),
             None,
             config_path,
             passes_path,
@@ -157,16 +324,25 @@ mod tests {
         let config = Config::default();
         let pass_manager = PassManager::load_or_create(&passes_path, 3).unwrap();
 
-        let state = AppState::new(config, pass_manager, None, config_path, passes_path);
+        let secrets = open_secrets(dir.path());
+        let webhooks = open_webhooks(dir.path());
+        let state = AppState::new(
+            config,
+            pass_manager,
+            secrets,
+            webhooks,
+            open_changes(dir.path()),
+            open_audit(dir.path()),
+            None,
+            config_path,
+            passes_path,
+        );
         let shared = state.into_shared();
 
         assert!(!shared.is_configured().await);
 
         // Mark as configured
-        {
-            let mut state = shared.write().await;
-            state.config.system.onboarding_complete = true;
-        }
+        shared.config.write().await.system.onboarding_complete = true;
 
         assert!(shared.is_configured().await);
     }
@@ -0,0 +1,435 @@
+//! Bluetooth adapter supervision and automatic recovery.
+//!
+//! The scanner created at startup breaks when `bluetoothd` restarts or the
+//! adapter's controller wedges. The supervisor runs in the background and:
+//!
+//! - Watches the adapter for removal and power changes via BlueZ events
+//! - Periodically checks that the adapter is still present and powered
+//! - Re-creates the BlueZ session when the adapter disappears or scans keep
+//!   failing, optionally power-cycling the adapter first
+//! - Retries with exponential backoff while no adapter is available
+//!
+//! Scan outcomes and recovery attempts are recorded in a [`HealthTracker`],
+//! which is reported by `/api/system/status`.
+
+use std::fmt::Display;
+use std::sync::{Mutex, PoisonError};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use tether_core::{AdapterChange, AdapterMonitor, BluetoothScanner};
+use tracing::{debug, info, warn};
+
+use crate::events::{EventBus, ServerEvent};
+use crate::state::SharedState;
+
+/// How often the adapter is checked when no events arrive.
+const HEALTH_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Consecutive scan failures after which the scanner is re-created.
+const FAILURE_THRESHOLD: u32 = 3;
+
+/// First delay between attempts to re-create the scanner.
+const INITIAL_RETRY_DELAY: Duration = Duration::from_secs(5);
+
+/// Upper bound for the delay between attempts to re-create the scanner.
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(300);
+
+// ============================================================================
+// Health Tracking
+// ============================================================================
+
+pub use tether_client::types::{BluetoothHealth, ScannerState};
+
+/// Mutable health counters behind [`HealthTracker`].
+#[derive(Debug)]
+struct HealthRecord {
+    state: ScannerState,
+    last_successful_scan: Option<DateTime<Utc>>,
+    consecutive_failures: u32,
+    total_failures: u64,
+    recoveries: u32,
+    last_error: Option<(String, DateTime<Utc>)>,
+}
+
+/// Records scanner health, shared by request handlers and the supervisor.
+///
+/// Uses a synchronous mutex so handlers can record outcomes without taking
+/// any of the application state locks. State changes are published as
+/// `scanner_health_changed` events when an [`EventBus`] is attached.
+#[derive(Debug)]
+pub struct HealthTracker {
+    record: Mutex<HealthRecord>,
+    events: Option<EventBus>,
+}
+
+impl HealthTracker {
+    /// Creates a tracker for a scanner that is initially (un)available.
+    #[must_use]
+    pub const fn new(available: bool) -> Self {
+        Self {
+            record: Mutex::new(HealthRecord {
+                state: if available {
+                    ScannerState::Healthy
+                } else {
+                    ScannerState::Unavailable
+                },
+                last_successful_scan: None,
+                consecutive_failures: 0,
+                total_failures: 0,
+                recoveries: 0,
+                last_error: None,
+            }),
+            events: None,
+        }
+    }
+
+    /// Publishes state changes to `events`.
+    #[must_use]
+    pub fn with_events(mut self, events: EventBus) -> Self {
+        self.events = Some(events);
+        self
+    }
+
+    fn record(&self) -> std::sync::MutexGuard<'_, HealthRecord> {
+        self.record.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Applies `change` to the record and publishes any state change.
+    fn update(&self, change: impl FnOnce(&mut HealthRecord)) {
+        let mut record = self.record();
+        let previous = record.state;
+        change(&mut record);
+        if record.state == previous {
+            return;
+        }
+
+        let event = ServerEvent::ScannerHealthChanged {
+            state: record.state,
+            previous,
+            last_error: record.last_error.as_ref().map(|(error, _)| error.clone()),
+        };
+        drop(record);
+        if let Some(events) = &self.events {
+            events.publish(event);
+        }
+    }
+
+    /// Records a successful scan.
+    pub fn record_success(&self) {
+        self.update(|record| {
+            record.state = ScannerState::Healthy;
+            record.last_successful_scan = Some(Utc::now());
+            record.consecutive_failures = 0;
+        });
+    }
+
+    /// Records a failed scan.
+    pub fn record_failure(&self, error: &impl Display) {
+        self.update(|record| {
+            if record.state == ScannerState::Healthy {
+                record.state = ScannerState::Degraded;
+            }
+            record.consecutive_failures = record.consecutive_failures.saturating_add(1);
+            record.total_failures = record.total_failures.saturating_add(1);
+            record.last_error = Some((error.to_string(), Utc::now()));
+        });
+    }
+
+    /// Marks the scanner as being re-created.
+    pub fn mark_recovering(&self) {
+        self.update(|record| record.state = ScannerState::Recovering);
+    }
+
+    /// Marks the scanner as unavailable because of `error`.
+    pub fn mark_unavailable(&self, error: &impl Display) {
+        self.update(|record| {
+            record.state = ScannerState::Unavailable;
+            record.last_error = Some((error.to_string(), Utc::now()));
+        });
+    }
+
+    /// Records that the scanner was successfully re-created.
+    pub fn record_recovery(&self) {
+        self.update(|record| {
+            record.state = ScannerState::Healthy;
+            record.consecutive_failures = 0;
+            record.recoveries = record.recoveries.saturating_add(1);
+        });
+    }
+
+    /// Returns the number of scan failures since the last success.
+    #[must_use]
+    pub fn consecutive_failures(&self) -> u32 {
+        self.record().consecutive_failures
+    }
+
+    /// Returns a snapshot of the current health.
+    #[must_use]
+    pub fn snapshot(&self) -> BluetoothHealth {
+        let record = self.record();
+        BluetoothHealth {
+            state: record.state,
+            last_successful_scan_utc: record.last_successful_scan.map(|at| at.to_rfc3339()),
+            consecutive_failures: record.consecutive_failures,
+            total_failures: record.total_failures,
+            recoveries: record.recoveries,
+            last_error: record.last_error.as_ref().map(|(error, _)| error.clone()),
+            last_error_at_utc: record.last_error.as_ref().map(|(_, at)| at.to_rfc3339()),
+        }
+    }
+}
+
+// ============================================================================
+// Supervisor
+// ============================================================================
+
+/// Spawns the supervisor as a background task.
+pub fn spawn(state: SharedState) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(run(state))
+}
+
+/// Supervises the Bluetooth scanner until the task is cancelled.
+pub async fn run(state: SharedState) {
+    info!("Bluetooth supervisor started");
+
+    let mut retry_delay = INITIAL_RETRY_DELAY;
+    loop {
+        if state.scanner().await.is_some() {
+            watch(&state).await;
+        } else if recover(&state).await {
+            retry_delay = INITIAL_RETRY_DELAY;
+        } else {
+            debug!(
+                delay_secs = retry_delay.as_secs(),
+                "Retrying Bluetooth recovery later"
+            );
+            tokio::time::sleep(retry_delay).await;
+            retry_delay = (retry_delay * 2).min(MAX_RETRY_DELAY);
+        }
+    }
+}
+
+/// Watches a working scanner until it has to be replaced.
+///
+/// Returns after the scanner has been removed from the state.
+async fn watch(state: &SharedState) {
+    let Some(scanner) = state.scanner().await else {
+        return;
+    };
+    let monitor = scanner.monitor_adapter().await;
+    drop(scanner);
+
+    // Without events the periodic check still detects failures
+    let mut monitor = match monitor {
+        Ok(monitor) => Some(monitor),
+        Err(e) => {
+            warn!(error = %e, "Adapter events unavailable, relying on polling");
+            None
+        }
+    };
+
+    let mut interval = tokio::time::interval(HEALTH_CHECK_INTERVAL);
+    interval.tick().await;
+
+    loop {
+        tokio::select! {
+            change = next_change(monitor.as_mut()) => match change {
+                Some(AdapterChange::Removed) => {
+                    invalidate(state, "Bluetooth adapter was removed").await;
+                    return;
+                }
+                None => {
+                    invalidate(state, "Lost connection to bluetoothd").await;
+                    return;
+                }
+                Some(AdapterChange::Powered(false)) => {
+                    invalidate(state, "Bluetooth adapter was powered off").await;
+                    return;
+                }
+                Some(change) => debug!(?change, "Adapter changed"),
+            },
+            _ = interval.tick() => {
+                if !check(state).await {
+                    return;
+                }
+            }
+        }
+    }
+}
+
+/// Waits for the next adapter change, or forever without a monitor.
+async fn next_change(monitor: Option<&mut AdapterMonitor>) -> Option<AdapterChange> {
+    match monitor {
+        Some(monitor) => monitor.next().await,
+        None => std::future::pending().await,
+    }
+}
+
+/// Checks the adapter and handles repeated scan failures.
+///
+/// Returns `false` if the scanner was removed from the state.
+async fn check(state: &SharedState) -> bool {
+    let Some(scanner) = state.scanner().await else {
+        return false;
+    };
+
+    let problem = match scanner.is_adapter_powered().await {
+        Ok(true) if state.bluetooth_health.consecutive_failures() < FAILURE_THRESHOLD => {
+            return true;
+        }
+        Ok(true) => {
+            let message = format!("{FAILURE_THRESHOLD} consecutive Bluetooth scans failed");
+            let power_cycle = state.config.read().await.bluetooth.power_cycle_on_failure;
+            if power_cycle {
+                if let Err(e) = scanner.power_cycle_adapter().await {
+                    warn!(error = %e, "Failed to power-cycle Bluetooth adapter");
+                }
+            }
+            message
+        }
+        Ok(false) => "Bluetooth adapter is powered off".to_string(),
+        Err(e) => e.to_string(),
+    };
+
+    drop(scanner);
+    invalidate(state, &problem).await;
+    false
+}
+
+/// Drops the current scanner so that it is re-created.
+async fn invalidate(state: &SharedState, reason: &str) {
+    warn!(reason, "Bluetooth scanner needs recovery");
+
+    state.set_scanner(None).await;
+    state.bluetooth_health.mark_unavailable(&reason);
+}
+
+/// Attempts to create a new scanner. Returns `true` on success.
+async fn recover(state: &SharedState) -> bool {
+    state.bluetooth_health.mark_recovering();
+    let adapters = state.config.read().await.bluetooth.adapters.clone();
+
+    match BluetoothScanner::with_adapters(&adapters).await {
+        Ok(scanner) => {
+            state.set_scanner(Some(scanner)).await;
+            state.bluetooth_health.record_recovery();
+            info!("Bluetooth scanner recovered");
+            true
+        }
+        Err(e) => {
+            warn!(error = %e, "Bluetooth scanner recovery failed");
+            state.bluetooth_health.mark_unavailable(&e);
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_health_tracker_failures_and_success() {
+        let tracker = HealthTracker::new(true);
+        assert_eq!(tracker.snapshot().state, ScannerState::Healthy);
+
+        tracker.record_failure(&"scan failed");
+        tracker.record_failure(&"scan failed again");
+        let health = tracker.snapshot();
+        assert_eq!(health.state, ScannerState::Degraded);
+        assert_eq!(health.consecutive_failures, 2);
+        assert_eq!(health.total_failures, 2);
+        assert_eq!(health.last_error.as_deref(), Some("scan failed again"));
+        assert!(health.last_error_at_utc.is_some());
+
+        tracker.record_success();
+        let health = tracker.snapshot();
+        assert_eq!(health.state, ScannerState::Healthy);
+        assert_eq!(health.consecutive_failures, 0);
+        assert_eq!(health.total_failures, 2);
+        assert!(health.last_successful_scan_utc.is_some());
+    }
+
+    #[test]
+    fn test_health_tracker_recovery() {
+        let tracker = HealthTracker::new(false);
+        assert_eq!(tracker.snapshot().state, ScannerState::Unavailable);
+
+        tracker.mark_recovering();
+        assert_eq!(tracker.snapshot().state, ScannerState::Recovering);
+
+        tracker.record_recovery();
+        let health = tracker.snapshot();
+        assert_eq!(health.state, ScannerState::Healthy);
+        assert_eq!(health.recoveries, 1);
+
+        // Failures do not mask an unavailable scanner
+        tracker.mark_unavailable(&"adapter removed");
+        tracker.record_failure(&"scan failed");
+        assert_eq!(tracker.snapshot().state, ScannerState::Unavailable);
+    }
+
+    #[test]
+    fn test_health_tracker_publishes_state_changes() {
+        let events = EventBus::new();
+        let mut subscription = events.subscribe(None);
+        let tracker = HealthTracker::new(true).with_events(events);
+
+        // Only the change from healthy to degraded is published
+        tracker.record_success();
+        tracker.record_failure(&"scan failed");
+        tracker.record_failure(&"scan failed again");
+
+        let message = subscription.receiver.try_recv().unwrap();
+        assert_eq!(
+            message.event,
+            ServerEvent::ScannerHealthChanged {
+                state: ScannerState::Degraded,
+                previous: ScannerState::Healthy,
+                last_error: Some("scan failed".to_string()),
+            }
+        );
+        assert!(subscription.receiver.try_recv().is_err());
+    }
+
+    #[cfg(feature = "mock-bluetooth")]
+    #[tokio::test]
+    async fn test_supervisor_recovers_removed_adapter() {
+        use crate::state::AppState;
+
+        let dir = tempfile::tempdir().unwrap();
+        let scanner = BluetoothScanner::new().await.unwrap();
+        let state = AppState::in_dir(dir.path(), Some(scanner)).into_shared();
+
+        let supervisor = spawn(state.clone());
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        state
+            .scanner()
+            .await
+            .unwrap()
+            .set_adapter_present(false)
+            .await;
+
+        // The removed scanner is replaced by a fresh one
+        let mut recovered = false;
+        for _ in 0..50 {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            if state.scanner().await.is_some() && state.bluetooth_health.snapshot().recoveries == 1
+            {
+                recovered = true;
+                break;
+            }
+        }
+        supervisor.abort();
+
+        assert!(recovered);
+        let health = state.bluetooth_health.snapshot();
+        assert_eq!(health.state, ScannerState::Healthy);
+        assert_eq!(
+            health.last_error.as_deref(),
+            Some("Bluetooth adapter was removed")
+        );
+    }
+}
@@ -0,0 +1,1105 @@
+//! Outbound webhooks.
+//!
+//! Webhooks forward [`ServerEvent`](crate::events::ServerEvent)s to other
+//! services, such as a team chat or an accountability partner's phone. Each
+//! webhook has a URL, an optional filter of event types, and a secret used
+//! to sign its requests.
+//!
+//! # Requests
+//!
+//! Events are sent as `POST` requests whose JSON body is the
+//! [`EventMessage`], exactly as streamed by `GET /api/events`, with the
+//! headers:
+//!
+//! - `X-Tether-Event`: the event type, e.g. `pass_used`
+//! - `X-Tether-Delivery`: unique id of the delivery, identical across retries
+//! - `X-Tether-Signature-256`: `sha256=` followed by the hex HMAC-SHA256 of
+//!   the raw body, keyed with the webhook's secret
+//!
+//! # Retries
+//!
+//! Any response other than 2xx, or no response within ten seconds, is a
+//! failed attempt. Failed deliveries are retried with exponential backoff up
+//! to [`MAX_ATTEMPTS`] times. Webhooks, queued deliveries and the delivery
+//! log are saved to `webhooks.json`, so retries survive restarts; secrets
+//! are encrypted in a separate [`SecretStore`].
+
+use std::collections::{HashSet, VecDeque};
+use std::path::PathBuf;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use tether_core::storage::{DurableFile, LoadError};
+use tether_core::{SecretError, SecretStore, SecretString};
+use thiserror::Error;
+use tokio::sync::broadcast::error::RecvError;
+use tracing::{debug, info, warn};
+use url::Url;
+use utoipa::ToSchema;
+
+use crate::events::{EventKind, EventMessage, Missed, Subscription};
+use crate::state::{AppState, SharedState};
+
+/// Attempts made to deliver an event before giving up.
+pub const MAX_ATTEMPTS: u32 = 8;
+
+/// Number of delivery attempts kept in the delivery log.
+pub const DELIVERY_LOG_CAPACITY: usize = 200;
+
+/// Delay before the first retry; doubled after every further failure.
+const INITIAL_RETRY_DELAY: Duration = Duration::from_secs(10);
+
+/// Upper bound for the delay between retries.
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(3600);
+
+/// How long to wait for a webhook to respond.
+const DELIVERY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Header carrying the event type.
+pub const EVENT_HEADER: &str = "x-tether-event";
+
+/// Header carrying the delivery id.
+pub const DELIVERY_HEADER: &str = "x-tether-delivery";
+
+/// Header carrying the body signature.
+pub const SIGNATURE_HEADER: &str = "x-tether-signature-256";
+
+// ============================================================================
+// Errors
+// ============================================================================
+
+/// Errors from managing webhooks.
+#[derive(Debug, Error)]
+pub enum WebhookError {
+    /// Failed to read the webhooks file.
+    #[error("Failed to read webhooks from {path}: {source}")]
+    ReadError {
+        /// Path of the webhooks file.
+        path: PathBuf,
+        /// Underlying I/O error.
+        source: std::io::Error,
+    },
+
+    /// The webhooks file is not valid JSON.
+    #[error("Failed to parse webhooks in {path}: {source}")]
+    ParseError {
+        /// Path of the webhooks file.
+        path: PathBuf,
+        /// Underlying JSON error.
+        source: serde_json::Error,
+    },
+
+    /// Failed to write the webhooks file.
+    #[error("Failed to write webhooks to {path}: {source}")]
+    WriteError {
+        /// Path of the webhooks file.
+        path: PathBuf,
+        /// Underlying I/O error.
+        source: std::io::Error,
+    },
+
+    /// Failed to serialize webhooks.
+    #[error("Failed to serialize webhooks: {0}")]
+    SerializeError(#[from] serde_json::Error),
+
+    /// The webhook URL is not an absolute `http` or `https` URL.
+    #[error("'{url}' is not an http:// or https:// URL")]
+    InvalidUrl {
+        /// The rejected URL.
+        url: String,
+    },
+
+    /// No webhook has the given id.
+    #[error("Webhook not found: {id}")]
+    NotFound {
+        /// The requested webhook id.
+        id: String,
+    },
+
+    /// The webhook's secret is missing from the secrets store.
+    #[error("Secret of webhook {id} is missing")]
+    SecretMissing {
+        /// The webhook id.
+        id: String,
+    },
+
+    /// Failed to store or read a webhook secret.
+    #[error(transparent)]
+    Secret(#[from] SecretError),
+}
+
+/// Result type for webhook operations.
+pub type WebhookResult<T> = Result<T, WebhookError>;
+
+// ============================================================================
+// Data
+// ============================================================================
+
+/// A webhook subscription.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Webhook {
+    /// Unique id of the webhook.
+    pub id: String,
+
+    /// URL events are posted to.
+    pub url: String,
+
+    /// Event types to deliver; empty to deliver all events.
+    #[serde(default)]
+    pub events: Vec<EventKind>,
+
+    /// Id of the signing secret in the secrets store.
+    pub secret_id: String,
+
+    /// When the webhook was created.
+    pub created_at: DateTime<Utc>,
+}
+
+impl Webhook {
+    /// Returns whether events of type `kind` are delivered to this webhook.
+    #[must_use]
+    pub fn accepts(&self, kind: EventKind) -> bool {
+        self.events.is_empty() || self.events.contains(&kind)
+    }
+}
+
+/// An event waiting to be delivered to a webhook.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PendingDelivery {
+    /// Unique id of the delivery, sent in the `X-Tether-Delivery` header.
+    pub id: String,
+
+    /// The webhook to deliver to.
+    pub webhook_id: String,
+
+    /// The event to deliver.
+    pub event: EventMessage,
+
+    /// Attempts made so far.
+    pub attempts: u32,
+
+    /// When the next attempt is due.
+    pub next_attempt_at: DateTime<Utc>,
+}
+
+/// Result of a delivery attempt.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum DeliveryStatus {
+    /// The webhook accepted the event.
+    Delivered,
+    /// The attempt failed and will be retried.
+    Retrying,
+    /// The attempt failed and no retries are left.
+    Failed,
+}
+
+/// Outcome of sending a delivery.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AttemptOutcome {
+    /// The webhook responded with a 2xx status.
+    Delivered {
+        /// The HTTP status of the response.
+        status_code: u16,
+    },
+    /// The webhook could not be reached or rejected the event.
+    Failed {
+        /// The HTTP status of the response, if there was one.
+        status_code: Option<u16>,
+        /// What went wrong.
+        error: String,
+    },
+}
+
+/// An entry in the delivery log.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeliveryAttempt {
+    /// Id of the delivery.
+    pub delivery_id: String,
+
+    /// The webhook delivered to.
+    pub webhook_id: String,
+
+    /// Id of the delivered event.
+    pub event_id: u64,
+
+    /// Type of the delivered event.
+    pub event_type: EventKind,
+
+    /// Attempt number, starting at 1.
+    pub attempt: u32,
+
+    /// Result of the attempt.
+    pub status: DeliveryStatus,
+
+    /// HTTP status of the response, if there was one.
+    pub status_code: Option<u16>,
+
+    /// What went wrong, for failed attempts.
+    pub error: Option<String>,
+
+    /// When the attempt was made.
+    pub attempted_at: DateTime<Utc>,
+
+    /// When the next attempt is due, for retried deliveries.
+    pub next_attempt_at: Option<DateTime<Utc>>,
+}
+
+/// Contents of the webhooks file.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct WebhookData {
+    #[serde(default)]
+    webhooks: Vec<Webhook>,
+    #[serde(default)]
+    pending: Vec<PendingDelivery>,
+    #[serde(default)]
+    log: VecDeque<DeliveryAttempt>,
+}
+
+// ============================================================================
+// Store
+// ============================================================================
+
+/// Webhooks, their queued deliveries and the delivery log.
+///
+/// Every change is saved immediately.
+#[derive(Debug)]
+pub struct WebhookStore {
+    file: DurableFile,
+    data: WebhookData,
+    secrets: SecretStore,
+}
+
+impl WebhookStore {
+    /// Opens the store at `path`, keeping webhook secrets in `secrets`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file exists but neither it nor a backup can
+    /// be read.
+    pub fn open(path: impl Into<PathBuf>, secrets: SecretStore) -> WebhookResult<Self> {
+        let path = path.into();
+        let file = DurableFile::new(&path);
+
+        let data = file
+            .load(|contents| serde_json::from_str::<WebhookData>(contents))
+            .map_err(|e| match e {
+                LoadError::Read(source) => WebhookError::ReadError {
+                    path: path.clone(),
+                    source,
+                },
+                LoadError::Parse(source) => WebhookError::ParseError {
+                    path: path.clone(),
+                    source,
+                },
+            })?
+            .unwrap_or_default();
+
+        Ok(Self {
+            file,
+            data,
+            secrets,
+        })
+    }
+
+    /// Returns all webhooks, oldest first.
+    #[must_use]
+    pub fn webhooks(&self) -> &[Webhook] {
+        &self.data.webhooks
+    }
+
+    /// Returns the webhook with the given id.
+    #[must_use]
+    pub fn get(&self, id: &str) -> Option<&Webhook> {
+        self.data.webhooks.iter().find(|w| w.id == id)
+    }
+
+    /// Creates a webhook.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`WebhookError::InvalidUrl`] if `url` is not an `http` or
+    /// `https` URL, or an error if the secret or the webhook cannot be saved.
+    pub fn create(
+        &mut self,
+        url: String,
+        events: Vec<EventKind>,
+        secret: &SecretString,
+    ) -> WebhookResult<Webhook> {
+        validate_url(&url)?;
+
+        // Save the secret first so the file never references a missing one
+        let secret_id = self.secrets.insert(secret)?;
+        self.secrets.save()?;
+
+        let webhook = Webhook {
+            id: uuid::Uuid::new_v4().to_string(),
+            url,
+            events,
+            secret_id,
+            created_at: Utc::now(),
+        };
+        self.data.webhooks.push(webhook.clone());
+        self.save()?;
+        Ok(webhook)
+    }
+
+    /// Updates a webhook; `None` leaves a setting unchanged.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`WebhookError::NotFound`] if there is no such webhook,
+    /// [`WebhookError::InvalidUrl`] if the new URL is not an `http` or `https`
+    /// URL, or an error if the change cannot be saved.
+    pub fn update(
+        &mut self,
+        id: &str,
+        url: Option<String>,
+        events: Option<Vec<EventKind>>,
+        secret: Option<&SecretString>,
+    ) -> WebhookResult<Webhook> {
+        let index = self.index_of(id)?;
+        if let Some(url) = &url {
+            validate_url(url)?;
+        }
+
+        let new_secret_id = match secret {
+            Some(secret) => {
+                let secret_id = self.secrets.insert(secret)?;
+                self.secrets.save()?;
+                Some(secret_id)
+            }
+            None => None,
+        };
+
+        let webhook = &mut self.data.webhooks[index];
+        if let Some(url) = url {
+            webhook.url = url;
+        }
+        if let Some(events) = events {
+            webhook.events = events;
+        }
+        let old_secret_id = new_secret_id.map(|new| std::mem::replace(&mut webhook.secret_id, new));
+        let webhook = webhook.clone();
+        self.save()?;
+
+        if let Some(old) = old_secret_id {
+            self.remove_secret(&old);
+        }
+        Ok(webhook)
+    }
+
+    /// Deletes a webhook along with its queued deliveries.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`WebhookError::NotFound`] if there is no such webhook, or an
+    /// error if the change cannot be saved.
+    pub fn delete(&mut self, id: &str) -> WebhookResult<Webhook> {
+        let index = self.index_of(id)?;
+        let webhook = self.data.webhooks.remove(index);
+        self.data.pending.retain(|d| d.webhook_id != id);
+        self.save()?;
+
+        self.remove_secret(&webhook.secret_id);
+        Ok(webhook)
+    }
+
+    /// Returns the URL and signing secret of a webhook.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there is no such webhook or its secret cannot be
+    /// read.
+    pub fn target(&self, id: &str) -> WebhookResult<(String, SecretString)> {
+        let webhook = self
+            .get(id)
+            .ok_or_else(|| WebhookError::NotFound { id: id.to_string() })?;
+        let secret = self
+            .secrets
+            .get(&webhook.secret_id)?
+            .ok_or_else(|| WebhookError::SecretMissing { id: id.to_string() })?;
+        Ok((webhook.url.clone(), secret))
+    }
+
+    /// Queues `event` for every webhook that accepts it.
+    ///
+    /// Returns the number of deliveries queued.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the queue cannot be saved.
+    pub fn enqueue(&mut self, event: &EventMessage, now: DateTime<Utc>) -> WebhookResult<usize> {
+        let kind = event.event.kind();
+        let deliveries: Vec<_> = self
+            .data
+            .webhooks
+            .iter()
+            .filter(|webhook| webhook.accepts(kind))
+            .map(|webhook| PendingDelivery {
+                id: uuid::Uuid::new_v4().to_string(),
+                webhook_id: webhook.id.clone(),
+                event: event.clone(),
+                attempts: 0,
+                next_attempt_at: now,
+            })
+            .collect();
+
+        let count = deliveries.len();
+        if count > 0 {
+            self.data.pending.extend(deliveries);
+            self.save()?;
+        }
+        Ok(count)
+    }
+
+    /// Returns the queued deliveries.
+    #[must_use]
+    pub fn pending(&self) -> &[PendingDelivery] {
+        &self.data.pending
+    }
+
+    /// Returns the deliveries due at `now`, oldest first.
+    #[must_use]
+    pub fn due(&self, now: DateTime<Utc>) -> Vec<PendingDelivery> {
+        let mut due: Vec<_> = self
+            .data
+            .pending
+            .iter()
+            .filter(|d| d.next_attempt_at <= now)
+            .cloned()
+            .collect();
+        due.sort_by_key(|d| d.next_attempt_at);
+        due
+    }
+
+    /// Returns when the next queued delivery is due.
+    #[must_use]
+    pub fn next_attempt_at(&self) -> Option<DateTime<Utc>> {
+        self.data.pending.iter().map(|d| d.next_attempt_at).min()
+    }
+
+    /// Records the outcome of an attempt to send a queued delivery.
+    ///
+    /// Delivered and finally failed deliveries leave the queue; others are
+    /// rescheduled with exponential backoff. Returns the log entry, or
+    /// `None` if the delivery is no longer queued.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the change cannot be saved.
+    pub fn record_attempt(
+        &mut self,
+        delivery_id: &str,
+        outcome: AttemptOutcome,
+        now: DateTime<Utc>,
+    ) -> WebhookResult<Option<DeliveryAttempt>> {
+        let Some(index) = self.data.pending.iter().position(|d| d.id == delivery_id) else {
+            return Ok(None);
+        };
+        let delivery = &mut self.data.pending[index];
+        delivery.attempts += 1;
+
+        let (status, status_code, error) = match outcome {
+            AttemptOutcome::Delivered { status_code } => {
+                (DeliveryStatus::Delivered, Some(status_code), None)
+            }
+            AttemptOutcome::Failed { status_code, error } if delivery.attempts < MAX_ATTEMPTS => {
+                (DeliveryStatus::Retrying, status_code, Some(error))
+            }
+            AttemptOutcome::Failed { status_code, error } => {
+                (DeliveryStatus::Failed, status_code, Some(error))
+            }
+        };
+
+        let next_attempt_at = (status == DeliveryStatus::Retrying).then(|| {
+            let delay = retry_delay(delivery.attempts);
+            now + chrono::Duration::from_std(delay).unwrap_or(chrono::Duration::MAX)
+        });
+        let attempt = DeliveryAttempt {
+            delivery_id: delivery.id.clone(),
+            webhook_id: delivery.webhook_id.clone(),
+            event_id: delivery.event.id,
+            event_type: delivery.event.event.kind(),
+            attempt: delivery.attempts,
+            status,
+            status_code,
+            error,
+            attempted_at: now,
+            next_attempt_at,
+        };
+
+        match next_attempt_at {
+            Some(at) => delivery.next_attempt_at = at,
+            None => {
+                self.data.pending.remove(index);
+            }
+        }
+        if self.data.log.len() == DELIVERY_LOG_CAPACITY {
+            self.data.log.pop_front();
+        }
+        self.data.log.push_back(attempt.clone());
+        self.save()?;
+
+        Ok(Some(attempt))
+    }
+
+    /// Returns the delivery log, newest first.
+    pub fn log(&self) -> impl Iterator<Item = &DeliveryAttempt> {
+        self.data.log.iter().rev()
+    }
+
+    fn index_of(&self, id: &str) -> WebhookResult<usize> {
+        self.data
+            .webhooks
+            .iter()
+            .position(|w| w.id == id)
+            .ok_or_else(|| WebhookError::NotFound { id: id.to_string() })
+    }
+
+    /// Removes a secret that is no longer referenced.
+    ///
+    /// Failures only leave an unused secret behind, so they are logged.
+    fn remove_secret(&mut self, secret_id: &str) {
+        let referenced: HashSet<&str> = self
+            .data
+            .webhooks
+            .iter()
+            .map(|w| w.secret_id.as_str())
+            .collect();
+        if referenced.contains(secret_id) || !self.secrets.remove(secret_id) {
+            return;
+        }
+        if let Err(e) = self.secrets.save() {
+            warn!(error = %e, "Failed to remove unused webhook secret");
+        }
+    }
+
+    fn save(&self) -> WebhookResult<()> {
+        let json = serde_json::to_string_pretty(&self.data)?;
+        self.file
+            .write(json.as_bytes())
+            .map_err(|source| WebhookError::WriteError {
+                path: self.file.path().to_path_buf(),
+                source,
+            })
+    }
+}
+
+/// Checks that `url` is an absolute `http` or `https` URL.
+///
+/// # Errors
+///
+/// Returns [`WebhookError::InvalidUrl`] for any other URL.
+pub fn validate_url(url: &str) -> WebhookResult<()> {
+    match Url::parse(url) {
+        Ok(parsed) if matches!(parsed.scheme(), "http" | "https") && parsed.has_host() => Ok(()),
+        _ => Err(WebhookError::InvalidUrl {
+            url: url.to_string(),
+        }),
+    }
+}
+
+/// Returns the delay before retrying after `attempts` failed attempts.
+fn retry_delay(attempts: u32) -> Duration {
+    let factor = 2_u32.saturating_pow(attempts.saturating_sub(1));
+    INITIAL_RETRY_DELAY
+        .saturating_mul(factor)
+        .min(MAX_RETRY_DELAY)
+}
+
+// ============================================================================
+// Delivery
+// ============================================================================
+
+/// Returns the `X-Tether-Signature-256` header value for `body`.
+///
+/// # Panics
+///
+/// Never: HMAC accepts keys of any length.
+#[must_use]
+pub fn signature(secret: &SecretString, body: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.expose().as_bytes())
+        .expect("HMAC accepts keys of any length");
+    mac.update(body);
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+/// Creates the HTTP client used to deliver webhooks.
+///
+/// # Errors
+///
+/// Returns an error if the TLS backend cannot be initialized.
+pub fn http_client() -> reqwest::Result<reqwest::Client> {
+    reqwest::Client::builder()
+        .timeout(DELIVERY_TIMEOUT)
+        .redirect(reqwest::redirect::Policy::none())
+        .user_agent(concat!("tether/", env!("CARGO_PKG_VERSION")))
+        .build()
+}
+
+/// Spawns the webhook dispatcher as a background task.
+pub fn spawn(state: SharedState) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(run(state))
+}
+
+/// Queues published events for webhooks and delivers them.
+pub async fn run(state: SharedState) {
+    let client = match http_client() {
+        Ok(client) => client,
+        Err(e) => {
+            warn!(error = %e, "Webhooks disabled: failed to create HTTP client");
+            return;
+        }
+    };
+
+    let Subscription { mut receiver, .. } = state.events.subscribe(None);
+    let mut last_seen = None;
+    info!("Webhook dispatcher started");
+
+    loop {
+        deliver_due(&state, &client, Utc::now()).await;
+        let next_attempt_at = state.webhooks.lock().await.next_attempt_at();
+
+        tokio::select! {
+            received = receiver.recv() => match received {
+                Ok(message) => {
+                    last_seen = Some(message.id);
+                    enqueue(&state, &message).await;
+                }
+                Err(RecvError::Lagged(_)) => {
+                    // Pick up the missed events from the buffer
+                    let subscription = state.events.subscribe(last_seen);
+                    receiver = subscription.receiver;
+                    match subscription.missed {
+                        Missed::Events(missed) => {
+                            for message in missed {
+                                last_seen = Some(message.id);
+                                enqueue(&state, &message).await;
+                            }
+                        }
+                        Missed::Gap(latest) => {
+                            warn!("Webhook dispatcher fell behind, some events were not delivered");
+                            last_seen = latest;
+                        }
+                    }
+                }
+                Err(RecvError::Closed) => return,
+            },
+            () = sleep_until(next_attempt_at) => {}
+        }
+    }
+}
+
+/// Sleeps until `at`, or forever without a time.
+async fn sleep_until(at: Option<DateTime<Utc>>) {
+    match at {
+        Some(at) => {
+            let delay = (at - Utc::now()).to_std().unwrap_or(Duration::ZERO);
+            tokio::time::sleep(delay).await;
+        }
+        None => std::future::pending().await,
+    }
+}
+
+/// Queues an event for the webhooks that accept it.
+async fn enqueue(state: &AppState, message: &EventMessage) {
+    let queued = state.webhooks.lock().await.enqueue(message, Utc::now());
+    match queued {
+        Ok(0) => {}
+        Ok(count) => debug!(event_id = message.id, count, "Queued webhook deliveries"),
+        Err(e) => warn!(event_id = message.id, error = %e, "Failed to queue webhook deliveries"),
+    }
+}
+
+/// Attempts every delivery due at `now`.
+///
+/// The store is not locked while requests are in flight.
+pub async fn deliver_due(state: &AppState, client: &reqwest::Client, now: DateTime<Utc>) {
+    let due: Vec<_> = {
+        let webhooks = state.webhooks.lock().await;
+        webhooks
+            .due(now)
+            .into_iter()
+            .map(|delivery| {
+                let target = webhooks.target(&delivery.webhook_id);
+                (delivery, target)
+            })
+            .collect()
+    };
+
+    for (delivery, target) in due {
+        let outcome = match target {
+            Ok((url, secret)) => send(client, &url, &secret, &delivery).await,
+            Err(e) => AttemptOutcome::Failed {
+                status_code: None,
+                error: e.to_string(),
+            },
+        };
+
+        let recorded =
+            state
+                .webhooks
+                .lock()
+                .await
+                .record_attempt(&delivery.id, outcome, Utc::now());
+        match recorded {
+            Ok(Some(attempt)) if attempt.status != DeliveryStatus::Delivered => warn!(
+                webhook_id = %attempt.webhook_id,
+                attempt = attempt.attempt,
+                error = attempt.error.as_deref().unwrap_or_default(),
+                "Webhook delivery failed"
+            ),
+            Ok(_) => {}
+            Err(e) => warn!(error = %e, "Failed to record webhook delivery"),
+        }
+    }
+}
+
+/// Sends a delivery to `url`.
+async fn send(
+    client: &reqwest::Client,
+    url: &str,
+    secret: &SecretString,
+    delivery: &PendingDelivery,
+) -> AttemptOutcome {
+    let body = match serde_json::to_vec(&delivery.event) {
+        Ok(body) => body,
+        Err(e) => {
+            return AttemptOutcome::Failed {
+                status_code: None,
+                error: e.to_string(),
+            }
+        }
+    };
+
+    let response = client
+        .post(url)
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .header(EVENT_HEADER, delivery.event.event.name())
+        .header(DELIVERY_HEADER, &delivery.id)
+        .header(SIGNATURE_HEADER, signature(secret, &body))
+        .body(body)
+        .send()
+        .await;
+
+    match response {
+        Ok(response) if response.status().is_success() => AttemptOutcome::Delivered {
+            status_code: response.status().as_u16(),
+        },
+        Ok(response) => AttemptOutcome::Failed {
+            status_code: Some(response.status().as_u16()),
+            error: format!("Webhook responded with {}", response.status()),
+        },
+        Err(e) => AttemptOutcome::Failed {
+            status_code: None,
+            error: error_chain(&e),
+        },
+    }
+}
+
+/// Formats an error with its sources, e.g. "error sending request: connection refused".
+fn error_chain(error: &dyn std::error::Error) -> String {
+    let mut message = error.to_string();
+    let mut source = error.source();
+    while let Some(e) = source {
+        message.push_str(": ");
+        message.push_str(&e.to_string());
+        source = e.source();
+    }
+    message
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::{ConfigSection, ServerEvent};
+    use std::path::Path;
+    use std::sync::{Arc, Mutex};
+
+    const SECRET: &str = "correct-horse-battery-staple";
+
+    fn open_store(dir: &Path) -> WebhookStore {
+        let secrets =
+            SecretStore::open(dir.join("webhook-secrets.json"), dir.join("secrets.key")).unwrap();
+        WebhookStore::open(dir.join("webhooks.json"), secrets).unwrap()
+    }
+
+    fn message(id: u64, event: ServerEvent) -> EventMessage {
+        EventMessage {
+            id,
+            emitted_at_utc: "2025-01-15T03:30:00+00:00".to_string(),
+            event,
+        }
+    }
+
+    fn pass_used() -> ServerEvent {
+        ServerEvent::PassUsed {
+            remaining: 2,
+            used_at_utc: "2025-01-15T03:30:00+00:00".to_string(),
+            reason: "On call tonight".to_string(),
+        }
+    }
+
+    fn failed() -> AttemptOutcome {
+        AttemptOutcome::Failed {
+            status_code: Some(500),
+            error: "Webhook responded with 500 Internal Server Error".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_signature() {
+        // HMAC-SHA256 test vector from RFC 4231, test case 2
+        let secret = SecretString::new("Jefe");
+        assert_eq!(
+            signature(&secret, b"what do ya want for nothing?"),
+            "sha256=5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    #[test]
+    fn test_validate_url() {
+        assert!(validate_url("https://chat.example.com/hooks/tether").is_ok());
+        assert!(validate_url("http://192.168.1.20:8123/api/webhook/tether").is_ok());
+        assert!(validate_url("ftp://example.com/").is_err());
+        assert!(validate_url("file:///etc/passwd").is_err());
+        assert!(validate_url("chat.example.com").is_err());
+    }
+
+    #[test]
+    fn test_create_rejects_non_http_url() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut store = open_store(dir.path());
+        let result = store.create(
+            "ftp://a.example/".into(),
+            Vec::new(),
+            &SecretString::new(SECRET),
+        );
+        assert!(matches!(result, Err(WebhookError::InvalidUrl { .. })));
+        assert!(store.webhooks().is_empty());
+        assert!(store.secrets.is_empty());
+    }
+
+    #[test]
+    fn test_enqueue_respects_event_filter() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut store = open_store(dir.path());
+        let secret = SecretString::new(SECRET);
+        let all = store
+            .create("https://a.example/".into(), Vec::new(), &secret)
+            .unwrap();
+        store
+            .create(
+                "https://b.example/".into(),
+                vec![EventKind::PassUsed],
+                &secret,
+            )
+            .unwrap();
+
+        let now = Utc::now();
+        let config_changed = ServerEvent::ConfigChanged {
+            section: ConfigSection::Wifi,
+        };
+        assert_eq!(store.enqueue(&message(1, config_changed), now).unwrap(), 1);
+        assert_eq!(store.pending()[0].webhook_id, all.id);
+        assert_eq!(store.enqueue(&message(2, pass_used()), now).unwrap(), 2);
+    }
+
+    #[test]
+    fn test_failed_deliveries_back_off_then_give_up() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut store = open_store(dir.path());
+        store
+            .create(
+                "https://a.example/".into(),
+                Vec::new(),
+                &SecretString::new(SECRET),
+            )
+            .unwrap();
+        let mut now = Utc::now();
+        store.enqueue(&message(1, pass_used()), now).unwrap();
+        let id = store.pending()[0].id.clone();
+
+        let mut delays = Vec::new();
+        for _ in 1..MAX_ATTEMPTS {
+            let attempt = store.record_attempt(&id, failed(), now).unwrap().unwrap();
+            assert_eq!(attempt.status, DeliveryStatus::Retrying);
+            let next = attempt.next_attempt_at.unwrap();
+            assert!(store.due(now).is_empty());
+            delays.push((next - now).num_seconds());
+            now = next;
+        }
+        assert_eq!(delays, [10, 20, 40, 80, 160, 320, 640]);
+
+        let attempt = store.record_attempt(&id, failed(), now).unwrap().unwrap();
+        assert_eq!(attempt.status, DeliveryStatus::Failed);
+        assert_eq!(attempt.attempt, MAX_ATTEMPTS);
+        assert!(store.pending().is_empty());
+        assert_eq!(store.log().count(), MAX_ATTEMPTS as usize);
+    }
+
+    #[test]
+    fn test_pending_deliveries_survive_reopen() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut store = open_store(dir.path());
+        let webhook = store
+            .create(
+                "https://a.example/".into(),
+                Vec::new(),
+                &SecretString::new(SECRET),
+            )
+            .unwrap();
+        let now = Utc::now();
+        store.enqueue(&message(1, pass_used()), now).unwrap();
+        let id = store.pending()[0].id.clone();
+        store.record_attempt(&id, failed(), now).unwrap();
+        drop(store);
+
+        let store = open_store(dir.path());
+        assert_eq!(store.pending().len(), 1);
+        assert_eq!(store.pending()[0].attempts, 1);
+        assert_eq!(store.log().count(), 1);
+        let (url, secret) = store.target(&webhook.id).unwrap();
+        assert_eq!(url, "https://a.example/");
+        assert_eq!(secret.expose(), SECRET);
+    }
+
+    #[test]
+    fn test_delete_removes_pending_and_secret() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut store = open_store(dir.path());
+        let webhook = store
+            .create(
+                "https://a.example/".into(),
+                Vec::new(),
+                &SecretString::new(SECRET),
+            )
+            .unwrap();
+        store.enqueue(&message(1, pass_used()), Utc::now()).unwrap();
+
+        store.delete(&webhook.id).unwrap();
+        assert!(store.pending().is_empty());
+        assert!(store.secrets.is_empty());
+        assert!(matches!(
+            store.delete(&webhook.id),
+            Err(WebhookError::NotFound { .. })
+        ));
+    }
+
+    /// A request received by the stub server.
+    #[derive(Debug, Clone)]
+    struct Received {
+        headers: axum::http::HeaderMap,
+        body: axum::body::Bytes,
+    }
+
+    /// Starts an HTTP server that records requests and answers with
+    /// `statuses` in turn, then 200.
+    async fn start_stub(statuses: Vec<u16>) -> (String, Arc<Mutex<Vec<Received>>>) {
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let statuses = Arc::new(Mutex::new(VecDeque::from(statuses)));
+        let recorded = received.clone();
+        let app = axum::Router::new().route(
+            "/hook",
+            axum::routing::post(
+                move |headers: axum::http::HeaderMap, body: axum::body::Bytes| async move {
+                    recorded.lock().unwrap().push(Received { headers, body });
+                    let status = statuses.lock().unwrap().pop_front().unwrap_or(200);
+                    axum::http::StatusCode::from_u16(status).unwrap()
+                },
+            ),
+        );
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        (url, received)
+    }
+
+    #[tokio::test]
+    async fn test_delivery_to_stub_is_signed_and_retried() {
+        let (url, received) = start_stub(vec![503]).await;
+        let dir = tempfile::tempdir().unwrap();
+        let state = AppState::in_dir(dir.path(), None);
+        let webhook = state
+            .webhooks
+            .lock()
+            .await
+            .create(url, vec![EventKind::PassUsed], &SecretString::new(SECRET))
+            .unwrap();
+
+        enqueue(&state, &message(7, pass_used())).await;
+
+        // The first attempt fails and is retried later
+        let client = http_client().unwrap();
+        deliver_due(&state, &client, Utc::now()).await;
+        let next_attempt_at = state.webhooks.lock().await.next_attempt_at().unwrap();
+        deliver_due(&state, &client, next_attempt_at).await;
+
+        let store = state.webhooks.lock().await;
+        assert!(store.pending().is_empty());
+        let statuses: Vec<_> = store
+            .log()
+            .map(|a| (a.attempt, a.status, a.status_code))
+            .collect();
+        assert_eq!(
+            statuses,
+            [
+                (2, DeliveryStatus::Delivered, Some(200)),
+                (1, DeliveryStatus::Retrying, Some(503)),
+            ]
+        );
+
+        let received = received.lock().unwrap().clone();
+        assert_eq!(received.len(), 2);
+        let request = &received[1];
+        assert_eq!(request.headers[EVENT_HEADER], "pass_used");
+        assert_eq!(
+            request.headers[DELIVERY_HEADER],
+            received[0].headers[DELIVERY_HEADER]
+        );
+        let expected = signature(&SecretString::new(SECRET), &request.body);
+        assert_eq!(request.headers[SIGNATURE_HEADER], expected.as_str());
+
+        let body: EventMessage = serde_json::from_slice(&request.body).unwrap();
+        assert_eq!(body.id, 7);
+        assert_eq!(body.event, pass_used());
+        assert_eq!(store.log().next().unwrap().webhook_id, webhook.id);
+    }
+
+    #[tokio::test]
+    async fn test_unreachable_webhook_is_retried() {
+        // Bind and drop a listener to get a port nothing listens on
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        drop(listener);
+
+        let dir = tempfile::tempdir().unwrap();
+        let state = AppState::in_dir(dir.path(), None);
+        state
+            .webhooks
+            .lock()
+            .await
+            .create(url, Vec::new(), &SecretString::new(SECRET))
+            .unwrap();
+        state
+            .webhooks
+            .lock()
+            .await
+            .enqueue(&message(1, pass_used()), Utc::now())
+            .unwrap();
+
+        deliver_due(&state, &http_client().unwrap(), Utc::now()).await;
+
+        let store = state.webhooks.lock().await;
+        let attempt = store.log().next().unwrap();
+        assert_eq!(attempt.status, DeliveryStatus::Retrying);
+        assert_eq!(attempt.status_code, None);
+        assert!(attempt.error.is_some());
+        assert_eq!(store.pending().len(), 1);
+    }
+}
@@ -0,0 +1,243 @@
+//! Wi-Fi profiles in `NetworkManager`.
+//!
+//! The network watchdog reconnects the Pi with `nmcli`, but passwords are
+//! kept in the encrypted secrets store rather than `config.toml`, so it
+//! cannot read them. Instead, the server saves each configured network as a
+//! `NetworkManager` connection profile named `tether-<ssid>`, which the
+//! watchdog brings up by SSID. `NetworkManager` keeps the password in its own
+//! root-only storage.
+//!
+//! Profiles are saved at startup and whenever the Wi-Fi networks change.
+//! Profiles of networks that are no longer configured are deleted. Failures
+//! are logged and otherwise ignored: the server works without
+//! `NetworkManager`, e.g. during development.
+
+use std::process::Output;
+
+use tether_core::{Config, SecretStore, SecretString};
+use tokio::process::Command;
+use tracing::{debug, info, warn};
+
+/// Prefix of the names of profiles managed by the server.
+pub const PROFILE_PREFIX: &str = "tether-";
+
+/// A network to save as a profile.
+#[derive(Debug, Clone)]
+pub struct Profile {
+    /// The network's SSID.
+    pub ssid: String,
+    /// The password, or `None` for an open network.
+    pub password: Option<SecretString>,
+}
+
+impl Profile {
+    /// Returns the name of the profile.
+    #[must_use]
+    pub fn name(&self) -> String {
+        format!("{PROFILE_PREFIX}{}", self.ssid)
+    }
+
+    /// Returns the `nmcli` settings of the profile.
+    fn settings(&self) -> Vec<String> {
+        let mut settings = vec!["802-11-wireless.ssid".to_string(), self.ssid.clone()];
+        if let Some(password) = &self.password {
+            settings.extend([
+                "wifi-sec.key-mgmt".to_string(),
+                "wpa-psk".to_string(),
+                "wifi-sec.psk".to_string(),
+                password.expose().to_string(),
+            ]);
+        }
+        settings
+    }
+
+    /// Returns the `nmcli` arguments that create the profile.
+    fn add_args(&self) -> Vec<String> {
+        let mut args = vec![
+            "connection".to_string(),
+            "add".to_string(),
+            "type".to_string(),
+            "wifi".to_string(),
+            "con-name".to_string(),
+            self.name(),
+        ];
+        args.extend(self.settings());
+        args
+    }
+
+    /// Returns the `nmcli` arguments that update the existing profile.
+    ///
+    /// The security settings are dropped for an open network, in case the
+    /// network used to have a password.
+    fn modify_args(&self) -> Vec<String> {
+        let mut args = vec!["connection".to_string(), "modify".to_string(), self.name()];
+        if self.password.is_none() {
+            args.extend(["remove".to_string(), "802-11-wireless-security".to_string()]);
+        }
+        args.extend(self.settings());
+        args
+    }
+}
+
+/// Returns the configured networks with their decrypted passwords.
+///
+/// A network whose password cannot be read is skipped, so its existing
+/// profile is left alone rather than overwritten without a password.
+#[must_use]
+pub fn profiles(config: &Config, secrets: &SecretStore) -> Vec<Profile> {
+    config
+        .wifi
+        .networks
+        .iter()
+        .filter_map(|network| {
+            let password = match &network.password_id {
+                None => None,
+                Some(id) => match secrets.get(id) {
+                    Ok(Some(password)) => Some(password),
+                    Ok(None) => {
+                        warn!(ssid = %network.ssid, "WiFi password is missing from the secrets store");
+                        return None;
+                    }
+                    Err(e) => {
+                        warn!(ssid = %network.ssid, error = %e, "Failed to decrypt WiFi password");
+                        return None;
+                    }
+                },
+            };
+            Some(Profile {
+                ssid: network.ssid.clone(),
+                password,
+            })
+        })
+        .collect()
+}
+
+/// Saves the profiles and deletes those of networks no longer configured.
+pub async fn sync(profiles: Vec<Profile>) {
+    let existing = match nmcli(&["-t", "-f", "NAME", "connection", "show"]).await {
+        Ok(output) => String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter(|name| name.starts_with(PROFILE_PREFIX))
+            .map(str::to_string)
+            .collect::<Vec<_>>(),
+        Err(e) => {
+            warn!(error = %e, "Failed to list NetworkManager profiles");
+            return;
+        }
+    };
+
+    for profile in &profiles {
+        let name = profile.name();
+        let args = if existing.contains(&name) {
+            profile.modify_args()
+        } else {
+            profile.add_args()
+        };
+        let args: Vec<&str> = args.iter().map(String::as_str).collect();
+        match nmcli(&args).await {
+            Ok(_) => debug!(profile = %name, "Saved NetworkManager profile"),
+            Err(e) => warn!(profile = %name, error = %e, "Failed to save NetworkManager profile"),
+        }
+    }
+
+    for name in &existing {
+        if profiles.iter().any(|profile| &profile.name() == name) {
+            continue;
+        }
+        match nmcli(&["connection", "delete", name]).await {
+            Ok(_) => info!(profile = %name, "Deleted NetworkManager profile of a removed network"),
+            Err(e) => warn!(profile = %name, error = %e, "Failed to delete NetworkManager profile"),
+        }
+    }
+}
+
+/// Runs `nmcli`, failing if it exits unsuccessfully.
+async fn nmcli(args: &[&str]) -> std::io::Result<Output> {
+    let output = Command::new("nmcli")
+        .args(args)
+        .env("LC_ALL", "C")
+        .output()
+        .await?;
+    if output.status.success() {
+        Ok(output)
+    } else {
+        Err(std::io::Error::other(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_add_args() {
+        let profile = Profile {
+            ssid: "HomeNetwork".to_string(),
+            password: Some(SecretString::new("hunter22")),
+        };
+        assert_eq!(profile.name(), "tether-HomeNetwork");
+        assert_eq!(
+            profile.add_args(),
+            [
+                "connection",
+                "add",
+                "type",
+                "wifi",
+                "con-name",
+                "tether-HomeNetwork",
+                "802-11-wireless.ssid",
+                "HomeNetwork",
+                "wifi-sec.key-mgmt",
+                "wpa-psk",
+                "wifi-sec.psk",
+                "hunter22",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_modify_args_for_open_network() {
+        let profile = Profile {
+            ssid: "Cafe".to_string(),
+            password: None,
+        };
+        assert_eq!(
+            profile.modify_args(),
+            [
+                "connection",
+                "modify",
+                "tether-Cafe",
+                "remove",
+                "802-11-wireless-security",
+                "802-11-wireless.ssid",
+                "Cafe",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_profiles_decrypts_passwords() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut secrets = SecretStore::open(
+            dir.path().join("secrets.json"),
+            dir.path().join("secrets.key"),
+        )
+        .unwrap();
+        let mut config = Config::default();
+        config.wifi.networks = vec![
+            tether_core::WifiNetwork::new("HomeNetwork", "hunter22", true),
+            tether_core::WifiNetwork::new("Cafe", "", false),
+        ];
+        config.store_secrets(&mut secrets).unwrap();
+
+        let profiles = profiles(&config, &secrets);
+        assert_eq!(profiles.len(), 2);
+        assert_eq!(
+            profiles[0].password.as_ref().map(SecretString::expose),
+            Some("hunter22")
+        );
+        assert!(profiles[1].password.is_none());
+    }
+}
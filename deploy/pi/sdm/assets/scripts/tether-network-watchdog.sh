@@ -289,6 +289,11 @@ check_prerequisites() {
 #   [[wifi_networks]]
 #   ssid = "NetworkName"
 #   password = "password123"  # Optional
+#
+# tether-server moves passwords into its encrypted secrets store, so current
+# configs only carry the SSIDs. The server saves each configured network as a
+# NetworkManager profile named "tether-<ssid>" holding its password, and
+# connecting uses that profile.
 
 parse_config() {
     log_info "Parsing configuration from $CONFIG_FILE"
@@ -565,7 +570,12 @@ connection_exists() {
 
 get_connection_for_ssid() {
     local ssid="$1"
-    # Find connection profile for this SSID
+    # Prefer the profile tether-server keeps current with the configured password
+    if connection_exists "tether-${ssid}"; then
+        echo "tether-${ssid}"
+        return
+    fi
+    # Otherwise find any connection profile for this SSID
     LC_ALL=C nmcli -t -f NAME,802-11-wireless.ssid connection show 2>/dev/null | \
         grep ":${ssid}$" | \
         cut -d: -f1 | \